@@ -0,0 +1,346 @@
+//! Mock implementations of the host imports
+//!
+//! Same names and signatures as the `extern "C"` block an edge function
+//! declares; `bind_mock_host!` brings them into its scope.
+
+// Same contract as the host imports they stand in for
+#![allow(clippy::missing_safety_doc)]
+
+use core::slice;
+
+use crate::*;
+
+unsafe fn read_str(ptr: *const u8, len: u32) -> String {
+    String::from_utf8_lossy(slice::from_raw_parts(ptr, len as usize)).into_owned()
+}
+
+fn set_shared(value: Vec<u8>) -> i32 {
+    with_host(|h| {
+        let len = value.len() as i32;
+        h.shared_buffer = value;
+        len
+    })
+}
+
+/// Record a scriptable call; Some(result) when an expectation answers it
+fn scripted(call: Call) -> Option<i32> {
+    match with_host(|h| h.record(call)) {
+        Reply::Simulate => None,
+        Reply::Value(value) => Some(set_shared(value)),
+        Reply::Code(code) => Some(code),
+        Reply::Timeout => {
+            with_host(|h| h.advance(HOST_CALL_TIMEOUT_SECS));
+            Some(-1)
+        }
+    }
+}
+
+pub unsafe fn log(ptr: *const u8, len: u32) {
+    let msg = read_str(ptr, len);
+    // Logging can't fail, so a scripted reply has nothing to change
+    with_host(|h| {
+        h.record(Call::Log(msg.clone()));
+        h.logs.push(msg);
+    });
+}
+
+pub unsafe fn cache_get(key_ptr: *const u8, key_len: u32) -> i32 {
+    let key = read_str(key_ptr, key_len);
+    if let Some(result) = scripted(Call::CacheGet(key.clone())) {
+        return result;
+    }
+    match with_host(|h| h.cache_lookup(&key)) {
+        Some(value) => set_shared(value),
+        None => -1,
+    }
+}
+
+pub unsafe fn cache_set(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32, ttl: u32) -> i32 {
+    let key = read_str(key_ptr, key_len);
+    if let Some(result) = scripted(Call::CacheSet { key: key.clone(), ttl }) {
+        return result;
+    }
+    let value = slice::from_raw_parts(value_ptr, value_len as usize).to_vec();
+    with_host(|h| {
+        if ttl > 0 {
+            h.cache_expiry.insert(key.clone(), h.now + u64::from(ttl));
+        } else {
+            h.cache_expiry.remove(&key);
+        }
+        h.cache.insert(key, value);
+    });
+    0
+}
+
+pub unsafe fn http_get(url_ptr: *const u8, url_len: u32) -> i32 {
+    let url = read_str(url_ptr, url_len);
+    if let Some(result) = scripted(Call::HttpGet(url.clone())) {
+        return result;
+    }
+    let body = with_host(|h| {
+        let body = h.http_responses.get(&url).cloned()?;
+        h.last_http_response_headers = h.http_response_headers.get(&url).cloned().unwrap_or_default();
+        Some(body)
+    });
+    match body {
+        Some(body) => set_shared(body),
+        None => -1,
+    }
+}
+
+pub unsafe fn http_post(
+    url_ptr: *const u8,
+    url_len: u32,
+    body_ptr: *const u8,
+    body_len: u32,
+    content_type_ptr: *const u8,
+    content_type_len: u32,
+) -> i32 {
+    let url = read_str(url_ptr, url_len);
+    if let Some(result) = scripted(Call::HttpPost(url.clone())) {
+        return result;
+    }
+    let content_type = read_str(content_type_ptr, content_type_len);
+    let body = slice::from_raw_parts(body_ptr, body_len as usize).to_vec();
+    let response = with_host(|h| {
+        h.http_posts.push((url.clone(), content_type, body));
+        h.http_responses.get(&url).cloned()
+    });
+    match response {
+        Some(response) => set_shared(response),
+        None => -1,
+    }
+}
+
+pub unsafe fn http_get_response_headers() -> i32 {
+    let headers = with_host(|h| h.last_http_response_headers.clone());
+    set_shared(serde_json::to_vec(&headers).unwrap())
+}
+
+pub unsafe fn request_get_uri() -> i32 {
+    let uri = with_host(|h| h.request_uri.clone());
+    set_shared(uri.into_bytes())
+}
+
+pub unsafe fn request_get_header(name_ptr: *const u8, name_len: u32) -> i32 {
+    let name = read_str(name_ptr, name_len);
+    let value = with_host(|h| {
+        h.request_headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+            .map(|(_, v)| v.clone())
+    });
+    match value {
+        Some(value) => set_shared(value.into_bytes()),
+        None => -1,
+    }
+}
+
+pub unsafe fn get_shared_buffer(dest_ptr: *mut u8, offset: u32, length: u32) -> i32 {
+    with_host(|h| {
+        let start = offset as usize;
+        let end = start + length as usize;
+        if end > h.shared_buffer.len() {
+            return -1;
+        }
+        let copied = h.truncate_next_read.take().map_or(length as usize, |max| max.min(length as usize));
+        core::ptr::copy_nonoverlapping(h.shared_buffer[start..].as_ptr(), dest_ptr, copied);
+        copied as i32
+    })
+}
+
+pub unsafe fn response_set_body(body_ptr: *const u8, body_len: u32) -> i32 {
+    let body = slice::from_raw_parts(body_ptr, body_len as usize).to_vec();
+    with_host(|h| h.response_body = Some(body));
+    0
+}
+
+pub unsafe fn response_set_header(name_ptr: *const u8, name_len: u32, value_ptr: *const u8, value_len: u32) -> i32 {
+    let name = read_str(name_ptr, name_len);
+    let value = read_str(value_ptr, value_len);
+    with_host(|h| {
+        h.response_headers.retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+        h.response_headers.push((name, value));
+    });
+    0
+}
+
+pub unsafe fn response_add_header(name_ptr: *const u8, name_len: u32, value_ptr: *const u8, value_len: u32) -> i32 {
+    let name = read_str(name_ptr, name_len);
+    let value = read_str(value_ptr, value_len);
+    with_host(|h| h.response_headers.push((name, value)));
+    0
+}
+
+pub unsafe fn response_start(status: u32, headers_ptr: *const u8, headers_len: u32) -> i32 {
+    let headers: Vec<(String, String)> =
+        match serde_json::from_slice(slice::from_raw_parts(headers_ptr, headers_len as usize)) {
+            Ok(headers) => headers,
+            Err(_) => return STREAM_ERR_FAILED,
+        };
+    with_host(|h| {
+        if h.stream_status.is_some() {
+            return STREAM_ERR_ALREADY_STARTED;
+        }
+        h.stream_status = Some(status as u16);
+        h.stream_headers = headers;
+        0
+    })
+}
+
+pub unsafe fn response_write(chunk_ptr: *const u8, chunk_len: u32) -> i32 {
+    let chunk = slice::from_raw_parts(chunk_ptr, chunk_len as usize).to_vec();
+    with_host(|h| {
+        if h.stream_status.is_none() {
+            return STREAM_ERR_NOT_STARTED;
+        }
+        if h.stream_ended {
+            return STREAM_ERR_ENDED;
+        }
+        if h.stream_would_block > 0 {
+            h.stream_would_block -= 1;
+            return STREAM_ERR_WOULD_BLOCK;
+        }
+        h.stream_chunks.push(chunk);
+        0
+    })
+}
+
+pub unsafe fn response_end() -> i32 {
+    with_host(|h| {
+        if h.stream_status.is_none() {
+            return STREAM_ERR_NOT_STARTED;
+        }
+        if h.stream_ended {
+            return STREAM_ERR_ENDED;
+        }
+        h.stream_ended = true;
+        0
+    })
+}
+
+pub unsafe fn kv_get(key_ptr: *const u8, key_len: u32) -> i32 {
+    let key = read_str(key_ptr, key_len);
+    if let Some(result) = scripted(Call::KvGet(key.clone())) {
+        return result;
+    }
+    let result = with_host(|h| {
+        if h.kv_unavailable {
+            return Err(KV_ERR_UNAVAILABLE);
+        }
+        h.kv.get(&key).cloned().ok_or(KV_ERR_NOT_FOUND)
+    });
+    match result {
+        Ok(value) => set_shared(value),
+        Err(code) => code,
+    }
+}
+
+pub unsafe fn kv_put(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32) -> i32 {
+    let key = read_str(key_ptr, key_len);
+    if let Some(result) = scripted(Call::KvPut(key.clone())) {
+        return result;
+    }
+    let value = slice::from_raw_parts(value_ptr, value_len as usize).to_vec();
+    with_host(|h| {
+        if h.kv_unavailable {
+            return KV_ERR_UNAVAILABLE;
+        }
+        if value.len() > MAX_KV_VALUE_SIZE {
+            return KV_ERR_VALUE_TOO_LARGE;
+        }
+        if let Some(max) = h.kv_max_keys {
+            if !h.kv.contains_key(&key) && h.kv.len() >= max {
+                return KV_ERR_QUOTA_EXCEEDED;
+            }
+        }
+        h.kv.insert(key, value);
+        0
+    })
+}
+
+pub unsafe fn kv_delete(key_ptr: *const u8, key_len: u32) -> i32 {
+    let key = read_str(key_ptr, key_len);
+    if let Some(result) = scripted(Call::KvDelete(key.clone())) {
+        return result;
+    }
+    with_host(|h| {
+        if h.kv_unavailable {
+            return KV_ERR_UNAVAILABLE;
+        }
+        h.kv.remove(&key).is_some() as i32
+    })
+}
+
+pub unsafe fn kv_list_prefix(
+    prefix_ptr: *const u8,
+    prefix_len: u32,
+    cursor_ptr: *const u8,
+    cursor_len: u32,
+    limit: u32,
+) -> i32 {
+    let prefix = read_str(prefix_ptr, prefix_len);
+    if let Some(result) = scripted(Call::KvListPrefix(prefix.clone())) {
+        return result;
+    }
+    let cursor = if cursor_len == 0 { None } else { Some(read_str(cursor_ptr, cursor_len)) };
+    let limit = limit.clamp(1, 100) as usize;
+
+    let page = with_host(|h| {
+        if h.kv_unavailable {
+            return Err(KV_ERR_UNAVAILABLE);
+        }
+        let mut keys: Vec<String> = h
+            .kv
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .filter(|k| match &cursor {
+                Some(c) => k.as_str() > c.as_str(),
+                None => true,
+            })
+            .take(limit + 1)
+            .cloned()
+            .collect();
+        let next = if keys.len() > limit {
+            keys.truncate(limit);
+            keys.last().cloned()
+        } else {
+            None
+        };
+        Ok(serde_json::json!({ "keys": keys, "cursor": next }))
+    });
+
+    match page {
+        Ok(json) => set_shared(json.to_string().into_bytes()),
+        Err(code) => code,
+    }
+}
+
+pub unsafe fn get_invocation_context() -> i32 {
+    let json = with_host(|h| {
+        h.invocation_context_calls += 1;
+        h.invocation_context.clone()
+    });
+    match json {
+        Some(json) => set_shared(json.into_bytes()),
+        None => -1,
+    }
+}
+
+pub unsafe fn get_usage() -> i32 {
+    match with_host(|h| h.usage.clone()) {
+        Some(json) => set_shared(json.into_bytes()),
+        None => -1,
+    }
+}
+
+pub unsafe fn get_client_info() -> i32 {
+    let json = with_host(|h| {
+        h.client_info_calls += 1;
+        h.client_info.clone()
+    });
+    match json {
+        Some(json) => set_shared(json.into_bytes()),
+        None => -1,
+    }
+}
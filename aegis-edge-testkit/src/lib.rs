@@ -0,0 +1,464 @@
+//! Deterministic test harness for AEGIS edge functions
+//!
+//! Edge functions call the node through `extern "C"` host imports that only
+//! exist inside the Wasm runtime. This crate provides an in-process
+//! `MockHost` implementing those imports so a function's Rust code can be
+//! unit tested natively:
+//!
+//! - Simulated cache, HTTP, KV, request/response and streaming state
+//! - Scriptable expectations on cache/http/kv/log calls, ordered or
+//!   unordered, each either simulated or answered with a canned reply
+//! - Captured logs, parsed as structured JSON on demand
+//! - A fake clock driving cache TTLs
+//! - Fault injection: timeouts and truncated shared-buffer reads
+//!
+//! Bind it in place of the extern block with `bind_mock_host!`:
+//!
+//! ```ignore
+//! #[cfg(not(test))]
+//! extern "C" { /* host imports */ }
+//!
+//! #[cfg(test)]
+//! aegis_edge_testkit::bind_mock_host!();
+//! ```
+//!
+//! State is thread-local, so each test (which runs on its own thread) starts
+//! with an empty host.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+pub mod ffi;
+
+pub const KV_ERR_UNAVAILABLE: i32 = -1;
+pub const KV_ERR_NOT_FOUND: i32 = -2;
+pub const KV_ERR_VALUE_TOO_LARGE: i32 = -3;
+pub const KV_ERR_QUOTA_EXCEEDED: i32 = -4;
+
+pub const STREAM_ERR_FAILED: i32 = -1;
+pub const STREAM_ERR_NOT_STARTED: i32 = -2;
+pub const STREAM_ERR_ENDED: i32 = -3;
+pub const STREAM_ERR_WOULD_BLOCK: i32 = -4;
+pub const STREAM_ERR_ALREADY_STARTED: i32 = -5;
+
+/// Largest value kv_put accepts
+pub const MAX_KV_VALUE_SIZE: usize = 64 * 1024;
+
+/// Initial fake clock (unix seconds)
+pub const DEFAULT_NOW: u64 = 1_700_000_000;
+
+/// How far a timed-out host call advances the fake clock (seconds)
+pub const HOST_CALL_TIMEOUT_SECS: u64 = 30;
+
+/// A scriptable host call, as recorded and as expected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Call {
+    /// Expected messages match any log line containing them
+    Log(String),
+    CacheGet(String),
+    CacheSet { key: String, ttl: u32 },
+    HttpGet(String),
+    HttpPost(String),
+    KvGet(String),
+    KvPut(String),
+    KvDelete(String),
+    KvListPrefix(String),
+}
+
+impl Call {
+    pub fn log(message: impl Into<String>) -> Self {
+        Call::Log(message.into())
+    }
+
+    pub fn cache_get(key: impl Into<String>) -> Self {
+        Call::CacheGet(key.into())
+    }
+
+    pub fn cache_set(key: impl Into<String>, ttl: u32) -> Self {
+        Call::CacheSet { key: key.into(), ttl }
+    }
+
+    pub fn http_get(url: impl Into<String>) -> Self {
+        Call::HttpGet(url.into())
+    }
+
+    pub fn http_post(url: impl Into<String>) -> Self {
+        Call::HttpPost(url.into())
+    }
+
+    pub fn kv_get(key: impl Into<String>) -> Self {
+        Call::KvGet(key.into())
+    }
+
+    pub fn kv_put(key: impl Into<String>) -> Self {
+        Call::KvPut(key.into())
+    }
+
+    pub fn kv_delete(key: impl Into<String>) -> Self {
+        Call::KvDelete(key.into())
+    }
+
+    pub fn kv_list_prefix(prefix: impl Into<String>) -> Self {
+        Call::KvListPrefix(prefix.into())
+    }
+
+    /// Whether an actual call satisfies this expected one
+    fn matches(&self, actual: &Call) -> bool {
+        match (self, actual) {
+            (Call::Log(expected), Call::Log(line)) => line.contains(expected.as_str()),
+            _ => self == actual,
+        }
+    }
+}
+
+/// How the host answers an expected call
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Reply {
+    /// Let the simulated host handle it
+    #[default]
+    Simulate,
+    /// Put these bytes in the shared buffer and return their length
+    Value(Vec<u8>),
+    /// Return this code without touching any state (-1 for a miss or
+    /// failure, or a KV/stream error code)
+    Code(i32),
+    /// Advance the clock by HOST_CALL_TIMEOUT_SECS and fail with -1
+    Timeout,
+}
+
+/// An expected host call, built by `MockHost::expect`
+#[derive(Debug)]
+pub struct Expectation {
+    call: Call,
+    reply: Reply,
+    ordered: bool,
+    remaining: usize,
+}
+
+impl Expectation {
+    /// Answer with `reply` instead of simulating the call
+    pub fn returning(&mut self, reply: Reply) -> &mut Self {
+        self.reply = reply;
+        self
+    }
+
+    /// Allow the call at any point instead of in sequence
+    pub fn unordered(&mut self) -> &mut Self {
+        self.ordered = false;
+        self
+    }
+
+    /// Expect the call `n` times (default once)
+    pub fn times(&mut self, n: usize) -> &mut Self {
+        self.remaining = n;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct MockHost {
+    pub logs: Vec<String>,
+    pub shared_buffer: Vec<u8>,
+    pub cache: HashMap<String, Vec<u8>>,
+    /// Fake clock time at which each cache entry expires (absent = never)
+    pub cache_expiry: HashMap<String, u64>,
+    /// Response bodies by URL, served to http_get and http_post
+    pub http_responses: HashMap<String, Vec<u8>>,
+    /// Response headers served alongside `http_responses`, by URL
+    pub http_response_headers: HashMap<String, Vec<(String, String)>>,
+    pub last_http_response_headers: Vec<(String, String)>,
+    /// Requests sent with http_post, in order: (url, content type, body)
+    pub http_posts: Vec<(String, String, Vec<u8>)>,
+    pub request_uri: String,
+    pub request_headers: Vec<(String, String)>,
+    pub response_body: Option<Vec<u8>>,
+    pub response_headers: Vec<(String, String)>,
+    pub kv: BTreeMap<String, Vec<u8>>,
+    /// Maximum number of KV keys before kv_put reports quota exceeded
+    pub kv_max_keys: Option<usize>,
+    /// Simulate a node without a KV store
+    pub kv_unavailable: bool,
+    /// JSON returned by get_invocation_context (None = host error)
+    pub invocation_context: Option<String>,
+    /// Number of get_invocation_context calls
+    pub invocation_context_calls: u32,
+    /// JSON returned by get_usage (None = usage unavailable)
+    pub usage: Option<String>,
+    /// JSON returned by get_client_info (None = unavailable)
+    pub client_info: Option<String>,
+    /// Number of get_client_info calls
+    pub client_info_calls: u32,
+    /// Status passed to response_start (None until a stream starts)
+    pub stream_status: Option<u16>,
+    pub stream_headers: Vec<(String, String)>,
+    /// Chunks accepted by response_write, in order
+    pub stream_chunks: Vec<Vec<u8>>,
+    pub stream_ended: bool,
+    /// Number of upcoming response_write calls that report would-block
+    pub stream_would_block: u32,
+    /// Fake clock (unix seconds)
+    pub now: u64,
+    /// Copy at most this many bytes on the next get_shared_buffer call
+    pub truncate_next_read: Option<usize>,
+    /// Every scriptable call, in order
+    pub calls: Vec<Call>,
+    expectations: Vec<Expectation>,
+    violations: Vec<String>,
+}
+
+impl Default for MockHost {
+    fn default() -> Self {
+        Self {
+            logs: Vec::new(),
+            shared_buffer: Vec::new(),
+            cache: HashMap::new(),
+            cache_expiry: HashMap::new(),
+            http_responses: HashMap::new(),
+            http_response_headers: HashMap::new(),
+            last_http_response_headers: Vec::new(),
+            http_posts: Vec::new(),
+            request_uri: String::new(),
+            request_headers: Vec::new(),
+            response_body: None,
+            response_headers: Vec::new(),
+            kv: BTreeMap::new(),
+            kv_max_keys: None,
+            kv_unavailable: false,
+            invocation_context: None,
+            invocation_context_calls: 0,
+            usage: None,
+            client_info: None,
+            client_info_calls: 0,
+            stream_status: None,
+            stream_headers: Vec::new(),
+            stream_chunks: Vec::new(),
+            stream_ended: false,
+            stream_would_block: 0,
+            now: DEFAULT_NOW,
+            truncate_next_read: None,
+            calls: Vec::new(),
+            expectations: Vec::new(),
+            violations: Vec::new(),
+        }
+    }
+}
+
+impl MockHost {
+    /// Expect `call`, in sequence with the other ordered expectations
+    ///
+    /// Calls without a matching expectation are still simulated; a call
+    /// matching an ordered expectation that isn't next is a violation.
+    pub fn expect(&mut self, call: Call) -> &mut Expectation {
+        self.expectations.push(Expectation { call, reply: Reply::Simulate, ordered: true, remaining: 1 });
+        self.expectations.last_mut().unwrap()
+    }
+
+    /// Advance the fake clock
+    pub fn advance(&mut self, secs: u64) {
+        self.now += secs;
+    }
+
+    /// Log lines parsed as JSON (lines that aren't JSON become strings)
+    pub fn log_lines(&self) -> Vec<Value> {
+        self.logs
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|_| Value::String(line.clone())))
+            .collect()
+    }
+
+    /// Structured log lines at `level`
+    pub fn logs_at(&self, level: &str) -> Vec<Value> {
+        self.log_lines().into_iter().filter(|line| line["level"] == level).collect()
+    }
+
+    /// Panic unless every expectation was met, in order
+    pub fn assert_expectations(&self) {
+        let mut problems = self.violations.clone();
+        problems.extend(
+            self.expectations
+                .iter()
+                .filter(|e| e.remaining > 0)
+                .map(|e| format!("expected {:?} {} more time(s)", e.call, e.remaining)),
+        );
+        assert!(problems.is_empty(), "host expectations not met:\n  {}", problems.join("\n  "));
+    }
+
+    /// Record a call and pick the reply of the expectation it satisfies
+    fn record(&mut self, call: Call) -> Reply {
+        let next_ordered = self.expectations.iter().position(|e| e.ordered && e.remaining > 0);
+        let matched = match next_ordered {
+            Some(i) if self.expectations[i].call.matches(&call) => Some(i),
+            _ => self
+                .expectations
+                .iter()
+                .position(|e| !e.ordered && e.remaining > 0 && e.call.matches(&call)),
+        };
+
+        let reply = match matched {
+            Some(i) => {
+                self.expectations[i].remaining -= 1;
+                self.expectations[i].reply.clone()
+            }
+            None => {
+                if let Some(e) = self.expectations.iter().find(|e| e.ordered && e.remaining > 0 && e.call.matches(&call)) {
+                    let next = &self.expectations[next_ordered.unwrap()].call;
+                    self.violations.push(format!("{:?} called out of order (expected {:?} first)", e.call, next));
+                }
+                Reply::Simulate
+            }
+        };
+        self.calls.push(call);
+        reply
+    }
+
+    /// Cached value for `key`, dropping it if its TTL has elapsed
+    fn cache_lookup(&mut self, key: &str) -> Option<Vec<u8>> {
+        if self.cache_expiry.get(key).is_some_and(|&expiry| expiry <= self.now) {
+            self.cache.remove(key);
+            self.cache_expiry.remove(key);
+        }
+        self.cache.get(key).cloned()
+    }
+}
+
+thread_local! {
+    static HOST: RefCell<MockHost> = RefCell::new(MockHost::default());
+}
+
+/// Access the mock host state for setup and assertions
+pub fn with_host<R>(f: impl FnOnce(&mut MockHost) -> R) -> R {
+    HOST.with(|h| f(&mut h.borrow_mut()))
+}
+
+/// Replace the host imports with the mock host for a test build
+///
+/// Invoke under `#[cfg(test)]` where the `extern "C"` import block is
+/// declared: it brings functions with the same names and signatures into
+/// scope, so call sites compile unchanged.
+#[macro_export]
+macro_rules! bind_mock_host {
+    () => {
+        pub use $crate::ffi::*;
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_get(key: &str) -> Option<Vec<u8>> {
+        let len = unsafe { ffi::cache_get(key.as_ptr(), key.len() as u32) };
+        if len < 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; len as usize];
+        let copied = unsafe { ffi::get_shared_buffer(buffer.as_mut_ptr(), 0, len as u32) };
+        buffer.truncate(copied.max(0) as usize);
+        Some(buffer)
+    }
+
+    fn cache_set(key: &str, value: &[u8], ttl: u32) -> i32 {
+        unsafe { ffi::cache_set(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32, ttl) }
+    }
+
+    #[test]
+    fn test_ordered_expectations_met() {
+        with_host(|h| {
+            h.expect(Call::cache_get("k")).returning(Reply::Value(b"canned".to_vec()));
+            h.expect(Call::cache_set("k", 60));
+        });
+
+        assert_eq!(cache_get("k").unwrap(), b"canned");
+        assert_eq!(cache_set("k", b"v", 60), 0);
+        with_host(|h| {
+            h.assert_expectations();
+            assert_eq!(h.cache["k"], b"v");
+            assert_eq!(h.calls, vec![Call::cache_get("k"), Call::cache_set("k", 60)]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "out of order")]
+    fn test_out_of_order_call_is_violation() {
+        with_host(|h| {
+            h.expect(Call::cache_get("a"));
+            h.expect(Call::cache_get("b"));
+        });
+
+        cache_get("b");
+        cache_get("a");
+        with_host(|h| h.assert_expectations());
+    }
+
+    #[test]
+    fn test_unordered_expectation_and_times() {
+        with_host(|h| {
+            h.expect(Call::cache_get("a")).unordered().times(2).returning(Reply::Code(-1));
+        });
+
+        cache_set("a", b"v", 0);
+        assert_eq!(cache_get("a"), None);
+        assert_eq!(cache_get("a"), None);
+        // Past the expected count the call is simulated again
+        assert_eq!(cache_get("a").unwrap(), b"v");
+        with_host(|h| h.assert_expectations());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 more time(s)")]
+    fn test_unmet_expectation_panics() {
+        with_host(|h| {
+            h.expect(Call::http_get("https://example.com/"));
+            h.assert_expectations();
+        });
+    }
+
+    #[test]
+    fn test_cache_ttl_follows_clock() {
+        cache_set("short", b"v", 60);
+        cache_set("forever", b"v", 0);
+
+        with_host(|h| h.advance(59));
+        assert!(cache_get("short").is_some());
+        with_host(|h| h.advance(1));
+        assert_eq!(cache_get("short"), None);
+        assert!(cache_get("forever").is_some());
+    }
+
+    #[test]
+    fn test_timeout_advances_clock() {
+        with_host(|h| {
+            h.cache.insert("k".to_string(), b"v".to_vec());
+            h.expect(Call::cache_get("k")).returning(Reply::Timeout);
+        });
+
+        assert_eq!(cache_get("k"), None);
+        assert_eq!(with_host(|h| h.now), DEFAULT_NOW + HOST_CALL_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn test_truncated_read() {
+        cache_set("k", b"0123456789", 0);
+        with_host(|h| h.truncate_next_read = Some(4));
+
+        assert_eq!(cache_get("k").unwrap(), b"0123");
+        assert_eq!(cache_get("k").unwrap(), b"0123456789");
+    }
+
+    #[test]
+    fn test_log_lines() {
+        let line = r#"{"level":"warn","message":"quota"}"#;
+        unsafe {
+            ffi::log(line.as_ptr(), line.len() as u32);
+            ffi::log("plain".as_ptr(), 5);
+        }
+
+        with_host(|h| {
+            assert_eq!(h.logs_at("warn").len(), 1);
+            assert_eq!(h.log_lines()[1], Value::from("plain"));
+            assert_eq!(h.calls[0], Call::log(line));
+        });
+    }
+}
@@ -83,6 +83,7 @@ proposalCommand
   .option("--type <type>", "Proposal type (general, treasuryWithdrawal, parameterChange)", "general")
   .option("--recipient <address>", "Recipient address (for treasury withdrawal)")
   .option("--amount <amount>", "Amount in AEGIS tokens (for treasury withdrawal)")
+  .option("--tags <tags>", "Comma-separated category tags (max 5, lowercase alphanumeric)")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .option("-k, --keypair <path>", "Path to keypair file")
   .action(async (options) => {
@@ -129,6 +130,7 @@ proposalCommand
         proposalType,
         executionData,
         proposerTokenAccount: new PublicKey(options.tokenAccount),
+        tags: options.tags ? options.tags.split(",").map((tag: string) => tag.trim()) : [],
       });
 
       spinner.stop();
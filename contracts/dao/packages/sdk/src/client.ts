@@ -210,12 +210,15 @@ export class DaoClient {
       : null;
 
     return (this.program.methods as any)
-      .createProposal(
-        params.title,
-        params.descriptionCid,
-        toAnchorProposalType(params.proposalType) as any,
-        executionData
-      )
+      .createProposal({
+        title: params.title,
+        descriptionCid: params.descriptionCid,
+        proposalType: toAnchorProposalType(params.proposalType) as any,
+        executionData,
+        tags: params.tags ?? [],
+        selfFunded: params.selfFunded ?? true,
+        urgency: { standard: {} },
+      })
       .accounts({
         daoConfig: daoConfigPDA,
         proposal: proposalPDA,
@@ -470,12 +473,21 @@ export class DaoClient {
     const [daoConfigPDA] = getDaoConfigPDA(this.programId);
 
     return (this.program.methods as any)
-      .queueConfigUpdate(
-        params.newVotingPeriod ? new BN(params.newVotingPeriod) : null,
-        params.newProposalBond ? this.toBN(params.newProposalBond) : null,
-        params.newQuorumPercentage ?? null,
-        params.newApprovalThreshold ?? null
-      )
+      .queueConfigUpdate({
+        newVotingPeriod: params.newVotingPeriod ? new BN(params.newVotingPeriod) : null,
+        newProposalBond: params.newProposalBond ? this.toBN(params.newProposalBond) : null,
+        newQuorumPercentage: params.newQuorumPercentage ?? null,
+        newApprovalThreshold: params.newApprovalThreshold ?? null,
+        newProposalFee: null,
+        newAdaptiveQuorum: null,
+        newMaxWithdrawalBps: null,
+        newConvictionSchedule: null,
+        newGuardian: null,
+        newVetoThresholdBps: null,
+        newGeneralVotingMode: null,
+        newMaxSnapshotVoteBps: null,
+        newVotingModel: null,
+      })
       .accounts({
         daoConfig: daoConfigPDA,
         authority: this.provider.wallet.publicKey,
@@ -580,9 +592,13 @@ export class DaoClient {
       executionEligibleAt: account.executionEligibleAt, // Whitepaper: 3-day timelock
       createdAt: account.createdAt,
       executedAt: account.executedAt,
+      finalizedBy: account.finalizedBy,
+      finalizedAt: account.finalizedAt,
+      executedBy: account.executedBy,
       bondReturned: account.bondReturned,
       snapshotSupply: account.snapshotSupply,
       bump: account.bump,
+      tags: account.tags,
     };
   }
 
@@ -15,6 +15,7 @@ export const SEEDS = {
   PROPOSAL: Buffer.from("proposal"),
   VOTE_ESCROW: Buffer.from("vote_escrow"),
   VOTE_RECORD: Buffer.from("vote"),
+  SPONSORSHIP: Buffer.from("sponsorship"),
 } as const;
 
 /**
@@ -7,6 +7,7 @@ export {
   getProposalPDA,
   getVoteEscrowPDA,
   getVoteRecordPDA,
+  getSponsorshipPDA,
 } from "./pda";
 
 // Types
@@ -59,6 +59,22 @@ export function getVoteRecordPDA(
   );
 }
 
+/**
+ * Derive a Proposal Sponsorship PDA
+ * Seeds: ["sponsorship", proposal_id.to_le_bytes(), sponsor.as_ref()]
+ */
+export function getSponsorshipPDA(
+  proposalId: BN | number | bigint,
+  sponsor: PublicKey,
+  programId: PublicKey = DAO_PROGRAM_ID
+): [PublicKey, number] {
+  const id = toBN(proposalId);
+  return PublicKey.findProgramAddressSync(
+    [SEEDS.SPONSORSHIP, id.toArrayLike(Buffer, "le", 8), sponsor.toBuffer()],
+    programId
+  );
+}
+
 /**
  * Helper to convert various number types to BN
  */
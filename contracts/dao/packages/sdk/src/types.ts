@@ -12,6 +12,7 @@ export enum ProposalType {
   General = "general",
   TreasuryWithdrawal = "treasuryWithdrawal",
   ParameterChange = "parameterChange",
+  EmergencyConfigCancel = "emergencyConfigCancel",
 }
 
 /**
@@ -23,6 +24,7 @@ export enum ProposalStatus {
   Defeated = "defeated",
   Executed = "executed",
   Cancelled = "cancelled",
+  Pending = "pending", // sponsored proposal still raising its bond
 }
 
 /**
@@ -81,6 +83,14 @@ export interface Proposal {
   bondReturned: boolean;
   snapshotSupply: BN;
   bump: number;
+  tags: string[]; // category tags for filtering
+  sponsored: boolean; // bond underwritten by sponsors
+  bondTarget: BN; // bond sponsors must raise (0 when self-funded)
+  bondRaised: BN;
+  fundingDeadline: BN; // end of the 72-hour sponsor funding window
+  finalizedBy: PublicKey; // default until finalized
+  finalizedAt: BN; // 0 until finalized
+  executedBy: PublicKey; // default until executed
 }
 
 /**
@@ -160,6 +170,10 @@ export interface CreateProposalParams {
     amount: BN | number | bigint;
   };
   proposerTokenAccount: PublicKey;
+  /** Up to 5 lowercase alphanumeric category tags (1-16 chars each) */
+  tags?: string[];
+  /** Post the bond yourself (default) instead of raising it from sponsors */
+  selfFunded?: boolean;
 }
 
 /**
@@ -220,6 +234,7 @@ export function parseProposalType(anchorType: object): ProposalType {
   if ("general" in anchorType) return ProposalType.General;
   if ("treasuryWithdrawal" in anchorType) return ProposalType.TreasuryWithdrawal;
   if ("parameterChange" in anchorType) return ProposalType.ParameterChange;
+  if ("emergencyConfigCancel" in anchorType) return ProposalType.EmergencyConfigCancel;
   throw new Error(`Unknown proposal type: ${JSON.stringify(anchorType)}`);
 }
 
@@ -232,6 +247,7 @@ export function parseProposalStatus(anchorStatus: object): ProposalStatus {
   if ("defeated" in anchorStatus) return ProposalStatus.Defeated;
   if ("executed" in anchorStatus) return ProposalStatus.Executed;
   if ("cancelled" in anchorStatus) return ProposalStatus.Cancelled;
+  if ("pending" in anchorStatus) return ProposalStatus.Pending;
   throw new Error(`Unknown proposal status: ${JSON.stringify(anchorStatus)}`);
 }
 
@@ -256,6 +272,8 @@ export function toAnchorProposalType(type: ProposalType): object {
       return { treasuryWithdrawal: {} };
     case ProposalType.ParameterChange:
       return { parameterChange: {} };
+    case ProposalType.EmergencyConfigCancel:
+      return { emergencyConfigCancel: {} };
   }
 }
 
@@ -27,9 +27,42 @@ const DEFAULT_PROPOSAL_BOND: u64 = 100_000_000_000;
 /// Minimum proposal bond (1 AEGIS token)
 const MIN_PROPOSAL_BOND: u64 = 1_000_000_000;
 
+/// Non-refundable proposal creation fee (1 AEGIS token - partially burned)
+const DEFAULT_PROPOSAL_FEE: u64 = 1_000_000_000;
+
+/// Maximum proposal creation fee (100 AEGIS tokens)
+const MAX_PROPOSAL_FEE: u64 = 100_000_000_000;
+
+/// Per-proposal treasury withdrawal cap (10% of treasury balance)
+const DEFAULT_MAX_WITHDRAWAL_BPS: u16 = 1000;
+
+/// Vote weight multiplier for deposits made before voting opens (1.25x)
+const DEFAULT_PRE_VOTING_MULTIPLIER_BPS: u16 = 12500;
+
+/// Vote weight multiplier for deposits in the first half of the voting window (1.1x)
+const DEFAULT_FIRST_HALF_MULTIPLIER_BPS: u16 = 11000;
+
+/// Vote weight multiplier for later deposits (1.0x)
+const DEFAULT_LATE_MULTIPLIER_BPS: u16 = 10000;
+
+/// Maximum conviction multiplier (2x)
+const MAX_CONVICTION_MULTIPLIER_BPS: u16 = 20000;
+
 /// Quorum percentage (10% of total supply must vote)
 const DEFAULT_QUORUM_PERCENTAGE: u8 = 10;
 
+/// Adaptive quorum floor (5%)
+const DEFAULT_MIN_QUORUM_PERCENTAGE: u8 = 5;
+
+/// Adaptive quorum cap (20%)
+const DEFAULT_MAX_QUORUM_PERCENTAGE: u8 = 20;
+
+/// Weight of the newest proposal in the rolling participation average (30%)
+const DEFAULT_PARTICIPATION_SMOOTHING_BPS: u16 = 3000;
+
+/// Adaptive quorum moves the base quorum 1/N of the way toward recent participation
+const ADAPTIVE_QUORUM_ADJUSTMENT_DIVISOR: i64 = 2;
+
 /// Approval threshold percentage (51% of votes must be FOR)
 const DEFAULT_APPROVAL_THRESHOLD: u8 = 51;
 
@@ -48,12 +81,59 @@ const MAX_TITLE_LENGTH: usize = 128;
 /// Maximum description CID length (IPFS CID)
 const MAX_DESCRIPTION_CID_LENGTH: usize = 64;
 
+/// Maximum number of tags on a proposal
+const MAX_PROPOSAL_TAGS: usize = 5;
+
+/// Maximum tag length (lowercase alphanumeric)
+const MAX_TAG_LENGTH: usize = 16;
+
 /// Timelock delay for config changes (48 hours)
 const CONFIG_TIMELOCK_DELAY: i64 = 48 * 60 * 60;
 
 /// Execution timelock after proposal passes (3 days - per whitepaper)
 const EXECUTION_TIMELOCK: i64 = 3 * 24 * 60 * 60;
 
+/// How long a passed withdrawal stays executable once its timelock ends
+/// (30 days); after that it can be expired, releasing its treasury commitment
+const EXECUTION_WINDOW: i64 = 30 * 24 * 60 * 60;
+
+/// Voting window for emergency config-cancel proposals (24 hours, so a vote
+/// can conclude inside the 48-hour config timelock)
+const EMERGENCY_VOTING_PERIOD: i64 = 24 * 60 * 60;
+
+/// Window for sponsors to fund the bond of a sponsored proposal (72 hours)
+const SPONSORSHIP_FUNDING_WINDOW: i64 = 72 * 60 * 60;
+
+/// Largest single snapshot vote (5% of snapshot supply)
+const DEFAULT_MAX_SNAPSHOT_VOTE_BPS: u16 = 500;
+
+/// Minimum time between tally checkpoints of one proposal (1 hour)
+const CHECKPOINT_INTERVAL: i64 = 60 * 60;
+
+/// Maximum proposals a voter position can hold counted votes on at once
+const MAX_POSITION_VOTES: usize = 8;
+
+/// Maximum recipients of one treasury withdrawal proposal
+const MAX_WITHDRAWAL_RECIPIENTS: usize = 8;
+
+/// Schema version of ProposalManifestEvent (bump on any field change)
+const PROPOSAL_MANIFEST_VERSION: u8 = 3;
+
+/// Layout version of DaoConfig (0 = the original layout, see LegacyDaoConfigV0)
+const CURRENT_DAO_CONFIG_VERSION: u8 = 1;
+
+/// Bond multiple for Emergency-urgency proposals
+const EMERGENCY_BOND_MULTIPLIER: u64 = 3;
+
+/// Minimum approval threshold for Emergency-urgency proposals (percent)
+const EMERGENCY_APPROVAL_THRESHOLD: u8 = 66;
+
+/// Domain prefix of vote messages signed for cast_vote_by_signature
+const SIGNED_VOTE_DOMAIN: &[u8] = b"aegis-dao:signed-vote:v1";
+
+/// Ed25519 signature verification program
+const ED25519_PROGRAM_ID: Pubkey = Pubkey::from_str_const("Ed25519SigVerify111111111111111111111111111");
+
 /// PDA seeds for vote vault (reserved for future PDA-based vault)
 #[allow(dead_code)]
 const _VOTE_VAULT_SEED: &[u8] = b"vote_vault";
@@ -71,6 +151,7 @@ pub mod dao {
         proposal_bond: u64,
         quorum_percentage: u8,
         approval_threshold: u8,
+        voting_model: VotingModel,
     ) -> Result<()> {
         // Validate parameters
         require!(
@@ -104,6 +185,23 @@ pub mod dao {
         dao_config.voting_period = voting_period;
         dao_config.discussion_period = discussion_period;
         dao_config.proposal_bond = proposal_bond;
+        dao_config.proposal_fee = DEFAULT_PROPOSAL_FEE;
+        dao_config.max_withdrawal_bps = DEFAULT_MAX_WITHDRAWAL_BPS;
+        dao_config.conviction_schedule = ConvictionSchedule::default();
+        dao_config.guardian = Pubkey::default();
+        dao_config.veto_threshold_bps = 0;
+        dao_config.general_voting_mode = VotingMode::Escrow;
+        dao_config.max_snapshot_vote_bps = DEFAULT_MAX_SNAPSHOT_VOTE_BPS;
+        dao_config.committed_outflow = 0;
+        dao_config.voting_model = voting_model;
+        dao_config.adaptive_quorum = AdaptiveQuorumConfig {
+            enabled: false,
+            min_quorum_percentage: DEFAULT_MIN_QUORUM_PERCENTAGE,
+            max_quorum_percentage: DEFAULT_MAX_QUORUM_PERCENTAGE,
+            smoothing_bps: DEFAULT_PARTICIPATION_SMOOTHING_BPS,
+        };
+        // Seed the rolling average at the base quorum so enabling the mode is neutral
+        dao_config.avg_participation_bps = quorum_percentage as u16 * 100;
         dao_config.quorum_percentage = quorum_percentage;
         dao_config.approval_threshold = approval_threshold;
         dao_config.proposal_count = 0;
@@ -111,14 +209,16 @@ pub mod dao {
         dao_config.paused = false;
         dao_config.pending_config_change = None;
         dao_config.bump = ctx.bumps.dao_config;
+        dao_config.config_version = CURRENT_DAO_CONFIG_VERSION;
 
         msg!(
-            "DAO initialized: discussion={}s, voting={}s, bond={}, quorum={}%, threshold={}%",
+            "DAO initialized: discussion={}s, voting={}s, bond={}, quorum={}%, threshold={}%, model={:?}",
             discussion_period,
             voting_period,
             proposal_bond,
             quorum_percentage,
-            approval_threshold
+            approval_threshold,
+            voting_model
         );
 
         emit!(DaoInitializedEvent {
@@ -127,6 +227,7 @@ pub mod dao {
             discussion_period,
             voting_period,
             proposal_bond,
+            voting_model,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -169,39 +270,116 @@ pub mod dao {
         Ok(())
     }
 
+    /// Rewrite a DaoConfig created by an older program version in the
+    /// current layout (authority only)
+    ///
+    /// The layout is identified by the account size (version 0 has no
+    /// version byte). Fields added since then get the defaults
+    /// initialize_dao uses, and a pending config change keeps its queued
+    /// values and timelock. No-op for configs already at the current version.
+    pub fn migrate_dao_config(ctx: Context<MigrateDaoConfig>) -> Result<()> {
+        let clock = Clock::get()?;
+        let info = ctx.accounts.dao_config.to_account_info();
+        let original_len = info.data_len();
+
+        // authority directly follows the discriminator in every layout
+        let authority = info
+            .try_borrow_data()?
+            .get(8..40)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(DaoError::UnauthorizedAuthority)?;
+        require_keys_eq!(ctx.accounts.authority.key(), authority, DaoError::UnauthorizedAuthority);
+
+        let (from_version, config) = DaoConfig::decode_any_version(&info.try_borrow_data()?)?;
+        if from_version == CURRENT_DAO_CONFIG_VERSION {
+            msg!("DAO config already at layout version {}", from_version);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            DaoConfig::MAX_SIZE,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "DAO config migrated from layout version {} to {} ({} -> {} bytes)",
+            from_version,
+            CURRENT_DAO_CONFIG_VERSION,
+            original_len,
+            DaoConfig::MAX_SIZE
+        );
+        emit!(DaoConfigMigratedEvent {
+            from_version,
+            to_version: CURRENT_DAO_CONFIG_VERSION,
+            from_len: original_len as u32,
+            to_len: DaoConfig::MAX_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Queue a DAO config update (subject to timelock)
-    pub fn queue_config_update(
-        ctx: Context<QueueConfigUpdate>,
-        new_voting_period: Option<i64>,
-        new_proposal_bond: Option<u64>,
-        new_quorum_percentage: Option<u8>,
-        new_approval_threshold: Option<u8>,
-    ) -> Result<()> {
+    pub fn queue_config_update(ctx: Context<QueueConfigUpdate>, params: ConfigUpdateParams) -> Result<()> {
+        let ConfigUpdateParams {
+            new_voting_period,
+            new_proposal_bond,
+            new_quorum_percentage,
+            new_approval_threshold,
+            new_proposal_fee,
+            new_adaptive_quorum,
+            new_max_withdrawal_bps,
+            new_conviction_schedule,
+            new_guardian,
+            new_veto_threshold_bps,
+            new_general_voting_mode,
+            new_max_snapshot_vote_bps,
+            new_voting_model,
+        } = params;
         let dao_config = &mut ctx.accounts.dao_config;
         let clock = Clock::get()?;
 
         // Validate new parameters if provided
-        if let Some(period) = new_voting_period {
-            require!(
-                period >= MIN_VOTING_PERIOD && period <= MAX_VOTING_PERIOD,
-                DaoError::InvalidVotingPeriod
-            );
-        }
-        if let Some(bond) = new_proposal_bond {
-            require!(bond >= MIN_PROPOSAL_BOND, DaoError::InvalidProposalBond);
+        validate_governance_parameters(
+            new_voting_period,
+            new_quorum_percentage,
+            new_approval_threshold,
+            new_proposal_bond,
+        )?;
+        // Zero disables the fee
+        if let Some(fee) = new_proposal_fee {
+            require!(fee <= MAX_PROPOSAL_FEE, DaoError::InvalidProposalFee);
         }
-        if let Some(quorum) = new_quorum_percentage {
+        if let Some(adaptive) = &new_adaptive_quorum {
             require!(
-                quorum > 0 && quorum <= 100,
-                DaoError::InvalidQuorumPercentage
+                adaptive.min_quorum_percentage > 0
+                    && adaptive.min_quorum_percentage <= adaptive.max_quorum_percentage
+                    && adaptive.max_quorum_percentage <= 100,
+                DaoError::InvalidQuorumBounds
             );
-        }
-        if let Some(threshold) = new_approval_threshold {
             require!(
-                threshold > 0 && threshold <= 100,
-                DaoError::InvalidApprovalThreshold
+                adaptive.smoothing_bps > 0 && adaptive.smoothing_bps <= 10000,
+                DaoError::InvalidParticipationSmoothing
             );
         }
+        if let Some(bps) = new_max_withdrawal_bps {
+            require!(bps > 0 && bps <= 10000, DaoError::InvalidMaxWithdrawalBps);
+        }
+        if let Some(schedule) = &new_conviction_schedule {
+            require!(schedule.is_valid(), DaoError::InvalidConvictionSchedule);
+        }
+        // Zero disables objections
+        if let Some(bps) = new_veto_threshold_bps {
+            require!(bps <= 10000, DaoError::InvalidVetoThreshold);
+        }
+        if let Some(bps) = new_max_snapshot_vote_bps {
+            require!(bps > 0 && bps <= 10000, DaoError::InvalidMaxSnapshotVoteBps);
+        }
+        dao_config.ensure_no_pending_config_change()?;
 
         // Queue the config change with timelock
         let execute_after = clock.unix_timestamp + CONFIG_TIMELOCK_DELAY;
@@ -210,8 +388,19 @@ pub mod dao {
             new_proposal_bond,
             new_quorum_percentage,
             new_approval_threshold,
+            new_proposal_fee,
+            new_adaptive_quorum: new_adaptive_quorum.clone(),
+            new_max_withdrawal_bps,
+            new_conviction_schedule: new_conviction_schedule.clone(),
             queued_at: clock.unix_timestamp,
             execute_after,
+            new_guardian,
+            new_veto_threshold_bps,
+            objection_total: 0,
+            vetoed: false,
+            new_general_voting_mode,
+            new_max_snapshot_vote_bps,
+            new_voting_model,
         });
 
         msg!(
@@ -224,6 +413,15 @@ pub mod dao {
             new_proposal_bond,
             new_quorum_percentage,
             new_approval_threshold,
+            new_proposal_fee,
+            new_adaptive_quorum,
+            new_max_withdrawal_bps,
+            new_conviction_schedule,
+            new_guardian,
+            new_veto_threshold_bps,
+            new_general_voting_mode,
+            new_max_snapshot_vote_bps,
+            new_voting_model,
             execute_after,
             timestamp: clock.unix_timestamp,
         });
@@ -242,11 +440,8 @@ pub mod dao {
             .clone()
             .ok_or(DaoError::NoPendingConfigChange)?;
 
-        // Check timelock has expired
-        require!(
-            clock.unix_timestamp >= pending.execute_after,
-            DaoError::TimelockNotExpired
-        );
+        // Check timelock has expired and token holders haven't vetoed the change
+        pending.ensure_executable(clock.unix_timestamp)?;
 
         // Apply changes
         if let Some(period) = pending.new_voting_period {
@@ -265,6 +460,53 @@ pub mod dao {
             dao_config.approval_threshold = threshold;
             msg!("Approval threshold updated to: {}%", threshold);
         }
+        if let Some(fee) = pending.new_proposal_fee {
+            dao_config.proposal_fee = fee;
+            msg!("Proposal fee updated to: {}", fee);
+        }
+        if let Some(adaptive) = pending.new_adaptive_quorum {
+            msg!(
+                "Adaptive quorum updated: enabled={}, bounds={}-{}%, smoothing={}bps",
+                adaptive.enabled,
+                adaptive.min_quorum_percentage,
+                adaptive.max_quorum_percentage,
+                adaptive.smoothing_bps
+            );
+            dao_config.adaptive_quorum = adaptive;
+        }
+        if let Some(bps) = pending.new_max_withdrawal_bps {
+            dao_config.max_withdrawal_bps = bps;
+            msg!("Max withdrawal per proposal updated to: {}bps", bps);
+        }
+        if let Some(schedule) = pending.new_conviction_schedule {
+            msg!(
+                "Conviction schedule updated: pre-voting={}bps, first-half={}bps, late={}bps",
+                schedule.pre_voting_bps,
+                schedule.first_half_bps,
+                schedule.late_bps
+            );
+            dao_config.conviction_schedule = schedule;
+        }
+        if let Some(guardian) = pending.new_guardian {
+            dao_config.guardian = guardian;
+            msg!("Guardian updated to: {}", guardian);
+        }
+        if let Some(bps) = pending.new_veto_threshold_bps {
+            dao_config.veto_threshold_bps = bps;
+            msg!("Config veto threshold updated to: {}bps", bps);
+        }
+        if let Some(mode) = pending.new_general_voting_mode {
+            dao_config.general_voting_mode = mode;
+            msg!("General proposal voting mode updated to: {:?}", mode);
+        }
+        if let Some(bps) = pending.new_max_snapshot_vote_bps {
+            dao_config.max_snapshot_vote_bps = bps;
+            msg!("Max snapshot vote updated to: {}bps", bps);
+        }
+        if let Some(model) = pending.new_voting_model {
+            dao_config.voting_model = model;
+            msg!("Voting model updated to: {:?}", model);
+        }
 
         // Clear pending change
         dao_config.pending_config_change = None;
@@ -274,6 +516,15 @@ pub mod dao {
             proposal_bond: dao_config.proposal_bond,
             quorum_percentage: dao_config.quorum_percentage,
             approval_threshold: dao_config.approval_threshold,
+            proposal_fee: dao_config.proposal_fee,
+            adaptive_quorum: dao_config.adaptive_quorum.clone(),
+            max_withdrawal_bps: dao_config.max_withdrawal_bps,
+            conviction_schedule: dao_config.conviction_schedule.clone(),
+            guardian: dao_config.guardian,
+            veto_threshold_bps: dao_config.veto_threshold_bps,
+            general_voting_mode: dao_config.general_voting_mode,
+            max_snapshot_vote_bps: dao_config.max_snapshot_vote_bps,
+            voting_model: dao_config.voting_model,
             timestamp: clock.unix_timestamp,
         });
 
@@ -281,13 +532,19 @@ pub mod dao {
     }
 
     /// Cancel a queued config update
+    ///
+    /// A vetoed change stays recorded until its execute_after, so the
+    /// authority can't clear the veto and queue the change again; the
+    /// guardian or an emergency proposal can still cancel it sooner.
     pub fn cancel_config_update(ctx: Context<CancelConfigUpdate>) -> Result<()> {
         let dao_config = &mut ctx.accounts.dao_config;
+        let clock = Clock::get()?;
 
-        require!(
-            dao_config.pending_config_change.is_some(),
-            DaoError::NoPendingConfigChange
-        );
+        dao_config
+            .pending_config_change
+            .as_ref()
+            .ok_or(DaoError::NoPendingConfigChange)?
+            .ensure_authority_cancellable(clock.unix_timestamp)?;
 
         dao_config.pending_config_change = None;
 
@@ -295,6 +552,216 @@ pub mod dao {
 
         emit!(ConfigUpdateCancelledEvent {
             authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the guardian (authority only, once)
+    ///
+    /// Afterwards the guardian can only be replaced through a queued config
+    /// update, which the current guardian can cancel, so a stolen authority
+    /// key can't swap it out.
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        let dao_config = &mut ctx.accounts.dao_config;
+
+        require!(
+            dao_config.guardian == Pubkey::default(),
+            DaoError::GuardianAlreadySet
+        );
+        require!(guardian != Pubkey::default(), DaoError::InvalidGuardian);
+
+        dao_config.guardian = guardian;
+
+        msg!("Guardian set to: {}", guardian);
+
+        emit!(GuardianSetEvent {
+            guardian,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel any pending config change without the authority
+    ///
+    /// Allowed for the guardian, or for anyone presenting a passed
+    /// EmergencyConfigCancel proposal created after the change was queued
+    /// (the proposal is marked executed). Covers the case where the
+    /// authority key is compromised and used to queue a malicious change.
+    pub fn emergency_cancel_config_update(ctx: Context<EmergencyCancelConfigUpdate>) -> Result<()> {
+        let dao_config = &mut ctx.accounts.dao_config;
+        let canceller = ctx.accounts.canceller.key();
+        let clock = Clock::get()?;
+
+        let pending = dao_config
+            .pending_config_change
+            .as_ref()
+            .ok_or(DaoError::NoPendingConfigChange)?;
+        let queued_at = pending.queued_at;
+        let objection_total = pending.objection_total;
+
+        let is_guardian = dao_config.guardian != Pubkey::default() && canceller == dao_config.guardian;
+        let proposal_id = if is_guardian {
+            None
+        } else {
+            let proposal = ctx
+                .accounts
+                .proposal
+                .as_mut()
+                .ok_or(DaoError::UnauthorizedEmergencyCancel)?;
+            require!(
+                proposal.authorizes_emergency_cancel(queued_at, clock.unix_timestamp),
+                DaoError::UnauthorizedEmergencyCancel
+            );
+            proposal.status = ProposalStatus::Executed;
+            proposal.executed_at = Some(clock.unix_timestamp);
+            proposal.executed_by = canceller;
+            Some(proposal.proposal_id)
+        };
+
+        dao_config.pending_config_change = None;
+
+        msg!(
+            "Pending config update (queued at {}) emergency-cancelled by {}",
+            queued_at,
+            canceller
+        );
+
+        emit!(ConfigUpdateEmergencyCancelledEvent {
+            cancelled_by: canceller,
+            proposal_id,
+            queued_at,
+            objection_total,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lock tokens as an objection against the pending config change
+    ///
+    /// Only open while veto_threshold_bps is non-zero and before the change's
+    /// execute_after. Once objections exceed the threshold share of supply
+    /// the change is vetoed and can never be executed. `queued_at` names the
+    /// pending change being objected to.
+    pub fn object_to_config_update(
+        ctx: Context<ObjectToConfigUpdate>,
+        queued_at: i64,
+        amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let supply = ctx.accounts.governance_token_mint.supply;
+        let veto_threshold_bps = ctx.accounts.dao_config.veto_threshold_bps;
+
+        require!(amount > 0, DaoError::InvalidAmount);
+        require!(veto_threshold_bps > 0, DaoError::VetoDisabled);
+        {
+            let pending = ctx
+                .accounts
+                .dao_config
+                .pending_config_change
+                .as_ref()
+                .ok_or(DaoError::NoPendingConfigChange)?;
+            require!(
+                pending.queued_at == queued_at,
+                DaoError::PendingConfigChangeMismatch
+            );
+            require!(
+                clock.unix_timestamp < pending.execute_after,
+                DaoError::ObjectionWindowClosed
+            );
+        }
+
+        // Objection tokens are escrowed in the vote vault until the change is resolved
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.objector_token_account.to_account_info(),
+            to: ctx.accounts.vote_vault.to_account_info(),
+            authority: ctx.accounts.objector.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let pending = ctx
+            .accounts
+            .dao_config
+            .pending_config_change
+            .as_mut()
+            .ok_or(DaoError::NoPendingConfigChange)?;
+        pending.record_objection(amount, supply, veto_threshold_bps)?;
+        let objection_total = pending.objection_total;
+        let vetoed = pending.vetoed;
+
+        let config_objection = &mut ctx.accounts.config_objection;
+        config_objection.objector = ctx.accounts.objector.key();
+        config_objection.queued_at = queued_at;
+        config_objection.amount = amount;
+        config_objection.bump = ctx.bumps.config_objection;
+
+        msg!(
+            "Objection to config update {}: objector={}, amount={}, total={}, vetoed={}",
+            queued_at,
+            config_objection.objector,
+            amount,
+            objection_total,
+            vetoed
+        );
+
+        emit!(ConfigObjectionEvent {
+            objector: config_objection.objector,
+            queued_at,
+            amount,
+            objection_total,
+            vetoed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw objection tokens once the objected change is resolved
+    ///
+    /// Tokens stay locked while the change is still pending and not vetoed;
+    /// a veto is final, so withdrawing afterwards doesn't reopen execution.
+    pub fn withdraw_objection(ctx: Context<WithdrawObjection>) -> Result<()> {
+        let dao_config = &ctx.accounts.dao_config;
+        let config_objection = &ctx.accounts.config_objection;
+
+        let still_contested = dao_config
+            .pending_config_change
+            .as_ref()
+            .is_some_and(|p| p.queued_at == config_objection.queued_at && !p.vetoed);
+        require!(!still_contested, DaoError::ObjectionLocked);
+
+        let amount = config_objection.amount;
+
+        let dao_bump = dao_config.bump;
+        let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vote_vault.to_account_info(),
+            to: ctx.accounts.objector_token_account.to_account_info(),
+            authority: ctx.accounts.dao_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        // ConfigObjection is closed via `close = objector`
+        msg!(
+            "Objection withdrawn: objector={}, amount={}",
+            config_objection.objector,
+            amount
+        );
+
+        emit!(ObjectionWithdrawnEvent {
+            objector: config_objection.objector,
+            queued_at: config_objection.queued_at,
+            amount,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -318,13 +785,21 @@ pub mod dao {
     }
 
     /// Create a new proposal
-    pub fn create_proposal(
-        ctx: Context<CreateProposal>,
-        title: String,
-        description_cid: String,
-        proposal_type: ProposalType,
-        execution_data: Option<ExecutionData>,
-    ) -> Result<()> {
+    ///
+    /// With `self_funded` the proposer posts the full bond and the discussion
+    /// period starts immediately. Otherwise the proposal is Pending until
+    /// sponsors cover the bond through `sponsor_proposal` within
+    /// SPONSORSHIP_FUNDING_WINDOW; the proposer still pays the creation fee.
+    pub fn create_proposal(ctx: Context<CreateProposal>, params: CreateProposalParams) -> Result<()> {
+        let CreateProposalParams {
+            title,
+            description_cid,
+            proposal_type,
+            execution_data,
+            tags,
+            self_funded,
+            urgency,
+        } = params;
         let dao_config = &mut ctx.accounts.dao_config;
         let clock = Clock::get()?;
 
@@ -340,16 +815,86 @@ pub mod dao {
             !description_cid.is_empty() && description_cid.len() <= MAX_DESCRIPTION_CID_LENGTH,
             DaoError::InvalidDescriptionCidLength
         );
+        validate_proposal_tags(&tags)?;
 
-        // Transfer proposal bond from proposer to bond escrow (PDA)
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.proposer_token_account.to_account_info(),
-            to: ctx.accounts.bond_escrow.to_account_info(),
-            authority: ctx.accounts.proposer.to_account_info(),
+        // Emergency cancels only make sense against a queued config change,
+        // and can't wait on sponsors inside the 48-hour config timelock
+        if proposal_type == ProposalType::EmergencyConfigCancel {
+            require!(
+                dao_config.pending_config_change.is_some(),
+                DaoError::NoPendingConfigChange
+            );
+            require!(self_funded, DaoError::EmergencyProposalNotSelfFunded);
+        }
+
+        // Emergency proposals skip discussion, so they cost more and need more support
+        let proposal_bond = urgency.bond(dao_config.proposal_bond)?;
+        let discussion_period = urgency.discussion_period(dao_config.discussion_period);
+
+        // Cap treasury withdrawals at max_withdrawal_bps of the current balance
+        let treasury_balance = ctx.accounts.treasury.amount;
+        let max_withdrawal_bps = dao_config.max_withdrawal_bps;
+        let withdrawal_cap = withdrawal_cap(treasury_balance, max_withdrawal_bps)?;
+        if let Some(data) = &execution_data {
+            data.validate(proposal_type)?;
+            if let ExecutionData::TreasuryWithdrawal { payouts } = data {
+                require!(
+                    payout_total(payouts)? <= withdrawal_cap,
+                    DaoError::WithdrawalExceedsCap
+                );
+            }
+        }
+        // A parameter change is applied by execute_proposal, so it must say what to change
+        require!(
+            proposal_type != ProposalType::ParameterChange || execution_data.is_some(),
+            DaoError::NoExecutionData
+        );
+
+        // Transfer proposal bond from proposer to bond escrow (PDA);
+        // sponsored proposals collect it through sponsor_proposal instead
+        if self_funded {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.proposer_token_account.to_account_info(),
+                to: ctx.accounts.bond_escrow.to_account_info(),
+                authority: ctx.accounts.proposer.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token::transfer(cpi_ctx, proposal_bond)?;
+        }
+
+        // Collect the non-refundable creation fee through the token program,
+        // which burns fee_burn_bps of it and routes the rest to the treasury
+        let proposal_fee = dao_config.proposal_fee;
+        let (fee_burned, fee_to_treasury) = if proposal_fee > 0 {
+            let burn_bps = ctx.accounts.token_config.fee_burn_bps;
+            let fee_burned = (proposal_fee as u128)
+                .checked_mul(burn_bps as u128)
+                .ok_or(DaoError::Overflow)?
+                .checked_div(10000)
+                .ok_or(DaoError::Overflow)? as u64;
+            let fee_to_treasury = proposal_fee
+                .checked_sub(fee_burned)
+                .ok_or(DaoError::Overflow)?;
+
+            let cpi_accounts = aegis_token::cpi::accounts::ProcessFeeWithBurn {
+                token_config: ctx.accounts.token_config.to_account_info(),
+                mint: ctx.accounts.governance_token_mint.to_account_info(),
+                fee_payer_account: ctx.accounts.proposer_token_account.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                fee_payer: ctx.accounts.proposer.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.aegis_token_program.to_account_info(),
+                cpi_accounts,
+            );
+            aegis_token::cpi::process_fee_with_burn(cpi_ctx, proposal_fee)?;
+
+            (fee_burned, fee_to_treasury)
+        } else {
+            (0, 0)
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, dao_config.proposal_bond)?;
 
         // Increment proposal count
         dao_config.proposal_count = dao_config
@@ -375,16 +920,64 @@ pub mod dao {
         proposal.for_votes = 0;
         proposal.against_votes = 0;
         proposal.abstain_votes = 0;
-        // Per whitepaper: voting starts after discussion period
-        proposal.vote_start = clock.unix_timestamp + dao_config.discussion_period;
-        proposal.vote_end = proposal.vote_start + dao_config.voting_period;
-        // Per whitepaper: 3-day execution timelock after voting ends
-        proposal.execution_eligible_at = proposal.vote_end + EXECUTION_TIMELOCK;
+        if !self_funded {
+            // Timeline is set by sponsor_proposal once the bond is covered
+            proposal.status = ProposalStatus::Pending;
+            proposal.vote_start = 0;
+            proposal.vote_end = 0;
+            proposal.execution_eligible_at = 0;
+            proposal.discussion_period = discussion_period;
+            proposal.voting_period = dao_config.voting_period;
+        } else if proposal_type == ProposalType::EmergencyConfigCancel {
+            // Must conclude before the 48-hour config timelock runs out
+            proposal.discussion_period = 0;
+            proposal.voting_period = EMERGENCY_VOTING_PERIOD;
+            proposal.vote_start = clock.unix_timestamp;
+            proposal.vote_end = proposal.vote_start + EMERGENCY_VOTING_PERIOD;
+            proposal.execution_eligible_at = proposal.vote_end;
+        } else {
+            // Per whitepaper: voting starts after discussion period
+            // (immediately for Emergency urgency)
+            proposal.discussion_period = discussion_period;
+            proposal.voting_period = dao_config.voting_period;
+            proposal.vote_start = clock.unix_timestamp + discussion_period;
+            proposal.vote_end = proposal.vote_start + dao_config.voting_period;
+            // Per whitepaper: 3-day execution timelock after voting ends
+            proposal.execution_eligible_at = proposal.vote_end + EXECUTION_TIMELOCK;
+        }
         proposal.created_at = clock.unix_timestamp;
         proposal.executed_at = None;
+        proposal.finalized_by = Pubkey::default();
+        proposal.finalized_at = 0;
+        proposal.executed_by = Pubkey::default();
+        proposal.last_checkpoint_at = 0;
         proposal.bond_returned = false;
         proposal.snapshot_supply = snapshot_supply;
+        proposal.quorum_percentage = dao_config.effective_quorum_percentage();
+        proposal.treasury_balance_snapshot = treasury_balance;
+        proposal.max_withdrawal_bps = max_withdrawal_bps;
+        proposal.tags = tags.clone();
+        proposal.appeal_proposal_id = None;
         proposal.bump = ctx.bumps.proposal;
+        proposal.sponsored = !self_funded;
+        if self_funded {
+            proposal.bond_target = 0;
+            proposal.funding_deadline = 0;
+        } else {
+            proposal.bond_target = proposal_bond;
+            proposal.funding_deadline = clock
+                .unix_timestamp
+                .checked_add(SPONSORSHIP_FUNDING_WINDOW)
+                .ok_or(DaoError::Overflow)?;
+        }
+        proposal.bond_raised = 0;
+        proposal.voting_mode = dao_config.voting_mode_for(proposal_type);
+        proposal.voting_model = dao_config.voting_model;
+        proposal.approval_threshold = urgency.approval_threshold(dao_config.approval_threshold);
+        proposal.bond_amount = proposal_bond;
+        proposal.execution_window = EXECUTION_WINDOW;
+        proposal.urgency = urgency;
+        proposal.conviction_schedule = dao_config.conviction_schedule.clone();
 
         msg!(
             "Proposal {} created: '{}' by {} | Discussion ends: {}, Voting ends: {}, Executable: {}",
@@ -405,47 +998,64 @@ pub mod dao {
             vote_end: proposal.vote_end,
             execution_eligible_at: proposal.execution_eligible_at,
             snapshot_supply,
+            quorum_percentage: proposal.quorum_percentage,
+            proposal_fee,
+            fee_burned,
+            fee_to_treasury,
+            treasury_balance,
+            withdrawal_cap,
+            tags,
+            self_funded,
+            funding_deadline: proposal.funding_deadline,
+            voting_mode: proposal.voting_mode,
+            urgency,
             timestamp: clock.unix_timestamp,
         });
+        emit!(proposal.manifest(clock.unix_timestamp)?);
 
         Ok(())
     }
 
     /// Cancel a proposal (proposer only, before voting ends)
+    ///
+    /// Sponsors of a cancelled sponsored proposal reclaim their full
+    /// contributions through `claim_sponsor_refund`.
     pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
         let dao_config = &ctx.accounts.dao_config;
         let clock = Clock::get()?;
 
-        // Check proposal is still active
+        // Check proposal is still active (or still raising its bond)
         require!(
-            proposal.status == ProposalStatus::Active,
+            proposal.status == ProposalStatus::Active || proposal.status == ProposalStatus::Pending,
             DaoError::ProposalNotActive
         );
 
         // Check voting hasn't ended (allow cancellation during voting)
         require!(
-            clock.unix_timestamp <= proposal.vote_end,
+            proposal.status == ProposalStatus::Pending || clock.unix_timestamp <= proposal.vote_end,
             DaoError::VotingEnded
         );
 
-        // Return bond to proposer
-        let dao_bump = dao_config.bump;
-        let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
-        let signer = &[&seeds[..]];
+        if !proposal.sponsored {
+            // Return bond to proposer
+            let dao_bump = dao_config.bump;
+            let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
+            let signer = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.bond_escrow.to_account_info(),
-            to: ctx.accounts.proposer_token_account.to_account_info(),
-            authority: ctx.accounts.dao_config.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, dao_config.proposal_bond)?;
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.bond_escrow.to_account_info(),
+                to: ctx.accounts.proposer_token_account.to_account_info(),
+                authority: ctx.accounts.dao_config.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, proposal.bond_amount)?;
+            proposal.bond_returned = true;
+        }
 
         // Mark as cancelled
         proposal.status = ProposalStatus::Cancelled;
-        proposal.bond_returned = true;
 
         msg!("Proposal {} cancelled by proposer", proposal.proposal_id);
 
@@ -469,6 +1079,9 @@ pub mod dao {
     /// Tokens are locked until EITHER:
     /// - The proposal's vote_end time has passed, OR
     /// - The voter retracts their vote (which removes their vote weight)
+    ///
+    /// Deposits are accepted from proposal creation (including the discussion
+    /// period) so early depositors can earn the conviction multiplier.
     pub fn deposit_vote_tokens(
         ctx: Context<DepositVoteTokens>,
         amount: u64,
@@ -484,9 +1097,9 @@ pub mod dao {
             DaoError::ProposalNotActive
         );
 
-        // Check within voting period
+        // Check voting period hasn't ended
         require!(
-            clock.unix_timestamp >= proposal.vote_start && clock.unix_timestamp <= proposal.vote_end,
+            clock.unix_timestamp <= proposal.vote_end,
             DaoError::VotingNotActive
         );
 
@@ -511,6 +1124,7 @@ pub mod dao {
         vote_escrow.vote_choice = None;
         vote_escrow.withdrawn = false;
         vote_escrow.bump = ctx.bumps.vote_escrow;
+        vote_escrow.vote_nonce = 0;
 
         msg!(
             "Vote tokens deposited for proposal {}: voter={}, amount={}",
@@ -533,9 +1147,30 @@ pub mod dao {
     ///
     /// Vote weight is determined by the tokens locked in the vote escrow,
     /// not by current wallet balance. This prevents flash loan attacks.
+    /// The escrowed amount is boosted by the conviction multiplier for how
+    /// early the tokens were deposited.
+    ///
+    /// Snapshot-mode proposals (General only, see `VotingMode`) skip the
+    /// escrow: the weight is the voter's current token balance, capped at
+    /// max_snapshot_vote_bps of the snapshot supply. Pass `voter_token_account`
+    /// instead of `vote_escrow`. The VoteRecord PDA still allows one vote per
+    /// voter per proposal; snapshot votes can't be retracted.
+    ///
+    /// A delegate may also pass a `delegation` to them together with the
+    /// delegator's escrow on this proposal or the delegator's standing
+    /// `delegator_position` (Escrow mode only) to add the delegator's boosted
+    /// weight; `vote_escrow` may then be omitted to vote with the delegated
+    /// weight alone. The delegator's escrow is marked as voted, or the
+    /// proposal added to the delegator's position locks, so the delegator
+    /// can't vote with those tokens and they stay locked until voting ends or
+    /// the delegate retracts.
+    ///
+    /// Instead of `vote_escrow`, an Escrow-mode vote can use the voter's
+    /// `voter_position`: its boosted balance is counted and the proposal is
+    /// added to the position's locks (see `withdraw_from_position`). Passing
+    /// both is rejected.
     pub fn cast_vote(ctx: Context<CastVote>, vote_choice: VoteChoice) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        let vote_escrow = &mut ctx.accounts.vote_escrow;
         let clock = Clock::get()?;
 
         // Check proposal is active
@@ -554,13 +1189,54 @@ pub mod dao {
             DaoError::VotingEnded
         );
 
-        // Check voter hasn't already voted (escrow-based double-vote prevention)
-        require!(!vote_escrow.has_voted, DaoError::AlreadyVoted);
+        let delegated = match (
+            ctx.accounts.delegation.as_deref(),
+            ctx.accounts.delegator_escrow.as_deref(),
+            ctx.accounts.delegator_position.as_deref(),
+        ) {
+            (Some(_), Some(_), Some(_)) => return err!(DaoError::AmbiguousVoteSource),
+            (Some(delegation), Some(delegator_escrow), None) => Some(delegated_vote_weight(
+                proposal,
+                &ctx.accounts.dao_config,
+                delegation,
+                delegator_escrow,
+                ctx.accounts.voter.key(),
+            )?),
+            (Some(delegation), None, Some(delegator_position)) => Some(delegated_position_weight(
+                proposal,
+                delegation,
+                delegator_position,
+                ctx.accounts.voter.key(),
+            )?),
+            (None, None, None) => None,
+            _ => return err!(DaoError::DelegatorEscrowRequired),
+        };
 
-        // SECURITY FIX: Get vote weight from ESCROWED tokens (prevents flash loan attacks!)
-        // The voter had to lock these tokens before voting, so they can't borrow and return
-        let vote_weight = vote_escrow.deposited_amount;
-        require!(vote_weight > 0, DaoError::NoVotingPower);
+        // A delegate without an escrow of their own votes with the delegated weight alone
+        let voter_balance = ctx.accounts.voter_token_account.as_ref().map(|a| a.amount);
+        let use_position = proposal.voting_mode == VotingMode::Escrow && ctx.accounts.voter_position.is_some();
+        let cast = match (delegated, ctx.accounts.vote_escrow.as_deref(), ctx.accounts.voter_position.as_deref()) {
+            (_, Some(_), Some(_)) => return err!(DaoError::AmbiguousVoteSource),
+            (_, None, Some(voter_position)) if use_position => {
+                position_vote_weight(proposal, voter_position)?
+            }
+            (Some(delegated), None, _) => CastWeight {
+                vote_weight: 0,
+                ..delegated
+            },
+            (_, vote_escrow, _) => resolve_vote_weight(proposal, &ctx.accounts.dao_config, vote_escrow, voter_balance)?,
+        };
+        let delegated_weight = delegated.map_or(0, |d| d.vote_weight);
+        let vote_weight = cast
+            .vote_weight
+            .checked_add(delegated_weight)
+            .ok_or(DaoError::Overflow)?;
+        let multiplier_bps = cast.multiplier_bps;
+        let delegator = ctx
+            .accounts
+            .delegation
+            .as_ref()
+            .map_or(Pubkey::default(), |d| d.delegator);
 
         // Initialize vote record
         let vote_record = &mut ctx.accounts.vote_record;
@@ -570,38 +1246,41 @@ pub mod dao {
         vote_record.vote_weight = vote_weight;
         vote_record.voted_at = clock.unix_timestamp;
         vote_record.bump = ctx.bumps.vote_record;
+        vote_record.multiplier_bps = multiplier_bps;
+        vote_record.snapshot_balance = cast.snapshot_balance;
+        vote_record.delegator = delegator;
+        vote_record.delegated_weight = delegated_weight;
+        vote_record.delegated_from_position = ctx.accounts.delegator_position.is_some();
 
         // Mark escrow as used and record vote choice
-        vote_escrow.has_voted = true;
-        vote_escrow.vote_choice = Some(vote_choice);
-
-        // Update proposal vote counts
-        match vote_choice {
-            VoteChoice::For => {
-                proposal.for_votes = proposal
-                    .for_votes
-                    .checked_add(vote_weight)
-                    .ok_or(DaoError::Overflow)?;
+        if proposal.voting_mode == VotingMode::Escrow {
+            if let Some(vote_escrow) = ctx.accounts.vote_escrow.as_mut() {
+                vote_escrow.has_voted = true;
+                vote_escrow.vote_choice = Some(vote_choice);
             }
-            VoteChoice::Against => {
-                proposal.against_votes = proposal
-                    .against_votes
-                    .checked_add(vote_weight)
-                    .ok_or(DaoError::Overflow)?;
+            // The delegator's tokens are spent on this proposal too
+            if let Some(delegator_escrow) = ctx.accounts.delegator_escrow.as_mut() {
+                delegator_escrow.has_voted = true;
+                delegator_escrow.vote_choice = Some(vote_choice);
             }
-            VoteChoice::Abstain => {
-                proposal.abstain_votes = proposal
-                    .abstain_votes
-                    .checked_add(vote_weight)
-                    .ok_or(DaoError::Overflow)?;
+            if let Some(delegator_position) = ctx.accounts.delegator_position.as_mut() {
+                delegator_position.record_vote(proposal.proposal_id, proposal.vote_end, clock.unix_timestamp)?;
+            }
+            // The position stays locked until this proposal's voting ends
+            if let Some(voter_position) = ctx.accounts.voter_position.as_mut() {
+                voter_position.record_vote(proposal.proposal_id, proposal.vote_end, clock.unix_timestamp)?;
             }
         }
 
+        // Update proposal vote counts
+        proposal.count_vote(vote_choice, vote_weight)?;
+
         msg!(
-            "Vote cast on proposal {}: {:?} with weight {} by {}",
+            "Vote cast on proposal {}: {:?} with weight {} ({}bps) by {}",
             proposal.proposal_id,
             vote_choice,
             vote_weight,
+            multiplier_bps,
             vote_record.voter
         );
 
@@ -610,22 +1289,140 @@ pub mod dao {
             voter: vote_record.voter,
             vote_choice,
             vote_weight,
+            multiplier_bps,
+            voting_mode: proposal.voting_mode,
+            snapshot_balance: cast.snapshot_balance,
             timestamp: clock.unix_timestamp,
+            delegator,
+            delegated_weight,
         });
 
         Ok(())
     }
 
-    /// SECURITY FIX: Retract a vote and allow token withdrawal
+    /// Cast a vote signed off-chain by the voter and submitted by a relayer
     ///
-    /// This allows voters to change their mind before the voting period ends.
-    /// When a vote is retracted:
-    /// 1. The vote weight is removed from the proposal's vote counts
+    /// The voter signs `signed_vote_message` (voter, proposal, choice, nonce
+    /// and expiry) with their wallet key. The relayer places an Ed25519
+    /// program instruction verifying that signature immediately before this
+    /// one, as record_performance in the rewards program does, and pays the
+    /// vote_record's rent. Only the voter's own VoteEscrow on an Escrow-mode
+    /// proposal is counted; delegated and position votes go through
+    /// cast_vote.
+    ///
+    /// `nonce` must exceed the escrow's `vote_nonce`, so a message can't be
+    /// replayed after the vote is retracted, and expires at `expires_at`.
+    /// The vote is otherwise the voter's: only they can retract it, and the
+    /// record's rent is refunded to them when they do.
+    pub fn cast_vote_by_signature(
+        ctx: Context<CastVoteBySignature>,
+        voter: Pubkey,
+        vote_choice: VoteChoice,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        require!(
+            proposal.status == ProposalStatus::Active,
+            DaoError::ProposalNotActive
+        );
+        require!(
+            clock.unix_timestamp >= proposal.vote_start,
+            DaoError::VotingNotStarted
+        );
+        require!(
+            clock.unix_timestamp <= proposal.vote_end,
+            DaoError::VotingEnded
+        );
+        require!(
+            proposal.voting_mode == VotingMode::Escrow,
+            DaoError::SignedVoteRequiresEscrow
+        );
+
+        // The Ed25519 program verifies the signature; check it signed this vote
+        let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+        let current_index =
+            anchor_lang::solana_program::sysvar::instructions::load_current_index_checked(&instructions_sysvar)?;
+        require!(current_index > 0, DaoError::InvalidVoteSignature);
+        let ed25519_ix = anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked(
+            usize::from(current_index - 1),
+            &instructions_sysvar,
+        )?;
+        let message = signed_vote_message(&voter, proposal.proposal_id, vote_choice, nonce, expires_at);
+        verify_ed25519_instruction(&ed25519_ix, &voter, &message)?;
+
+        let vote_escrow = &mut ctx.accounts.vote_escrow;
+        let cast = resolve_vote_weight(proposal, &ctx.accounts.dao_config, Some(vote_escrow), None)?;
+        vote_escrow.accept_signed_vote(nonce, expires_at, clock.unix_timestamp)?;
+        vote_escrow.has_voted = true;
+        vote_escrow.vote_choice = Some(vote_choice);
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.proposal_id = proposal.proposal_id;
+        vote_record.voter = voter;
+        vote_record.vote_choice = vote_choice;
+        vote_record.vote_weight = cast.vote_weight;
+        vote_record.voted_at = clock.unix_timestamp;
+        vote_record.bump = ctx.bumps.vote_record;
+        vote_record.multiplier_bps = cast.multiplier_bps;
+        vote_record.snapshot_balance = 0;
+        vote_record.delegator = Pubkey::default();
+        vote_record.delegated_weight = 0;
+        vote_record.delegated_from_position = false;
+
+        proposal.count_vote(vote_choice, cast.vote_weight)?;
+
+        msg!(
+            "Signed vote relayed on proposal {}: {:?} with weight {} ({}bps) by {} via {}",
+            proposal.proposal_id,
+            vote_choice,
+            cast.vote_weight,
+            cast.multiplier_bps,
+            voter,
+            ctx.accounts.relayer.key()
+        );
+
+        emit!(VoteCastEvent {
+            proposal_id: proposal.proposal_id,
+            voter,
+            vote_choice,
+            vote_weight: cast.vote_weight,
+            multiplier_bps: cast.multiplier_bps,
+            voting_mode: proposal.voting_mode,
+            snapshot_balance: 0,
+            timestamp: clock.unix_timestamp,
+            delegator: Pubkey::default(),
+            delegated_weight: 0,
+        });
+
+        emit!(SignedVoteRelayedEvent {
+            proposal_id: proposal.proposal_id,
+            voter,
+            relayer: ctx.accounts.relayer.key(),
+            nonce,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// SECURITY FIX: Retract a vote and allow token withdrawal
+    ///
+    /// This allows voters to change their mind before the voting period ends.
+    /// When a vote is retracted:
+    /// 1. The vote weight is removed from the proposal's vote counts
     /// 2. The vote_escrow is updated to allow withdrawal
     /// 3. The vote_record is closed (rent returned to voter)
+    ///
+    /// A delegate retracting a delegated vote passes the delegator's escrow,
+    /// which is released as well; `vote_escrow` is only needed when the vote
+    /// carried weight of the voter's own. A vote cast with the voter's
+    /// position passes `voter_position` instead, releasing its lock on the
+    /// proposal.
     pub fn retract_vote(ctx: Context<RetractVote>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        let vote_escrow = &mut ctx.accounts.vote_escrow;
         let vote_record = &ctx.accounts.vote_record;
         let clock = Clock::get()?;
 
@@ -641,49 +1438,32 @@ pub mod dao {
             DaoError::VotingEnded
         );
 
-        // Check the voter has actually voted
-        require!(vote_escrow.has_voted, DaoError::NotVoted);
+        // Check the voter has actually voted, and unlock the escrows the vote used
+        release_vote_escrows(
+            vote_record,
+            ctx.accounts.vote_escrow.as_deref_mut(),
+            ctx.accounts.voter_position.as_deref_mut(),
+            ctx.accounts.delegator_escrow.as_deref_mut(),
+            ctx.accounts.delegator_position.as_deref_mut(),
+        )?;
 
-        // Get the vote weight and choice from the escrow
-        let vote_weight = vote_escrow.deposited_amount;
-        let vote_choice = vote_escrow.vote_choice.ok_or(DaoError::NotVoted)?;
+        // Remove exactly the (boosted) weight that was counted
+        let vote_weight = vote_record.vote_weight;
+        let vote_choice = vote_record.vote_choice;
 
         // Decrement proposal vote counts
-        match vote_choice {
-            VoteChoice::For => {
-                proposal.for_votes = proposal
-                    .for_votes
-                    .checked_sub(vote_weight)
-                    .ok_or(DaoError::Underflow)?;
-            }
-            VoteChoice::Against => {
-                proposal.against_votes = proposal
-                    .against_votes
-                    .checked_sub(vote_weight)
-                    .ok_or(DaoError::Underflow)?;
-            }
-            VoteChoice::Abstain => {
-                proposal.abstain_votes = proposal
-                    .abstain_votes
-                    .checked_sub(vote_weight)
-                    .ok_or(DaoError::Underflow)?;
-            }
-        }
-
-        // Mark escrow as not voted (allows re-voting or withdrawal)
-        vote_escrow.has_voted = false;
-        vote_escrow.vote_choice = None;
+        proposal.uncount_vote(vote_choice, vote_weight)?;
 
         msg!(
             "Vote retracted on proposal {}: weight {} by {}",
             proposal.proposal_id,
             vote_weight,
-            vote_escrow.voter
+            vote_record.voter
         );
 
         emit!(VoteRetractedEvent {
             proposal_id: proposal.proposal_id,
-            voter: vote_escrow.voter,
+            voter: vote_record.voter,
             vote_weight,
             timestamp: clock.unix_timestamp,
         });
@@ -697,6 +1477,9 @@ pub mod dao {
     /// 1. The proposal's voting period has ended (vote_end has passed), OR
     /// 2. The voter has NOT voted (or has retracted their vote)
     ///
+    /// An escrow a delegate voted with counts as voted, so it stays locked
+    /// while the delegated vote is counted.
+    ///
     /// This prevents voters from:
     /// - Voting with borrowed tokens and returning them before the vote counts
     /// - Double voting by transferring tokens between wallets
@@ -720,6 +1503,7 @@ pub mod dao {
             DaoError::TokensLockedDuringVoting
         );
 
+        // Only the principal is returned; the conviction boost is vote weight only
         let amount = vote_escrow.deposited_amount;
 
         // Transfer tokens back to voter from vote vault
@@ -756,10 +1540,267 @@ pub mod dao {
         Ok(())
     }
 
+    /// Delegate escrowed voting power to another wallet
+    ///
+    /// The delegate can then vote with the delegator's escrow on any proposal
+    /// created at or after this point (see `cast_vote`). Proposals already
+    /// open keep the delegation state they were created under.
+    pub fn delegate_votes(ctx: Context<DelegateVotes>, delegate: Pubkey) -> Result<()> {
+        let delegator = ctx.accounts.delegator.key();
+        require!(delegate != delegator, DaoError::SelfDelegation);
+        let clock = Clock::get()?;
+
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.delegator = delegator;
+        delegation.delegate = delegate;
+        delegation.delegated_at = clock.unix_timestamp;
+        delegation.revoked_at = 0;
+        delegation.bump = ctx.bumps.delegation;
+
+        msg!("Votes delegated: delegator={}, delegate={}", delegator, delegate);
+
+        emit!(VoteDelegatedEvent {
+            delegator,
+            delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a delegation (delegator only)
+    ///
+    /// Takes effect for proposals created from now on; proposals created
+    /// while the delegation was active still accept it, so the account is
+    /// kept rather than closed.
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+        let clock = Clock::get()?;
+
+        require!(delegation.revoked_at == 0, DaoError::DelegationRevoked);
+        delegation.revoked_at = clock.unix_timestamp;
+
+        msg!(
+            "Delegation revoked: delegator={}, delegate={}",
+            delegation.delegator,
+            delegation.delegate
+        );
+
+        emit!(DelegationRevokedEvent {
+            delegator: delegation.delegator,
+            delegate: delegation.delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Deposit tokens into the voter's position, creating it if needed
+    ///
+    /// Unlike a VoteEscrow, which is tied to one proposal, the position's
+    /// balance votes on every proposal (see `cast_vote`), so overlapping
+    /// proposals don't need a deposit each. Top-ups are locked with the rest
+    /// of the position; for the conviction multiplier the position counts
+    /// as deposited at the latest top-up.
+    ///
+    /// The account uses `init_if_needed`: its PDA is derived from the
+    /// signing voter, and the voter and bump are only written while the
+    /// account is brand new (voter still the zero key, which can never sign).
+    pub fn deposit_to_position(ctx: Context<DepositToPosition>, amount: u64) -> Result<()> {
+        require!(amount > 0, DaoError::InvalidAmount);
+        let clock = Clock::get()?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.vote_vault.to_account_info(),
+            authority: ctx.accounts.voter.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let voter_position = &mut ctx.accounts.voter_position;
+        voter_position.init_if_new(ctx.accounts.voter.key(), ctx.bumps.voter_position);
+        voter_position.add_deposit(amount, clock.unix_timestamp)?;
+
+        msg!(
+            "Voter position deposit: voter={}, amount={}, total={}",
+            voter_position.voter,
+            amount,
+            voter_position.deposited_amount
+        );
+
+        emit!(VoterPositionDepositedEvent {
+            voter: voter_position.voter,
+            amount,
+            deposited_amount: voter_position.deposited_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw the whole voter position and close it
+    ///
+    /// Only once voting has ended on every proposal the position still has a
+    /// counted vote on; retracting a vote releases that proposal's lock.
+    pub fn withdraw_from_position(ctx: Context<WithdrawFromPosition>) -> Result<()> {
+        let voter_position = &ctx.accounts.voter_position;
+        let clock = Clock::get()?;
+
+        voter_position.ensure_unlocked(clock.unix_timestamp)?;
+        let amount = voter_position.deposited_amount;
+
+        let dao_bump = ctx.accounts.dao_config.bump;
+        let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vote_vault.to_account_info(),
+            to: ctx.accounts.voter_token_account.to_account_info(),
+            authority: ctx.accounts.dao_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        msg!(
+            "Voter position withdrawn: voter={}, amount={} (account closed, rent recovered)",
+            voter_position.voter,
+            amount
+        );
+
+        emit!(VoterPositionWithdrawnEvent {
+            voter: voter_position.voter,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move a per-proposal VoteEscrow into the voter's position
+    ///
+    /// Allowed whenever the escrow could be withdrawn (not voted, or voting
+    /// ended). The tokens already sit in the vote vault, so only the
+    /// bookkeeping moves: the escrow is closed and its principal added to
+    /// the position, keeping the escrow's deposit time for conviction.
+    /// Escrows that aren't migrated keep working as before.
+    pub fn migrate_escrow_to_position(ctx: Context<MigrateEscrowToPosition>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let vote_escrow = &ctx.accounts.vote_escrow;
+        let clock = Clock::get()?;
+
+        require!(!vote_escrow.withdrawn, DaoError::AlreadyWithdrawn);
+        require!(
+            clock.unix_timestamp > proposal.vote_end || !vote_escrow.has_voted,
+            DaoError::TokensLockedDuringVoting
+        );
+
+        let amount = vote_escrow.deposited_amount;
+        let voter_position = &mut ctx.accounts.voter_position;
+        voter_position.init_if_new(ctx.accounts.voter.key(), ctx.bumps.voter_position);
+        voter_position.add_deposit(amount, vote_escrow.deposited_at)?;
+
+        msg!(
+            "Vote escrow for proposal {} migrated to position: voter={}, amount={}",
+            proposal.proposal_id,
+            voter_position.voter,
+            amount
+        );
+
+        emit!(VoteEscrowMigratedEvent {
+            proposal_id: proposal.proposal_id,
+            voter: voter_position.voter,
+            amount,
+            deposited_amount: voter_position.deposited_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grow a VoteEscrow created before vote_nonce existed to the current
+    /// layout (permissionless; the payer covers the extra rent)
+    ///
+    /// Older escrows are too short to decode, which blocks voting, retracting
+    /// and withdrawing them until they are resized. The new vote_nonce starts
+    /// at zero. No-op for escrows already at the current size.
+    pub fn resize_vote_escrow(
+        ctx: Context<ResizeVoteEscrow>,
+        proposal_id: u64,
+        voter: Pubkey,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let info = ctx.accounts.vote_escrow.to_account_info();
+        let original_len = info.data_len();
+
+        if original_len >= VoteEscrow::MAX_SIZE {
+            msg!("Vote escrow already at the current layout ({} bytes)", original_len);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            VoteEscrow::MAX_SIZE,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        // Appended fields decode as zero, which is vote_nonce's starting value
+        let vote_escrow = VoteEscrow::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(vote_escrow.voter == voter, DaoError::InvalidVoter);
+        require!(vote_escrow.proposal_id == proposal_id, DaoError::InvalidProposal);
+
+        msg!(
+            "Vote escrow for proposal {} resized: voter={}, {} -> {} bytes",
+            proposal_id,
+            voter,
+            original_len,
+            VoteEscrow::MAX_SIZE
+        );
+
+        emit!(VoteEscrowResizedEvent {
+            proposal_id,
+            voter,
+            from_len: original_len as u32,
+            to_len: VoteEscrow::MAX_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Emit the current tallies of an active proposal (permissionless)
+    ///
+    /// Gives front-ends a consistent on-chain snapshot when indexers fall
+    /// behind. Rate-limited to once per CHECKPOINT_INTERVAL per proposal;
+    /// tallies are not modified.
+    pub fn checkpoint_proposal(ctx: Context<CheckpointProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        let event = proposal.checkpoint(clock.unix_timestamp)?;
+        msg!(
+            "Proposal {} checkpoint: {}% participation, {}s remaining",
+            event.proposal_id,
+            event.participation_pct,
+            event.time_remaining
+        );
+        emit!(event);
+
+        Ok(())
+    }
+
     /// Finalize a proposal after voting ends
+    ///
+    /// A passing treasury withdrawal reserves its amount in
+    /// `committed_outflow`. If the treasury can't cover it on top of the
+    /// withdrawals already committed, it finalizes as PassedUnfunded instead
+    /// of Passed, so it can never race an earlier approved withdrawal for the
+    /// same funds; `fund_passed_proposal` commits it once headroom frees up.
     pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        let dao_config = &ctx.accounts.dao_config;
+        let dao_config = &mut ctx.accounts.dao_config;
         let clock = Clock::get()?;
 
         // Check proposal is still active
@@ -782,41 +1823,24 @@ pub mod dao {
             .checked_add(proposal.abstain_votes)
             .ok_or(DaoError::Overflow)?;
 
-        // Use snapshot supply for quorum calculation (prevents manipulation)
-        let quorum_required = proposal
-            .snapshot_supply
-            .checked_mul(dao_config.quorum_percentage as u64)
-            .ok_or(DaoError::Overflow)?
-            .checked_div(100)
-            .ok_or(DaoError::Overflow)?;
-
         // Check quorum (total participation must meet threshold)
+        let quorum_required = proposal.quorum_required()?;
         let quorum_met = total_participation >= quorum_required;
-
-        // Calculate approval percentage (for votes / (for + against))
-        // Abstain votes don't count towards approval calculation
-        let votes_cast = proposal
-            .for_votes
-            .checked_add(proposal.against_votes)
-            .ok_or(DaoError::Overflow)?;
-
-        let approval_met = if votes_cast > 0 {
-            let approval_percentage = proposal
-                .for_votes
-                .checked_mul(100)
-                .ok_or(DaoError::Overflow)?
-                .checked_div(votes_cast)
-                .ok_or(DaoError::Overflow)?;
-
-            approval_percentage >= dao_config.approval_threshold as u64
-        } else {
-            false
-        };
+        let approval_met = proposal.approval_met()?;
 
         // Determine final status
         if quorum_met && approval_met {
-            proposal.status = ProposalStatus::Passed;
-            msg!("Proposal {} PASSED", proposal.proposal_id);
+            proposal.settle_passed(dao_config, ctx.accounts.treasury.amount)?;
+            if proposal.status == ProposalStatus::PassedUnfunded {
+                msg!(
+                    "Proposal {} PASSED but UNFUNDED (treasury {} already has {} committed)",
+                    proposal.proposal_id,
+                    ctx.accounts.treasury.amount,
+                    dao_config.committed_outflow
+                );
+            } else {
+                msg!("Proposal {} PASSED", proposal.proposal_id);
+            }
         } else if !quorum_met {
             proposal.status = ProposalStatus::Defeated;
             msg!(
@@ -833,6 +1857,29 @@ pub mod dao {
             );
         }
 
+        // Permissionless, so record who finalized it for auditability
+        proposal.finalized_by = ctx.accounts.finalizer.key();
+        proposal.finalized_at = clock.unix_timestamp;
+
+        // Fold this proposal's turnout into the rolling participation average
+        // (tracked even while adaptive quorum is disabled so enabling it has history)
+        let participation_bps = if proposal.snapshot_supply > 0 {
+            ((total_participation as u128)
+                .checked_mul(10000)
+                .ok_or(DaoError::Overflow)?
+                / proposal.vote_supply() as u128)
+                .min(10000) as u16
+        } else {
+            0
+        };
+        if proposal.snapshot_supply > 0 {
+            dao_config.avg_participation_bps = update_participation_average(
+                dao_config.avg_participation_bps,
+                participation_bps,
+                dao_config.adaptive_quorum.smoothing_bps,
+            );
+        }
+
         emit!(ProposalFinalizedEvent {
             proposal_id: proposal.proposal_id,
             status: proposal.status,
@@ -840,92 +1887,203 @@ pub mod dao {
             against_votes: proposal.against_votes,
             abstain_votes: proposal.abstain_votes,
             quorum_met,
+            participation_bps,
+            avg_participation_bps: dao_config.avg_participation_bps,
+            finalized_by: proposal.finalized_by,
+            committed_outflow: dao_config.committed_outflow,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Execute a passed proposal (for treasury withdrawals)
+    /// Execute a passed treasury withdrawal or parameter change
     /// Per whitepaper: 3-day execution timelock after voting ends
-    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    ///
+    /// Withdrawal recipients' token accounts are passed as writable
+    /// remaining_accounts in payout order; all payouts transfer or none do.
+    ///
+    /// Parameter changes are re-validated and written straight to DaoConfig;
+    /// they don't go through the queued config timelock because the vote and
+    /// execution timelock already gave holders the same notice.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
         let clock = Clock::get()?;
 
-        // Check proposal passed
-        require!(
-            proposal.status == ProposalStatus::Passed,
-            DaoError::ProposalNotPassed
-        );
+        let execution_data = proposal.executable_data(clock.unix_timestamp)?;
 
-        // Check not already executed
-        require!(
-            proposal.executed_at.is_none(),
-            DaoError::ProposalAlreadyExecuted
-        );
+        let proposal_id = proposal.proposal_id;
 
-        // Per whitepaper: Check 3-day execution timelock has elapsed
-        require!(
-            clock.unix_timestamp >= proposal.execution_eligible_at,
-            DaoError::ExecutionTimelockNotExpired
-        );
+        match execution_data {
+            ExecutionData::TreasuryWithdrawal { payouts } => {
+                // Recipient token accounts come as writable remaining_accounts,
+                // in payout order. Everything is checked before the first
+                // transfer, and any failed transfer fails the whole instruction.
+                let recipients = ctx.remaining_accounts;
+                let recipient_keys: Vec<Pubkey> = recipients.iter().map(|info| info.key()).collect();
+                check_payout_recipients(&payouts, &recipient_keys)?;
+                for info in recipients {
+                    require!(info.is_writable, DaoError::InvalidRecipient);
+                    require_keys_eq!(*info.owner, token::ID, DaoError::InvalidRecipient);
+                    let account = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+                    require!(
+                        account.mint == ctx.accounts.dao_config.governance_token_mint,
+                        DaoError::InvalidMint
+                    );
+                }
+
+                let amount = check_treasury_withdrawal(
+                    &payouts,
+                    ctx.accounts.treasury.amount,
+                    proposal.max_withdrawal_bps,
+                )?;
+
+                // Execute treasury withdrawal
+                let dao_bump = ctx.accounts.dao_config.bump;
+                let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
+                let signer = &[&seeds[..]];
+
+                for (index, (payout, info)) in payouts.iter().zip(recipients).enumerate() {
+                    let cpi_accounts = Transfer {
+                        from: ctx.accounts.treasury.to_account_info(),
+                        to: info.clone(),
+                        authority: ctx.accounts.dao_config.to_account_info(),
+                    };
+                    let cpi_program = ctx.accounts.token_program.to_account_info();
+                    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+                    token::transfer(cpi_ctx, payout.amount)?;
+
+                    emit!(ProposalExecutedEvent {
+                        proposal_id,
+                        executor: ctx.accounts.executor.key(),
+                        amount: payout.amount,
+                        recipient: payout.recipient,
+                        timestamp: clock.unix_timestamp,
+                        payout_index: index as u8,
+                        payout_count: payouts.len() as u8,
+                    });
+                }
+                ctx.accounts.dao_config.release_outflow(amount);
 
-        // Check proposal type allows execution
-        require!(
-            proposal.proposal_type == ProposalType::TreasuryWithdrawal,
-            DaoError::ProposalNotExecutable
-        );
+                msg!(
+                    "Proposal {} executed: {} tokens transferred to {} recipient(s)",
+                    proposal_id,
+                    amount,
+                    payouts.len()
+                );
+            }
+            ExecutionData::ParameterChange {
+                new_voting_period,
+                new_quorum,
+                new_threshold,
+                new_bond,
+            } => {
+                let dao_config = &mut ctx.accounts.dao_config;
+                dao_config.apply_parameter_change(
+                    new_voting_period,
+                    new_quorum,
+                    new_threshold,
+                    new_bond,
+                );
 
-        // Get execution data
-        let execution_data = proposal
-            .execution_data
-            .clone()
-            .ok_or(DaoError::NoExecutionData)?;
+                msg!("Proposal {} executed: DAO parameters updated", proposal_id);
+
+                emit!(ParameterChangeExecutedEvent {
+                    proposal_id,
+                    executor: ctx.accounts.executor.key(),
+                    voting_period: dao_config.voting_period,
+                    quorum_percentage: dao_config.quorum_percentage,
+                    approval_threshold: dao_config.approval_threshold,
+                    proposal_bond: dao_config.proposal_bond,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
 
-        // CRITICAL FIX: Validate recipient matches proposal's intended recipient
-        require!(
-            ctx.accounts.recipient.key() == execution_data.recipient,
-            DaoError::InvalidRecipient
+        // Mark as executed
+        proposal.status = ProposalStatus::Executed;
+        proposal.executed_at = Some(clock.unix_timestamp);
+        proposal.executed_by = ctx.accounts.executor.key();
+
+        Ok(())
+    }
+
+    /// Commit the treasury to a PassedUnfunded withdrawal (permissionless)
+    ///
+    /// Succeeds once executions, expiries, cancellations or deposits leave
+    /// enough uncommitted balance; the proposal becomes Passed and executes
+    /// on its original timelock.
+    pub fn fund_passed_proposal(ctx: Context<FundPassedProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let dao_config = &mut ctx.accounts.dao_config;
+        let clock = Clock::get()?;
+
+        let amount = proposal.fund(dao_config, ctx.accounts.treasury.amount, clock.unix_timestamp)?;
+
+        msg!(
+            "Proposal {} funded: {} committed ({} total)",
+            proposal.proposal_id,
+            amount,
+            dao_config.committed_outflow
         );
 
-        // Check treasury has sufficient balance
-        require!(
-            ctx.accounts.treasury.amount >= execution_data.amount,
-            DaoError::InsufficientTreasuryBalance
+        emit!(ProposalFundedEvent {
+            proposal_id: proposal.proposal_id,
+            amount,
+            committed_outflow: dao_config.committed_outflow,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Expire a passed withdrawal left unexecuted past EXECUTION_WINDOW
+    /// (permissionless), releasing its treasury commitment
+    pub fn expire_proposal(ctx: Context<ExpireProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let dao_config = &mut ctx.accounts.dao_config;
+        let clock = Clock::get()?;
+
+        let released = proposal.expire(dao_config, clock.unix_timestamp)?;
+
+        msg!(
+            "Proposal {} expired unexecuted, {} released",
+            proposal.proposal_id,
+            released
         );
 
-        let proposal_id = proposal.proposal_id;
+        emit!(ProposalExpiredEvent {
+            proposal_id: proposal.proposal_id,
+            released,
+            committed_outflow: dao_config.committed_outflow,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Execute treasury withdrawal
-        let dao_bump = ctx.accounts.dao_config.bump;
-        let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
-        let signer = &[&seeds[..]];
+        Ok(())
+    }
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.treasury.to_account_info(),
-            to: ctx.accounts.recipient.to_account_info(),
-            authority: ctx.accounts.dao_config.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, execution_data.amount)?;
+    /// Withdraw a passed, unexecuted treasury withdrawal (proposer only),
+    /// releasing its treasury commitment
+    ///
+    /// The bond stays returnable in full, as for any passed proposal.
+    pub fn cancel_passed_withdrawal(ctx: Context<CancelPassedWithdrawal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let dao_config = &mut ctx.accounts.dao_config;
+        let clock = Clock::get()?;
 
-        // Mark as executed
-        proposal.status = ProposalStatus::Executed;
-        proposal.executed_at = Some(clock.unix_timestamp);
+        let released = proposal.cancel_passed_withdrawal(dao_config)?;
 
         msg!(
-            "Proposal {} executed: {} tokens transferred to {}",
-            proposal_id,
-            execution_data.amount,
-            execution_data.recipient
+            "Passed proposal {} cancelled by proposer, {} released",
+            proposal.proposal_id,
+            released
         );
 
-        emit!(ProposalExecutedEvent {
-            proposal_id,
-            executor: ctx.accounts.executor.key(),
-            amount: execution_data.amount,
-            recipient: execution_data.recipient,
+        emit!(ProposalCancelledEvent {
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
             timestamp: clock.unix_timestamp,
         });
 
@@ -946,6 +2104,9 @@ pub mod dao {
         let proposal = &mut ctx.accounts.proposal;
         let dao_config = &ctx.accounts.dao_config;
 
+        // Sponsors are refunded individually
+        require!(!proposal.sponsored, DaoError::SponsoredBond);
+
         // Check proposal is finalized
         require!(
             proposal.status != ProposalStatus::Active,
@@ -956,54 +2117,7 @@ pub mod dao {
         require!(!proposal.bond_returned, DaoError::BondAlreadyReturned);
 
         // Y7.1: Calculate return amount based on proposal outcome and participation
-        let full_bond = dao_config.proposal_bond;
-        let return_amount: u64;
-        let return_type: &str;
-
-        if proposal.status == ProposalStatus::Passed || proposal.status == ProposalStatus::Executed {
-            // Full bond return for successful proposals
-            return_amount = full_bond;
-            return_type = "full";
-        } else if proposal.status == ProposalStatus::Defeated {
-            // Y7.1: Check if proposal achieved partial quorum for partial return
-            let total_votes = proposal.for_votes
-                .checked_add(proposal.against_votes)
-                .ok_or(DaoError::Overflow)?;
-
-            // Calculate what % of quorum was achieved
-            let quorum_required = dao_config.quorum_percentage as u64;
-            let quorum_achieved_percentage = if quorum_required > 0 {
-                (total_votes as u128)
-                    .checked_mul(100)
-                    .ok_or(DaoError::Overflow)?
-                    .checked_div(quorum_required as u128)
-                    .ok_or(DaoError::Overflow)? as u64
-            } else {
-                0
-            };
-
-            if quorum_achieved_percentage >= PARTIAL_BOND_QUORUM_THRESHOLD as u64 {
-                // Partial bond return (50%) for proposals with significant participation
-                return_amount = full_bond
-                    .checked_mul(PARTIAL_BOND_RETURN_PERCENTAGE)
-                    .ok_or(DaoError::Overflow)?
-                    .checked_div(100)
-                    .ok_or(DaoError::Overflow)?;
-                return_type = "partial";
-                msg!(
-                    "Y7.1: Partial bond return - proposal achieved {}% of quorum",
-                    quorum_achieved_percentage
-                );
-            } else {
-                // No bond return - insufficient participation
-                return Err(DaoError::BondForfeited.into());
-            }
-        } else {
-            // Cancelled or other status - no return
-            return Err(DaoError::BondForfeited.into());
-        }
-
-        require!(return_amount > 0, DaoError::BondForfeited);
+        let (return_amount, return_type) = proposal.bond_return(proposal.bond_amount)?;
 
         // Transfer bond (full or partial) back to proposer
         let dao_bump = dao_config.bump;
@@ -1053,9 +2167,8 @@ pub mod dao {
         ctx: Context<AppealProposal>,
         original_proposal_id: u64,
     ) -> Result<()> {
-        let original = &ctx.accounts.original_proposal;
-        let appeal = &mut ctx.accounts.appeal_proposal;
-        let dao_config = &ctx.accounts.dao_config;
+        let original = &mut ctx.accounts.original_proposal;
+        let dao_config = &mut ctx.accounts.dao_config;
         let clock = Clock::get()?;
 
         // Verify original proposal is defeated
@@ -1063,13 +2176,19 @@ pub mod dao {
             original.status == ProposalStatus::Defeated,
             DaoError::CannotAppealNonDefeated
         );
+        require!(original.appeal_proposal_id.is_none(), DaoError::AlreadyAppealed);
+        // An appeal still under the legacy ["appeal", id] seeds must be migrated instead
+        require!(
+            ctx.accounts.legacy_appeal.data_is_empty(),
+            DaoError::LegacyAppealSeeds
+        );
 
         // Y7.2: Check proposal achieved minimum participation for appeal eligibility
         let total_votes = original.for_votes
             .checked_add(original.against_votes)
             .ok_or(DaoError::Overflow)?;
 
-        let quorum_required = dao_config.quorum_percentage as u64;
+        let quorum_required = original.quorum_percentage as u64;
         let quorum_achieved_percentage = if quorum_required > 0 {
             (total_votes as u128)
                 .checked_mul(100)
@@ -1085,12 +2204,7 @@ pub mod dao {
             DaoError::InsufficientVotesForAppeal
         );
 
-        // Calculate appeal bond (1.5x normal bond)
-        let appeal_bond = dao_config.proposal_bond
-            .checked_mul(3)
-            .ok_or(DaoError::Overflow)?
-            .checked_div(2)
-            .ok_or(DaoError::Overflow)?;
+        let appeal_bond = appeal_bond(dao_config.proposal_bond)?;
 
         // Transfer appeal bond from appellant
         let cpi_accounts = Transfer {
@@ -1102,39 +2216,24 @@ pub mod dao {
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
         token::transfer(cpi_ctx, appeal_bond)?;
 
-        // Create appeal proposal with extended voting period
-        let appeal_id = dao_config.proposal_count
+        // The appeal is an ordinary proposal under the standard ["proposal", id]
+        // seeds, so deposit/vote/finalize/execute work on it unchanged
+        dao_config.proposal_count = dao_config
+            .proposal_count
             .checked_add(1)
             .ok_or(DaoError::Overflow)?;
+        let appeal_id = dao_config.proposal_count;
 
-        appeal.proposal_id = appeal_id;
-        appeal.proposer = ctx.accounts.appellant.key();
-        appeal.title = format!("APPEAL: {}", original.title);
-        appeal.description_cid = original.description_cid.clone();
-        appeal.proposal_type = original.proposal_type;
-        appeal.status = ProposalStatus::Active;
-        appeal.for_votes = 0;
-        appeal.against_votes = 0;
-        // Extended voting period (1.5x normal)
-        let extended_duration = dao_config.voting_period
-            .checked_mul(3)
-            .ok_or(DaoError::Overflow)?
-            .checked_div(2)
-            .ok_or(DaoError::Overflow)?;
-        appeal.abstain_votes = 0;
-        appeal.vote_start = clock.unix_timestamp;
-        appeal.vote_end = clock.unix_timestamp
-            .checked_add(extended_duration)
-            .ok_or(DaoError::Overflow)?;
-        // Execution eligible after extended voting + timelock (3 days per whitepaper)
-        appeal.execution_eligible_at = appeal.vote_end
-            .checked_add(EXECUTION_TIMELOCK)
-            .ok_or(DaoError::Overflow)?;
-        appeal.created_at = clock.unix_timestamp;
-        appeal.executed_at = None;
-        appeal.execution_data = original.execution_data.clone();
-        appeal.bond_returned = false;
-        appeal.bump = ctx.bumps.appeal_proposal;
+        let appeal = original.appeal(
+            appeal_id,
+            ctx.accounts.appellant.key(),
+            dao_config,
+            clock.unix_timestamp,
+            ctx.bumps.appeal_proposal,
+        )?;
+        emit!(appeal.manifest(clock.unix_timestamp)?);
+        ctx.accounts.appeal_proposal.set_inner(appeal);
+        original.appeal_proposal_id = Some(appeal_id);
 
         msg!(
             "Y7.2: Appeal created for proposal {} -> new proposal {}. Appeal bond: {}",
@@ -1154,6 +2253,173 @@ pub mod dao {
         Ok(())
     }
 
+    /// Y7.2: Move an appeal created under the legacy ["appeal", original_id]
+    /// seeds to the standard ["proposal", id] seeds
+    ///
+    /// Deposit/vote/finalize/execute derive the proposal PDA from
+    /// ["proposal", proposal_id], so a legacy appeal fails their seeds check and
+    /// can never be voted on (its proposal_id also collides with the next
+    /// regular proposal). Migration closes the legacy account, refunding rent to
+    /// the appellant, and recreates the appeal under a fresh proposal id with a
+    /// new voting window. The appeal bond already sits in the bond escrow.
+    pub fn migrate_legacy_appeal(
+        ctx: Context<MigrateLegacyAppeal>,
+        original_proposal_id: u64,
+    ) -> Result<()> {
+        let original = &mut ctx.accounts.original_proposal;
+        let legacy = &ctx.accounts.legacy_appeal;
+        let dao_config = &mut ctx.accounts.dao_config;
+        let clock = Clock::get()?;
+
+        // Nobody can have voted on it, so only never-finalized appeals qualify
+        require!(
+            legacy.status == ProposalStatus::Active,
+            DaoError::ProposalNotActive
+        );
+        require!(original.appeal_proposal_id.is_none(), DaoError::AlreadyAppealed);
+
+        dao_config.proposal_count = dao_config
+            .proposal_count
+            .checked_add(1)
+            .ok_or(DaoError::Overflow)?;
+        let appeal_id = dao_config.proposal_count;
+
+        let appeal = original.appeal(
+            appeal_id,
+            legacy.proposer,
+            dao_config,
+            clock.unix_timestamp,
+            ctx.bumps.appeal_proposal,
+        )?;
+        emit!(appeal.manifest(clock.unix_timestamp)?);
+        ctx.accounts.appeal_proposal.set_inner(appeal);
+        original.appeal_proposal_id = Some(appeal_id);
+
+        msg!(
+            "Y7.2: Legacy appeal of proposal {} migrated to proposal {}",
+            original_proposal_id,
+            appeal_id
+        );
+
+        emit!(LegacyAppealMigratedEvent {
+            original_proposal_id,
+            legacy_appeal: ctx.accounts.legacy_appeal.key(),
+            appeal_proposal_id: appeal_id,
+            appellant: ctx.accounts.appellant.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Contribute toward the bond of a Pending sponsored proposal
+    ///
+    /// Any token holder may sponsor, once per proposal. Contributions beyond
+    /// the remaining bond are trimmed, and the contribution that covers the
+    /// bond activates the proposal: the discussion period starts now.
+    pub fn sponsor_proposal(ctx: Context<SponsorProposal>, amount: u64) -> Result<()> {
+        let dao_config = &ctx.accounts.dao_config;
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        require!(!dao_config.paused, DaoError::DaoPaused);
+        require!(amount > 0, DaoError::InvalidAmount);
+
+        let accepted = proposal.record_sponsorship(amount, clock.unix_timestamp)?;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.sponsor_token_account.to_account_info(),
+            to: ctx.accounts.bond_escrow.to_account_info(),
+            authority: ctx.accounts.sponsor.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, accepted)?;
+
+        let sponsorship = &mut ctx.accounts.sponsorship;
+        sponsorship.proposal_id = proposal.proposal_id;
+        sponsorship.sponsor = ctx.accounts.sponsor.key();
+        sponsorship.amount = accepted;
+        sponsorship.refunded = false;
+        sponsorship.bump = ctx.bumps.sponsorship;
+
+        let activated = proposal.status == ProposalStatus::Active;
+        msg!(
+            "Proposal {} sponsored with {} by {} ({}/{} raised)",
+            proposal.proposal_id,
+            accepted,
+            sponsorship.sponsor,
+            proposal.bond_raised,
+            proposal.bond_target
+        );
+
+        emit!(ProposalSponsoredEvent {
+            proposal_id: proposal.proposal_id,
+            sponsor: sponsorship.sponsor,
+            amount: accepted,
+            bond_raised: proposal.bond_raised,
+            bond_target: proposal.bond_target,
+            activated,
+            vote_start: proposal.vote_start,
+            timestamp: clock.unix_timestamp,
+        });
+        // The timeline is only known now, so publish the manifest again
+        if activated {
+            emit!(proposal.manifest(clock.unix_timestamp)?);
+        }
+
+        Ok(())
+    }
+
+    /// Refund a sponsor's contribution to a sponsored proposal
+    ///
+    /// Refund tiers follow `return_proposal_bond`, split pro-rata by
+    /// contribution; a cancelled proposal or a lapsed funding window refunds
+    /// the contribution in full.
+    pub fn claim_sponsor_refund(ctx: Context<ClaimSponsorRefund>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let sponsorship = &mut ctx.accounts.sponsorship;
+        let clock = Clock::get()?;
+
+        require!(!sponsorship.refunded, DaoError::BondAlreadyReturned);
+
+        let refund = proposal.sponsor_refund(sponsorship.amount, clock.unix_timestamp)?;
+        require!(refund > 0, DaoError::BondForfeited);
+
+        let dao_bump = ctx.accounts.dao_config.bump;
+        let seeds = &[b"dao_config".as_ref(), &[dao_bump]];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bond_escrow.to_account_info(),
+            to: ctx.accounts.sponsor_token_account.to_account_info(),
+            authority: ctx.accounts.dao_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, refund)?;
+
+        sponsorship.refunded = true;
+
+        msg!(
+            "Proposal {} sponsor {} refunded {} of {}",
+            proposal.proposal_id,
+            sponsorship.sponsor,
+            refund,
+            sponsorship.amount
+        );
+
+        emit!(SponsorRefundedEvent {
+            proposal_id: proposal.proposal_id,
+            sponsor: sponsorship.sponsor,
+            contribution: sponsorship.amount,
+            amount: refund,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Deposit tokens to DAO treasury
     pub fn deposit_to_treasury(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
         let dao_config = &mut ctx.accounts.dao_config;
@@ -1198,6 +2464,89 @@ pub mod dao {
 // ACCOUNT STRUCTURES
 // ============================================================================
 
+/// Adaptive quorum settings
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AdaptiveQuorumConfig {
+    /// Whether new proposals snapshot an adjusted quorum instead of the base quorum
+    pub enabled: bool,
+    /// Floor for the effective quorum percentage
+    pub min_quorum_percentage: u8,
+    /// Cap for the effective quorum percentage
+    pub max_quorum_percentage: u8,
+    /// Weight of the newest proposal in the participation average (basis points)
+    pub smoothing_bps: u16,
+}
+
+impl AdaptiveQuorumConfig {
+    pub const MAX_SIZE: usize = 1 + 1 + 1 + 2;
+}
+
+/// Vote weight multipliers by deposit time (basis points, 10000 = 1x)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConvictionSchedule {
+    /// Deposits made before vote_start
+    pub pre_voting_bps: u16,
+    /// Deposits in the first half of the voting window
+    pub first_half_bps: u16,
+    /// Deposits in the second half of the voting window
+    pub late_bps: u16,
+}
+
+impl ConvictionSchedule {
+    pub const MAX_SIZE: usize = 2 + 2 + 2;
+
+    /// Multipliers never drop below 1x, never exceed the cap, and never favour later deposits
+    pub fn is_valid(&self) -> bool {
+        10000 <= self.late_bps
+            && self.late_bps <= self.first_half_bps
+            && self.first_half_bps <= self.pre_voting_bps
+            && self.pre_voting_bps <= MAX_CONVICTION_MULTIPLIER_BPS
+    }
+
+    /// Multiplier for tokens deposited at `deposited_at`
+    pub fn multiplier_bps(&self, deposited_at: i64, vote_start: i64, vote_end: i64) -> u16 {
+        let midpoint = vote_start + (vote_end - vote_start) / 2;
+        if deposited_at < vote_start {
+            self.pre_voting_bps
+        } else if deposited_at < midpoint {
+            self.first_half_bps
+        } else {
+            self.late_bps
+        }
+    }
+}
+
+impl Default for ConvictionSchedule {
+    fn default() -> Self {
+        Self {
+            pre_voting_bps: DEFAULT_PRE_VOTING_MULTIPLIER_BPS,
+            first_half_bps: DEFAULT_FIRST_HALF_MULTIPLIER_BPS,
+            late_bps: DEFAULT_LATE_MULTIPLIER_BPS,
+        }
+    }
+}
+
+/// Changes queued by queue_config_update (None leaves a setting unchanged)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct ConfigUpdateParams {
+    pub new_voting_period: Option<i64>,
+    pub new_proposal_bond: Option<u64>,
+    pub new_quorum_percentage: Option<u8>,
+    pub new_approval_threshold: Option<u8>,
+    /// Zero disables the fee
+    pub new_proposal_fee: Option<u64>,
+    pub new_adaptive_quorum: Option<AdaptiveQuorumConfig>,
+    pub new_max_withdrawal_bps: Option<u16>,
+    pub new_conviction_schedule: Option<ConvictionSchedule>,
+    /// Pubkey::default() removes the guardian
+    pub new_guardian: Option<Pubkey>,
+    /// Zero disables objections
+    pub new_veto_threshold_bps: Option<u16>,
+    pub new_general_voting_mode: Option<VotingMode>,
+    pub new_max_snapshot_vote_bps: Option<u16>,
+    pub new_voting_model: Option<VotingModel>,
+}
+
 /// Pending configuration change (for timelock)
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct PendingConfigChange {
@@ -1205,8 +2554,21 @@ pub struct PendingConfigChange {
     pub new_proposal_bond: Option<u64>,
     pub new_quorum_percentage: Option<u8>,
     pub new_approval_threshold: Option<u8>,
+    pub new_proposal_fee: Option<u64>,
+    pub new_adaptive_quorum: Option<AdaptiveQuorumConfig>,
+    pub new_max_withdrawal_bps: Option<u16>,
+    pub new_conviction_schedule: Option<ConvictionSchedule>,
     pub queued_at: i64,
     pub execute_after: i64,
+    pub new_guardian: Option<Pubkey>,
+    pub new_veto_threshold_bps: Option<u16>,
+    /// Tokens locked in objections against this change
+    pub objection_total: u64,
+    /// Objections exceeded the veto threshold; the change can't be executed
+    pub vetoed: bool,
+    pub new_general_voting_mode: Option<VotingMode>,
+    pub new_max_snapshot_vote_bps: Option<u16>,
+    pub new_voting_model: Option<VotingModel>,
 }
 
 impl PendingConfigChange {
@@ -1214,8 +2576,54 @@ impl PendingConfigChange {
         1 + 8 +  // Option<u64>
         1 + 1 +  // Option<u8>
         1 + 1 +  // Option<u8>
+        1 + 8 +  // Option<u64> (proposal fee)
+        1 + AdaptiveQuorumConfig::MAX_SIZE + // Option<AdaptiveQuorumConfig>
+        1 + 2 +  // Option<u16> (max withdrawal bps)
+        1 + ConvictionSchedule::MAX_SIZE + // Option<ConvictionSchedule>
         8 +      // queued_at
-        8; // execute_after
+        8 +      // execute_after
+        1 + 32 + // Option<Pubkey> (guardian)
+        1 + 2 +  // Option<u16> (veto threshold bps)
+        8 +      // objection_total
+        1 +      // vetoed
+        1 + 1 +  // Option<VotingMode>
+        1 + 2 +  // Option<u16> (max snapshot vote bps)
+        1 + 1; // Option<VotingModel>
+
+    /// Add an objection, vetoing the change once the total exceeds the threshold
+    pub fn record_objection(&mut self, amount: u64, supply: u64, veto_threshold_bps: u16) -> Result<()> {
+        self.objection_total = self
+            .objection_total
+            .checked_add(amount)
+            .ok_or(DaoError::Overflow)?;
+        if objections_exceed_threshold(self.objection_total, supply, veto_threshold_bps) {
+            self.vetoed = true;
+        }
+        Ok(())
+    }
+
+    /// Timelock has expired and the change wasn't vetoed
+    pub fn ensure_executable(&self, now: i64) -> Result<()> {
+        require!(!self.vetoed, DaoError::ConfigChangeVetoed);
+        require!(now >= self.execute_after, DaoError::TimelockNotExpired);
+        Ok(())
+    }
+
+    /// The authority may cancel unless the change is vetoed and its timelock
+    /// hasn't expired yet
+    pub fn ensure_authority_cancellable(&self, now: i64) -> Result<()> {
+        require!(
+            !self.vetoed || now >= self.execute_after,
+            DaoError::VetoedConfigChangeLocked
+        );
+        Ok(())
+    }
+}
+
+/// Objections are over `veto_threshold_bps` of `supply` (a zero threshold never vetoes)
+fn objections_exceed_threshold(objection_total: u64, supply: u64, veto_threshold_bps: u16) -> bool {
+    veto_threshold_bps > 0
+        && (objection_total as u128) * 10000 > (supply as u128) * veto_threshold_bps as u128
 }
 
 /// DAO configuration account
@@ -1251,6 +2659,31 @@ pub struct DaoConfig {
     pub pending_config_change: Option<PendingConfigChange>,
     /// PDA bump
     pub bump: u8,
+    /// Non-refundable proposal creation fee (0 = disabled)
+    pub proposal_fee: u64,
+    /// Adaptive quorum mode, bounds, and smoothing factor
+    pub adaptive_quorum: AdaptiveQuorumConfig,
+    /// Exponentially weighted average of finalized proposal turnout (basis points of supply)
+    pub avg_participation_bps: u16,
+    /// Max share of the treasury a single withdrawal proposal may take (basis points)
+    pub max_withdrawal_bps: u16,
+    /// Vote weight multipliers rewarding early escrow deposits
+    pub conviction_schedule: ConvictionSchedule,
+    /// May cancel any pending config change (Pubkey::default() = none)
+    pub guardian: Pubkey,
+    /// Objections above this share of supply veto a pending config change
+    /// (basis points, 0 = objections disabled)
+    pub veto_threshold_bps: u16,
+    /// How General proposals are voted on (other types always use Escrow)
+    pub general_voting_mode: VotingMode,
+    /// Largest single snapshot vote (basis points of snapshot supply)
+    pub max_snapshot_vote_bps: u16,
+    /// Treasury amount reserved for passed, unexecuted withdrawal proposals
+    pub committed_outflow: u64,
+    /// How token amounts become vote weight (snapshotted onto each proposal)
+    pub voting_model: VotingModel,
+    /// Layout version, see CURRENT_DAO_CONFIG_VERSION
+    pub config_version: u8,
 }
 
 impl DaoConfig {
@@ -1269,14 +2702,680 @@ impl DaoConfig {
         8 +                          // total_treasury_deposits
         1 +                          // paused
         1 + PendingConfigChange::MAX_SIZE + // pending_config_change (Option)
-        1; // bump
-}
+        1 +                          // bump
+        8 +                          // proposal_fee
+        AdaptiveQuorumConfig::MAX_SIZE + // adaptive_quorum
+        2 +                          // avg_participation_bps
+        2 +                          // max_withdrawal_bps
+        ConvictionSchedule::MAX_SIZE + // conviction_schedule
+        32 +                         // guardian
+        2 +                          // veto_threshold_bps
+        1 +                          // general_voting_mode
+        2 +                          // max_snapshot_vote_bps
+        8 +                          // committed_outflow
+        1 +                          // voting_model
+        1; // config_version
+
+    /// Voting mode for a new proposal of `proposal_type`
+    ///
+    /// Only General proposals (no on-chain execution) may use Snapshot;
+    /// treasury, parameter and emergency proposals always require escrow.
+    pub fn voting_mode_for(&self, proposal_type: ProposalType) -> VotingMode {
+        match proposal_type {
+            ProposalType::General => self.general_voting_mode,
+            _ => VotingMode::Escrow,
+        }
+    }
 
-/// Proposal account
-#[account]
-pub struct Proposal {
-    /// Unique proposal ID
-    pub proposal_id: u64,
+    /// Quorum percentage a proposal created now should be held to
+    ///
+    /// With adaptive quorum enabled, the base quorum moves part of the way
+    /// toward recent participation, clamped to the configured bounds.
+    pub fn effective_quorum_percentage(&self) -> u8 {
+        if !self.adaptive_quorum.enabled {
+            return self.quorum_percentage;
+        }
+        let base_bps = self.quorum_percentage as i64 * 100;
+        let adjustment =
+            (self.avg_participation_bps as i64 - base_bps) / ADAPTIVE_QUORUM_ADJUSTMENT_DIVISOR;
+        let adjusted = ((base_bps + adjustment) / 100) as u8;
+        adjusted.clamp(
+            self.adaptive_quorum.min_quorum_percentage,
+            self.adaptive_quorum.max_quorum_percentage,
+        )
+    }
+
+    /// Reserve `amount` of a treasury holding `treasury_balance` for a passed
+    /// withdrawal; false (nothing reserved) when it doesn't fit alongside the
+    /// existing commitments
+    pub fn try_commit_outflow(&mut self, amount: u64, treasury_balance: u64) -> Result<bool> {
+        let committed = self
+            .committed_outflow
+            .checked_add(amount)
+            .ok_or(DaoError::Overflow)?;
+        if committed > treasury_balance {
+            return Ok(false);
+        }
+        self.committed_outflow = committed;
+        Ok(true)
+    }
+
+    /// Release a reservation made by `try_commit_outflow`
+    ///
+    /// Saturates: withdrawals that passed before commitments were tracked
+    /// were never reserved.
+    pub fn release_outflow(&mut self, amount: u64) {
+        self.committed_outflow = self.committed_outflow.saturating_sub(amount);
+    }
+
+    /// Apply an executed ParameterChange proposal (None keeps the current value)
+    pub fn apply_parameter_change(
+        &mut self,
+        voting_period: Option<i64>,
+        quorum_percentage: Option<u8>,
+        approval_threshold: Option<u8>,
+        proposal_bond: Option<u64>,
+    ) {
+        if let Some(period) = voting_period {
+            self.voting_period = period;
+        }
+        if let Some(quorum) = quorum_percentage {
+            self.quorum_percentage = quorum;
+        }
+        if let Some(threshold) = approval_threshold {
+            self.approval_threshold = threshold;
+        }
+        if let Some(bond) = proposal_bond {
+            self.proposal_bond = bond;
+        }
+    }
+}
+
+impl DaoConfig {
+    /// A new change can only be queued once the last one is executed or
+    /// cancelled; overwriting it would reset its objections or lift a veto
+    pub fn ensure_no_pending_config_change(&self) -> Result<()> {
+        require!(
+            self.pending_config_change.is_none(),
+            DaoError::ConfigChangeAlreadyPending
+        );
+        Ok(())
+    }
+
+    /// Decode a DaoConfig account in any supported layout, returning the
+    /// layout version it was stored in and the config in the current layout
+    pub fn decode_any_version(data: &[u8]) -> Result<(u8, DaoConfig)> {
+        require!(
+            data.starts_with(DaoConfig::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        match data.len() {
+            DaoConfig::MAX_SIZE => {
+                let config = DaoConfig::try_deserialize(&mut &data[..])?;
+                Ok((config.config_version, config))
+            }
+            LegacyDaoConfigV0::SIZE => {
+                let legacy = LegacyDaoConfigV0::deserialize(&mut &data[8..])?;
+                Ok((0, legacy.into_current()))
+            }
+            _ => err!(DaoError::UnsupportedDaoConfigLayout),
+        }
+    }
+}
+
+/// PendingConfigChange as stored by DaoConfig layout version 0
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyPendingConfigChangeV0 {
+    pub new_voting_period: Option<i64>,
+    pub new_proposal_bond: Option<u64>,
+    pub new_quorum_percentage: Option<u8>,
+    pub new_approval_threshold: Option<u8>,
+    pub queued_at: i64,
+    pub execute_after: i64,
+}
+
+impl LegacyPendingConfigChangeV0 {
+    pub const MAX_SIZE: usize = 1 + 8 + 1 + 8 + 1 + 1 + 1 + 1 + 8 + 8;
+}
+
+/// DaoConfig layout version 0 (without discriminator), the layout before
+/// config_version existed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyDaoConfigV0 {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub governance_token_mint: Pubkey,
+    pub bond_escrow: Pubkey,
+    pub vote_vault: Pubkey,
+    pub discussion_period: i64,
+    pub voting_period: i64,
+    pub proposal_bond: u64,
+    pub quorum_percentage: u8,
+    pub approval_threshold: u8,
+    pub proposal_count: u64,
+    pub total_treasury_deposits: u64,
+    pub paused: bool,
+    pub pending_config_change: Option<LegacyPendingConfigChangeV0>,
+    pub bump: u8,
+}
+
+impl LegacyDaoConfigV0 {
+    /// Account size, discriminator included
+    pub const SIZE: usize = 8 + 32 * 5 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 1
+        + 1 + LegacyPendingConfigChangeV0::MAX_SIZE
+        + 1;
+
+    /// The same config in the current layout; fields added since version 0
+    /// get the defaults initialize_dao uses
+    pub fn into_current(self) -> DaoConfig {
+        let pending_config_change = self.pending_config_change.map(|pending| PendingConfigChange {
+            new_voting_period: pending.new_voting_period,
+            new_proposal_bond: pending.new_proposal_bond,
+            new_quorum_percentage: pending.new_quorum_percentage,
+            new_approval_threshold: pending.new_approval_threshold,
+            new_proposal_fee: None,
+            new_adaptive_quorum: None,
+            new_max_withdrawal_bps: None,
+            new_conviction_schedule: None,
+            queued_at: pending.queued_at,
+            execute_after: pending.execute_after,
+            new_guardian: None,
+            new_veto_threshold_bps: None,
+            objection_total: 0,
+            vetoed: false,
+            new_general_voting_mode: None,
+            new_max_snapshot_vote_bps: None,
+            new_voting_model: None,
+        });
+
+        DaoConfig {
+            authority: self.authority,
+            treasury: self.treasury,
+            governance_token_mint: self.governance_token_mint,
+            bond_escrow: self.bond_escrow,
+            vote_vault: self.vote_vault,
+            discussion_period: self.discussion_period,
+            voting_period: self.voting_period,
+            proposal_bond: self.proposal_bond,
+            quorum_percentage: self.quorum_percentage,
+            approval_threshold: self.approval_threshold,
+            proposal_count: self.proposal_count,
+            total_treasury_deposits: self.total_treasury_deposits,
+            paused: self.paused,
+            pending_config_change,
+            bump: self.bump,
+            proposal_fee: DEFAULT_PROPOSAL_FEE,
+            adaptive_quorum: AdaptiveQuorumConfig {
+                enabled: false,
+                min_quorum_percentage: DEFAULT_MIN_QUORUM_PERCENTAGE,
+                max_quorum_percentage: DEFAULT_MAX_QUORUM_PERCENTAGE,
+                smoothing_bps: DEFAULT_PARTICIPATION_SMOOTHING_BPS,
+            },
+            // Seeded at the base quorum, as initialize_dao does
+            avg_participation_bps: self.quorum_percentage as u16 * 100,
+            max_withdrawal_bps: DEFAULT_MAX_WITHDRAWAL_BPS,
+            conviction_schedule: ConvictionSchedule::default(),
+            guardian: Pubkey::default(),
+            veto_threshold_bps: 0,
+            general_voting_mode: VotingMode::Escrow,
+            max_snapshot_vote_bps: DEFAULT_MAX_SNAPSHOT_VOTE_BPS,
+            // Version 0 had no withdrawal reservations
+            committed_outflow: 0,
+            voting_model: VotingModel::TokenWeighted,
+            config_version: CURRENT_DAO_CONFIG_VERSION,
+        }
+    }
+}
+
+/// Resize a program-owned account to `len`, topping up rent from `payer`
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= len {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Largest withdrawal allowed from a treasury holding `balance`
+fn withdrawal_cap(balance: u64, max_withdrawal_bps: u16) -> Result<u64> {
+    Ok((balance as u128)
+        .checked_mul(max_withdrawal_bps as u128)
+        .ok_or(DaoError::Overflow)?
+        .checked_div(10000)
+        .ok_or(DaoError::Overflow)? as u64)
+}
+
+/// Sum of a withdrawal's payouts
+fn payout_total(payouts: &[WithdrawalPayout]) -> Result<u64> {
+    payouts.iter().try_fold(0u64, |total, payout| {
+        total.checked_add(payout.amount).ok_or_else(|| DaoError::Overflow.into())
+    })
+}
+
+/// Total of a withdrawal's payouts, checked against the live treasury
+/// balance at execution
+fn check_treasury_withdrawal(
+    payouts: &[WithdrawalPayout],
+    treasury_balance: u64,
+    max_withdrawal_bps: u16,
+) -> Result<u64> {
+    // Check treasury has sufficient balance for every payout
+    let amount = payout_total(payouts)?;
+    require!(
+        treasury_balance >= amount,
+        DaoError::InsufficientTreasuryBalance
+    );
+
+    // Re-apply the cap to the live balance so a treasury that shrank
+    // since creation can't be drained beyond the approved percentage
+    let live_cap = withdrawal_cap(treasury_balance, max_withdrawal_bps)?;
+    require!(amount <= live_cap, DaoError::WithdrawalExceedsCap);
+    Ok(amount)
+}
+
+/// Check the recipient accounts passed to execute_proposal are exactly the
+/// payout recipients, in payout order
+fn check_payout_recipients(payouts: &[WithdrawalPayout], recipients: &[Pubkey]) -> Result<()> {
+    require!(
+        recipients.len() == payouts.len(),
+        DaoError::WithdrawalRecipientCountMismatch
+    );
+    for (payout, recipient) in payouts.iter().zip(recipients) {
+        require_keys_eq!(*recipient, payout.recipient, DaoError::InvalidRecipient);
+    }
+    Ok(())
+}
+
+/// Bond for appealing a defeated proposal (1.5x the proposal bond)
+fn appeal_bond(proposal_bond: u64) -> Result<u64> {
+    Ok(proposal_bond
+        .checked_mul(3)
+        .ok_or(DaoError::Overflow)?
+        .checked_div(2)
+        .ok_or(DaoError::Overflow)?)
+}
+
+/// Vote weight of `principal` escrowed tokens under `multiplier_bps`
+fn boosted_vote_weight(principal: u64, multiplier_bps: u16) -> Result<u64> {
+    let weight = (principal as u128)
+        .checked_mul(multiplier_bps as u128)
+        .ok_or(DaoError::Overflow)?
+        / 10000;
+    u64::try_from(weight).map_err(|_| DaoError::Overflow.into())
+}
+
+/// Integer square root (floor) via Newton's method
+fn integer_sqrt(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    let n = n as u128;
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x as u64
+}
+
+/// Weight of a snapshot vote: the voter's balance, capped at
+/// `max_snapshot_vote_bps` of the snapshot supply
+fn snapshot_vote_weight(balance: u64, snapshot_supply: u64, max_snapshot_vote_bps: u16) -> Result<u64> {
+    let cap = (snapshot_supply as u128)
+        .checked_mul(max_snapshot_vote_bps as u128)
+        .ok_or(DaoError::Overflow)?
+        / 10000;
+    Ok((balance as u128).min(cap) as u64)
+}
+
+/// Weight a vote counts for, and what it was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CastWeight {
+    vote_weight: u64,
+    multiplier_bps: u16,
+    /// Token balance read for a snapshot vote (0 for escrow votes)
+    snapshot_balance: u64,
+}
+
+/// Resolve a vote's weight under the proposal's voting mode
+///
+/// Escrow: the escrowed principal boosted by the conviction multiplier;
+/// the escrow is required. Snapshot: the voter's current balance, capped by
+/// `snapshot_vote_weight`, without a multiplier; the token account is
+/// required and any escrow is ignored. Either way the token amount goes
+/// through the proposal's voting model before any boost.
+fn resolve_vote_weight(
+    proposal: &Proposal,
+    dao_config: &DaoConfig,
+    vote_escrow: Option<&VoteEscrow>,
+    voter_balance: Option<u64>,
+) -> Result<CastWeight> {
+    match proposal.voting_mode {
+        VotingMode::Escrow => {
+            let vote_escrow = vote_escrow.ok_or(DaoError::VoteEscrowRequired)?;
+
+            // Check voter hasn't already voted (escrow-based double-vote prevention)
+            require!(!vote_escrow.has_voted, DaoError::AlreadyVoted);
+
+            // SECURITY FIX: Get vote weight from ESCROWED tokens (prevents flash loan attacks!)
+            // The voter had to lock these tokens before voting, so they can't borrow and return
+            require!(vote_escrow.deposited_amount > 0, DaoError::NoVotingPower);
+
+            // Reward tokens locked for more of the proposal lifecycle
+            let multiplier_bps = proposal.conviction_schedule.multiplier_bps(
+                vote_escrow.deposited_at,
+                proposal.vote_start,
+                proposal.vote_end,
+            );
+            let principal = proposal.voting_model.vote_power(vote_escrow.deposited_amount);
+            Ok(CastWeight {
+                vote_weight: boosted_vote_weight(principal, multiplier_bps)?,
+                multiplier_bps,
+                snapshot_balance: 0,
+            })
+        }
+        VotingMode::Snapshot => {
+            let balance = voter_balance.ok_or(DaoError::VoterTokenAccountRequired)?;
+            let capped =
+                snapshot_vote_weight(balance, proposal.snapshot_supply, dao_config.max_snapshot_vote_bps)?;
+            let vote_weight = proposal.voting_model.vote_power(capped);
+            require!(vote_weight > 0, DaoError::NoVotingPower);
+            Ok(CastWeight {
+                vote_weight,
+                multiplier_bps: 10000,
+                snapshot_balance: balance,
+            })
+        }
+    }
+}
+
+/// Resolve the weight of an Escrow-mode vote cast with a voter position
+///
+/// Same as an escrow vote: the position's principal boosted by the
+/// conviction multiplier for when it was (last) deposited.
+fn position_vote_weight(
+    proposal: &Proposal,
+    voter_position: &VoterPosition,
+) -> Result<CastWeight> {
+    require!(
+        !voter_position.has_voted_on(proposal.proposal_id),
+        DaoError::AlreadyVoted
+    );
+    require!(voter_position.deposited_amount > 0, DaoError::NoVotingPower);
+
+    let multiplier_bps = proposal.conviction_schedule.multiplier_bps(
+        voter_position.deposited_at,
+        proposal.vote_start,
+        proposal.vote_end,
+    );
+    let principal = proposal.voting_model.vote_power(voter_position.deposited_amount);
+    Ok(CastWeight {
+        vote_weight: boosted_vote_weight(principal, multiplier_bps)?,
+        multiplier_bps,
+        snapshot_balance: 0,
+    })
+}
+
+/// Resolve the weight a delegate adds with the delegator's escrow
+///
+/// The delegation must name `delegate`, have been in effect when the
+/// proposal was created and belong to the escrow's owner; the escrow must
+/// not have voted yet. The weight is the escrow's boosted principal, as if
+/// the delegator had voted.
+fn delegated_vote_weight(
+    proposal: &Proposal,
+    dao_config: &DaoConfig,
+    delegation: &VoteDelegation,
+    delegator_escrow: &VoteEscrow,
+    delegate: Pubkey,
+) -> Result<CastWeight> {
+    require!(
+        delegator_escrow.proposal_id == proposal.proposal_id,
+        DaoError::InvalidDelegation
+    );
+    check_delegation(proposal, delegation, delegate, delegator_escrow.voter)?;
+    require!(!delegator_escrow.withdrawn, DaoError::AlreadyWithdrawn);
+    require!(!delegator_escrow.has_voted, DaoError::DelegatedVotesUsed);
+
+    resolve_vote_weight(proposal, dao_config, Some(delegator_escrow), None)
+}
+
+/// Resolve the weight a delegate adds with the delegator's voter position
+///
+/// Same checks as `delegated_vote_weight`, but the delegator needs no
+/// escrow on the proposal: their standing position counts, boosted as if
+/// they had voted with it, unless it already voted on the proposal.
+fn delegated_position_weight(
+    proposal: &Proposal,
+    delegation: &VoteDelegation,
+    delegator_position: &VoterPosition,
+    delegate: Pubkey,
+) -> Result<CastWeight> {
+    check_delegation(proposal, delegation, delegate, delegator_position.voter)?;
+    require!(
+        !delegator_position.has_voted_on(proposal.proposal_id),
+        DaoError::DelegatedVotesUsed
+    );
+
+    position_vote_weight(proposal, delegator_position)
+}
+
+/// Check `delegation` lets `delegate` vote `delegator`'s tokens on `proposal`
+fn check_delegation(
+    proposal: &Proposal,
+    delegation: &VoteDelegation,
+    delegate: Pubkey,
+    delegator: Pubkey,
+) -> Result<()> {
+    require!(
+        proposal.voting_mode == VotingMode::Escrow,
+        DaoError::DelegationRequiresEscrow
+    );
+    require!(
+        delegation.delegate == delegate && delegation.delegator == delegator,
+        DaoError::InvalidDelegation
+    );
+    require!(
+        delegation.is_effective_for(proposal.created_at),
+        DaoError::DelegationNotEffective
+    );
+    Ok(())
+}
+
+/// Unlock the escrows a retracted vote was cast with
+///
+/// The voter's own escrow (or their position, if it voted on the proposal)
+/// is required when the vote carried own weight; the delegator's escrow, or
+/// their position if the vote record says it was used, when it carried
+/// delegated weight.
+fn release_vote_escrows(
+    vote_record: &VoteRecord,
+    vote_escrow: Option<&mut VoteEscrow>,
+    voter_position: Option<&mut VoterPosition>,
+    delegator_escrow: Option<&mut VoteEscrow>,
+    delegator_position: Option<&mut VoterPosition>,
+) -> Result<()> {
+    let own_weight = vote_record
+        .vote_weight
+        .checked_sub(vote_record.delegated_weight)
+        .ok_or(DaoError::Underflow)?;
+
+    if own_weight > 0 {
+        match voter_position {
+            Some(voter_position) if voter_position.has_voted_on(vote_record.proposal_id) => {
+                voter_position.release_vote(vote_record.proposal_id)?;
+            }
+            _ => {
+                let vote_escrow = vote_escrow.ok_or(DaoError::VoteEscrowRequired)?;
+                require!(vote_escrow.has_voted, DaoError::NotVoted);
+                vote_escrow.has_voted = false;
+                vote_escrow.vote_choice = None;
+            }
+        }
+    }
+
+    if vote_record.delegated_weight > 0 {
+        if vote_record.delegated_from_position {
+            let delegator_position = delegator_position.ok_or(DaoError::DelegatorEscrowRequired)?;
+            require!(
+                delegator_position.voter == vote_record.delegator,
+                DaoError::InvalidDelegation
+            );
+            delegator_position.release_vote(vote_record.proposal_id)?;
+        } else {
+            let delegator_escrow = delegator_escrow.ok_or(DaoError::DelegatorEscrowRequired)?;
+            require!(
+                delegator_escrow.voter == vote_record.delegator
+                    && delegator_escrow.proposal_id == vote_record.proposal_id,
+                DaoError::InvalidDelegation
+            );
+            require!(delegator_escrow.has_voted, DaoError::NotVoted);
+            delegator_escrow.has_voted = false;
+            delegator_escrow.vote_choice = None;
+        }
+    }
+
+    Ok(())
+}
+
+/// Message a voter signs for cast_vote_by_signature
+///
+/// domain || program id || voter || proposal_id || choice || nonce || expires_at,
+/// integers little-endian. The program id keeps a signature from being
+/// accepted by another deployment.
+fn signed_vote_message(
+    voter: &Pubkey,
+    proposal_id: u64,
+    vote_choice: VoteChoice,
+    nonce: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SIGNED_VOTE_DOMAIN.len() + 32 + 32 + 8 + 1 + 8 + 8);
+    message.extend_from_slice(SIGNED_VOTE_DOMAIN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(voter.as_ref());
+    message.extend_from_slice(&proposal_id.to_le_bytes());
+    message.push(vote_choice as u8);
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Check an Ed25519 program instruction verifies `signer`'s signature over `message`
+///
+/// The instruction must carry exactly one signature with its public key and
+/// message inline (instruction index u16::MAX); offsets into another
+/// instruction could point the check at data the Ed25519 program never saw
+/// together. The signature itself is checked by the Ed25519 program, which
+/// fails the whole transaction if it's invalid.
+fn verify_ed25519_instruction(
+    ix: &anchor_lang::solana_program::instruction::Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    require_keys_eq!(ix.program_id, ED25519_PROGRAM_ID, DaoError::InvalidVoteSignature);
+
+    // Signature count, padding, then one 14-byte offsets record
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, DaoError::InvalidVoteSignature);
+    let field = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]);
+    let (signature_offset, signature_ix) = (field(0), field(1));
+    let (pubkey_offset, pubkey_ix) = (field(2), field(3));
+    let (message_offset, message_size, message_ix) = (field(4), field(5), field(6));
+    require!(
+        signature_ix == u16::MAX && pubkey_ix == u16::MAX && message_ix == u16::MAX,
+        DaoError::InvalidVoteSignature
+    );
+
+    let slice = |offset: u16, len: usize| data.get(usize::from(offset)..usize::from(offset) + len);
+    require!(slice(signature_offset, 64).is_some(), DaoError::InvalidVoteSignature);
+    require!(
+        slice(pubkey_offset, 32) == Some(signer.as_ref()),
+        DaoError::InvalidVoteSignature
+    );
+    require!(
+        slice(message_offset, usize::from(message_size)) == Some(message),
+        DaoError::InvalidVoteSignature
+    );
+    Ok(())
+}
+
+/// Check the parameters a ParameterChange proposal or queued config change
+/// can set (None leaves a parameter unchanged)
+fn validate_governance_parameters(
+    voting_period: Option<i64>,
+    quorum_percentage: Option<u8>,
+    approval_threshold: Option<u8>,
+    proposal_bond: Option<u64>,
+) -> Result<()> {
+    if let Some(period) = voting_period {
+        require!(
+            (MIN_VOTING_PERIOD..=MAX_VOTING_PERIOD).contains(&period),
+            DaoError::InvalidVotingPeriod
+        );
+    }
+    if let Some(bond) = proposal_bond {
+        require!(bond >= MIN_PROPOSAL_BOND, DaoError::InvalidProposalBond);
+    }
+    if let Some(quorum) = quorum_percentage {
+        require!(
+            quorum > 0 && quorum <= 100,
+            DaoError::InvalidQuorumPercentage
+        );
+    }
+    if let Some(threshold) = approval_threshold {
+        require!(
+            threshold > 0 && threshold <= 100,
+            DaoError::InvalidApprovalThreshold
+        );
+    }
+    Ok(())
+}
+
+/// Check proposal tags: at most MAX_PROPOSAL_TAGS, each 1..=MAX_TAG_LENGTH
+/// lowercase ASCII letters or digits, no duplicates
+fn validate_proposal_tags(tags: &[String]) -> Result<()> {
+    require!(tags.len() <= MAX_PROPOSAL_TAGS, DaoError::TooManyProposalTags);
+    for (i, tag) in tags.iter().enumerate() {
+        require!(
+            !tag.is_empty()
+                && tag.len() <= MAX_TAG_LENGTH
+                && tag.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit()),
+            DaoError::InvalidProposalTag
+        );
+        require!(!tags[..i].contains(tag), DaoError::DuplicateProposalTag);
+    }
+    Ok(())
+}
+
+/// Exponentially weighted moving average of participation (all values in basis points)
+fn update_participation_average(avg_bps: u16, sample_bps: u16, smoothing_bps: u16) -> u16 {
+    let smoothing = smoothing_bps.min(10000) as u32;
+    ((avg_bps as u32 * (10000 - smoothing) + sample_bps as u32 * smoothing) / 10000) as u16
+}
+
+/// Proposal account
+#[account]
+pub struct Proposal {
+    /// Unique proposal ID
+    pub proposal_id: u64,
     /// Proposer's public key
     pub proposer: Pubkey,
     /// Proposal title
@@ -1311,6 +3410,50 @@ pub struct Proposal {
     pub snapshot_supply: u64,
     /// PDA bump
     pub bump: u8,
+    /// Quorum percentage snapshotted at creation (unaffected by later config changes)
+    pub quorum_percentage: u8,
+    /// Treasury balance at creation
+    pub treasury_balance_snapshot: u64,
+    /// Withdrawal cap (basis points of treasury) snapshotted at creation
+    pub max_withdrawal_bps: u16,
+    /// Category tags for filtering and notification routing (see validate_proposal_tags)
+    pub tags: Vec<String>,
+    /// Y7.2: Id of the appeal of this proposal, if one was filed
+    pub appeal_proposal_id: Option<u64>,
+    /// Bond underwritten by sponsors rather than the proposer
+    pub sponsored: bool,
+    /// Bond sponsors must raise (proposal_bond at creation; 0 when self-funded)
+    pub bond_target: u64,
+    /// Sponsor contributions held in the bond escrow
+    pub bond_raised: u64,
+    /// End of the sponsor funding window (0 when self-funded)
+    pub funding_deadline: i64,
+    /// Who finalized the proposal (default until finalized)
+    pub finalized_by: Pubkey,
+    /// Finalization timestamp (0 until finalized)
+    pub finalized_at: i64,
+    /// Who executed the proposal (default until executed)
+    pub executed_by: Pubkey,
+    /// How votes are weighted, resolved from the proposal type at creation
+    pub voting_mode: VotingMode,
+    /// Last checkpoint_proposal timestamp (0 if never checkpointed)
+    pub last_checkpoint_at: i64,
+    /// Voting model snapshotted at creation (unaffected by later config changes)
+    pub voting_model: VotingModel,
+    /// Approval threshold (percent) snapshotted at creation
+    pub approval_threshold: u8,
+    /// Bond posted for this proposal (by the proposer, sponsors or appellant)
+    pub bond_amount: u64,
+    /// Discussion period snapshotted at creation (0 when voting opens at once)
+    pub discussion_period: i64,
+    /// Voting period snapshotted at creation
+    pub voting_period: i64,
+    /// How long a passed proposal stays executable after execution_eligible_at
+    pub execution_window: i64,
+    /// Emergency proposals skip discussion for a higher bond and threshold
+    pub urgency: ProposalUrgency,
+    /// Conviction multipliers snapshotted at creation
+    pub conviction_schedule: ConvictionSchedule,
 }
 
 impl Proposal {
@@ -1332,982 +3475,4705 @@ impl Proposal {
         1 + 8 +                      // executed_at (Option<i64>)
         1 +                          // bond_returned
         8 +                          // snapshot_supply
-        1; // bump
-}
+        1 +                          // bump
+        1 +                          // quorum_percentage
+        8 +                          // treasury_balance_snapshot
+        2 +                          // max_withdrawal_bps
+        4 + MAX_PROPOSAL_TAGS * (4 + MAX_TAG_LENGTH) + // tags
+        1 + 8 +                      // appeal_proposal_id (Option<u64>)
+        1 +                          // sponsored
+        8 +                          // bond_target
+        8 +                          // bond_raised
+        8 +                          // funding_deadline
+        32 +                         // finalized_by
+        8 +                          // finalized_at
+        32 +                         // executed_by
+        1 +                          // voting_mode
+        8 +                          // last_checkpoint_at
+        1 +                          // voting_model
+        1 +                          // approval_threshold
+        8 +                          // bond_amount
+        8 +                          // discussion_period
+        8 +                          // voting_period
+        8 +                          // execution_window
+        1 +                          // urgency
+        ConvictionSchedule::MAX_SIZE; // conviction_schedule
+
+    /// Add a vote's weight to the tally for its choice
+    pub fn count_vote(&mut self, vote_choice: VoteChoice, vote_weight: u64) -> Result<()> {
+        let tally = match vote_choice {
+            VoteChoice::For => &mut self.for_votes,
+            VoteChoice::Against => &mut self.against_votes,
+            VoteChoice::Abstain => &mut self.abstain_votes,
+        };
+        *tally = tally.checked_add(vote_weight).ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
 
-/// SECURITY FIX: Vote escrow account - tracks deposited tokens for voting
-/// Replaces the vulnerable VoteSnapshot that only recorded balance
-#[account]
-pub struct VoteEscrow {
-    /// Proposal ID
-    pub proposal_id: u64,
-    /// Voter's public key
-    pub voter: Pubkey,
-    /// Amount of tokens deposited (escrowed)
-    pub deposited_amount: u64,
-    /// When tokens were deposited
-    pub deposited_at: i64,
-    /// Whether the voter has cast their vote
-    pub has_voted: bool,
-    /// Vote choice (if voted)
-    pub vote_choice: Option<VoteChoice>,
-    /// Whether tokens have been withdrawn
-    pub withdrawn: bool,
-    /// PDA bump
-    pub bump: u8,
-}
+    /// Remove a retracted vote's weight from the tally for its choice
+    pub fn uncount_vote(&mut self, vote_choice: VoteChoice, vote_weight: u64) -> Result<()> {
+        let tally = match vote_choice {
+            VoteChoice::For => &mut self.for_votes,
+            VoteChoice::Against => &mut self.against_votes,
+            VoteChoice::Abstain => &mut self.abstain_votes,
+        };
+        *tally = tally.checked_sub(vote_weight).ok_or(DaoError::Underflow)?;
+        Ok(())
+    }
 
-impl VoteEscrow {
-    pub const MAX_SIZE: usize = 8 + // discriminator
-        8 +                          // proposal_id
-        32 +                         // voter
-        8 +                          // deposited_amount
-        8 +                          // deposited_at
-        1 +                          // has_voted
-        1 + 1 +                      // vote_choice (Option<enum>)
-        1 +                          // withdrawn
-        1; // bump
-}
+    /// Snapshotted supply in vote-weight units: the supply itself, or its
+    /// square root under Quadratic voting
+    pub fn vote_supply(&self) -> u64 {
+        self.voting_model.vote_power(self.snapshot_supply)
+    }
 
-/// Vote record for a single voter on a proposal
-#[account]
-pub struct VoteRecord {
-    /// Proposal ID
-    pub proposal_id: u64,
-    /// Voter's public key
-    pub voter: Pubkey,
-    /// Vote choice
-    pub vote_choice: VoteChoice,
-    /// Vote weight (from escrow)
-    pub vote_weight: u64,
-    /// When the vote was cast
-    pub voted_at: i64,
-    /// PDA bump
-    pub bump: u8,
-}
+    /// Votes needed for quorum, from the supply and quorum snapshotted at
+    /// creation (prevents manipulation)
+    pub fn quorum_required(&self) -> Result<u64> {
+        Ok(self
+            .vote_supply()
+            .checked_mul(self.quorum_percentage as u64)
+            .ok_or(DaoError::Overflow)?
+            .checked_div(100)
+            .ok_or(DaoError::Overflow)?)
+    }
 
-impl VoteRecord {
-    pub const MAX_SIZE: usize = 8 + // discriminator
-        8 +                          // proposal_id
-        32 +                         // voter
-        1 +                          // vote_choice
-        8 +                          // vote_weight
-        8 +                          // voted_at
-        1; // bump
-}
+    /// FOR share of for + against meets the approval threshold snapshotted
+    /// at creation; abstain votes don't count towards approval
+    pub fn approval_met(&self) -> Result<bool> {
+        let votes_cast = self
+            .for_votes
+            .checked_add(self.against_votes)
+            .ok_or(DaoError::Overflow)?;
+        if votes_cast == 0 {
+            return Ok(false);
+        }
+        let approval_percentage = self
+            .for_votes
+            .checked_mul(100)
+            .ok_or(DaoError::Overflow)?
+            .checked_div(votes_cast)
+            .ok_or(DaoError::Overflow)?;
+        Ok(approval_percentage >= self.approval_threshold as u64)
+    }
 
-/// Execution data for treasury withdrawal proposals
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct ExecutionData {
-    /// Recipient of the withdrawal
-    pub recipient: Pubkey,
-    /// Amount to transfer
-    pub amount: u64,
-}
+    /// Everything needed to render this proposal, from its own snapshots
+    pub fn manifest(&self, now: i64) -> Result<ProposalManifestEvent> {
+        Ok(ProposalManifestEvent {
+            schema_version: PROPOSAL_MANIFEST_VERSION,
+            proposal_id: self.proposal_id,
+            proposer: self.proposer,
+            proposal_type: self.proposal_type,
+            status: self.status,
+            voting_mode: self.voting_mode,
+            voting_model: self.voting_model,
+            urgency: self.urgency,
+            created_at: self.created_at,
+            discussion_period: self.discussion_period,
+            voting_period: self.voting_period,
+            vote_start: self.vote_start,
+            vote_end: self.vote_end,
+            execution_eligible_at: self.execution_eligible_at,
+            execution_window: self.execution_window,
+            snapshot_supply: self.snapshot_supply,
+            quorum_percentage: self.quorum_percentage,
+            quorum_required: self.quorum_required()?,
+            approval_threshold: self.approval_threshold,
+            bond_amount: self.bond_amount,
+            sponsored: self.sponsored,
+            funding_deadline: self.funding_deadline,
+            treasury_balance_snapshot: self.treasury_balance_snapshot,
+            max_withdrawal_bps: self.max_withdrawal_bps,
+            withdrawal_cap: withdrawal_cap(self.treasury_balance_snapshot, self.max_withdrawal_bps)?,
+            withdrawal_amount: self.withdrawal_amount(),
+            conviction_schedule: self.conviction_schedule.clone(),
+            timestamp: now,
+        })
+    }
 
-impl ExecutionData {
-    pub const MAX_SIZE: usize = 32 + 8;
-}
+    /// Execution data of a passed proposal that can be executed at `now`
+    pub fn executable_data(&self, now: i64) -> Result<ExecutionData> {
+        // Check proposal passed
+        require!(
+            self.status == ProposalStatus::Passed,
+            DaoError::ProposalNotPassed
+        );
 
-/// Proposal types
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum ProposalType {
-    /// General governance proposal (no on-chain execution)
-    General,
-    /// Treasury withdrawal proposal
-    TreasuryWithdrawal,
-    /// Parameter change proposal (requires authority execution)
-    ParameterChange,
-}
+        // Check not already executed
+        require!(
+            self.executed_at.is_none(),
+            DaoError::ProposalAlreadyExecuted
+        );
 
-/// Proposal status
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum ProposalStatus {
-    /// Voting is active
-    Active,
-    /// Proposal passed (quorum met + approval threshold met)
-    Passed,
-    /// Proposal defeated (quorum not met or approval threshold not met)
-    Defeated,
-    /// Proposal executed
-    Executed,
-    /// Proposal cancelled by proposer
-    Cancelled,
-}
+        // Per whitepaper: Check 3-day execution timelock has elapsed
+        require!(
+            now >= self.execution_eligible_at,
+            DaoError::ExecutionTimelockNotExpired
+        );
+        require!(
+            !self.execution_expired(now),
+            DaoError::ExecutionWindowClosed
+        );
 
-/// Vote choices
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
-pub enum VoteChoice {
-    For,
-    Against,
-    Abstain,
-}
+        // Check proposal type allows execution
+        require!(
+            matches!(
+                self.proposal_type,
+                ProposalType::TreasuryWithdrawal | ProposalType::ParameterChange
+            ),
+            DaoError::ProposalNotExecutable
+        );
 
-// ============================================================================
-// ACCOUNT CONTEXTS
-// ============================================================================
+        // Get execution data
+        let execution_data = self
+            .execution_data
+            .clone()
+            .ok_or(DaoError::NoExecutionData)?;
+        execution_data.validate(self.proposal_type)?;
+        Ok(execution_data)
+    }
 
-/// Initialize DAO configuration
-#[derive(Accounts)]
-pub struct InitializeDao<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = DaoConfig::MAX_SIZE,
-        seeds = [b"dao_config"],
-        bump
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    /// Y7.2: Appeal of this defeated proposal with a 1.5x voting period
+    ///
+    /// Voting opens immediately and the appeal keeps the original's supply and
+    /// treasury snapshots, since it decides the same question.
+    pub fn appeal(
+        &self,
+        appeal_id: u64,
+        appellant: Pubkey,
+        dao_config: &DaoConfig,
+        now: i64,
+        bump: u8,
+    ) -> Result<Proposal> {
+        let extended_duration = dao_config.voting_period
+            .checked_mul(3)
+            .ok_or(DaoError::Overflow)?
+            .checked_div(2)
+            .ok_or(DaoError::Overflow)?;
+        let vote_end = now.checked_add(extended_duration).ok_or(DaoError::Overflow)?;
+        // Execution eligible after extended voting + timelock (3 days per whitepaper)
+        let execution_eligible_at = vote_end
+            .checked_add(EXECUTION_TIMELOCK)
+            .ok_or(DaoError::Overflow)?;
 
-    /// Treasury token account (must be owned by DAO PDA)
-    #[account(
-        mut,
-        constraint = treasury.mint == governance_token_mint.key() @ DaoError::InvalidMint,
-        constraint = treasury.owner == dao_config.key() @ DaoError::InvalidTreasuryOwner
-    )]
-    pub treasury: Account<'info, TokenAccount>,
+        Ok(Proposal {
+            proposal_id: appeal_id,
+            proposer: appellant,
+            title: format!("APPEAL: {}", self.title),
+            description_cid: self.description_cid.clone(),
+            proposal_type: self.proposal_type,
+            execution_data: self.execution_data.clone(),
+            status: ProposalStatus::Active,
+            for_votes: 0,
+            against_votes: 0,
+            abstain_votes: 0,
+            vote_start: now,
+            vote_end,
+            execution_eligible_at,
+            created_at: now,
+            executed_at: None,
+            bond_returned: false,
+            snapshot_supply: self.snapshot_supply,
+            bump,
+            quorum_percentage: dao_config.effective_quorum_percentage(),
+            // Same withdrawal as the original, so it keeps the original's cap
+            treasury_balance_snapshot: self.treasury_balance_snapshot,
+            max_withdrawal_bps: self.max_withdrawal_bps,
+            tags: self.tags.clone(),
+            appeal_proposal_id: None,
+            // The appellant posts the appeal bond
+            sponsored: false,
+            bond_target: 0,
+            bond_raised: 0,
+            funding_deadline: 0,
+            finalized_by: Pubkey::default(),
+            finalized_at: 0,
+            executed_by: Pubkey::default(),
+            voting_mode: self.voting_mode,
+            last_checkpoint_at: 0,
+            voting_model: self.voting_model,
+            approval_threshold: self.urgency.approval_threshold(dao_config.approval_threshold),
+            bond_amount: appeal_bond(dao_config.proposal_bond)?,
+            discussion_period: 0,
+            voting_period: extended_duration,
+            execution_window: EXECUTION_WINDOW,
+            urgency: self.urgency,
+            conviction_schedule: dao_config.conviction_schedule.clone(),
+        })
+    }
 
-    /// Bond escrow token account (must be owned by DAO PDA)
-    #[account(
-        mut,
-        constraint = bond_escrow.mint == governance_token_mint.key() @ DaoError::InvalidMint,
-        constraint = bond_escrow.owner == dao_config.key() @ DaoError::InvalidBondEscrowOwner
-    )]
-    pub bond_escrow: Account<'info, TokenAccount>,
+    /// Y7.1: Bond refund for a finalized proposal and its tier
+    ///
+    /// - Passed/Executed: 100% bond returned, as for withdrawals that passed
+    ///   but went unfunded, expired, or were cancelled after passing
+    /// - Defeated with ≥50% quorum participation: 50% bond returned
+    /// - Defeated with <50% quorum participation, or cancelled: forfeited
+    pub fn bond_return(&self, full_bond: u64) -> Result<(u64, &'static str)> {
+        // Only cancel_passed_withdrawal cancels a finalized proposal
+        let cancelled_after_passing = self.status == ProposalStatus::Cancelled && self.finalized_at != 0;
+        let (return_amount, return_type) = if self.status == ProposalStatus::Passed
+            || self.status == ProposalStatus::Executed
+            || self.status == ProposalStatus::PassedUnfunded
+            || self.status == ProposalStatus::Expired
+            || cancelled_after_passing
+        {
+            // Full bond return for successful proposals
+            (full_bond, "full")
+        } else if self.status == ProposalStatus::Defeated {
+            // Y7.1: Check if proposal achieved partial quorum for partial return
+            let total_votes = self.for_votes
+                .checked_add(self.against_votes)
+                .ok_or(DaoError::Overflow)?;
 
-    /// SECURITY FIX: Vote vault token account for escrowing vote tokens (must be owned by DAO PDA)
-    #[account(
-        mut,
-        constraint = vote_vault.mint == governance_token_mint.key() @ DaoError::InvalidMint,
-        constraint = vote_vault.owner == dao_config.key() @ DaoError::InvalidVoteVaultOwner
-    )]
-    pub vote_vault: Account<'info, TokenAccount>,
+            // Calculate what % of quorum was achieved
+            let quorum_required = self.quorum_percentage as u64;
+            let quorum_achieved_percentage = if quorum_required > 0 {
+                (total_votes as u128)
+                    .checked_mul(100)
+                    .ok_or(DaoError::Overflow)?
+                    .checked_div(quorum_required as u128)
+                    .ok_or(DaoError::Overflow)? as u64
+            } else {
+                0
+            };
 
-    /// Governance token mint ($AEGIS)
-    pub governance_token_mint: Account<'info, Mint>,
+            if quorum_achieved_percentage >= PARTIAL_BOND_QUORUM_THRESHOLD as u64 {
+                // Partial bond return (50%) for proposals with significant participation
+                msg!(
+                    "Y7.1: Partial bond return - proposal achieved {}% of quorum",
+                    quorum_achieved_percentage
+                );
+                let partial = full_bond
+                    .checked_mul(PARTIAL_BOND_RETURN_PERCENTAGE)
+                    .ok_or(DaoError::Overflow)?
+                    .checked_div(100)
+                    .ok_or(DaoError::Overflow)?;
+                (partial, "partial")
+            } else {
+                // No bond return - insufficient participation
+                return Err(DaoError::BondForfeited.into());
+            }
+        } else {
+            // Cancelled or other status - no return
+            return Err(DaoError::BondForfeited.into());
+        };
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        require!(return_amount > 0, DaoError::BondForfeited);
+        Ok((return_amount, return_type))
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// Sponsor funding window has closed without the bond being covered
+    pub fn funding_lapsed(&self, now: i64) -> bool {
+        self.status == ProposalStatus::Pending && now > self.funding_deadline
+    }
 
-/// SECURITY FIX (X1.4 + X6): Close DAO configuration (for migration/cleanup)
-///
-/// Security measures:
-/// 1. PDA validation via seeds constraint (prevents closing arbitrary accounts)
-/// 2. Program ownership verified via Anchor's Account<DaoConfig> type
-/// 3. Authority validated via has_one constraint (no raw byte manipulation)
-/// 4. Account properly closed via Anchor's close constraint
-#[derive(Accounts)]
-pub struct CloseDaoConfig<'info> {
-    /// 🔒 SECURITY FIX (X6): Using Account<DaoConfig> instead of AccountInfo
-    /// This provides:
-    /// - Automatic program ownership verification
-    /// - Proper account deserialization
-    /// - Type-safe authority field access
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = authority @ DaoError::UnauthorizedAuthority,
-        close = authority
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    /// Add a sponsor contribution, activating the proposal once the bond is
+    /// covered; returns the accepted amount (capped at the remaining bond)
+    pub fn record_sponsorship(&mut self, amount: u64, now: i64) -> Result<u64> {
+        require!(
+            self.status == ProposalStatus::Pending,
+            DaoError::ProposalNotPending
+        );
+        require!(now <= self.funding_deadline, DaoError::FundingWindowClosed);
 
-    /// Authority is validated via has_one constraint on dao_config
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        let remaining = self
+            .bond_target
+            .checked_sub(self.bond_raised)
+            .ok_or(DaoError::Overflow)?;
+        let accepted = amount.min(remaining);
+        require!(accepted > 0, DaoError::InvalidAmount);
+        self.bond_raised = self
+            .bond_raised
+            .checked_add(accepted)
+            .ok_or(DaoError::Overflow)?;
 
-    pub system_program: Program<'info, System>,
-}
+        if self.bond_raised == self.bond_target {
+            // Vote timeline starts at activation, not creation
+            self.status = ProposalStatus::Active;
+            self.vote_start = now
+                .checked_add(self.discussion_period)
+                .ok_or(DaoError::Overflow)?;
+            self.vote_end = self
+                .vote_start
+                .checked_add(self.voting_period)
+                .ok_or(DaoError::Overflow)?;
+            self.execution_eligible_at = self
+                .vote_end
+                .checked_add(EXECUTION_TIMELOCK)
+                .ok_or(DaoError::Overflow)?;
+        }
+        Ok(accepted)
+    }
 
-/// Queue DAO configuration update (with timelock)
-#[derive(Accounts)]
-pub struct QueueConfigUpdate<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = authority @ DaoError::UnauthorizedAuthority
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    /// Refund owed to a sponsor who contributed `contribution`
+    ///
+    /// Cancelled and lapsed proposals refund in full; finalized proposals
+    /// refund this sponsor's pro-rata share of the `bond_return` tier.
+    pub fn sponsor_refund(&self, contribution: u64, now: i64) -> Result<u64> {
+        require!(self.sponsored, DaoError::NotSponsored);
+        match self.status {
+            ProposalStatus::Cancelled => Ok(contribution),
+            ProposalStatus::Pending => {
+                require!(self.funding_lapsed(now), DaoError::FundingWindowOpen);
+                Ok(contribution)
+            }
+            ProposalStatus::Active => Err(DaoError::ProposalStillActive.into()),
+            _ => {
+                let (tier_amount, _) = self.bond_return(self.bond_target)?;
+                Ok((tier_amount as u128)
+                    .checked_mul(contribution as u128)
+                    .ok_or(DaoError::Overflow)?
+                    .checked_div(self.bond_target as u128)
+                    .ok_or(DaoError::Overflow)? as u64)
+            }
+        }
+    }
 
-    pub authority: Signer<'info>,
+    /// Record a tally checkpoint at `now` and build its event
+    ///
+    /// Only active proposals can be checkpointed, at most once per
+    /// CHECKPOINT_INTERVAL. Participation counts abstain votes, as quorum does.
+    pub fn checkpoint(&mut self, now: i64) -> Result<ProposalTallyCheckpointEvent> {
+        require!(
+            self.status == ProposalStatus::Active,
+            DaoError::ProposalNotActive
+        );
+        require!(
+            self.last_checkpoint_at == 0
+                || now >= self.last_checkpoint_at.saturating_add(CHECKPOINT_INTERVAL),
+            DaoError::CheckpointTooSoon
+        );
+
+        let total_participation = self
+            .for_votes
+            .checked_add(self.against_votes)
+            .ok_or(DaoError::Overflow)?
+            .checked_add(self.abstain_votes)
+            .ok_or(DaoError::Overflow)?;
+        let participation_pct = if self.snapshot_supply > 0 {
+            ((total_participation as u128)
+                .checked_mul(100)
+                .ok_or(DaoError::Overflow)?
+                / self.vote_supply() as u128)
+                .min(100) as u8
+        } else {
+            0
+        };
+
+        self.last_checkpoint_at = now;
+        Ok(ProposalTallyCheckpointEvent {
+            proposal_id: self.proposal_id,
+            for_votes: self.for_votes,
+            against_votes: self.against_votes,
+            abstain_votes: self.abstain_votes,
+            participation_pct,
+            time_remaining: self.vote_end.saturating_sub(now).max(0),
+            timestamp: now,
+        })
+    }
+
+    /// A passed, unexecuted emergency proposal created after the change queued at `queued_at`
+    pub fn authorizes_emergency_cancel(&self, queued_at: i64, now: i64) -> bool {
+        self.proposal_type == ProposalType::EmergencyConfigCancel
+            && self.status == ProposalStatus::Passed
+            && self.executed_at.is_none()
+            && self.created_at >= queued_at
+            && now >= self.execution_eligible_at
+    }
+
+    /// Amount a treasury withdrawal proposal transfers (None for other types)
+    pub fn withdrawal_amount(&self) -> Option<u64> {
+        match (&self.proposal_type, &self.execution_data) {
+            // The total is checked when the proposal is created
+            (ProposalType::TreasuryWithdrawal, Some(ExecutionData::TreasuryWithdrawal { payouts })) => {
+                payout_total(payouts).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// The execution window after the timelock has closed
+    pub fn execution_expired(&self, now: i64) -> bool {
+        now > self.execution_eligible_at.saturating_add(self.execution_window)
+    }
+
+    /// Record a passing vote: Passed, or PassedUnfunded for a withdrawal the
+    /// treasury can't cover on top of `committed_outflow`
+    pub fn settle_passed(&mut self, dao_config: &mut DaoConfig, treasury_balance: u64) -> Result<()> {
+        self.status = match self.withdrawal_amount() {
+            Some(amount) if !dao_config.try_commit_outflow(amount, treasury_balance)? => {
+                ProposalStatus::PassedUnfunded
+            }
+            _ => ProposalStatus::Passed,
+        };
+        Ok(())
+    }
+
+    /// Commit the treasury to a PassedUnfunded withdrawal; returns the amount
+    pub fn fund(&mut self, dao_config: &mut DaoConfig, treasury_balance: u64, now: i64) -> Result<u64> {
+        require!(
+            self.status == ProposalStatus::PassedUnfunded,
+            DaoError::ProposalNotUnfunded
+        );
+        require!(!self.execution_expired(now), DaoError::ExecutionWindowClosed);
+        let amount = self.withdrawal_amount().ok_or(DaoError::NoExecutionData)?;
+        require!(
+            dao_config.try_commit_outflow(amount, treasury_balance)?,
+            DaoError::TreasuryOvercommitted
+        );
+        self.status = ProposalStatus::Passed;
+        Ok(amount)
+    }
+
+    /// Expire a passed withdrawal whose execution window has closed;
+    /// returns the commitment released
+    pub fn expire(&mut self, dao_config: &mut DaoConfig, now: i64) -> Result<u64> {
+        let amount = self.withdrawal_amount().ok_or(DaoError::ProposalNotExecutable)?;
+        require!(
+            self.status == ProposalStatus::Passed || self.status == ProposalStatus::PassedUnfunded,
+            DaoError::ProposalNotPassed
+        );
+        require!(self.execution_expired(now), DaoError::ExecutionWindowOpen);
+        let released = self.release_commitment(dao_config, amount);
+        self.status = ProposalStatus::Expired;
+        Ok(released)
+    }
+
+    /// Cancel a passed, unexecuted withdrawal; returns the commitment released
+    pub fn cancel_passed_withdrawal(&mut self, dao_config: &mut DaoConfig) -> Result<u64> {
+        let amount = self.withdrawal_amount().ok_or(DaoError::ProposalNotExecutable)?;
+        require!(
+            self.status == ProposalStatus::Passed || self.status == ProposalStatus::PassedUnfunded,
+            DaoError::ProposalNotPassed
+        );
+        let released = self.release_commitment(dao_config, amount);
+        self.status = ProposalStatus::Cancelled;
+        Ok(released)
+    }
+
+    /// Release this proposal's reservation (only Passed withdrawals hold one)
+    fn release_commitment(&self, dao_config: &mut DaoConfig, amount: u64) -> u64 {
+        if self.status != ProposalStatus::Passed {
+            return 0;
+        }
+        dao_config.release_outflow(amount);
+        amount
+    }
 }
 
-/// Execute queued configuration update
-#[derive(Accounts)]
-pub struct ExecuteConfigUpdate<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = authority @ DaoError::UnauthorizedAuthority
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+/// Tokens locked against a pending config change
+#[account]
+pub struct ConfigObjection {
+    /// Holder who locked the tokens
+    pub objector: Pubkey,
+    /// queued_at of the config change objected to
+    pub queued_at: i64,
+    /// Tokens locked in the vote vault
+    pub amount: u64,
+    /// PDA bump
+    pub bump: u8,
+}
 
-    pub authority: Signer<'info>,
+impl ConfigObjection {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                         // objector
+        8 +                          // queued_at
+        8 +                          // amount
+        1; // bump
 }
 
-/// Cancel queued configuration update
+/// One sponsor's contribution toward a sponsored proposal's bond
+#[account]
+pub struct ProposalSponsorship {
+    /// Proposal ID
+    pub proposal_id: u64,
+    /// Token holder who contributed
+    pub sponsor: Pubkey,
+    /// Tokens held in the bond escrow for this sponsor
+    pub amount: u64,
+    /// Whether the refund has been claimed
+    pub refunded: bool,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ProposalSponsorship {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        8 +                          // proposal_id
+        32 +                         // sponsor
+        8 +                          // amount
+        1 +                          // refunded
+        1; // bump
+}
+
+/// SECURITY FIX: Vote escrow account - tracks deposited tokens for voting
+/// Replaces the vulnerable VoteSnapshot that only recorded balance
+#[account]
+pub struct VoteEscrow {
+    /// Proposal ID
+    pub proposal_id: u64,
+    /// Voter's public key
+    pub voter: Pubkey,
+    /// Amount of tokens deposited (escrowed)
+    pub deposited_amount: u64,
+    /// When tokens were deposited
+    pub deposited_at: i64,
+    /// Whether the voter has cast their vote
+    pub has_voted: bool,
+    /// Vote choice (if voted)
+    pub vote_choice: Option<VoteChoice>,
+    /// Whether tokens have been withdrawn
+    pub withdrawn: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Highest nonce of a signed vote relayed with this escrow
+    pub vote_nonce: u64,
+}
+
+impl VoteEscrow {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        8 +                          // proposal_id
+        32 +                         // voter
+        8 +                          // deposited_amount
+        8 +                          // deposited_at
+        1 +                          // has_voted
+        1 + 1 +                      // vote_choice (Option<enum>)
+        1 +                          // withdrawn
+        1 +                          // bump
+        8; // vote_nonce
+
+    /// Size of escrows created before vote_nonce was added
+    pub const LEGACY_SIZE: usize = Self::MAX_SIZE - 8;
+
+    /// Accept a signed vote's nonce, rejecting expired and replayed messages
+    pub fn accept_signed_vote(&mut self, nonce: u64, expires_at: i64, now: i64) -> Result<()> {
+        require!(now <= expires_at, DaoError::SignedVoteExpired);
+        require!(nonce > self.vote_nonce, DaoError::SignedVoteReplayed);
+        self.vote_nonce = nonce;
+        Ok(())
+    }
+}
+
+/// Vote record for a single voter on a proposal
+#[account]
+pub struct VoteRecord {
+    /// Proposal ID
+    pub proposal_id: u64,
+    /// Voter's public key
+    pub voter: Pubkey,
+    /// Vote choice
+    pub vote_choice: VoteChoice,
+    /// Vote weight (escrowed amount boosted by the conviction multiplier)
+    pub vote_weight: u64,
+    /// When the vote was cast
+    pub voted_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Conviction multiplier applied to the escrowed amount (basis points)
+    pub multiplier_bps: u16,
+    /// Token balance read for a snapshot vote (0 for escrow votes)
+    pub snapshot_balance: u64,
+    /// Delegator whose escrow or position the vote also used (default if none)
+    pub delegator: Pubkey,
+    /// Part of vote_weight delegated by `delegator`
+    pub delegated_weight: u64,
+    /// The delegated weight came from the delegator's position, not an escrow
+    pub delegated_from_position: bool,
+}
+
+impl VoteRecord {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        8 +                          // proposal_id
+        32 +                         // voter
+        1 +                          // vote_choice
+        8 +                          // vote_weight
+        8 +                          // voted_at
+        1 +                          // bump
+        2 +                          // multiplier_bps
+        8 +                          // snapshot_balance
+        32 +                         // delegator
+        8 +                          // delegated_weight
+        1; // delegated_from_position
+}
+
+/// Delegation of a wallet's escrowed voting power to a delegate
+/// Seeds: ["delegation", delegator, delegate]
+#[account]
+pub struct VoteDelegation {
+    /// Wallet whose escrows the delegate may vote with
+    pub delegator: Pubkey,
+    /// Wallet voting on the delegator's behalf
+    pub delegate: Pubkey,
+    /// When the delegation was made
+    pub delegated_at: i64,
+    /// When the delegation was revoked (0 while active)
+    pub revoked_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl VoteDelegation {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                         // delegator
+        32 +                         // delegate
+        8 +                          // delegated_at
+        8 +                          // revoked_at
+        1; // bump
+
+    /// Whether the delegation applies to a proposal created at `created_at`:
+    /// made no later than the proposal and not revoked by then
+    pub fn is_effective_for(&self, created_at: i64) -> bool {
+        self.delegated_at <= created_at && (self.revoked_at == 0 || self.revoked_at > created_at)
+    }
+}
+
+/// A proposal a voter position has a counted vote on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionVote {
+    pub proposal_id: u64,
+    /// The proposal's vote_end; the position is locked until it passes
+    pub vote_end: i64,
+}
+
+/// One deposit that votes on any number of overlapping proposals
+/// Seeds: ["voter_position", voter]
+#[account]
+pub struct VoterPosition {
+    /// Owner of the deposit
+    pub voter: Pubkey,
+    /// Tokens held in the vote vault for this position
+    pub deposited_amount: u64,
+    /// Latest deposit time, for the conviction multiplier
+    pub deposited_at: i64,
+    /// Proposals with a counted vote whose voting may not have ended
+    pub votes: Vec<PositionVote>,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl VoterPosition {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                         // voter
+        8 +                          // deposited_amount
+        8 +                          // deposited_at
+        4 + MAX_POSITION_VOTES * (8 + 8) + // votes (Vec<PositionVote>)
+        1; // bump
+
+    /// Set the owner of a position just created by `init_if_needed`
+    pub fn init_if_new(&mut self, voter: Pubkey, bump: u8) {
+        if self.voter == Pubkey::default() {
+            self.voter = voter;
+            self.bump = bump;
+        }
+    }
+
+    /// Add `amount` deposited at `deposited_at`
+    ///
+    /// The position counts as deposited at the latest deposit, so a late
+    /// top-up can't borrow an earlier deposit's conviction.
+    pub fn add_deposit(&mut self, amount: u64, deposited_at: i64) -> Result<()> {
+        self.deposited_at = if self.deposited_amount == 0 {
+            deposited_at
+        } else {
+            self.deposited_at.max(deposited_at)
+        };
+        self.deposited_amount = self
+            .deposited_amount
+            .checked_add(amount)
+            .ok_or(DaoError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn has_voted_on(&self, proposal_id: u64) -> bool {
+        self.votes.iter().any(|v| v.proposal_id == proposal_id)
+    }
+
+    /// Lock the position until `vote_end` for a vote on `proposal_id`
+    ///
+    /// Votes whose voting has ended no longer lock anything and are dropped
+    /// first to make room.
+    pub fn record_vote(&mut self, proposal_id: u64, vote_end: i64, now: i64) -> Result<()> {
+        self.votes.retain(|v| v.vote_end >= now);
+        require!(!self.has_voted_on(proposal_id), DaoError::AlreadyVoted);
+        require!(
+            self.votes.len() < MAX_POSITION_VOTES,
+            DaoError::PositionVoteLimitReached
+        );
+        self.votes.push(PositionVote { proposal_id, vote_end });
+        Ok(())
+    }
+
+    /// Drop the lock of a retracted vote
+    pub fn release_vote(&mut self, proposal_id: u64) -> Result<()> {
+        let index = self
+            .votes
+            .iter()
+            .position(|v| v.proposal_id == proposal_id)
+            .ok_or(DaoError::NotVoted)?;
+        self.votes.remove(index);
+        Ok(())
+    }
+
+    /// Require voting to have ended on every proposal the position voted on
+    pub fn ensure_unlocked(&self, now: i64) -> Result<()> {
+        require!(
+            self.votes.iter().all(|v| now > v.vote_end),
+            DaoError::TokensLockedDuringVoting
+        );
+        Ok(())
+    }
+}
+
+/// What an executable proposal does when executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ExecutionData {
+    /// Transfer each payout from the treasury (1 to MAX_WITHDRAWAL_RECIPIENTS)
+    TreasuryWithdrawal { payouts: Vec<WithdrawalPayout> },
+    /// Set DaoConfig parameters (None keeps the current value)
+    ParameterChange {
+        new_voting_period: Option<i64>,
+        new_quorum: Option<u8>,
+        new_threshold: Option<u8>,
+        new_bond: Option<u64>,
+    },
+}
+
+impl ExecutionData {
+    // Variant tag plus the larger variant: TreasuryWithdrawal (4 + 8 * 40)
+    // outweighs ParameterChange (9 + 2 + 2 + 9)
+    pub const MAX_SIZE: usize =
+        1 + 4 + WithdrawalPayout::MAX_SIZE * MAX_WITHDRAWAL_RECIPIENTS;
+
+    /// Check the data matches `proposal_type`, a withdrawal pays 1 to
+    /// MAX_WITHDRAWAL_RECIPIENTS distinct recipients nonzero amounts, and a
+    /// parameter change sets at least one parameter, each within its allowed range
+    pub fn validate(&self, proposal_type: ProposalType) -> Result<()> {
+        match (self, proposal_type) {
+            (ExecutionData::TreasuryWithdrawal { payouts }, ProposalType::TreasuryWithdrawal) => {
+                require!(
+                    !payouts.is_empty() && payouts.len() <= MAX_WITHDRAWAL_RECIPIENTS,
+                    DaoError::InvalidWithdrawalPayouts
+                );
+                for (i, payout) in payouts.iter().enumerate() {
+                    require!(payout.amount > 0, DaoError::InvalidWithdrawalPayouts);
+                    require!(
+                        !payouts[..i].iter().any(|prev| prev.recipient == payout.recipient),
+                        DaoError::DuplicateWithdrawalRecipient
+                    );
+                }
+                payout_total(payouts).map(|_| ())
+            }
+            (
+                ExecutionData::ParameterChange {
+                    new_voting_period,
+                    new_quorum,
+                    new_threshold,
+                    new_bond,
+                },
+                ProposalType::ParameterChange,
+            ) => {
+                require!(
+                    new_voting_period.is_some()
+                        || new_quorum.is_some()
+                        || new_threshold.is_some()
+                        || new_bond.is_some(),
+                    DaoError::EmptyParameterChange
+                );
+                validate_governance_parameters(*new_voting_period, *new_quorum, *new_threshold, *new_bond)
+            }
+            _ => err!(DaoError::ExecutionDataMismatch),
+        }
+    }
+}
+
+/// One recipient of a treasury withdrawal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawalPayout {
+    /// Token account receiving the payout
+    pub recipient: Pubkey,
+    /// Amount to transfer
+    pub amount: u64,
+}
+
+impl WithdrawalPayout {
+    pub const MAX_SIZE: usize = 32 + 8;
+}
+
+/// Proposal types
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalType {
+    /// General governance proposal (no on-chain execution)
+    General,
+    /// Treasury withdrawal proposal
+    TreasuryWithdrawal,
+    /// Parameter change proposal (applied to DaoConfig by execute_proposal)
+    ParameterChange,
+    /// Cancel the pending config change (24h vote, no discussion or execution delay)
+    EmergencyConfigCancel,
+}
+
+/// Proposal status
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// Voting is active
+    Active,
+    /// Proposal passed (quorum met + approval threshold met)
+    Passed,
+    /// Proposal defeated (quorum not met or approval threshold not met)
+    Defeated,
+    /// Proposal executed
+    Executed,
+    /// Proposal cancelled by proposer
+    Cancelled,
+    /// Sponsored proposal raising its bond (voting timeline not started)
+    Pending,
+    /// Withdrawal passed, but the treasury couldn't cover it alongside the
+    /// withdrawals already committed (awaiting `fund_passed_proposal`)
+    PassedUnfunded,
+    /// Passed withdrawal not executed within EXECUTION_WINDOW
+    Expired,
+}
+
+/// Vote choices
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+/// How a proposal's votes are weighted
+///
+/// Snapshot voting reads the voter's balance at vote time instead of locking
+/// tokens, so borrowed tokens can vote and be returned in the same
+/// transaction. That flash-loan exposure is accepted for non-binding General
+/// proposals only, and each snapshot vote is capped at
+/// max_snapshot_vote_bps of the snapshot supply.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VotingMode {
+    /// Weight from tokens locked in a VoteEscrow (conviction-boosted)
+    Escrow,
+    /// Weight from the voter's current token balance (capped)
+    Snapshot,
+}
+
+/// How token amounts become vote weight
+///
+/// Quadratic voting counts the square root of the tokens behind a vote, so
+/// doubling a vote's weight takes four times the tokens. Quorum and turnout
+/// are measured against the square root of the snapshot supply to match.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VotingModel {
+    /// One token, one vote
+    TokenWeighted,
+    /// Square root of the tokens (rounded down)
+    Quadratic,
+}
+
+impl VotingModel {
+    /// Vote weight of `tokens` under this model, before any conviction boost
+    pub fn vote_power(self, tokens: u64) -> u64 {
+        match self {
+            VotingModel::TokenWeighted => tokens,
+            VotingModel::Quadratic => integer_sqrt(tokens),
+        }
+    }
+}
+
+/// How quickly a proposal goes to a vote
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalUrgency {
+    /// Voting opens after the discussion period
+    Standard,
+    /// Voting opens immediately, for EMERGENCY_BOND_MULTIPLIER times the
+    /// bond and at least EMERGENCY_APPROVAL_THRESHOLD approval
+    Emergency,
+}
+
+impl ProposalUrgency {
+    /// Bond for a proposal of this urgency
+    pub fn bond(self, proposal_bond: u64) -> Result<u64> {
+        match self {
+            ProposalUrgency::Standard => Ok(proposal_bond),
+            ProposalUrgency::Emergency => Ok(proposal_bond
+                .checked_mul(EMERGENCY_BOND_MULTIPLIER)
+                .ok_or(DaoError::Overflow)?),
+        }
+    }
+
+    /// Discussion period before voting opens
+    pub fn discussion_period(self, discussion_period: i64) -> i64 {
+        match self {
+            ProposalUrgency::Standard => discussion_period,
+            ProposalUrgency::Emergency => 0,
+        }
+    }
+
+    /// Approval threshold, never below the DAO's own
+    pub fn approval_threshold(self, approval_threshold: u8) -> u8 {
+        match self {
+            ProposalUrgency::Standard => approval_threshold,
+            ProposalUrgency::Emergency => approval_threshold.max(EMERGENCY_APPROVAL_THRESHOLD),
+        }
+    }
+}
+
+// ============================================================================
+// ACCOUNT CONTEXTS
+// ============================================================================
+
+/// Initialize DAO configuration
 #[derive(Accounts)]
-pub struct CancelConfigUpdate<'info> {
+pub struct InitializeDao<'info> {
     #[account(
-        mut,
+        init,
+        payer = authority,
+        space = DaoConfig::MAX_SIZE,
         seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = authority @ DaoError::UnauthorizedAuthority
+        bump
     )]
     pub dao_config: Account<'info, DaoConfig>,
 
-    pub authority: Signer<'info>,
-}
+    /// Treasury token account (must be owned by DAO PDA)
+    #[account(
+        mut,
+        constraint = treasury.mint == governance_token_mint.key() @ DaoError::InvalidMint,
+        constraint = treasury.owner == dao_config.key() @ DaoError::InvalidTreasuryOwner
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Bond escrow token account (must be owned by DAO PDA)
+    #[account(
+        mut,
+        constraint = bond_escrow.mint == governance_token_mint.key() @ DaoError::InvalidMint,
+        constraint = bond_escrow.owner == dao_config.key() @ DaoError::InvalidBondEscrowOwner
+    )]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// SECURITY FIX: Vote vault token account for escrowing vote tokens (must be owned by DAO PDA)
+    #[account(
+        mut,
+        constraint = vote_vault.mint == governance_token_mint.key() @ DaoError::InvalidMint,
+        constraint = vote_vault.owner == dao_config.key() @ DaoError::InvalidVoteVaultOwner
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Governance token mint ($AEGIS)
+    pub governance_token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// SECURITY FIX (X1.4 + X6): Close DAO configuration (for migration/cleanup)
+///
+/// Security measures:
+/// 1. PDA validation via seeds constraint (prevents closing arbitrary accounts)
+/// 2. Program ownership verified via Anchor's Account<DaoConfig> type
+/// 3. Authority validated via has_one constraint (no raw byte manipulation)
+/// 4. Account properly closed via Anchor's close constraint
+#[derive(Accounts)]
+pub struct CloseDaoConfig<'info> {
+    /// 🔒 SECURITY FIX (X6): Using Account<DaoConfig> instead of AccountInfo
+    /// This provides:
+    /// - Automatic program ownership verification
+    /// - Proper account deserialization
+    /// - Type-safe authority field access
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority,
+        close = authority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    /// Authority is validated via has_one constraint on dao_config
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Migrate the DAO config to the current layout
+#[derive(Accounts)]
+pub struct MigrateDaoConfig<'info> {
+    /// CHECK: Older layouts can't be decoded as Account<DaoConfig>; the PDA
+    /// and owner are checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub dao_config: UncheckedAccount<'info>,
+
+    /// Must match dao_config.authority; pays any extra rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Queue DAO configuration update (with timelock)
+#[derive(Accounts)]
+pub struct QueueConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Execute queued configuration update
+#[derive(Accounts)]
+pub struct ExecuteConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Cancel queued configuration update
+#[derive(Accounts)]
+pub struct CancelConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Set the guardian (once)
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Cancel a pending config change as guardian or via an emergency proposal
+#[derive(Accounts)]
+pub struct EmergencyCancelConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    /// Passed EmergencyConfigCancel proposal (not needed when the guardian signs)
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+
+    /// Guardian, or anyone when a passed proposal is supplied
+    pub canceller: Signer<'info>,
+}
+
+/// Lock tokens against a pending config change
+#[derive(Accounts)]
+#[instruction(queued_at: i64)]
+pub struct ObjectToConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault,
+        has_one = governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    /// Governance token mint (supply for the veto threshold)
+    pub governance_token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = objector,
+        space = ConfigObjection::MAX_SIZE,
+        seeds = [b"config_objection", queued_at.to_le_bytes().as_ref(), objector.key().as_ref()],
+        bump
+    )]
+    pub config_objection: Account<'info, ConfigObjection>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(mut)]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Objector's token account
+    #[account(
+        mut,
+        constraint = objector_token_account.owner == objector.key() @ DaoError::InvalidTokenOwner,
+        constraint = objector_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub objector_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub objector: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdraw objection tokens
+#[derive(Accounts)]
+pub struct WithdrawObjection<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"config_objection",
+            config_objection.queued_at.to_le_bytes().as_ref(),
+            objector.key().as_ref()
+        ],
+        bump = config_objection.bump,
+        has_one = objector @ DaoError::InvalidVoter,
+        close = objector
+    )]
+    pub config_objection: Account<'info, ConfigObjection>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(mut)]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Objector's token account
+    #[account(
+        mut,
+        constraint = objector_token_account.owner == objector.key() @ DaoError::InvalidTokenOwner,
+        constraint = objector_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub objector_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub objector: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Set DAO paused status
+#[derive(Accounts)]
+pub struct SetDaoPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = authority @ DaoError::UnauthorizedAuthority
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Arguments of create_proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreateProposalParams {
+    pub title: String,
+    /// IPFS CID of the full proposal text
+    pub description_cid: String,
+    pub proposal_type: ProposalType,
+    /// Required for ParameterChange; validated against proposal_type
+    pub execution_data: Option<ExecutionData>,
+    /// Up to MAX_PROPOSAL_TAGS lowercase alphanumeric tags
+    pub tags: Vec<String>,
+    /// Proposer posts the full bond (false waits for sponsors)
+    pub self_funded: bool,
+    pub urgency: ProposalUrgency,
+}
+
+/// Create a new proposal
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow,
+        has_one = governance_token_mint @ DaoError::InvalidMint,
+        has_one = treasury @ DaoError::InvalidTreasury
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::MAX_SIZE,
+        seeds = [b"proposal", (dao_config.proposal_count + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Bond escrow account (PDA-owned)
+    #[account(
+        mut,
+        constraint = bond_escrow.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Governance token mint (mutable: the proposal fee is partially burned)
+    #[account(mut)]
+    pub governance_token_mint: Account<'info, Mint>,
+
+    /// Proposer's token account
+    #[account(
+        mut,
+        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
+        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    /// DAO treasury (receives the non-burned part of the proposal fee)
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Token program config for the governance mint (holds fee_burn_bps)
+    #[account(
+        mut,
+        constraint = token_config.mint == dao_config.governance_token_mint @ DaoError::InvalidTokenConfig
+    )]
+    pub token_config: Account<'info, aegis_token::TokenConfig>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub aegis_token_program: Program<'info, aegis_token::program::AegisToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Cancel a proposal
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ DaoError::NotProposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Bond escrow account
+    #[account(mut)]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Proposer's token account
+    #[account(
+        mut,
+        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
+        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    pub proposer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// SECURITY FIX: Deposit vote tokens (Vote Escrow pattern)
+#[derive(Accounts)]
+pub struct DepositVoteTokens<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = VoteEscrow::MAX_SIZE,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(
+        mut,
+        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Voter's token account
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
+        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// SECURITY FIX: Cast a vote (using escrowed tokens)
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Required for Escrow-mode proposals
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump = vote_escrow.bump,
+        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
+        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal,
+        constraint = !vote_escrow.withdrawn @ DaoError::AlreadyWithdrawn
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// Voter's token account, read for Snapshot-mode proposals
+    #[account(
+        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
+        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub voter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Delegation to the voter, when also voting with a delegator's escrow or position
+    #[account(
+        seeds = [b"delegation", delegation.delegator.as_ref(), voter.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Option<Account<'info, VoteDelegation>>,
+
+    /// The delegator's escrow on this proposal (required with `delegation`)
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), delegator_escrow.voter.as_ref()],
+        bump = delegator_escrow.bump
+    )]
+    pub delegator_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// The delegator's position, in place of `delegator_escrow`
+    #[account(
+        mut,
+        seeds = [b"voter_position", delegator_position.voter.as_ref()],
+        bump = delegator_position.bump
+    )]
+    pub delegator_position: Option<Account<'info, VoterPosition>>,
+
+    /// The voter's position, voting in place of `vote_escrow`
+    #[account(
+        mut,
+        seeds = [b"voter_position", voter.key().as_ref()],
+        bump = voter_position.bump
+    )]
+    pub voter_position: Option<Account<'info, VoterPosition>>,
+
+    /// One vote per voter per proposal in either mode
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::MAX_SIZE,
+        seeds = [b"vote", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Cast a voter's off-chain signed vote (see `cast_vote_by_signature`)
+#[derive(Accounts)]
+#[instruction(voter: Pubkey)]
+pub struct CastVoteBySignature<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.as_ref()],
+        bump = vote_escrow.bump,
+        constraint = vote_escrow.voter == voter @ DaoError::InvalidVoter,
+        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal,
+        constraint = !vote_escrow.withdrawn @ DaoError::AlreadyWithdrawn
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    /// Same PDA as cast_vote's, so the voter can't also vote directly
+    #[account(
+        init,
+        payer = relayer,
+        space = VoteRecord::MAX_SIZE,
+        seeds = [b"vote", proposal.proposal_id.to_le_bytes().as_ref(), voter.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// Submits the transaction and pays the vote record's rent
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, read for the Ed25519 signature instruction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// SECURITY FIX: Retract a vote
+#[derive(Accounts)]
+pub struct RetractVote<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Required unless the whole vote was delegated weight
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump = vote_escrow.bump,
+        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
+        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// The delegator's escrow, required when the vote carried delegated weight
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), delegator_escrow.voter.as_ref()],
+        bump = delegator_escrow.bump
+    )]
+    pub delegator_escrow: Option<Account<'info, VoteEscrow>>,
+
+    /// The delegator's position, required when it carried the delegated weight
+    #[account(
+        mut,
+        seeds = [b"voter_position", delegator_position.voter.as_ref()],
+        bump = delegator_position.bump
+    )]
+    pub delegator_position: Option<Account<'info, VoterPosition>>,
+
+    /// The voter's position, required when the vote was cast with it
+    #[account(
+        mut,
+        seeds = [b"voter_position", voter.key().as_ref()],
+        bump = voter_position.bump
+    )]
+    pub voter_position: Option<Account<'info, VoterPosition>>,
+
+    /// Vote record (will be closed, returning rent to voter)
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+        constraint = vote_record.voter == voter.key() @ DaoError::InvalidVoter,
+        close = voter
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+}
+
+/// SECURITY FIX: Withdraw vote tokens
+#[derive(Accounts)]
+pub struct WithdrawVoteTokens<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Y9.11: VoteEscrow account is closed after withdrawal, returning rent to voter
+    #[account(
+        mut,
+        close = voter,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump = vote_escrow.bump,
+        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
+        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(
+        mut,
+        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Voter's token account
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
+        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Delegate escrowed voting power
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct DelegateVotes<'info> {
+    #[account(
+        init,
+        payer = delegator,
+        space = VoteDelegation::MAX_SIZE,
+        seeds = [b"delegation", delegator.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, VoteDelegation>,
+
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a delegation
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+    #[account(
+        mut,
+        seeds = [b"delegation", delegator.key().as_ref(), delegation.delegate.as_ref()],
+        bump = delegation.bump,
+        has_one = delegator @ DaoError::InvalidDelegation
+    )]
+    pub delegation: Account<'info, VoteDelegation>,
+
+    pub delegator: Signer<'info>,
+}
+
+/// Deposit tokens into a voter position
+#[derive(Accounts)]
+pub struct DepositToPosition<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VoterPosition::MAX_SIZE,
+        seeds = [b"voter_position", voter.key().as_ref()],
+        bump
+    )]
+    pub voter_position: Account<'info, VoterPosition>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(
+        mut,
+        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Voter's token account
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
+        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdraw a voter position (closed, returning rent to the voter)
+#[derive(Accounts)]
+pub struct WithdrawFromPosition<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = vote_vault @ DaoError::InvalidVoteVault
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        close = voter,
+        seeds = [b"voter_position", voter.key().as_ref()],
+        bump = voter_position.bump,
+        constraint = voter_position.voter == voter.key() @ DaoError::InvalidVoter
+    )]
+    pub voter_position: Account<'info, VoterPosition>,
+
+    /// Vote vault token account (PDA-owned)
+    #[account(
+        mut,
+        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Voter's token account
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
+        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Move a vote escrow into the voter's position
+#[derive(Accounts)]
+pub struct MigrateEscrowToPosition<'info> {
+    #[account(
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Closed by the migration, returning rent to the voter
+    #[account(
+        mut,
+        close = voter,
+        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
+        bump = vote_escrow.bump,
+        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
+        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VoterPosition::MAX_SIZE,
+        seeds = [b"voter_position", voter.key().as_ref()],
+        bump
+    )]
+    pub voter_position: Account<'info, VoterPosition>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Resize a vote escrow to the current layout
+#[derive(Accounts)]
+#[instruction(proposal_id: u64, voter: Pubkey)]
+pub struct ResizeVoteEscrow<'info> {
+    /// CHECK: Older layouts are shorter than VoteEscrow::MAX_SIZE and can't
+    /// be decoded as Account<VoteEscrow> until resized; the PDA and owner are
+    /// checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", proposal_id.to_le_bytes().as_ref(), voter.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub vote_escrow: UncheckedAccount<'info>,
+
+    /// Pays any extra rent (the voter or a relayer)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Finalize a proposal
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = treasury @ DaoError::InvalidTreasury
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// DAO treasury (its balance bounds the committed outflow)
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Anyone can finalize after voting ends
+    pub finalizer: Signer<'info>,
+}
+
+/// Emit a tally checkpoint for an active proposal
+#[derive(Accounts)]
+pub struct CheckpointProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+/// Execute a passed proposal
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = treasury @ DaoError::InvalidTreasury
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// DAO treasury
+    #[account(
+        mut,
+        constraint = treasury.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    // Withdrawal recipients' token accounts follow as writable
+    // remaining_accounts, in the proposal's payout order
+
+    /// Anyone can execute a passed proposal
+    pub executor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Commit the treasury to a PassedUnfunded withdrawal
+#[derive(Accounts)]
+pub struct FundPassedProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = treasury @ DaoError::InvalidTreasury
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// DAO treasury
+    pub treasury: Account<'info, TokenAccount>,
+}
+
+/// Expire a passed withdrawal past its execution window
+#[derive(Accounts)]
+pub struct ExpireProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+/// Cancel a passed, unexecuted withdrawal
+#[derive(Accounts)]
+pub struct CancelPassedWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ DaoError::NotProposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer: Signer<'info>,
+}
+
+/// Return proposal bond
+#[derive(Accounts)]
+pub struct ReturnProposalBond<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ DaoError::NotProposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// Bond escrow account
+    #[account(mut)]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Proposer's token account
+    #[account(
+        mut,
+        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
+        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Verified via proposal.proposer
+    pub proposer: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Sponsor a proposal's bond
+#[derive(Accounts)]
+pub struct SponsorProposal<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = ProposalSponsorship::MAX_SIZE,
+        seeds = [b"sponsorship", proposal.proposal_id.to_le_bytes().as_ref(), sponsor.key().as_ref()],
+        bump
+    )]
+    pub sponsorship: Account<'info, ProposalSponsorship>,
+
+    /// Bond escrow account
+    #[account(mut)]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Sponsor's token account
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key() @ DaoError::InvalidTokenOwner,
+        constraint = sponsor_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Refund a sponsor's bond contribution
+#[derive(Accounts)]
+pub struct ClaimSponsorRefund<'info> {
+    #[account(
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsorship", proposal.proposal_id.to_le_bytes().as_ref(), sponsor.key().as_ref()],
+        bump = sponsorship.bump
+    )]
+    pub sponsorship: Account<'info, ProposalSponsorship>,
+
+    /// Bond escrow account
+    #[account(mut)]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Sponsor's token account
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key() @ DaoError::InvalidTokenOwner,
+        constraint = sponsor_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Verified via sponsorship PDA seeds
+    pub sponsor: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Y7.2: Appeal a defeated proposal
+#[derive(Accounts)]
+#[instruction(original_proposal_id: u64)]
+pub struct AppealProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = bond_escrow @ DaoError::InvalidBondEscrow
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    /// Original defeated proposal
+    #[account(
+        mut,
+        seeds = [b"proposal", original_proposal_id.to_le_bytes().as_ref()],
+        bump = original_proposal.bump
+    )]
+    pub original_proposal: Account<'info, Proposal>,
+
+    /// New appeal proposal account (standard proposal seeds)
+    #[account(
+        init,
+        payer = appellant,
+        space = Proposal::MAX_SIZE,
+        seeds = [b"proposal", (dao_config.proposal_count + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub appeal_proposal: Account<'info, Proposal>,
+
+    /// CHECK: Legacy appeal PDA; must be empty (otherwise use migrate_legacy_appeal)
+    #[account(
+        seeds = [b"appeal", original_proposal_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub legacy_appeal: UncheckedAccount<'info>,
+
+    /// Bond escrow to receive appeal bond
+    #[account(mut)]
+    pub bond_escrow: Account<'info, TokenAccount>,
+
+    /// Appellant's token account
+    #[account(
+        mut,
+        constraint = appellant_token_account.owner == appellant.key() @ DaoError::InvalidTokenOwner,
+        constraint = appellant_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub appellant_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub appellant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Y7.2: Migrate a legacy-seeded appeal to the standard proposal seeds
+#[derive(Accounts)]
+#[instruction(original_proposal_id: u64)]
+pub struct MigrateLegacyAppeal<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", original_proposal_id.to_le_bytes().as_ref()],
+        bump = original_proposal.bump
+    )]
+    pub original_proposal: Account<'info, Proposal>,
+
+    /// Appeal created under the legacy ["appeal", original_id] seeds
+    #[account(
+        mut,
+        seeds = [b"appeal", original_proposal_id.to_le_bytes().as_ref()],
+        bump = legacy_appeal.bump,
+        close = appellant
+    )]
+    pub legacy_appeal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = appellant,
+        space = Proposal::MAX_SIZE,
+        seeds = [b"proposal", (dao_config.proposal_count + 1).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub appeal_proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        address = legacy_appeal.proposer @ DaoError::NotProposer
+    )]
+    pub appellant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposit to treasury
+#[derive(Accounts)]
+pub struct DepositToTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"dao_config"],
+        bump = dao_config.bump,
+        has_one = treasury @ DaoError::InvalidTreasury
+    )]
+    pub dao_config: Account<'info, DaoConfig>,
+
+    #[account(
+        mut,
+        constraint = treasury.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.owner == depositor.key() @ DaoError::InvalidTokenOwner,
+        constraint = depositor_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
+    )]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    pub depositor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct DaoInitializedEvent {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub discussion_period: i64,
+    pub voting_period: i64,
+    pub proposal_bond: u64,
+    pub voting_model: VotingModel,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DaoConfigClosedEvent {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DaoConfigMigratedEvent {
+    pub from_version: u8,
+    pub to_version: u8,
+    pub from_len: u32,
+    pub to_len: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdateQueuedEvent {
+    pub new_voting_period: Option<i64>,
+    pub new_proposal_bond: Option<u64>,
+    pub new_quorum_percentage: Option<u8>,
+    pub new_approval_threshold: Option<u8>,
+    pub new_proposal_fee: Option<u64>,
+    pub new_adaptive_quorum: Option<AdaptiveQuorumConfig>,
+    pub new_max_withdrawal_bps: Option<u16>,
+    pub new_conviction_schedule: Option<ConvictionSchedule>,
+    pub new_guardian: Option<Pubkey>,
+    pub new_veto_threshold_bps: Option<u16>,
+    pub new_general_voting_mode: Option<VotingMode>,
+    pub new_max_snapshot_vote_bps: Option<u16>,
+    pub new_voting_model: Option<VotingModel>,
+    pub execute_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdateExecutedEvent {
+    pub voting_period: i64,
+    pub proposal_bond: u64,
+    pub quorum_percentage: u8,
+    pub approval_threshold: u8,
+    pub proposal_fee: u64,
+    pub adaptive_quorum: AdaptiveQuorumConfig,
+    pub max_withdrawal_bps: u16,
+    pub conviction_schedule: ConvictionSchedule,
+    pub guardian: Pubkey,
+    pub veto_threshold_bps: u16,
+    pub general_voting_mode: VotingMode,
+    pub max_snapshot_vote_bps: u16,
+    pub voting_model: VotingModel,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdateCancelledEvent {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianSetEvent {
+    pub guardian: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdateEmergencyCancelledEvent {
+    pub cancelled_by: Pubkey,
+    /// Emergency proposal used (None when the guardian cancelled)
+    pub proposal_id: Option<u64>,
+    pub queued_at: i64,
+    pub objection_total: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigObjectionEvent {
+    pub objector: Pubkey,
+    pub queued_at: i64,
+    pub amount: u64,
+    pub objection_total: u64,
+    pub vetoed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ObjectionWithdrawnEvent {
+    pub objector: Pubkey,
+    pub queued_at: i64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DaoPausedEvent {
+    pub paused: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreatedEvent {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub title: String,
+    pub description_cid: String,
+    pub vote_start: i64,
+    pub vote_end: i64,
+    pub execution_eligible_at: i64,
+    pub snapshot_supply: u64,
+    pub quorum_percentage: u8,
+    pub proposal_fee: u64,
+    pub fee_burned: u64,
+    pub fee_to_treasury: u64,
+    pub treasury_balance: u64,
+    pub withdrawal_cap: u64,
+    pub tags: Vec<String>,
+    pub self_funded: bool,
+    pub funding_deadline: i64,
+    pub voting_mode: VotingMode,
+    pub urgency: ProposalUrgency,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCancelledEvent {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+}
+
+/// SECURITY FIX: New event for vote token deposits
+#[event]
+pub struct VoteTokensDepositedEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteCastEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub vote_choice: VoteChoice,
+    pub vote_weight: u64,
+    pub multiplier_bps: u16,
+    pub voting_mode: VotingMode,
+    /// Balance read for a snapshot vote (0 for escrow votes)
+    pub snapshot_balance: u64,
+    pub timestamp: i64,
+    /// Delegator whose escrow or position was also used (default if none)
+    pub delegator: Pubkey,
+    /// Part of vote_weight delegated by `delegator`
+    pub delegated_weight: u64,
+}
+
+/// SECURITY FIX: New event for vote retractions
+#[event]
+pub struct VoteRetractedEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub vote_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteDelegatedEvent {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationRevokedEvent {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoterPositionDepositedEvent {
+    pub voter: Pubkey,
+    pub amount: u64,
+    /// Position balance after the deposit
+    pub deposited_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoterPositionWithdrawnEvent {
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteEscrowMigratedEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub amount: u64,
+    /// Position balance after the migration
+    pub deposited_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteEscrowResizedEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub from_len: u32,
+    pub to_len: u32,
+    pub timestamp: i64,
+}
+
+/// SECURITY FIX: New event for vote token withdrawals
+#[event]
+pub struct VoteTokensWithdrawnEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalFinalizedEvent {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub abstain_votes: u64,
+    pub quorum_met: bool,
+    pub participation_bps: u16,
+    pub avg_participation_bps: u16,
+    pub finalized_by: Pubkey,
+    /// Treasury reserved for passed withdrawals after this finalization
+    pub committed_outflow: u64,
+    pub timestamp: i64,
+}
+
+/// Complete parameter set of a proposal, emitted at creation (and again
+/// when a sponsored proposal activates) so clients needn't read DaoConfig
+#[event]
+pub struct ProposalManifestEvent {
+    pub schema_version: u8,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub proposal_type: ProposalType,
+    pub status: ProposalStatus,
+    pub voting_mode: VotingMode,
+    pub voting_model: VotingModel,
+    pub urgency: ProposalUrgency,
+    pub created_at: i64,
+    pub discussion_period: i64,
+    pub voting_period: i64,
+    /// 0 until a sponsored proposal activates
+    pub vote_start: i64,
+    pub vote_end: i64,
+    pub execution_eligible_at: i64,
+    pub execution_window: i64,
+    pub snapshot_supply: u64,
+    pub quorum_percentage: u8,
+    /// Absolute votes needed for quorum, in vote-weight units
+    pub quorum_required: u64,
+    pub approval_threshold: u8,
+    pub bond_amount: u64,
+    pub sponsored: bool,
+    pub funding_deadline: i64,
+    pub treasury_balance_snapshot: u64,
+    pub max_withdrawal_bps: u16,
+    pub withdrawal_cap: u64,
+    /// Treasury withdrawal amount (None for other proposal types)
+    pub withdrawal_amount: Option<u64>,
+    pub conviction_schedule: ConvictionSchedule,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalTallyCheckpointEvent {
+    pub proposal_id: u64,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub abstain_votes: u64,
+    /// Votes cast (abstain included) as a percentage of the snapshot supply
+    pub participation_pct: u8,
+    /// Seconds until voting ends (0 once it has)
+    pub time_remaining: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecutedEvent {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub timestamp: i64,
+    /// One event per payout of a multi-recipient withdrawal
+    pub payout_index: u8,
+    pub payout_count: u8,
+}
+
+#[event]
+pub struct ParameterChangeExecutedEvent {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub voting_period: i64,
+    pub quorum_percentage: u8,
+    pub approval_threshold: u8,
+    pub proposal_bond: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalFundedEvent {
+    pub proposal_id: u64,
+    pub amount: u64,
+    pub committed_outflow: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExpiredEvent {
+    pub proposal_id: u64,
+    /// Commitment released (0 if the proposal was never funded)
+    pub released: u64,
+    pub committed_outflow: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondReturnedEvent {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalSponsoredEvent {
+    pub proposal_id: u64,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub bond_raised: u64,
+    pub bond_target: u64,
+    pub activated: bool,
+    pub vote_start: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SponsorRefundedEvent {
+    pub proposal_id: u64,
+    pub sponsor: Pubkey,
+    pub contribution: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Y7.2: Event emitted when a proposal is appealed
+#[event]
+pub struct ProposalAppealedEvent {
+    pub original_proposal_id: u64,
+    pub appeal_proposal_id: u64,
+    pub appellant: Pubkey,
+    pub appeal_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Y7.2: Event emitted when a legacy-seeded appeal is moved to the standard seeds
+#[event]
+pub struct LegacyAppealMigratedEvent {
+    pub original_proposal_id: u64,
+    pub legacy_appeal: Pubkey,
+    pub appeal_proposal_id: u64,
+    pub appellant: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryDepositEvent {
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub total_deposits: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted with VoteCastEvent when a relayer submits a signed vote
+#[event]
+pub struct SignedVoteRelayedEvent {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub relayer: Pubkey,
+    pub nonce: u64,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[error_code]
+pub enum DaoError {
+    #[msg("Voting period must be between 1 and 14 days")]
+    InvalidVotingPeriod,
+
+    #[msg("Proposal bond must be at least 1 token")]
+    InvalidProposalBond,
+
+    #[msg("Quorum percentage must be between 1 and 100")]
+    InvalidQuorumPercentage,
+
+    #[msg("Approval threshold must be between 1 and 100")]
+    InvalidApprovalThreshold,
+
+    #[msg("DAO is currently paused")]
+    DaoPaused,
+
+    #[msg("Title length must be between 1 and 128 characters")]
+    InvalidTitleLength,
+
+    #[msg("Description CID length must be between 1 and 64 characters")]
+    InvalidDescriptionCidLength,
+
+    #[msg("Proposal is not active")]
+    ProposalNotActive,
+
+    #[msg("Voting has not started yet")]
+    VotingNotStarted,
+
+    #[msg("Voting period has ended")]
+    VotingEnded,
+
+    #[msg("Voting is not currently active")]
+    VotingNotActive,
+
+    #[msg("Voting period has not ended yet")]
+    VotingNotEnded,
+
+    #[msg("No voting power (zero token balance at snapshot)")]
+    NoVotingPower,
+
+    #[msg("Already voted on this proposal")]
+    AlreadyVoted,
+
+    #[msg("Proposal has not passed")]
+    ProposalNotPassed,
+
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("This proposal type cannot be executed")]
+    ProposalNotExecutable,
+
+    #[msg("No execution data provided")]
+    NoExecutionData,
+
+    #[msg("Proposal is still active")]
+    ProposalStillActive,
+
+    #[msg("Bond has already been returned")]
+    BondAlreadyReturned,
+
+    #[msg("Bond is forfeited for defeated proposals")]
+    BondForfeited,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Unauthorized: Only DAO authority can perform this action")]
+    UnauthorizedAuthority,
+
+    #[msg("Invalid treasury account")]
+    InvalidTreasury,
+
+    #[msg("Only the proposer can perform this action")]
+    NotProposer,
+
+    #[msg("Arithmetic overflow")]
+    Overflow,
+
+    #[msg("Arithmetic underflow")]
+    Underflow,
+
+    #[msg("Invalid token mint")]
+    InvalidMint,
+
+    #[msg("Invalid token account owner")]
+    InvalidTokenOwner,
+
+    #[msg("Invalid bond escrow account")]
+    InvalidBondEscrow,
+
+    #[msg("Treasury must be owned by DAO PDA")]
+    InvalidTreasuryOwner,
+
+    #[msg("Bond escrow must be owned by DAO PDA")]
+    InvalidBondEscrowOwner,
+
+    #[msg("Recipient does not match proposal execution data")]
+    InvalidRecipient,
+
+    #[msg("Insufficient treasury balance")]
+    InsufficientTreasuryBalance,
+
+    #[msg("No pending configuration change")]
+    NoPendingConfigChange,
+
+    #[msg("Timelock has not expired yet")]
+    TimelockNotExpired,
+
+    #[msg("Invalid voter")]
+    InvalidVoter,
+
+    #[msg("Invalid proposal")]
+    InvalidProposal,
+
+    // SECURITY FIX: New error codes for Vote Escrow pattern
+    #[msg("Vote vault must be owned by DAO PDA")]
+    InvalidVoteVaultOwner,
+
+    #[msg("Invalid vote vault account")]
+    InvalidVoteVault,
+
+    #[msg("Tokens are locked during active voting - retract vote first or wait for vote_end")]
+    TokensLockedDuringVoting,
+
+    #[msg("Tokens have already been withdrawn")]
+    AlreadyWithdrawn,
+
+    #[msg("User has not voted on this proposal")]
+    NotVoted,
+
+    #[msg("Discussion period must be between 1 and 14 days")]
+    InvalidDiscussionPeriod,
+
+    #[msg("Execution timelock has not expired (3 days after voting ends)")]
+    ExecutionTimelockNotExpired,
+
+    // SECURITY FIX (X1.4): New error codes for DAO config close validation
+    #[msg("Invalid DAO config account - incorrect data or not owned by program")]
+    InvalidDaoConfig,
+
+    // Y7.2: Appeal mechanism error codes
+    #[msg("Only defeated proposals can be appealed")]
+    CannotAppealNonDefeated,
+
+    #[msg("Proposal did not achieve minimum quorum (40%) required for appeal")]
+    InsufficientVotesForAppeal,
+
+    #[msg("Proposal fee cannot exceed 100 AEGIS")]
+    InvalidProposalFee,
+
+    #[msg("Token config does not belong to the governance token mint")]
+    InvalidTokenConfig,
+
+    #[msg("Adaptive quorum bounds must satisfy 0 < min <= max <= 100")]
+    InvalidQuorumBounds,
+
+    #[msg("Participation smoothing must be between 1 and 10000 basis points")]
+    InvalidParticipationSmoothing,
+
+    #[msg("Max withdrawal must be between 1 and 10000 basis points")]
+    InvalidMaxWithdrawalBps,
+
+    #[msg("Withdrawal amount exceeds the per-proposal treasury cap")]
+    WithdrawalExceedsCap,
+
+    #[msg("Conviction multipliers must satisfy 10000 <= late <= first half <= pre-voting <= 20000 bps")]
+    InvalidConvictionSchedule,
+
+    #[msg("Veto threshold cannot exceed 10000 basis points")]
+    InvalidVetoThreshold,
+
+    #[msg("Guardian has already been set; replace it with a queued config update")]
+    GuardianAlreadySet,
+
+    #[msg("Guardian cannot be the default public key")]
+    InvalidGuardian,
+
+    #[msg("Only the guardian or a passed emergency proposal can cancel this config change")]
+    UnauthorizedEmergencyCancel,
+
+    #[msg("Config change objections are disabled")]
+    VetoDisabled,
+
+    #[msg("Objection does not match the pending config change")]
+    PendingConfigChangeMismatch,
+
+    #[msg("Objections close when the config change becomes executable")]
+    ObjectionWindowClosed,
+
+    #[msg("Objection tokens are locked while the config change is pending")]
+    ObjectionLocked,
+
+    #[msg("Config change was vetoed by token holder objections")]
+    ConfigChangeVetoed,
+
+    #[msg("A proposal can have at most 5 tags")]
+    TooManyProposalTags,
+
+    #[msg("Tags must be 1-16 lowercase letters or digits")]
+    InvalidProposalTag,
+
+    #[msg("Duplicate proposal tag")]
+    DuplicateProposalTag,
+
+    #[msg("Proposal has already been appealed")]
+    AlreadyAppealed,
+
+    #[msg("Appeal uses the legacy [\"appeal\", id] seeds; call migrate_legacy_appeal before voting")]
+    LegacyAppealSeeds,
+
+    #[msg("Emergency proposals must be self-funded")]
+    EmergencyProposalNotSelfFunded,
+
+    #[msg("Proposal is not awaiting sponsor funding")]
+    ProposalNotPending,
+
+    #[msg("Sponsor funding window has closed")]
+    FundingWindowClosed,
+
+    #[msg("Sponsor funding window is still open")]
+    FundingWindowOpen,
+
+    #[msg("Proposal bond was not sponsored")]
+    NotSponsored,
+
+    #[msg("Sponsored bonds are refunded per sponsor via claim_sponsor_refund")]
+    SponsoredBond,
+
+    #[msg("Max snapshot vote must be between 1 and 10000 basis points")]
+    InvalidMaxSnapshotVoteBps,
+
+    #[msg("This proposal requires escrowed tokens to vote")]
+    VoteEscrowRequired,
+
+    #[msg("Snapshot voting requires the voter's token account")]
+    VoterTokenAccountRequired,
+
+    #[msg("Proposal was checkpointed less than an hour ago")]
+    CheckpointTooSoon,
+
+    #[msg("Proposal is not a passed withdrawal awaiting funding")]
+    ProposalNotUnfunded,
+
+    #[msg("Treasury cannot cover this withdrawal on top of committed outflows")]
+    TreasuryOvercommitted,
+
+    #[msg("Execution window has closed; expire the proposal")]
+    ExecutionWindowClosed,
+
+    #[msg("Proposal is still within its execution window")]
+    ExecutionWindowOpen,
+
+    #[msg("Cannot delegate votes to yourself")]
+    SelfDelegation,
+
+    #[msg("Delegation has already been revoked")]
+    DelegationRevoked,
+
+    #[msg("Delegation does not match the voter or the delegator's escrow")]
+    InvalidDelegation,
+
+    #[msg("Delegation was not in effect when this proposal was created")]
+    DelegationNotEffective,
+
+    #[msg("Delegated voting requires an escrow-mode proposal")]
+    DelegationRequiresEscrow,
+
+    #[msg("A delegated vote needs the delegation and the delegator's escrow or position")]
+    DelegatorEscrowRequired,
+
+    #[msg("The delegator's escrow or position has already voted on this proposal")]
+    DelegatedVotesUsed,
+
+    #[msg("Voter position already has votes on the maximum number of open proposals")]
+    PositionVoteLimitReached,
+
+    #[msg("Vote with either the vote escrow or the voter position, not both")]
+    AmbiguousVoteSource,
+
+    #[msg("Execution data does not match the proposal type")]
+    ExecutionDataMismatch,
+
+    #[msg("A parameter change must set at least one parameter")]
+    EmptyParameterChange,
+
+    #[msg("A withdrawal needs 1 to 8 recipients, each with a nonzero amount")]
+    InvalidWithdrawalPayouts,
 
-/// Set DAO paused status
-#[derive(Accounts)]
-pub struct SetDaoPaused<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = authority @ DaoError::UnauthorizedAuthority
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[msg("A withdrawal can't pay the same recipient twice")]
+    DuplicateWithdrawalRecipient,
 
-    pub authority: Signer<'info>,
-}
+    #[msg("Pass one recipient token account per payout")]
+    WithdrawalRecipientCountMismatch,
 
-/// Create a new proposal
-#[derive(Accounts)]
-#[instruction(title: String, description_cid: String)]
-pub struct CreateProposal<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = bond_escrow @ DaoError::InvalidBondEscrow,
-        has_one = governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[msg("Signed vote needs a preceding Ed25519 instruction by the voter over the vote message")]
+    InvalidVoteSignature,
 
-    #[account(
-        init,
-        payer = proposer,
-        space = Proposal::MAX_SIZE,
-        seeds = [b"proposal", (dao_config.proposal_count + 1).to_le_bytes().as_ref()],
-        bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+    #[msg("Signed vote message has expired")]
+    SignedVoteExpired,
 
-    /// Bond escrow account (PDA-owned)
-    #[account(
-        mut,
-        constraint = bond_escrow.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub bond_escrow: Account<'info, TokenAccount>,
+    #[msg("Signed vote nonce has already been used for this escrow")]
+    SignedVoteReplayed,
 
-    /// Governance token mint
-    pub governance_token_mint: Account<'info, Mint>,
+    #[msg("Signed votes are only accepted on escrow-mode proposals")]
+    SignedVoteRequiresEscrow,
 
-    /// Proposer's token account
-    #[account(
-        mut,
-        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
-        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub proposer_token_account: Account<'info, TokenAccount>,
+    #[msg("DAO config account has an unrecognized layout")]
+    UnsupportedDaoConfigLayout,
 
-    #[account(mut)]
-    pub proposer: Signer<'info>,
+    #[msg("A config change is already pending; execute or cancel it first")]
+    ConfigChangeAlreadyPending,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    #[msg("A vetoed config change can only be cancelled by the authority after its timelock")]
+    VetoedConfigChangeLocked,
 }
 
-/// Cancel a proposal
-#[derive(Accounts)]
-pub struct CancelProposal<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = bond_escrow @ DaoError::InvalidBondEscrow
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump,
-        has_one = proposer @ DaoError::NotProposer
-    )]
-    pub proposal: Account<'info, Proposal>,
+    fn adaptive_config() -> DaoConfig {
+        DaoConfig {
+            authority: Pubkey::default(),
+            treasury: Pubkey::default(),
+            governance_token_mint: Pubkey::default(),
+            bond_escrow: Pubkey::default(),
+            vote_vault: Pubkey::default(),
+            discussion_period: DEFAULT_DISCUSSION_PERIOD,
+            voting_period: DEFAULT_VOTING_PERIOD,
+            proposal_bond: DEFAULT_PROPOSAL_BOND,
+            quorum_percentage: DEFAULT_QUORUM_PERCENTAGE,
+            approval_threshold: DEFAULT_APPROVAL_THRESHOLD,
+            proposal_count: 0,
+            total_treasury_deposits: 0,
+            paused: false,
+            pending_config_change: None,
+            bump: 255,
+            proposal_fee: DEFAULT_PROPOSAL_FEE,
+            adaptive_quorum: AdaptiveQuorumConfig {
+                enabled: true,
+                min_quorum_percentage: DEFAULT_MIN_QUORUM_PERCENTAGE,
+                max_quorum_percentage: DEFAULT_MAX_QUORUM_PERCENTAGE,
+                smoothing_bps: DEFAULT_PARTICIPATION_SMOOTHING_BPS,
+            },
+            avg_participation_bps: DEFAULT_QUORUM_PERCENTAGE as u16 * 100,
+            max_withdrawal_bps: DEFAULT_MAX_WITHDRAWAL_BPS,
+            conviction_schedule: ConvictionSchedule::default(),
+            guardian: Pubkey::default(),
+            veto_threshold_bps: 0,
+            general_voting_mode: VotingMode::Escrow,
+            max_snapshot_vote_bps: DEFAULT_MAX_SNAPSHOT_VOTE_BPS,
+            committed_outflow: 0,
+            voting_model: VotingModel::TokenWeighted,
+            config_version: CURRENT_DAO_CONFIG_VERSION,
+        }
+    }
 
-    /// Bond escrow account
-    #[account(mut)]
-    pub bond_escrow: Account<'info, TokenAccount>,
+    fn legacy_v0_config(pending_config_change: Option<LegacyPendingConfigChangeV0>) -> LegacyDaoConfigV0 {
+        LegacyDaoConfigV0 {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            governance_token_mint: Pubkey::new_unique(),
+            bond_escrow: Pubkey::new_unique(),
+            vote_vault: Pubkey::new_unique(),
+            discussion_period: DEFAULT_DISCUSSION_PERIOD,
+            voting_period: MIN_VOTING_PERIOD,
+            proposal_bond: DEFAULT_PROPOSAL_BOND,
+            quorum_percentage: 15,
+            approval_threshold: 60,
+            proposal_count: 12,
+            total_treasury_deposits: 5_000,
+            paused: true,
+            pending_config_change,
+            bump: 254,
+        }
+    }
 
-    /// Proposer's token account
-    #[account(
-        mut,
-        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
-        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub proposer_token_account: Account<'info, TokenAccount>,
+    /// A version 0 account as the original program allocated it
+    fn legacy_v0_bytes(legacy: &LegacyDaoConfigV0) -> Vec<u8> {
+        let mut bytes = DaoConfig::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut bytes).unwrap();
+        assert!(bytes.len() <= LegacyDaoConfigV0::SIZE);
+        bytes.resize(LegacyDaoConfigV0::SIZE, 0);
+        bytes
+    }
 
-    pub proposer: Signer<'info>,
+    #[test]
+    fn test_migrate_legacy_dao_config() {
+        let legacy = legacy_v0_config(Some(LegacyPendingConfigChangeV0 {
+            new_voting_period: Some(DEFAULT_VOTING_PERIOD),
+            new_proposal_bond: Some(MIN_PROPOSAL_BOND),
+            new_quorum_percentage: Some(20),
+            new_approval_threshold: Some(55),
+            queued_at: 1_000,
+            execute_after: 1_000 + CONFIG_TIMELOCK_DELAY,
+        }));
+        // With every option set the pending change fills the allocation
+        assert_eq!(8 + legacy.try_to_vec().unwrap().len(), LegacyDaoConfigV0::SIZE);
+        let bytes = legacy_v0_bytes(&legacy);
+        assert!(DaoConfig::try_deserialize(&mut &bytes[..]).is_err());
+
+        let (from_version, config) = DaoConfig::decode_any_version(&bytes).unwrap();
+        assert_eq!(from_version, 0);
+        assert_eq!(config.config_version, CURRENT_DAO_CONFIG_VERSION);
+        assert_eq!(config.authority, legacy.authority);
+        assert_eq!(config.vote_vault, legacy.vote_vault);
+        assert_eq!(config.voting_period, MIN_VOTING_PERIOD);
+        assert_eq!(config.quorum_percentage, 15);
+        assert_eq!(config.proposal_count, 12);
+        assert!(config.paused);
+        assert_eq!(config.bump, 254);
+
+        let pending = config.pending_config_change.as_ref().unwrap();
+        assert_eq!(pending.new_voting_period, Some(DEFAULT_VOTING_PERIOD));
+        assert_eq!(pending.new_quorum_percentage, Some(20));
+        assert_eq!(pending.new_approval_threshold, Some(55));
+        assert_eq!(pending.execute_after, 1_000 + CONFIG_TIMELOCK_DELAY);
+        assert_eq!(pending.new_proposal_fee, None);
+        assert!(!pending.vetoed);
+
+        // Fields added since version 0 get initialize_dao's defaults
+        assert_eq!(config.proposal_fee, DEFAULT_PROPOSAL_FEE);
+        assert!(!config.adaptive_quorum.enabled);
+        assert_eq!(config.avg_participation_bps, 1500);
+        assert_eq!(config.max_withdrawal_bps, DEFAULT_MAX_WITHDRAWAL_BPS);
+        assert_eq!(config.conviction_schedule, ConvictionSchedule::default());
+        assert_eq!(config.guardian, Pubkey::default());
+        assert_eq!(config.general_voting_mode, VotingMode::Escrow);
+        assert_eq!(config.max_snapshot_vote_bps, DEFAULT_MAX_SNAPSHOT_VOTE_BPS);
+        assert_eq!(config.voting_model, VotingModel::TokenWeighted);
+
+        // Written back at the current size, it decodes as current
+        let mut migrated = Vec::new();
+        config.try_serialize(&mut migrated).unwrap();
+        migrated.resize(DaoConfig::MAX_SIZE, 0);
+        let (version, again) = DaoConfig::decode_any_version(&migrated).unwrap();
+        assert_eq!(version, CURRENT_DAO_CONFIG_VERSION);
+        assert_eq!(again.proposal_count, 12);
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    #[test]
+    fn test_migrate_legacy_dao_config_without_pending_change() {
+        let legacy = legacy_v0_config(None);
+        let (from_version, config) = DaoConfig::decode_any_version(&legacy_v0_bytes(&legacy)).unwrap();
+        assert_eq!(from_version, 0);
+        assert!(config.pending_config_change.is_none());
+        assert_eq!(config.treasury, legacy.treasury);
+    }
 
-/// SECURITY FIX: Deposit vote tokens (Vote Escrow pattern)
-#[derive(Accounts)]
-pub struct DepositVoteTokens<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = vote_vault @ DaoError::InvalidVoteVault
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[test]
+    fn test_unknown_dao_config_layout_rejected() {
+        let mut bytes = legacy_v0_bytes(&legacy_v0_config(None));
+        bytes.push(0);
+        let err = DaoConfig::decode_any_version(&bytes).err().unwrap();
+        assert_eq!(err, DaoError::UnsupportedDaoConfigLayout.into());
 
-    #[account(
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+        let mut bytes = legacy_v0_bytes(&legacy_v0_config(None));
+        bytes[0] ^= 1;
+        assert!(DaoConfig::decode_any_version(&bytes).is_err());
+    }
 
-    #[account(
-        init,
-        payer = voter,
-        space = VoteEscrow::MAX_SIZE,
-        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump
-    )]
-    pub vote_escrow: Account<'info, VoteEscrow>,
+    fn finalize_with_participation(config: &mut DaoConfig, participation_bps: u16) {
+        config.avg_participation_bps = update_participation_average(
+            config.avg_participation_bps,
+            participation_bps,
+            config.adaptive_quorum.smoothing_bps,
+        );
+    }
 
-    /// Vote vault token account (PDA-owned)
-    #[account(
-        mut,
-        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub vote_vault: Account<'info, TokenAccount>,
+    #[test]
+    fn test_disabled_uses_base_quorum() {
+        let mut config = adaptive_config();
+        config.adaptive_quorum.enabled = false;
+        finalize_with_participation(&mut config, 0);
+        assert_eq!(config.effective_quorum_percentage(), DEFAULT_QUORUM_PERCENTAGE);
+    }
 
-    /// Voter's token account
-    #[account(
-        mut,
-        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
-        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub voter_token_account: Account<'info, TokenAccount>,
+    #[test]
+    fn test_low_participation_lowers_quorum_within_floor() {
+        let mut config = adaptive_config();
+        assert_eq!(config.effective_quorum_percentage(), DEFAULT_QUORUM_PERCENTAGE);
 
-    #[account(mut)]
-    pub voter: Signer<'info>,
+        // Three finalizations with 1% turnout
+        for _ in 0..3 {
+            finalize_with_participation(&mut config, 100);
+        }
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+        let quorum = config.effective_quorum_percentage();
+        assert!(quorum < DEFAULT_QUORUM_PERCENTAGE);
+        assert!(quorum >= DEFAULT_MIN_QUORUM_PERCENTAGE);
 
-/// SECURITY FIX: Cast a vote (using escrowed tokens)
-#[derive(Accounts)]
-pub struct CastVote<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+        // Sustained zero turnout bottoms out at the floor
+        for _ in 0..20 {
+            finalize_with_participation(&mut config, 0);
+        }
+        assert_eq!(config.effective_quorum_percentage(), DEFAULT_MIN_QUORUM_PERCENTAGE);
+    }
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+    #[test]
+    fn test_high_participation_raises_quorum_within_cap() {
+        let mut config = adaptive_config();
 
-    #[account(
-        mut,
-        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump = vote_escrow.bump,
-        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
-        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal,
-        constraint = !vote_escrow.withdrawn @ DaoError::AlreadyWithdrawn
-    )]
-    pub vote_escrow: Account<'info, VoteEscrow>,
+        // A single finalization with 25% turnout
+        finalize_with_participation(&mut config, 2500);
+        let quorum = config.effective_quorum_percentage();
+        assert!(quorum > DEFAULT_QUORUM_PERCENTAGE);
+        assert!(quorum <= DEFAULT_MAX_QUORUM_PERCENTAGE);
 
-    #[account(
-        init,
-        payer = voter,
-        space = VoteRecord::MAX_SIZE,
-        seeds = [b"vote", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump
-    )]
-    pub vote_record: Account<'info, VoteRecord>,
+        // Full turnout is held at the cap
+        finalize_with_participation(&mut config, 10000);
+        assert_eq!(config.effective_quorum_percentage(), DEFAULT_MAX_QUORUM_PERCENTAGE);
+    }
 
-    #[account(mut)]
-    pub voter: Signer<'info>,
+    #[test]
+    fn test_participation_average_bounds() {
+        assert_eq!(update_participation_average(1000, 1000, 3000), 1000);
+        assert_eq!(update_participation_average(0, 10000, 10000), 10000);
+        assert_eq!(update_participation_average(10000, 10000, 3000), 10000);
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    #[test]
+    fn test_withdrawal_cap_at_creation() {
+        // 10% of a 1,000-token treasury
+        let cap = withdrawal_cap(1_000_000_000_000, DEFAULT_MAX_WITHDRAWAL_BPS).unwrap();
+        assert_eq!(cap, 100_000_000_000);
+        assert_eq!(withdrawal_cap(u64::MAX, 10000).unwrap(), u64::MAX);
+    }
 
-/// SECURITY FIX: Retract a vote
-#[derive(Accounts)]
-pub struct RetractVote<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[test]
+    fn test_withdrawal_cap_rechecked_against_shrunken_treasury() {
+        // Approved at the cap of a 1,000-token treasury...
+        let approved = withdrawal_cap(1_000_000_000_000, DEFAULT_MAX_WITHDRAWAL_BPS).unwrap();
+        let mut proposal = defeated_withdrawal(3);
+        proposal.execution_data = Some(single_payout(approved));
+        proposal.treasury_balance_snapshot = 1_000_000_000_000;
+        proposal.max_withdrawal_bps = DEFAULT_MAX_WITHDRAWAL_BPS;
+        proposal.status = ProposalStatus::Passed;
+        proposal.execution_eligible_at = VOTE_END + EXECUTION_TIMELOCK;
+
+        let now = proposal.execution_eligible_at;
+        let ExecutionData::TreasuryWithdrawal { payouts } = proposal.executable_data(now).unwrap() else {
+            panic!("expected a treasury withdrawal");
+        };
+        assert_eq!(
+            check_treasury_withdrawal(&payouts, 1_000_000_000_000, proposal.max_withdrawal_bps).unwrap(),
+            approved
+        );
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+        // ...but by execution the treasury has shrunk to 500 tokens
+        let err = check_treasury_withdrawal(&payouts, 500_000_000_000, proposal.max_withdrawal_bps).unwrap_err();
+        assert_eq!(err, DaoError::WithdrawalExceedsCap.into());
 
-    #[account(
-        mut,
-        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump = vote_escrow.bump,
-        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
-        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal
-    )]
-    pub vote_escrow: Account<'info, VoteEscrow>,
+        // Below the payout itself it's a plain shortfall
+        let err = check_treasury_withdrawal(&payouts, approved - 1, proposal.max_withdrawal_bps).unwrap_err();
+        assert_eq!(err, DaoError::InsufficientTreasuryBalance.into());
+    }
 
-    /// Vote record (will be closed, returning rent to voter)
-    #[account(
-        mut,
-        seeds = [b"vote", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump = vote_record.bump,
-        constraint = vote_record.voter == voter.key() @ DaoError::InvalidVoter,
-        close = voter
-    )]
-    pub vote_record: Account<'info, VoteRecord>,
+    const VOTE_START: i64 = 1_000_000;
+    const VOTE_END: i64 = VOTE_START + DEFAULT_VOTING_PERIOD;
 
-    #[account(mut)]
-    pub voter: Signer<'info>,
-}
+    fn tally_contribution(deposited_at: i64) -> u64 {
+        let schedule = ConvictionSchedule::default();
+        let multiplier = schedule.multiplier_bps(deposited_at, VOTE_START, VOTE_END);
+        boosted_vote_weight(100_000_000_000, multiplier).unwrap()
+    }
 
-/// SECURITY FIX: Withdraw vote tokens
-#[derive(Accounts)]
-pub struct WithdrawVoteTokens<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = vote_vault @ DaoError::InvalidVoteVault
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[test]
+    fn test_conviction_multiplier_by_deposit_time() {
+        let pre_voting = tally_contribution(VOTE_START - 3600);
+        let first_half = tally_contribution(VOTE_START + 3600);
+        let last_day = tally_contribution(VOTE_END - 3600);
 
-    #[account(
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+        assert_eq!(pre_voting, 125_000_000_000);
+        assert_eq!(first_half, 110_000_000_000);
+        assert_eq!(last_day, 100_000_000_000);
+    }
 
-    /// Y9.11: VoteEscrow account is closed after withdrawal, returning rent to voter
-    #[account(
-        mut,
-        close = voter,
-        seeds = [b"vote_escrow", proposal.proposal_id.to_le_bytes().as_ref(), voter.key().as_ref()],
-        bump = vote_escrow.bump,
-        constraint = vote_escrow.voter == voter.key() @ DaoError::InvalidVoter,
-        constraint = vote_escrow.proposal_id == proposal.proposal_id @ DaoError::InvalidProposal
-    )]
-    pub vote_escrow: Account<'info, VoteEscrow>,
+    #[test]
+    fn test_retract_and_withdraw_round_trip() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::General, &config);
+        proposal.count_vote(VoteChoice::For, 42).unwrap();
+        let mut vote_escrow = escrow(100_000_000_000);
+        vote_escrow.proposal_id = proposal.proposal_id;
+
+        // Cast: a first-half deposit is counted with its boost
+        let cast = resolve_vote_weight(&proposal, &config, Some(&vote_escrow), None).unwrap();
+        assert_eq!(cast.vote_weight, 110_000_000_000);
+        proposal.count_vote(VoteChoice::For, cast.vote_weight).unwrap();
+        vote_escrow.has_voted = true;
+        vote_escrow.vote_choice = Some(VoteChoice::For);
+        let vote_record = VoteRecord {
+            proposal_id: proposal.proposal_id,
+            voter: vote_escrow.voter,
+            vote_choice: VoteChoice::For,
+            vote_weight: cast.vote_weight,
+            voted_at: VOTE_START + 7200,
+            bump: 0,
+            multiplier_bps: cast.multiplier_bps,
+            snapshot_balance: 0,
+            delegator: Pubkey::default(),
+            delegated_weight: 0,
+            delegated_from_position: false,
+        };
+
+        // Retract: the escrow unlocks and exactly the recorded weight leaves the tally
+        release_vote_escrows(&vote_record, Some(&mut vote_escrow), None, None, None).unwrap();
+        proposal.uncount_vote(vote_record.vote_choice, vote_record.vote_weight).unwrap();
+        assert_eq!(proposal.for_votes, 42);
+        assert!(!vote_escrow.has_voted);
+        assert_eq!(vote_escrow.vote_choice, None);
+
+        // A second retraction has nothing left to release
+        let err = release_vote_escrows(&vote_record, Some(&mut vote_escrow), None, None, None).unwrap_err();
+        assert_eq!(err, DaoError::NotVoted.into());
+
+        // Withdrawal returns the escrowed principal, never the boost
+        assert_eq!(vote_escrow.deposited_amount, 100_000_000_000);
+    }
+
+    #[test]
+    fn test_conviction_schedule_snapshotted_at_creation() {
+        let mut config = adaptive_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+        let vote_escrow = escrow(100_000_000_000);
+        let before = resolve_vote_weight(&proposal, &config, Some(&vote_escrow), None).unwrap();
+
+        // A config update mid-vote doesn't change the proposal's multipliers
+        config.conviction_schedule = ConvictionSchedule {
+            first_half_bps: MAX_CONVICTION_MULTIPLIER_BPS,
+            pre_voting_bps: MAX_CONVICTION_MULTIPLIER_BPS,
+            ..Default::default()
+        };
+        assert!(config.conviction_schedule.is_valid());
+        let after = resolve_vote_weight(&proposal, &config, Some(&vote_escrow), None).unwrap();
+        assert_eq!(after.multiplier_bps, before.multiplier_bps);
+        assert_eq!(after.vote_weight, before.vote_weight);
+        assert_eq!(proposal.manifest(VOTE_START).unwrap().conviction_schedule, ConvictionSchedule::default());
+    }
+
+    #[test]
+    fn test_conviction_schedule_validation() {
+        assert!(ConvictionSchedule::default().is_valid());
+
+        let schedule = ConvictionSchedule {
+            late_bps: 9000,
+            ..Default::default()
+        };
+        assert!(!schedule.is_valid());
+
+        let schedule = ConvictionSchedule {
+            first_half_bps: 13000,
+            ..Default::default()
+        };
+        assert!(!schedule.is_valid());
+
+        let schedule = ConvictionSchedule {
+            pre_voting_bps: MAX_CONVICTION_MULTIPLIER_BPS + 1,
+            ..Default::default()
+        };
+        assert!(!schedule.is_valid());
+    }
 
-    /// Vote vault token account (PDA-owned)
-    #[account(
-        mut,
-        constraint = vote_vault.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub vote_vault: Account<'info, TokenAccount>,
+    #[test]
+    fn test_boosted_weight_overflow() {
+        assert!(boosted_vote_weight(u64::MAX, MAX_CONVICTION_MULTIPLIER_BPS).is_err());
+        assert_eq!(boosted_vote_weight(u64::MAX, 10000).unwrap(), u64::MAX);
+    }
 
-    /// Voter's token account
-    #[account(
-        mut,
-        constraint = voter_token_account.owner == voter.key() @ DaoError::InvalidTokenOwner,
-        constraint = voter_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub voter_token_account: Account<'info, TokenAccount>,
+    const QUEUED_AT: i64 = 5_000_000;
+    const SUPPLY: u64 = 1_000_000_000_000_000;
+
+    fn pending_change() -> PendingConfigChange {
+        PendingConfigChange {
+            new_voting_period: None,
+            new_proposal_bond: None,
+            new_quorum_percentage: Some(1),
+            new_approval_threshold: None,
+            new_proposal_fee: None,
+            new_adaptive_quorum: None,
+            new_max_withdrawal_bps: None,
+            new_conviction_schedule: None,
+            queued_at: QUEUED_AT,
+            execute_after: QUEUED_AT + CONFIG_TIMELOCK_DELAY,
+            new_guardian: None,
+            new_veto_threshold_bps: None,
+            objection_total: 0,
+            vetoed: false,
+            new_general_voting_mode: None,
+            new_max_snapshot_vote_bps: None,
+            new_voting_model: None,
+        }
+    }
 
-    pub voter: Signer<'info>,
+    #[test]
+    fn test_objections_over_threshold_block_execution() {
+        let mut pending = pending_change();
+        // 10% threshold: 6% + 5% of supply crosses it
+        pending.record_objection(SUPPLY / 100 * 6, SUPPLY, 1000).unwrap();
+        assert!(!pending.vetoed);
+        pending.record_objection(SUPPLY / 100 * 5, SUPPLY, 1000).unwrap();
+        assert!(pending.vetoed);
 
-    pub token_program: Program<'info, Token>,
-}
+        assert!(pending.ensure_executable(pending.execute_after + 1).is_err());
+    }
 
-/// Finalize a proposal
-#[derive(Accounts)]
-pub struct FinalizeProposal<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[test]
+    fn test_objections_under_threshold_allow_execution() {
+        let mut pending = pending_change();
+        // Exactly at the threshold is not over it
+        pending.record_objection(SUPPLY / 10, SUPPLY, 1000).unwrap();
+        assert!(!pending.vetoed);
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+        assert!(pending.ensure_executable(pending.execute_after - 1).is_err());
+        assert!(pending.ensure_executable(pending.execute_after).is_ok());
+    }
 
-    /// Anyone can finalize after voting ends
-    pub finalizer: Signer<'info>,
-}
+    #[test]
+    fn test_requeue_over_vetoed_change_rejected() {
+        let mut config = adaptive_config();
+        config.ensure_no_pending_config_change().unwrap();
+
+        let mut pending = pending_change();
+        pending.record_objection(SUPPLY / 100 * 11, SUPPLY, 1000).unwrap();
+        assert!(pending.vetoed);
+        config.pending_config_change = Some(pending.clone());
+
+        // Queuing again would wipe the veto
+        let err = config.ensure_no_pending_config_change().unwrap_err();
+        assert_eq!(err, DaoError::ConfigChangeAlreadyPending.into());
+
+        // Nor can the authority cancel it away before execute_after
+        let err = pending
+            .ensure_authority_cancellable(pending.execute_after - 1)
+            .unwrap_err();
+        assert_eq!(err, DaoError::VetoedConfigChangeLocked.into());
+        pending.ensure_authority_cancellable(pending.execute_after).unwrap();
+
+        // A change that isn't vetoed can be cancelled at any time
+        pending_change().ensure_authority_cancellable(QUEUED_AT).unwrap();
+    }
 
-/// Execute a passed proposal
-#[derive(Accounts)]
-pub struct ExecuteProposal<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = treasury @ DaoError::InvalidTreasury
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    #[test]
+    fn test_zero_veto_threshold_never_vetoes() {
+        assert!(!objections_exceed_threshold(SUPPLY, SUPPLY, 0));
+        assert!(objections_exceed_threshold(1, 0, 1));
+    }
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+    fn emergency_proposal(created_at: i64) -> Proposal {
+        Proposal {
+            proposal_id: 7,
+            proposer: Pubkey::default(),
+            title: String::new(),
+            description_cid: String::new(),
+            proposal_type: ProposalType::EmergencyConfigCancel,
+            execution_data: None,
+            status: ProposalStatus::Passed,
+            for_votes: 0,
+            against_votes: 0,
+            abstain_votes: 0,
+            vote_start: created_at,
+            vote_end: created_at + EMERGENCY_VOTING_PERIOD,
+            execution_eligible_at: created_at + EMERGENCY_VOTING_PERIOD,
+            created_at,
+            executed_at: None,
+            bond_returned: false,
+            snapshot_supply: SUPPLY,
+            bump: 0,
+            quorum_percentage: DEFAULT_QUORUM_PERCENTAGE,
+            treasury_balance_snapshot: 0,
+            max_withdrawal_bps: DEFAULT_MAX_WITHDRAWAL_BPS,
+            tags: Vec::new(),
+            appeal_proposal_id: None,
+            sponsored: false,
+            bond_target: 0,
+            bond_raised: 0,
+            funding_deadline: 0,
+            finalized_by: Pubkey::default(),
+            finalized_at: 0,
+            executed_by: Pubkey::default(),
+            voting_mode: VotingMode::Escrow,
+            last_checkpoint_at: 0,
+            voting_model: VotingModel::TokenWeighted,
+            approval_threshold: DEFAULT_APPROVAL_THRESHOLD,
+            bond_amount: DEFAULT_PROPOSAL_BOND,
+            discussion_period: 0,
+            voting_period: EMERGENCY_VOTING_PERIOD,
+            execution_window: EXECUTION_WINDOW,
+            urgency: ProposalUrgency::Standard,
+            conviction_schedule: ConvictionSchedule::default(),
+        }
+    }
 
-    /// DAO treasury
-    #[account(
-        mut,
-        constraint = treasury.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub treasury: Account<'info, TokenAccount>,
+    #[test]
+    fn test_emergency_proposal_authorizes_cancel() {
+        let proposal = emergency_proposal(QUEUED_AT + 60);
+        let decided = proposal.execution_eligible_at;
+
+        assert!(proposal.authorizes_emergency_cancel(QUEUED_AT, decided));
+        // Concludes inside the config timelock
+        assert!(decided < QUEUED_AT + CONFIG_TIMELOCK_DELAY);
+        // Not before the vote ends
+        assert!(!proposal.authorizes_emergency_cancel(QUEUED_AT, decided - 1));
+        // Not against a change queued after the proposal
+        assert!(!proposal.authorizes_emergency_cancel(QUEUED_AT + 120, decided));
+    }
 
-    /// Recipient of treasury withdrawal (validated against proposal.execution_data.recipient)
-    #[account(
-        mut,
-        constraint = recipient.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub recipient: Account<'info, TokenAccount>,
+    #[test]
+    fn test_emergency_cancel_requires_passed_unexecuted_emergency_proposal() {
+        let mut proposal = emergency_proposal(QUEUED_AT);
+        let decided = proposal.execution_eligible_at;
 
-    /// Anyone can execute a passed proposal
-    pub executor: Signer<'info>,
+        proposal.status = ProposalStatus::Defeated;
+        assert!(!proposal.authorizes_emergency_cancel(QUEUED_AT, decided));
 
-    pub token_program: Program<'info, Token>,
-}
+        let mut proposal = emergency_proposal(QUEUED_AT);
+        proposal.executed_at = Some(decided);
+        assert!(!proposal.authorizes_emergency_cancel(QUEUED_AT, decided));
 
-/// Return proposal bond
-#[derive(Accounts)]
-pub struct ReturnProposalBond<'info> {
-    #[account(
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = bond_escrow @ DaoError::InvalidBondEscrow
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+        let mut proposal = emergency_proposal(QUEUED_AT);
+        proposal.proposal_type = ProposalType::General;
+        assert!(!proposal.authorizes_emergency_cancel(QUEUED_AT, decided));
+    }
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
-        bump = proposal.bump,
-        has_one = proposer @ DaoError::NotProposer
-    )]
-    pub proposal: Account<'info, Proposal>,
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
 
-    /// Bond escrow account
-    #[account(mut)]
-    pub bond_escrow: Account<'info, TokenAccount>,
+    #[test]
+    fn test_valid_proposal_tags() {
+        assert!(validate_proposal_tags(&[]).is_ok());
+        assert!(validate_proposal_tags(&tags(&["treasury", "parameters", "networkops", "v2", "q3review2025"])).is_ok());
+        assert!(validate_proposal_tags(&tags(&["abcdefghijklmnop"])).is_ok());
+    }
 
-    /// Proposer's token account
-    #[account(
-        mut,
-        constraint = proposer_token_account.owner == proposer.key() @ DaoError::InvalidTokenOwner,
-        constraint = proposer_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub proposer_token_account: Account<'info, TokenAccount>,
+    #[test]
+    fn test_sixth_proposal_tag_rejected() {
+        let err = validate_proposal_tags(&tags(&["a", "b", "c", "d", "e", "f"])).unwrap_err();
+        assert_eq!(err, DaoError::TooManyProposalTags.into());
+    }
 
-    /// CHECK: Verified via proposal.proposer
-    pub proposer: AccountInfo<'info>,
+    #[test]
+    fn test_malformed_proposal_tags_rejected() {
+        for bad in ["Treasury", "network-ops", "network ops", "", "abcdefghijklmnopq", "caf\u{e9}"] {
+            let err = validate_proposal_tags(&tags(&[bad])).unwrap_err();
+            assert_eq!(err, DaoError::InvalidProposalTag.into(), "tag: {:?}", bad);
+        }
+        let err = validate_proposal_tags(&tags(&["treasury", "ops", "treasury"])).unwrap_err();
+        assert_eq!(err, DaoError::DuplicateProposalTag.into());
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    fn payout(amount: u64) -> WithdrawalPayout {
+        WithdrawalPayout {
+            recipient: Pubkey::new_unique(),
+            amount,
+        }
+    }
 
-/// Y7.2: Appeal a defeated proposal
-#[derive(Accounts)]
-#[instruction(original_proposal_id: u64)]
-pub struct AppealProposal<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = bond_escrow @ DaoError::InvalidBondEscrow
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    fn single_payout(amount: u64) -> ExecutionData {
+        ExecutionData::TreasuryWithdrawal {
+            payouts: vec![payout(amount)],
+        }
+    }
 
-    /// Original defeated proposal
-    #[account(
-        seeds = [b"proposal", original_proposal_id.to_le_bytes().as_ref()],
-        bump = original_proposal.bump
-    )]
-    pub original_proposal: Account<'info, Proposal>,
+    fn defeated_withdrawal(proposal_id: u64) -> Proposal {
+        let mut proposal = emergency_proposal(VOTE_START - DEFAULT_DISCUSSION_PERIOD);
+        proposal.proposal_id = proposal_id;
+        proposal.title = "Fund audit".to_string();
+        proposal.proposal_type = ProposalType::TreasuryWithdrawal;
+        proposal.execution_data = Some(single_payout(1_000));
+        proposal.status = ProposalStatus::Defeated;
+        proposal.vote_start = VOTE_START;
+        proposal.vote_end = VOTE_END;
+        proposal.for_votes = SUPPLY / 20;
+        proposal.against_votes = SUPPLY / 25;
+        proposal
+    }
 
-    /// New appeal proposal account
-    #[account(
-        init,
-        payer = appellant,
-        space = Proposal::MAX_SIZE,
-        seeds = [b"appeal", original_proposal_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub appeal_proposal: Account<'info, Proposal>,
+    fn proposal_pda(proposal_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"proposal", proposal_id.to_le_bytes().as_ref()], &crate::ID)
+    }
 
-    /// Bond escrow to receive appeal bond
-    #[account(mut)]
-    pub bond_escrow: Account<'info, TokenAccount>,
+    #[test]
+    fn test_appeal_uses_standard_proposal_seeds() {
+        let mut config = adaptive_config();
+        config.proposal_count = 3;
+        let original = defeated_withdrawal(3);
+
+        let appeal_id = config.proposal_count + 1;
+        let (pda, bump) = proposal_pda(appeal_id);
+        let appeal = original
+            .appeal(appeal_id, Pubkey::new_unique(), &config, VOTE_END + 10, bump)
+            .unwrap();
+
+        // Every downstream context re-derives ["proposal", proposal.proposal_id] with proposal.bump
+        let derived = Pubkey::create_program_address(
+            &[b"proposal", appeal.proposal_id.to_le_bytes().as_ref(), &[appeal.bump]],
+            &crate::ID,
+        )
+        .unwrap();
+        assert_eq!(derived, pda);
+
+        // The legacy scheme never matched that derivation
+        let (legacy, _) = Pubkey::find_program_address(&[b"appeal", 3u64.to_le_bytes().as_ref()], &crate::ID);
+        assert_ne!(legacy, pda);
+        assert_ne!(legacy, proposal_pda(original.proposal_id).0);
+    }
 
-    /// Appellant's token account
-    #[account(
-        mut,
-        constraint = appellant_token_account.owner == appellant.key() @ DaoError::InvalidTokenOwner,
-        constraint = appellant_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub appellant_token_account: Account<'info, TokenAccount>,
+    #[test]
+    fn test_appeal_lifecycle() {
+        let mut config = adaptive_config();
+        config.proposal_count = 3;
+        let original = defeated_withdrawal(3);
+        let created_at = VOTE_END + 10;
+
+        // Create: voting opens immediately for 1.5x the voting period
+        let (_, bump) = proposal_pda(4);
+        let mut appeal = original.appeal(4, Pubkey::new_unique(), &config, created_at, bump).unwrap();
+        assert_eq!(appeal.status, ProposalStatus::Active);
+        assert_eq!(appeal.title, "APPEAL: Fund audit");
+        assert_eq!(appeal.vote_start, created_at);
+        assert_eq!(appeal.vote_end, created_at + DEFAULT_VOTING_PERIOD * 3 / 2);
+        assert_eq!(appeal.execution_eligible_at, appeal.vote_end + EXECUTION_TIMELOCK);
+        assert_eq!(appeal.snapshot_supply, original.snapshot_supply);
+        assert_eq!(appeal.withdrawal_amount(), Some(1_000));
+
+        // Deposit + vote: escrowed tokens are weighted like any other proposal
+        let multiplier =
+            appeal.conviction_schedule.multiplier_bps(created_at + 60, appeal.vote_start, appeal.vote_end);
+        appeal.for_votes += boosted_vote_weight(SUPPLY / 5, multiplier).unwrap();
+        appeal.against_votes += boosted_vote_weight(SUPPLY / 50, multiplier).unwrap();
+
+        // Finalize: quorum from the original's supply snapshot, approval over for + against
+        let participation = appeal.for_votes + appeal.against_votes + appeal.abstain_votes;
+        assert!(participation >= appeal.quorum_required().unwrap());
+        assert!(appeal.approval_met().unwrap());
+        appeal.status = ProposalStatus::Passed;
+
+        // Execute: after the timelock, within the original's withdrawal cap
+        let treasury_balance = 100_000;
+        let amount = appeal.withdrawal_amount().unwrap();
+        assert!(amount <= withdrawal_cap(treasury_balance, appeal.max_withdrawal_bps).unwrap());
+        assert!(appeal.execution_eligible_at > appeal.vote_end);
+        assert_eq!(appeal.appeal_proposal_id, None);
+    }
 
-    #[account(mut)]
-    pub appellant: Signer<'info>,
+    #[test]
+    fn test_appeal_zero_votes_not_approved() {
+        let config = adaptive_config();
+        let appeal = defeated_withdrawal(3).appeal(4, Pubkey::default(), &config, 0, 255).unwrap();
+        assert!(!appeal.approval_met().unwrap());
+        assert!(appeal.quorum_required().unwrap() > 0);
+    }
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-}
+    #[test]
+    fn test_appeal_does_not_inherit_finalizer() {
+        let config = adaptive_config();
+        let mut original = defeated_withdrawal(3);
+        original.finalized_by = Pubkey::new_unique();
+        original.finalized_at = VOTE_END + 1;
+
+        let appeal = original.appeal(4, Pubkey::new_unique(), &config, VOTE_END + 10, 255).unwrap();
+        assert_eq!(appeal.finalized_by, Pubkey::default());
+        assert_eq!(appeal.finalized_at, 0);
+        assert_eq!(appeal.executed_by, Pubkey::default());
+        assert_eq!(appeal.executed_at, None);
+    }
 
-/// Deposit to treasury
-#[derive(Accounts)]
-pub struct DepositToTreasury<'info> {
-    #[account(
-        mut,
-        seeds = [b"dao_config"],
-        bump = dao_config.bump,
-        has_one = treasury @ DaoError::InvalidTreasury
-    )]
-    pub dao_config: Account<'info, DaoConfig>,
+    fn sponsored_proposal(created_at: i64) -> Proposal {
+        let mut proposal = emergency_proposal(created_at);
+        proposal.proposal_type = ProposalType::General;
+        proposal.status = ProposalStatus::Pending;
+        proposal.vote_start = 0;
+        proposal.vote_end = 0;
+        proposal.execution_eligible_at = 0;
+        proposal.sponsored = true;
+        proposal.bond_target = DEFAULT_PROPOSAL_BOND;
+        proposal.funding_deadline = created_at + SPONSORSHIP_FUNDING_WINDOW;
+        proposal.discussion_period = DEFAULT_DISCUSSION_PERIOD;
+        proposal.voting_period = DEFAULT_VOTING_PERIOD;
+        proposal
+    }
 
-    #[account(
-        mut,
-        constraint = treasury.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub treasury: Account<'info, TokenAccount>,
+    #[test]
+    fn test_sponsorship_lapse_refunds_sponsors_in_full() {
+        let mut proposal = sponsored_proposal(QUEUED_AT);
 
-    #[account(
-        mut,
-        constraint = depositor_token_account.owner == depositor.key() @ DaoError::InvalidTokenOwner,
-        constraint = depositor_token_account.mint == dao_config.governance_token_mint @ DaoError::InvalidMint
-    )]
-    pub depositor_token_account: Account<'info, TokenAccount>,
+        assert_eq!(proposal.record_sponsorship(30_000_000_000, QUEUED_AT + 60).unwrap(), 30_000_000_000);
+        assert_eq!(proposal.record_sponsorship(20_000_000_000, QUEUED_AT + 120).unwrap(), 20_000_000_000);
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert_eq!(proposal.bond_raised, 50_000_000_000);
 
-    pub depositor: Signer<'info>,
+        // Refunds wait for the window to lapse
+        let deadline = proposal.funding_deadline;
+        let err = proposal.sponsor_refund(30_000_000_000, deadline).unwrap_err();
+        assert_eq!(err, DaoError::FundingWindowOpen.into());
 
-    pub token_program: Program<'info, Token>,
-}
+        // No more contributions after the deadline; everyone gets their tokens back
+        let err = proposal.record_sponsorship(50_000_000_000, deadline + 1).unwrap_err();
+        assert_eq!(err, DaoError::FundingWindowClosed.into());
+        assert!(proposal.funding_lapsed(deadline + 1));
+        assert_eq!(proposal.sponsor_refund(30_000_000_000, deadline + 1).unwrap(), 30_000_000_000);
+        assert_eq!(proposal.sponsor_refund(20_000_000_000, deadline + 1).unwrap(), 20_000_000_000);
 
-// ============================================================================
-// EVENTS
-// ============================================================================
+        // Cancelling while pending also refunds in full
+        proposal.status = ProposalStatus::Cancelled;
+        assert_eq!(proposal.sponsor_refund(20_000_000_000, QUEUED_AT).unwrap(), 20_000_000_000);
+    }
 
-#[event]
-pub struct DaoInitializedEvent {
-    pub authority: Pubkey,
-    pub treasury: Pubkey,
-    pub discussion_period: i64,
-    pub voting_period: i64,
-    pub proposal_bond: u64,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_full_sponsorship_activates_proposal() {
+        let config = adaptive_config();
+        let mut proposal = sponsored_proposal(QUEUED_AT);
+        let funded_at = QUEUED_AT + 3_600;
+
+        proposal.record_sponsorship(60_000_000_000, QUEUED_AT + 60).unwrap();
+        // Only the remaining bond is taken from the last sponsor
+        assert_eq!(proposal.record_sponsorship(70_000_000_000, funded_at).unwrap(), 40_000_000_000);
+
+        assert_eq!(proposal.status, ProposalStatus::Active);
+        assert_eq!(proposal.bond_raised, proposal.bond_target);
+        assert_eq!(proposal.vote_start, funded_at + config.discussion_period);
+        assert_eq!(proposal.vote_end, proposal.vote_start + config.voting_period);
+        assert_eq!(proposal.execution_eligible_at, proposal.vote_end + EXECUTION_TIMELOCK);
+
+        let err = proposal.record_sponsorship(1, funded_at + 1).unwrap_err();
+        assert_eq!(err, DaoError::ProposalNotPending.into());
+        let err = proposal.sponsor_refund(60_000_000_000, funded_at + 1).unwrap_err();
+        assert_eq!(err, DaoError::ProposalStillActive.into());
+    }
 
-#[event]
-pub struct DaoConfigClosedEvent {
-    pub authority: Pubkey,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_sponsor_bond_return_split_pro_rata() {
+        let mut proposal = sponsored_proposal(QUEUED_AT);
+        proposal.record_sponsorship(75_000_000_000, QUEUED_AT + 60).unwrap();
+        proposal.record_sponsorship(25_000_000_000, QUEUED_AT + 60).unwrap();
+
+        proposal.status = ProposalStatus::Passed;
+        assert_eq!(proposal.sponsor_refund(75_000_000_000, VOTE_END).unwrap(), 75_000_000_000);
+        assert_eq!(proposal.sponsor_refund(25_000_000_000, VOTE_END).unwrap(), 25_000_000_000);
+
+        // Defeated with enough participation: the 50% tier, split by contribution
+        proposal.status = ProposalStatus::Defeated;
+        proposal.for_votes = SUPPLY / 20;
+        proposal.against_votes = SUPPLY / 25;
+        let (tier, return_type) = proposal.bond_return(proposal.bond_target).unwrap();
+        assert_eq!((tier, return_type), (50_000_000_000, "partial"));
+        assert_eq!(proposal.sponsor_refund(75_000_000_000, VOTE_END).unwrap(), 37_500_000_000);
+        assert_eq!(proposal.sponsor_refund(25_000_000_000, VOTE_END).unwrap(), 12_500_000_000);
+
+        // Defeated without participation forfeits every share
+        proposal.for_votes = 0;
+        proposal.against_votes = 0;
+        let err = proposal.sponsor_refund(75_000_000_000, VOTE_END).unwrap_err();
+        assert_eq!(err, DaoError::BondForfeited.into());
+    }
 
-#[event]
-pub struct ConfigUpdateQueuedEvent {
-    pub new_voting_period: Option<i64>,
-    pub new_proposal_bond: Option<u64>,
-    pub new_quorum_percentage: Option<u8>,
-    pub new_approval_threshold: Option<u8>,
-    pub execute_after: i64,
-    pub timestamp: i64,
-}
+    fn snapshot_config() -> DaoConfig {
+        let mut config = adaptive_config();
+        config.general_voting_mode = VotingMode::Snapshot;
+        config
+    }
 
-#[event]
-pub struct ConfigUpdateExecutedEvent {
-    pub voting_period: i64,
-    pub proposal_bond: u64,
-    pub quorum_percentage: u8,
-    pub approval_threshold: u8,
-    pub timestamp: i64,
-}
+    fn voting_proposal(proposal_type: ProposalType, config: &DaoConfig) -> Proposal {
+        let mut proposal = emergency_proposal(QUEUED_AT);
+        proposal.proposal_type = proposal_type;
+        proposal.status = ProposalStatus::Active;
+        proposal.vote_start = VOTE_START;
+        proposal.vote_end = VOTE_END;
+        proposal.voting_mode = config.voting_mode_for(proposal_type);
+        proposal.voting_model = config.voting_model;
+        proposal.approval_threshold = config.approval_threshold;
+        proposal.discussion_period = config.discussion_period;
+        proposal.voting_period = config.voting_period;
+        proposal.conviction_schedule = config.conviction_schedule.clone();
+        proposal
+    }
 
-#[event]
-pub struct ConfigUpdateCancelledEvent {
-    pub authority: Pubkey,
-    pub timestamp: i64,
-}
+    fn escrow(deposited_amount: u64) -> VoteEscrow {
+        VoteEscrow {
+            proposal_id: 7,
+            voter: Pubkey::default(),
+            deposited_amount,
+            deposited_at: VOTE_START + 3600,
+            has_voted: false,
+            vote_choice: None,
+            withdrawn: false,
+            bump: 0,
+            vote_nonce: 0,
+        }
+    }
 
-#[event]
-pub struct DaoPausedEvent {
-    pub paused: bool,
-    pub authority: Pubkey,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_snapshot_vote_uses_current_balance() {
+        let config = snapshot_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+        assert_eq!(proposal.voting_mode, VotingMode::Snapshot);
+
+        // No escrow needed; the balance is recorded and counted without a boost
+        let cast = resolve_vote_weight(&proposal, &config, None, Some(1_000_000_000_000)).unwrap();
+        assert_eq!(
+            cast,
+            CastWeight { vote_weight: 1_000_000_000_000, multiplier_bps: 10000, snapshot_balance: 1_000_000_000_000 }
+        );
 
-#[event]
-pub struct ProposalCreatedEvent {
-    pub proposal_id: u64,
-    pub proposer: Pubkey,
-    pub title: String,
-    pub description_cid: String,
-    pub vote_start: i64,
-    pub vote_end: i64,
-    pub execution_eligible_at: i64,
-    pub snapshot_supply: u64,
-    pub timestamp: i64,
-}
+        // A (possibly borrowed) whale balance is capped at 5% of snapshot supply
+        let cast = resolve_vote_weight(&proposal, &config, None, Some(SUPPLY)).unwrap();
+        assert_eq!(cast.vote_weight, SUPPLY / 20);
+        assert_eq!(cast.snapshot_balance, SUPPLY);
+
+        let err = resolve_vote_weight(&proposal, &config, None, None).unwrap_err();
+        assert_eq!(err, DaoError::VoterTokenAccountRequired.into());
+        let err = resolve_vote_weight(&proposal, &config, None, Some(0)).unwrap_err();
+        assert_eq!(err, DaoError::NoVotingPower.into());
+    }
 
-#[event]
-pub struct ProposalCancelledEvent {
-    pub proposal_id: u64,
-    pub proposer: Pubkey,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_snapshot_double_vote_blocked_by_vote_record() {
+        let config = snapshot_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+        let voter = Pubkey::new_unique();
+
+        // Nothing in the snapshot path marks the voter as having voted: the
+        // VoteRecord PDA (`init` in CastVote) is the only dedupe, and it is
+        // the same address however much the voter's balance changes
+        let first = resolve_vote_weight(&proposal, &config, None, Some(1_000)).unwrap();
+        let second = resolve_vote_weight(&proposal, &config, None, Some(5_000)).unwrap();
+        assert_ne!(first, second);
+
+        let seeds: &[&[u8]] = &[b"vote", &proposal.proposal_id.to_le_bytes(), voter.as_ref()];
+        let (record, _) = Pubkey::find_program_address(seeds, &crate::ID);
+        let (again, _) = Pubkey::find_program_address(seeds, &crate::ID);
+        assert_eq!(record, again);
+
+        let other = Pubkey::new_unique();
+        let (other_record, _) =
+            Pubkey::find_program_address(&[b"vote", &proposal.proposal_id.to_le_bytes(), other.as_ref()], &crate::ID);
+        assert_ne!(record, other_record);
+    }
 
-/// SECURITY FIX: New event for vote token deposits
-#[event]
-pub struct VoteTokensDepositedEvent {
-    pub proposal_id: u64,
-    pub voter: Pubkey,
-    pub amount: u64,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_treasury_withdrawal_still_requires_escrow() {
+        let config = snapshot_config();
+        for proposal_type in [
+            ProposalType::TreasuryWithdrawal,
+            ProposalType::ParameterChange,
+            ProposalType::EmergencyConfigCancel,
+        ] {
+            assert_eq!(config.voting_mode_for(proposal_type), VotingMode::Escrow);
+        }
 
-#[event]
-pub struct VoteCastEvent {
-    pub proposal_id: u64,
-    pub voter: Pubkey,
-    pub vote_choice: VoteChoice,
-    pub vote_weight: u64,
-    pub timestamp: i64,
-}
+        let proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        let err = resolve_vote_weight(&proposal, &config, None, Some(1_000_000_000_000)).unwrap_err();
+        assert_eq!(err, DaoError::VoteEscrowRequired.into());
 
-/// SECURITY FIX: New event for vote retractions
-#[event]
-pub struct VoteRetractedEvent {
-    pub proposal_id: u64,
-    pub voter: Pubkey,
-    pub vote_weight: u64,
-    pub timestamp: i64,
-}
+        // With an escrow the usual conviction-boosted weight applies
+        let cast = resolve_vote_weight(&proposal, &config, Some(&escrow(100_000_000_000)), None).unwrap();
+        assert_eq!(cast.vote_weight, 110_000_000_000);
+        assert_eq!(cast.snapshot_balance, 0);
 
-/// SECURITY FIX: New event for vote token withdrawals
-#[event]
-pub struct VoteTokensWithdrawnEvent {
-    pub proposal_id: u64,
-    pub voter: Pubkey,
-    pub amount: u64,
-    pub timestamp: i64,
-}
+        let mut voted = escrow(100_000_000_000);
+        voted.has_voted = true;
+        let err = resolve_vote_weight(&proposal, &config, Some(&voted), None).unwrap_err();
+        assert_eq!(err, DaoError::AlreadyVoted.into());
+    }
 
-#[event]
-pub struct ProposalFinalizedEvent {
-    pub proposal_id: u64,
-    pub status: ProposalStatus,
-    pub for_votes: u64,
-    pub against_votes: u64,
-    pub abstain_votes: u64,
-    pub quorum_met: bool,
-    pub timestamp: i64,
-}
+    fn delegation(delegator: Pubkey, delegate: Pubkey, delegated_at: i64) -> VoteDelegation {
+        VoteDelegation {
+            delegator,
+            delegate,
+            delegated_at,
+            revoked_at: 0,
+            bump: 0,
+        }
+    }
 
-#[event]
-pub struct ProposalExecutedEvent {
-    pub proposal_id: u64,
-    pub executor: Pubkey,
-    pub amount: u64,
-    pub recipient: Pubkey,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_delegated_vote_uses_delegator_escrow() {
+        let config = adaptive_config();
+        let proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        let (delegator, delegate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let delegation = delegation(delegator, delegate, QUEUED_AT - 10);
+        let mut delegator_escrow = escrow(100_000_000_000);
+        delegator_escrow.voter = delegator;
+
+        // Same conviction-boosted weight the delegator would have voted with
+        let cast =
+            delegated_vote_weight(&proposal, &config, &delegation, &delegator_escrow, delegate).unwrap();
+        assert_eq!(cast.vote_weight, 110_000_000_000);
+
+        // Only the named delegate, and only with the delegator's own escrow
+        let err = delegated_vote_weight(&proposal, &config, &delegation, &delegator_escrow, delegator)
+            .unwrap_err();
+        assert_eq!(err, DaoError::InvalidDelegation.into());
+        let err = delegated_vote_weight(&proposal, &config, &delegation, &escrow(100_000_000_000), delegate)
+            .unwrap_err();
+        assert_eq!(err, DaoError::InvalidDelegation.into());
+
+        // The delegator already voted with these tokens
+        let mut voted = delegator_escrow.clone();
+        voted.has_voted = true;
+        let err = delegated_vote_weight(&proposal, &config, &delegation, &voted, delegate).unwrap_err();
+        assert_eq!(err, DaoError::DelegatedVotesUsed.into());
+
+        // Snapshot proposals have no escrow to delegate
+        let config = snapshot_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+        let err = delegated_vote_weight(&proposal, &config, &delegation, &delegator_escrow, delegate)
+            .unwrap_err();
+        assert_eq!(err, DaoError::DelegationRequiresEscrow.into());
+    }
 
-#[event]
-pub struct BondReturnedEvent {
-    pub proposal_id: u64,
-    pub proposer: Pubkey,
-    pub amount: u64,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_delegation_changes_apply_to_later_proposals_only() {
+        let config = adaptive_config();
+        let proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        let (delegator, delegate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut delegator_escrow = escrow(100_000_000_000);
+        delegator_escrow.voter = delegator;
+
+        // Delegated after the proposal was created: not counted on it
+        let late = delegation(delegator, delegate, QUEUED_AT + 1);
+        assert!(late.is_effective_for(QUEUED_AT + 1));
+        let err = delegated_vote_weight(&proposal, &config, &late, &delegator_escrow, delegate).unwrap_err();
+        assert_eq!(err, DaoError::DelegationNotEffective.into());
+
+        // Revoked mid-proposal: still counted on it, but not on later proposals
+        let mut revoked = delegation(delegator, delegate, QUEUED_AT - 10);
+        revoked.revoked_at = QUEUED_AT + 1;
+        assert!(delegated_vote_weight(&proposal, &config, &revoked, &delegator_escrow, delegate).is_ok());
+        assert!(!revoked.is_effective_for(QUEUED_AT + 1));
+        assert!(!revoked.is_effective_for(QUEUED_AT + 100));
+
+        // Revoked by the time the proposal was created
+        revoked.revoked_at = QUEUED_AT;
+        let err = delegated_vote_weight(&proposal, &config, &revoked, &delegator_escrow, delegate).unwrap_err();
+        assert_eq!(err, DaoError::DelegationNotEffective.into());
+    }
 
-/// Y7.2: Event emitted when a proposal is appealed
-#[event]
-pub struct ProposalAppealedEvent {
-    pub original_proposal_id: u64,
-    pub appeal_proposal_id: u64,
-    pub appellant: Pubkey,
-    pub appeal_bond: u64,
-    pub timestamp: i64,
-}
+    #[test]
+    fn test_retract_releases_delegated_escrow() {
+        let delegator = Pubkey::new_unique();
+        let mut record = VoteRecord {
+            proposal_id: 7,
+            voter: Pubkey::new_unique(),
+            vote_choice: VoteChoice::For,
+            vote_weight: 110,
+            voted_at: VOTE_START,
+            bump: 0,
+            multiplier_bps: 11000,
+            snapshot_balance: 0,
+            delegator,
+            delegated_weight: 110,
+            delegated_from_position: false,
+        };
+        let mut delegator_escrow = escrow(100);
+        delegator_escrow.voter = delegator;
+        delegator_escrow.has_voted = true;
+        delegator_escrow.vote_choice = Some(VoteChoice::For);
+
+        // Delegated-only vote: the delegator's escrow is required, the voter's isn't
+        let err = release_vote_escrows(&record, None, None, None, None).unwrap_err();
+        assert_eq!(err, DaoError::DelegatorEscrowRequired.into());
+        let mut stranger = delegator_escrow.clone();
+        stranger.voter = Pubkey::new_unique();
+        let err = release_vote_escrows(&record, None, None, Some(&mut stranger), None).unwrap_err();
+        assert_eq!(err, DaoError::InvalidDelegation.into());
+
+        release_vote_escrows(&record, None, None, Some(&mut delegator_escrow), None).unwrap();
+        assert!(!delegator_escrow.has_voted);
+        assert_eq!(delegator_escrow.vote_choice, None);
+
+        // Own plus delegated weight releases both escrows
+        record.vote_weight = 220;
+        delegator_escrow.has_voted = true;
+        let mut own = escrow(100);
+        own.has_voted = true;
+        let err = release_vote_escrows(&record, None, None, Some(&mut delegator_escrow), None).unwrap_err();
+        assert_eq!(err, DaoError::VoteEscrowRequired.into());
+        release_vote_escrows(&record, Some(&mut own), None, Some(&mut delegator_escrow), None).unwrap();
+        assert!(!own.has_voted);
+        assert!(!delegator_escrow.has_voted);
+    }
 
-#[event]
-pub struct TreasuryDepositEvent {
-    pub depositor: Pubkey,
-    pub amount: u64,
-    pub total_deposits: u64,
-    pub timestamp: i64,
-}
+    fn position(deposited_amount: u64) -> VoterPosition {
+        VoterPosition {
+            voter: Pubkey::new_unique(),
+            deposited_amount,
+            deposited_at: VOTE_START + 3600,
+            votes: Vec::new(),
+            bump: 0,
+        }
+    }
 
-// ============================================================================
-// ERRORS
-// ============================================================================
+    #[test]
+    fn test_delegate_votes_with_delegator_position() {
+        let config = adaptive_config();
+        let proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        let delegate = Pubkey::new_unique();
+        // The delegator has only a standing position, nothing on this proposal
+        let mut delegator_position = position(100_000_000_000);
+        let delegation = delegation(delegator_position.voter, delegate, QUEUED_AT - 10);
+
+        // Same boosted weight the delegator would have voted the position with
+        let cast = delegated_position_weight(&proposal, &delegation, &delegator_position, delegate).unwrap();
+        assert_eq!(cast.vote_weight, 110_000_000_000);
+        let err = delegated_position_weight(&proposal, &delegation, &position(100), delegate).unwrap_err();
+        assert_eq!(err, DaoError::InvalidDelegation.into());
+
+        // Counting it locks the delegator's position on the proposal
+        delegator_position
+            .record_vote(proposal.proposal_id, proposal.vote_end, VOTE_START + 7200)
+            .unwrap();
+        let err = delegated_position_weight(&proposal, &delegation, &delegator_position, delegate).unwrap_err();
+        assert_eq!(err, DaoError::DelegatedVotesUsed.into());
+        let err = position_vote_weight(&proposal, &delegator_position).unwrap_err();
+        assert_eq!(err, DaoError::AlreadyVoted.into());
+        assert!(delegator_position.ensure_unlocked(VOTE_END).is_err());
+
+        // Retracting releases the position only if the record says it was used
+        let mut record = VoteRecord {
+            proposal_id: proposal.proposal_id,
+            voter: delegate,
+            vote_choice: VoteChoice::For,
+            vote_weight: cast.vote_weight,
+            voted_at: VOTE_START + 7200,
+            bump: 0,
+            multiplier_bps: cast.multiplier_bps,
+            snapshot_balance: 0,
+            delegator: delegator_position.voter,
+            delegated_weight: cast.vote_weight,
+            delegated_from_position: false,
+        };
+        let err = release_vote_escrows(&record, None, None, None, Some(&mut delegator_position)).unwrap_err();
+        assert_eq!(err, DaoError::DelegatorEscrowRequired.into());
+        record.delegated_from_position = true;
+        release_vote_escrows(&record, None, None, None, Some(&mut delegator_position)).unwrap();
+        assert!(delegator_position.ensure_unlocked(VOTE_START).is_ok());
+    }
 
-#[error_code]
-pub enum DaoError {
-    #[msg("Voting period must be between 1 and 14 days")]
-    InvalidVotingPeriod,
+    #[test]
+    fn test_position_votes_on_overlapping_proposals() {
+        let config = adaptive_config();
+        let mut voter_position = position(100_000_000_000);
+
+        // Three proposals open at once, ending a day apart
+        let proposals: Vec<Proposal> = (0..3)
+            .map(|i| {
+                let mut proposal = voting_proposal(ProposalType::General, &config);
+                proposal.proposal_id = 7 + i;
+                proposal.vote_end = VOTE_END + i as i64 * 86_400;
+                proposal
+            })
+            .collect();
+
+        // One deposit carries the full boosted weight on each of them
+        for proposal in &proposals {
+            let cast = position_vote_weight(proposal, &voter_position).unwrap();
+            assert_eq!(cast.vote_weight, 110_000_000_000);
+            voter_position
+                .record_vote(proposal.proposal_id, proposal.vote_end, VOTE_START + 7200)
+                .unwrap();
+        }
+        let err = position_vote_weight(&proposals[1], &voter_position).unwrap_err();
+        assert_eq!(err, DaoError::AlreadyVoted.into());
+
+        // Locked until the last of them has ended
+        let err = voter_position.ensure_unlocked(VOTE_END + 1).unwrap_err();
+        assert_eq!(err, DaoError::TokensLockedDuringVoting.into());
+        let err = voter_position.ensure_unlocked(VOTE_END + 2 * 86_400).unwrap_err();
+        assert_eq!(err, DaoError::TokensLockedDuringVoting.into());
+        voter_position.ensure_unlocked(VOTE_END + 2 * 86_400 + 1).unwrap();
+
+        let err = position_vote_weight(&proposals[0], &position(0)).unwrap_err();
+        assert_eq!(err, DaoError::NoVotingPower.into());
+    }
 
-    #[msg("Proposal bond must be at least 1 token")]
-    InvalidProposalBond,
+    #[test]
+    fn test_position_vote_limit_frees_ended_proposals() {
+        let mut voter_position = position(100);
+        for i in 0..MAX_POSITION_VOTES as u64 {
+            voter_position.record_vote(i, VOTE_END + i as i64, VOTE_START).unwrap();
+        }
+        let err = voter_position.record_vote(100, VOTE_END, VOTE_START).unwrap_err();
+        assert_eq!(err, DaoError::PositionVoteLimitReached.into());
+
+        // Once the first proposal's voting ends its slot is reused
+        voter_position.record_vote(100, VOTE_END + 100, VOTE_END + 1).unwrap();
+        assert_eq!(voter_position.votes.len(), MAX_POSITION_VOTES);
+        assert!(!voter_position.has_voted_on(0));
+        assert!(voter_position.has_voted_on(100));
+    }
 
-    #[msg("Quorum percentage must be between 1 and 100")]
-    InvalidQuorumPercentage,
+    #[test]
+    fn test_retract_releases_position_vote() {
+        let mut voter_position = position(100);
+        voter_position.record_vote(7, VOTE_END, VOTE_START).unwrap();
+        voter_position.record_vote(8, VOTE_END + 86_400, VOTE_START).unwrap();
+        let record = VoteRecord {
+            proposal_id: 8,
+            voter: voter_position.voter,
+            vote_choice: VoteChoice::Against,
+            vote_weight: 110,
+            voted_at: VOTE_START,
+            bump: 0,
+            multiplier_bps: 11000,
+            snapshot_balance: 0,
+            delegator: Pubkey::default(),
+            delegated_weight: 0,
+            delegated_from_position: false,
+        };
 
-    #[msg("Approval threshold must be between 1 and 100")]
-    InvalidApprovalThreshold,
+        // Retracting the later vote leaves only the earlier lock
+        release_vote_escrows(&record, None, Some(&mut voter_position), None, None).unwrap();
+        assert!(!voter_position.has_voted_on(8));
+        voter_position.ensure_unlocked(VOTE_END + 1).unwrap();
 
-    #[msg("DAO is currently paused")]
-    DaoPaused,
+        // Nothing left to release for that proposal
+        let err = release_vote_escrows(&record, None, Some(&mut voter_position), None, None).unwrap_err();
+        assert_eq!(err, DaoError::VoteEscrowRequired.into());
+        let err = voter_position.release_vote(8).unwrap_err();
+        assert_eq!(err, DaoError::NotVoted.into());
+    }
 
-    #[msg("Title length must be between 1 and 128 characters")]
-    InvalidTitleLength,
+    #[test]
+    fn test_position_deposit_time_for_conviction() {
+        // A position created by migrating an escrow keeps its deposit time
+        let mut voter_position = position(0);
+        voter_position.add_deposit(100, VOTE_START - 10).unwrap();
+        assert_eq!(voter_position.deposited_at, VOTE_START - 10);
 
-    #[msg("Description CID length must be between 1 and 64 characters")]
-    InvalidDescriptionCidLength,
+        // Migrating an older escrow into it doesn't backdate the position
+        voter_position.add_deposit(50, VOTE_START - 1_000).unwrap();
+        assert_eq!(voter_position.deposited_at, VOTE_START - 10);
 
-    #[msg("Proposal is not active")]
-    ProposalNotActive,
+        // A top-up counts the whole position from the top-up
+        voter_position.add_deposit(50, VOTE_START + 10).unwrap();
+        assert_eq!(voter_position.deposited_at, VOTE_START + 10);
+        assert_eq!(voter_position.deposited_amount, 200);
 
-    #[msg("Voting has not started yet")]
-    VotingNotStarted,
+        let err = voter_position.add_deposit(u64::MAX, VOTE_START).unwrap_err();
+        assert_eq!(err, DaoError::Overflow.into());
+    }
 
-    #[msg("Voting period has ended")]
-    VotingEnded,
+    #[test]
+    fn test_checkpoint_emits_tallies_and_percentages() {
+        let mut proposal = voting_proposal(ProposalType::General, &snapshot_config());
+        proposal.for_votes = SUPPLY / 10;
+        proposal.against_votes = SUPPLY / 20;
+        proposal.abstain_votes = SUPPLY / 20;
+
+        let event = proposal.checkpoint(VOTE_END - 600).unwrap();
+        assert_eq!(event.proposal_id, proposal.proposal_id);
+        assert_eq!(event.for_votes, SUPPLY / 10);
+        assert_eq!(event.against_votes, SUPPLY / 20);
+        assert_eq!(event.abstain_votes, SUPPLY / 20);
+        assert_eq!(event.participation_pct, 20);
+        assert_eq!(event.time_remaining, 600);
+        assert_eq!(proposal.last_checkpoint_at, VOTE_END - 600);
+        // Tallies untouched
+        assert_eq!(proposal.for_votes, SUPPLY / 10);
+    }
 
-    #[msg("Voting is not currently active")]
-    VotingNotActive,
+    #[test]
+    fn test_checkpoint_rate_limited_per_hour() {
+        let mut proposal = voting_proposal(ProposalType::General, &snapshot_config());
+        let first = VOTE_START + 60;
+        proposal.checkpoint(first).unwrap();
 
-    #[msg("Voting period has not ended yet")]
-    VotingNotEnded,
+        let err = proposal.checkpoint(first + CHECKPOINT_INTERVAL - 1).err().unwrap();
+        assert_eq!(err, DaoError::CheckpointTooSoon.into());
+        assert_eq!(proposal.last_checkpoint_at, first);
 
-    #[msg("No voting power (zero token balance at snapshot)")]
-    NoVotingPower,
+        proposal.checkpoint(first + CHECKPOINT_INTERVAL).unwrap();
+        // After voting ends the remaining time bottoms out at zero
+        let event = proposal.checkpoint(VOTE_END + 2 * CHECKPOINT_INTERVAL).unwrap();
+        assert_eq!(event.time_remaining, 0);
+    }
 
-    #[msg("Already voted on this proposal")]
-    AlreadyVoted,
+    #[test]
+    fn test_checkpoint_rejected_once_finalized() {
+        let mut proposal = voting_proposal(ProposalType::General, &snapshot_config());
+        for status in [ProposalStatus::Passed, ProposalStatus::Defeated, ProposalStatus::Executed] {
+            proposal.status = status;
+            let err = proposal.checkpoint(VOTE_END + 1).err().unwrap();
+            assert_eq!(err, DaoError::ProposalNotActive.into());
+        }
+        assert_eq!(proposal.last_checkpoint_at, 0);
+    }
 
-    #[msg("Proposal has not passed")]
-    ProposalNotPassed,
+    fn passed_withdrawal(proposal_id: u64, amount: u64) -> Proposal {
+        let mut proposal = defeated_withdrawal(proposal_id);
+        proposal.status = ProposalStatus::Active;
+        proposal.execution_data = Some(single_payout(amount));
+        proposal.execution_eligible_at = VOTE_END + EXECUTION_TIMELOCK;
+        proposal.finalized_at = VOTE_END + 1;
+        proposal
+    }
 
-    #[msg("Proposal has already been executed")]
-    ProposalAlreadyExecuted,
+    #[test]
+    fn test_second_withdrawal_over_balance_passes_unfunded() {
+        let mut config = adaptive_config();
+        let treasury = 10_000;
+        let mut first = passed_withdrawal(1, 6_000);
+        let mut second = passed_withdrawal(2, 5_000);
+
+        first.settle_passed(&mut config, treasury).unwrap();
+        assert_eq!(first.status, ProposalStatus::Passed);
+        assert_eq!(config.committed_outflow, 6_000);
+
+        // Each fits the balance alone, but not on top of the first
+        second.settle_passed(&mut config, treasury).unwrap();
+        assert_eq!(second.status, ProposalStatus::PassedUnfunded);
+        assert_eq!(config.committed_outflow, 6_000);
+
+        let err = second.fund(&mut config, treasury, VOTE_END + 10).err().unwrap();
+        assert_eq!(err, DaoError::TreasuryOvercommitted.into());
+        assert_eq!(second.status, ProposalStatus::PassedUnfunded);
+    }
 
-    #[msg("This proposal type cannot be executed")]
-    ProposalNotExecutable,
+    #[test]
+    fn test_executing_first_withdrawal_frees_headroom() {
+        let mut config = adaptive_config();
+        let mut first = passed_withdrawal(1, 6_000);
+        let mut second = passed_withdrawal(2, 5_000);
+        first.settle_passed(&mut config, 10_000).unwrap();
+        second.settle_passed(&mut config, 10_000).unwrap();
+
+        // execute_proposal transfers 6_000 out and releases the commitment
+        config.release_outflow(6_000);
+        first.status = ProposalStatus::Executed;
+        assert_eq!(config.committed_outflow, 0);
+
+        let err = second.fund(&mut config, 4_000, VOTE_END + EXECUTION_TIMELOCK).err().unwrap();
+        assert_eq!(err, DaoError::TreasuryOvercommitted.into());
+
+        // A deposit brings the balance back over the second withdrawal
+        let amount = second.fund(&mut config, 5_000, VOTE_END + EXECUTION_TIMELOCK).unwrap();
+        assert_eq!(amount, 5_000);
+        assert_eq!(second.status, ProposalStatus::Passed);
+        assert_eq!(config.committed_outflow, 5_000);
+    }
 
-    #[msg("No execution data provided")]
-    NoExecutionData,
+    #[test]
+    fn test_non_withdrawal_passes_without_commitment() {
+        let mut config = adaptive_config();
+        let mut proposal = emergency_proposal(VOTE_START);
+        proposal.settle_passed(&mut config, 0).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Passed);
+        assert_eq!(config.committed_outflow, 0);
+    }
 
-    #[msg("Proposal is still active")]
-    ProposalStillActive,
+    #[test]
+    fn test_expiry_releases_commitment() {
+        let mut config = adaptive_config();
+        let mut first = passed_withdrawal(1, 6_000);
+        let mut second = passed_withdrawal(2, 5_000);
+        first.settle_passed(&mut config, 10_000).unwrap();
+        second.settle_passed(&mut config, 10_000).unwrap();
+
+        let window_end = first.execution_eligible_at + EXECUTION_WINDOW;
+        let err = first.expire(&mut config, window_end).err().unwrap();
+        assert_eq!(err, DaoError::ExecutionWindowOpen.into());
+
+        assert_eq!(first.expire(&mut config, window_end + 1).unwrap(), 6_000);
+        assert_eq!(first.status, ProposalStatus::Expired);
+        assert_eq!(config.committed_outflow, 0);
+
+        // The unfunded one expired too and held nothing to release
+        let err = second.fund(&mut config, 10_000, window_end + 1).err().unwrap();
+        assert_eq!(err, DaoError::ExecutionWindowClosed.into());
+        assert_eq!(second.expire(&mut config, window_end + 1).unwrap(), 0);
+        assert_eq!(second.status, ProposalStatus::Expired);
+
+        let err = first.expire(&mut config, window_end + 1).err().unwrap();
+        assert_eq!(err, DaoError::ProposalNotPassed.into());
+    }
 
-    #[msg("Bond has already been returned")]
-    BondAlreadyReturned,
+    #[test]
+    fn test_cancel_passed_withdrawal_releases_commitment() {
+        let mut config = adaptive_config();
+        let mut first = passed_withdrawal(1, 6_000);
+        let mut second = passed_withdrawal(2, 5_000);
+        first.settle_passed(&mut config, 10_000).unwrap();
+        second.settle_passed(&mut config, 10_000).unwrap();
+
+        assert_eq!(first.cancel_passed_withdrawal(&mut config).unwrap(), 6_000);
+        assert_eq!(first.status, ProposalStatus::Cancelled);
+        assert_eq!(config.committed_outflow, 0);
+        assert_eq!(second.fund(&mut config, 10_000, VOTE_END + 10).unwrap(), 5_000);
+
+        let err = first.cancel_passed_withdrawal(&mut config).err().unwrap();
+        assert_eq!(err, DaoError::ProposalNotPassed.into());
+    }
 
-    #[msg("Bond is forfeited for defeated proposals")]
-    BondForfeited,
+    #[test]
+    fn test_release_outflow_saturates_for_untracked_withdrawals() {
+        let mut config = adaptive_config();
+        config.committed_outflow = 100;
+        config.release_outflow(1_000);
+        assert_eq!(config.committed_outflow, 0);
+    }
 
-    #[msg("Invalid amount")]
-    InvalidAmount,
+    #[test]
+    fn test_unfunded_and_expired_withdrawals_return_full_bond() {
+        let mut proposal = passed_withdrawal(1, 6_000);
+        for status in [ProposalStatus::PassedUnfunded, ProposalStatus::Expired, ProposalStatus::Cancelled] {
+            proposal.status = status;
+            assert_eq!(proposal.bond_return(DEFAULT_PROPOSAL_BOND).unwrap().0, DEFAULT_PROPOSAL_BOND);
+        }
 
-    #[msg("Unauthorized: Only DAO authority can perform this action")]
-    UnauthorizedAuthority,
+        // Cancelled before finalization still forfeits
+        proposal.finalized_at = 0;
+        let err = proposal.bond_return(DEFAULT_PROPOSAL_BOND).err().unwrap();
+        assert_eq!(err, DaoError::BondForfeited.into());
+    }
 
-    #[msg("Invalid treasury account")]
-    InvalidTreasury,
+    fn quadratic_config() -> DaoConfig {
+        let mut config = adaptive_config();
+        config.voting_model = VotingModel::Quadratic;
+        config
+    }
 
-    #[msg("Only the proposer can perform this action")]
-    NotProposer,
+    #[test]
+    fn test_integer_sqrt_rounds_down() {
+        assert_eq!(integer_sqrt(0), 0);
+        assert_eq!(integer_sqrt(1), 1);
+        assert_eq!(integer_sqrt(3), 1);
+        assert_eq!(integer_sqrt(4), 2);
+        assert_eq!(integer_sqrt(99), 9);
+        assert_eq!(integer_sqrt(100), 10);
+        assert_eq!(integer_sqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(VotingModel::TokenWeighted.vote_power(99), 99);
+        assert_eq!(VotingModel::Quadratic.vote_power(99), 9);
+    }
 
-    #[msg("Arithmetic overflow")]
-    Overflow,
+    #[test]
+    fn test_quadratic_vote_weight() {
+        let mut config = quadratic_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+        assert_eq!(proposal.voting_model, VotingModel::Quadratic);
+
+        // sqrt(100_000_000_000) = 316_227, then the 1.1x first-half boost
+        let cast = resolve_vote_weight(&proposal, &config, Some(&escrow(100_000_000_000)), None).unwrap();
+        assert_eq!(cast, CastWeight { vote_weight: 347_849, multiplier_bps: 11000, snapshot_balance: 0 });
+        let cast = position_vote_weight(&proposal, &position(100_000_000_000)).unwrap();
+        assert_eq!(cast.vote_weight, 347_849);
+        // Four times the tokens only doubles the weight
+        let cast = resolve_vote_weight(&proposal, &config, Some(&escrow(400_000_000_000)), None).unwrap();
+        assert_eq!(cast.vote_weight, 695_700);
+
+        // Snapshot balances are capped first, then square-rooted
+        config.general_voting_mode = VotingMode::Snapshot;
+        let snapshot = voting_proposal(ProposalType::General, &config);
+        let cast = resolve_vote_weight(&snapshot, &config, None, Some(SUPPLY)).unwrap();
+        assert_eq!(cast.vote_weight, integer_sqrt(SUPPLY / 20));
+
+        // Quorum is measured in the same units: 10% of sqrt(supply)
+        assert_eq!(proposal.vote_supply(), 31_622_776);
+        assert_eq!(proposal.quorum_required().unwrap(), 3_162_277);
+
+        // A model change after creation doesn't reach the open proposal
+        config.voting_model = VotingModel::TokenWeighted;
+        let cast = resolve_vote_weight(&proposal, &config, Some(&escrow(100_000_000_000)), None).unwrap();
+        assert_eq!(cast.vote_weight, 347_849);
+    }
 
-    #[msg("Arithmetic underflow")]
-    Underflow,
+    #[test]
+    fn test_quadratic_retract_never_underflows() {
+        let config = quadratic_config();
+        let proposal = voting_proposal(ProposalType::General, &config);
+
+        // Non-square deposits, so every weight is rounded down
+        let deposits = [2u64, 3, 99_999, 1_000_001, 123_456_789];
+        let weights: Vec<u64> = deposits
+            .iter()
+            .map(|&amount| resolve_vote_weight(&proposal, &config, Some(&escrow(amount)), None).unwrap().vote_weight)
+            .collect();
+        // sqrt(2) = 1, boosted 1.1x and rounded down again
+        assert_eq!(weights[..2], [1, 1]);
+        let tally: u64 = weights.iter().sum();
+
+        // Retract subtracts each recorded weight, so any order drains the
+        // tally to exactly zero
+        for order in [weights.clone(), weights.iter().rev().copied().collect()] {
+            let mut for_votes = tally;
+            for weight in order {
+                for_votes = for_votes.checked_sub(weight).unwrap();
+            }
+            assert_eq!(for_votes, 0);
+        }
+    }
 
-    #[msg("Invalid token mint")]
-    InvalidMint,
+    #[test]
+    fn test_config_change_mid_proposal_uses_snapshot() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::General, &config);
+        let mut pending = sponsored_proposal(QUEUED_AT);
+        proposal.for_votes = SUPPLY / 10;
+        proposal.against_votes = SUPPLY / 15;
+        let quorum_required = proposal.quorum_required().unwrap();
+
+        // Every parameter the proposal depends on changes after creation,
+        // which only proposals created afterwards pick up
+        let config = DaoConfig {
+            approval_threshold: 90,
+            quorum_percentage: 50,
+            proposal_bond: DEFAULT_PROPOSAL_BOND * 2,
+            discussion_period: MIN_DISCUSSION_PERIOD,
+            voting_period: MIN_VOTING_PERIOD,
+            ..config
+        };
+        let later = voting_proposal(ProposalType::General, &config);
+        assert_eq!(later.approval_threshold, 90);
+        assert_eq!(later.voting_period, MIN_VOTING_PERIOD);
+
+        // 60% FOR still clears the 51% it was created under
+        assert!(proposal.approval_met().unwrap());
+        assert_eq!(proposal.quorum_required().unwrap(), quorum_required);
+        proposal.status = ProposalStatus::Passed;
+        assert_eq!(proposal.bond_return(proposal.bond_amount).unwrap().0, DEFAULT_PROPOSAL_BOND);
+
+        // A sponsored proposal activating later keeps its snapshotted periods
+        let funded_at = QUEUED_AT + 3_600;
+        pending.record_sponsorship(DEFAULT_PROPOSAL_BOND, funded_at).unwrap();
+        assert_eq!(pending.vote_start, funded_at + DEFAULT_DISCUSSION_PERIOD);
+        assert_eq!(pending.vote_end, pending.vote_start + DEFAULT_VOTING_PERIOD);
+
+        // Execution window is the proposal's own
+        let window_end = proposal.execution_eligible_at + EXECUTION_WINDOW;
+        assert!(!proposal.execution_expired(window_end));
+        assert!(proposal.execution_expired(window_end + 1));
+    }
 
-    #[msg("Invalid token account owner")]
-    InvalidTokenOwner,
+    #[test]
+    fn test_manifest_matches_proposal() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        proposal.execution_data = Some(single_payout(5_000));
+        proposal.treasury_balance_snapshot = 1_000_000;
+
+        let manifest = proposal.manifest(QUEUED_AT).unwrap();
+        assert_eq!(manifest.schema_version, PROPOSAL_MANIFEST_VERSION);
+        assert_eq!(manifest.proposal_id, proposal.proposal_id);
+        assert_eq!(manifest.proposal_type, ProposalType::TreasuryWithdrawal);
+        assert_eq!(manifest.status, ProposalStatus::Active);
+        assert_eq!(manifest.voting_mode, VotingMode::Escrow);
+        assert_eq!(manifest.voting_model, VotingModel::TokenWeighted);
+        assert_eq!((manifest.discussion_period, manifest.voting_period), (DEFAULT_DISCUSSION_PERIOD, DEFAULT_VOTING_PERIOD));
+        assert_eq!((manifest.vote_start, manifest.vote_end), (VOTE_START, VOTE_END));
+        assert_eq!(manifest.execution_eligible_at, proposal.execution_eligible_at);
+        assert_eq!(manifest.execution_window, EXECUTION_WINDOW);
+        assert_eq!(manifest.snapshot_supply, SUPPLY);
+        assert_eq!(manifest.quorum_percentage, DEFAULT_QUORUM_PERCENTAGE);
+        assert_eq!(manifest.quorum_required, SUPPLY / 10);
+        assert_eq!(manifest.approval_threshold, DEFAULT_APPROVAL_THRESHOLD);
+        assert_eq!(manifest.bond_amount, DEFAULT_PROPOSAL_BOND);
+        assert!(!manifest.sponsored);
+        assert_eq!(manifest.max_withdrawal_bps, DEFAULT_MAX_WITHDRAWAL_BPS);
+        assert_eq!(
+            manifest.withdrawal_cap,
+            withdrawal_cap(1_000_000, DEFAULT_MAX_WITHDRAWAL_BPS).unwrap()
+        );
+        assert_eq!(manifest.withdrawal_amount, Some(5_000));
+        assert_eq!(manifest.timestamp, QUEUED_AT);
+
+        // Appeals post 1.5x the bond and vote at once for 1.5x the period
+        proposal.status = ProposalStatus::Defeated;
+        let appeal = proposal.appeal(8, Pubkey::default(), &config, VOTE_END + 1, 0).unwrap();
+        let manifest = appeal.manifest(VOTE_END + 1).unwrap();
+        assert_eq!(manifest.bond_amount, DEFAULT_PROPOSAL_BOND * 3 / 2);
+        assert_eq!(manifest.discussion_period, 0);
+        assert_eq!(manifest.voting_period, appeal.vote_end - appeal.vote_start);
+    }
 
-    #[msg("Invalid bond escrow account")]
-    InvalidBondEscrow,
+    #[test]
+    fn test_emergency_urgency_terms() {
+        let standard = ProposalUrgency::Standard;
+        let emergency = ProposalUrgency::Emergency;
 
-    #[msg("Treasury must be owned by DAO PDA")]
-    InvalidTreasuryOwner,
+        assert_eq!(standard.bond(DEFAULT_PROPOSAL_BOND).unwrap(), DEFAULT_PROPOSAL_BOND);
+        assert_eq!(emergency.bond(DEFAULT_PROPOSAL_BOND).unwrap(), 3 * DEFAULT_PROPOSAL_BOND);
+        let err = emergency.bond(u64::MAX).unwrap_err();
+        assert_eq!(err, DaoError::Overflow.into());
 
-    #[msg("Bond escrow must be owned by DAO PDA")]
-    InvalidBondEscrowOwner,
+        assert_eq!(standard.discussion_period(DEFAULT_DISCUSSION_PERIOD), DEFAULT_DISCUSSION_PERIOD);
+        assert_eq!(emergency.discussion_period(DEFAULT_DISCUSSION_PERIOD), 0);
 
-    #[msg("Recipient does not match proposal execution data")]
-    InvalidRecipient,
+        // Raised to 66%, but a stricter DAO threshold still applies
+        assert_eq!(standard.approval_threshold(DEFAULT_APPROVAL_THRESHOLD), DEFAULT_APPROVAL_THRESHOLD);
+        assert_eq!(emergency.approval_threshold(DEFAULT_APPROVAL_THRESHOLD), 66);
+        assert_eq!(emergency.approval_threshold(75), 75);
+    }
 
-    #[msg("Insufficient treasury balance")]
-    InsufficientTreasuryBalance,
+    #[test]
+    fn test_emergency_proposal_needs_higher_approval() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        proposal.urgency = ProposalUrgency::Emergency;
+        proposal.approval_threshold = proposal.urgency.approval_threshold(config.approval_threshold);
+        proposal.for_votes = 60;
+        proposal.against_votes = 40;
+
+        // 60% would pass a standard proposal but not an emergency one
+        assert!(!proposal.approval_met().unwrap());
+        proposal.for_votes = 66;
+        proposal.against_votes = 34;
+        assert!(proposal.approval_met().unwrap());
+
+        // Sponsors fund the tripled bond; voting opens as soon as it's covered
+        let mut pending = sponsored_proposal(QUEUED_AT);
+        pending.urgency = ProposalUrgency::Emergency;
+        pending.bond_target = pending.urgency.bond(DEFAULT_PROPOSAL_BOND).unwrap();
+        pending.discussion_period = pending.urgency.discussion_period(DEFAULT_DISCUSSION_PERIOD);
+        pending.record_sponsorship(DEFAULT_PROPOSAL_BOND, QUEUED_AT + 60).unwrap();
+        assert_eq!(pending.status, ProposalStatus::Pending);
+        pending.record_sponsorship(2 * DEFAULT_PROPOSAL_BOND, QUEUED_AT + 120).unwrap();
+        assert_eq!(pending.status, ProposalStatus::Active);
+        assert_eq!(pending.vote_start, QUEUED_AT + 120);
+
+        // Appeals keep the stricter threshold
+        proposal.status = ProposalStatus::Defeated;
+        let appeal = proposal.appeal(8, Pubkey::default(), &config, VOTE_END + 1, 0).unwrap();
+        assert_eq!(appeal.urgency, ProposalUrgency::Emergency);
+        assert_eq!(appeal.approval_threshold, EMERGENCY_APPROVAL_THRESHOLD);
+        assert_eq!(appeal.manifest(VOTE_END + 1).unwrap().urgency, ProposalUrgency::Emergency);
+    }
 
-    #[msg("No pending configuration change")]
-    NoPendingConfigChange,
+    fn parameter_change(
+        new_voting_period: Option<i64>,
+        new_quorum: Option<u8>,
+        new_threshold: Option<u8>,
+        new_bond: Option<u64>,
+    ) -> ExecutionData {
+        ExecutionData::ParameterChange {
+            new_voting_period,
+            new_quorum,
+            new_threshold,
+            new_bond,
+        }
+    }
 
-    #[msg("Timelock has not expired yet")]
-    TimelockNotExpired,
+    #[test]
+    fn test_parameter_change_validation() {
+        let valid = parameter_change(Some(MIN_VOTING_PERIOD), Some(20), None, Some(MIN_PROPOSAL_BOND));
+        valid.validate(ProposalType::ParameterChange).unwrap();
+
+        for (data, expected) in [
+            (parameter_change(None, None, None, None), DaoError::EmptyParameterChange),
+            (parameter_change(Some(MIN_VOTING_PERIOD - 1), None, None, None), DaoError::InvalidVotingPeriod),
+            (parameter_change(Some(MAX_VOTING_PERIOD + 1), None, None, None), DaoError::InvalidVotingPeriod),
+            (parameter_change(None, Some(0), None, None), DaoError::InvalidQuorumPercentage),
+            (parameter_change(None, None, Some(101), None), DaoError::InvalidApprovalThreshold),
+            (parameter_change(None, None, None, Some(MIN_PROPOSAL_BOND - 1)), DaoError::InvalidProposalBond),
+        ] {
+            let err = data.validate(ProposalType::ParameterChange).unwrap_err();
+            assert_eq!(err, expected.into());
+        }
 
-    #[msg("Invalid voter")]
-    InvalidVoter,
+        // Data must match the proposal type
+        let withdrawal = single_payout(1_000);
+        withdrawal.validate(ProposalType::TreasuryWithdrawal).unwrap();
+        for (data, proposal_type) in [
+            (&withdrawal, ProposalType::ParameterChange),
+            (&valid, ProposalType::TreasuryWithdrawal),
+            (&valid, ProposalType::General),
+        ] {
+            let err = data.validate(proposal_type).unwrap_err();
+            assert_eq!(err, DaoError::ExecutionDataMismatch.into());
+        }
+    }
 
-    #[msg("Invalid proposal")]
-    InvalidProposal,
+    #[test]
+    fn test_parameter_change_applies_to_config() {
+        let mut config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::ParameterChange, &config);
+        proposal.execution_data = Some(parameter_change(Some(MAX_VOTING_PERIOD), None, Some(60), None));
+        assert_eq!(proposal.withdrawal_amount(), None);
 
-    // SECURITY FIX: New error codes for Vote Escrow pattern
-    #[msg("Vote vault must be owned by DAO PDA")]
-    InvalidVoteVaultOwner,
+        let Some(ExecutionData::ParameterChange {
+            new_voting_period,
+            new_quorum,
+            new_threshold,
+            new_bond,
+        }) = proposal.execution_data.clone()
+        else {
+            panic!("parameter change");
+        };
+        config.apply_parameter_change(new_voting_period, new_quorum, new_threshold, new_bond);
+
+        assert_eq!(config.voting_period, MAX_VOTING_PERIOD);
+        assert_eq!(config.approval_threshold, 60);
+        // Unset parameters keep their current values
+        assert_eq!(config.quorum_percentage, adaptive_config().quorum_percentage);
+        assert_eq!(config.proposal_bond, adaptive_config().proposal_bond);
+        // Proposals already open keep the terms they were created under
+        assert_eq!(proposal.approval_threshold, DEFAULT_APPROVAL_THRESHOLD);
+    }
 
-    #[msg("Invalid vote vault account")]
-    InvalidVoteVault,
+    #[test]
+    fn test_multi_recipient_withdrawal_validation() {
+        let payouts: Vec<WithdrawalPayout> = (1..=3).map(|i| payout(i * 1_000)).collect();
+        let data = ExecutionData::TreasuryWithdrawal { payouts: payouts.clone() };
+        data.validate(ProposalType::TreasuryWithdrawal).unwrap();
+        assert_eq!(payout_total(&payouts).unwrap(), 6_000);
+
+        // The proposal commits and caps the total, not any single payout
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        proposal.execution_data = Some(data);
+        assert_eq!(proposal.withdrawal_amount(), Some(6_000));
+
+        let full: Vec<WithdrawalPayout> = (1..=MAX_WITHDRAWAL_RECIPIENTS as u64).map(payout).collect();
+        let mut too_many = full.clone();
+        too_many.push(payout(1));
+        let mut duplicate = payouts.clone();
+        duplicate[2].recipient = duplicate[0].recipient;
+        let mut zero = payouts.clone();
+        zero[1].amount = 0;
+        let overflowing = vec![payout(u64::MAX), payout(1)];
+
+        ExecutionData::TreasuryWithdrawal { payouts: full }
+            .validate(ProposalType::TreasuryWithdrawal)
+            .unwrap();
+        for (payouts, expected) in [
+            (vec![], DaoError::InvalidWithdrawalPayouts),
+            (too_many, DaoError::InvalidWithdrawalPayouts),
+            (zero, DaoError::InvalidWithdrawalPayouts),
+            (duplicate, DaoError::DuplicateWithdrawalRecipient),
+            (overflowing, DaoError::Overflow),
+        ] {
+            let err = ExecutionData::TreasuryWithdrawal { payouts }
+                .validate(ProposalType::TreasuryWithdrawal)
+                .unwrap_err();
+            assert_eq!(err, expected.into());
+        }
+    }
 
-    #[msg("Tokens are locked during active voting - retract vote first or wait for vote_end")]
-    TokensLockedDuringVoting,
+    #[test]
+    fn test_payout_recipients_must_match_in_order() {
+        let payouts: Vec<WithdrawalPayout> = (1..=3).map(|i| payout(i * 1_000)).collect();
+        let keys: Vec<Pubkey> = payouts.iter().map(|p| p.recipient).collect();
+        check_payout_recipients(&payouts, &keys).unwrap();
+
+        // Same accounts, wrong order
+        let mut swapped = keys.clone();
+        swapped.swap(0, 1);
+        let err = check_payout_recipients(&payouts, &swapped).unwrap_err();
+        assert_eq!(err, DaoError::InvalidRecipient.into());
+
+        // A stranger in place of a recipient
+        let mut stranger = keys.clone();
+        stranger[2] = Pubkey::new_unique();
+        let err = check_payout_recipients(&payouts, &stranger).unwrap_err();
+        assert_eq!(err, DaoError::InvalidRecipient.into());
+
+        // Missing or extra accounts
+        let err = check_payout_recipients(&payouts, &keys[..2]).unwrap_err();
+        assert_eq!(err, DaoError::WithdrawalRecipientCountMismatch.into());
+        let mut extra = keys.clone();
+        extra.push(Pubkey::new_unique());
+        let err = check_payout_recipients(&payouts, &extra).unwrap_err();
+        assert_eq!(err, DaoError::WithdrawalRecipientCountMismatch.into());
+    }
 
-    #[msg("Tokens have already been withdrawn")]
-    AlreadyWithdrawn,
+    #[test]
+    fn test_full_withdrawal_fits_proposal_account() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        proposal.execution_data = Some(ExecutionData::TreasuryWithdrawal {
+            payouts: (1..=MAX_WITHDRAWAL_RECIPIENTS as u64).map(payout).collect(),
+        });
+        proposal.title = "t".repeat(MAX_TITLE_LENGTH);
+        proposal.description_cid = "c".repeat(MAX_DESCRIPTION_CID_LENGTH);
+        proposal.tags = (0..MAX_PROPOSAL_TAGS).map(|i| format!("{}", i).repeat(MAX_TAG_LENGTH)).collect();
+        let mut data = Vec::new();
+        proposal.serialize(&mut data).unwrap();
+        assert!(8 + data.len() <= Proposal::MAX_SIZE);
+    }
 
-    #[msg("User has not voted on this proposal")]
-    NotVoted,
+    /// Ed25519 program instruction verifying one inline signature, laid out
+    /// like the web3.js Ed25519Program helpers (signature left zeroed)
+    fn ed25519_ix(signer: &Pubkey, message: &[u8]) -> anchor_lang::solana_program::instruction::Instruction {
+        let mut data = vec![1, 0];
+        for field in [48, u16::MAX, 16, u16::MAX, 112, message.len() as u16, u16::MAX] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: ED25519_PROGRAM_ID,
+            accounts: Vec::new(),
+            data,
+        }
+    }
 
-    #[msg("Discussion period must be between 1 and 14 days")]
-    InvalidDiscussionPeriod,
+    #[test]
+    fn test_signed_vote_message_binds_every_field() {
+        let voter = Pubkey::new_unique();
+        let message = signed_vote_message(&voter, 7, VoteChoice::For, 1, VOTE_END);
+        assert!(message.starts_with(SIGNED_VOTE_DOMAIN));
+
+        for other in [
+            signed_vote_message(&Pubkey::new_unique(), 7, VoteChoice::For, 1, VOTE_END),
+            signed_vote_message(&voter, 8, VoteChoice::For, 1, VOTE_END),
+            signed_vote_message(&voter, 7, VoteChoice::Against, 1, VOTE_END),
+            signed_vote_message(&voter, 7, VoteChoice::For, 2, VOTE_END),
+            signed_vote_message(&voter, 7, VoteChoice::For, 1, VOTE_END + 1),
+        ] {
+            assert_ne!(message, other);
+        }
+    }
 
-    #[msg("Execution timelock has not expired (3 days after voting ends)")]
-    ExecutionTimelockNotExpired,
+    #[test]
+    fn test_forged_vote_signature_rejected() {
+        let voter = Pubkey::new_unique();
+        let message = signed_vote_message(&voter, 7, VoteChoice::For, 1, VOTE_END);
+        verify_ed25519_instruction(&ed25519_ix(&voter, &message), &voter, &message).unwrap();
+
+        // Signed by someone else, or over a different vote
+        let forger = Pubkey::new_unique();
+        let err = verify_ed25519_instruction(&ed25519_ix(&forger, &message), &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+        let against = signed_vote_message(&voter, 7, VoteChoice::Against, 1, VOTE_END);
+        let err = verify_ed25519_instruction(&ed25519_ix(&voter, &against), &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+
+        // Not the Ed25519 program
+        let mut ix = ed25519_ix(&voter, &message);
+        ix.program_id = Pubkey::new_unique();
+        let err = verify_ed25519_instruction(&ix, &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+
+        // Public key read from another instruction
+        let mut ix = ed25519_ix(&voter, &message);
+        ix.data[8..10].copy_from_slice(&0u16.to_le_bytes());
+        let err = verify_ed25519_instruction(&ix, &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+
+        // Truncated message, and no signatures at all
+        let mut ix = ed25519_ix(&voter, &message);
+        ix.data.pop();
+        let err = verify_ed25519_instruction(&ix, &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+        let mut ix = ed25519_ix(&voter, &message);
+        ix.data[0] = 0;
+        let err = verify_ed25519_instruction(&ix, &voter, &message).unwrap_err();
+        assert_eq!(err, DaoError::InvalidVoteSignature.into());
+    }
 
-    // SECURITY FIX (X1.4): New error codes for DAO config close validation
-    #[msg("Invalid DAO config account - incorrect data or not owned by program")]
-    InvalidDaoConfig,
+    #[test]
+    fn test_replayed_signed_vote_rejected() {
+        let mut vote_escrow = escrow(1_000);
+        vote_escrow.accept_signed_vote(1, VOTE_END, VOTE_START).unwrap();
+        assert_eq!(vote_escrow.vote_nonce, 1);
+
+        // The same message again (e.g. after the voter retracts), or an older one
+        let err = vote_escrow.accept_signed_vote(1, VOTE_END, VOTE_START).unwrap_err();
+        assert_eq!(err, DaoError::SignedVoteReplayed.into());
+        let err = vote_escrow.accept_signed_vote(0, VOTE_END, VOTE_START).unwrap_err();
+        assert_eq!(err, DaoError::SignedVoteReplayed.into());
+
+        // A fresh nonce is accepted until its expiry
+        let err = vote_escrow.accept_signed_vote(2, VOTE_START, VOTE_START + 1).unwrap_err();
+        assert_eq!(err, DaoError::SignedVoteExpired.into());
+        assert_eq!(vote_escrow.vote_nonce, 1);
+        vote_escrow.accept_signed_vote(2, VOTE_START, VOTE_START).unwrap();
+        assert_eq!(vote_escrow.vote_nonce, 2);
+    }
 
-    // Y7.2: Appeal mechanism error codes
-    #[msg("Only defeated proposals can be appealed")]
-    CannotAppealNonDefeated,
+    #[test]
+    fn test_resized_legacy_escrow_accepts_signed_votes() {
+        for vote_choice in [None, Some(VoteChoice::For)] {
+            let mut legacy = escrow(1_000);
+            legacy.has_voted = vote_choice.is_some();
+            legacy.vote_choice = vote_choice;
+            let mut bytes = Vec::new();
+            legacy.try_serialize(&mut bytes).unwrap();
+
+            // As allocated before vote_nonce existed: too short to decode
+            bytes.truncate(VoteEscrow::LEGACY_SIZE);
+            bytes.resize(VoteEscrow::LEGACY_SIZE, 0);
+            assert!(VoteEscrow::try_deserialize(&mut &bytes[..]).is_err());
+
+            // After resize_vote_escrow zero-extends it
+            bytes.resize(VoteEscrow::MAX_SIZE, 0);
+            let mut resized = VoteEscrow::try_deserialize(&mut &bytes[..]).unwrap();
+            assert_eq!(resized.deposited_amount, 1_000);
+            assert_eq!(resized.vote_choice, vote_choice);
+            assert_eq!(resized.vote_nonce, 0);
+            resized.accept_signed_vote(1, VOTE_END, VOTE_START).unwrap();
+            assert_eq!(resized.vote_nonce, 1);
+        }
+    }
 
-    #[msg("Proposal did not achieve minimum quorum (40%) required for appeal")]
-    InsufficientVotesForAppeal,
+    #[test]
+    fn test_signed_vote_counts_escrow_weight() {
+        let config = adaptive_config();
+        let mut proposal = voting_proposal(ProposalType::TreasuryWithdrawal, &config);
+        let vote_escrow = escrow(1_000_000_000);
+
+        // Same weight as a direct vote with the escrow, added to the chosen tally
+        let cast = resolve_vote_weight(&proposal, &config, Some(&vote_escrow), None).unwrap();
+        proposal.count_vote(VoteChoice::Against, cast.vote_weight).unwrap();
+        assert_eq!(proposal.against_votes, cast.vote_weight);
+        assert_eq!((proposal.for_votes, proposal.abstain_votes), (0, 0));
+
+        proposal.against_votes = u64::MAX;
+        let err = proposal.count_vote(VoteChoice::Against, 1).unwrap_err();
+        assert_eq!(err, DaoError::Overflow.into());
+    }
 }
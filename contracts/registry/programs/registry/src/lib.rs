@@ -10,10 +10,64 @@ const MIN_STAKE_FOR_REGISTRATION: u64 = 100_000_000_000; // 100 AEGIS tokens
 /// Set to 10% (1000 out of 10000 = 10.00%)
 const AUTO_DEACTIVATION_REPUTATION_THRESHOLD: u64 = 1000;
 
-/// Y7.7: Minimum reputation required for re-registration
+/// Y7.7: Default minimum reputation required to reactivate a node
 /// Prevents abusive nodes from simply re-registering
-/// Set to 30% (3000 out of 10000 = 30.00%)
-const MIN_REPUTATION_FOR_REREGISTRATION: u64 = 3000;
+/// Set to 25% (2500 out of 10000 = 25.00%)
+const DEFAULT_MIN_REPUTATION_FOR_REACTIVATION: u64 = 2500;
+
+/// Rolling period over which voluntary deactivations are counted (90 days)
+const DEACTIVATION_WINDOW: i64 = 90 * 24 * 60 * 60;
+
+/// Voluntary deactivations per DEACTIVATION_WINDOW that reactivate without a haircut
+const FREE_DEACTIVATIONS_PER_WINDOW: u8 = 2;
+
+/// Default reputation haircut for reactivating after too many deactivations (10%)
+const DEFAULT_REACTIVATION_HAIRCUT_BPS: u16 = 1000;
+
+/// Basis point scale (10000 = 100%)
+const BPS_SCALE: u64 = 10_000;
+
+/// Maximum number of nodes a single heartbeat_many call can cover
+const MAX_HEARTBEAT_BATCH: usize = 8;
+
+/// How far a heartbeat's client_timestamp may drift from the cluster clock (seconds)
+const HEARTBEAT_CLOCK_TOLERANCE: i64 = 90;
+
+/// Routing score scale (1,000,000 = healthy, fully staked, fresh node)
+const ROUTING_SCORE_SCALE: u64 = 1_000_000;
+
+/// Stake at which a node earns the full routing stake factor (10,000 AEGIS)
+const DEFAULT_ROUTING_STAKE_SATURATION: u64 = 10_000_000_000_000;
+
+/// Longest maintenance window an operator can announce (24 hours)
+const MAX_MAINTENANCE_DURATION: i64 = 24 * 60 * 60;
+
+/// Minimum notice before a maintenance window starts (1 hour)
+const MIN_MAINTENANCE_NOTICE: i64 = 60 * 60;
+
+/// Rolling period limiting how often a node can schedule maintenance (30 days)
+const MAINTENANCE_QUOTA_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Maintenance windows allowed per node within MAINTENANCE_QUOTA_PERIOD
+const MAX_MAINTENANCE_WINDOWS: usize = 2;
+
+/// Heartbeat age with no freshness penalty (matches the heartbeat grace period)
+const DEFAULT_ROUTING_FRESH_WINDOW: i64 = 900;
+
+/// Time after the fresh window over which freshness decays linearly to zero (1 hour)
+const DEFAULT_ROUTING_DECAY_WINDOW: i64 = 3600;
+
+/// Missed-heartbeat gaps kept per node for slashing disputes (oldest overwritten)
+const MAX_HEARTBEAT_GAPS: usize = 32;
+
+/// NodeAccount::notify_flags bit: notify the operator of slash requests
+pub const NOTIFY_SLASH_REQUESTS: u8 = 1 << 0;
+
+/// NodeAccount::notify_flags bit: notify the operator of reputation penalties
+pub const NOTIFY_REPUTATION_PENALTIES: u8 = 1 << 1;
+
+/// All defined notify_flags bits
+const NOTIFY_FLAGS_MASK: u8 = NOTIFY_SLASH_REQUESTS | NOTIFY_REPUTATION_PENALTIES;
 
 #[program]
 pub mod node_registry {
@@ -35,6 +89,9 @@ pub mod node_registry {
         config.min_stake_for_registration = min_stake;
         config.paused = false;
         config.bump = ctx.bumps.registry_config;
+        config.routing_params = RoutingScoreParams::default();
+        config.zone_assigner = Pubkey::default();  // Admin-only until set
+        config.reactivation_policy = ReactivationPolicy::default();
 
         msg!(
             "Registry config initialized: admin={}, staking_program={}",
@@ -51,6 +108,9 @@ pub mod node_registry {
         new_admin: Option<Pubkey>,
         new_staking_program: Option<Pubkey>,
         new_min_stake: Option<u64>,
+        new_routing_params: Option<RoutingScoreParams>,
+        new_zone_assigner: Option<Pubkey>,
+        new_reactivation_policy: Option<ReactivationPolicy>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.registry_config;
 
@@ -75,6 +135,84 @@ pub mod node_registry {
             msg!("Min stake updated to: {}", min_stake);
         }
 
+        if let Some(params) = new_routing_params {
+            require!(params.is_valid(), RegistryError::InvalidRoutingParams);
+            msg!(
+                "Routing params updated: stake_saturation={}, fresh_window={}s, decay_window={}s",
+                params.stake_saturation,
+                params.fresh_window,
+                params.decay_window
+            );
+            config.routing_params = params;
+        }
+
+        // Pubkey::default() removes the designated assigner
+        if let Some(zone_assigner) = new_zone_assigner {
+            config.zone_assigner = zone_assigner;
+            msg!("Zone assigner updated to: {}", zone_assigner);
+        }
+
+        if let Some(policy) = new_reactivation_policy {
+            require!(policy.is_valid(), RegistryError::InvalidReactivationPolicy);
+            msg!(
+                "Reactivation policy updated: min_reputation={}, haircut={} bps",
+                policy.min_reputation_for_reactivation,
+                policy.haircut_bps
+            );
+            config.reactivation_policy = policy;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate the registry config to the current layout (admin only)
+    ///
+    /// Configs created before the routing params, zone assigner and
+    /// reactivation policy were appended are shorter than
+    /// RegistryConfig::MAX_SIZE and can't be loaded by other instructions.
+    /// The account is grown (the admin pays the extra rent) and the appended
+    /// fields get initialize_registry_config's defaults. A no-op for a config
+    /// already at the current layout.
+    pub fn migrate_registry_config(ctx: Context<MigrateRegistryConfig>) -> Result<()> {
+        let info = ctx.accounts.registry_config.to_account_info();
+        let original_len = info.data_len();
+
+        // admin_authority directly follows the discriminator in every layout
+        let admin = info
+            .try_borrow_data()?
+            .get(8..40)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(RegistryError::UnauthorizedAdmin)?;
+        require_keys_eq!(ctx.accounts.admin.key(), admin, RegistryError::UnauthorizedAdmin);
+
+        if original_len >= RegistryConfig::MAX_SIZE {
+            msg!("Registry config already at the current layout ({} bytes)", original_len);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            RegistryConfig::MAX_SIZE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+        let mut config = RegistryConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        config.fill_appended_defaults(original_len);
+        config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Registry config migrated from {} to {} bytes",
+            original_len,
+            RegistryConfig::MAX_SIZE
+        );
+        emit!(RegistryConfigMigratedEvent {
+            admin,
+            from_len: original_len as u32,
+            to_len: RegistryConfig::MAX_SIZE as u32,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -83,10 +221,20 @@ pub mod node_registry {
     /// Creates a PDA account storing node metadata and initial stake amount.
     /// The metadata_url should be an IPFS CID containing detailed node information.
     /// Y2.6: Uses min_stake_for_registration from RegistryConfig instead of hardcoded constant
+    ///
+    /// An operator may run several nodes; each gets the next node_id from the
+    /// operator's node counter and lives at ["node", operator, node_id].
+    /// Operators with a legacy ["node", operator] account must migrate it
+    /// (migrate_legacy_node) before registering additional nodes.
+    ///
+    /// contact_hash and notify_flags are as in update_contact; pass zeros to
+    /// publish no contact channel.
     pub fn register_node(
         ctx: Context<RegisterNode>,
         metadata_url: String,
         initial_stake: u64,
+        contact_hash: [u8; 32],
+        notify_flags: u8,
     ) -> Result<()> {
         require!(
             metadata_url.len() <= MAX_METADATA_URL_LENGTH,
@@ -102,10 +250,21 @@ pub mod node_registry {
             RegistryError::InsufficientStake
         );
 
+        let operator_nodes = &mut ctx.accounts.operator_nodes;
+        if operator_nodes.operator == Pubkey::default() {
+            operator_nodes.operator = ctx.accounts.operator.key();
+            operator_nodes.bump = ctx.bumps.operator_nodes;
+        }
+        let node_id = operator_nodes.node_count;
+        operator_nodes.node_count = node_id
+            .checked_add(1)
+            .ok_or(RegistryError::TooManyNodes)?;
+
         let node_account = &mut ctx.accounts.node_account;
         let clock = Clock::get()?;
 
         node_account.operator = ctx.accounts.operator.key();
+        node_account.node_id = node_id;
         node_account.metadata_url = metadata_url.clone();
         node_account.status = NodeStatus::Active;
         node_account.stake_amount = initial_stake;
@@ -116,15 +275,31 @@ pub mod node_registry {
         node_account.last_heartbeat = clock.unix_timestamp; // First heartbeat at registration
         node_account.total_heartbeats = 1;
         node_account.missed_heartbeats = 0;
+        node_account.refresh_uptime();
         node_account.bump = ctx.bumps.node_account;
+        node_account.maintenance_windows = Default::default();
+        node_account.last_client_timestamp = 0;
+        node_account.heartbeat_sequence = 0;
+        node_account.zone_assignments = 0;
+        node_account.heartbeat_gaps = Default::default();
+        node_account.heartbeat_gap_count = 0;
+        node_account.deactivation_count = 0;
+        node_account.deactivation_window_start = 0;
+        node_account.set_contact(contact_hash, notify_flags)?;
+        node_account.routing_score = routing_score(
+            node_account,
+            &ctx.accounts.registry_config.routing_params,
+            clock.unix_timestamp,
+        );
 
         msg!("Node registered successfully");
-        msg!("Operator: {}", node_account.operator);
+        msg!("Operator: {} (node {})", node_account.operator, node_id);
         msg!("Metadata: {}", metadata_url);
         msg!("Initial Stake: {}", initial_stake);
 
         emit!(NodeRegisteredEvent {
             operator: node_account.operator,
+            node_id,
             metadata_url,
             stake_amount: initial_stake,
             timestamp: clock.unix_timestamp,
@@ -164,6 +339,37 @@ pub mod node_registry {
         Ok(())
     }
 
+    /// Set the node's contact channel and notification preferences (only
+    /// operator can update)
+    ///
+    /// contact_hash is a hash of an email address or URL the operator
+    /// publishes off-chain, so watchers can match it without the contact
+    /// itself going on-chain; all zeros clears it. notify_flags is a set of
+    /// NOTIFY_* bits.
+    pub fn update_contact(
+        ctx: Context<UpdateContact>,
+        contact_hash: [u8; 32],
+        notify_flags: u8,
+    ) -> Result<()> {
+        let node_account = &mut ctx.accounts.node_account;
+        let clock = Clock::get()?;
+
+        node_account.set_contact(contact_hash, notify_flags)?;
+        node_account.updated_at = clock.unix_timestamp;
+
+        msg!("Contact updated for node: {}", node_account.operator);
+
+        emit!(ContactUpdatedEvent {
+            operator: node_account.operator,
+            node_id: node_account.node_id,
+            contact_hash,
+            notify_flags,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Deactivate a node (only operator can deactivate their own node)
     pub fn deactivate_node(ctx: Context<DeactivateNode>) -> Result<()> {
         let node_account = &mut ctx.accounts.node_account;
@@ -176,6 +382,9 @@ pub mod node_registry {
 
         node_account.status = NodeStatus::Inactive;
         node_account.updated_at = clock.unix_timestamp;
+        // Inactive nodes receive no traffic
+        node_account.routing_score = 0;
+        node_account.record_deactivation(clock.unix_timestamp);
 
         msg!("Node deactivated: {}", node_account.operator);
 
@@ -189,6 +398,10 @@ pub mod node_registry {
 
     /// Reactivate a previously deactivated node
     /// Y7.7: Requires minimum reputation score to prevent abusive re-registration
+    ///
+    /// Nodes cycling deactivate/reactivate to dodge penalties lose the
+    /// policy's haircut_bps of their reputation for every voluntary
+    /// deactivation beyond the second within DEACTIVATION_WINDOW.
     pub fn reactivate_node(ctx: Context<ReactivateNode>) -> Result<()> {
         let node_account = &mut ctx.accounts.node_account;
         let clock = Clock::get()?;
@@ -198,24 +411,30 @@ pub mod node_registry {
             RegistryError::NodeNotInactive
         );
 
-        // Y7.7: Check reputation floor - nodes with very low reputation cannot reactivate
-        // They must wait for reputation to recover or appeal to admin
-        require!(
-            node_account.reputation_score >= MIN_REPUTATION_FOR_REREGISTRATION,
-            RegistryError::ReputationTooLow
+        let haircut = node_account.reactivate(
+            &ctx.accounts.registry_config.reactivation_policy,
+            clock.unix_timestamp,
+        )?;
+        // Deactivation zeroed the score; score the node again after any haircut
+        node_account.routing_score = routing_score(
+            node_account,
+            &ctx.accounts.registry_config.routing_params,
+            clock.unix_timestamp,
         );
 
-        node_account.status = NodeStatus::Active;
-        node_account.updated_at = clock.unix_timestamp;
-
         msg!(
-            "Y7.7: Node reactivated: {} (reputation: {})",
+            "Y7.7: Node reactivated: {} (reputation: {}, haircut: {})",
             node_account.operator,
-            node_account.reputation_score
+            node_account.reputation_score,
+            haircut
         );
 
         emit!(NodeReactivatedEvent {
             operator: node_account.operator,
+            reputation_score: node_account.reputation_score,
+            reputation_haircut: haircut,
+            deactivation_count: node_account.deactivation_count,
+            routing_score: node_account.routing_score,
             timestamp: clock.unix_timestamp,
         });
 
@@ -252,6 +471,11 @@ pub mod node_registry {
         // Update stake amount
         node_account.stake_amount = new_stake_amount;
         node_account.updated_at = clock.unix_timestamp;
+        node_account.routing_score = routing_score(
+            node_account,
+            &ctx.accounts.registry_config.routing_params,
+            clock.unix_timestamp,
+        );
 
         msg!(
             "Stake updated for node: {} to {} by staking program (via CPI)",
@@ -262,6 +486,7 @@ pub mod node_registry {
         emit!(StakeUpdatedEvent {
             operator: node_account.operator,
             new_stake_amount,
+            routing_score: node_account.routing_score,
             timestamp: clock.unix_timestamp,
         });
 
@@ -271,88 +496,200 @@ pub mod node_registry {
     /// Node heartbeat - called periodically by nodes to prove liveness
     /// Per whitepaper: Nodes must submit heartbeats every 5 minutes
     /// Missing heartbeats affects reputation and can trigger slashing
-    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
-        let node_account = &mut ctx.accounts.node_account;
+    ///
+    /// `client_timestamp` binds the transaction to the moment it was signed:
+    /// it must be within 90 seconds of the cluster clock and later than the
+    /// node's previous heartbeat, so a relayer can't replay an old heartbeat
+    /// for on-time credit. `sequence`, if the operator's agent keeps one, must
+    /// also increase and is emitted for off-chain gap detection.
+    pub fn heartbeat(
+        ctx: Context<Heartbeat>,
+        client_timestamp: i64,
+        sequence: Option<u64>,
+    ) -> Result<()> {
         let clock = Clock::get()?;
+        apply_heartbeat(
+            &mut ctx.accounts.node_account,
+            &ctx.accounts.registry_config.routing_params,
+            clock.unix_timestamp,
+            client_timestamp,
+            sequence,
+        )
+    }
+
+    /// Submit heartbeats for several of the signing operator's nodes in one transaction
+    ///
+    /// Node accounts are passed as writable remaining_accounts (up to 8). Every
+    /// node must belong to the signer; a foreign or duplicate node fails the
+    /// whole batch. `client_timestamp` and `sequence` are checked against each
+    /// node as in heartbeat.
+    pub fn heartbeat_many(
+        ctx: Context<HeartbeatMany>,
+        client_timestamp: i64,
+        sequence: Option<u64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let operator = ctx.accounts.operator.key();
+        let routing_params = &ctx.accounts.registry_config.routing_params;
+        let nodes = ctx.remaining_accounts;
 
-        // Only active nodes can submit heartbeats
         require!(
-            node_account.status == NodeStatus::Active,
-            RegistryError::NodeNotActive
+            !nodes.is_empty() && nodes.len() <= MAX_HEARTBEAT_BATCH,
+            RegistryError::InvalidHeartbeatBatch
         );
 
-        let last_heartbeat = node_account.last_heartbeat;
-        let current_time = clock.unix_timestamp;
-        let time_since_last = current_time - last_heartbeat;
+        for (i, info) in nodes.iter().enumerate() {
+            require!(
+                !nodes[..i].iter().any(|prev| prev.key == info.key),
+                RegistryError::DuplicateNodeInBatch
+            );
+            require!(info.owner == ctx.program_id, RegistryError::InvalidNodeAccount);
+            require!(info.is_writable, RegistryError::InvalidNodeAccount);
+
+            let mut data = info.try_borrow_mut_data()?;
+            let mut node_account = NodeAccount::try_deserialize(&mut &data[..])?;
 
-        // Calculate missed heartbeat intervals (excluding current)
-        let expected_intervals = if time_since_last > NodeAccount::HEARTBEAT_INTERVAL {
-            (time_since_last / NodeAccount::HEARTBEAT_INTERVAL) - 1
-        } else {
-            0
-        };
+            require!(
+                node_account.operator == operator,
+                RegistryError::UnauthorizedOperator
+            );
+            let expected = Pubkey::create_program_address(
+                &[
+                    b"node",
+                    operator.as_ref(),
+                    &[node_account.node_id],
+                    &[node_account.bump],
+                ],
+                ctx.program_id,
+            )
+            .map_err(|_| RegistryError::InvalidNodeAccount)?;
+            require_keys_eq!(expected, *info.key, RegistryError::InvalidNodeAccount);
+
+            apply_heartbeat(
+                &mut node_account,
+                routing_params,
+                clock.unix_timestamp,
+                client_timestamp,
+                sequence,
+            )?;
+
+            let mut writer: &mut [u8] = &mut data[..];
+            node_account.try_serialize(&mut writer)?;
+        }
 
-        // Track missed heartbeats for slashing detection
-        if expected_intervals > 0 {
-            node_account.missed_heartbeats = node_account
-                .missed_heartbeats
-                .checked_add(expected_intervals as u64)
-                .ok_or(RegistryError::Overflow)?;
+        msg!("Batched heartbeat for {} node(s) of operator {}", nodes.len(), operator);
 
-            // Reputation penalty for missed heartbeats (1% per missed interval)
-            let penalty = (expected_intervals as u64).saturating_mul(100);
-            node_account.reputation_score = node_account
-                .reputation_score
-                .saturating_sub(penalty);
+        Ok(())
+    }
 
-            msg!(
-                "Node {} missed {} heartbeat(s), reputation reduced to {}",
-                node_account.operator,
-                expected_intervals,
-                node_account.reputation_score
-            );
+    /// Announce a planned maintenance window for a node (operator only)
+    ///
+    /// Heartbeat intervals inside the window are not counted as missed and
+    /// the window's downtime is excluded from liveness checks. Windows last
+    /// at most 24 hours, must start at least 1 hour ahead, can't overlap the
+    /// previous window, and are limited to 2 per node in any 30 days.
+    pub fn schedule_maintenance(
+        ctx: Context<ScheduleMaintenance>,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        let node_account = &mut ctx.accounts.node_account;
+        let clock = Clock::get()?;
+
+        node_account.schedule_maintenance(start, end, clock.unix_timestamp)?;
+        node_account.updated_at = clock.unix_timestamp;
 
-            // Y7.6: Auto-deactivate node if reputation falls below threshold
-            if node_account.reputation_score < AUTO_DEACTIVATION_REPUTATION_THRESHOLD
-                && node_account.status == NodeStatus::Active
-            {
-                node_account.status = NodeStatus::Inactive;
-                msg!(
-                    "Y7.6: Node {} AUTO-DEACTIVATED due to low reputation ({})",
-                    node_account.operator,
-                    node_account.reputation_score
-                );
-
-                emit!(NodeAutoDeactivatedEvent {
-                    operator: node_account.operator,
-                    reputation_score: node_account.reputation_score,
-                    threshold: AUTO_DEACTIVATION_REPUTATION_THRESHOLD,
-                    timestamp: current_time,
-                });
-            }
-        } else {
-            // Reputation boost for on-time heartbeat (0.1% per successful heartbeat)
-            let boost = 10; // 0.10%
-            node_account.reputation_score = core::cmp::min(
-                node_account.reputation_score.saturating_add(boost),
-                NodeAccount::MAX_REPUTATION,
+        msg!(
+            "Node {} (node {}) maintenance scheduled: {} - {}",
+            node_account.operator,
+            node_account.node_id,
+            start,
+            end
+        );
+
+        emit!(MaintenanceScheduledEvent {
+            operator: node_account.operator,
+            node_id: node_account.node_id,
+            start,
+            end,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Migrate a legacy single-node account (["node", operator]) to node_id 0
+    /// under the multi-node seed scheme (["node", operator, 0])
+    ///
+    /// Copies all node state, initializes the operator's node counter, and
+    /// closes the legacy account, returning its rent to the operator.
+    pub fn migrate_legacy_node(ctx: Context<MigrateLegacyNode>) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_node_account.to_account_info();
+        let operator = ctx.accounts.operator.key();
+
+        require!(
+            legacy_info.owner == ctx.program_id,
+            RegistryError::InvalidNodeAccount
+        );
+
+        let legacy = {
+            let data = legacy_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *NodeAccount::DISCRIMINATOR,
+                RegistryError::InvalidNodeAccount
             );
-        }
+            LegacyNodeAccount::deserialize(&mut &data[8..])?
+        };
+        require!(legacy.operator == operator, RegistryError::UnauthorizedOperator);
 
-        // Update heartbeat tracking
-        node_account.last_heartbeat = current_time;
-        node_account.total_heartbeats = node_account
-            .total_heartbeats
-            .checked_add(1)
+        let node_account = &mut ctx.accounts.node_account;
+        node_account.operator = legacy.operator;
+        node_account.metadata_url = legacy.metadata_url;
+        node_account.status = legacy.status;
+        node_account.stake_amount = legacy.stake_amount;
+        node_account.registered_at = legacy.registered_at;
+        node_account.updated_at = Clock::get()?.unix_timestamp;
+        node_account.reputation_score = legacy.reputation_score;
+        node_account.last_heartbeat = legacy.last_heartbeat;
+        node_account.total_heartbeats = legacy.total_heartbeats;
+        node_account.missed_heartbeats = legacy.missed_heartbeats;
+        node_account.refresh_uptime();
+        node_account.bump = ctx.bumps.node_account;
+        node_account.node_id = 0;
+        node_account.maintenance_windows = Default::default();
+        node_account.last_client_timestamp = 0;
+        node_account.heartbeat_sequence = 0;
+        node_account.zone_assignments = 0;
+        node_account.heartbeat_gaps = Default::default();
+        node_account.heartbeat_gap_count = 0;
+        node_account.deactivation_count = 0;
+        node_account.deactivation_window_start = 0;
+        node_account.contact_hash = [0; 32];
+        node_account.notify_flags = 0;
+
+        let operator_nodes = &mut ctx.accounts.operator_nodes;
+        operator_nodes.operator = operator;
+        operator_nodes.node_count = 1;
+        operator_nodes.bump = ctx.bumps.operator_nodes;
+
+        // Close the legacy account
+        let operator_info = ctx.accounts.operator.to_account_info();
+        let lamports = legacy_info.lamports();
+        **operator_info.try_borrow_mut_lamports()? = operator_info
+            .lamports()
+            .checked_add(lamports)
             .ok_or(RegistryError::Overflow)?;
-        node_account.updated_at = current_time;
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        legacy_info.assign(&System::id());
+        legacy_info.resize(0)?;
 
-        emit!(HeartbeatEvent {
-            operator: node_account.operator,
-            reputation_score: node_account.reputation_score,
-            total_heartbeats: node_account.total_heartbeats,
-            missed_heartbeats: node_account.missed_heartbeats,
-            timestamp: current_time,
+        msg!("Legacy node for operator {} migrated to node_id 0", operator);
+
+        emit!(NodeMigratedEvent {
+            operator,
+            legacy_account: legacy_info.key(),
+            node_account: node_account.key(),
+            timestamp: node_account.updated_at,
         });
 
         Ok(())
@@ -384,6 +721,8 @@ pub mod node_registry {
         let old_score = node_account.reputation_score;
         node_account.reputation_score = new_score;
         node_account.updated_at = clock.unix_timestamp;
+        node_account.routing_score =
+            routing_score(node_account, &config.routing_params, clock.unix_timestamp);
 
         msg!(
             "Reputation updated for node {}: {} -> {}",
@@ -396,6 +735,7 @@ pub mod node_registry {
             operator: node_account.operator,
             old_score,
             new_score,
+            routing_score: node_account.routing_score,
             timestamp: clock.unix_timestamp,
         });
 
@@ -408,7 +748,8 @@ pub mod node_registry {
         let node_account = &ctx.accounts.node_account;
         let clock = Clock::get()?;
 
-        let time_since_heartbeat = clock.unix_timestamp - node_account.last_heartbeat;
+        let time_since_heartbeat = node_account.downtime_since_heartbeat(clock.unix_timestamp);
+        let in_maintenance = node_account.in_maintenance(clock.unix_timestamp);
         let is_offline = time_since_heartbeat > NodeAccount::HEARTBEAT_GRACE_PERIOD;
 
         // 48 hours = 172800 seconds (whitepaper slashing threshold)
@@ -430,12 +771,288 @@ pub mod node_registry {
             offline_48_hours,
             reputation_score: node_account.reputation_score,
             timestamp: clock.unix_timestamp,
+            in_maintenance,
+        });
+
+        Ok(())
+    }
+
+    /// Emit a node's full current state plus derived liveness values
+    ///
+    /// Read-only; meant to be simulated by light clients that can't decode
+    /// raw account data.
+    pub fn emit_node_snapshot(ctx: Context<EmitNodeSnapshot>, operator: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        msg!("Node snapshot for operator {}", operator);
+        emit!(NodeSnapshotEvent::from_node(&ctx.accounts.node_account, clock.unix_timestamp));
+        Ok(())
+    }
+
+    /// Emit a node's heartbeat counters and uptime for an oracle attestation
+    /// window starting at `window_start` (permissionless)
+    ///
+    /// Oracles reference the report in their uptime attestations so the
+    /// claimed uptime can be reconciled against the registry's heartbeats.
+    /// The counters are lifetime totals; `window_start` is recorded as given.
+    pub fn emit_uptime_report(
+        ctx: Context<EmitUptimeReport>,
+        operator: Pubkey,
+        window_start: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let report = UptimeReportEvent::from_node(
+            &ctx.accounts.node_account,
+            window_start,
+            clock.unix_timestamp,
+        )?;
+        msg!("Uptime report for operator {}: {}bps", operator, report.uptime_bps);
+        emit!(report);
+        Ok(())
+    }
+
+    /// Record that a node is authorized to serve a customer zone
+    /// (registry admin or the designated zone assigner only)
+    ///
+    /// zone_hash identifies the zone off-chain (e.g. sha256 of the zone name).
+    /// The record lives at ["zone", zone_hash, operator]; a revoked zone can
+    /// be assigned again, reusing the record.
+    pub fn assign_zone(ctx: Context<AssignZone>, zone_hash: [u8; 32]) -> Result<()> {
+        let assigner = ctx.accounts.assigner.key();
+        require!(
+            ctx.accounts.registry_config.can_assign_zones(&assigner),
+            RegistryError::UnauthorizedZoneAssigner
+        );
+
+        let clock = Clock::get()?;
+        let node_account = &mut ctx.accounts.node_account;
+        let assignment = &mut ctx.accounts.zone_assignment;
+        if assignment.operator == Pubkey::default() {
+            assignment.zone_hash = zone_hash;
+            assignment.operator = node_account.operator;
+            assignment.bump = ctx.bumps.zone_assignment;
+        }
+        assignment.assign(node_account, assigner, clock.unix_timestamp)?;
+
+        msg!(
+            "Zone assigned to operator {} (node {})",
+            node_account.operator,
+            node_account.node_id
+        );
+
+        emit!(ZoneAssignedEvent {
+            zone_hash,
+            operator: node_account.operator,
+            node_id: node_account.node_id,
+            assigned_by: assigner,
+            zone_assignments: node_account.zone_assignments,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a node's zone assignment (registry admin or the designated
+    /// zone assigner only)
+    ///
+    /// The record is kept (inactive) so the assignment period stays
+    /// auditable for billing disputes.
+    pub fn revoke_zone(ctx: Context<RevokeZone>) -> Result<()> {
+        let revoker = ctx.accounts.assigner.key();
+        require!(
+            ctx.accounts.registry_config.can_assign_zones(&revoker),
+            RegistryError::UnauthorizedZoneAssigner
+        );
+
+        let clock = Clock::get()?;
+        let node_account = &mut ctx.accounts.node_account;
+        let assignment = &mut ctx.accounts.zone_assignment;
+        assignment.revoke(node_account, clock.unix_timestamp)?;
+
+        msg!(
+            "Zone revoked from operator {} (node {})",
+            assignment.operator,
+            assignment.node_id
+        );
+
+        emit!(ZoneRevokedEvent {
+            zone_hash: assignment.zone_hash,
+            operator: assignment.operator,
+            node_id: assignment.node_id,
+            revoked_by: revoker,
+            assigned_at: assignment.assigned_at,
+            zone_assignments: node_account.zone_assignments,
+            timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 }
 
+/// Apply one heartbeat to a node: missed-interval accounting, reputation
+/// adjustment, Y7.6 auto-deactivation, and routing score refresh. Shared by
+/// heartbeat and heartbeat_many.
+fn apply_heartbeat(
+    node_account: &mut NodeAccount,
+    routing_params: &RoutingScoreParams,
+    current_time: i64,
+    client_timestamp: i64,
+    sequence: Option<u64>,
+) -> Result<()> {
+    // Only active nodes can submit heartbeats
+    require!(
+        node_account.status == NodeStatus::Active,
+        RegistryError::NodeNotActive
+    );
+    node_account.accept_heartbeat_binding(client_timestamp, sequence, current_time)?;
+
+    let last_heartbeat = node_account.last_heartbeat;
+    // Intervals inside an announced maintenance window aren't missed
+    let time_since_last = current_time
+        - last_heartbeat
+        - node_account.maintenance_overlap(last_heartbeat, current_time);
+
+    // Calculate missed heartbeat intervals (excluding current)
+    let expected_intervals = if time_since_last > NodeAccount::HEARTBEAT_INTERVAL {
+        (time_since_last / NodeAccount::HEARTBEAT_INTERVAL) - 1
+    } else {
+        0
+    };
+
+    // Track missed heartbeats for slashing detection
+    if expected_intervals > 0 {
+        node_account.missed_heartbeats = node_account
+            .missed_heartbeats
+            .checked_add(expected_intervals as u64)
+            .ok_or(RegistryError::Overflow)?;
+
+        // The first missed interval was due one interval after the last heartbeat
+        let gap = node_account.record_heartbeat_gap(
+            last_heartbeat + NodeAccount::HEARTBEAT_INTERVAL,
+            current_time,
+            expected_intervals as u64,
+        )?;
+        emit!(gap);
+
+        // Reputation penalty for missed heartbeats (1% per missed interval)
+        let penalty = (expected_intervals as u64).saturating_mul(100);
+        node_account.reputation_score = node_account
+            .reputation_score
+            .saturating_sub(penalty);
+
+        msg!(
+            "Node {} missed {} heartbeat(s), reputation reduced to {}",
+            node_account.operator,
+            expected_intervals,
+            node_account.reputation_score
+        );
+
+        // Y7.6: Auto-deactivate node if reputation falls below threshold
+        if node_account.reputation_score < AUTO_DEACTIVATION_REPUTATION_THRESHOLD
+            && node_account.status == NodeStatus::Active
+        {
+            node_account.status = NodeStatus::Inactive;
+            msg!(
+                "Y7.6: Node {} AUTO-DEACTIVATED due to low reputation ({})",
+                node_account.operator,
+                node_account.reputation_score
+            );
+
+            emit!(NodeAutoDeactivatedEvent {
+                operator: node_account.operator,
+                reputation_score: node_account.reputation_score,
+                threshold: AUTO_DEACTIVATION_REPUTATION_THRESHOLD,
+                timestamp: current_time,
+            });
+        }
+    } else {
+        // Reputation boost for on-time heartbeat (0.1% per successful heartbeat)
+        let boost = 10; // 0.10%
+        node_account.reputation_score = core::cmp::min(
+            node_account.reputation_score.saturating_add(boost),
+            NodeAccount::MAX_REPUTATION,
+        );
+    }
+
+    // Update heartbeat tracking
+    node_account.last_heartbeat = current_time;
+    node_account.total_heartbeats = node_account
+        .total_heartbeats
+        .checked_add(1)
+        .ok_or(RegistryError::Overflow)?;
+    node_account.refresh_uptime();
+    node_account.updated_at = current_time;
+    node_account.routing_score = routing_score(node_account, routing_params, current_time);
+
+    emit!(HeartbeatEvent {
+        operator: node_account.operator,
+        node_id: node_account.node_id,
+        reputation_score: node_account.reputation_score,
+        total_heartbeats: node_account.total_heartbeats,
+        missed_heartbeats: node_account.missed_heartbeats,
+        routing_score: node_account.routing_score,
+        sequence,
+        client_timestamp,
+        timestamp: current_time,
+        uptime_bps: node_account.uptime_bps,
+    });
+
+    Ok(())
+}
+
+/// Resize a program-owned account to `len`, topping up rent from `payer`
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= len {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Routing weight for a node, scaled to 0..=1,000,000:
+///
+///   score = 1,000,000 × reputation_factor × stake_factor × freshness
+///
+/// - reputation_factor = reputation_score / 10,000
+/// - stake_factor = min(stake_amount / stake_saturation, 1)
+/// - freshness = 1 while the last heartbeat is within fresh_window, then
+///   decays linearly to 0 over the following decay_window seconds
+///
+/// Inactive or slashed nodes always score 0.
+fn routing_score(node_account: &NodeAccount, params: &RoutingScoreParams, now: i64) -> u32 {
+    if node_account.status != NodeStatus::Active {
+        return 0;
+    }
+
+    let scale = ROUTING_SCORE_SCALE as u128;
+    let reputation = node_account.reputation_score.min(NodeAccount::MAX_REPUTATION) as u128 * scale
+        / NodeAccount::MAX_REPUTATION as u128;
+    let stake = (node_account.stake_amount as u128 * scale / params.stake_saturation.max(1) as u128)
+        .min(scale);
+
+    let age = now.saturating_sub(node_account.last_heartbeat).max(0);
+    let stale_for = age.saturating_sub(params.fresh_window).max(0) as u128;
+    let decay_window = params.decay_window.max(1) as u128;
+    let freshness = scale - (stale_for * scale / decay_window).min(scale);
+
+    (reputation * stake / scale * freshness / scale) as u32
+}
+
 /// SECURITY FIX: Registry configuration account
 /// Stores authorized program IDs and admin authority
 #[account]
@@ -446,6 +1063,9 @@ pub struct RegistryConfig {
     pub min_stake_for_registration: u64, // Minimum stake to register (8 bytes)
     pub paused: bool,                   // Emergency pause flag (1 byte)
     pub bump: u8,                       // PDA bump (1 byte)
+    pub routing_params: RoutingScoreParams, // Routing score coefficients (24 bytes)
+    pub zone_assigner: Pubkey,          // Key allowed to assign zones besides admin, default = none (32 bytes)
+    pub reactivation_policy: ReactivationPolicy, // Reactivation floor and haircut (10 bytes)
 }
 
 impl RegistryConfig {
@@ -455,7 +1075,83 @@ impl RegistryConfig {
         32 +                          // rewards_program_id
         8 +                           // min_stake_for_registration
         1 +                           // paused
-        1;                            // bump
+        1 +                           // bump
+        RoutingScoreParams::MAX_SIZE + // routing_params
+        32 +                          // zone_assigner
+        ReactivationPolicy::MAX_SIZE; // reactivation_policy
+
+    /// Set defaults for fields appended after a config of `original_len`
+    /// bytes was created; they deserialize as zero after a resize
+    ///
+    /// A zero zone_assigner is already the default (admin only).
+    pub fn fill_appended_defaults(&mut self, original_len: usize) {
+        // routing_params is followed by zone_assigner and reactivation_policy
+        if original_len
+            <= Self::MAX_SIZE - RoutingScoreParams::MAX_SIZE - 32 - ReactivationPolicy::MAX_SIZE
+        {
+            self.routing_params = RoutingScoreParams::default();
+        }
+        if original_len <= Self::MAX_SIZE - ReactivationPolicy::MAX_SIZE {
+            self.reactivation_policy = ReactivationPolicy::default();
+        }
+    }
+
+    /// Whether `key` may assign and revoke zones
+    pub fn can_assign_zones(&self, key: &Pubkey) -> bool {
+        *key == self.admin_authority
+            || (self.zone_assigner != Pubkey::default() && *key == self.zone_assigner)
+    }
+}
+
+/// Admin-tunable coefficients of the routing score (see `routing_score`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoutingScoreParams {
+    pub stake_saturation: u64,          // Stake earning the full stake factor (8 bytes)
+    pub fresh_window: i64,              // Heartbeat age with no penalty, seconds (8 bytes)
+    pub decay_window: i64,              // Seconds for freshness to decay to zero (8 bytes)
+}
+
+impl RoutingScoreParams {
+    pub const MAX_SIZE: usize = 8 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        self.stake_saturation > 0 && self.fresh_window >= 0 && self.decay_window > 0
+    }
+}
+
+impl Default for RoutingScoreParams {
+    fn default() -> Self {
+        Self {
+            stake_saturation: DEFAULT_ROUTING_STAKE_SATURATION,
+            fresh_window: DEFAULT_ROUTING_FRESH_WINDOW,
+            decay_window: DEFAULT_ROUTING_DECAY_WINDOW,
+        }
+    }
+}
+
+/// Admin-tunable rules for reactivating a deactivated node
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReactivationPolicy {
+    pub min_reputation_for_reactivation: u64, // Reputation floor for reactivate_node (8 bytes)
+    pub haircut_bps: u16,               // Reputation cut per excess deactivation (2 bytes)
+}
+
+impl ReactivationPolicy {
+    pub const MAX_SIZE: usize = 8 + 2;
+
+    pub fn is_valid(&self) -> bool {
+        self.min_reputation_for_reactivation <= NodeAccount::MAX_REPUTATION
+            && self.haircut_bps as u64 <= BPS_SCALE
+    }
+}
+
+impl Default for ReactivationPolicy {
+    fn default() -> Self {
+        Self {
+            min_reputation_for_reactivation: DEFAULT_MIN_REPUTATION_FOR_REACTIVATION,
+            haircut_bps: DEFAULT_REACTIVATION_HAIRCUT_BPS,
+        }
+    }
 }
 
 /// Node account - stores operator information
@@ -473,6 +1169,19 @@ pub struct NodeAccount {
     pub total_heartbeats: u64,      // Total heartbeat count for uptime (8 bytes)
     pub missed_heartbeats: u64,     // Missed heartbeats for slashing detection (8 bytes)
     pub bump: u8,                   // PDA bump seed (1 byte)
+    pub node_id: u8,                // Index among the operator's nodes (1 byte)
+    pub routing_score: u32,         // Routing weight, 0-1,000,000 (4 bytes)
+    pub maintenance_windows: [MaintenanceWindow; MAX_MAINTENANCE_WINDOWS], // Latest windows, oldest first (2 * 16 bytes)
+    pub last_client_timestamp: i64, // client_timestamp of the last heartbeat (8 bytes)
+    pub heartbeat_sequence: u64,    // Last agent-supplied heartbeat sequence, 0 = none (8 bytes)
+    pub zone_assignments: u32,      // Active zone assignments (4 bytes)
+    pub heartbeat_gaps: [HeartbeatGap; MAX_HEARTBEAT_GAPS], // Ring buffer of missed-heartbeat gaps (32 * 16 bytes)
+    pub heartbeat_gap_count: u64,   // Gaps ever recorded; gap i is in slot i % 32 (8 bytes)
+    pub deactivation_count: u8,     // Voluntary deactivations in the current window (1 byte)
+    pub deactivation_window_start: i64, // First deactivation of the current window, 0 = none (8 bytes)
+    pub contact_hash: [u8; 32],     // Hash of the operator's off-chain contact, zeros = none (32 bytes)
+    pub notify_flags: u8,           // NOTIFY_* notification preferences (1 byte)
+    pub uptime_bps: u16,            // uptime_ratio_bps as of the last heartbeat (2 bytes)
 }
 
 impl NodeAccount {
@@ -487,7 +1196,20 @@ impl NodeAccount {
         8 +                         // last_heartbeat
         8 +                         // total_heartbeats
         8 +                         // missed_heartbeats
-        1;                          // bump
+        1 +                         // bump
+        1 +                         // node_id
+        4 +                         // routing_score
+        MaintenanceWindow::MAX_SIZE * MAX_MAINTENANCE_WINDOWS + // maintenance_windows
+        8 +                         // last_client_timestamp
+        8 +                         // heartbeat_sequence
+        4 +                         // zone_assignments
+        HeartbeatGap::MAX_SIZE * MAX_HEARTBEAT_GAPS + // heartbeat_gaps
+        8 +                         // heartbeat_gap_count
+        1 +                         // deactivation_count
+        8 +                         // deactivation_window_start
+        32 +                        // contact_hash
+        1 +                         // notify_flags
+        2;                          // uptime_bps
 
     /// Default reputation score for new nodes (50.00%)
     pub const DEFAULT_REPUTATION: u64 = 5000;
@@ -500,30 +1222,333 @@ impl NodeAccount {
 
     /// Grace period for missed heartbeats (15 minutes = 3 intervals)
     pub const HEARTBEAT_GRACE_PERIOD: i64 = 900;
-}
 
-/// Node status enum
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum NodeStatus {
-    Active,
-    Inactive,
-    Slashed,
-}
+    /// Set the contact hash and notification preferences, rejecting unknown flags
+    pub fn set_contact(&mut self, contact_hash: [u8; 32], notify_flags: u8) -> Result<()> {
+        require!(
+            notify_flags & !NOTIFY_FLAGS_MASK == 0,
+            RegistryError::InvalidNotifyFlags
+        );
+        self.contact_hash = contact_hash;
+        self.notify_flags = notify_flags;
+        Ok(())
+    }
 
-/// SECURITY FIX: Initialize registry config
-#[derive(Accounts)]
-pub struct InitializeRegistryConfig<'info> {
-    #[account(
-        init,
-        payer = deployer,
-        space = RegistryConfig::MAX_SIZE,
-        seeds = [b"registry_config"],
-        bump
-    )]
-    pub registry_config: Account<'info, RegistryConfig>,
+    /// Count a voluntary deactivation, starting a new window once the
+    /// previous one has run out
+    pub fn record_deactivation(&mut self, now: i64) {
+        if !self.in_deactivation_window(now) {
+            self.deactivation_window_start = now;
+            self.deactivation_count = 0;
+        }
+        self.deactivation_count = self.deactivation_count.saturating_add(1);
+    }
 
-    #[account(mut)]
-    pub deployer: Signer<'info>,
+    fn in_deactivation_window(&self, now: i64) -> bool {
+        self.deactivation_window_start != 0
+            && now < self.deactivation_window_start.saturating_add(DEACTIVATION_WINDOW)
+    }
+
+    /// Reputation lost on reactivating at `now`: `haircut_bps` of the current
+    /// score once the window holds more than FREE_DEACTIVATIONS_PER_WINDOW
+    pub fn reactivation_haircut(&self, haircut_bps: u16, now: i64) -> u64 {
+        if !self.in_deactivation_window(now)
+            || self.deactivation_count <= FREE_DEACTIVATIONS_PER_WINDOW
+        {
+            return 0;
+        }
+        self.reputation_score * haircut_bps as u64 / BPS_SCALE
+    }
+
+    /// Check the reactivation floor, apply any haircut and mark the node
+    /// active; returns the reputation taken
+    ///
+    /// The floor applies to the reputation the node left with, so a node
+    /// just above it can come back even if the haircut takes it below.
+    pub fn reactivate(&mut self, policy: &ReactivationPolicy, now: i64) -> Result<u64> {
+        // Y7.7: Nodes with very low reputation cannot reactivate; they must
+        // wait for reputation to recover or appeal to admin
+        require!(
+            self.reputation_score >= policy.min_reputation_for_reactivation,
+            RegistryError::ReputationTooLow
+        );
+
+        let haircut = self.reactivation_haircut(policy.haircut_bps, now);
+        self.reputation_score -= haircut;
+        self.status = NodeStatus::Active;
+        self.updated_at = now;
+        Ok(haircut)
+    }
+
+    /// Check a heartbeat's replay binding and record it
+    ///
+    /// `client_timestamp` must be within HEARTBEAT_CLOCK_TOLERANCE of `now` and
+    /// strictly after the previous heartbeat's; a supplied `sequence` must be
+    /// strictly greater than the last one recorded.
+    pub fn accept_heartbeat_binding(
+        &mut self,
+        client_timestamp: i64,
+        sequence: Option<u64>,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            client_timestamp.abs_diff(now) <= HEARTBEAT_CLOCK_TOLERANCE as u64,
+            RegistryError::HeartbeatTimestampOutOfRange
+        );
+        require!(
+            client_timestamp > self.last_client_timestamp,
+            RegistryError::HeartbeatTimestampNotIncreasing
+        );
+        if let Some(sequence) = sequence {
+            require!(
+                sequence > self.heartbeat_sequence,
+                RegistryError::HeartbeatSequenceNotIncreasing
+            );
+            self.heartbeat_sequence = sequence;
+        }
+        self.last_client_timestamp = client_timestamp;
+        Ok(())
+    }
+
+    /// Seconds since the last heartbeat, not counting announced maintenance
+    pub fn downtime_since_heartbeat(&self, now: i64) -> i64 {
+        now - self.last_heartbeat - self.maintenance_overlap(self.last_heartbeat, now)
+    }
+
+    /// Share of heartbeat intervals that were on time, in basis points
+    /// (total_heartbeats / (total_heartbeats + missed_heartbeats))
+    pub fn uptime_ratio_bps(&self) -> u64 {
+        let expected = self.total_heartbeats as u128 + self.missed_heartbeats as u128;
+        if expected == 0 {
+            return 0;
+        }
+        (self.total_heartbeats as u128 * 10_000 / expected) as u64
+    }
+
+    /// Recompute the stored uptime_bps from the heartbeat counters
+    pub fn refresh_uptime(&mut self) {
+        // uptime_ratio_bps is at most 10,000
+        self.uptime_bps = self.uptime_ratio_bps() as u16;
+    }
+
+    /// Seconds of [from, to) covered by announced maintenance windows
+    pub fn maintenance_overlap(&self, from: i64, to: i64) -> i64 {
+        self.maintenance_windows
+            .iter()
+            .map(|w| w.end.min(to).saturating_sub(w.start.max(from)).max(0))
+            .sum()
+    }
+
+    /// Record a missed-heartbeat gap in the ring buffer, overwriting the
+    /// oldest once MAX_HEARTBEAT_GAPS are stored
+    ///
+    /// Returns the event describing the gap; its `gap_index` is what slashing
+    /// counter-evidence cites to look the entry up with heartbeat_gap.
+    pub fn record_heartbeat_gap(
+        &mut self,
+        start: i64,
+        end: i64,
+        missed_intervals: u64,
+    ) -> Result<HeartbeatIntervalEvent> {
+        let gap_index = self.heartbeat_gap_count;
+        self.heartbeat_gaps[(gap_index % MAX_HEARTBEAT_GAPS as u64) as usize] = HeartbeatGap {
+            start,
+            missed_intervals,
+        };
+        self.heartbeat_gap_count = gap_index.checked_add(1).ok_or(RegistryError::Overflow)?;
+
+        Ok(HeartbeatIntervalEvent {
+            operator: self.operator,
+            node_id: self.node_id,
+            gap_index,
+            gap_start: start,
+            gap_end: end,
+            missed_intervals,
+            timestamp: end,
+        })
+    }
+
+    /// Gap number `gap_index`, if it's still in the history
+    pub fn heartbeat_gap(&self, gap_index: u64) -> Option<HeartbeatGap> {
+        if gap_index >= self.heartbeat_gap_count
+            || self.heartbeat_gap_count - gap_index > MAX_HEARTBEAT_GAPS as u64
+        {
+            return None;
+        }
+        Some(self.heartbeat_gaps[(gap_index % MAX_HEARTBEAT_GAPS as u64) as usize])
+    }
+
+    /// Whether `now` falls inside an announced maintenance window
+    pub fn in_maintenance(&self, now: i64) -> bool {
+        self.maintenance_windows
+            .iter()
+            .any(|w| w.start <= now && now < w.end)
+    }
+
+    /// Validate and record a new maintenance window, evicting the oldest
+    pub fn schedule_maintenance(&mut self, start: i64, end: i64, now: i64) -> Result<()> {
+        require!(
+            end > start && end - start <= MAX_MAINTENANCE_DURATION,
+            RegistryError::InvalidMaintenanceWindow
+        );
+        require!(
+            start >= now.saturating_add(MIN_MAINTENANCE_NOTICE),
+            RegistryError::MaintenanceNoticeTooShort
+        );
+
+        let latest = self.maintenance_windows[MAX_MAINTENANCE_WINDOWS - 1];
+        require!(start >= latest.end, RegistryError::InvalidMaintenanceWindow);
+
+        let recent = self
+            .maintenance_windows
+            .iter()
+            .filter(|w| w.end > 0 && w.start > start.saturating_sub(MAINTENANCE_QUOTA_PERIOD))
+            .count();
+        require!(
+            recent < MAX_MAINTENANCE_WINDOWS,
+            RegistryError::MaintenanceQuotaExceeded
+        );
+
+        self.maintenance_windows.rotate_left(1);
+        self.maintenance_windows[MAX_MAINTENANCE_WINDOWS - 1] = MaintenanceWindow { start, end };
+        Ok(())
+    }
+}
+
+/// Announced maintenance period, [start, end) in unix seconds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl MaintenanceWindow {
+    pub const MAX_SIZE: usize = 8 + 8;
+}
+
+/// A run of consecutive missed heartbeat intervals; the first was due at `start`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeartbeatGap {
+    pub start: i64,
+    pub missed_intervals: u64,
+}
+
+impl HeartbeatGap {
+    pub const MAX_SIZE: usize = 8 + 8;
+}
+
+/// Zone assignment - records that a node was authorized to serve a customer zone
+#[account]
+pub struct ZoneAssignment {
+    pub zone_hash: [u8; 32],        // Off-chain zone identifier hash (32 bytes)
+    pub operator: Pubkey,           // Node operator wallet (32 bytes)
+    pub node_id: u8,                // Operator's node serving the zone (1 byte)
+    pub assigned_by: Pubkey,        // Admin or zone assigner that made the assignment (32 bytes)
+    pub assigned_at: i64,           // Latest assignment timestamp (8 bytes)
+    pub revoked_at: i64,            // Latest revocation timestamp, 0 = never (8 bytes)
+    pub active: bool,               // Whether the assignment is in force (1 byte)
+    pub bump: u8,                   // PDA bump seed (1 byte)
+}
+
+impl ZoneAssignment {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                        // zone_hash
+        32 +                        // operator
+        1 +                         // node_id
+        32 +                        // assigned_by
+        8 +                         // assigned_at
+        8 +                         // revoked_at
+        1 +                         // active
+        1;                          // bump
+
+    /// Activate the assignment for `node` and count it on the node
+    pub fn assign(&mut self, node: &mut NodeAccount, assigned_by: Pubkey, now: i64) -> Result<()> {
+        require!(!self.active, RegistryError::ZoneAlreadyAssigned);
+        require!(
+            node.status == NodeStatus::Active,
+            RegistryError::NodeNotActive
+        );
+
+        node.zone_assignments = node
+            .zone_assignments
+            .checked_add(1)
+            .ok_or(RegistryError::Overflow)?;
+        self.node_id = node.node_id;
+        self.assigned_by = assigned_by;
+        self.assigned_at = now;
+        self.revoked_at = 0;
+        self.active = true;
+        Ok(())
+    }
+
+    /// Deactivate the assignment and drop it from the node's count
+    pub fn revoke(&mut self, node: &mut NodeAccount, now: i64) -> Result<()> {
+        require!(self.active, RegistryError::ZoneNotAssigned);
+
+        node.zone_assignments = node
+            .zone_assignments
+            .checked_sub(1)
+            .ok_or(RegistryError::Overflow)?;
+        self.revoked_at = now;
+        self.active = false;
+        Ok(())
+    }
+}
+
+/// Layout of node accounts created before multi-node support (no node_id),
+/// read by migrate_legacy_node
+#[derive(AnchorDeserialize)]
+pub struct LegacyNodeAccount {
+    pub operator: Pubkey,
+    pub metadata_url: String,
+    pub status: NodeStatus,
+    pub stake_amount: u64,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub reputation_score: u64,
+    pub last_heartbeat: i64,
+    pub total_heartbeats: u64,
+    pub missed_heartbeats: u64,
+    pub bump: u8,
+}
+
+/// Per-operator node counter - assigns node_ids for multi-node operators
+#[account]
+pub struct OperatorNodes {
+    pub operator: Pubkey,           // Node operator wallet (32 bytes)
+    pub node_count: u8,             // Nodes registered so far / next node_id (1 byte)
+    pub bump: u8,                   // PDA bump seed (1 byte)
+}
+
+impl OperatorNodes {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                        // operator
+        1 +                         // node_count
+        1;                          // bump
+}
+
+/// Node status enum
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum NodeStatus {
+    Active,
+    Inactive,
+    Slashed,
+}
+
+/// SECURITY FIX: Initialize registry config
+#[derive(Accounts)]
+pub struct InitializeRegistryConfig<'info> {
+    #[account(
+        init,
+        payer = deployer,
+        space = RegistryConfig::MAX_SIZE,
+        seeds = [b"registry_config"],
+        bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(mut)]
+    pub deployer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
@@ -542,6 +1567,27 @@ pub struct UpdateRegistryConfig<'info> {
     pub admin: Signer<'info>,
 }
 
+/// Migrate the registry config to the current layout
+#[derive(Accounts)]
+pub struct MigrateRegistryConfig<'info> {
+    /// CHECK: Older layouts are shorter than RegistryConfig::MAX_SIZE and
+    /// can't be decoded as Account<RegistryConfig> until resized; the PDA
+    /// and owner are checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [b"registry_config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub registry_config: UncheckedAccount<'info>,
+
+    /// Must be current admin; pays any extra rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 /// Register new node
 /// Y2.6: Updated to include registry_config for min_stake validation
 #[derive(Accounts)]
@@ -553,15 +1599,33 @@ pub struct RegisterNode<'info> {
     )]
     pub registry_config: Account<'info, RegistryConfig>,
 
+    /// Operator's node counter (created on first registration)
+    #[account(
+        init_if_needed,
+        payer = operator,
+        space = OperatorNodes::MAX_SIZE,
+        seeds = [b"operator_nodes", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_nodes: Account<'info, OperatorNodes>,
+
     #[account(
         init,
         payer = operator,
         space = NodeAccount::MAX_SIZE,
-        seeds = [b"node", operator.key().as_ref()],
+        seeds = [b"node", operator.key().as_ref(), &[operator_nodes.node_count]],
         bump
     )]
     pub node_account: Account<'info, NodeAccount>,
 
+    /// CHECK: Legacy single-node PDA; must not exist (migrate it first)
+    #[account(
+        seeds = [b"node", operator.key().as_ref()],
+        bump,
+        constraint = legacy_node_account.data_is_empty() @ RegistryError::LegacyNodeNotMigrated
+    )]
+    pub legacy_node_account: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub operator: Signer<'info>,
 
@@ -573,7 +1637,21 @@ pub struct RegisterNode<'info> {
 pub struct UpdateNodeMetadata<'info> {
     #[account(
         mut,
-        seeds = [b"node", operator.key().as_ref()],
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
+        bump = node_account.bump,
+        has_one = operator @ RegistryError::UnauthorizedOperator
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    pub operator: Signer<'info>,
+}
+
+/// Update a node's contact channel
+#[derive(Accounts)]
+pub struct UpdateContact<'info> {
+    #[account(
+        mut,
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
         bump = node_account.bump,
         has_one = operator @ RegistryError::UnauthorizedOperator
     )]
@@ -587,7 +1665,7 @@ pub struct UpdateNodeMetadata<'info> {
 pub struct DeactivateNode<'info> {
     #[account(
         mut,
-        seeds = [b"node", operator.key().as_ref()],
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
         bump = node_account.bump,
         has_one = operator @ RegistryError::UnauthorizedOperator
     )]
@@ -599,9 +1677,16 @@ pub struct DeactivateNode<'info> {
 /// Reactivate node
 #[derive(Accounts)]
 pub struct ReactivateNode<'info> {
+    /// Reactivation floor and haircut
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
     #[account(
         mut,
-        seeds = [b"node", operator.key().as_ref()],
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
         bump = node_account.bump,
         has_one = operator @ RegistryError::UnauthorizedOperator
     )]
@@ -628,7 +1713,7 @@ pub struct UpdateStake<'info> {
 
     #[account(
         mut,
-        seeds = [b"node", node_account.operator.as_ref()],
+        seeds = [b"node", node_account.operator.as_ref(), &[node_account.node_id]],
         bump = node_account.bump
     )]
     pub node_account: Account<'info, NodeAccount>,
@@ -651,9 +1736,29 @@ pub struct UpdateStake<'info> {
 /// Node heartbeat - prove liveness
 #[derive(Accounts)]
 pub struct Heartbeat<'info> {
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
     #[account(
         mut,
-        seeds = [b"node", operator.key().as_ref()],
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
+        bump = node_account.bump,
+        has_one = operator @ RegistryError::UnauthorizedOperator
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    pub operator: Signer<'info>,
+}
+
+/// Schedule a node maintenance window
+#[derive(Accounts)]
+pub struct ScheduleMaintenance<'info> {
+    #[account(
+        mut,
+        seeds = [b"node", operator.key().as_ref(), &[node_account.node_id]],
         bump = node_account.bump,
         has_one = operator @ RegistryError::UnauthorizedOperator
     )]
@@ -662,6 +1767,53 @@ pub struct Heartbeat<'info> {
     pub operator: Signer<'info>,
 }
 
+/// Batched heartbeat - node accounts are passed as remaining_accounts
+#[derive(Accounts)]
+pub struct HeartbeatMany<'info> {
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    pub operator: Signer<'info>,
+}
+
+/// Migrate a legacy single-node account to node_id 0
+#[derive(Accounts)]
+pub struct MigrateLegacyNode<'info> {
+    /// CHECK: Legacy node PDA, validated by seeds and deserialized manually
+    #[account(
+        mut,
+        seeds = [b"node", operator.key().as_ref()],
+        bump
+    )]
+    pub legacy_node_account: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = OperatorNodes::MAX_SIZE,
+        seeds = [b"operator_nodes", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_nodes: Account<'info, OperatorNodes>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = NodeAccount::MAX_SIZE,
+        seeds = [b"node", operator.key().as_ref(), &[0u8]],
+        bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 /// Update reputation score (admin/rewards program only)
 #[derive(Accounts)]
 pub struct UpdateReputation<'info> {
@@ -673,7 +1825,7 @@ pub struct UpdateReputation<'info> {
 
     #[account(
         mut,
-        seeds = [b"node", node_account.operator.as_ref()],
+        seeds = [b"node", node_account.operator.as_ref(), &[node_account.node_id]],
         bump = node_account.bump
     )]
     pub node_account: Account<'info, NodeAccount>,
@@ -686,16 +1838,99 @@ pub struct UpdateReputation<'info> {
 #[derive(Accounts)]
 pub struct CheckLiveness<'info> {
     #[account(
-        seeds = [b"node", node_account.operator.as_ref()],
+        seeds = [b"node", node_account.operator.as_ref(), &[node_account.node_id]],
+        bump = node_account.bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+}
+
+/// Emit a node snapshot (read-only)
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct EmitNodeSnapshot<'info> {
+    #[account(
+        seeds = [b"node", operator.as_ref(), &[node_account.node_id]],
         bump = node_account.bump
     )]
     pub node_account: Account<'info, NodeAccount>,
 }
 
+/// Emit a node's uptime report (read-only)
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct EmitUptimeReport<'info> {
+    #[account(
+        seeds = [b"node", operator.as_ref(), &[node_account.node_id]],
+        bump = node_account.bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+}
+
+/// Assign a zone to a node
+#[derive(Accounts)]
+#[instruction(zone_hash: [u8; 32])]
+pub struct AssignZone<'info> {
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"node", node_account.operator.as_ref(), &[node_account.node_id]],
+        bump = node_account.bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = assigner,
+        space = ZoneAssignment::MAX_SIZE,
+        seeds = [b"zone", zone_hash.as_ref(), node_account.operator.as_ref()],
+        bump
+    )]
+    pub zone_assignment: Account<'info, ZoneAssignment>,
+
+    /// Must be admin or the designated zone assigner (verified in instruction)
+    #[account(mut)]
+    pub assigner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a zone assignment
+#[derive(Accounts)]
+pub struct RevokeZone<'info> {
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"zone", zone_assignment.zone_hash.as_ref(), zone_assignment.operator.as_ref()],
+        bump = zone_assignment.bump
+    )]
+    pub zone_assignment: Account<'info, ZoneAssignment>,
+
+    #[account(
+        mut,
+        seeds = [b"node", zone_assignment.operator.as_ref(), &[zone_assignment.node_id]],
+        bump = node_account.bump
+    )]
+    pub node_account: Account<'info, NodeAccount>,
+
+    /// Must be admin or the designated zone assigner (verified in instruction)
+    pub assigner: Signer<'info>,
+}
+
 /// Events
 #[event]
 pub struct NodeRegisteredEvent {
     pub operator: Pubkey,
+    pub node_id: u8,
     pub metadata_url: String,
     pub stake_amount: u64,
     pub timestamp: i64,
@@ -708,6 +1943,15 @@ pub struct NodeUpdatedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ContactUpdatedEvent {
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub contact_hash: [u8; 32],
+    pub notify_flags: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct NodeDeactivatedEvent {
     pub operator: Pubkey,
@@ -717,6 +1961,14 @@ pub struct NodeDeactivatedEvent {
 #[event]
 pub struct NodeReactivatedEvent {
     pub operator: Pubkey,
+    /// Reputation after any haircut
+    pub reputation_score: u64,
+    /// Reputation taken for excess deactivations, 0 = none
+    pub reputation_haircut: u64,
+    /// Voluntary deactivations in the current window
+    pub deactivation_count: u8,
+    /// Routing weight after reactivation
+    pub routing_score: u32,
     pub timestamp: i64,
 }
 
@@ -733,9 +1985,49 @@ pub struct NodeAutoDeactivatedEvent {
 #[event]
 pub struct HeartbeatEvent {
     pub operator: Pubkey,
+    pub node_id: u8,
     pub reputation_score: u64,
     pub total_heartbeats: u64,
     pub missed_heartbeats: u64,
+    pub routing_score: u32,
+    /// Agent-supplied sequence, for off-chain gap detection
+    pub sequence: Option<u64>,
+    pub client_timestamp: i64,
+    pub timestamp: i64,
+    /// Heartbeat-derived uptime after this heartbeat, basis points
+    pub uptime_bps: u16,
+}
+
+/// Event emitted for each missed-heartbeat gap, when the node's next heartbeat
+/// closes it. Also recorded in the node's gap history under `gap_index`.
+#[event]
+pub struct HeartbeatIntervalEvent {
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub gap_index: u64,
+    /// When the first missed heartbeat was due
+    pub gap_start: i64,
+    /// When the next heartbeat arrived
+    pub gap_end: i64,
+    pub missed_intervals: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the registry config is grown to the current layout
+#[event]
+pub struct RegistryConfigMigratedEvent {
+    pub admin: Pubkey,
+    pub from_len: u32,
+    pub to_len: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a legacy single-node account is migrated to node_id 0
+#[event]
+pub struct NodeMigratedEvent {
+    pub operator: Pubkey,
+    pub legacy_account: Pubkey,
+    pub node_account: Pubkey,
     pub timestamp: i64,
 }
 
@@ -745,6 +2037,7 @@ pub struct ReputationUpdatedEvent {
     pub operator: Pubkey,
     pub old_score: u64,
     pub new_score: u64,
+    pub routing_score: u32,
     pub timestamp: i64,
 }
 
@@ -758,6 +2051,99 @@ pub struct LivenessCheckEvent {
     pub offline_48_hours: bool,
     pub reputation_score: u64,
     pub timestamp: i64,
+    pub in_maintenance: bool,
+}
+
+/// Full node state plus derived liveness values, emitted by emit_node_snapshot
+#[event]
+pub struct NodeSnapshotEvent {
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub metadata_url: String,
+    pub status: NodeStatus,
+    pub stake_amount: u64,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub reputation_score: u64,
+    pub last_heartbeat: i64,
+    pub total_heartbeats: u64,
+    pub missed_heartbeats: u64,
+    pub routing_score: u32,
+    pub maintenance_windows: [MaintenanceWindow; MAX_MAINTENANCE_WINDOWS],
+    /// total_heartbeats / (total_heartbeats + missed_heartbeats), basis points
+    pub uptime_ratio_bps: u64,
+    pub seconds_since_heartbeat: i64,
+    /// Beyond the heartbeat grace period, not counting announced maintenance
+    pub is_offline: bool,
+    pub in_maintenance: bool,
+    pub timestamp: i64,
+}
+
+impl NodeSnapshotEvent {
+    fn from_node(node_account: &NodeAccount, now: i64) -> Self {
+        Self {
+            operator: node_account.operator,
+            node_id: node_account.node_id,
+            metadata_url: node_account.metadata_url.clone(),
+            status: node_account.status.clone(),
+            stake_amount: node_account.stake_amount,
+            registered_at: node_account.registered_at,
+            updated_at: node_account.updated_at,
+            reputation_score: node_account.reputation_score,
+            last_heartbeat: node_account.last_heartbeat,
+            total_heartbeats: node_account.total_heartbeats,
+            missed_heartbeats: node_account.missed_heartbeats,
+            routing_score: node_account.routing_score,
+            maintenance_windows: node_account.maintenance_windows,
+            uptime_ratio_bps: node_account.uptime_ratio_bps(),
+            seconds_since_heartbeat: now - node_account.last_heartbeat,
+            is_offline: node_account.downtime_since_heartbeat(now)
+                > NodeAccount::HEARTBEAT_GRACE_PERIOD,
+            in_maintenance: node_account.in_maintenance(now),
+            timestamp: now,
+        }
+    }
+}
+
+/// Heartbeat counters for oracles to anchor uptime attestations against
+#[event]
+pub struct UptimeReportEvent {
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub window_start: i64,
+    pub total_heartbeats: u64,
+    pub missed_heartbeats: u64,
+    /// total_heartbeats / (total_heartbeats + missed_heartbeats), basis points
+    pub uptime_bps: u16,
+    pub last_heartbeat: i64,
+    pub timestamp: i64,
+}
+
+impl UptimeReportEvent {
+    fn from_node(node_account: &NodeAccount, window_start: i64, now: i64) -> Result<Self> {
+        require!(window_start <= now, RegistryError::InvalidUptimeWindow);
+        Ok(Self {
+            operator: node_account.operator,
+            node_id: node_account.node_id,
+            window_start,
+            total_heartbeats: node_account.total_heartbeats,
+            missed_heartbeats: node_account.missed_heartbeats,
+            uptime_bps: node_account.uptime_bps,
+            last_heartbeat: node_account.last_heartbeat,
+            timestamp: now,
+        })
+    }
+}
+
+/// Event emitted when an operator announces a maintenance window, so the
+/// control plane can drain traffic from the node
+#[event]
+pub struct MaintenanceScheduledEvent {
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub start: i64,
+    pub end: i64,
+    pub timestamp: i64,
 }
 
 /// Event emitted when stake is updated via CPI (X1.3 security fix)
@@ -765,6 +2151,30 @@ pub struct LivenessCheckEvent {
 pub struct StakeUpdatedEvent {
     pub operator: Pubkey,
     pub new_stake_amount: u64,
+    pub routing_score: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a node is authorized to serve a zone
+#[event]
+pub struct ZoneAssignedEvent {
+    pub zone_hash: [u8; 32],
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub assigned_by: Pubkey,
+    pub zone_assignments: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a node's zone assignment is revoked
+#[event]
+pub struct ZoneRevokedEvent {
+    pub zone_hash: [u8; 32],
+    pub operator: Pubkey,
+    pub node_id: u8,
+    pub revoked_by: Pubkey,
+    pub assigned_at: i64,
+    pub zone_assignments: u32,
     pub timestamp: i64,
 }
 
@@ -812,4 +2222,765 @@ pub enum RegistryError {
     /// Y7.7: Reputation floor for reactivation
     #[msg("Reputation score too low for reactivation (minimum 30% required)")]
     ReputationTooLow,
+
+    /// Multi-node operators
+    #[msg("Operator has reached the maximum number of nodes")]
+    TooManyNodes,
+
+    #[msg("Legacy node account must be migrated before registering more nodes")]
+    LegacyNodeNotMigrated,
+
+    #[msg("Heartbeat batch must contain between 1 and 8 node accounts")]
+    InvalidHeartbeatBatch,
+
+    #[msg("Node appears more than once in the heartbeat batch")]
+    DuplicateNodeInBatch,
+
+    #[msg("Account is not a valid node account")]
+    InvalidNodeAccount,
+
+    #[msg("Routing params require stake_saturation > 0, fresh_window >= 0, decay_window > 0")]
+    InvalidRoutingParams,
+
+    #[msg("Maintenance window must end after it starts, last at most 24 hours, and not overlap the previous window")]
+    InvalidMaintenanceWindow,
+
+    #[msg("Maintenance must be announced at least 1 hour in advance")]
+    MaintenanceNoticeTooShort,
+
+    #[msg("At most 2 maintenance windows per node in any 30 days")]
+    MaintenanceQuotaExceeded,
+
+    #[msg("Heartbeat client_timestamp must be within 90 seconds of the cluster clock")]
+    HeartbeatTimestampOutOfRange,
+
+    #[msg("Heartbeat client_timestamp must be later than the previous heartbeat's")]
+    HeartbeatTimestampNotIncreasing,
+
+    #[msg("Heartbeat sequence must be greater than the previous heartbeat's")]
+    HeartbeatSequenceNotIncreasing,
+
+    /// Zone assignments
+    #[msg("Unauthorized: Only admin or the zone assigner can manage zone assignments")]
+    UnauthorizedZoneAssigner,
+
+    #[msg("Zone is already assigned to this operator")]
+    ZoneAlreadyAssigned,
+
+    #[msg("Zone assignment is not active")]
+    ZoneNotAssigned,
+
+    #[msg("Reactivation floor must be at most 10000 and haircut at most 10000 bps")]
+    InvalidReactivationPolicy,
+
+    #[msg("Notify flags contain undefined bits")]
+    InvalidNotifyFlags,
+
+    #[msg("Uptime report window can't start in the future")]
+    InvalidUptimeWindow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn node(reputation_score: u64, stake_amount: u64, last_heartbeat: i64) -> NodeAccount {
+        NodeAccount {
+            operator: Pubkey::new_unique(),
+            metadata_url: String::new(),
+            status: NodeStatus::Active,
+            stake_amount,
+            registered_at: 0,
+            updated_at: 0,
+            reputation_score,
+            last_heartbeat,
+            total_heartbeats: 1,
+            missed_heartbeats: 0,
+            bump: 255,
+            node_id: 0,
+            routing_score: 0,
+            maintenance_windows: Default::default(),
+            last_client_timestamp: 0,
+            heartbeat_sequence: 0,
+            zone_assignments: 0,
+            heartbeat_gaps: Default::default(),
+            heartbeat_gap_count: 0,
+            deactivation_count: 0,
+            deactivation_window_start: 0,
+            contact_hash: [0; 32],
+            notify_flags: 0,
+            uptime_bps: 10_000,
+        }
+    }
+
+    fn score(node_account: &NodeAccount, now: i64) -> u32 {
+        routing_score(node_account, &RoutingScoreParams::default(), now)
+    }
+
+    #[test]
+    fn test_routing_score_bounds() {
+        let saturated = DEFAULT_ROUTING_STAKE_SATURATION;
+        assert_eq!(score(&node(NodeAccount::MAX_REPUTATION, saturated, NOW), NOW), 1_000_000);
+        assert_eq!(score(&node(0, saturated, NOW), NOW), 0);
+        assert_eq!(score(&node(NodeAccount::MAX_REPUTATION, 0, NOW), NOW), 0);
+        // Stake above saturation doesn't raise the score further
+        assert_eq!(score(&node(NodeAccount::MAX_REPUTATION, u64::MAX, NOW), NOW), 1_000_000);
+    }
+
+    #[test]
+    fn test_routing_score_is_product_of_factors() {
+        // 50% reputation × 50% stake = 25%
+        let half_stake = DEFAULT_ROUTING_STAKE_SATURATION / 2;
+        assert_eq!(score(&node(5000, half_stake, NOW), NOW), 250_000);
+    }
+
+    #[test]
+    fn test_routing_score_freshness_decay() {
+        let n = node(NodeAccount::MAX_REPUTATION, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+
+        // No penalty through the end of the fresh window
+        assert_eq!(score(&n, NOW + DEFAULT_ROUTING_FRESH_WINDOW), 1_000_000);
+        // Halfway through the decay window
+        let half_decayed = NOW + DEFAULT_ROUTING_FRESH_WINDOW + DEFAULT_ROUTING_DECAY_WINDOW / 2;
+        assert_eq!(score(&n, half_decayed), 500_000);
+        // Fully decayed and beyond
+        let decayed = NOW + DEFAULT_ROUTING_FRESH_WINDOW + DEFAULT_ROUTING_DECAY_WINDOW;
+        assert_eq!(score(&n, decayed), 0);
+        assert_eq!(score(&n, i64::MAX), 0);
+        // A heartbeat timestamp in the future is treated as fresh
+        assert_eq!(score(&n, NOW - 60), 1_000_000);
+    }
+
+    #[test]
+    fn test_routing_score_zero_when_not_active() {
+        let mut n = node(NodeAccount::MAX_REPUTATION, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.status = NodeStatus::Slashed;
+        assert_eq!(score(&n, NOW), 0);
+    }
+
+    #[test]
+    fn test_heartbeat_refreshes_routing_score() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        let later = NOW + NodeAccount::HEARTBEAT_INTERVAL;
+        apply_heartbeat(&mut n, &RoutingScoreParams::default(), later, later, None).unwrap();
+
+        // On-time heartbeat: +10 reputation, fresh
+        assert_eq!(n.reputation_score, 5010);
+        assert_eq!(n.routing_score, 501_000);
+    }
+
+    const HOUR: i64 = 60 * 60;
+
+    #[test]
+    fn test_heartbeat_penalty_suppressed_inside_maintenance() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.schedule_maintenance(NOW + HOUR, NOW + 3 * HOUR, NOW - HOUR).unwrap();
+
+        // Offline from NOW through the whole window, back one interval after it
+        let back = NOW + 3 * HOUR + NodeAccount::HEARTBEAT_INTERVAL;
+        apply_heartbeat(&mut n, &RoutingScoreParams::default(), back, back, None).unwrap();
+
+        // Only the hour before the window and the interval after it count:
+        // 3900/300 - 1 = 12 missed (36 without the window)
+        assert_eq!(n.missed_heartbeats, 12);
+        assert_eq!(n.reputation_score, 5000 - 12 * 100);
+    }
+
+    #[test]
+    fn test_heartbeat_penalty_outside_maintenance() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.schedule_maintenance(NOW + 10 * HOUR, NOW + 12 * HOUR, NOW - HOUR).unwrap();
+
+        // Gap ends before the window starts, so every interval counts
+        apply_heartbeat(&mut n, &RoutingScoreParams::default(), NOW + HOUR, NOW + HOUR, None).unwrap();
+        assert_eq!(n.missed_heartbeats, 11);
+    }
+
+    #[test]
+    fn test_maintenance_overlap_and_liveness() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.schedule_maintenance(NOW + HOUR, NOW + 25 * HOUR, NOW).unwrap();
+
+        assert_eq!(n.maintenance_overlap(NOW, NOW + HOUR), 0);
+        assert_eq!(n.maintenance_overlap(NOW, NOW + 2 * HOUR), HOUR);
+        assert_eq!(n.maintenance_overlap(NOW, NOW + 48 * HOUR), 24 * HOUR);
+        assert!(!n.in_maintenance(NOW));
+        assert!(n.in_maintenance(NOW + HOUR));
+        assert!(!n.in_maintenance(NOW + 25 * HOUR));
+    }
+
+    #[test]
+    fn test_maintenance_quota_per_thirty_days() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        let day = 24 * HOUR;
+
+        n.schedule_maintenance(NOW + HOUR, NOW + 2 * HOUR, NOW).unwrap();
+        n.schedule_maintenance(NOW + day, NOW + day + HOUR, NOW).unwrap();
+        assert!(n.schedule_maintenance(NOW + 2 * day, NOW + 2 * day + HOUR, NOW).is_err());
+
+        // Once the first window is 30 days old there is room again
+        let later = NOW + HOUR + MAINTENANCE_QUOTA_PERIOD;
+        n.schedule_maintenance(later, later + HOUR, later - HOUR).unwrap();
+        assert_eq!(n.maintenance_windows[1], MaintenanceWindow { start: later, end: later + HOUR });
+    }
+
+    #[test]
+    fn test_maintenance_window_validation() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+
+        // Retroactive or short-notice windows
+        assert!(n.schedule_maintenance(NOW - HOUR, NOW + HOUR, NOW).is_err());
+        assert!(n.schedule_maintenance(NOW + HOUR - 1, NOW + 2 * HOUR, NOW).is_err());
+        // Empty or longer than 24 hours
+        assert!(n.schedule_maintenance(NOW + HOUR, NOW + HOUR, NOW).is_err());
+        assert!(n.schedule_maintenance(NOW + HOUR, NOW + 25 * HOUR + 1, NOW).is_err());
+
+        // Overlapping the previous window
+        n.schedule_maintenance(NOW + HOUR, NOW + 3 * HOUR, NOW).unwrap();
+        assert!(n.schedule_maintenance(NOW + 2 * HOUR, NOW + 4 * HOUR, NOW).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_derived_values() {
+        let mut n = node(7000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.total_heartbeats = 90;
+        n.missed_heartbeats = 10;
+
+        let later = NOW + NodeAccount::HEARTBEAT_GRACE_PERIOD;
+        let snapshot = NodeSnapshotEvent::from_node(&n, later);
+        assert_eq!(snapshot.uptime_ratio_bps, 9_000);
+        assert_eq!(snapshot.seconds_since_heartbeat, NodeAccount::HEARTBEAT_GRACE_PERIOD);
+        assert!(!snapshot.is_offline);
+        assert_eq!(snapshot.status, NodeStatus::Active);
+        assert_eq!(snapshot.reputation_score, 7000);
+        assert_eq!(snapshot.timestamp, later);
+
+        let snapshot = NodeSnapshotEvent::from_node(&n, later + 1);
+        assert!(snapshot.is_offline);
+
+        // Announced maintenance doesn't count towards going offline
+        n.maintenance_windows[1] = MaintenanceWindow { start: NOW + 60, end: NOW + HOUR };
+        let snapshot = NodeSnapshotEvent::from_node(&n, NOW + HOUR);
+        assert_eq!(snapshot.seconds_since_heartbeat, HOUR);
+        assert!(!snapshot.is_offline);
+        assert!(!snapshot.in_maintenance);
+        assert!(NodeSnapshotEvent::from_node(&n, NOW + 60).in_maintenance);
+    }
+
+    #[test]
+    fn test_snapshot_reports_slashed_status() {
+        let mut n = node(NodeAccount::MAX_REPUTATION, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        n.status = NodeStatus::Slashed;
+        n.routing_score = 0;
+
+        let snapshot = NodeSnapshotEvent::from_node(&n, NOW);
+        assert_eq!(snapshot.status, NodeStatus::Slashed);
+        assert_eq!(snapshot.routing_score, 0);
+        assert_eq!(snapshot.uptime_ratio_bps, 10_000);
+    }
+
+    #[test]
+    fn test_uptime_ratio_without_history() {
+        let mut n = node(5000, 0, NOW);
+        n.total_heartbeats = 0;
+        assert_eq!(n.uptime_ratio_bps(), 0);
+    }
+
+    #[test]
+    fn test_uptime_bps_tracks_heartbeats() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        let params = RoutingScoreParams::default();
+        let interval = NodeAccount::HEARTBEAT_INTERVAL;
+
+        // On time: 2 of 2
+        let mut t = NOW + interval;
+        apply_heartbeat(&mut n, &params, t, t, None).unwrap();
+        assert_eq!(n.uptime_bps, 10_000);
+
+        // Four intervals late: 3 missed, 3 of 6
+        t += 4 * interval;
+        apply_heartbeat(&mut n, &params, t, t, None).unwrap();
+        assert_eq!((n.total_heartbeats, n.missed_heartbeats), (3, 3));
+        assert_eq!(n.uptime_bps, 5_000);
+
+        // Two more on time: 5 of 8
+        for _ in 0..2 {
+            t += interval;
+            apply_heartbeat(&mut n, &params, t, t, None).unwrap();
+        }
+        assert_eq!(n.uptime_bps, 6_250);
+        assert_eq!(u64::from(n.uptime_bps), n.uptime_ratio_bps());
+
+        // Announced maintenance isn't downtime
+        n.maintenance_windows[1] = MaintenanceWindow { start: t, end: t + HOUR };
+        t += HOUR + interval;
+        apply_heartbeat(&mut n, &params, t, t, None).unwrap();
+        assert_eq!((n.total_heartbeats, n.missed_heartbeats), (6, 3));
+        assert_eq!(n.uptime_bps, 6_666);
+    }
+
+    #[test]
+    fn test_uptime_bps_without_history_or_at_limits() {
+        let mut n = node(5000, 0, NOW);
+        n.total_heartbeats = 0;
+        n.refresh_uptime();
+        assert_eq!(n.uptime_bps, 0);
+
+        n.missed_heartbeats = 5;
+        n.refresh_uptime();
+        assert_eq!(n.uptime_bps, 0);
+
+        n.total_heartbeats = u64::MAX;
+        n.missed_heartbeats = u64::MAX;
+        n.refresh_uptime();
+        assert_eq!(n.uptime_bps, 5_000);
+    }
+
+    #[test]
+    fn test_uptime_report_contents() {
+        let mut n = node(5000, 0, NOW);
+        n.node_id = 2;
+        n.total_heartbeats = 95;
+        n.missed_heartbeats = 5;
+        n.refresh_uptime();
+
+        let window_start = NOW - 24 * HOUR;
+        let report = UptimeReportEvent::from_node(&n, window_start, NOW + 60).unwrap();
+        assert_eq!(report.operator, n.operator);
+        assert_eq!(report.node_id, 2);
+        assert_eq!(report.window_start, window_start);
+        assert_eq!((report.total_heartbeats, report.missed_heartbeats), (95, 5));
+        assert_eq!(report.uptime_bps, 9_500);
+        assert_eq!(report.last_heartbeat, NOW);
+        assert_eq!(report.timestamp, NOW + 60);
+
+        let err = UptimeReportEvent::from_node(&n, NOW + 61, NOW + 60).err().unwrap();
+        assert_eq!(err, RegistryError::InvalidUptimeWindow.into());
+    }
+
+    #[test]
+    fn test_routing_params_validation() {
+        assert!(RoutingScoreParams::default().is_valid());
+        let params = RoutingScoreParams {
+            stake_saturation: 0,
+            ..Default::default()
+        };
+        assert!(!params.is_valid());
+        let params = RoutingScoreParams {
+            decay_window: 0,
+            ..Default::default()
+        };
+        assert!(!params.is_valid());
+    }
+
+    #[test]
+    fn test_heartbeat_normal_cadence_accepted() {
+        let mut n = node(5000, 0, NOW);
+        for i in 0..3 {
+            let now = NOW + i * NodeAccount::HEARTBEAT_INTERVAL;
+            n.accept_heartbeat_binding(now - 2, Some(i as u64 + 1), now).unwrap();
+        }
+        assert_eq!(n.heartbeat_sequence, 3);
+        assert_eq!(n.last_client_timestamp, NOW + 2 * NodeAccount::HEARTBEAT_INTERVAL - 2);
+
+        // Agents without a sequence counter only need increasing timestamps
+        n.accept_heartbeat_binding(NOW + 1_000, None, NOW + 1_000).unwrap();
+        assert_eq!(n.heartbeat_sequence, 3);
+    }
+
+    #[test]
+    fn test_heartbeat_stale_timestamp_rejected() {
+        let mut n = node(5000, 0, NOW);
+        assert_eq!(
+            n.accept_heartbeat_binding(NOW - 91, None, NOW).unwrap_err(),
+            error!(RegistryError::HeartbeatTimestampOutOfRange)
+        );
+        assert_eq!(
+            n.accept_heartbeat_binding(NOW + 91, None, NOW).unwrap_err(),
+            error!(RegistryError::HeartbeatTimestampOutOfRange)
+        );
+
+        // A replay of an accepted heartbeat is still inside the clock window
+        n.accept_heartbeat_binding(NOW - 30, None, NOW).unwrap();
+        assert_eq!(
+            n.accept_heartbeat_binding(NOW - 30, None, NOW + 30).unwrap_err(),
+            error!(RegistryError::HeartbeatTimestampNotIncreasing)
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_non_monotonic_sequence_rejected() {
+        let mut n = node(5000, 0, NOW);
+        n.accept_heartbeat_binding(NOW, Some(7), NOW).unwrap();
+        assert_eq!(
+            n.accept_heartbeat_binding(NOW + 300, Some(7), NOW + 300).unwrap_err(),
+            error!(RegistryError::HeartbeatSequenceNotIncreasing)
+        );
+        assert_eq!(
+            n.accept_heartbeat_binding(NOW + 300, Some(6), NOW + 300).unwrap_err(),
+            error!(RegistryError::HeartbeatSequenceNotIncreasing)
+        );
+        // Rejected heartbeats leave the recorded binding untouched
+        assert_eq!(n.last_client_timestamp, NOW);
+        n.accept_heartbeat_binding(NOW + 300, Some(8), NOW + 300).unwrap();
+    }
+
+    fn zone(n: &NodeAccount) -> ZoneAssignment {
+        ZoneAssignment {
+            zone_hash: [7; 32],
+            operator: n.operator,
+            node_id: 0,
+            assigned_by: Pubkey::default(),
+            assigned_at: 0,
+            revoked_at: 0,
+            active: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_zone_assign() {
+        let mut n = node(5000, 0, NOW);
+        n.node_id = 2;
+        let mut z = zone(&n);
+        let admin = Pubkey::new_unique();
+
+        z.assign(&mut n, admin, NOW).unwrap();
+        assert!(z.active);
+        assert_eq!(z.node_id, 2);
+        assert_eq!(z.assigned_by, admin);
+        assert_eq!(z.assigned_at, NOW);
+        assert_eq!(n.zone_assignments, 1);
+
+        // Inactive nodes can't take new zones
+        let mut inactive = node(5000, 0, NOW);
+        inactive.status = NodeStatus::Inactive;
+        assert_eq!(
+            zone(&inactive).assign(&mut inactive, admin, NOW).unwrap_err(),
+            error!(RegistryError::NodeNotActive)
+        );
+    }
+
+    #[test]
+    fn test_zone_duplicate_assign_rejected() {
+        let mut n = node(5000, 0, NOW);
+        let mut z = zone(&n);
+        z.assign(&mut n, Pubkey::new_unique(), NOW).unwrap();
+        assert_eq!(
+            z.assign(&mut n, Pubkey::new_unique(), NOW + 1).unwrap_err(),
+            error!(RegistryError::ZoneAlreadyAssigned)
+        );
+        assert_eq!(z.assigned_at, NOW);
+        assert_eq!(n.zone_assignments, 1);
+    }
+
+    #[test]
+    fn test_zone_revoke() {
+        let mut n = node(5000, 0, NOW);
+        let mut z = zone(&n);
+        assert_eq!(
+            z.revoke(&mut n, NOW).unwrap_err(),
+            error!(RegistryError::ZoneNotAssigned)
+        );
+
+        z.assign(&mut n, Pubkey::new_unique(), NOW).unwrap();
+        z.revoke(&mut n, NOW + 60).unwrap();
+        assert!(!z.active);
+        assert_eq!(z.assigned_at, NOW);
+        assert_eq!(z.revoked_at, NOW + 60);
+        assert_eq!(
+            z.revoke(&mut n, NOW + 61).unwrap_err(),
+            error!(RegistryError::ZoneNotAssigned)
+        );
+
+        // Revoked zones can be assigned again
+        z.assign(&mut n, Pubkey::new_unique(), NOW + 120).unwrap();
+        assert_eq!(z.revoked_at, 0);
+    }
+
+    #[test]
+    fn test_zone_counter_consistency() {
+        let mut n = node(5000, 0, NOW);
+        let mut zones: Vec<ZoneAssignment> = (0..3u8)
+            .map(|i| ZoneAssignment { zone_hash: [i; 32], ..zone(&n) })
+            .collect();
+
+        for z in zones.iter_mut() {
+            z.assign(&mut n, Pubkey::new_unique(), NOW).unwrap();
+        }
+        assert_eq!(n.zone_assignments, 3);
+
+        zones[1].revoke(&mut n, NOW + 1).unwrap();
+        // Failed transitions leave the counter alone
+        assert!(zones[1].revoke(&mut n, NOW + 2).is_err());
+        assert!(zones[0].assign(&mut n, Pubkey::new_unique(), NOW + 2).is_err());
+        assert_eq!(n.zone_assignments, 2);
+        assert_eq!(
+            n.zone_assignments as usize,
+            zones.iter().filter(|z| z.active).count()
+        );
+    }
+
+    #[test]
+    fn test_zone_assigner_authorization() {
+        let admin = Pubkey::new_unique();
+        let assigner = Pubkey::new_unique();
+        let mut config = RegistryConfig {
+            admin_authority: admin,
+            staking_program_id: Pubkey::default(),
+            rewards_program_id: Pubkey::default(),
+            min_stake_for_registration: 0,
+            paused: false,
+            bump: 255,
+            routing_params: RoutingScoreParams::default(),
+            zone_assigner: Pubkey::default(),
+            reactivation_policy: ReactivationPolicy::default(),
+        };
+        assert!(config.can_assign_zones(&admin));
+        assert!(!config.can_assign_zones(&assigner));
+        assert!(!config.can_assign_zones(&Pubkey::default()));
+
+        config.zone_assigner = assigner;
+        assert!(config.can_assign_zones(&assigner));
+        assert!(config.can_assign_zones(&admin));
+    }
+
+    fn legacy_config_bytes(config: &RegistryConfig, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        config.try_serialize(&mut bytes).unwrap();
+        bytes.truncate(len);
+        assert!(RegistryConfig::try_deserialize(&mut &bytes[..]).is_err());
+        bytes.resize(RegistryConfig::MAX_SIZE, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_migrate_legacy_registry_config() {
+        let routing_params = RoutingScoreParams {
+            stake_saturation: 1,
+            ..Default::default()
+        };
+        let reactivation_policy = ReactivationPolicy {
+            haircut_bps: 1,
+            ..Default::default()
+        };
+        let current = RegistryConfig {
+            admin_authority: Pubkey::new_unique(),
+            staking_program_id: Pubkey::new_unique(),
+            rewards_program_id: Pubkey::default(),
+            min_stake_for_registration: 42,
+            paused: true,
+            bump: 254,
+            routing_params: routing_params.clone(),
+            zone_assigner: Pubkey::new_unique(),
+            reactivation_policy,
+        };
+        let legacy_len = RegistryConfig::MAX_SIZE - RoutingScoreParams::MAX_SIZE - 32 - ReactivationPolicy::MAX_SIZE;
+
+        // Before the routing params, the zone assigner and the reactivation policy
+        for (len, routing) in [
+            (legacy_len, RoutingScoreParams::default()),
+            (legacy_len + RoutingScoreParams::MAX_SIZE, routing_params.clone()),
+            (RegistryConfig::MAX_SIZE - ReactivationPolicy::MAX_SIZE, routing_params.clone()),
+        ] {
+            let bytes = legacy_config_bytes(&current, len);
+            let mut config = RegistryConfig::try_deserialize(&mut &bytes[..]).unwrap();
+            config.fill_appended_defaults(len);
+
+            assert_eq!(config.admin_authority, current.admin_authority);
+            assert_eq!(config.min_stake_for_registration, 42);
+            assert!(config.paused);
+            assert_eq!(config.bump, 254);
+            assert_eq!(config.routing_params, routing);
+            assert_eq!(config.reactivation_policy, ReactivationPolicy::default());
+        }
+
+        // Zones stay admin-only until an assigner is set
+        let bytes = legacy_config_bytes(&current, legacy_len);
+        let config = RegistryConfig::try_deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(config.zone_assigner, Pubkey::default());
+
+        let mut config = current.clone();
+        config.fill_appended_defaults(RegistryConfig::MAX_SIZE);
+        assert_eq!(config.routing_params, routing_params);
+        assert_eq!(config.reactivation_policy.haircut_bps, 1);
+    }
+
+    #[test]
+    fn test_heartbeat_gap_recorded_with_bounds() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        // Heartbeats due at +300, +600 and +900 missed; +1200 arrives
+        let back = NOW + 4 * NodeAccount::HEARTBEAT_INTERVAL;
+        apply_heartbeat(&mut n, &RoutingScoreParams::default(), back, back, None).unwrap();
+
+        assert_eq!(n.heartbeat_gap_count, 1);
+        assert_eq!(
+            n.heartbeat_gap(0),
+            Some(HeartbeatGap { start: NOW + NodeAccount::HEARTBEAT_INTERVAL, missed_intervals: 3 })
+        );
+        assert_eq!(n.heartbeat_gap(1), None);
+
+        // On-time heartbeats don't add entries
+        let next = back + NodeAccount::HEARTBEAT_INTERVAL;
+        apply_heartbeat(&mut n, &RoutingScoreParams::default(), next, next, None).unwrap();
+        assert_eq!(n.heartbeat_gap_count, 1);
+    }
+
+    #[test]
+    fn test_heartbeat_gap_event_bounds() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        let event = n
+            .record_heartbeat_gap(NOW + NodeAccount::HEARTBEAT_INTERVAL, NOW + 1200, 3)
+            .unwrap();
+
+        assert_eq!(event.operator, n.operator);
+        assert_eq!(event.gap_index, 0);
+        assert_eq!(event.gap_start, NOW + NodeAccount::HEARTBEAT_INTERVAL);
+        assert_eq!(event.gap_end, NOW + 1200);
+        assert_eq!(event.missed_intervals, 3);
+    }
+
+    #[test]
+    fn test_heartbeat_gap_history_wraps() {
+        let mut n = node(5000, DEFAULT_ROUTING_STAKE_SATURATION, NOW);
+        let gaps = MAX_HEARTBEAT_GAPS as u64 + 3;
+        for i in 0..gaps {
+            let start = NOW + i as i64 * HOUR;
+            let event = n.record_heartbeat_gap(start, start + 600, i + 1).unwrap();
+            assert_eq!(event.gap_index, i);
+        }
+
+        assert_eq!(n.heartbeat_gap_count, gaps);
+        // The first 3 gaps were overwritten by the newest
+        assert_eq!(n.heartbeat_gap(2), None);
+        assert_eq!(n.heartbeat_gap(3).unwrap().missed_intervals, 4);
+        assert_eq!(n.heartbeat_gaps[0].missed_intervals, MAX_HEARTBEAT_GAPS as u64 + 1);
+        assert_eq!(n.heartbeat_gap(gaps - 1).unwrap().missed_intervals, gaps);
+        assert_eq!(n.heartbeat_gap(gaps), None);
+    }
+    /// Deactivate at `at` the way deactivate_node does
+    fn deactivate(n: &mut NodeAccount, at: i64) {
+        n.status = NodeStatus::Inactive;
+        n.record_deactivation(at);
+    }
+
+    const DAY: i64 = 24 * HOUR;
+
+    #[test]
+    fn test_reactivation_policy_validation() {
+        assert!(ReactivationPolicy::default().is_valid());
+        let too_deep = ReactivationPolicy {
+            min_reputation_for_reactivation: 0,
+            haircut_bps: 10_001,
+        };
+        assert!(!too_deep.is_valid());
+        let too_high = ReactivationPolicy {
+            min_reputation_for_reactivation: NodeAccount::MAX_REPUTATION + 1,
+            haircut_bps: 10_000,
+        };
+        assert!(!too_high.is_valid());
+    }
+
+    #[test]
+    fn test_reactivation_below_floor_rejected() {
+        let policy = ReactivationPolicy::default();
+        let mut n = node(DEFAULT_MIN_REPUTATION_FOR_REACTIVATION - 1, 0, NOW);
+        deactivate(&mut n, NOW);
+
+        let err = n.reactivate(&policy, NOW + HOUR).unwrap_err();
+        assert_eq!(err, RegistryError::ReputationTooLow.into());
+        assert_eq!(n.status, NodeStatus::Inactive);
+
+        n.reputation_score = DEFAULT_MIN_REPUTATION_FOR_REACTIVATION;
+        assert_eq!(n.reactivate(&policy, NOW + HOUR).unwrap(), 0);
+        assert_eq!(n.status, NodeStatus::Active);
+    }
+
+    #[test]
+    fn test_third_reactivation_in_window_takes_haircut() {
+        let policy = ReactivationPolicy::default();
+        let mut n = node(5000, 0, NOW);
+
+        for i in 0..2 {
+            deactivate(&mut n, NOW + i * DAY);
+            assert_eq!(n.reactivate(&policy, NOW + i * DAY + HOUR).unwrap(), 0);
+        }
+        assert_eq!(n.reputation_score, 5000);
+
+        deactivate(&mut n, NOW + 2 * DAY);
+        assert_eq!(n.deactivation_count, 3);
+        // 10% of 5000
+        assert_eq!(n.reactivate(&policy, NOW + 2 * DAY + HOUR).unwrap(), 500);
+        assert_eq!(n.reputation_score, 4500);
+
+        // Every further cycle in the window costs again
+        deactivate(&mut n, NOW + 3 * DAY);
+        assert_eq!(n.reactivate(&policy, NOW + 3 * DAY + HOUR).unwrap(), 450);
+        assert_eq!(n.reputation_score, 4050);
+    }
+
+    #[test]
+    fn test_deactivation_count_resets_outside_window() {
+        let policy = ReactivationPolicy::default();
+        let mut n = node(5000, 0, NOW);
+
+        for i in 0..3 {
+            deactivate(&mut n, NOW + i * DAY);
+            n.status = NodeStatus::Active;
+        }
+        assert_eq!(n.deactivation_count, 3);
+
+        // Window has run out by reactivation time: no haircut
+        n.status = NodeStatus::Inactive;
+        assert_eq!(n.reactivate(&policy, NOW + DEACTIVATION_WINDOW).unwrap(), 0);
+
+        // The next deactivation opens a fresh window
+        deactivate(&mut n, NOW + DEACTIVATION_WINDOW + DAY);
+        assert_eq!(n.deactivation_count, 1);
+        assert_eq!(n.deactivation_window_start, NOW + DEACTIVATION_WINDOW + DAY);
+        assert_eq!(n.reactivate(&policy, NOW + DEACTIVATION_WINDOW + 2 * DAY).unwrap(), 0);
+        assert_eq!(n.reputation_score, 5000);
+    }
+
+    #[test]
+    fn test_contact_set_and_cleared() {
+        let mut n = node(5000, 0, NOW);
+        let hash = [7u8; 32];
+
+        n.set_contact(hash, NOTIFY_SLASH_REQUESTS).unwrap();
+        assert_eq!(n.contact_hash, hash);
+        assert_eq!(n.notify_flags, NOTIFY_SLASH_REQUESTS);
+
+        n.set_contact([0; 32], 0).unwrap();
+        assert_eq!(n.contact_hash, [0; 32]);
+        assert_eq!(n.notify_flags, 0);
+    }
+
+    #[test]
+    fn test_notify_flags_round_trip() {
+        let mut n = node(5000, 0, NOW);
+        n.set_contact([1; 32], NOTIFY_SLASH_REQUESTS | NOTIFY_REPUTATION_PENALTIES).unwrap();
+
+        let mut data = Vec::new();
+        n.serialize(&mut data).unwrap();
+        let decoded = NodeAccount::deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.contact_hash, [1; 32]);
+        assert_eq!(decoded.notify_flags, NOTIFY_SLASH_REQUESTS | NOTIFY_REPUTATION_PENALTIES);
+        // MAX_SIZE covers the new fields
+        assert!(8 + data.len() <= NodeAccount::MAX_SIZE);
+    }
+
+    #[test]
+    fn test_undefined_notify_flags_rejected() {
+        let mut n = node(5000, 0, NOW);
+        n.set_contact([1; 32], NOTIFY_SLASH_REQUESTS).unwrap();
+
+        let err = n.set_contact([2; 32], 1 << 7).unwrap_err();
+        assert_eq!(err, RegistryError::InvalidNotifyFlags.into());
+        // A rejected update leaves the contact untouched
+        assert_eq!(n.contact_hash, [1; 32]);
+        assert_eq!(n.notify_flags, NOTIFY_SLASH_REQUESTS);
+    }
 }
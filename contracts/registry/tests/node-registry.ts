@@ -2,6 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { NodeRegistry } from "../target/types/node_registry";
 import { expect } from "chai";
+import { createHash } from "crypto";
 
 describe("node-registry", () => {
   const provider = anchor.AnchorProvider.env();
@@ -12,11 +13,12 @@ describe("node-registry", () => {
 
   const VALID_IPFS_CID = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
   const MIN_STAKE = new anchor.BN(100_000_000_000); // 100 AEGIS tokens
+  const NO_CONTACT = Array(32).fill(0); // registerNode contact_hash when none is published
 
-  // Helper to derive node PDA
-  function getNodePDA(operator: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+  // Helper to derive node PDA (node_id is the operator's node index)
+  function getNodePDA(operator: anchor.web3.PublicKey, nodeId: number = 0): [anchor.web3.PublicKey, number] {
     return anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("node"), operator.toBuffer()],
+      [Buffer.from("node"), operator.toBuffer(), Buffer.from([nodeId])],
       program.programId
     );
   }
@@ -34,6 +36,12 @@ describe("node-registry", () => {
     await provider.sendAndConfirm(tx);
   }
 
+  // Cluster clock, used as the heartbeat client_timestamp
+  async function clusterTime(): Promise<number> {
+    const slot = await provider.connection.getSlot();
+    return (await provider.connection.getBlockTime(slot)) as number;
+  }
+
   describe("Node Registration", () => {
     let firstOperator: anchor.web3.Keypair;
     let firstNodePDA: anchor.web3.PublicKey;
@@ -44,7 +52,7 @@ describe("node-registry", () => {
       [firstNodePDA] = getNodePDA(firstOperator.publicKey);
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, MIN_STAKE)
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: firstNodePDA,
           operator: firstOperator.publicKey,
@@ -67,7 +75,7 @@ describe("node-registry", () => {
     it("Prevents duplicate registration", async () => {
       try {
         await program.methods
-          .registerNode(VALID_IPFS_CID, MIN_STAKE)
+          .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
           .accounts({
             nodeAccount: firstNodePDA,
             operator: firstOperator.publicKey,
@@ -90,7 +98,7 @@ describe("node-registry", () => {
 
       try {
         await program.methods
-          .registerNode("", MIN_STAKE)
+          .registerNode("", MIN_STAKE, NO_CONTACT, 0)
           .accounts({
             nodeAccount: nodePDA,
             operator: newOperator.publicKey,
@@ -113,7 +121,7 @@ describe("node-registry", () => {
 
       try {
         await program.methods
-          .registerNode(longUrl, MIN_STAKE)
+          .registerNode(longUrl, MIN_STAKE, NO_CONTACT, 0)
           .accounts({
             nodeAccount: nodePDA,
             operator: newOperator.publicKey,
@@ -136,7 +144,7 @@ describe("node-registry", () => {
 
       try {
         await program.methods
-          .registerNode(VALID_IPFS_CID, insufficientStake)
+          .registerNode(VALID_IPFS_CID, insufficientStake, NO_CONTACT, 0)
           .accounts({
             nodeAccount: nodePDA,
             operator: newOperator.publicKey,
@@ -158,7 +166,7 @@ describe("node-registry", () => {
       const largeStake = MIN_STAKE.muln(10); // 1000 AEGIS
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, largeStake)
+        .registerNode(VALID_IPFS_CID, largeStake, NO_CONTACT, 0)
         .accounts({
           nodeAccount: nodePDA,
           operator: newOperator.publicKey,
@@ -183,7 +191,7 @@ describe("node-registry", () => {
 
       // Register node first
       await program.methods
-        .registerNode(VALID_IPFS_CID, MIN_STAKE)
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: testNodePDA,
           operator: testOperator.publicKey,
@@ -253,6 +261,95 @@ describe("node-registry", () => {
     });
   });
 
+  // Contact hashing happens off-chain; only the hash is ever sent
+  describe("Contact channel", () => {
+    const NOTIFY_SLASH_REQUESTS = 1;
+    const NOTIFY_REPUTATION_PENALTIES = 2;
+    const contactHash = (contact: string) => Array.from(createHash("sha256").update(contact).digest());
+
+    let contactOperator: anchor.web3.Keypair;
+    let contactNodePDA: anchor.web3.PublicKey;
+
+    function updateContact(hash: number[], flags: number, signer = contactOperator) {
+      return program.methods
+        .updateContact(hash, flags)
+        .accounts({
+          nodeAccount: contactNodePDA,
+          operator: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      contactOperator = anchor.web3.Keypair.generate();
+      await fundAccount(contactOperator.publicKey);
+      [contactNodePDA] = getNodePDA(contactOperator.publicKey);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, contactHash("mailto:noc@operator.example"), NOTIFY_SLASH_REQUESTS)
+        .accounts({
+          nodeAccount: contactNodePDA,
+          operator: contactOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([contactOperator])
+        .rpc();
+    });
+
+    it("Stores the contact set at registration", async () => {
+      const node = await program.account.nodeAccount.fetch(contactNodePDA);
+      expect(Array.from(node.contactHash)).to.deep.equal(contactHash("mailto:noc@operator.example"));
+      expect(node.notifyFlags).to.equal(NOTIFY_SLASH_REQUESTS);
+    });
+
+    it("Updates the contact and round-trips the flags", async () => {
+      const hash = contactHash("https://status.operator.example/contact");
+      const flags = NOTIFY_SLASH_REQUESTS | NOTIFY_REPUTATION_PENALTIES;
+
+      let event: any = null;
+      const listener = program.addEventListener("contactUpdatedEvent", (e) => {
+        event = e;
+      });
+      await updateContact(hash, flags);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const node = await program.account.nodeAccount.fetch(contactNodePDA);
+      expect(Array.from(node.contactHash)).to.deep.equal(hash);
+      expect(node.notifyFlags).to.equal(flags);
+      expect(Array.from(event.contactHash)).to.deep.equal(hash);
+      expect(event.notifyFlags).to.equal(flags);
+
+      // Zeros clear it
+      await updateContact(NO_CONTACT, 0);
+      const cleared = await program.account.nodeAccount.fetch(contactNodePDA);
+      expect(Array.from(cleared.contactHash)).to.deep.equal(NO_CONTACT);
+      expect(cleared.notifyFlags).to.equal(0);
+    });
+
+    it("Rejects undefined notify flags", async () => {
+      try {
+        await updateContact(NO_CONTACT, 0x80);
+        expect.fail("Should have rejected undefined flags");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNotifyFlags");
+      }
+    });
+
+    it("Prevents unauthorized contact updates", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await updateContact(contactHash("mailto:attacker@example.com"), 0, unauthorized);
+        expect.fail("Should have prevented unauthorized update");
+      } catch (error) {
+        expect(error.toString()).to.include("AnchorError");
+      }
+    });
+  });
+
   describe("Node Status Management", () => {
     let statusOperator: anchor.web3.Keypair;
     let statusNodePDA: anchor.web3.PublicKey;
@@ -263,7 +360,7 @@ describe("node-registry", () => {
       [statusNodePDA] = getNodePDA(statusOperator.publicKey);
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, MIN_STAKE)
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: statusNodePDA,
           operator: statusOperator.publicKey,
@@ -299,6 +396,7 @@ describe("node-registry", () => {
 
       const nodeAccount = await program.account.nodeAccount.fetch(statusNodePDA);
       expect(nodeAccount.status).to.deep.equal({ active: {} });
+      expect(nodeAccount.routingScore).to.be.greaterThan(0);
     });
 
     it("Prevents deactivating already inactive node", async () => {
@@ -360,7 +458,7 @@ describe("node-registry", () => {
       [stakeNodePDA] = getNodePDA(stakeOperator.publicKey);
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, MIN_STAKE)
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: stakeNodePDA,
           operator: stakeOperator.publicKey,
@@ -426,7 +524,7 @@ describe("node-registry", () => {
         const [nodePDA] = getNodePDA(operators[i].publicKey);
 
         await program.methods
-          .registerNode(VALID_IPFS_CID, stakes[i])
+          .registerNode(VALID_IPFS_CID, stakes[i], NO_CONTACT, 0)
           .accounts({
             nodeAccount: nodePDA,
             operator: operators[i].publicKey,
@@ -451,7 +549,7 @@ describe("node-registry", () => {
       const maxLengthUrl = "Q".repeat(128); // Exactly at limit
 
       await program.methods
-        .registerNode(maxLengthUrl, MIN_STAKE)
+        .registerNode(maxLengthUrl, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: nodePDA,
           operator: newOperator.publicKey,
@@ -471,7 +569,7 @@ describe("node-registry", () => {
       const [nodePDA] = getNodePDA(newOperator.publicKey);
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, MIN_STAKE)
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
         .accounts({
           nodeAccount: nodePDA,
           operator: newOperator.publicKey,
@@ -491,7 +589,7 @@ describe("node-registry", () => {
       const largeStake = new anchor.BN("1000000000000000000"); // 1 billion AEGIS
 
       await program.methods
-        .registerNode(VALID_IPFS_CID, largeStake)
+        .registerNode(VALID_IPFS_CID, largeStake, NO_CONTACT, 0)
         .accounts({
           nodeAccount: nodePDA,
           operator: newOperator.publicKey,
@@ -505,6 +603,341 @@ describe("node-registry", () => {
     });
   });
 
+  describe("Routing score", () => {
+    let routingOperator: anchor.web3.Keypair;
+    let routingNodePDA: anchor.web3.PublicKey;
+
+    // 50% default reputation x (100 AEGIS / 10,000 AEGIS saturation), fresh heartbeat
+    const INITIAL_ROUTING_SCORE = 5000;
+
+    before(async () => {
+      routingOperator = anchor.web3.Keypair.generate();
+      await fundAccount(routingOperator.publicKey);
+      [routingNodePDA] = getNodePDA(routingOperator.publicKey);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: routingNodePDA,
+          operator: routingOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([routingOperator])
+        .rpc();
+    });
+
+    it("Scores a node at registration", async () => {
+      const nodeAccount = await program.account.nodeAccount.fetch(routingNodePDA);
+      expect(nodeAccount.routingScore).to.equal(INITIAL_ROUTING_SCORE);
+    });
+
+    it("Refreshes the score on heartbeat", async () => {
+      await program.methods
+        .heartbeat(new anchor.BN(await clusterTime()), null)
+        .accounts({
+          nodeAccount: routingNodePDA,
+          operator: routingOperator.publicKey,
+        })
+        .signers([routingOperator])
+        .rpc();
+
+      // On-time heartbeat adds 0.10% reputation (5000 -> 5010)
+      const nodeAccount = await program.account.nodeAccount.fetch(routingNodePDA);
+      expect(nodeAccount.reputationScore.toNumber()).to.equal(5010);
+      expect(nodeAccount.routingScore).to.equal(5010);
+    });
+
+    it("Drops the score to zero on deactivation", async () => {
+      await program.methods
+        .deactivateNode()
+        .accounts({
+          nodeAccount: routingNodePDA,
+          operator: routingOperator.publicKey,
+        })
+        .signers([routingOperator])
+        .rpc();
+
+      const nodeAccount = await program.account.nodeAccount.fetch(routingNodePDA);
+      expect(nodeAccount.routingScore).to.equal(0);
+    });
+
+    it("Restores the score on reactivation", async () => {
+      await program.methods
+        .reactivateNode()
+        .accounts({
+          nodeAccount: routingNodePDA,
+          operator: routingOperator.publicKey,
+        })
+        .signers([routingOperator])
+        .rpc();
+
+      // First deactivation in the window: no haircut, heartbeat still fresh
+      const nodeAccount = await program.account.nodeAccount.fetch(routingNodePDA);
+      expect(nodeAccount.routingScore).to.equal(5010);
+    });
+
+    // update_stake (staking CPI) and update_reputation (admin / rewards program)
+    // refresh the score through the same routing_score fn; see the unit tests
+  });
+
+  describe("Heartbeat replay protection", () => {
+    let heartbeatOperator: anchor.web3.Keypair;
+    let heartbeatNodePDA: anchor.web3.PublicKey;
+
+    async function heartbeat(clientTimestamp: number, sequence: number | null) {
+      await program.methods
+        .heartbeat(new anchor.BN(clientTimestamp), sequence === null ? null : new anchor.BN(sequence))
+        .accounts({
+          nodeAccount: heartbeatNodePDA,
+          operator: heartbeatOperator.publicKey,
+        })
+        .signers([heartbeatOperator])
+        .rpc();
+    }
+
+    before(async () => {
+      heartbeatOperator = anchor.web3.Keypair.generate();
+      await fundAccount(heartbeatOperator.publicKey);
+      [heartbeatNodePDA] = getNodePDA(heartbeatOperator.publicKey);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: heartbeatNodePDA,
+          operator: heartbeatOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([heartbeatOperator])
+        .rpc();
+    });
+
+    it("Accepts heartbeats at a normal cadence", async () => {
+      const first = await clusterTime();
+      await heartbeat(first, 1);
+      await heartbeat(first + 1, 2);
+
+      const nodeAccount = await program.account.nodeAccount.fetch(heartbeatNodePDA);
+      expect(nodeAccount.lastClientTimestamp.toNumber()).to.equal(first + 1);
+      expect(nodeAccount.heartbeatSequence.toNumber()).to.equal(2);
+    });
+
+    it("Rejects a stale client timestamp", async () => {
+      try {
+        await heartbeat((await clusterTime()) - 600, 3);
+        expect.fail("Should have rejected a stale heartbeat");
+      } catch (error) {
+        expect(error.message).to.include("HeartbeatTimestampOutOfRange");
+      }
+    });
+
+    it("Rejects a replayed client timestamp", async () => {
+      const nodeAccount = await program.account.nodeAccount.fetch(heartbeatNodePDA);
+      try {
+        await heartbeat(nodeAccount.lastClientTimestamp.toNumber(), 3);
+        expect.fail("Should have rejected a replayed heartbeat");
+      } catch (error) {
+        expect(error.message).to.include("HeartbeatTimestampNotIncreasing");
+      }
+    });
+
+    it("Rejects a non-monotonic sequence", async () => {
+      const nodeAccount = await program.account.nodeAccount.fetch(heartbeatNodePDA);
+      try {
+        await heartbeat(nodeAccount.lastClientTimestamp.toNumber() + 5, 2);
+        expect.fail("Should have rejected a repeated sequence");
+      } catch (error) {
+        expect(error.message).to.include("HeartbeatSequenceNotIncreasing");
+      }
+    });
+
+    // A gap needs several heartbeat intervals to elapse; the recorded bounds
+    // and the 32-entry wraparound are covered by the program unit tests
+    it("Records no gap history for on-time heartbeats", async () => {
+      const nodeAccount = await program.account.nodeAccount.fetch(heartbeatNodePDA);
+      expect(nodeAccount.heartbeatGapCount.toNumber()).to.equal(0);
+      expect(nodeAccount.heartbeatGaps).to.have.length(32);
+      expect(nodeAccount.heartbeatGaps.every((gap) => gap.missedIntervals.toNumber() === 0)).to.be.true;
+    });
+  });
+
+  describe("Maintenance windows", () => {
+    let maintenanceOperator: anchor.web3.Keypair;
+    let maintenanceNodePDA: anchor.web3.PublicKey;
+
+    const HOUR = 60 * 60;
+    const DAY = 24 * HOUR;
+
+    async function now(): Promise<number> {
+      const slot = await provider.connection.getSlot();
+      return (await provider.connection.getBlockTime(slot)) as number;
+    }
+
+    function schedule(start: number, end: number) {
+      return program.methods
+        .scheduleMaintenance(new anchor.BN(start), new anchor.BN(end))
+        .accounts({
+          nodeAccount: maintenanceNodePDA,
+          operator: maintenanceOperator.publicKey,
+        })
+        .signers([maintenanceOperator])
+        .rpc();
+    }
+
+    before(async () => {
+      maintenanceOperator = anchor.web3.Keypair.generate();
+      await fundAccount(maintenanceOperator.publicKey);
+      [maintenanceNodePDA] = getNodePDA(maintenanceOperator.publicKey);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: maintenanceNodePDA,
+          operator: maintenanceOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([maintenanceOperator])
+        .rpc();
+    });
+
+    // Penalty suppression inside / outside a window depends on elapsed time
+    // and is covered by the program unit tests
+    it("Stores an announced window and emits MaintenanceScheduledEvent", async () => {
+      let received: any = null;
+      const listener = program.addEventListener("maintenanceScheduledEvent", (event) => {
+        received = event;
+      });
+
+      const start = (await now()) + 2 * HOUR;
+      await schedule(start, start + 4 * HOUR);
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const nodeAccount = await program.account.nodeAccount.fetch(maintenanceNodePDA);
+      expect(nodeAccount.maintenanceWindows[1].start.toNumber()).to.equal(start);
+      expect(nodeAccount.maintenanceWindows[1].end.toNumber()).to.equal(start + 4 * HOUR);
+
+      expect(received).to.not.be.null;
+      expect(received.start.toNumber()).to.equal(start);
+      expect(received.end.toNumber()).to.equal(start + 4 * HOUR);
+    });
+
+    it("Rejects retroactive scheduling", async () => {
+      const current = await now();
+      try {
+        await schedule(current - HOUR, current + HOUR);
+        expect.fail("Should have rejected a window that already started");
+      } catch (error) {
+        expect(error.message).to.include("MaintenanceNoticeTooShort");
+      }
+    });
+
+    it("Rejects a third window within 30 days", async () => {
+      const start = (await now()) + DAY;
+      await schedule(start, start + HOUR);
+
+      try {
+        await schedule(start + DAY, start + DAY + HOUR);
+        expect.fail("Should have rejected a third window");
+      } catch (error) {
+        expect(error.message).to.include("MaintenanceQuotaExceeded");
+      }
+    });
+  });
+
+  describe("Node snapshots", () => {
+    let snapshotOperator: anchor.web3.Keypair;
+    let snapshotNodePDA: anchor.web3.PublicKey;
+
+    before(async () => {
+      snapshotOperator = anchor.web3.Keypair.generate();
+      await fundAccount(snapshotOperator.publicKey);
+      [snapshotNodePDA] = getNodePDA(snapshotOperator.publicKey);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: snapshotNodePDA,
+          operator: snapshotOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([snapshotOperator])
+        .rpc();
+    });
+
+    // Uptime ratio, offline detection and Slashed status for longer
+    // heartbeat histories are covered by the program unit tests
+    it("Emits a NodeSnapshotEvent from a simulation without mutating the node", async () => {
+      const before = await program.account.nodeAccount.fetch(snapshotNodePDA);
+
+      const simulation = await program.methods
+        .emitNodeSnapshot(snapshotOperator.publicKey)
+        .accounts({ nodeAccount: snapshotNodePDA })
+        .simulate();
+
+      const event = simulation.events.find((e) => e.name === "nodeSnapshotEvent");
+      expect(event).to.not.be.undefined;
+      const snapshot = event.data as any;
+      expect(snapshot.operator.toString()).to.equal(snapshotOperator.publicKey.toString());
+      expect(snapshot.metadataUrl).to.equal(VALID_IPFS_CID);
+      expect(snapshot.status).to.deep.equal({ active: {} });
+      expect(snapshot.stakeAmount.toString()).to.equal(MIN_STAKE.toString());
+      // A fresh node has one heartbeat (at registration) and none missed
+      expect(snapshot.totalHeartbeats.toNumber()).to.equal(1);
+      expect(snapshot.uptimeRatioBps.toNumber()).to.equal(10_000);
+      expect(snapshot.isOffline).to.equal(false);
+      expect(snapshot.routingScore).to.equal(before.routingScore);
+
+      const after = await program.account.nodeAccount.fetch(snapshotNodePDA);
+      expect(after.updatedAt.toString()).to.equal(before.updatedAt.toString());
+      expect(after.lastHeartbeat.toString()).to.equal(before.lastHeartbeat.toString());
+    });
+
+    it("Rejects a snapshot request for another operator's node", async () => {
+      try {
+        await program.methods
+          .emitNodeSnapshot(anchor.web3.Keypair.generate().publicKey)
+          .accounts({ nodeAccount: snapshotNodePDA })
+          .simulate();
+        expect.fail("Should have rejected a mismatched operator");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    it("Emits an UptimeReportEvent with the heartbeat-derived uptime", async () => {
+      const node = await program.account.nodeAccount.fetch(snapshotNodePDA);
+      const windowStart = new anchor.BN(Math.floor(Date.now() / 1000) - 86_400);
+
+      const simulation = await program.methods
+        .emitUptimeReport(snapshotOperator.publicKey, windowStart)
+        .accounts({ nodeAccount: snapshotNodePDA })
+        .simulate();
+
+      const event = simulation.events.find((e) => e.name === "uptimeReportEvent");
+      expect(event).to.not.be.undefined;
+      const report = event.data as any;
+      expect(report.operator.toString()).to.equal(snapshotOperator.publicKey.toString());
+      expect(report.windowStart.toString()).to.equal(windowStart.toString());
+      expect(report.totalHeartbeats.toString()).to.equal(node.totalHeartbeats.toString());
+      expect(report.missedHeartbeats.toString()).to.equal(node.missedHeartbeats.toString());
+      expect(report.uptimeBps).to.equal(node.uptimeBps);
+      expect(report.uptimeBps).to.equal(10_000);
+    });
+
+    it("Rejects an uptime report window starting in the future", async () => {
+      try {
+        await program.methods
+          .emitUptimeReport(snapshotOperator.publicKey, new anchor.BN(Math.floor(Date.now() / 1000) + 86_400))
+          .accounts({ nodeAccount: snapshotNodePDA })
+          .simulate();
+        expect.fail("Should have rejected a future window");
+      } catch (error) {
+        expect(error.message).to.include("InvalidUptimeWindow");
+      }
+    });
+  });
+
   describe("PDA Derivation", () => {
     it("Derives correct PDA for each operator", async () => {
       const operator1 = anchor.web3.Keypair.generate();
@@ -520,5 +953,113 @@ describe("node-registry", () => {
       const [pda1Again] = getNodePDA(operator1.publicKey);
       expect(pda1.toString()).to.equal(pda1Again.toString());
     });
+
+    it("Derives distinct PDAs for each node of one operator", async () => {
+      const op = anchor.web3.Keypair.generate();
+
+      const [node0] = getNodePDA(op.publicKey, 0);
+      const [node1] = getNodePDA(op.publicKey, 1);
+
+      expect(node0.toString()).to.not.equal(node1.toString());
+    });
+  });
+
+  describe("Multiple nodes per operator", () => {
+    let multiOperator: anchor.web3.Keypair;
+    let node0PDA: anchor.web3.PublicKey;
+    let node1PDA: anchor.web3.PublicKey;
+
+    before(async () => {
+      multiOperator = anchor.web3.Keypair.generate();
+      await fundAccount(multiOperator.publicKey);
+      [node0PDA] = getNodePDA(multiOperator.publicKey, 0);
+      [node1PDA] = getNodePDA(multiOperator.publicKey, 1);
+    });
+
+    it("Registers two nodes under one operator", async () => {
+      for (const nodePDA of [node0PDA, node1PDA]) {
+        await program.methods
+          .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+          .accounts({
+            nodeAccount: nodePDA,
+            operator: multiOperator.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([multiOperator])
+          .rpc();
+      }
+
+      const node0 = await program.account.nodeAccount.fetch(node0PDA);
+      const node1 = await program.account.nodeAccount.fetch(node1PDA);
+      expect(node0.nodeId).to.equal(0);
+      expect(node1.nodeId).to.equal(1);
+      expect(node0.operator.toString()).to.equal(multiOperator.publicKey.toString());
+      expect(node1.operator.toString()).to.equal(multiOperator.publicKey.toString());
+
+      const [operatorNodesPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("operator_nodes"), multiOperator.publicKey.toBuffer()],
+        program.programId
+      );
+      const operatorNodes = await program.account.operatorNodes.fetch(operatorNodesPDA);
+      expect(operatorNodes.nodeCount).to.equal(2);
+    });
+
+    it("Updates both nodes with a batched heartbeat", async () => {
+      const before0 = await program.account.nodeAccount.fetch(node0PDA);
+      const before1 = await program.account.nodeAccount.fetch(node1PDA);
+
+      await program.methods
+        .heartbeatMany(new anchor.BN(await clusterTime()), null)
+        .accounts({ operator: multiOperator.publicKey })
+        .remainingAccounts([
+          { pubkey: node0PDA, isWritable: true, isSigner: false },
+          { pubkey: node1PDA, isWritable: true, isSigner: false },
+        ])
+        .signers([multiOperator])
+        .rpc();
+
+      const after0 = await program.account.nodeAccount.fetch(node0PDA);
+      const after1 = await program.account.nodeAccount.fetch(node1PDA);
+      expect(after0.totalHeartbeats.toNumber()).to.equal(before0.totalHeartbeats.toNumber() + 1);
+      expect(after1.totalHeartbeats.toNumber()).to.equal(before1.totalHeartbeats.toNumber() + 1);
+    });
+
+    it("Rejects a batch containing another operator's node", async () => {
+      const otherOperator = anchor.web3.Keypair.generate();
+      await fundAccount(otherOperator.publicKey);
+      const [foreignPDA] = getNodePDA(otherOperator.publicKey, 0);
+
+      await program.methods
+        .registerNode(VALID_IPFS_CID, MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: foreignPDA,
+          operator: otherOperator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([otherOperator])
+        .rpc();
+
+      const before0 = await program.account.nodeAccount.fetch(node0PDA);
+
+      try {
+        await program.methods
+          .heartbeatMany(new anchor.BN(await clusterTime()), null)
+          .accounts({ operator: multiOperator.publicKey })
+          .remainingAccounts([
+            { pubkey: node0PDA, isWritable: true, isSigner: false },
+            { pubkey: foreignPDA, isWritable: true, isSigner: false },
+          ])
+          .signers([multiOperator])
+          .rpc();
+
+        expect.fail("Should have rejected foreign node");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedOperator");
+      }
+
+      // The whole batch is rolled back
+      const after0 = await program.account.nodeAccount.fetch(node0PDA);
+      expect(after0.totalHeartbeats.toNumber()).to.equal(before0.totalHeartbeats.toNumber());
+    });
   });
 });
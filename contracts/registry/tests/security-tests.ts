@@ -16,6 +16,7 @@ describe("Registry Security Tests", () => {
   let registryConfigPda: PublicKey;
   let nodeAccountPda: PublicKey;
   const stakingProgramId = new PublicKey("5oGLkNZ7Hku3bRD4aWnRNo8PsXusXmojm8EzAiQUVD1H");
+  const NO_CONTACT = Array(32).fill(0); // registerNode contact_hash when none is published
 
   before(async () => {
     // Create test keypairs
@@ -46,7 +47,7 @@ describe("Registry Security Tests", () => {
     );
 
     [nodeAccountPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("node"), operator.publicKey.toBuffer()],
+      [Buffer.from("node"), operator.publicKey.toBuffer(), Buffer.from([0])],
       program.programId
     );
   });
@@ -84,7 +85,9 @@ describe("Registry Security Tests", () => {
       await program.methods
         .registerNode(
           "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
-          new anchor.BN(100_000_000_000)
+          new anchor.BN(100_000_000_000),
+          NO_CONTACT,
+          0
         )
         .accounts({
           nodeAccount: nodeAccountPda,
@@ -174,7 +177,7 @@ describe("Registry Security Tests", () => {
 
       try {
         await program.methods
-          .updateRegistryConfig(null, maliciousProgramId, null)
+          .updateRegistryConfig(null, maliciousProgramId, null, null, null, null)
           .accounts({
             registryConfig: registryConfigPda,
             admin: attacker.publicKey,
@@ -194,7 +197,7 @@ describe("Registry Security Tests", () => {
       const newStakingProgram = Keypair.generate().publicKey;
 
       await program.methods
-        .updateRegistryConfig(null, newStakingProgram, null)
+        .updateRegistryConfig(null, newStakingProgram, null, null, null, null)
         .accounts({
           registryConfig: registryConfigPda,
           admin: admin.publicKey,
@@ -238,7 +241,7 @@ describe("Registry Security Tests", () => {
 
       try {
         await program.methods
-          .updateRegistryConfig(null, null, new anchor.BN(0))
+          .updateRegistryConfig(null, null, new anchor.BN(0), null, null, null)
           .accounts({
             registryConfig: registryConfigPda,
             admin: attacker.publicKey,
@@ -258,7 +261,7 @@ describe("Registry Security Tests", () => {
       const newMinStake = new anchor.BN(50_000_000_000); // 50 AEGIS
 
       await program.methods
-        .updateRegistryConfig(null, null, newMinStake)
+        .updateRegistryConfig(null, null, newMinStake, null, null, null)
         .accounts({
           registryConfig: registryConfigPda,
           admin: admin.publicKey,
@@ -272,4 +275,287 @@ describe("Registry Security Tests", () => {
       expect(config.minStakeForRegistration.toNumber()).to.equal(50_000_000_000);
     });
   });
+
+  describe("Zone assignments", () => {
+    const zoneHash = Array.from(Buffer.alloc(32, 7));
+    let zoneAssigner: Keypair;
+    let zoneAssignmentPda: PublicKey;
+
+    function assignZone(signer: Keypair) {
+      return program.methods
+        .assignZone(zoneHash)
+        .accounts({
+          registryConfig: registryConfigPda,
+          nodeAccount: nodeAccountPda,
+          zoneAssignment: zoneAssignmentPda,
+          assigner: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    function revokeZone(signer: Keypair) {
+      return program.methods
+        .revokeZone()
+        .accounts({
+          registryConfig: registryConfigPda,
+          zoneAssignment: zoneAssignmentPda,
+          nodeAccount: nodeAccountPda,
+          assigner: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      zoneAssigner = Keypair.generate();
+      await provider.connection.requestAirdrop(zoneAssigner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      [zoneAssignmentPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("zone"), Buffer.from(zoneHash), operator.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("❌ Prevents non-admin from assigning zones", async () => {
+      try {
+        await assignZone(attacker);
+        expect.fail("Attacker should NOT be able to assign zones");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedZoneAssigner");
+      }
+    });
+
+    it("✅ Allows admin to assign a zone to a node", async () => {
+      await assignZone(admin);
+
+      const assignment = await program.account.zoneAssignment.fetch(zoneAssignmentPda);
+      expect(assignment.active).to.be.true;
+      expect(assignment.operator.toString()).to.equal(operator.publicKey.toString());
+      expect(assignment.assignedBy.toString()).to.equal(admin.publicKey.toString());
+
+      const node = await program.account.nodeAccount.fetch(nodeAccountPda);
+      expect(node.zoneAssignments).to.equal(1);
+    });
+
+    it("❌ Rejects a duplicate assignment", async () => {
+      try {
+        await assignZone(admin);
+        expect.fail("Should have rejected a duplicate zone assignment");
+      } catch (error) {
+        expect(error.message).to.include("ZoneAlreadyAssigned");
+      }
+
+      const node = await program.account.nodeAccount.fetch(nodeAccountPda);
+      expect(node.zoneAssignments).to.equal(1);
+    });
+
+    it("✅ Allows the designated zone assigner to revoke", async () => {
+      await program.methods
+        .updateRegistryConfig(null, null, null, null, zoneAssigner.publicKey, null)
+        .accounts({
+          registryConfig: registryConfigPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      await revokeZone(zoneAssigner);
+
+      const assignment = await program.account.zoneAssignment.fetch(zoneAssignmentPda);
+      expect(assignment.active).to.be.false;
+      expect(assignment.revokedAt.toNumber()).to.be.greaterThan(0);
+
+      const node = await program.account.nodeAccount.fetch(nodeAccountPda);
+      expect(node.zoneAssignments).to.equal(0);
+
+      try {
+        await revokeZone(zoneAssigner);
+        expect.fail("Should have rejected revoking an inactive assignment");
+      } catch (error) {
+        expect(error.message).to.include("ZoneNotAssigned");
+      }
+    });
+  });
+
+  describe("Reactivation policy", () => {
+    // Deactivations older than the 90-day window can't be produced on a test
+    // validator; the window reset is covered by
+    // test_deactivation_count_resets_outside_window
+    async function registerFreshNode(): Promise<{ owner: Keypair; node: PublicKey }> {
+      const owner = Keypair.generate();
+      await provider.connection.requestAirdrop(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const [node] = PublicKey.findProgramAddressSync(
+        [Buffer.from("node"), owner.publicKey.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+      await program.methods
+        .registerNode("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG", new anchor.BN(100_000_000_000), NO_CONTACT, 0)
+        .accounts({
+          nodeAccount: node,
+          operator: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      return { owner, node };
+    }
+
+    function deactivate(owner: Keypair, node: PublicKey) {
+      return program.methods
+        .deactivateNode()
+        .accounts({ nodeAccount: node, operator: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    }
+
+    function reactivate(owner: Keypair, node: PublicKey) {
+      return program.methods
+        .reactivateNode()
+        .accounts({
+          registryConfig: registryConfigPda,
+          nodeAccount: node,
+          operator: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+    }
+
+    function setReputation(node: PublicKey, score: number) {
+      return program.methods
+        .updateReputation(new anchor.BN(score))
+        .accounts({
+          registryConfig: registryConfigPda,
+          nodeAccount: node,
+          authority: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+    }
+
+    it("✅ Defaults to a 2500 floor and a 10% haircut", async () => {
+      const config = await program.account.registryConfig.fetch(registryConfigPda);
+      expect(config.reactivationPolicy.minReputationForReactivation.toNumber()).to.equal(2500);
+      expect(config.reactivationPolicy.haircutBps).to.equal(1000);
+    });
+
+    it("❌ Rejects reactivation below the reputation floor", async () => {
+      const { owner, node } = await registerFreshNode();
+      await setReputation(node, 2499);
+      await deactivate(owner, node);
+
+      try {
+        await reactivate(owner, node);
+        expect.fail("Should have rejected reactivation below the floor");
+      } catch (error) {
+        expect(error.message).to.include("ReputationTooLow");
+      }
+
+      await setReputation(node, 2500);
+      await reactivate(owner, node);
+      const account = await program.account.nodeAccount.fetch(node);
+      expect(account.status).to.deep.equal({ active: {} });
+    });
+
+    it("✅ Takes the haircut on the third reactivation in the window", async () => {
+      const { owner, node } = await registerFreshNode();
+
+      for (let i = 0; i < 2; i++) {
+        await deactivate(owner, node);
+        await reactivate(owner, node);
+      }
+      let account = await program.account.nodeAccount.fetch(node);
+      expect(account.reputationScore.toNumber()).to.equal(5000);
+
+      let haircut: number | null = null;
+      let routingScore: number | null = null;
+      const listener = program.addEventListener("nodeReactivatedEvent", (event) => {
+        haircut = event.reputationHaircut.toNumber();
+        routingScore = event.routingScore;
+      });
+      await deactivate(owner, node);
+      await reactivate(owner, node);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      account = await program.account.nodeAccount.fetch(node);
+      expect(account.deactivationCount).to.equal(3);
+      expect(account.reputationScore.toNumber()).to.equal(4500);
+      expect(haircut).to.equal(500);
+      // Rescored after the haircut
+      expect(account.routingScore).to.be.greaterThan(0);
+      expect(routingScore).to.equal(account.routingScore);
+    });
+
+    it("❌ Only the admin can change the policy, within bounds", async () => {
+      const policy = { minReputationForReactivation: new anchor.BN(3000), haircutBps: 10_001 };
+      try {
+        await program.methods
+          .updateRegistryConfig(null, null, null, null, null, policy)
+          .accounts({ registryConfig: registryConfigPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc();
+        expect.fail("Should have rejected a haircut above 100%");
+      } catch (error) {
+        expect(error.message).to.include("InvalidReactivationPolicy");
+      }
+
+      try {
+        await program.methods
+          .updateRegistryConfig(null, null, null, null, null, { ...policy, haircutBps: 500 })
+          .accounts({ registryConfig: registryConfigPda, admin: attacker.publicKey })
+          .signers([attacker])
+          .rpc();
+        expect.fail("Attacker should NOT be able to change the policy");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Config migration", () => {
+    // The current program can't write a shorter config, so growing a legacy
+    // one and filling its defaults are covered by
+    // test_migrate_legacy_registry_config
+
+    it("✅ Leaves a current config untouched", async () => {
+      const before = await program.account.registryConfig.fetch(registryConfigPda);
+      await program.methods
+        .migrateRegistryConfig()
+        .accounts({
+          registryConfig: registryConfigPda,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const after = await program.account.registryConfig.fetch(registryConfigPda);
+      expect(after.routingParams.stakeSaturation.toString()).to.equal(
+        before.routingParams.stakeSaturation.toString()
+      );
+      expect(after.reactivationPolicy.haircutBps).to.equal(before.reactivationPolicy.haircutBps);
+    });
+
+    it("❌ Prevents non-admin from migrating the config", async () => {
+      try {
+        await program.methods
+          .migrateRegistryConfig()
+          .accounts({
+            registryConfig: registryConfigPda,
+            admin: attacker.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc();
+        expect.fail("Attacker should NOT be able to migrate the config");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedAdmin");
+      }
+    });
+  });
 });
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 declare_id!("8nr66XQcjr11HhMP9NU6d8j5iwX3yo59VDawQSmPWgnK");
 
@@ -8,6 +8,12 @@ const OPERATOR_REWARDS_SEED: &[u8] = b"operator_rewards";
 const ORACLE_REGISTRY_SEED: &[u8] = b"oracle_registry";
 /// Y2.3: Seed for nonce tracking account
 const NONCE_TRACKER_SEED: &[u8] = b"nonce_tracker";
+/// Seed for a partner's secondary incentive program (one per partner mint)
+const SECONDARY_INCENTIVE_SEED: &[u8] = b"secondary_incentive";
+/// Seed for a secondary incentive program's token vault
+const SECONDARY_VAULT_SEED: &[u8] = b"secondary_vault";
+/// Seed for an operator's balance in a secondary incentive program
+const SECONDARY_REWARDS_SEED: &[u8] = b"secondary_rewards";
 
 /// Minimum stake required (1000 AEGIS with 9 decimals)
 const MIN_STAKE: u64 = 1_000_000_000_000;
@@ -18,6 +24,9 @@ const MAX_STORED_NONCES: usize = 100;
 /// Y7.4: Rate limit for authority performance recording (minimum seconds between recordings)
 const AUTHORITY_RECORDING_COOLDOWN: i64 = 60; // 1 minute cooldown
 
+/// Maximum claim cooldown the authority can configure (30 days)
+const MAX_CLAIM_COOLDOWN_SECONDS: i64 = 30 * 24 * 60 * 60;
+
 /// Maximum stake multiplier (3x as per whitepaper)
 const MAX_STAKE_MULTIPLIER: u64 = 300; // 3.00x in basis points (100 = 1x)
 
@@ -29,6 +38,43 @@ const PRECISION: u128 = 1_000_000;
 const INITIAL_YEARLY_EMISSION: u64 = 100_000_000_000_000_000; // 100M with 9 decimals
 const EPOCHS_PER_YEAR: u64 = 365; // Daily epochs
 
+/// Length of one epoch in seconds (daily epochs)
+const EPOCH_DURATION_SECONDS: i64 = 24 * 60 * 60;
+
+/// Year index of the last halving; emission stays at this level afterwards
+const FINAL_HALVING_YEAR: u64 = 10;
+
+/// How long a reward dispute freezes the disputed rewards (72 hours)
+const REWARD_DISPUTE_WINDOW: i64 = 72 * 60 * 60;
+
+/// Maximum length of a dispute's reason CID
+const MAX_REASON_CID_LENGTH: usize = 64;
+
+/// Scale of the weighted performance score (10000 = 100%)
+const PERFORMANCE_SCALE: u16 = 10_000;
+
+/// Default weighted score below which an operator earns nothing
+const DEFAULT_PERFORMANCE_CLIFF: u16 = 5_000;
+
+/// Default weighted score from which rewards scale linearly again
+const DEFAULT_PERFORMANCE_KNEE: u16 = 8_000;
+
+/// Default epochs without an attestation before the performance score decays
+const DEFAULT_STALENESS_GRACE_EPOCHS: u16 = 3;
+
+/// Default epochs without an attestation at which the performance score reaches zero
+const DEFAULT_STALENESS_CUTOFF_EPOCHS: u16 = 10;
+
+/// Longest window a secondary incentive program may run (one year of epochs)
+const MAX_SECONDARY_INCENTIVE_EPOCHS: u64 = EPOCHS_PER_YEAR;
+
+/// Score semantics attestations must declare: uptime, latency and throughput
+/// are all 0-100 with higher = better (latency 100 is fastest, never raw ms)
+const METRIC_SEMANTICS_VERSION: u8 = 1;
+
+/// Highest (best) value of a performance score
+const MAX_SCORE: u8 = 100;
+
 #[program]
 pub mod rewards {
     use super::*;
@@ -47,10 +93,18 @@ pub mod rewards {
         pool.total_network_requests = 0;
         // Y7.4: Initialize rate limiting timestamp
         pool.last_authority_recording = 0;
+        // Claim policy starts disabled; see update_claim_policy
+        pool.min_claim_amount = 0;
+        pool.claim_cooldown_seconds = 0;
         pool.bump = ctx.bumps.reward_pool;
+        pool.performance_cliff = DEFAULT_PERFORMANCE_CLIFF;
+        pool.performance_knee = DEFAULT_PERFORMANCE_KNEE;
+        pool.epoch_started_at = Clock::get()?.unix_timestamp;
+        pool.staleness_grace_epochs = DEFAULT_STALENESS_GRACE_EPOCHS;
+        pool.staleness_cutoff_epochs = DEFAULT_STALENESS_CUTOFF_EPOCHS;
 
         // Calculate initial emission rate based on halving schedule
-        let base_emission = calculate_epoch_emission(0);
+        let base_emission = calculate_epoch_emission(0).emission;
         msg!("Rewards pool initialized. Year 1 daily emission: {}", base_emission);
         Ok(())
     }
@@ -142,9 +196,14 @@ pub mod rewards {
     }
 
     /// Initialize operator rewards account with extended metrics
+    ///
+    /// The operator only earns emission from the pool's current epoch on, and
+    /// that first epoch is prorated by how much of it was left at registration.
     pub fn initialize_operator_rewards(
         ctx: Context<InitializeOperatorRewards>,
     ) -> Result<()> {
+        let pool = &ctx.accounts.reward_pool;
+        let now = Clock::get()?.unix_timestamp;
         let rewards = &mut ctx.accounts.operator_rewards;
         rewards.operator = ctx.accounts.operator.key();
         rewards.total_earned = 0;
@@ -158,9 +217,19 @@ pub mod rewards {
         rewards.requests_served = 0;         // For demand multiplier
         rewards.last_performance_epoch = 0;
         rewards.bump = ctx.bumps.operator_rewards;
+        rewards.frozen_rewards = 0;
+        rewards.dispute_expires_at = 0;
+        rewards.first_active_epoch = pool.current_epoch;
+        rewards.first_epoch_share_bps = remaining_epoch_share_bps(pool.epoch_started_at, now);
+        rewards.claim_delegate = Pubkey::default();
+        rewards.registered_payout_account = Pubkey::default();
+        rewards.flagged_attestation = PerformanceAttestation::default();
+        rewards.flagged_anomaly = MetricAnomaly::None;
 
         emit!(OperatorRewardsInitializedEvent {
             operator: rewards.operator,
+            first_active_epoch: rewards.first_active_epoch,
+            first_epoch_share_bps: rewards.first_epoch_share_bps,
         });
 
         Ok(())
@@ -172,8 +241,13 @@ pub mod rewards {
     /// Y2.1: Added epoch validation - epoch must be greater than last_performance_epoch
     /// Y2.2: Added nonce to signature message to prevent replay attacks
     /// Y2.3: Nonces are tracked per operator to prevent reuse
+    ///
+    /// Implausible attestations (see PerformanceAttestation::anomaly) are not
+    /// recorded: they're held on the operator's account and reported in a
+    /// MetricAnomalyEvent until the authority accepts them.
     pub fn record_performance(
         ctx: Context<RecordPerformance>,
+        metric_semantics_version: u8, // Must be METRIC_SEMANTICS_VERSION
         uptime_percentage: u8,   // 0-100, weight: 0.5
         latency_score: u8,       // 0-100, weight: 0.3 (higher = better, lower latency)
         throughput_score: u8,    // 0-100, weight: 0.2
@@ -183,10 +257,15 @@ pub mod rewards {
         oracle_pubkey: [u8; 32], // Ed25519 public key of the signing oracle
         _signature: [u8; 64],    // Ed25519 signature (verified via Ed25519 program instruction)
     ) -> Result<()> {
-        // Validate percentage ranges
-        require!(uptime_percentage <= 100, RewardsError::InvalidPercentage);
-        require!(latency_score <= 100, RewardsError::InvalidPercentage);
-        require!(throughput_score <= 100, RewardsError::InvalidPercentage);
+        let attestation = PerformanceAttestation {
+            metric_semantics_version,
+            uptime_percentage,
+            latency_score,
+            throughput_score,
+            requests_served,
+            epoch,
+        };
+        attestation.validate()?;
 
         // Y2.1: Validate epoch is strictly increasing (prevents replay of old epochs)
         let rewards = &ctx.accounts.operator_rewards;
@@ -208,13 +287,14 @@ pub mod rewards {
         require!(oracle_active, RewardsError::InvalidOracle);
 
         // Verify Ed25519 signature over attestation data
-        // Y2.2: Message format now includes nonce: operator || epoch || nonce || uptime || latency || throughput || requests
+        // Y2.2: Message format now includes nonce:
+        // operator || epoch || nonce || semantics version || uptime || latency || throughput || requests
         let operator = ctx.accounts.operator_rewards.operator;
-        let mut message = Vec::with_capacity(32 + 8 + 8 + 1 + 1 + 1 + 8);
+        let mut message = Vec::with_capacity(32 + 8 + 8 + 1 + 1 + 1 + 1 + 8);
         message.extend_from_slice(operator.as_ref());
         message.extend_from_slice(&epoch.to_le_bytes());
         message.extend_from_slice(&nonce.to_le_bytes());  // Y2.2: Include nonce in signed message
-        message.extend_from_slice(&[uptime_percentage, latency_score, throughput_score]);
+        message.extend_from_slice(&[metric_semantics_version, uptime_percentage, latency_score, throughput_score]);
         message.extend_from_slice(&requests_served.to_le_bytes());
 
         // Verify Ed25519 signature using instruction introspection
@@ -244,32 +324,12 @@ pub mod rewards {
         nonce_tracker.last_nonce = nonce;
 
         // Update operator rewards with verified metrics
-        let rewards = &mut ctx.accounts.operator_rewards;
-        rewards.uptime_percentage = uptime_percentage;
-        rewards.latency_score = latency_score;
-        rewards.throughput_score = throughput_score;
-        rewards.requests_served = rewards.requests_served
-            .checked_add(requests_served)
-            .ok_or(RewardsError::Overflow)?;
-        rewards.last_performance_epoch = epoch;
-
-        // Update total network requests for demand multiplier
-        let pool = &mut ctx.accounts.reward_pool;
-        pool.total_network_requests = pool.total_network_requests
-            .checked_add(requests_served)
-            .ok_or(RewardsError::Overflow)?;
-
-        emit!(PerformanceRecordedEvent {
-            operator: rewards.operator,
-            uptime: uptime_percentage,
-            latency: latency_score,
-            throughput: throughput_score,
-            requests: requests_served,
-            epoch,
+        record_attestation(
+            &mut ctx.accounts.operator_rewards,
+            &mut ctx.accounts.reward_pool,
+            attestation,
             nonce,  // Y2.2: Include nonce in event
-        });
-
-        Ok(())
+        )
     }
 
     /// Simplified record_performance for backward compatibility (authority-only, no signature)
@@ -278,6 +338,7 @@ pub mod rewards {
     /// Y7.5: Added audit event emission for authority actions
     pub fn record_performance_authority(
         ctx: Context<RecordPerformanceAuthority>,
+        metric_semantics_version: u8,
         uptime_percentage: u8,
         latency_score: u8,
         throughput_score: u8,
@@ -286,9 +347,15 @@ pub mod rewards {
     ) -> Result<()> {
         let clock = Clock::get()?;
 
-        require!(uptime_percentage <= 100, RewardsError::InvalidPercentage);
-        require!(latency_score <= 100, RewardsError::InvalidPercentage);
-        require!(throughput_score <= 100, RewardsError::InvalidPercentage);
+        let attestation = PerformanceAttestation {
+            metric_semantics_version,
+            uptime_percentage,
+            latency_score,
+            throughput_score,
+            requests_served,
+            epoch,
+        };
+        attestation.validate()?;
 
         // Y2.1: Validate epoch is strictly increasing
         let rewards = &ctx.accounts.operator_rewards;
@@ -315,33 +382,36 @@ pub mod rewards {
             timestamp: clock.unix_timestamp,
         });
 
-        let rewards = &mut ctx.accounts.operator_rewards;
-        rewards.uptime_percentage = uptime_percentage;
-        rewards.latency_score = latency_score;
-        rewards.throughput_score = throughput_score;
-        rewards.requests_served = rewards.requests_served
-            .checked_add(requests_served)
-            .ok_or(RewardsError::Overflow)?;
-        rewards.last_performance_epoch = epoch;
-
-        let pool = &mut ctx.accounts.reward_pool;
-        pool.total_network_requests = pool.total_network_requests
-            .checked_add(requests_served)
-            .ok_or(RewardsError::Overflow)?;
         // Y7.4: Update last recording timestamp
-        pool.last_authority_recording = clock.unix_timestamp;
+        ctx.accounts.reward_pool.last_authority_recording = clock.unix_timestamp;
+
+        record_attestation(
+            &mut ctx.accounts.operator_rewards,
+            &mut ctx.accounts.reward_pool,
+            attestation,
+            0,  // Authority-based recording doesn't use nonces
+        )
+    }
+
+    /// Record the attestation held back by the plausibility checks
+    ///
+    /// For metrics the authority has confirmed out of band (e.g. an operator
+    /// that really served no requests at full throughput capacity). The held
+    /// epoch must still be newer than the last recorded one.
+    pub fn accept_flagged_attestation(ctx: Context<AcceptFlaggedAttestation>) -> Result<()> {
+        let clock = Clock::get()?;
+        let rewards = &mut ctx.accounts.operator_rewards;
+        let attestation = rewards.accept_flagged()?;
 
-        emit!(PerformanceRecordedEvent {
+        emit!(AuthorityActionAuditEvent {
+            action_type: AuthorityActionType::AcceptFlaggedAttestation,
+            authority: ctx.accounts.authority.key(),
             operator: rewards.operator,
-            uptime: uptime_percentage,
-            latency: latency_score,
-            throughput: throughput_score,
-            requests: requests_served,
-            epoch,
-            nonce: 0,  // Authority-based recording doesn't use nonces
+            epoch: attestation.epoch,
+            timestamp: clock.unix_timestamp,
         });
 
-        Ok(())
+        credit_attestation(&mut ctx.accounts.reward_pool, rewards.operator, &attestation, 0)
     }
 
     /// Y2.3: Initialize nonce tracker for an operator
@@ -364,8 +434,14 @@ pub mod rewards {
     ///
     /// Where:
     /// - stake_multiplier = min(3.0, sqrt(stake_amount / MIN_STAKE))
-    /// - performance_score = (uptime × 0.5) + (latency_score × 0.3) + (throughput_score × 0.2)
+    /// - performance_score = (uptime × 0.5) + (latency_score × 0.3) + (throughput_score × 0.2),
+    ///   shaped by the pool's performance cliff and knee (see effective_performance)
+    ///   and decayed when attestations have stopped arriving (see decay_stale_performance)
     /// - demand_multiplier = operator_requests / total_network_requests
+    ///
+    /// With a secondary incentive program and the operator's balance in it,
+    /// the partner token is also allocated for the epochs overlapping the
+    /// program (see SecondaryIncentive::allocation); primary rewards are unaffected.
     pub fn calculate_rewards(
         ctx: Context<CalculateRewards>,
         staked_amount: u64,
@@ -409,6 +485,19 @@ pub mod rewards {
             )
             .ok_or(RewardsError::Overflow)?;
         // weighted_performance is now 0-10000 (100 * 100 max)
+        let staleness_epochs = pool
+            .current_epoch
+            .saturating_sub(rewards.last_performance_epoch);
+        let performance = decay_stale_performance(
+            effective_performance(
+                weighted_performance,
+                pool.performance_cliff as u64,
+                pool.performance_knee as u64,
+            ),
+            staleness_epochs,
+            pool.staleness_grace_epochs as u64,
+            pool.staleness_cutoff_epochs as u64,
+        );
 
         // 3. Calculate demand multiplier: operator_requests / total_network_requests
         // Scale by PRECISION to maintain accuracy
@@ -422,15 +511,9 @@ pub mod rewards {
             PRECISION // Default to 1.0 if no network activity yet
         };
 
-        // 4. Calculate base emission for the epoch range (with halving schedule)
-        let current_epoch = pool.current_epoch;
-        let mut total_emission: u128 = 0;
-        for epoch_offset in 0..epochs_elapsed {
-            let epoch = current_epoch.saturating_add(epoch_offset);
-            total_emission = total_emission
-                .checked_add(calculate_epoch_emission(epoch.saturating_sub(pool.start_epoch)) as u128)
-                .ok_or(RewardsError::Overflow)?;
-        }
+        // 4. Calculate base emission for the epoch range (with halving schedule),
+        // skipping epochs before the operator joined
+        let total_emission = rewards.range_emission(pool.current_epoch, epochs_elapsed, pool.start_epoch)?;
 
         // 5. Final reward calculation:
         // reward = base_emission × (stake_multiplier / PRECISION) × (performance / 10000) × (demand / PRECISION)
@@ -439,7 +522,7 @@ pub mod rewards {
             .ok_or(RewardsError::Overflow)?
             .checked_div(PRECISION)
             .ok_or(RewardsError::Underflow)?
-            .checked_mul(weighted_performance as u128)
+            .checked_mul(performance as u128)
             .ok_or(RewardsError::Overflow)?
             .checked_div(10000) // Normalize performance (0-10000 -> 0-1)
             .ok_or(RewardsError::Underflow)?
@@ -465,6 +548,45 @@ pub mod rewards {
             .checked_add(final_reward)
             .ok_or(RewardsError::Overflow)?;
 
+        if let Some(incentive) = ctx.accounts.secondary_incentive.as_mut() {
+            let secondary = ctx
+                .accounts
+                .secondary_rewards
+                .as_mut()
+                .ok_or(RewardsError::SecondaryRewardsRequired)?;
+            require_keys_eq!(
+                secondary.incentive,
+                incentive.key(),
+                RewardsError::SecondaryIncentiveMismatch
+            );
+
+            let (overlap_start, overlap_epochs) = incentive.overlap(pool.current_epoch, epochs_elapsed);
+            let overlap_emission = rewards.range_emission(overlap_start, overlap_epochs, pool.start_epoch)?;
+            let window_emission = emission_between(incentive.start_epoch, incentive.end_epoch, pool.start_epoch)?;
+            let amount = incentive.allocation(final_reward, total_emission, overlap_emission, window_emission)?;
+
+            incentive.allocated = incentive
+                .allocated
+                .checked_add(amount)
+                .ok_or(RewardsError::Overflow)?;
+            secondary.unclaimed_rewards = secondary
+                .unclaimed_rewards
+                .checked_add(amount)
+                .ok_or(RewardsError::Overflow)?;
+            secondary.total_earned = secondary
+                .total_earned
+                .checked_add(amount)
+                .ok_or(RewardsError::Overflow)?;
+
+            emit!(SecondaryRewardsAllocatedEvent {
+                incentive: incentive.key(),
+                operator: rewards.operator,
+                primary_reward: final_reward,
+                amount,
+                overlap_epochs,
+            });
+        }
+
         emit!(RewardsCalculatedEvent {
             operator: rewards.operator,
             amount: final_reward,
@@ -472,6 +594,8 @@ pub mod rewards {
             stake_multiplier: stake_multiplier as u64,
             performance_score: weighted_performance,
             demand_multiplier: demand_multiplier as u64,
+            effective_performance: performance,
+            staleness_epochs,
         });
 
         Ok(())
@@ -487,27 +611,101 @@ pub mod rewards {
 
         // Reset request counters for new epoch
         pool.total_network_requests = 0;
+        pool.epoch_started_at = Clock::get()?.unix_timestamp;
 
         emit!(EpochAdvancedEvent {
             old_epoch,
             new_epoch: pool.current_epoch,
         });
 
+        // Announce the new emission rate at each halving-year boundary
+        let epochs_since_start = pool.current_epoch.saturating_sub(pool.start_epoch);
+        if epochs_since_start % EPOCHS_PER_YEAR == 0 {
+            emit!(EmissionScheduleEvent::for_epoch(pool.current_epoch, pool.start_epoch));
+        }
+
+        Ok(())
+    }
+
+    /// Emit the emission schedule for the current epoch (read-only, anyone can call)
+    /// Lets dashboards read the current emission and next halving without
+    /// re-implementing the schedule off-chain.
+    pub fn emit_emission_info(ctx: Context<EmitEmissionInfo>) -> Result<()> {
+        let pool = &ctx.accounts.reward_pool;
+        emit!(EmissionScheduleEvent::for_epoch(pool.current_epoch, pool.start_epoch));
         Ok(())
     }
 
     /// Claim pending rewards
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    ///
+    /// Claims are subject to the pool's minimum claim amount and cooldown.
+    /// With `close_account`, the operator's entire remaining balance is paid
+    /// out regardless of the minimum and the rewards account is closed.
+    /// Rewards frozen by an open dispute stay behind, and the account can't
+    /// be closed until the dispute is resolved or its window lapses.
+    ///
+    /// Signed by the operator, or by their claim delegate; a delegate can only
+    /// pay out to the operator's registered payout account and can't close.
+    ///
+    /// If the vault holds less than the claimable amount (funding lag), the
+    /// claim fails with `InsufficientVaultBalance` unless `allow_partial` is
+    /// set, in which case the vault balance is paid out and the rest stays
+    /// unclaimed for a later claim. Closing always requires a full payout.
+    pub fn claim_rewards(
+        ctx: Context<ClaimRewards>,
+        close_account: bool,
+        allow_partial: bool,
+    ) -> Result<()> {
         let clock = Clock::get()?;
+        let pool = &ctx.accounts.reward_pool;
         let rewards = &mut ctx.accounts.operator_rewards;
+        let claimant = ctx.accounts.claimant.key();
+
+        rewards.authorize_claim(
+            claimant,
+            ctx.accounts.operator_token_account.key(),
+            close_account,
+        )?;
+
+        if rewards.frozen_at(clock.unix_timestamp) == 0 {
+            // Clear a lapsed dispute
+            rewards.frozen_rewards = 0;
+            rewards.dispute_expires_at = 0;
+        } else {
+            require!(!close_account, RewardsError::RewardsDisputed);
+        }
+
+        let amount = rewards.claimable(clock.unix_timestamp);
+
+        // Closing with an empty balance just closes the account
+        if !close_account {
+            require!(amount > 0, RewardsError::NoRewardsToClaim);
+            require!(amount >= pool.min_claim_amount, RewardsError::ClaimTooSmall);
+        }
 
+        if amount > 0 && pool.claim_cooldown_seconds > 0 && rewards.last_claim_time > 0 {
+            let next_claim_time = rewards
+                .last_claim_time
+                .checked_add(pool.claim_cooldown_seconds)
+                .ok_or(RewardsError::Overflow)?;
+            require!(
+                clock.unix_timestamp >= next_claim_time,
+                RewardsError::ClaimCooldownActive
+            );
+        }
+
+        if amount == 0 {
+            msg!("Closing rewards account for operator: {}", rewards.operator);
+            return ctx.accounts.operator_rewards.close(ctx.accounts.operator.to_account_info());
+        }
+
+        let claimable = amount;
+        let amount = vault_payout(claimable, ctx.accounts.reward_vault.amount, allow_partial)?;
         require!(
-            rewards.unclaimed_rewards > 0,
-            RewardsError::NoRewardsToClaim
+            !close_account || amount == claimable,
+            RewardsError::InsufficientVaultBalance
         );
 
-        let amount = rewards.unclaimed_rewards;
-
         // Transfer rewards from vault to operator
         let pool_seeds = &[
             REWARD_POOL_SEED,
@@ -525,7 +723,10 @@ pub mod rewards {
         token::transfer(cpi_ctx, amount)?;
 
         // Update state
-        rewards.unclaimed_rewards = 0;
+        rewards.unclaimed_rewards = rewards
+            .unclaimed_rewards
+            .checked_sub(amount)
+            .ok_or(RewardsError::Underflow)?;
         rewards.total_claimed = rewards
             .total_claimed
             .checked_add(amount)
@@ -542,6 +743,332 @@ pub mod rewards {
         emit!(RewardsClaimedEvent {
             operator: rewards.operator,
             amount,
+            claimed_by: claimant,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if amount < claimable {
+            emit!(PartialClaimEvent {
+                operator: rewards.operator,
+                amount,
+                shortfall: claimable - amount,
+                claimed_by: claimant,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        if close_account {
+            msg!("Closing rewards account for operator: {}", rewards.operator);
+            ctx.accounts.operator_rewards.close(ctx.accounts.operator.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Let `delegate` trigger claims on the operator's behalf (operator only)
+    ///
+    /// For custodial setups where an ops key runs scheduled claims without
+    /// holding the operator key. Replaces any previous delegate.
+    pub fn set_claim_delegate(ctx: Context<ManageClaimDelegate>, delegate: Pubkey) -> Result<()> {
+        let rewards = &mut ctx.accounts.operator_rewards;
+        require!(
+            delegate != Pubkey::default() && delegate != rewards.operator,
+            RewardsError::InvalidClaimDelegate
+        );
+
+        rewards.claim_delegate = delegate;
+
+        msg!("Claim delegate for operator {} set to {}", rewards.operator, delegate);
+
+        emit!(ClaimDelegateUpdatedEvent {
+            operator: rewards.operator,
+            delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Remove the operator's claim delegate (operator only)
+    pub fn revoke_claim_delegate(ctx: Context<ManageClaimDelegate>) -> Result<()> {
+        let rewards = &mut ctx.accounts.operator_rewards;
+        require!(
+            rewards.claim_delegate != Pubkey::default(),
+            RewardsError::NoClaimDelegate
+        );
+
+        rewards.claim_delegate = Pubkey::default();
+
+        msg!("Claim delegate for operator {} revoked", rewards.operator);
+
+        emit!(ClaimDelegateUpdatedEvent {
+            operator: rewards.operator,
+            delegate: Pubkey::default(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register the token account delegate-initiated claims pay out to
+    /// (operator only, must hold the reward mint)
+    pub fn register_payout_account(ctx: Context<RegisterPayoutAccount>) -> Result<()> {
+        let rewards = &mut ctx.accounts.operator_rewards;
+        let payout_account = ctx.accounts.payout_account.key();
+
+        rewards.registered_payout_account = payout_account;
+
+        msg!("Payout account for operator {} set to {}", rewards.operator, payout_account);
+
+        emit!(PayoutAccountRegisteredEvent {
+            operator: rewards.operator,
+            payout_account,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update the claim policy (authority only)
+    /// min_claim_amount: smallest claim accepted (0 = no minimum)
+    /// claim_cooldown_seconds: minimum time between claims (0 = no cooldown)
+    pub fn update_claim_policy(
+        ctx: Context<UpdateClaimPolicy>,
+        min_claim_amount: u64,
+        claim_cooldown_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_CLAIM_COOLDOWN_SECONDS).contains(&claim_cooldown_seconds),
+            RewardsError::InvalidClaimCooldown
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.min_claim_amount = min_claim_amount;
+        pool.claim_cooldown_seconds = claim_cooldown_seconds;
+
+        msg!(
+            "Claim policy updated: min_claim_amount={}, cooldown={}s",
+            min_claim_amount,
+            claim_cooldown_seconds
+        );
+
+        emit!(ClaimPolicyUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            min_claim_amount,
+            claim_cooldown_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update the performance cliff and knee (authority only)
+    /// cliff: weighted score (0-10000) below which rewards are zero
+    /// knee: weighted score from which rewards are linear again; between the
+    /// two they scale quadratically
+    pub fn update_performance_curve(
+        ctx: Context<UpdatePerformanceCurve>,
+        cliff: u16,
+        knee: u16,
+    ) -> Result<()> {
+        require!(
+            cliff <= knee && knee <= PERFORMANCE_SCALE,
+            RewardsError::InvalidPerformanceCurve
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.performance_cliff = cliff;
+        pool.performance_knee = knee;
+
+        msg!("Performance curve updated: cliff={}, knee={}", cliff, knee);
+
+        emit!(PerformanceCurveUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            cliff,
+            knee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update how stale performance data decays (authority only)
+    /// grace_epochs: epochs without an attestation before the score decays
+    /// cutoff_epochs: epochs without an attestation at which the score is
+    /// zero; it decays linearly in between. A cutoff of 0 disables decay.
+    pub fn update_staleness_policy(
+        ctx: Context<UpdateStalenessPolicy>,
+        grace_epochs: u16,
+        cutoff_epochs: u16,
+    ) -> Result<()> {
+        require!(
+            cutoff_epochs == 0 || grace_epochs < cutoff_epochs,
+            RewardsError::InvalidStalenessPolicy
+        );
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.staleness_grace_epochs = grace_epochs;
+        pool.staleness_cutoff_epochs = cutoff_epochs;
+
+        msg!(
+            "Staleness policy updated: grace={} epochs, cutoff={} epochs",
+            grace_epochs,
+            cutoff_epochs
+        );
+
+        emit!(StalenessPolicyUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            grace_epochs,
+            cutoff_epochs,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Migrate the reward pool to the current layout (authority only)
+    ///
+    /// Pools created before the claim policy, performance curve, epoch
+    /// start and staleness policy fields were appended are shorter than
+    /// RewardPool::MAX_SIZE and can't be loaded by other instructions. The
+    /// account is grown (the authority pays the extra rent) and the appended
+    /// fields get initialize_pool's defaults; epoch_started_at stays 0
+    /// (unknown). A no-op for a pool already at the current layout.
+    pub fn migrate_reward_pool(ctx: Context<MigrateRewardPool>) -> Result<()> {
+        let clock = Clock::get()?;
+        let info = ctx.accounts.reward_pool.to_account_info();
+        let original_len = info.data_len();
+
+        // authority directly follows the discriminator in every layout
+        let authority = info
+            .try_borrow_data()?
+            .get(8..40)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(RewardsError::UnauthorizedAuthority)?;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            authority,
+            RewardsError::UnauthorizedAuthority
+        );
+
+        if original_len >= RewardPool::MAX_SIZE {
+            msg!("Reward pool already at the current layout ({} bytes)", original_len);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            RewardPool::MAX_SIZE,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        let mut pool = RewardPool::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        pool.fill_appended_defaults(original_len);
+        pool.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Reward pool migrated from {} to {} bytes",
+            original_len,
+            RewardPool::MAX_SIZE
+        );
+        emit!(RewardPoolMigratedEvent {
+            authority,
+            from_len: original_len as u32,
+            to_len: RewardPool::MAX_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze part of an operator's unclaimed rewards pending review
+    ///
+    /// Used when a faulty attestation inflated rewards for the given epoch
+    /// range. `amount` stays unclaimable for REWARD_DISPUTE_WINDOW or until
+    /// resolve_reward_dispute; if the window lapses unresolved it is released.
+    /// Callable by the pool authority or a majority of active oracles (extra
+    /// oracle signers are passed as remaining accounts).
+    pub fn flag_reward_dispute(
+        ctx: Context<RewardDispute>,
+        operator: Pubkey,
+        start_epoch: u64,
+        end_epoch: u64,
+        amount: u64,
+        reason_cid: String,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require_dispute_authority(
+            &ctx.accounts.reward_pool,
+            ctx.accounts.oracle_registry.as_deref(),
+            &ctx.accounts.arbiter,
+            ctx.remaining_accounts,
+        )?;
+        require!(start_epoch <= end_epoch, RewardsError::InvalidEpochRange);
+        require!(
+            !reason_cid.is_empty() && reason_cid.len() <= MAX_REASON_CID_LENGTH,
+            RewardsError::InvalidReasonCid
+        );
+
+        let rewards = &mut ctx.accounts.operator_rewards;
+        let expires_at = rewards.open_dispute(amount, clock.unix_timestamp)?;
+
+        msg!(
+            "Reward dispute flagged for operator {}: {} frozen until {}",
+            operator,
+            amount,
+            expires_at
+        );
+
+        emit!(RewardDisputeFlaggedEvent {
+            operator,
+            amount,
+            start_epoch,
+            end_epoch,
+            reason_cid,
+            flagged_by: ctx.accounts.arbiter.key(),
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Resolve an open reward dispute
+    ///
+    /// `confirm` forfeits the frozen rewards (they stay in the vault as pool
+    /// budget); otherwise they are released back to the operator. Same
+    /// authorization as flag_reward_dispute.
+    pub fn resolve_reward_dispute(
+        ctx: Context<RewardDispute>,
+        operator: Pubkey,
+        confirm: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        require_dispute_authority(
+            &ctx.accounts.reward_pool,
+            ctx.accounts.oracle_registry.as_deref(),
+            &ctx.accounts.arbiter,
+            ctx.remaining_accounts,
+        )?;
+
+        let rewards = &mut ctx.accounts.operator_rewards;
+        let amount = rewards.resolve_dispute(confirm, clock.unix_timestamp)?;
+
+        msg!(
+            "Reward dispute for operator {} {}: {}",
+            operator,
+            if confirm { "confirmed" } else { "released" },
+            amount
+        );
+
+        emit!(RewardDisputeResolvedEvent {
+            operator,
+            amount,
+            confirmed: confirm,
+            resolved_by: ctx.accounts.arbiter.key(),
             timestamp: clock.unix_timestamp,
         });
 
@@ -569,37 +1096,416 @@ pub mod rewards {
         Ok(())
     }
 
-}
-
-/// Calculate emission for a specific epoch based on halving schedule
-/// Year 1: 100M/365 per day, Year 2: 50M/365, Year 3: 25M/365, etc.
-fn calculate_epoch_emission(epochs_since_start: u64) -> u64 {
-    let year = epochs_since_start / EPOCHS_PER_YEAR;
-
-    // Halving: divide by 2^year, but cap at year 10 (minimum emission)
-    let halving_divisor = 1u64 << std::cmp::min(year, 10);
-
-    // Daily emission = yearly emission / 365 / halving divisor
-    (INITIAL_YEARLY_EMISSION / EPOCHS_PER_YEAR) / halving_divisor
-}
-
-/// Integer square root using Newton's method
-/// Returns sqrt(n) with PRECISION scaling
-fn integer_sqrt(n: u128) -> u128 {
-    if n == 0 {
-        return 0;
-    }
+    /// Create a secondary incentive program paying a partner token
+    ///
+    /// Over [start_epoch, end_epoch) operators earn the partner token
+    /// alongside their AEGIS rewards, from whatever the signer (the program's
+    /// authority) funds into its vault. Allocation only happens when the pool
+    /// authority passes the program to calculate_rewards.
+    pub fn create_secondary_incentive(
+        ctx: Context<CreateSecondaryIncentive>,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<()> {
+        require!(
+            start_epoch < end_epoch && end_epoch - start_epoch <= MAX_SECONDARY_INCENTIVE_EPOCHS,
+            RewardsError::InvalidIncentiveWindow
+        );
 
-    let mut x = n;
-    let mut y = (x + 1) / 2;
+        let incentive = &mut ctx.accounts.secondary_incentive;
+        incentive.authority = ctx.accounts.authority.key();
+        incentive.mint = ctx.accounts.mint.key();
+        incentive.vault = ctx.accounts.secondary_vault.key();
+        incentive.budget = 0;
+        incentive.allocated = 0;
+        incentive.claimed = 0;
+        incentive.start_epoch = start_epoch;
+        incentive.end_epoch = end_epoch;
+        incentive.closed = false;
+        incentive.bump = ctx.bumps.secondary_incentive;
+
+        emit!(SecondaryIncentiveCreatedEvent {
+            incentive: incentive.key(),
+            authority: incentive.authority,
+            mint: incentive.mint,
+            start_epoch,
+            end_epoch,
+        });
 
-    while y < x {
-        x = y;
-        y = (x + n / x) / 2;
+        Ok(())
     }
 
-    x
-}
+    /// Add to a secondary incentive program's budget (program authority only)
+    pub fn fund_secondary_incentive(ctx: Context<FundSecondaryIncentive>, amount: u64) -> Result<()> {
+        require!(amount > 0, RewardsError::InvalidAmount);
+        require!(
+            !ctx.accounts.secondary_incentive.closed,
+            RewardsError::SecondaryIncentiveClosed
+        );
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.secondary_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let incentive = &mut ctx.accounts.secondary_incentive;
+        incentive.budget = incentive
+            .budget
+            .checked_add(amount)
+            .ok_or(RewardsError::Overflow)?;
+
+        emit!(SecondaryIncentiveFundedEvent {
+            incentive: incentive.key(),
+            amount,
+            budget: incentive.budget,
+        });
+
+        Ok(())
+    }
+
+    /// Close a secondary incentive program (program authority only)
+    ///
+    /// Stops further allocation and returns the unallocated budget to the
+    /// authority. Allocated rewards stay in the vault for operators to claim.
+    pub fn close_secondary_incentive(ctx: Context<CloseSecondaryIncentive>) -> Result<()> {
+        let incentive = &ctx.accounts.secondary_incentive;
+        require!(!incentive.closed, RewardsError::SecondaryIncentiveClosed);
+
+        let unspent = incentive.unallocated();
+        if unspent > 0 {
+            let seeds = &[
+                SECONDARY_INCENTIVE_SEED,
+                incentive.mint.as_ref(),
+                &[incentive.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.secondary_vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.secondary_incentive.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, unspent)?;
+        }
+
+        let incentive = &mut ctx.accounts.secondary_incentive;
+        incentive.budget = incentive.allocated;
+        incentive.closed = true;
+
+        emit!(SecondaryIncentiveClosedEvent {
+            incentive: incentive.key(),
+            returned: unspent,
+            allocated: incentive.allocated,
+        });
+
+        Ok(())
+    }
+
+    /// Open an operator's balance in a secondary incentive program
+    ///
+    /// Anyone can pay for it (typically the pool authority before calculating
+    /// rewards); the operator is taken from their rewards account.
+    pub fn initialize_secondary_rewards(ctx: Context<InitializeSecondaryRewards>) -> Result<()> {
+        let secondary = &mut ctx.accounts.secondary_rewards;
+        secondary.incentive = ctx.accounts.secondary_incentive.key();
+        secondary.operator = ctx.accounts.operator_rewards.operator;
+        secondary.unclaimed_rewards = 0;
+        secondary.total_earned = 0;
+        secondary.total_claimed = 0;
+        secondary.bump = ctx.bumps.secondary_rewards;
+        Ok(())
+    }
+
+    /// Claim secondary incentive rewards from the program's vault
+    ///
+    /// Still possible after the program is closed, for rewards allocated before.
+    pub fn claim_secondary_rewards(ctx: Context<ClaimSecondaryRewards>) -> Result<()> {
+        let amount = ctx.accounts.secondary_rewards.unclaimed_rewards;
+        require!(amount > 0, RewardsError::NoRewardsToClaim);
+
+        let incentive = &ctx.accounts.secondary_incentive;
+        let seeds = &[
+            SECONDARY_INCENTIVE_SEED,
+            incentive.mint.as_ref(),
+            &[incentive.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.secondary_vault.to_account_info(),
+            to: ctx.accounts.operator_token_account.to_account_info(),
+            authority: ctx.accounts.secondary_incentive.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        let secondary = &mut ctx.accounts.secondary_rewards;
+        secondary.unclaimed_rewards = 0;
+        secondary.total_claimed = secondary
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(RewardsError::Overflow)?;
+
+        let incentive = &mut ctx.accounts.secondary_incentive;
+        incentive.claimed = incentive
+            .claimed
+            .checked_add(amount)
+            .ok_or(RewardsError::Overflow)?;
+
+        emit!(SecondaryRewardsClaimedEvent {
+            incentive: incentive.key(),
+            operator: secondary.operator,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Emission for one epoch and its position in the halving schedule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct EpochEmission {
+    /// Tokens emitted this epoch
+    emission: u64,
+    /// Zero-based halving year
+    year_index: u64,
+    /// Epochs since start at which the next halving applies (None once emission has floored)
+    next_halving_offset: Option<u64>,
+}
+
+/// Calculate emission for a specific epoch based on halving schedule
+/// Year 1: 100M/365 per day, Year 2: 50M/365, Year 3: 25M/365, etc.
+fn calculate_epoch_emission(epochs_since_start: u64) -> EpochEmission {
+    let year = epochs_since_start / EPOCHS_PER_YEAR;
+
+    // Halving: divide by 2^year, but cap at year 10 (minimum emission)
+    let halving_divisor = 1u64 << std::cmp::min(year, FINAL_HALVING_YEAR);
+
+    // Daily emission = yearly emission / 365 / halving divisor
+    let emission = (INITIAL_YEARLY_EMISSION / EPOCHS_PER_YEAR) / halving_divisor;
+
+    let next_halving_offset = if year < FINAL_HALVING_YEAR {
+        Some((year + 1) * EPOCHS_PER_YEAR)
+    } else {
+        None
+    };
+
+    EpochEmission {
+        emission,
+        year_index: year,
+        next_halving_offset,
+    }
+}
+
+/// Base emission of epochs [from, to)
+fn emission_between(from: u64, to: u64, pool_start_epoch: u64) -> Result<u128> {
+    let mut total: u128 = 0;
+    for epoch in from..to {
+        total = total
+            .checked_add(calculate_epoch_emission(epoch.saturating_sub(pool_start_epoch)).emission as u128)
+            .ok_or(RewardsError::Overflow)?;
+    }
+    Ok(total)
+}
+
+/// Check the caller may flag or resolve a reward dispute: either the pool
+/// authority, or signers covering a majority of the active oracles
+/// Performance factor (0-10000) actually paid for a weighted score
+///
+/// Scores below `cliff` earn nothing, so barely-alive nodes aren't worth
+/// running. From `knee` up the factor is the score itself (linear). In
+/// between it is score² / knee: quadratic, and continuous at the knee.
+fn effective_performance(weighted_performance: u64, cliff: u64, knee: u64) -> u64 {
+    if weighted_performance < cliff {
+        0
+    } else if weighted_performance >= knee {
+        weighted_performance
+    } else {
+        weighted_performance * weighted_performance / knee
+    }
+}
+
+/// Performance factor left once the operator's attestations have gone stale
+///
+/// Oracles that stop attesting (node dropped from monitoring, oracle outage)
+/// would otherwise leave the last recorded score in force indefinitely. Up
+/// to `grace` epochs without an attestation the factor is untouched; it then
+/// falls linearly, reaching zero at `cutoff`. A `cutoff` of 0 disables decay.
+fn decay_stale_performance(performance: u64, staleness_epochs: u64, grace: u64, cutoff: u64) -> u64 {
+    if cutoff == 0 || staleness_epochs <= grace {
+        performance
+    } else if staleness_epochs >= cutoff {
+        0
+    } else {
+        performance * (cutoff - staleness_epochs) / (cutoff - grace)
+    }
+}
+
+/// Record `attestation` for the operator, or hold it if it's implausible
+fn record_attestation(
+    rewards: &mut OperatorRewards,
+    pool: &mut RewardPool,
+    attestation: PerformanceAttestation,
+    nonce: u64,
+) -> Result<()> {
+    match rewards.submit_attestation(attestation)? {
+        Some(anomaly) => {
+            msg!("Attestation for epoch {} held: {:?}", attestation.epoch, anomaly);
+            emit!(MetricAnomalyEvent {
+                operator: rewards.operator,
+                anomaly,
+                uptime: attestation.uptime_percentage,
+                latency: attestation.latency_score,
+                throughput: attestation.throughput_score,
+                requests: attestation.requests_served,
+                epoch: attestation.epoch,
+                last_performance_epoch: rewards.last_performance_epoch,
+                nonce,
+            });
+            Ok(())
+        }
+        None => credit_attestation(pool, rewards.operator, &attestation, nonce),
+    }
+}
+
+/// Count a recorded attestation's requests toward the network total
+fn credit_attestation(
+    pool: &mut RewardPool,
+    operator: Pubkey,
+    attestation: &PerformanceAttestation,
+    nonce: u64,
+) -> Result<()> {
+    // Update total network requests for demand multiplier
+    pool.total_network_requests = pool.total_network_requests
+        .checked_add(attestation.requests_served)
+        .ok_or(RewardsError::Overflow)?;
+
+    emit!(PerformanceRecordedEvent {
+        operator,
+        uptime: attestation.uptime_percentage,
+        latency: attestation.latency_score,
+        throughput: attestation.throughput_score,
+        requests: attestation.requests_served,
+        epoch: attestation.epoch,
+        nonce,
+    });
+    Ok(())
+}
+
+fn require_dispute_authority(
+    pool: &RewardPool,
+    registry: Option<&OracleRegistry>,
+    arbiter: &Signer,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if arbiter.key() == pool.authority {
+        return Ok(());
+    }
+
+    let registry = registry.ok_or(RewardsError::UnauthorizedAuthority)?;
+    let signers: Vec<Pubkey> = std::iter::once(arbiter.key())
+        .chain(
+            remaining_accounts
+                .iter()
+                .filter(|account| account.is_signer)
+                .map(|account| account.key()),
+        )
+        .collect();
+    require!(
+        oracle_quorum_reached(&registry.oracles, &signers),
+        RewardsError::UnauthorizedAuthority
+    );
+    Ok(())
+}
+
+/// Whether `signers` include more than half of the active oracles
+fn oracle_quorum_reached(oracles: &[OracleInfo], signers: &[Pubkey]) -> bool {
+    let active = oracles.iter().filter(|o| o.is_active).count();
+    let approving = oracles
+        .iter()
+        .filter(|o| o.is_active && signers.iter().any(|s| s.to_bytes() == o.pubkey))
+        .count();
+    active > 0 && approving * 2 > active
+}
+
+/// Amount a claim of `claimable` pays out of a vault holding `vault_balance`
+///
+/// A shortfall pays out whatever the vault holds when `allow_partial` is set
+/// and fails otherwise, rather than letting the token transfer fail.
+fn vault_payout(claimable: u64, vault_balance: u64, allow_partial: bool) -> Result<u64> {
+    if claimable <= vault_balance {
+        return Ok(claimable);
+    }
+    require!(
+        allow_partial && vault_balance > 0,
+        RewardsError::InsufficientVaultBalance
+    );
+    Ok(vault_balance)
+}
+
+/// Share of the current epoch (in PERFORMANCE_SCALE units) still left at `now`
+///
+/// Pools created before epoch start times were tracked have
+/// `epoch_started_at == 0`; operators joining those get the full epoch.
+fn remaining_epoch_share_bps(epoch_started_at: i64, now: i64) -> u16 {
+    if epoch_started_at == 0 {
+        return PERFORMANCE_SCALE;
+    }
+    let elapsed = now.saturating_sub(epoch_started_at).clamp(0, EPOCH_DURATION_SECONDS);
+    let remaining = EPOCH_DURATION_SECONDS - elapsed;
+    (remaining * PERFORMANCE_SCALE as i64 / EPOCH_DURATION_SECONDS) as u16
+}
+
+/// Resize a program-owned account to `len`, topping up rent from `payer`
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= len {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Integer square root using Newton's method
+/// Returns sqrt(n) with PRECISION scaling
+fn integer_sqrt(n: u128) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+
+    x
+}
 
 /// Reward Pool - Global state with emission schedule support
 #[account]
@@ -613,6 +1519,13 @@ pub struct RewardPool {
     /// Y7.4: Last time authority recorded performance (for rate limiting)
     pub last_authority_recording: i64,// Last authority recording timestamp (8)
     pub bump: u8,                     // PDA bump (1)
+    pub min_claim_amount: u64,        // Smallest claim accepted, 0 = none (8)
+    pub claim_cooldown_seconds: i64,  // Minimum time between claims, 0 = none (8)
+    pub performance_cliff: u16,       // Weighted score below which rewards are zero (2)
+    pub performance_knee: u16,        // Weighted score where rewards turn linear (2)
+    pub epoch_started_at: i64,        // When current_epoch began, 0 = unknown (8)
+    pub staleness_grace_epochs: u16,  // Epochs without attestation before decay (2)
+    pub staleness_cutoff_epochs: u16, // Epochs without attestation until zero, 0 = off (2)
 }
 
 impl RewardPool {
@@ -624,7 +1537,32 @@ impl RewardPool {
         8 +   // start_epoch
         8 +   // total_network_requests
         8 +   // last_authority_recording (Y7.4)
-        1;    // bump
+        1 +   // bump
+        8 +   // min_claim_amount
+        8 +   // claim_cooldown_seconds
+        2 +   // performance_cliff
+        2 +   // performance_knee
+        8 +   // epoch_started_at
+        2 +   // staleness_grace_epochs
+        2;    // staleness_cutoff_epochs
+
+    /// Set defaults for fields appended after a pool of `original_len`
+    /// bytes was created; they deserialize as zero after a resize
+    ///
+    /// A zero claim policy is already the default (disabled), as is a zero
+    /// epoch_started_at (unknown).
+    pub fn fill_appended_defaults(&mut self, original_len: usize) {
+        // performance_cliff is followed by knee, epoch_started_at and the staleness policy
+        if original_len <= Self::MAX_SIZE - 16 {
+            self.performance_cliff = DEFAULT_PERFORMANCE_CLIFF;
+            self.performance_knee = DEFAULT_PERFORMANCE_KNEE;
+        }
+        // staleness_grace_epochs and staleness_cutoff_epochs are the last 4 bytes
+        if original_len <= Self::MAX_SIZE - 4 {
+            self.staleness_grace_epochs = DEFAULT_STALENESS_GRACE_EPOCHS;
+            self.staleness_cutoff_epochs = DEFAULT_STALENESS_CUTOFF_EPOCHS;
+        }
+    }
 }
 
 /// Oracle Registry - Stores registered oracle public keys for signature verification
@@ -667,6 +1605,14 @@ pub struct OperatorRewards {
     pub requests_served: u64,         // For demand multiplier calculation (8)
     pub last_performance_epoch: u64,  // Last epoch performance was recorded (8)
     pub bump: u8,                     // PDA bump (1)
+    pub frozen_rewards: u64,          // Unclaimed rewards held by a dispute (8)
+    pub dispute_expires_at: i64,      // When the dispute freeze lapses, 0 = none (8)
+    pub first_active_epoch: u64,      // Pool epoch at initialization (8)
+    pub first_epoch_share_bps: u16,   // Share of first_active_epoch earned, 10000 = all (2)
+    pub claim_delegate: Pubkey,       // Key allowed to claim for the operator, default = none (32)
+    pub registered_payout_account: Pubkey, // Only payout target for delegate claims, default = none (32)
+    pub flagged_attestation: PerformanceAttestation, // Attestation held for authority review (20)
+    pub flagged_anomaly: MetricAnomaly, // Why it was held, None = nothing held (1)
 }
 
 impl OperatorRewards {
@@ -681,7 +1627,219 @@ impl OperatorRewards {
         1 +   // throughput_score
         8 +   // requests_served
         8 +   // last_performance_epoch
-        1;    // bump
+        1 +   // bump
+        8 +   // frozen_rewards
+        8 +   // dispute_expires_at
+        8 +   // first_active_epoch
+        2 +   // first_epoch_share_bps
+        32 +  // claim_delegate
+        32 +  // registered_payout_account
+        PerformanceAttestation::SIZE + // flagged_attestation
+        1;    // flagged_anomaly
+
+    /// Base emission owed to this operator for `epochs` epochs from `range_start`
+    ///
+    /// Epochs before first_active_epoch contribute nothing and the first
+    /// active epoch is prorated by first_epoch_share_bps.
+    pub fn range_emission(&self, range_start: u64, epochs: u64, pool_start_epoch: u64) -> Result<u128> {
+        let range_end = range_start.saturating_add(epochs);
+        let mut total_emission: u128 = 0;
+        for epoch in range_start.max(self.first_active_epoch)..range_end {
+            let mut emission = calculate_epoch_emission(epoch.saturating_sub(pool_start_epoch)).emission as u128;
+            if epoch == self.first_active_epoch {
+                emission = emission
+                    .checked_mul(self.first_epoch_share_bps as u128)
+                    .ok_or(RewardsError::Overflow)?
+                    / PERFORMANCE_SCALE as u128;
+            }
+            total_emission = total_emission
+                .checked_add(emission)
+                .ok_or(RewardsError::Overflow)?;
+        }
+        Ok(total_emission)
+    }
+
+    /// Check that `claimant` may claim to `payout_account`
+    ///
+    /// The operator can claim anywhere. The active delegate can only claim to
+    /// the registered payout account and can't close the rewards account.
+    pub fn authorize_claim(
+        &self,
+        claimant: Pubkey,
+        payout_account: Pubkey,
+        close_account: bool,
+    ) -> Result<()> {
+        if claimant == self.operator {
+            return Ok(());
+        }
+
+        require!(
+            self.claim_delegate != Pubkey::default() && claimant == self.claim_delegate,
+            RewardsError::UnauthorizedOperator
+        );
+        require!(!close_account, RewardsError::DelegateCannotClose);
+        require!(
+            self.registered_payout_account != Pubkey::default()
+                && payout_account == self.registered_payout_account,
+            RewardsError::UnregisteredPayoutAccount
+        );
+        Ok(())
+    }
+
+    /// Rewards frozen by a dispute at `now` (0 once its window has lapsed)
+    pub fn frozen_at(&self, now: i64) -> u64 {
+        if now < self.dispute_expires_at {
+            self.frozen_rewards
+        } else {
+            0
+        }
+    }
+
+    /// Unclaimed rewards that can be paid out at `now`
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.unclaimed_rewards.saturating_sub(self.frozen_at(now))
+    }
+
+    /// Record `attestation`, or hold it for authority review when it fails
+    /// the plausibility checks; returns the anomaly if it was held
+    ///
+    /// A held attestation replaces any earlier one, and recording a plausible
+    /// attestation discards it.
+    pub fn submit_attestation(&mut self, attestation: PerformanceAttestation) -> Result<Option<MetricAnomaly>> {
+        match attestation.anomaly(self.last_performance_epoch) {
+            Some(anomaly) => {
+                self.flagged_attestation = attestation;
+                self.flagged_anomaly = anomaly;
+                Ok(Some(anomaly))
+            }
+            None => {
+                self.apply_attestation(&attestation)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record the held attestation regardless of its anomaly and return it
+    pub fn accept_flagged(&mut self) -> Result<PerformanceAttestation> {
+        require!(
+            self.flagged_anomaly != MetricAnomaly::None,
+            RewardsError::NoFlaggedAttestation
+        );
+        let attestation = self.flagged_attestation;
+        require!(
+            attestation.epoch > self.last_performance_epoch,
+            RewardsError::EpochNotIncreasing
+        );
+        self.apply_attestation(&attestation)?;
+        Ok(attestation)
+    }
+
+    fn apply_attestation(&mut self, attestation: &PerformanceAttestation) -> Result<()> {
+        self.uptime_percentage = attestation.uptime_percentage;
+        self.latency_score = attestation.latency_score;
+        self.throughput_score = attestation.throughput_score;
+        self.requests_served = self.requests_served
+            .checked_add(attestation.requests_served)
+            .ok_or(RewardsError::Overflow)?;
+        self.last_performance_epoch = attestation.epoch;
+        self.flagged_attestation = PerformanceAttestation::default();
+        self.flagged_anomaly = MetricAnomaly::None;
+        Ok(())
+    }
+
+    /// Freeze `amount` of unclaimed rewards; returns when the freeze lapses
+    pub fn open_dispute(&mut self, amount: u64, now: i64) -> Result<i64> {
+        require!(self.frozen_at(now) == 0, RewardsError::DisputeAlreadyOpen);
+        require!(
+            amount > 0 && amount <= self.unclaimed_rewards,
+            RewardsError::InvalidAmount
+        );
+
+        let expires_at = now
+            .checked_add(REWARD_DISPUTE_WINDOW)
+            .ok_or(RewardsError::Overflow)?;
+        self.frozen_rewards = amount;
+        self.dispute_expires_at = expires_at;
+        Ok(expires_at)
+    }
+
+    /// Close the open dispute, forfeiting the frozen rewards if `confirm`;
+    /// returns the amount that was frozen
+    pub fn resolve_dispute(&mut self, confirm: bool, now: i64) -> Result<u64> {
+        let amount = self.frozen_at(now);
+        require!(amount > 0, RewardsError::NoOpenDispute);
+
+        if confirm {
+            self.unclaimed_rewards = self
+                .unclaimed_rewards
+                .checked_sub(amount)
+                .ok_or(RewardsError::Underflow)?;
+            self.total_earned = self
+                .total_earned
+                .checked_sub(amount)
+                .ok_or(RewardsError::Underflow)?;
+        }
+        self.frozen_rewards = 0;
+        self.dispute_expires_at = 0;
+        Ok(amount)
+    }
+}
+
+/// One epoch's performance metrics as reported by an oracle or the authority
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerformanceAttestation {
+    pub metric_semantics_version: u8, // Score semantics the reporter used (1)
+    pub uptime_percentage: u8,        // 0-100 (1)
+    pub latency_score: u8,            // 0-100, higher = lower latency (1)
+    pub throughput_score: u8,         // 0-100 (1)
+    pub requests_served: u64,         // Requests served during the epoch (8)
+    pub epoch: u64,                   // Epoch the metrics cover (8)
+}
+
+impl PerformanceAttestation {
+    pub const SIZE: usize = 1 + 1 + 1 + 1 + 8 + 8;
+
+    /// Reject attestations using other score semantics or out-of-range scores
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.metric_semantics_version == METRIC_SEMANTICS_VERSION,
+            RewardsError::UnsupportedMetricSemantics
+        );
+        require!(self.uptime_percentage <= MAX_SCORE, RewardsError::InvalidPercentage);
+        require!(self.latency_score <= MAX_SCORE, RewardsError::InvalidPercentage);
+        require!(self.throughput_score <= MAX_SCORE, RewardsError::InvalidPercentage);
+        Ok(())
+    }
+
+    /// Combination of metrics no healthy node can produce, if any
+    ///
+    /// `last_performance_epoch` is the operator's previous attestation (0 if
+    /// none yet): claiming perfect uptime across epochs that went unattested
+    /// means the oracle isn't reporting uptime as a share of the epoch.
+    pub fn anomaly(&self, last_performance_epoch: u64) -> Option<MetricAnomaly> {
+        if self.throughput_score == MAX_SCORE && self.requests_served == 0 {
+            Some(MetricAnomaly::ThroughputWithoutRequests)
+        } else if self.uptime_percentage == MAX_SCORE
+            && last_performance_epoch > 0
+            && self.epoch > last_performance_epoch.saturating_add(1)
+        {
+            Some(MetricAnomaly::UptimeAcrossGap)
+        } else {
+            None
+        }
+    }
+}
+
+/// Plausibility check an attestation failed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetricAnomaly {
+    /// Nothing held
+    #[default]
+    None,
+    /// Perfect throughput while serving no requests
+    ThroughputWithoutRequests,
+    /// Perfect uptime over epochs with no attestation
+    UptimeAcrossGap,
 }
 
 /// Y2.3: Nonce Tracker - Stores used nonces per operator to prevent replay attacks
@@ -702,6 +1860,97 @@ impl NonceTracker {
         1;                            // bump
 }
 
+/// Secondary incentive program - a partner token paid alongside AEGIS rewards
+#[account]
+pub struct SecondaryIncentive {
+    pub authority: Pubkey,            // Partner who funds and can close the program (32)
+    pub mint: Pubkey,                 // Partner token mint (32)
+    pub vault: Pubkey,                // Token account holding the budget (32)
+    pub budget: u64,                  // Total funded, less any returned at close (8)
+    pub allocated: u64,               // Allocated to operators so far (8)
+    pub claimed: u64,                 // Claimed by operators so far (8)
+    pub start_epoch: u64,             // First epoch of the program (8)
+    pub end_epoch: u64,               // Epoch the program ends at, exclusive (8)
+    pub closed: bool,                 // No further allocation once closed (1)
+    pub bump: u8,                     // PDA bump (1)
+}
+
+impl SecondaryIncentive {
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +  // authority
+        32 +  // mint
+        32 +  // vault
+        8 +   // budget
+        8 +   // allocated
+        8 +   // claimed
+        8 +   // start_epoch
+        8 +   // end_epoch
+        1 +   // closed
+        1;    // bump
+
+    /// Budget not yet allocated to any operator
+    pub fn unallocated(&self) -> u64 {
+        self.budget.saturating_sub(self.allocated)
+    }
+
+    /// Part of [range_start, range_start + epochs) inside the program window,
+    /// as (first epoch, epoch count)
+    pub fn overlap(&self, range_start: u64, epochs: u64) -> (u64, u64) {
+        let start = range_start.max(self.start_epoch);
+        let end = range_start.saturating_add(epochs).min(self.end_epoch);
+        (start, end.saturating_sub(start))
+    }
+
+    /// Partner tokens owed alongside a primary reward
+    ///
+    /// `primary_reward` was earned from `total_emission` of base emission, of
+    /// which `overlap_emission` fell inside the program window. That share of
+    /// the primary reward earns the budget at the rate the budget bears to the
+    /// window's base emission (`window_emission`), so operators split the
+    /// budget as they split AEGIS emission. Capped by the unallocated budget.
+    pub fn allocation(
+        &self,
+        primary_reward: u64,
+        total_emission: u128,
+        overlap_emission: u128,
+        window_emission: u128,
+    ) -> Result<u64> {
+        if self.closed || total_emission == 0 || window_emission == 0 {
+            return Ok(0);
+        }
+        let amount = (primary_reward as u128)
+            .checked_mul(overlap_emission)
+            .ok_or(RewardsError::Overflow)?
+            / total_emission;
+        let amount = amount
+            .checked_mul(self.budget as u128)
+            .ok_or(RewardsError::Overflow)?
+            / window_emission;
+        Ok(amount.min(self.unallocated() as u128) as u64)
+    }
+}
+
+/// An operator's balance in one secondary incentive program
+#[account]
+pub struct SecondaryRewards {
+    pub incentive: Pubkey,            // Secondary incentive program (32)
+    pub operator: Pubkey,             // Operator pubkey (32)
+    pub unclaimed_rewards: u64,       // Pending partner tokens (8)
+    pub total_earned: u64,            // Lifetime partner tokens earned (8)
+    pub total_claimed: u64,           // Lifetime partner tokens claimed (8)
+    pub bump: u8,                     // PDA bump (1)
+}
+
+impl SecondaryRewards {
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +  // incentive
+        32 +  // operator
+        8 +   // unclaimed_rewards
+        8 +   // total_earned
+        8 +   // total_claimed
+        1;    // bump
+}
+
 /// Initialize reward pool
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
@@ -725,6 +1974,12 @@ pub struct InitializePool<'info> {
 /// Initialize operator rewards
 #[derive(Accounts)]
 pub struct InitializeOperatorRewards<'info> {
+    #[account(
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
     #[account(
         init,
         payer = operator,
@@ -794,6 +2049,25 @@ pub struct RecordPerformanceAuthority<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Accept an operator's flagged attestation
+#[derive(Accounts)]
+pub struct AcceptFlaggedAttestation<'info> {
+    #[account(
+        mut,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [OPERATOR_REWARDS_SEED, operator_rewards.operator.as_ref()],
+        bump = operator_rewards.bump
+    )]
+    pub operator_rewards: Account<'info, OperatorRewards>,
+
+    pub authority: Signer<'info>,
+}
+
 /// Y2.3: Initialize nonce tracker for an operator
 #[derive(Accounts)]
 pub struct InitializeNonceTracker<'info> {
@@ -892,6 +2166,16 @@ pub struct AdvanceEpoch<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Emit emission info (read-only, permissionless)
+#[derive(Accounts)]
+pub struct EmitEmissionInfo<'info> {
+    #[account(
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+}
+
 /// Calculate rewards
 /// SECURITY FIX: Added has_one constraint to verify authority matches reward_pool.authority
 /// This prevents unauthorized users from triggering reward calculations with arbitrary parameters
@@ -911,10 +2195,25 @@ pub struct CalculateRewards<'info> {
 
     /// SECURITY FIX: Must match reward_pool.authority - enforced by has_one constraint above
     pub authority: Signer<'info>,
-}
 
-/// Claim rewards
-#[derive(Accounts)]
+    /// Only needed to allocate a secondary incentive program's token
+    #[account(
+        mut,
+        seeds = [SECONDARY_INCENTIVE_SEED, secondary_incentive.mint.as_ref()],
+        bump = secondary_incentive.bump
+    )]
+    pub secondary_incentive: Option<Account<'info, SecondaryIncentive>>,
+
+    /// The operator's balance in that program
+    #[account(
+        mut,
+        constraint = secondary_rewards.operator == operator_rewards.operator @ RewardsError::UnauthorizedOperator
+    )]
+    pub secondary_rewards: Option<Account<'info, SecondaryRewards>>,
+}
+
+/// Claim rewards
+#[derive(Accounts)]
 pub struct ClaimRewards<'info> {
     #[account(
         mut,
@@ -938,11 +2237,151 @@ pub struct ClaimRewards<'info> {
     #[account(mut)]
     pub operator_token_account: Account<'info, TokenAccount>,
 
-    pub operator: Signer<'info>,
+    /// CHECK: Matched against operator_rewards.operator; mutable to receive
+    /// rent when the rewards account is closed
+    #[account(mut)]
+    pub operator: UncheckedAccount<'info>,
+
+    /// The operator or their claim delegate (see OperatorRewards::authorize_claim)
+    pub claimant: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
 }
 
+/// Set or revoke a claim delegate
+#[derive(Accounts)]
+pub struct ManageClaimDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [OPERATOR_REWARDS_SEED, operator.key().as_ref()],
+        bump = operator_rewards.bump,
+        has_one = operator @ RewardsError::UnauthorizedOperator
+    )]
+    pub operator_rewards: Account<'info, OperatorRewards>,
+
+    pub operator: Signer<'info>,
+}
+
+/// Register the payout account for delegate claims
+#[derive(Accounts)]
+pub struct RegisterPayoutAccount<'info> {
+    #[account(
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump,
+        has_one = reward_vault @ RewardsError::InvalidRewardVault
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [OPERATOR_REWARDS_SEED, operator.key().as_ref()],
+        bump = operator_rewards.bump,
+        has_one = operator @ RewardsError::UnauthorizedOperator
+    )]
+    pub operator_rewards: Account<'info, OperatorRewards>,
+
+    #[account(
+        constraint = payout_account.mint == reward_vault.mint @ RewardsError::InvalidPayoutAccount
+    )]
+    pub payout_account: Account<'info, TokenAccount>,
+
+    pub operator: Signer<'info>,
+}
+
+/// Update claim policy
+#[derive(Accounts)]
+pub struct UpdateClaimPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Migrate the reward pool to the current layout
+#[derive(Accounts)]
+pub struct MigrateRewardPool<'info> {
+    /// CHECK: Older layouts are shorter than RewardPool::MAX_SIZE and
+    /// can't be decoded as Account<RewardPool> until resized; the PDA and
+    /// owner are checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub reward_pool: UncheckedAccount<'info>,
+
+    /// Pool authority; pays any extra rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Update performance curve
+#[derive(Accounts)]
+pub struct UpdatePerformanceCurve<'info> {
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Update staleness policy
+#[derive(Accounts)]
+pub struct UpdateStalenessPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Flag or resolve a reward dispute
+/// Oracle co-signers beyond the arbiter are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct RewardDispute<'info> {
+    #[account(
+        seeds = [REWARD_POOL_SEED],
+        bump = reward_pool.bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    /// Only needed when oracles, rather than the authority, act on the dispute
+    #[account(
+        seeds = [ORACLE_REGISTRY_SEED],
+        bump = oracle_registry.bump
+    )]
+    pub oracle_registry: Option<Account<'info, OracleRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [OPERATOR_REWARDS_SEED, operator.as_ref()],
+        bump = operator_rewards.bump
+    )]
+    pub operator_rewards: Account<'info, OperatorRewards>,
+
+    /// Pool authority or an active oracle
+    pub arbiter: Signer<'info>,
+}
+
 /// Fund pool
 #[derive(Accounts)]
 pub struct FundPool<'info> {
@@ -964,11 +2403,170 @@ pub struct FundPool<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+/// Create a secondary incentive program
+#[derive(Accounts)]
+pub struct CreateSecondaryIncentive<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = SecondaryIncentive::MAX_SIZE,
+        seeds = [SECONDARY_INCENTIVE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub secondary_incentive: Account<'info, SecondaryIncentive>,
+
+    /// Vault owned by the program's PDA
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SECONDARY_VAULT_SEED, secondary_incentive.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = secondary_incentive
+    )]
+    pub secondary_vault: Account<'info, TokenAccount>,
+
+    /// Partner token mint
+    pub mint: Account<'info, Mint>,
+
+    /// Partner running the program
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Fund a secondary incentive program
+#[derive(Accounts)]
+pub struct FundSecondaryIncentive<'info> {
+    #[account(
+        mut,
+        seeds = [SECONDARY_INCENTIVE_SEED, secondary_incentive.mint.as_ref()],
+        bump = secondary_incentive.bump,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub secondary_incentive: Account<'info, SecondaryIncentive>,
+
+    #[account(mut)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    /// The program's vault
+    #[account(
+        mut,
+        constraint = secondary_vault.key() == secondary_incentive.vault @ RewardsError::InvalidRewardVault
+    )]
+    pub secondary_vault: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close a secondary incentive program
+#[derive(Accounts)]
+pub struct CloseSecondaryIncentive<'info> {
+    #[account(
+        mut,
+        seeds = [SECONDARY_INCENTIVE_SEED, secondary_incentive.mint.as_ref()],
+        bump = secondary_incentive.bump,
+        has_one = authority @ RewardsError::UnauthorizedAuthority
+    )]
+    pub secondary_incentive: Account<'info, SecondaryIncentive>,
+
+    /// The program's vault
+    #[account(
+        mut,
+        constraint = secondary_vault.key() == secondary_incentive.vault @ RewardsError::InvalidRewardVault
+    )]
+    pub secondary_vault: Account<'info, TokenAccount>,
+
+    /// Receives the unallocated budget
+    #[account(
+        mut,
+        constraint = authority_token_account.mint == secondary_incentive.mint @ RewardsError::InvalidRewardVault
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Open an operator's balance in a secondary incentive program
+#[derive(Accounts)]
+pub struct InitializeSecondaryRewards<'info> {
+    #[account(
+        seeds = [SECONDARY_INCENTIVE_SEED, secondary_incentive.mint.as_ref()],
+        bump = secondary_incentive.bump
+    )]
+    pub secondary_incentive: Account<'info, SecondaryIncentive>,
+
+    #[account(
+        seeds = [OPERATOR_REWARDS_SEED, operator_rewards.operator.as_ref()],
+        bump = operator_rewards.bump
+    )]
+    pub operator_rewards: Account<'info, OperatorRewards>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SecondaryRewards::MAX_SIZE,
+        seeds = [
+            SECONDARY_REWARDS_SEED,
+            secondary_incentive.key().as_ref(),
+            operator_rewards.operator.as_ref()
+        ],
+        bump
+    )]
+    pub secondary_rewards: Account<'info, SecondaryRewards>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Claim secondary incentive rewards
+#[derive(Accounts)]
+pub struct ClaimSecondaryRewards<'info> {
+    #[account(
+        mut,
+        seeds = [SECONDARY_INCENTIVE_SEED, secondary_incentive.mint.as_ref()],
+        bump = secondary_incentive.bump
+    )]
+    pub secondary_incentive: Account<'info, SecondaryIncentive>,
+
+    #[account(
+        mut,
+        seeds = [SECONDARY_REWARDS_SEED, secondary_incentive.key().as_ref(), operator.key().as_ref()],
+        bump = secondary_rewards.bump,
+        has_one = operator @ RewardsError::UnauthorizedOperator
+    )]
+    pub secondary_rewards: Account<'info, SecondaryRewards>,
+
+    /// The program's vault
+    #[account(
+        mut,
+        constraint = secondary_vault.key() == secondary_incentive.vault @ RewardsError::InvalidRewardVault
+    )]
+    pub secondary_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub operator_token_account: Account<'info, TokenAccount>,
+
+    pub operator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
 
 /// Events
 #[event]
 pub struct OperatorRewardsInitializedEvent {
     pub operator: Pubkey,
+    pub first_active_epoch: u64,
+    pub first_epoch_share_bps: u16,
 }
 
 /// Y2.2: Updated to include nonce for audit trail
@@ -983,6 +2581,20 @@ pub struct PerformanceRecordedEvent {
     pub nonce: u64,  // Y2.2: Nonce used for this recording
 }
 
+/// An attestation failed the plausibility checks and was held, not recorded
+#[event]
+pub struct MetricAnomalyEvent {
+    pub operator: Pubkey,
+    pub anomaly: MetricAnomaly,
+    pub uptime: u8,
+    pub latency: u8,
+    pub throughput: u8,
+    pub requests: u64,
+    pub epoch: u64,
+    pub last_performance_epoch: u64,
+    pub nonce: u64,
+}
+
 #[event]
 pub struct RewardsCalculatedEvent {
     pub operator: Pubkey,
@@ -991,12 +2603,99 @@ pub struct RewardsCalculatedEvent {
     pub stake_multiplier: u64,
     pub performance_score: u64,
     pub demand_multiplier: u64,
+    /// Performance factor paid after the cliff, knee and staleness decay are applied
+    pub effective_performance: u64,
+    /// Epochs since the operator's last recorded performance
+    pub staleness_epochs: u64,
 }
 
 #[event]
 pub struct RewardsClaimedEvent {
     pub operator: Pubkey,
     pub amount: u64,
+    /// Operator or their claim delegate
+    pub claimed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Claim paid out less than was claimable because the vault ran short
+#[event]
+pub struct PartialClaimEvent {
+    pub operator: Pubkey,
+    /// Amount paid out (the vault balance)
+    pub amount: u64,
+    /// Claimable rewards left unclaimed for a later claim
+    pub shortfall: u64,
+    pub claimed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimDelegateUpdatedEvent {
+    pub operator: Pubkey,
+    /// New delegate, default pubkey when revoked
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutAccountRegisteredEvent {
+    pub operator: Pubkey,
+    pub payout_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimPolicyUpdatedEvent {
+    pub authority: Pubkey,
+    pub min_claim_amount: u64,
+    pub claim_cooldown_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPoolMigratedEvent {
+    pub authority: Pubkey,
+    pub from_len: u32,
+    pub to_len: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PerformanceCurveUpdatedEvent {
+    pub authority: Pubkey,
+    pub cliff: u16,
+    pub knee: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StalenessPolicyUpdatedEvent {
+    pub authority: Pubkey,
+    pub grace_epochs: u16,
+    pub cutoff_epochs: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardDisputeFlaggedEvent {
+    pub operator: Pubkey,
+    pub amount: u64,
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+    pub reason_cid: String,
+    pub flagged_by: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardDisputeResolvedEvent {
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// true = frozen rewards forfeited, false = released to the operator
+    pub confirmed: bool,
+    pub resolved_by: Pubkey,
     pub timestamp: i64,
 }
 
@@ -1006,12 +2705,75 @@ pub struct PoolFundedEvent {
     pub funder: Pubkey,
 }
 
+#[event]
+pub struct SecondaryIncentiveCreatedEvent {
+    pub incentive: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+}
+
+#[event]
+pub struct SecondaryIncentiveFundedEvent {
+    pub incentive: Pubkey,
+    pub amount: u64,
+    pub budget: u64,
+}
+
+#[event]
+pub struct SecondaryIncentiveClosedEvent {
+    pub incentive: Pubkey,
+    pub returned: u64,
+    pub allocated: u64,
+}
+
+#[event]
+pub struct SecondaryRewardsAllocatedEvent {
+    pub incentive: Pubkey,
+    pub operator: Pubkey,
+    pub primary_reward: u64,
+    pub amount: u64,
+    pub overlap_epochs: u64,
+}
+
+#[event]
+pub struct SecondaryRewardsClaimedEvent {
+    pub incentive: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct EpochAdvancedEvent {
     pub old_epoch: u64,
     pub new_epoch: u64,
 }
 
+/// Emission schedule snapshot, emitted at halving-year boundaries and on request
+#[event]
+pub struct EmissionScheduleEvent {
+    pub epoch: u64,
+    pub emission: u64,
+    pub year_index: u64,
+    pub next_halving_epoch: Option<u64>, // None once emission has floored
+}
+
+impl EmissionScheduleEvent {
+    fn for_epoch(epoch: u64, start_epoch: u64) -> Self {
+        let schedule = calculate_epoch_emission(epoch.saturating_sub(start_epoch));
+        Self {
+            epoch,
+            emission: schedule.emission,
+            year_index: schedule.year_index,
+            next_halving_epoch: schedule
+                .next_halving_offset
+                .map(|offset| start_epoch.saturating_add(offset)),
+        }
+    }
+}
+
 #[event]
 pub struct OracleRegisteredEvent {
     pub oracle_pubkey: [u8; 32],
@@ -1046,6 +2808,8 @@ pub enum AuthorityActionType {
     RegisterOracle,
     /// Oracle deactivation
     DeactivateOracle,
+    /// Recording an attestation that failed the plausibility checks
+    AcceptFlaggedAttestation,
 }
 
 /// Errors
@@ -1087,4 +2851,615 @@ pub enum RewardsError {
     /// Y7.4: Rate limiting for authority actions
     #[msg("Rate limit exceeded - please wait before performing this action again")]
     RateLimitExceeded,
+
+    #[msg("Claim is below the pool's minimum claim amount")]
+    ClaimTooSmall,
+    #[msg("Claim cooldown is still active")]
+    ClaimCooldownActive,
+    #[msg("Claim cooldown must be between 0 and 30 days")]
+    InvalidClaimCooldown,
+    #[msg("Dispute start epoch must not be after its end epoch")]
+    InvalidEpochRange,
+    #[msg("Reason CID is required and must be at most 64 bytes")]
+    InvalidReasonCid,
+    #[msg("Operator already has an open reward dispute")]
+    DisputeAlreadyOpen,
+    #[msg("Operator has no open reward dispute")]
+    NoOpenDispute,
+    #[msg("Rewards account cannot be closed while rewards are disputed")]
+    RewardsDisputed,
+    #[msg("Performance cliff must not exceed the knee, and the knee must be at most 10000")]
+    InvalidPerformanceCurve,
+    #[msg("Staleness grace must be below the cutoff (or the cutoff 0 to disable decay)")]
+    InvalidStalenessPolicy,
+    #[msg("Secondary incentive must start before it ends and run at most a year of epochs")]
+    InvalidIncentiveWindow,
+    #[msg("Secondary incentive program is closed")]
+    SecondaryIncentiveClosed,
+    #[msg("Operator's secondary rewards account is required with a secondary incentive")]
+    SecondaryRewardsRequired,
+    #[msg("Secondary rewards account belongs to a different incentive program")]
+    SecondaryIncentiveMismatch,
+    #[msg("Claim delegate must be set and differ from the operator")]
+    InvalidClaimDelegate,
+    #[msg("Operator has no claim delegate")]
+    NoClaimDelegate,
+    #[msg("Claim delegate cannot close the rewards account")]
+    DelegateCannotClose,
+    #[msg("Delegate claims must pay out to the operator's registered payout account")]
+    UnregisteredPayoutAccount,
+    #[msg("Payout account must hold the reward mint")]
+    InvalidPayoutAccount,
+    #[msg("Attestation uses unsupported metric semantics")]
+    UnsupportedMetricSemantics,
+    #[msg("Operator has no flagged attestation")]
+    NoFlaggedAttestation,
+    #[msg("Reward vault holds less than the claimable rewards")]
+    InsufficientVaultBalance,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YEAR_ONE_DAILY: u64 = INITIAL_YEARLY_EMISSION / EPOCHS_PER_YEAR;
+
+    #[test]
+    fn test_first_year() {
+        for epoch in [0, 364] {
+            let schedule = calculate_epoch_emission(epoch);
+            assert_eq!(schedule.emission, YEAR_ONE_DAILY);
+            assert_eq!(schedule.year_index, 0);
+            assert_eq!(schedule.next_halving_offset, Some(365));
+        }
+    }
+
+    #[test]
+    fn test_first_halving_boundary() {
+        let schedule = calculate_epoch_emission(365);
+        assert_eq!(schedule.emission, YEAR_ONE_DAILY / 2);
+        assert_eq!(schedule.year_index, 1);
+        assert_eq!(schedule.next_halving_offset, Some(730));
+    }
+
+    #[test]
+    fn test_final_halving_boundary() {
+        let before = calculate_epoch_emission(3649);
+        assert_eq!(before.emission, YEAR_ONE_DAILY >> 9);
+        assert_eq!(before.year_index, 9);
+        assert_eq!(before.next_halving_offset, Some(3650));
+
+        let at = calculate_epoch_emission(3650);
+        assert_eq!(at.emission, YEAR_ONE_DAILY >> 10);
+        assert_eq!(at.year_index, 10);
+        assert_eq!(at.next_halving_offset, None);
+    }
+
+    #[test]
+    fn test_emission_floors_after_year_ten() {
+        let floor = calculate_epoch_emission(3650).emission;
+        for epoch in [4015, 10_000, u64::MAX] {
+            let schedule = calculate_epoch_emission(epoch);
+            assert_eq!(schedule.emission, floor);
+            assert!(schedule.year_index >= FINAL_HALVING_YEAR);
+            assert_eq!(schedule.next_halving_offset, None);
+        }
+    }
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn operator_rewards(unclaimed_rewards: u64) -> OperatorRewards {
+        OperatorRewards {
+            operator: Pubkey::new_unique(),
+            total_earned: unclaimed_rewards,
+            total_claimed: 0,
+            unclaimed_rewards,
+            last_claim_time: 0,
+            uptime_percentage: 100,
+            latency_score: 100,
+            throughput_score: 100,
+            requests_served: 0,
+            last_performance_epoch: 0,
+            bump: 255,
+            frozen_rewards: 0,
+            dispute_expires_at: 0,
+            first_active_epoch: 0,
+            first_epoch_share_bps: PERFORMANCE_SCALE,
+            claim_delegate: Pubkey::default(),
+            registered_payout_account: Pubkey::default(),
+            flagged_attestation: PerformanceAttestation::default(),
+            flagged_anomaly: MetricAnomaly::None,
+        }
+    }
+
+    #[test]
+    fn test_delegate_claims_only_to_registered_account() {
+        let mut rewards = operator_rewards(1_000);
+        let delegate = Pubkey::new_unique();
+        let payout = Pubkey::new_unique();
+        rewards.claim_delegate = delegate;
+
+        // No payout account registered yet
+        let err = rewards.authorize_claim(delegate, payout, false).unwrap_err();
+        assert_eq!(err, RewardsError::UnregisteredPayoutAccount.into());
+
+        rewards.registered_payout_account = payout;
+        rewards.authorize_claim(delegate, payout, false).unwrap();
+
+        let err = rewards.authorize_claim(delegate, Pubkey::new_unique(), false).unwrap_err();
+        assert_eq!(err, RewardsError::UnregisteredPayoutAccount.into());
+        let err = rewards.authorize_claim(delegate, payout, true).unwrap_err();
+        assert_eq!(err, RewardsError::DelegateCannotClose.into());
+
+        // The operator still claims anywhere and can close
+        rewards.authorize_claim(rewards.operator, Pubkey::new_unique(), true).unwrap();
+    }
+
+    #[test]
+    fn test_revoked_or_unknown_delegate_cannot_claim() {
+        let mut rewards = operator_rewards(1_000);
+        let delegate = Pubkey::new_unique();
+        let payout = Pubkey::new_unique();
+        rewards.claim_delegate = delegate;
+        rewards.registered_payout_account = payout;
+
+        let err = rewards.authorize_claim(Pubkey::new_unique(), payout, false).unwrap_err();
+        assert_eq!(err, RewardsError::UnauthorizedOperator.into());
+
+        rewards.claim_delegate = Pubkey::default();
+        let err = rewards.authorize_claim(delegate, payout, false).unwrap_err();
+        assert_eq!(err, RewardsError::UnauthorizedOperator.into());
+        // A default claimant never matches a cleared delegate
+        assert!(rewards.authorize_claim(Pubkey::default(), payout, false).is_err());
+    }
+
+    #[test]
+    fn test_funded_vault_pays_full_claim() {
+        assert_eq!(vault_payout(1_000, 1_000, false).unwrap(), 1_000);
+        assert_eq!(vault_payout(1_000, 5_000, true).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_partial_claim_leaves_remainder_claimable() {
+        let mut rewards = operator_rewards(1_000);
+        let paid = vault_payout(rewards.claimable(NOW), 600, true).unwrap();
+        assert_eq!(paid, 600);
+
+        rewards.unclaimed_rewards -= paid;
+        assert_eq!(rewards.claimable(NOW), 400);
+        // Once the vault is refunded the remainder pays out in full
+        assert_eq!(vault_payout(rewards.claimable(NOW), 10_000, false).unwrap(), 400);
+    }
+
+    #[test]
+    fn test_vault_shortfall_fails_cleanly() {
+        let err = vault_payout(1_000, 999, false).unwrap_err();
+        assert_eq!(err, RewardsError::InsufficientVaultBalance.into());
+        // An empty vault has nothing to pay even partially
+        let err = vault_payout(1_000, 0, true).unwrap_err();
+        assert_eq!(err, RewardsError::InsufficientVaultBalance.into());
+    }
+
+    #[test]
+    fn test_dispute_freezes_claimable() {
+        let mut rewards = operator_rewards(1_000);
+        assert_eq!(rewards.claimable(NOW), 1_000);
+
+        let expires_at = rewards.open_dispute(400, NOW).unwrap();
+        assert_eq!(expires_at, NOW + REWARD_DISPUTE_WINDOW);
+        // A claim during the dispute pays only the unfrozen part
+        assert_eq!(rewards.claimable(NOW), 600);
+        assert_eq!(rewards.claimable(expires_at - 1), 600);
+        // Unresolved disputes lapse after the window
+        assert_eq!(rewards.claimable(expires_at), 1_000);
+
+        assert!(rewards.open_dispute(100, NOW + 1).is_err());
+        assert!(rewards.open_dispute(100, expires_at).is_ok());
+    }
+
+    #[test]
+    fn test_dispute_amount_bounded_by_unclaimed() {
+        let mut rewards = operator_rewards(1_000);
+        assert!(rewards.open_dispute(0, NOW).is_err());
+        assert!(rewards.open_dispute(1_001, NOW).is_err());
+    }
+
+    #[test]
+    fn test_confirmed_dispute_deducts_frozen() {
+        let mut rewards = operator_rewards(1_000);
+        rewards.open_dispute(400, NOW).unwrap();
+
+        assert_eq!(rewards.resolve_dispute(true, NOW + 10).unwrap(), 400);
+        assert_eq!(rewards.unclaimed_rewards, 600);
+        assert_eq!(rewards.total_earned, 600);
+        assert_eq!(rewards.claimable(NOW + 10), 600);
+        assert!(rewards.resolve_dispute(true, NOW + 10).is_err());
+    }
+
+    #[test]
+    fn test_released_dispute_restores_claimable() {
+        let mut rewards = operator_rewards(1_000);
+        rewards.open_dispute(400, NOW).unwrap();
+
+        assert_eq!(rewards.resolve_dispute(false, NOW + 10).unwrap(), 400);
+        assert_eq!(rewards.unclaimed_rewards, 1_000);
+        assert_eq!(rewards.claimable(NOW + 10), 1_000);
+    }
+
+    #[test]
+    fn test_lapsed_dispute_cannot_be_resolved() {
+        let mut rewards = operator_rewards(1_000);
+        rewards.open_dispute(400, NOW).unwrap();
+        assert!(rewards
+            .resolve_dispute(true, NOW + REWARD_DISPUTE_WINDOW)
+            .is_err());
+    }
+
+    #[test]
+    fn test_oracle_quorum_needs_majority_of_active() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut oracles = [OracleInfo::default(); 10];
+        for (oracle, key) in oracles.iter_mut().zip(&keys) {
+            oracle.pubkey = key.to_bytes();
+            oracle.is_active = true;
+        }
+        // Inactive oracles don't count towards or against the quorum
+        oracles[3].is_active = false;
+
+        assert!(!oracle_quorum_reached(&oracles, &keys[..1]));
+        assert!(oracle_quorum_reached(&oracles, &keys[..2]));
+        assert!(!oracle_quorum_reached(&oracles, &[keys[0], keys[3]]));
+        assert!(!oracle_quorum_reached(&[OracleInfo::default(); 10], &keys));
+    }
+
+    const CLIFF: u64 = DEFAULT_PERFORMANCE_CLIFF as u64;
+    const KNEE: u64 = DEFAULT_PERFORMANCE_KNEE as u64;
+
+    #[test]
+    fn test_scores_below_cliff_earn_nothing() {
+        for score in [0, 2_000, 4_000, 4_999] {
+            assert_eq!(effective_performance(score, CLIFF, KNEE), 0);
+        }
+    }
+
+    #[test]
+    fn test_scores_between_cliff_and_knee_scale_quadratically() {
+        let at_cliff = effective_performance(5_000, CLIFF, KNEE);
+        assert_eq!(at_cliff, 3_125);
+        assert!(at_cliff > 0 && at_cliff < 5_000);
+        assert_eq!(effective_performance(6_000, CLIFF, KNEE), 4_500);
+        // Continuous at the knee
+        assert_eq!(effective_performance(7_999, CLIFF, KNEE), 7_998);
+    }
+
+    #[test]
+    fn test_scores_from_knee_stay_linear() {
+        for score in [8_000, 9_000, 9_500, 10_000] {
+            assert_eq!(effective_performance(score, CLIFF, KNEE), score);
+        }
+    }
+
+    #[test]
+    fn test_zeroed_curve_is_fully_linear() {
+        for score in [0, 1, 4_000, 10_000] {
+            assert_eq!(effective_performance(score, 0, 0), score);
+        }
+    }
+
+    const GRACE: u64 = DEFAULT_STALENESS_GRACE_EPOCHS as u64;
+    const CUTOFF: u64 = DEFAULT_STALENESS_CUTOFF_EPOCHS as u64;
+
+    #[test]
+    fn test_fresh_attestation_keeps_full_score() {
+        for staleness in [0, 1, GRACE] {
+            assert_eq!(decay_stale_performance(9_000, staleness, GRACE, CUTOFF), 9_000);
+        }
+    }
+
+    #[test]
+    fn test_stale_attestation_decays_partially() {
+        // 5 epochs stale: 5 of the 7 decay epochs remain
+        assert_eq!(decay_stale_performance(7_000, 5, GRACE, CUTOFF), 5_000);
+        assert_eq!(decay_stale_performance(7_000, 9, GRACE, CUTOFF), 1_000);
+    }
+
+    #[test]
+    fn test_long_stale_attestation_earns_nothing() {
+        for staleness in [CUTOFF, 12, u64::MAX] {
+            assert_eq!(decay_stale_performance(10_000, staleness, GRACE, CUTOFF), 0);
+        }
+        // Decay disabled
+        assert_eq!(decay_stale_performance(10_000, 12, GRACE, 0), 10_000);
+    }
+
+    #[test]
+    fn test_event_uses_absolute_epochs() {
+        let event = EmissionScheduleEvent::for_epoch(1_000, 900);
+        assert_eq!(event.epoch, 1_000);
+        assert_eq!(event.year_index, 0);
+        assert_eq!(event.emission, YEAR_ONE_DAILY);
+        assert_eq!(event.next_halving_epoch, Some(900 + 365));
+    }
+
+    #[test]
+    fn test_epochs_before_joining_earn_nothing() {
+        let mut rewards = operator_rewards(0);
+        rewards.first_active_epoch = 10;
+
+        // Claiming epochs 5-15 pays only for 10-15
+        assert_eq!(rewards.range_emission(5, 11, 0).unwrap(), 6 * YEAR_ONE_DAILY as u128);
+        assert_eq!(rewards.range_emission(0, 10, 0).unwrap(), 0);
+        assert_eq!(rewards.range_emission(10, 6, 0).unwrap(), 6 * YEAR_ONE_DAILY as u128);
+    }
+
+    #[test]
+    fn test_first_epoch_prorated() {
+        let mut rewards = operator_rewards(0);
+        rewards.first_active_epoch = 10;
+        rewards.first_epoch_share_bps = remaining_epoch_share_bps(NOW, NOW + 18 * 60 * 60);
+        assert_eq!(rewards.first_epoch_share_bps, 2_500);
+
+        let quarter = YEAR_ONE_DAILY as u128 * 2_500 / 10_000;
+        assert_eq!(rewards.range_emission(5, 11, 0).unwrap(), quarter + 5 * YEAR_ONE_DAILY as u128);
+        assert_eq!(rewards.range_emission(11, 5, 0).unwrap(), 5 * YEAR_ONE_DAILY as u128);
+    }
+
+    #[test]
+    fn test_remaining_epoch_share() {
+        // Unknown epoch start: full share
+        assert_eq!(remaining_epoch_share_bps(0, NOW), 10_000);
+        assert_eq!(remaining_epoch_share_bps(NOW, NOW), 10_000);
+        assert_eq!(remaining_epoch_share_bps(NOW, NOW + 12 * 60 * 60), 5_000);
+        // Epoch overdue for advancing: nothing left
+        assert_eq!(remaining_epoch_share_bps(NOW, NOW + 3 * 24 * 60 * 60), 0);
+    }
+
+    fn secondary_incentive(budget: u64, start_epoch: u64, end_epoch: u64) -> SecondaryIncentive {
+        SecondaryIncentive {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            budget,
+            allocated: 0,
+            claimed: 0,
+            start_epoch,
+            end_epoch,
+            closed: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_secondary_allocation_proportional_to_primary() {
+        let incentive = secondary_incentive(9_000, 0, 90);
+        let window = emission_between(0, 90, 0).unwrap();
+        let emission = 5 * YEAR_ONE_DAILY as u128;
+
+        let small = incentive.allocation(YEAR_ONE_DAILY, emission, emission, window).unwrap();
+        let large = incentive.allocation(2 * YEAR_ONE_DAILY, emission, emission, window).unwrap();
+        assert!(small > 0);
+        assert_eq!(large, 2 * small);
+
+        // Earning the whole window's emission earns the whole budget
+        assert_eq!(
+            incentive.allocation(window as u64, window, window, window).unwrap(),
+            9_000
+        );
+    }
+
+    #[test]
+    fn test_secondary_allocation_only_for_overlapping_epochs() {
+        let incentive = secondary_incentive(9_000, 10, 100);
+        assert_eq!(incentive.overlap(5, 10), (10, 5));
+        assert_eq!(incentive.overlap(95, 10), (95, 5));
+        assert_eq!(incentive.overlap(0, 10), (10, 0));
+        assert_eq!(incentive.overlap(100, 10), (100, 0));
+
+        // Half of the calculated range overlaps: half the primary reward counts
+        let window = emission_between(10, 100, 0).unwrap();
+        let emission = 10 * YEAR_ONE_DAILY as u128;
+        let full = incentive.allocation(4 * YEAR_ONE_DAILY, emission, emission, window).unwrap();
+        let half = incentive.allocation(4 * YEAR_ONE_DAILY, emission, emission / 2, window).unwrap();
+        assert!(full > 0);
+        assert_eq!(half, full / 2);
+        assert_eq!(incentive.allocation(4 * YEAR_ONE_DAILY, emission, 0, window).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_secondary_allocation_capped_by_budget() {
+        let mut incentive = secondary_incentive(1_000, 0, 10);
+        let window = emission_between(0, 10, 0).unwrap();
+
+        incentive.allocated = 900;
+        assert_eq!(incentive.unallocated(), 100);
+        assert_eq!(incentive.allocation(u64::MAX, window, window, window).unwrap(), 100);
+
+        // Closed programs allocate nothing
+        incentive.closed = true;
+        assert_eq!(incentive.allocation(u64::MAX, window, window, window).unwrap(), 0);
+    }
+
+    fn attestation(uptime: u8, throughput: u8, requests_served: u64, epoch: u64) -> PerformanceAttestation {
+        PerformanceAttestation {
+            metric_semantics_version: METRIC_SEMANTICS_VERSION,
+            uptime_percentage: uptime,
+            latency_score: 90,
+            throughput_score: throughput,
+            requests_served,
+            epoch,
+        }
+    }
+
+    #[test]
+    fn test_attestation_semantics_and_ranges() {
+        assert!(attestation(100, 100, 1, 1).validate().is_ok());
+
+        let mut raw_millis = attestation(100, 100, 1, 1);
+        raw_millis.metric_semantics_version = 0;
+        assert_eq!(
+            raw_millis.validate().unwrap_err(),
+            RewardsError::UnsupportedMetricSemantics.into()
+        );
+
+        let mut out_of_range = attestation(100, 100, 1, 1);
+        out_of_range.latency_score = 250;
+        assert_eq!(
+            out_of_range.validate().unwrap_err(),
+            RewardsError::InvalidPercentage.into()
+        );
+    }
+
+    #[test]
+    fn test_attestation_anomalies() {
+        assert_eq!(attestation(100, 100, 0, 1).anomaly(0), Some(MetricAnomaly::ThroughputWithoutRequests));
+        assert_eq!(attestation(100, 99, 0, 1).anomaly(0), None);
+
+        // Perfect uptime is fine for the next epoch or a first attestation
+        assert_eq!(attestation(100, 80, 10, 5).anomaly(4), None);
+        assert_eq!(attestation(100, 80, 10, 5).anomaly(0), None);
+        assert_eq!(attestation(100, 80, 10, 5).anomaly(3), Some(MetricAnomaly::UptimeAcrossGap));
+        assert_eq!(attestation(99, 80, 10, 5).anomaly(3), None);
+    }
+
+    #[test]
+    fn test_plausible_attestation_recorded() {
+        let mut rewards = operator_rewards(0);
+        let outcome = rewards.submit_attestation(attestation(95, 80, 1_000, 1)).unwrap();
+
+        assert_eq!(outcome, None);
+        assert_eq!(rewards.uptime_percentage, 95);
+        assert_eq!(rewards.throughput_score, 80);
+        assert_eq!(rewards.requests_served, 1_000);
+        assert_eq!(rewards.last_performance_epoch, 1);
+        assert_eq!(rewards.flagged_anomaly, MetricAnomaly::None);
+    }
+
+    #[test]
+    fn test_implausible_attestation_withheld() {
+        let mut rewards = operator_rewards(0);
+        rewards.submit_attestation(attestation(95, 80, 1_000, 1)).unwrap();
+
+        let held = attestation(100, 80, 500, 4);
+        let outcome = rewards.submit_attestation(held).unwrap();
+
+        assert_eq!(outcome, Some(MetricAnomaly::UptimeAcrossGap));
+        assert_eq!(rewards.uptime_percentage, 95);
+        assert_eq!(rewards.requests_served, 1_000);
+        assert_eq!(rewards.last_performance_epoch, 1);
+        assert_eq!(rewards.flagged_attestation, held);
+        assert_eq!(rewards.flagged_anomaly, MetricAnomaly::UptimeAcrossGap);
+
+        // A later plausible attestation supersedes the held one
+        rewards.submit_attestation(attestation(90, 80, 200, 5)).unwrap();
+        assert_eq!(rewards.flagged_anomaly, MetricAnomaly::None);
+        assert_eq!(
+            rewards.accept_flagged().unwrap_err(),
+            RewardsError::NoFlaggedAttestation.into()
+        );
+    }
+
+    #[test]
+    fn test_override_records_flagged_attestation() {
+        let mut rewards = operator_rewards(0);
+        let held = attestation(100, 100, 0, 1);
+        rewards.submit_attestation(held).unwrap();
+
+        assert_eq!(rewards.accept_flagged().unwrap(), held);
+        assert_eq!(rewards.uptime_percentage, 100);
+        assert_eq!(rewards.throughput_score, 100);
+        assert_eq!(rewards.last_performance_epoch, 1);
+        assert_eq!(rewards.flagged_anomaly, MetricAnomaly::None);
+
+        // Only once
+        assert_eq!(
+            rewards.accept_flagged().unwrap_err(),
+            RewardsError::NoFlaggedAttestation.into()
+        );
+    }
+
+    #[test]
+    fn test_override_rejects_superseded_epoch() {
+        let mut rewards = operator_rewards(0);
+        rewards.flagged_attestation = attestation(100, 100, 0, 3);
+        rewards.flagged_anomaly = MetricAnomaly::ThroughputWithoutRequests;
+        rewards.last_performance_epoch = 3;
+
+        assert_eq!(
+            rewards.accept_flagged().unwrap_err(),
+            RewardsError::EpochNotIncreasing.into()
+        );
+        assert_eq!(rewards.flagged_anomaly, MetricAnomaly::ThroughputWithoutRequests);
+    }
+
+    fn reward_pool() -> RewardPool {
+        RewardPool {
+            authority: Pubkey::new_unique(),
+            reward_vault: Pubkey::new_unique(),
+            total_distributed: 1_234,
+            current_epoch: 12,
+            start_epoch: 0,
+            total_network_requests: 0,
+            last_authority_recording: NOW,
+            bump: 254,
+            min_claim_amount: 5,
+            claim_cooldown_seconds: 60,
+            performance_cliff: 1_000,
+            performance_knee: 2_000,
+            epoch_started_at: NOW,
+            staleness_grace_epochs: 1,
+            staleness_cutoff_epochs: 2,
+        }
+    }
+
+    fn legacy_pool_bytes(pool: &RewardPool, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        pool.try_serialize(&mut bytes).unwrap();
+        bytes.truncate(len);
+        assert!(RewardPool::try_deserialize(&mut &bytes[..]).is_err());
+        bytes.resize(RewardPool::MAX_SIZE, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_migrate_legacy_reward_pool() {
+        let current = reward_pool();
+
+        // Before the claim policy, the performance curve, epoch_started_at
+        // and the staleness policy
+        for (len, cliff, grace) in [
+            (RewardPool::MAX_SIZE - 32, DEFAULT_PERFORMANCE_CLIFF, DEFAULT_STALENESS_GRACE_EPOCHS),
+            (RewardPool::MAX_SIZE - 16, DEFAULT_PERFORMANCE_CLIFF, DEFAULT_STALENESS_GRACE_EPOCHS),
+            (RewardPool::MAX_SIZE - 12, 1_000, DEFAULT_STALENESS_GRACE_EPOCHS),
+            (RewardPool::MAX_SIZE - 4, 1_000, DEFAULT_STALENESS_GRACE_EPOCHS),
+        ] {
+            let bytes = legacy_pool_bytes(&current, len);
+            let mut pool = RewardPool::try_deserialize(&mut &bytes[..]).unwrap();
+            pool.fill_appended_defaults(len);
+
+            assert_eq!(pool.authority, current.authority);
+            assert_eq!(pool.total_distributed, 1_234);
+            assert_eq!(pool.bump, 254);
+            assert_eq!(pool.performance_cliff, cliff);
+            assert_eq!(pool.staleness_grace_epochs, grace);
+            assert!(pool.staleness_grace_epochs < pool.staleness_cutoff_epochs);
+            assert!(pool.performance_cliff <= pool.performance_knee);
+        }
+
+        // A pool from before the claim policy has it disabled, and no epoch start
+        let bytes = legacy_pool_bytes(&current, RewardPool::MAX_SIZE - 32);
+        let pool = RewardPool::try_deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(pool.min_claim_amount, 0);
+        assert_eq!(pool.claim_cooldown_seconds, 0);
+        assert_eq!(pool.epoch_started_at, 0);
+    }
+
+    #[test]
+    fn test_current_reward_pool_keeps_settings() {
+        let mut pool = reward_pool();
+        pool.fill_appended_defaults(RewardPool::MAX_SIZE);
+        assert_eq!(pool.performance_cliff, 1_000);
+        assert_eq!(pool.performance_knee, 2_000);
+        assert_eq!(pool.staleness_grace_epochs, 1);
+        assert_eq!(pool.staleness_cutoff_epochs, 2);
+    }
 }
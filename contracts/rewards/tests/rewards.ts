@@ -4,6 +4,7 @@ import { Rewards } from "../target/types/rewards";
 import { expect } from "chai";
 import {
   createMint,
+  createAccount,
   mintTo,
   getAccount,
   TOKEN_PROGRAM_ID,
@@ -154,6 +155,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -169,6 +171,11 @@ describe("rewards", () => {
       expect(rewards.unclaimedRewards.toString()).to.equal("0");
       expect(rewards.performanceScore).to.equal(100);
       expect(rewards.uptimePercentage).to.equal(0);
+
+      // Emission starts at the pool's current epoch, prorated for the part already elapsed
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(rewards.firstActiveEpoch.toString()).to.equal(pool.currentEpoch.toString());
+      expect(rewards.firstEpochShareBps).to.be.at.most(10000);
     });
   });
 
@@ -185,6 +192,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -261,6 +269,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -306,6 +315,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA2,
           operator: operator2.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -359,6 +369,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -420,13 +431,14 @@ describe("rewards", () => {
       const beforeBalance = await getAccount(provider.connection, operatorTokenAccount);
 
       await program.methods
-        .claimRewards()
+        .claimRewards(false, false)
         .accounts({
           rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           rewardVault,
           operatorTokenAccount,
           operator: operator.publicKey,
+          claimant: operator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([operator])
@@ -445,13 +457,14 @@ describe("rewards", () => {
     it("Rejects claim when no rewards available", async () => {
       try {
         await program.methods
-          .claimRewards()
+          .claimRewards(false, false)
           .accounts({
             rewardPool: rewardPoolPDA,
             operatorRewards: operatorRewardsPDA,
             rewardVault,
             operatorTokenAccount,
             operator: operator.publicKey,
+            claimant: operator.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([operator])
@@ -464,6 +477,810 @@ describe("rewards", () => {
     });
   });
 
+  describe("Claim Policy", () => {
+    let operatorTokenAccount: anchor.web3.PublicKey;
+
+    // Creates an operator with some accrued rewards
+    async function setupOperatorWithRewards(): Promise<{
+      operator: anchor.web3.Keypair;
+      operatorRewardsPDA: anchor.web3.PublicKey;
+    }> {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      const [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .recordPerformance(100, 100, new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await accrue(operatorRewardsPDA);
+
+      operatorTokenAccount = await createTokenAccount(operator.publicKey, operator);
+      return { operator, operatorRewardsPDA };
+    }
+
+    async function accrue(operatorRewardsPDA: anchor.web3.PublicKey) {
+      await program.methods
+        .calculateRewards(new anchor.BN(100_000_000_000), new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    async function setPolicy(minClaimAmount: anchor.BN, cooldownSeconds: number) {
+      await program.methods
+        .updateClaimPolicy(minClaimAmount, new anchor.BN(cooldownSeconds))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    function claim(
+      operator: anchor.web3.Keypair,
+      operatorRewardsPDA: anchor.web3.PublicKey,
+      closeAccount: boolean
+    ) {
+      return program.methods
+        .claimRewards(closeAccount, false)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          rewardVault,
+          operatorTokenAccount,
+          operator: operator.publicKey,
+          claimant: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+    }
+
+    afterEach(async () => {
+      // Leave the pool without a claim policy for the remaining suites
+      await setPolicy(new anchor.BN(0), 0);
+    });
+
+    it("Allows authority to update the claim policy", async () => {
+      await setPolicy(new anchor.BN(5_000_000_000), 3600);
+
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(pool.minClaimAmount.toString()).to.equal("5000000000");
+      expect(pool.claimCooldownSeconds.toNumber()).to.equal(3600);
+    });
+
+    it("Rejects unauthorized claim policy update", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await program.methods
+          .updateClaimPolicy(new anchor.BN(0), new anchor.BN(0))
+          .accounts({
+            rewardPool: rewardPoolPDA,
+            authority: unauthorized.publicKey,
+          })
+          .signers([unauthorized])
+          .rpc();
+
+        expect.fail("Should have rejected unauthorized policy update");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+
+    it("Rejects claims below the minimum", async () => {
+      const { operator, operatorRewardsPDA } = await setupOperatorWithRewards();
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      await setPolicy(rewards.unclaimedRewards.add(new anchor.BN(1)), 0);
+
+      try {
+        await claim(operator, operatorRewardsPDA, false);
+        expect.fail("Should have rejected sub-minimum claim");
+      } catch (error) {
+        expect(error.toString()).to.include("ClaimTooSmall");
+      }
+    });
+
+    it("Allows a sub-minimum claim when closing the account", async () => {
+      const { operator, operatorRewardsPDA } = await setupOperatorWithRewards();
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      await setPolicy(rewards.unclaimedRewards.add(new anchor.BN(1)), 0);
+
+      await claim(operator, operatorRewardsPDA, true);
+
+      const balance = await getAccount(provider.connection, operatorTokenAccount);
+      expect(balance.amount.toString()).to.equal(rewards.unclaimedRewards.toString());
+
+      const closed = await provider.connection.getAccountInfo(operatorRewardsPDA);
+      expect(closed).to.be.null;
+    });
+
+    it("Enforces the cooldown against the last claim time", async () => {
+      const { operator, operatorRewardsPDA } = await setupOperatorWithRewards();
+      await setPolicy(new anchor.BN(0), 3600);
+
+      await claim(operator, operatorRewardsPDA, false);
+      const afterFirst = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(afterFirst.lastClaimTime.toNumber()).to.be.greaterThan(0);
+
+      await accrue(operatorRewardsPDA);
+
+      try {
+        await claim(operator, operatorRewardsPDA, false);
+        expect.fail("Should have rejected claim during cooldown");
+      } catch (error) {
+        expect(error.toString()).to.include("ClaimCooldownActive");
+      }
+    });
+  });
+
+  describe("Claim Delegates", () => {
+    // Creates an operator with accrued rewards, a payout account and a funded
+    // delegate key
+    async function setupDelegatedOperator(): Promise<{
+      operator: anchor.web3.Keypair;
+      delegate: anchor.web3.Keypair;
+      operatorRewardsPDA: anchor.web3.PublicKey;
+      payoutAccount: anchor.web3.PublicKey;
+    }> {
+      const operator = anchor.web3.Keypair.generate();
+      const delegate = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      await fundAccount(delegate.publicKey);
+      const [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .recordPerformance(100, 100, new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .calculateRewards(new anchor.BN(100_000_000_000), new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const payoutAccount = await createTokenAccount(operator.publicKey, operator);
+      await program.methods
+        .registerPayoutAccount()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          rewardVault,
+          operatorRewards: operatorRewardsPDA,
+          payoutAccount,
+          operator: operator.publicKey,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .setClaimDelegate(delegate.publicKey)
+        .accounts({
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+        })
+        .signers([operator])
+        .rpc();
+
+      return { operator, delegate, operatorRewardsPDA, payoutAccount };
+    }
+
+    function delegateClaim(
+      operator: anchor.web3.Keypair,
+      delegate: anchor.web3.Keypair,
+      operatorRewardsPDA: anchor.web3.PublicKey,
+      operatorTokenAccount: anchor.web3.PublicKey
+    ) {
+      return program.methods
+        .claimRewards(false, false)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          rewardVault,
+          operatorTokenAccount,
+          operator: operator.publicKey,
+          claimant: delegate.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([delegate])
+        .rpc();
+    }
+
+    it("Lets the delegate claim to the registered payout account", async () => {
+      const { operator, delegate, operatorRewardsPDA, payoutAccount } = await setupDelegatedOperator();
+      const before = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(before.claimDelegate.toString()).to.equal(delegate.publicKey.toString());
+      expect(before.registeredPayoutAccount.toString()).to.equal(payoutAccount.toString());
+
+      let claimedBy: anchor.web3.PublicKey | null = null;
+      const listener = program.addEventListener("rewardsClaimedEvent", (event) => {
+        claimedBy = event.claimedBy;
+      });
+      await delegateClaim(operator, delegate, operatorRewardsPDA, payoutAccount);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const balance = await getAccount(provider.connection, payoutAccount);
+      expect(balance.amount.toString()).to.equal(before.unclaimedRewards.toString());
+      expect(claimedBy?.toString()).to.equal(delegate.publicKey.toString());
+    });
+
+    it("Rejects delegate claims to any other account", async () => {
+      const { operator, delegate, operatorRewardsPDA } = await setupDelegatedOperator();
+      const delegateAccount = await createTokenAccount(delegate.publicKey, delegate);
+
+      try {
+        await delegateClaim(operator, delegate, operatorRewardsPDA, delegateAccount);
+        expect.fail("Should have rejected claim to an unregistered account");
+      } catch (error) {
+        expect(error.toString()).to.include("UnregisteredPayoutAccount");
+      }
+    });
+
+    it("Stops delegate claims once revoked", async () => {
+      const { operator, delegate, operatorRewardsPDA, payoutAccount } = await setupDelegatedOperator();
+
+      await program.methods
+        .revokeClaimDelegate()
+        .accounts({
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+        })
+        .signers([operator])
+        .rpc();
+
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(rewards.claimDelegate.toString()).to.equal(anchor.web3.PublicKey.default.toString());
+
+      try {
+        await delegateClaim(operator, delegate, operatorRewardsPDA, payoutAccount);
+        expect.fail("Should have rejected claim by a revoked delegate");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedOperator");
+      }
+    });
+
+    it("Only lets the operator set a delegate", async () => {
+      const { delegate, operatorRewardsPDA } = await setupDelegatedOperator();
+
+      try {
+        await program.methods
+          .setClaimDelegate(delegate.publicKey)
+          .accounts({
+            operatorRewards: operatorRewardsPDA,
+            operator: delegate.publicKey,
+          })
+          .signers([delegate])
+          .rpc();
+        expect.fail("Should have rejected a delegate setting itself");
+      } catch (error) {
+        expect(error.toString()).to.match(/UnauthorizedOperator|ConstraintSeeds/);
+      }
+    });
+  });
+
+  describe("Performance Curve", () => {
+    const DEFAULT_CLIFF = 5000;
+    const DEFAULT_KNEE = 8000;
+
+    function setCurve(cliff: number, knee: number, authority?: anchor.web3.Keypair) {
+      const builder = program.methods
+        .updatePerformanceCurve(cliff, knee)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        });
+      return (authority ? builder.signers([authority]) : builder).rpc();
+    }
+
+    afterEach(async () => {
+      await setCurve(DEFAULT_CLIFF, DEFAULT_KNEE);
+    });
+
+    // Score shaping (4999 -> 0, 5000 -> 3125, 8000+ linear) is covered by
+    // the effective_performance unit tests in lib.rs
+    it("Starts with the default cliff and knee", async () => {
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(pool.performanceCliff).to.equal(DEFAULT_CLIFF);
+      expect(pool.performanceKnee).to.equal(DEFAULT_KNEE);
+    });
+
+    it("Allows authority to update the curve and emits an event", async () => {
+      let event = null;
+      const listener = program.addEventListener("performanceCurveUpdatedEvent", (e) => {
+        event = e;
+      });
+
+      await setCurve(4000, 9000);
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(pool.performanceCliff).to.equal(4000);
+      expect(pool.performanceKnee).to.equal(9000);
+      expect(event).to.not.be.null;
+      expect(event.cliff).to.equal(4000);
+      expect(event.knee).to.equal(9000);
+      expect(event.authority.toString()).to.equal(provider.wallet.publicKey.toString());
+    });
+
+    it("Rejects a cliff above the knee or a knee above 10000", async () => {
+      for (const [cliff, knee] of [[8001, 8000], [5000, 10001]]) {
+        try {
+          await setCurve(cliff, knee);
+          expect.fail("Should have rejected an invalid curve");
+        } catch (error) {
+          expect(error.toString()).to.include("InvalidPerformanceCurve");
+        }
+      }
+    });
+
+    it("Rejects unauthorized curve update", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await setCurve(0, 0, unauthorized);
+        expect.fail("Should have rejected unauthorized curve update");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+  });
+
+  describe("Performance Staleness", () => {
+    const DEFAULT_GRACE = 3;
+    const DEFAULT_CUTOFF = 10;
+
+    function setPolicy(grace: number, cutoff: number, authority?: anchor.web3.Keypair) {
+      const builder = program.methods
+        .updateStalenessPolicy(grace, cutoff)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        });
+      return (authority ? builder.signers([authority]) : builder).rpc();
+    }
+
+    afterEach(async () => {
+      await setPolicy(DEFAULT_GRACE, DEFAULT_CUTOFF);
+    });
+
+    // Decay (fresh -> full, 5 stale -> partial, 12 stale -> zero) is covered
+    // by the decay_stale_performance unit tests in lib.rs
+    it("Starts with the default grace and cutoff", async () => {
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(pool.stalenessGraceEpochs).to.equal(DEFAULT_GRACE);
+      expect(pool.stalenessCutoffEpochs).to.equal(DEFAULT_CUTOFF);
+    });
+
+    it("Allows authority to update the policy and emits an event", async () => {
+      let event = null;
+      const listener = program.addEventListener("stalenessPolicyUpdatedEvent", (e) => {
+        event = e;
+      });
+
+      await setPolicy(5, 20);
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(pool.stalenessGraceEpochs).to.equal(5);
+      expect(pool.stalenessCutoffEpochs).to.equal(20);
+      expect(event).to.not.be.null;
+      expect(event.graceEpochs).to.equal(5);
+      expect(event.cutoffEpochs).to.equal(20);
+    });
+
+    it("Rejects a grace at or above the cutoff", async () => {
+      try {
+        await setPolicy(10, 10);
+        expect.fail("Should have rejected an invalid staleness policy");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidStalenessPolicy");
+      }
+    });
+
+    it("Reports staleness in the rewards calculated event", async () => {
+      const event = program.idl.types.find((t) => t.name === "rewardsCalculatedEvent");
+      expect(event).to.not.be.undefined;
+      const fields = (event.type as any).fields.map((f) => f.name);
+      expect(fields).to.include("stalenessEpochs");
+    });
+
+    it("Rejects unauthorized policy update", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await setPolicy(0, 0, unauthorized);
+        expect.fail("Should have rejected unauthorized policy update");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+  });
+
+  describe("Pool Migration", () => {
+    // The current program can't write a shorter pool, so growing a legacy
+    // one and filling its defaults are covered by
+    // test_migrate_legacy_reward_pool
+
+    it("Leaves a current pool untouched", async () => {
+      const before = await program.account.rewardPool.fetch(rewardPoolPDA);
+      await program.methods
+        .migrateRewardPool()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          authority: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(after.performanceCliff).to.equal(before.performanceCliff);
+      expect(after.stalenessGraceEpochs).to.equal(before.stalenessGraceEpochs);
+      expect(after.minClaimAmount.toString()).to.equal(before.minClaimAmount.toString());
+    });
+
+    it("Only lets the authority migrate the pool", async () => {
+      const impostor = anchor.web3.Keypair.generate();
+      await fundAccount(impostor.publicKey);
+
+      try {
+        await program.methods
+          .migrateRewardPool()
+          .accounts({
+            rewardPool: rewardPoolPDA,
+            authority: impostor.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected a non-authority");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+  });
+
+  describe("Metric Plausibility", () => {
+    const METRIC_SEMANTICS_VERSION = 1;
+
+    let operator: anchor.web3.Keypair;
+    let operatorRewardsPDA: anchor.web3.PublicKey;
+
+    function accept(authority?: anchor.web3.Keypair) {
+      const builder = program.methods
+        .acceptFlaggedAttestation()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        });
+      return (authority ? builder.signers([authority]) : builder).rpc();
+    }
+
+    before(async () => {
+      operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+    });
+
+    // Plausible attestations being recorded directly, the uptime-gap check and
+    // superseding a held attestation are covered by the submit_attestation
+    // unit tests in lib.rs (authority recordings are rate limited to one a minute)
+    it("Rejects attestations with other score semantics", async () => {
+      try {
+        await program.methods
+          .recordPerformanceAuthority(0, 100, 100, 100, new anchor.BN(10), new anchor.BN(1))
+          .accounts({
+            rewardPool: rewardPoolPDA,
+            operatorRewards: operatorRewardsPDA,
+            authority: provider.wallet.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have rejected an unversioned attestation");
+      } catch (error) {
+        expect(error.toString()).to.include("UnsupportedMetricSemantics");
+      }
+    });
+
+    it("Rejects accepting when nothing is flagged", async () => {
+      try {
+        await accept();
+        expect.fail("Should have rejected accepting without a flagged attestation");
+      } catch (error) {
+        expect(error.toString()).to.include("NoFlaggedAttestation");
+      }
+    });
+
+    it("Withholds an implausible attestation and emits an anomaly event", async () => {
+      let event = null;
+      const listener = program.addEventListener("metricAnomalyEvent", (e) => {
+        event = e;
+      });
+
+      // Perfect throughput while serving no requests
+      await program.methods
+        .recordPerformanceAuthority(METRIC_SEMANTICS_VERSION, 100, 90, 100, new anchor.BN(0), new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(event).to.not.be.null;
+      expect(event.operator.toString()).to.equal(operator.publicKey.toString());
+      expect(event.anomaly).to.have.property("throughputWithoutRequests");
+      expect(event.epoch.toNumber()).to.equal(1);
+
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(rewards.lastPerformanceEpoch.toNumber()).to.equal(0);
+      expect(rewards.throughputScore).to.equal(0);
+      expect(rewards.flaggedAttestation.epoch.toNumber()).to.equal(1);
+      expect(rewards.flaggedAnomaly).to.have.property("throughputWithoutRequests");
+    });
+
+    it("Rejects unauthorized acceptance", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await accept(unauthorized);
+        expect.fail("Should have rejected unauthorized acceptance");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+
+    it("Records the flagged attestation on authority override", async () => {
+      let event = null;
+      const listener = program.addEventListener("performanceRecordedEvent", (e) => {
+        event = e;
+      });
+
+      await accept();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(event).to.not.be.null;
+      expect(event.throughput).to.equal(100);
+      expect(event.epoch.toNumber()).to.equal(1);
+
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(rewards.lastPerformanceEpoch.toNumber()).to.equal(1);
+      expect(rewards.uptimePercentage).to.equal(100);
+      expect(rewards.throughputScore).to.equal(100);
+      expect(rewards.flaggedAnomaly).to.have.property("none");
+    });
+  });
+
+  describe("Reward Disputes", () => {
+    const REASON_CID = "QmFaultyOracleAttestationReport";
+
+    let operator: anchor.web3.Keypair;
+    let operatorRewardsPDA: anchor.web3.PublicKey;
+    let operatorTokenAccount: anchor.web3.PublicKey;
+
+    async function accrue() {
+      await program.methods
+        .calculateRewards(new anchor.BN(100_000_000_000), new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    function flag(amount: anchor.BN) {
+      return program.methods
+        .flagRewardDispute(
+          operator.publicKey,
+          new anchor.BN(0),
+          new anchor.BN(1),
+          amount,
+          REASON_CID
+        )
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          oracleRegistry: null,
+          operatorRewards: operatorRewardsPDA,
+          arbiter: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    function resolve(confirm: boolean) {
+      return program.methods
+        .resolveRewardDispute(operator.publicKey, confirm)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          oracleRegistry: null,
+          operatorRewards: operatorRewardsPDA,
+          arbiter: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    function claim() {
+      return program.methods
+        .claimRewards(false, false)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          rewardVault,
+          operatorTokenAccount,
+          operator: operator.publicKey,
+          claimant: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+    }
+
+    beforeEach(async () => {
+      operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .recordPerformance(100, 100, new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await accrue();
+      operatorTokenAccount = await createTokenAccount(operator.publicKey, operator);
+    });
+
+    // Lapse of the 72-hour window is covered by the program unit tests
+    it("Freezing reduces what a claim pays out", async () => {
+      const before = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      const frozen = before.unclaimedRewards.divn(4);
+      await flag(frozen);
+
+      const flagged = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(flagged.frozenRewards.toString()).to.equal(frozen.toString());
+      expect(flagged.disputeExpiresAt.toNumber()).to.be.greaterThan(0);
+
+      await claim();
+
+      const balance = await getAccount(provider.connection, operatorTokenAccount);
+      expect(balance.amount.toString()).to.equal(
+        before.unclaimedRewards.sub(frozen).toString()
+      );
+      const after = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(after.unclaimedRewards.toString()).to.equal(frozen.toString());
+    });
+
+    it("Confirming a dispute deducts the frozen rewards", async () => {
+      const before = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      const frozen = before.unclaimedRewards.divn(4);
+      await flag(frozen);
+      await resolve(true);
+
+      const after = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      expect(after.unclaimedRewards.toString()).to.equal(
+        before.unclaimedRewards.sub(frozen).toString()
+      );
+      expect(after.totalEarned.toString()).to.equal(
+        before.totalEarned.sub(frozen).toString()
+      );
+      expect(after.frozenRewards.toString()).to.equal("0");
+    });
+
+    it("Releasing a dispute restores the claimable balance", async () => {
+      const before = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      await flag(before.unclaimedRewards.divn(4));
+      await resolve(false);
+
+      await claim();
+
+      const balance = await getAccount(provider.connection, operatorTokenAccount);
+      expect(balance.amount.toString()).to.equal(before.unclaimedRewards.toString());
+    });
+
+    it("Rejects disputes from anyone but the authority or an oracle quorum", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await fundAccount(outsider.publicKey);
+
+      try {
+        await program.methods
+          .flagRewardDispute(
+            operator.publicKey,
+            new anchor.BN(0),
+            new anchor.BN(1),
+            new anchor.BN(1),
+            REASON_CID
+          )
+          .accounts({
+            rewardPool: rewardPoolPDA,
+            oracleRegistry: null,
+            operatorRewards: operatorRewardsPDA,
+            arbiter: outsider.publicKey,
+          })
+          .signers([outsider])
+          .rpc();
+        expect.fail("Should have rejected an unauthorized dispute");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+  });
+
   describe("Pool Management", () => {
     it("Allows authority to fund pool", async () => {
       const funderTokenAccount = await createTokenAccount(
@@ -547,6 +1364,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -583,13 +1401,14 @@ describe("rewards", () => {
       const operatorTokenAccount = await createTokenAccount(operator.publicKey, operator);
 
       await program.methods
-        .claimRewards()
+        .claimRewards(false, false)
         .accounts({
           rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           rewardVault,
           operatorTokenAccount,
           operator: operator.publicKey,
+          claimant: operator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([operator])
@@ -604,6 +1423,282 @@ describe("rewards", () => {
     });
   });
 
+  describe("Secondary Incentives", () => {
+    const BUDGET = new anchor.BN(1_000_000_000_000); // 1M partner tokens (6 decimals)
+
+    let partnerMint: anchor.web3.PublicKey;
+    let incentivePDA: anchor.web3.PublicKey;
+    let secondaryVaultPDA: anchor.web3.PublicKey;
+    let authorityPartnerAccount: anchor.web3.PublicKey;
+
+    function getSecondaryIncentivePDA(partner: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("secondary_incentive"), partner.toBuffer()],
+        program.programId
+      );
+    }
+
+    function getSecondaryVaultPDA(incentive: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("secondary_vault"), incentive.toBuffer()],
+        program.programId
+      );
+    }
+
+    function getSecondaryRewardsPDA(
+      incentive: anchor.web3.PublicKey,
+      operator: anchor.web3.PublicKey
+    ): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("secondary_rewards"), incentive.toBuffer(), operator.toBuffer()],
+        program.programId
+      );
+    }
+
+    // Creates an operator enrolled in the program and accrues 10 epochs of
+    // rewards for the given stake
+    async function setupOperator(stakedAmount: anchor.BN): Promise<{
+      operator: anchor.web3.Keypair;
+      operatorRewardsPDA: anchor.web3.PublicKey;
+      secondaryRewardsPDA: anchor.web3.PublicKey;
+    }> {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      const [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+      const [secondaryRewardsPDA] = getSecondaryRewardsPDA(incentivePDA, operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .initializeSecondaryRewards()
+        .accounts({
+          secondaryIncentive: incentivePDA,
+          operatorRewards: operatorRewardsPDA,
+          secondaryRewards: secondaryRewardsPDA,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .recordPerformance(100, 100, new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .calculateRewards(stakedAmount, new anchor.BN(10))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+          secondaryIncentive: incentivePDA,
+          secondaryRewards: secondaryRewardsPDA,
+        })
+        .rpc();
+
+      return { operator, operatorRewardsPDA, secondaryRewardsPDA };
+    }
+
+    before(async () => {
+      partnerMint = await createMint(
+        provider.connection,
+        provider.wallet.payer,
+        provider.wallet.publicKey,
+        null,
+        6
+      );
+      [incentivePDA] = getSecondaryIncentivePDA(partnerMint);
+      [secondaryVaultPDA] = getSecondaryVaultPDA(incentivePDA);
+
+      // Program runs over the next quarter of epochs
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      await program.methods
+        .createSecondaryIncentive(pool.currentEpoch, pool.currentEpoch.add(new anchor.BN(90)))
+        .accounts({
+          secondaryIncentive: incentivePDA,
+          secondaryVault: secondaryVaultPDA,
+          mint: partnerMint,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      authorityPartnerAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        partnerMint,
+        provider.wallet.publicKey,
+        anchor.web3.Keypair.generate()
+      );
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        partnerMint,
+        authorityPartnerAccount,
+        provider.wallet.publicKey,
+        BigInt(BUDGET.toString())
+      );
+
+      await program.methods
+        .fundSecondaryIncentive(BUDGET)
+        .accounts({
+          secondaryIncentive: incentivePDA,
+          funderTokenAccount: authorityPartnerAccount,
+          secondaryVault: secondaryVaultPDA,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("Allocates the partner token in proportion to the primary reward", async () => {
+      const small = await setupOperator(new anchor.BN(100_000_000_000));
+      const large = await setupOperator(new anchor.BN(10_000_000_000_000));
+
+      const smallPrimary = await program.account.operatorRewards.fetch(small.operatorRewardsPDA);
+      const largePrimary = await program.account.operatorRewards.fetch(large.operatorRewardsPDA);
+      const smallSecondary = await program.account.secondaryRewards.fetch(small.secondaryRewardsPDA);
+      const largeSecondary = await program.account.secondaryRewards.fetch(large.secondaryRewardsPDA);
+
+      expect(smallSecondary.unclaimedRewards.toNumber()).to.be.greaterThan(0);
+      expect(largePrimary.totalEarned.gt(smallPrimary.totalEarned)).to.be.true;
+
+      // Same secondary-per-primary rate for both operators, up to rounding
+      const smallRate = smallSecondary.unclaimedRewards.toNumber() / smallPrimary.totalEarned.toNumber();
+      const largeRate = largeSecondary.unclaimedRewards.toNumber() / largePrimary.totalEarned.toNumber();
+      expect(largeRate).to.be.closeTo(smallRate, smallRate * 0.01);
+
+      const incentive = await program.account.secondaryIncentive.fetch(incentivePDA);
+      expect(incentive.allocated.toString()).to.equal(
+        smallSecondary.unclaimedRewards.add(largeSecondary.unclaimedRewards).toString()
+      );
+    });
+
+    it("Claims from both the reward vault and the secondary vault", async () => {
+      const { operator, operatorRewardsPDA, secondaryRewardsPDA } = await setupOperator(
+        new anchor.BN(100_000_000_000)
+      );
+      const primary = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      const secondary = await program.account.secondaryRewards.fetch(secondaryRewardsPDA);
+
+      const operatorTokenAccount = await createTokenAccount(operator.publicKey, operator);
+      const operatorPartnerAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        partnerMint,
+        operator.publicKey,
+        anchor.web3.Keypair.generate()
+      );
+
+      await program.methods
+        .claimRewards(false, false)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          rewardVault,
+          operatorTokenAccount,
+          operator: operator.publicKey,
+          claimant: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .claimSecondaryRewards()
+        .accounts({
+          secondaryIncentive: incentivePDA,
+          secondaryRewards: secondaryRewardsPDA,
+          secondaryVault: secondaryVaultPDA,
+          operatorTokenAccount: operatorPartnerAccount,
+          operator: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+
+      const aegisBalance = await getAccount(provider.connection, operatorTokenAccount);
+      const partnerBalance = await getAccount(provider.connection, operatorPartnerAccount);
+      expect(aegisBalance.amount.toString()).to.equal(primary.unclaimedRewards.toString());
+      expect(partnerBalance.amount.toString()).to.equal(secondary.unclaimedRewards.toString());
+
+      const after = await program.account.secondaryRewards.fetch(secondaryRewardsPDA);
+      expect(after.unclaimedRewards.toString()).to.equal("0");
+      expect(after.totalClaimed.toString()).to.equal(secondary.unclaimedRewards.toString());
+    });
+
+    it("Rejects funding and closing by anyone but the program authority", async () => {
+      const unauthorized = anchor.web3.Keypair.generate();
+      await fundAccount(unauthorized.publicKey);
+
+      try {
+        await program.methods
+          .closeSecondaryIncentive()
+          .accounts({
+            secondaryIncentive: incentivePDA,
+            secondaryVault: secondaryVaultPDA,
+            authorityTokenAccount: authorityPartnerAccount,
+            authority: unauthorized.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([unauthorized])
+          .rpc();
+
+        expect.fail("Should have rejected unauthorized close");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAuthority");
+      }
+    });
+
+    it("Returns the unspent budget to the authority on close", async () => {
+      const before = await program.account.secondaryIncentive.fetch(incentivePDA);
+      const beforeBalance = await getAccount(provider.connection, authorityPartnerAccount);
+      const unspent = before.budget.sub(before.allocated);
+      expect(unspent.toNumber()).to.be.greaterThan(0);
+
+      await program.methods
+        .closeSecondaryIncentive()
+        .accounts({
+          secondaryIncentive: incentivePDA,
+          secondaryVault: secondaryVaultPDA,
+          authorityTokenAccount: authorityPartnerAccount,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const after = await program.account.secondaryIncentive.fetch(incentivePDA);
+      const afterBalance = await getAccount(provider.connection, authorityPartnerAccount);
+      const vault = await getAccount(provider.connection, secondaryVaultPDA);
+
+      expect(after.closed).to.be.true;
+      expect(after.budget.toString()).to.equal(before.allocated.toString());
+      expect((afterBalance.amount - beforeBalance.amount).toString()).to.equal(unspent.toString());
+      // Only allocated-but-unclaimed rewards remain in the vault
+      expect(vault.amount.toString()).to.equal(before.allocated.sub(before.claimed).toString());
+    });
+
+    it("Stops allocating once closed", async () => {
+      const { secondaryRewardsPDA } = await setupOperator(new anchor.BN(100_000_000_000));
+      const secondary = await program.account.secondaryRewards.fetch(secondaryRewardsPDA);
+      expect(secondary.unclaimedRewards.toString()).to.equal("0");
+    });
+  });
+
   describe("Edge Cases", () => {
     it("Handles zero performance correctly", async () => {
       const operator = anchor.web3.Keypair.generate();
@@ -614,6 +1709,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -655,6 +1751,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -687,6 +1784,34 @@ describe("rewards", () => {
     });
   });
 
+  describe("Emission Schedule", () => {
+    it("Emits the current epoch's emission info without an authority", async () => {
+      let received: any = null;
+      const listener = program.addEventListener("emissionScheduleEvent", (event) => {
+        received = event;
+      });
+
+      await program.methods
+        .emitEmissionInfo()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const pool = await program.account.rewardPool.fetch(rewardPoolPDA);
+      expect(received).to.not.be.null;
+      expect(received.epoch.toString()).to.equal(pool.currentEpoch.toString());
+      expect(received.emission.toString()).to.not.equal("0");
+      expect(received.yearIndex.toString()).to.equal("0");
+      expect(received.nextHalvingEpoch.toString()).to.equal(
+        pool.startEpoch.addn(365).toString()
+      );
+    });
+  });
+
   describe("PDA Derivation", () => {
     it("Derives unique PDAs for different operators", async () => {
       const operator1 = anchor.web3.Keypair.generate();
@@ -723,6 +1848,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -754,13 +1880,14 @@ describe("rewards", () => {
 
       try {
         await program.methods
-          .claimRewards()
+          .claimRewards(false, false)
           .accounts({
             rewardPool: rewardPoolPDA,
             operatorRewards: operatorRewardsPDA,
             rewardVault,
             operatorTokenAccount: attackerTokenAccount,
             operator: attacker.publicKey, // Wrong operator!
+            claimant: attacker.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
           .signers([attacker])
@@ -844,6 +1971,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -888,6 +2016,7 @@ describe("rewards", () => {
       await program.methods
         .initializeOperatorRewards()
         .accounts({
+          rewardPool: rewardPoolPDA,
           operatorRewards: operatorRewardsPDA,
           operator: operator.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -959,6 +2088,7 @@ describe("rewards", () => {
         await program.methods
           .initializeOperatorRewards()
           .accounts({
+            rewardPool: rewardPoolPDA,
             operatorRewards: opRewards,
             operator: op.publicKey,
             systemProgram: anchor.web3.SystemProgram.programId,
@@ -994,26 +2124,28 @@ describe("rewards", () => {
       const op2TokenAccount = await createTokenAccount(operator2.publicKey, operator2);
 
       await program.methods
-        .claimRewards()
+        .claimRewards(false, false)
         .accounts({
           rewardPool: rewardPoolPDA,
           operatorRewards: opRewards1,
           rewardVault,
           operatorTokenAccount: op1TokenAccount,
           operator: operator1.publicKey,
+          claimant: operator1.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([operator1])
         .rpc();
 
       await program.methods
-        .claimRewards()
+        .claimRewards(false, false)
         .accounts({
           rewardPool: rewardPoolPDA,
           operatorRewards: opRewards2,
           rewardVault,
           operatorTokenAccount: op2TokenAccount,
           operator: operator2.publicKey,
+          claimant: operator2.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([operator2])
@@ -1028,4 +2160,165 @@ describe("rewards", () => {
       );
     });
   });
+
+  // Pure shortfall arithmetic is covered by the program unit tests
+  // (test_funded_vault_pays_full_claim, test_partial_claim_leaves_remainder_claimable,
+  // test_vault_shortfall_fails_cleanly)
+  describe("Vault Shortfall", () => {
+    let operator: anchor.web3.Keypair;
+    let operatorRewardsPDA: anchor.web3.PublicKey;
+    let operatorTokenAccount: anchor.web3.PublicKey;
+
+    // Tops the vault up by `amount`
+    async function refund(amount: bigint) {
+      if (amount <= BigInt(0)) {
+        return;
+      }
+      const funderTokenAccount = await createTokenAccount(
+        provider.wallet.publicKey,
+        provider.wallet.payer
+      );
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        funderTokenAccount,
+        provider.wallet.publicKey,
+        amount
+      );
+      await program.methods
+        .fundPool(new anchor.BN(amount.toString()))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          funderTokenAccount,
+          rewardVault,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    async function vaultBalance(): Promise<bigint> {
+      return (await getAccount(provider.connection, rewardVault)).amount;
+    }
+
+    async function unclaimed(): Promise<bigint> {
+      const rewards = await program.account.operatorRewards.fetch(operatorRewardsPDA);
+      return BigInt(rewards.unclaimedRewards.toString());
+    }
+
+    // Leaves the vault holding half of what the operator is owed
+    async function underfundVault() {
+      const owed = await unclaimed();
+      const vault = await vaultBalance();
+      expect(vault < owed, "operator should be owed more than the vault holds").to.be.true;
+      await refund(owed / BigInt(2) - vault);
+    }
+
+    function claim(allowPartial: boolean) {
+      return program.methods
+        .claimRewards(false, allowPartial)
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          rewardVault,
+          operatorTokenAccount,
+          operator: operator.publicKey,
+          claimant: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operator])
+        .rpc();
+    }
+
+    beforeEach(async () => {
+      operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      [operatorRewardsPDA] = getOperatorRewardsPDA(operator.publicKey);
+
+      await program.methods
+        .initializeOperatorRewards()
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      // Serving most of the network's requests accrues more than the vault holds
+      await program.methods
+        .recordPerformance(100, 100, new anchor.BN(1_000_000_000))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .calculateRewards(new anchor.BN(100_000_000_000), new anchor.BN(1))
+        .accounts({
+          rewardPool: rewardPoolPDA,
+          operatorRewards: operatorRewardsPDA,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      operatorTokenAccount = await createTokenAccount(operator.publicKey, operator);
+    });
+
+    it("Pays a covered claim in full", async () => {
+      const owed = await unclaimed();
+      await refund(owed - (await vaultBalance()));
+
+      await claim(false);
+
+      const balance = await getAccount(provider.connection, operatorTokenAccount);
+      expect(balance.amount.toString()).to.equal(owed.toString());
+      expect((await unclaimed()).toString()).to.equal("0");
+    });
+
+    it("Pays out the vault balance and leaves the remainder claimable", async () => {
+      await underfundVault();
+      const owed = await unclaimed();
+      const available = await vaultBalance();
+
+      let shortfall: anchor.BN | null = null;
+      const listener = program.addEventListener("partialClaimEvent", (event) => {
+        shortfall = event.shortfall;
+      });
+      await claim(true);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const remainder = owed - available;
+      expect(shortfall?.toString()).to.equal(remainder.toString());
+      expect((await vaultBalance()).toString()).to.equal("0");
+      expect((await unclaimed()).toString()).to.equal(remainder.toString());
+
+      // The remainder pays out once the vault is refunded
+      await refund(remainder);
+      await claim(false);
+
+      const balance = await getAccount(provider.connection, operatorTokenAccount);
+      expect(balance.amount.toString()).to.equal(owed.toString());
+      expect((await unclaimed()).toString()).to.equal("0");
+    });
+
+    it("Rejects a short vault without allow_partial", async () => {
+      await underfundVault();
+      const owed = await unclaimed();
+
+      try {
+        await claim(false);
+        expect.fail("Should have rejected a claim the vault can't cover");
+      } catch (error) {
+        expect(error.toString()).to.include("InsufficientVaultBalance");
+      }
+
+      expect((await unclaimed()).toString()).to.equal(owed.toString());
+    });
+  });
 });
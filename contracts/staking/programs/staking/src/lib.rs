@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+pub mod stake_view;
 
 declare_id!("85Pd1GRJ1qA3kVTn3ERHsyuUpkr2bbb9L9opwS9UnHEQ");
 
@@ -18,6 +20,39 @@ pub const SLASH_TIMELOCK_PERIOD: i64 = 24 * 60 * 60; // 24 hours
 /// This prevents users from setting dangerously short cooldowns
 pub const MIN_COOLDOWN_PERIOD: i64 = 24 * 60 * 60; // 1 day minimum
 
+/// Most recent slash requests kept in each operator's SlashRequestIndex
+pub const SLASH_INDEX_CAPACITY: usize = 8;
+
+/// Longest an investigation flag can delay exits (7 days)
+pub const MAX_INVESTIGATION_FLAG_DURATION: i64 = 7 * 24 * 60 * 60;
+
+/// Window for the per-oracle investigation flag rate limit (24 hours)
+pub const FLAG_RATE_WINDOW: i64 = 24 * 60 * 60;
+
+/// Investigation flags one oracle can place per FLAG_RATE_WINDOW
+pub const MAX_FLAGS_PER_WINDOW: u8 = 5;
+
+/// StakeAccount layout written by this program version
+///
+/// Accounts from earlier versions are shorter (fields are only ever
+/// appended) and carry no version; migrate_stake_account brings them here.
+/// Instructions that change a stake require at least this version.
+pub const CURRENT_STAKE_SCHEMA_VERSION: u8 = 1;
+
+/// Default per-violation slash cooldowns, indexed by SlashingViolation
+///
+/// One incident keeps matching its trigger (a 48-hour outage is still offline
+/// the next day), so a violation type can't be slashed again until its
+/// cooldown has passed since the last executed slash. Data integrity and
+/// malicious behavior are distinct incidents each time and have none.
+pub const DEFAULT_VIOLATION_COOLDOWNS: [i64; SlashingViolation::COUNT] = [
+    7 * 24 * 60 * 60,  // Offline48Hours: 7 days
+    30 * 24 * 60 * 60, // LowUptime: 30 days
+    3 * 24 * 60 * 60,  // ChallengeFailed: 3 days
+    0,                 // DataIntegrityViolation
+    0,                 // MaliciousBehavior
+];
+
 #[program]
 pub mod staking {
     use super::*;
@@ -50,6 +85,8 @@ pub mod staking {
         config.paused = false;
         config.bump = ctx.bumps.global_config;
         config.slash_nonce = 0;  // Y2.5: Initialize nonce
+        config.exit_delay_on_flag = false;
+        config.violation_cooldowns = DEFAULT_VIOLATION_COOLDOWNS;
 
         msg!(
             "Global config initialized: admin={}, min_stake={}, cooldown={}, registry={}",
@@ -125,10 +162,71 @@ pub mod staking {
         Ok(())
     }
 
+    /// Enable or disable exit delays for operators under investigation (admin only)
+    ///
+    /// While enabled, oracles can place investigation flags and a flagged
+    /// operator's execute_unstake is blocked until the flag expires or is cleared.
+    pub fn set_exit_delay_on_flag(ctx: Context<SetExitDelayOnFlag>, enabled: bool) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+
+        require!(
+            ctx.accounts.admin.key() == config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+
+        config.exit_delay_on_flag = enabled;
+
+        msg!("Exit delay on investigation flag set to: {}", enabled);
+
+        Ok(())
+    }
+
+    /// Set how long a violation type is exempt from new slash requests after
+    /// a slash for it executes (admin only, 0 disables the cooldown)
+    pub fn set_violation_cooldown(
+        ctx: Context<SetViolationCooldown>,
+        violation_type: SlashingViolation,
+        cooldown: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+
+        require!(
+            ctx.accounts.admin.key() == config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+        require!(cooldown >= 0, StakingError::InvalidViolationCooldown);
+
+        config.violation_cooldowns[violation_type.index()] = cooldown;
+
+        msg!("Slash cooldown for {:?} set to: {}s", violation_type, cooldown);
+
+        Ok(())
+    }
+
+    /// Create the stake vault (admin only, once per deployment)
+    ///
+    /// All operators' stake is held in one token account at the
+    /// ["stake_vault"] PDA whose token authority is that same PDA, so only
+    /// this program can move tokens out of it (see stake_vault_seeds).
+    pub fn initialize_stake_vault(ctx: Context<InitializeStakeVault>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.key() == ctx.accounts.global_config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+
+        msg!(
+            "Stake vault initialized: {} (mint {})",
+            ctx.accounts.stake_vault.key(),
+            ctx.accounts.mint.key()
+        );
+
+        Ok(())
+    }
+
     /// Initialize a stake account for a node operator
     pub fn initialize_stake(ctx: Context<InitializeStake>) -> Result<()> {
         let stake_account = &mut ctx.accounts.stake_account;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         stake_account.operator = ctx.accounts.operator.key();
         stake_account.staked_amount = 0;
@@ -139,6 +237,7 @@ pub mod staking {
         stake_account.created_at = clock.unix_timestamp;
         stake_account.updated_at = clock.unix_timestamp;
         stake_account.bump = ctx.bumps.stake_account;
+        stake_account.schema_version = CURRENT_STAKE_SCHEMA_VERSION;
 
         msg!("Stake account initialized for operator: {}", stake_account.operator);
 
@@ -165,7 +264,7 @@ pub mod staking {
         );
 
         let stake_account = &mut ctx.accounts.stake_account;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         // Transfer tokens from operator to stake vault
         let cpi_accounts = Transfer {
@@ -219,6 +318,100 @@ pub mod staking {
             total_staked: stake_account.staked_amount,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::Stake,
+            amount,
+            clock.unix_timestamp,
+        ));
+
+        Ok(())
+    }
+
+    /// Create the operator's stake account if needed, then stake, atomically
+    ///
+    /// For operators who sign a stake before `initialize_stake`. The account
+    /// uses `init_if_needed`, which is safe here because:
+    /// - its PDA is derived from the signing operator, so nobody can point
+    ///   this at another operator's account
+    /// - `init_or_verify` only writes the initial fields when the account is
+    ///   brand new (operator still the zero key, which can never sign) and
+    ///   otherwise requires the stored operator to match, so an existing
+    ///   account's created_at and lifetime counters are never reset
+    pub fn initialize_and_stake(ctx: Context<InitializeAndStake>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.global_config;
+        require!(!config.paused, StakingError::StakingPaused);
+        require!(
+            amount >= config.min_stake_amount,
+            StakingError::InsufficientStakeAmount
+        );
+
+        let stake_account = &mut ctx.accounts.stake_account;
+        let clock = current_clock(ctx.remaining_accounts)?;
+
+        let created = stake_account.init_or_verify(
+            ctx.accounts.operator.key(),
+            ctx.bumps.stake_account,
+            clock.unix_timestamp,
+        )?;
+        if created {
+            msg!("Stake account initialized for operator: {}", stake_account.operator);
+            emit!(StakeAccountCreatedEvent {
+                operator: stake_account.operator,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        // Transfer tokens from operator to stake vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.operator_token_account.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+            authority: ctx.accounts.operator.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        stake_account.staked_amount = stake_account
+            .staked_amount
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+        stake_account.total_staked_ever = stake_account
+            .total_staked_ever
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+        stake_account.updated_at = clock.unix_timestamp;
+
+        // Keep the registry in sync, as in stake()
+        let new_total_stake = stake_account.staked_amount;
+        let cpi_program = ctx.accounts.registry_program.to_account_info();
+        let cpi_accounts = registry::cpi::accounts::UpdateStake {
+            registry_config: ctx.accounts.registry_config.to_account_info(),
+            node_account: ctx.accounts.node_account.to_account_info(),
+            staking_authority: ctx.accounts.staking_authority.to_account_info(),
+        };
+        let seeds = &[
+            b"staking_authority".as_ref(),
+            &[ctx.bumps.staking_authority],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        registry::cpi::update_stake(cpi_ctx, new_total_stake)?;
+
+        msg!("Staked {} tokens for operator: {}", amount, stake_account.operator);
+
+        emit!(StakedEvent {
+            operator: stake_account.operator,
+            amount,
+            total_staked: stake_account.staked_amount,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::Stake,
+            amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
@@ -227,7 +420,7 @@ pub mod staking {
     pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
         let config = &ctx.accounts.global_config;
         let stake_account = &mut ctx.accounts.stake_account;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         require!(amount > 0, StakingError::InvalidAmount);
         require!(
@@ -253,6 +446,14 @@ pub mod staking {
         // SECURITY FIX: Use config cooldown period instead of hardcoded
         let cooldown_end = clock.unix_timestamp + config.unstake_cooldown_period;
 
+        // A flagged operator can still request (the cooldown runs), but
+        // execute_unstake stays blocked while the flag is active
+        let exit_blocked_until = active_investigation_flag(
+            config,
+            &ctx.accounts.investigation_flag,
+            clock.unix_timestamp,
+        )?;
+
         msg!(
             "Unstake requested: {} tokens, cooldown ends at: {}",
             amount,
@@ -263,8 +464,15 @@ pub mod staking {
             operator: stake_account.operator,
             amount,
             cooldown_ends_at: cooldown_end,
+            exit_blocked_until,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::RequestUnstake,
+            amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
@@ -273,7 +481,7 @@ pub mod staking {
     /// SECURITY FIX: Now calls Registry via CPI to keep stake amounts synchronized
     pub fn execute_unstake(ctx: Context<ExecuteUnstake>) -> Result<()> {
         let config = &ctx.accounts.global_config;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         require!(
             ctx.accounts.stake_account.pending_unstake > 0,
@@ -287,22 +495,28 @@ pub mod staking {
             StakingError::CooldownNotComplete
         );
 
+        if let Some(expires_at) = active_investigation_flag(
+            config,
+            &ctx.accounts.investigation_flag,
+            clock.unix_timestamp,
+        )? {
+            msg!("Unstake blocked by investigation flag until: {}", expires_at);
+            return Err(StakingError::UnderInvestigation.into());
+        }
+
         let amount = ctx.accounts.stake_account.pending_unstake;
         let operator = ctx.accounts.stake_account.operator;
-        let bump = ctx.accounts.stake_account.bump;
 
-        // Transfer tokens from vault back to operator
-        let seeds = &[
-            b"stake",
-            operator.as_ref(),
-            &[bump],
-        ];
-        let signer = &[&seeds[..]];
+        // Transfer tokens from vault back to operator; the vault is its own
+        // authority, the same as on the slash paths
+        let vault_bump = [ctx.bumps.stake_vault];
+        let vault_seeds = stake_vault_seeds(&vault_bump);
+        let signer = &[&vault_seeds[..]];
 
         let cpi_accounts = Transfer {
             from: ctx.accounts.stake_vault.to_account_info(),
             to: ctx.accounts.operator_token_account.to_account_info(),
-            authority: ctx.accounts.stake_account.to_account_info(),
+            authority: ctx.accounts.stake_vault.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
@@ -351,6 +565,12 @@ pub mod staking {
             remaining_staked: stake_account.staked_amount,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::ExecuteUnstake,
+            amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
@@ -358,7 +578,7 @@ pub mod staking {
     /// Cancel unstake request (before cooldown completes)
     pub fn cancel_unstake(ctx: Context<CancelUnstake>) -> Result<()> {
         let stake_account = &mut ctx.accounts.stake_account;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         require!(
             stake_account.pending_unstake > 0,
@@ -383,6 +603,12 @@ pub mod staking {
             amount,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::CancelUnstake,
+            amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
@@ -398,6 +624,9 @@ pub mod staking {
     /// 3. ChallengeFailed - Failed security challenge (15% slash)
     /// 4. DataIntegrityViolation - Corrupted/invalid data (25% slash)
     /// 5. MaliciousBehavior - Attack detection (100% slash)
+    ///
+    /// Passing the operator's registry node account includes the contact
+    /// hash and notification preferences they published there in the event.
     pub fn request_slash(
         ctx: Context<RequestSlash>,
         violation_type: SlashingViolation,
@@ -406,7 +635,7 @@ pub mod staking {
         let config = &mut ctx.accounts.global_config;
         let stake_account = &ctx.accounts.stake_account;
         let slash_request = &mut ctx.accounts.slash_request;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         // Verify oracle is authorized
         require!(
@@ -417,31 +646,11 @@ pub mod staking {
         // Validate evidence CID length
         require!(evidence_cid.len() <= 128, StakingError::EvidenceCidTooLong);
 
-        // Calculate slash amount based on violation type (per whitepaper)
-        let slash_percentage = match violation_type {
-            SlashingViolation::Offline48Hours => 10,         // 10% for extended offline
-            SlashingViolation::LowUptime => 5,               // 5% for <90% uptime
-            SlashingViolation::ChallengeFailed => 15,        // 15% for failed challenge
-            SlashingViolation::DataIntegrityViolation => 25, // 25% for data corruption
-            SlashingViolation::MaliciousBehavior => 100,     // 100% for attacks
-        };
-
-        let slash_amount = stake_account
-            .staked_amount
-            .checked_mul(slash_percentage)
-            .ok_or(StakingError::Overflow)?
-            .checked_div(100)
-            .ok_or(StakingError::Underflow)?;
-
-        require!(
-            slash_amount > 0,
-            StakingError::InvalidAmount
-        );
+        // One slash per incident: same violation type must wait out its cooldown
+        stake_account.check_violation_cooldown(config, violation_type, clock.unix_timestamp)?;
 
-        require!(
-            stake_account.staked_amount >= slash_amount,
-            StakingError::InsufficientStakedBalance
-        );
+        // Calculate slash amount based on violation type (per whitepaper)
+        let SlashQuote { slash_percentage, amount: slash_amount } = quote_slash(stake_account, violation_type)?;
 
         let operator = stake_account.operator;
 
@@ -462,9 +671,25 @@ pub mod staking {
         slash_request.cancelled = false;
         slash_request.bump = ctx.bumps.slash_request;
         slash_request.slash_nonce = current_nonce;  // Y2.4: Store nonce
+        slash_request.additional_evidence = Default::default();
+        slash_request.additional_evidence_count = 0;
+        slash_request.indexed = true;
+
+        let slash_request_index = &mut ctx.accounts.slash_request_index;
+        slash_request_index.init_or_verify(operator, ctx.bumps.slash_request_index)?;
+        slash_request_index.record_request(
+            slash_request.key(),
+            current_nonce,
+            clock.unix_timestamp,
+        )?;
 
         let execute_after = clock.unix_timestamp + SLASH_TIMELOCK_PERIOD;
 
+        let (contact_hash, notify_flags) = match ctx.accounts.node_account.as_ref() {
+            Some(node_info) => operator_contact(&config.registry_program_id, node_info, operator)?,
+            None => ([0; 32], 0),
+        };
+
         msg!(
             "Slash requested (nonce={}): {} tokens ({}%) from {} for {:?} - Evidence: {} - Executable after: {}",
             current_nonce, slash_amount, slash_percentage, operator, violation_type, evidence_cid, execute_after
@@ -477,6 +702,8 @@ pub mod staking {
             evidence_cid,
             request_time: clock.unix_timestamp,
             execute_after,
+            contact_hash,
+            notify_flags,
         });
 
         Ok(())
@@ -489,40 +716,17 @@ pub mod staking {
         let config = &ctx.accounts.global_config;
         let stake_account = &mut ctx.accounts.stake_account;
         let slash_request = &mut ctx.accounts.slash_request;
-        let clock = Clock::get()?;
-
-        // Verify slash request is not already executed or cancelled
-        require!(
-            !slash_request.executed,
-            StakingError::SlashAlreadyExecuted
-        );
-        require!(
-            !slash_request.cancelled,
-            StakingError::SlashCancelled
-        );
-
-        // CRITICAL: Enforce 24-hour timelock
-        let execute_after = slash_request.request_time + SLASH_TIMELOCK_PERIOD;
-        require!(
-            clock.unix_timestamp >= execute_after,
-            StakingError::SlashTimelockNotExpired
-        );
+        let clock = current_clock(ctx.remaining_accounts)?;
 
-        // Verify there's still enough stake to slash
-        require!(
-            stake_account.staked_amount >= slash_request.amount,
-            StakingError::InsufficientStakedBalance
-        );
+        slash_request.check_executable(stake_account, config, clock.unix_timestamp)?;
 
         let operator = slash_request.operator;
         let slash_amount = slash_request.amount;
 
         // Transfer slashed tokens to treasury
-        let vault_seeds: &[&[u8]] = &[
-            b"stake_vault",
-            &[ctx.bumps.stake_vault],
-        ];
-        let signer = &[vault_seeds];
+        let vault_bump = [ctx.bumps.stake_vault];
+        let vault_seeds = stake_vault_seeds(&vault_bump);
+        let signer = &[&vault_seeds[..]];
 
         let cpi_accounts = Transfer {
             from: ctx.accounts.stake_vault.to_account_info(),
@@ -539,9 +743,16 @@ pub mod staking {
             .checked_sub(slash_amount)
             .ok_or(StakingError::Underflow)?;
         stake_account.updated_at = clock.unix_timestamp;
+        // Cooldown runs from execution, not from the request
+        stake_account.record_slash(slash_request.violation_type, clock.unix_timestamp);
 
         // Mark slash as executed
         slash_request.executed = true;
+        resolve_in_index(
+            slash_request,
+            ctx.accounts.slash_request_index.as_deref_mut(),
+            SlashRequestStatus::Executed,
+        )?;
 
         // Call Registry to update stake via CPI
         let new_total_stake = stake_account.staked_amount;
@@ -579,6 +790,12 @@ pub mod staking {
             remaining_staked: stake_account.staked_amount,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::ExecuteSlash,
+            slash_amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
@@ -589,7 +806,7 @@ pub mod staking {
     pub fn cancel_slash(ctx: Context<CancelSlash>) -> Result<()> {
         let config = &ctx.accounts.global_config;
         let slash_request = &mut ctx.accounts.slash_request;
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         // Verify caller is admin
         require!(
@@ -609,6 +826,11 @@ pub mod staking {
 
         // Mark as cancelled
         slash_request.cancelled = true;
+        resolve_in_index(
+            slash_request,
+            ctx.accounts.slash_request_index.as_deref_mut(),
+            SlashRequestStatus::Cancelled,
+        )?;
 
         msg!(
             "Slash cancelled: {} tokens for operator {} - Violation: {:?}",
@@ -626,6 +848,42 @@ pub mod staking {
         Ok(())
     }
 
+    /// Attach follow-up evidence to a pending slash request
+    /// Lets the requesting oracle add probe results or packet captures gathered
+    /// during the 24-hour window so disputes aren't decided on the first CID alone.
+    pub fn append_evidence(ctx: Context<AppendEvidence>, evidence_cid: String) -> Result<()> {
+        let slash_request = &mut ctx.accounts.slash_request;
+        let clock = current_clock(ctx.remaining_accounts)?;
+
+        // Only the oracle that filed the request can extend it
+        require!(
+            ctx.accounts.oracle.key() == slash_request.authority,
+            StakingError::UnauthorizedOracle
+        );
+
+        slash_request.append_evidence(evidence_cid.clone())?;
+
+        msg!(
+            "Evidence appended to slash request (nonce={}) for {}: {} ({}/{})",
+            slash_request.slash_nonce,
+            slash_request.operator,
+            evidence_cid,
+            slash_request.additional_evidence_count,
+            SlashRequest::MAX_ADDITIONAL_EVIDENCE
+        );
+
+        emit!(EvidenceAppendedEvent {
+            operator: slash_request.operator,
+            slash_nonce: slash_request.slash_nonce,
+            evidence_cid,
+            evidence_index: slash_request.additional_evidence_count - 1,
+            appended_by: ctx.accounts.oracle.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// SECURITY FIX: Slash stake (NOW REQUIRES ADMIN AUTHORIZATION)
     /// Only the admin_authority from GlobalConfig can slash stakes
     /// SECURITY FIX: Now calls Registry via CPI to keep stake amounts synchronized
@@ -646,7 +904,7 @@ pub mod staking {
         require!(amount > 0, StakingError::InvalidAmount);
         require!(reason.len() <= 128, StakingError::ReasonTooLong);
 
-        let clock = Clock::get()?;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
         require!(
             ctx.accounts.stake_account.staked_amount >= amount,
@@ -656,11 +914,9 @@ pub mod staking {
         let operator = ctx.accounts.stake_account.operator;
 
         // Use vault bump for signing the transfer
-        let vault_seeds: &[&[u8]] = &[
-            b"stake_vault",
-            &[ctx.bumps.stake_vault],
-        ];
-        let signer = &[vault_seeds];
+        let vault_bump = [ctx.bumps.stake_vault];
+        let vault_seeds = stake_vault_seeds(&vault_bump);
+        let signer = &[&vault_seeds[..]];
 
         // Transfer slashed tokens to treasury from config
         let cpi_accounts = Transfer {
@@ -715,90 +971,474 @@ pub mod staking {
             remaining_staked: stake_account.staked_amount,
             timestamp: clock.unix_timestamp,
         });
+        emit!(StakePositionChangedEvent::new(
+            stake_account,
+            StakeChangeKind::SlashStake,
+            amount,
+            clock.unix_timestamp,
+        ));
 
         Ok(())
     }
-}
 
-/// SECURITY FIX: Global configuration for staking program
-/// Stores admin authority and configurable parameters
-/// Now includes registry_program_id for CPI integration
-/// Y2.5: Added slash_nonce for deterministic SlashRequest PDA seeds
-#[account]
-pub struct GlobalConfig {
-    pub admin_authority: Pubkey,        // Admin who can slash and update config (32 bytes)
-    pub min_stake_amount: u64,          // Minimum stake required (8 bytes)
-    pub unstake_cooldown_period: i64,   // Cooldown in seconds (8 bytes)
-    pub treasury: Pubkey,               // Treasury for slashed tokens (32 bytes)
-    pub registry_program_id: Pubkey,    // SECURITY FIX: Registry program for CPI (32 bytes)
-    pub paused: bool,                   // Emergency pause flag (1 byte)
-    pub bump: u8,                       // PDA bump (1 byte)
-    pub slash_nonce: u64,               // Y2.5: Nonce for SlashRequest PDA seeds (8 bytes)
-}
+    /// Flag an operator under investigation, delaying their exits
+    ///
+    /// Only authorized oracles (the admin authority, as for request_slash)
+    /// can flag, at most MAX_FLAGS_PER_WINDOW times per FLAG_RATE_WINDOW.
+    /// The flag expires on its own after `duration` (at most 7 days); it
+    /// blocks execute_unstake, not request_unstake, so the cooldown still runs.
+    pub fn place_investigation_flag(ctx: Context<PlaceInvestigationFlag>, duration: i64) -> Result<()> {
+        let config = &ctx.accounts.global_config;
+        let clock = current_clock(ctx.remaining_accounts)?;
 
-impl GlobalConfig {
-    pub const MAX_SIZE: usize = 8 +  // discriminator
-        32 +                          // admin_authority
-        8 +                           // min_stake_amount
-        8 +                           // unstake_cooldown_period
-        32 +                          // treasury
-        32 +                          // registry_program_id
-        1 +                           // paused
-        1 +                           // bump
-        8;                            // slash_nonce (Y2.5)
-}
+        require!(
+            ctx.accounts.oracle.key() == config.admin_authority,
+            StakingError::UnauthorizedOracle
+        );
+        require!(config.exit_delay_on_flag, StakingError::ExitDelayDisabled);
+        require!(
+            duration > 0 && duration <= MAX_INVESTIGATION_FLAG_DURATION,
+            StakingError::InvalidFlagDuration
+        );
 
-/// Stake account - tracks operator's staked tokens
-#[account]
-pub struct StakeAccount {
-    pub operator: Pubkey,           // Node operator (32 bytes)
-    pub staked_amount: u64,         // Currently staked amount (8 bytes)
-    pub pending_unstake: u64,       // Amount pending unstake (8 bytes)
-    pub unstake_request_time: i64,  // When unstake was requested (8 bytes)
-    pub total_staked_ever: u64,     // Lifetime staking total (8 bytes)
-    pub total_unstaked_ever: u64,   // Lifetime unstaking total (8 bytes)
-    pub created_at: i64,            // Account creation timestamp (8 bytes)
-    pub updated_at: i64,            // Last update timestamp (8 bytes)
-    pub bump: u8,                   // PDA bump seed (1 byte)
-}
+        let flag = &mut ctx.accounts.investigation_flag;
+        require!(
+            !flag.blocks_exit(clock.unix_timestamp),
+            StakingError::InvestigationFlagActive
+        );
 
-impl StakeAccount {
-    pub const MAX_SIZE: usize = 8 + // discriminator
-        32 +                        // operator
-        8 +                         // staked_amount
-        8 +                         // pending_unstake
-        8 +                         // unstake_request_time
-        8 +                         // total_staked_ever
-        8 +                         // total_unstaked_ever
-        8 +                         // created_at
-        8 +                         // updated_at
-        1;                          // bump
-}
+        let oracle = ctx.accounts.oracle.key();
+        let flag_quota = &mut ctx.accounts.flag_quota;
+        flag_quota.init_or_verify(oracle, ctx.bumps.flag_quota)?;
+        flag_quota.record_flag(clock.unix_timestamp)?;
 
-/// SECURITY FIX: Slash request account - implements timelock pattern
-/// Stores pending slash requests that can be executed after 24 hours
-/// This prevents instant slashing and allows for dispute resolution
-/// Y2.4: Uses nonce instead of timestamp for PDA seeds
-#[account]
-pub struct SlashRequest {
-    pub operator: Pubkey,             // Target operator to slash (32 bytes)
-    pub amount: u64,                  // Amount to slash (8 bytes)
-    pub violation_type: SlashingViolation, // Type of violation (1 byte)
-    pub evidence_cid: String,         // IPFS CID of evidence (4 + 128 bytes max)
-    pub request_time: i64,            // When request was created (8 bytes)
-    pub authority: Pubkey,            // Oracle/admin who requested (32 bytes)
-    pub executed: bool,               // Whether slash has been executed (1 byte)
-    pub cancelled: bool,              // Whether slash was cancelled (1 byte)
-    pub bump: u8,                     // PDA bump (1 byte)
-    pub slash_nonce: u64,             // Y2.4: Unique nonce for this slash request (8 bytes)
-}
+        let operator = ctx.accounts.stake_account.operator;
+        flag.operator = operator;
+        flag.flagged_by = oracle;
+        flag.flagged_at = clock.unix_timestamp;
+        flag.expires_at = clock
+            .unix_timestamp
+            .checked_add(duration)
+            .ok_or(StakingError::Overflow)?;
+        flag.bump = ctx.bumps.investigation_flag;
 
-impl SlashRequest {
-    /// Maximum evidence CID length (IPFS CIDv1)
-    pub const MAX_EVIDENCE_CID_LEN: usize = 128;
+        msg!(
+            "Investigation flag placed on {} by {} until {}",
+            operator,
+            oracle,
+            flag.expires_at
+        );
 
-    pub const MAX_SIZE: usize = 8 +   // discriminator
-        32 +                          // operator
+        emit!(InvestigationFlagPlacedEvent {
+            operator,
+            oracle,
+            expires_at: flag.expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Clear an investigation flag before it expires (admin only)
+    pub fn clear_investigation_flag(ctx: Context<ClearInvestigationFlag>) -> Result<()> {
+        require!(
+            ctx.accounts.admin.key() == ctx.accounts.global_config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+
+        let operator = ctx.accounts.investigation_flag.operator;
+        msg!("Investigation flag cleared for operator: {}", operator);
+
+        emit!(InvestigationFlagClearedEvent {
+            operator,
+            admin: ctx.accounts.admin.key(),
+            timestamp: current_clock(ctx.remaining_accounts)?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Emit a StakeAttestationEvent for an operator's current stake
+    ///
+    /// Read-only and permissionless: other programs (or their clients) call
+    /// this to get a versioned snapshot of the stake without depending on
+    /// the StakeAccount layout.
+    pub fn emit_stake_attestation(
+        ctx: Context<EmitStakeAttestation>,
+        _operator: Pubkey,
+    ) -> Result<()> {
+        let clock = current_clock(ctx.remaining_accounts)?;
+
+        emit!(StakeAttestationEvent::new(&ctx.accounts.stake_account, clock.slot));
+
+        Ok(())
+    }
+
+    /// Emit a SlashPreviewEvent: what a `request_slash` for `violation_type`
+    /// would take from the operator right now
+    ///
+    /// Read-only and permissionless. The amount comes from `quote_slash`,
+    /// the same calculation request_slash records; a stake too small to
+    /// slash fails here as the request would. A running cooldown doesn't
+    /// fail the preview but is reported in `cooldown_ends_at`.
+    pub fn emit_slash_preview(
+        ctx: Context<EmitSlashPreview>,
+        _operator: Pubkey,
+        violation_type: SlashingViolation,
+    ) -> Result<()> {
+        let stake_account = &ctx.accounts.stake_account;
+        let clock = current_clock(ctx.remaining_accounts)?;
+
+        let quote = quote_slash(stake_account, violation_type)?;
+        let cooldown_ends_at = stake_account
+            .cooldown_ends_at(&ctx.accounts.global_config, violation_type)
+            .filter(|&ends_at| clock.unix_timestamp < ends_at)
+            .unwrap_or(0);
+
+        emit!(SlashPreviewEvent {
+            operator: stake_account.operator,
+            violation_type,
+            slash_percentage: quote.slash_percentage as u8,
+            amount: quote.amount,
+            staked_amount: stake_account.staked_amount,
+            pending_unstake: stake_account.pending_unstake,
+            remaining_staked: stake_account.staked_amount - quote.amount,
+            cooldown_ends_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Bring a legacy stake account to the current schema (admin only)
+    ///
+    /// Accounts created before GlobalConfig or before later fields were
+    /// appended are shorter than StakeAccount::MAX_SIZE, so the account is
+    /// first grown (zero-filled, rent topped up by the admin) and only then
+    /// decoded. Known inconsistencies are repaired by `StakeAccount::migrate`.
+    /// Accounts already at the current schema are left untouched.
+    pub fn migrate_stake_account(ctx: Context<MigrateStakeAccount>, operator: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.admin.key() == ctx.accounts.global_config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+        let clock = current_clock(ctx.remaining_accounts)?;
+        let info = ctx.accounts.stake_account.to_account_info();
+        grow_account(
+            &info,
+            StakeAccount::MAX_SIZE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+
+        let mut stake_account = StakeAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(stake_account.operator, operator, StakingError::UnauthorizedOperator);
+
+        let Some(event) = stake_account.migrate(clock.unix_timestamp) else {
+            msg!("Stake account for {} already at schema {}", operator, stake_account.schema_version);
+            return Ok(());
+        };
+        stake_account.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Stake account for {} migrated from schema {} to {}",
+            operator,
+            event.from_version,
+            event.to_version
+        );
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Bring the global config to the current layout (admin only)
+    ///
+    /// A config written before later fields were appended is shorter than
+    /// GlobalConfig::MAX_SIZE and can't be loaded by any other instruction,
+    /// including migrate_stake_account, so run this first after an upgrade.
+    /// The account is grown (zero-filled, rent topped up by the admin) and
+    /// the appended fields get their defaults. A current config is left
+    /// untouched.
+    pub fn migrate_global_config(ctx: Context<MigrateGlobalConfig>) -> Result<()> {
+        let clock = current_clock(ctx.remaining_accounts)?;
+        let info = ctx.accounts.global_config.to_account_info();
+        let original_len = info.data_len();
+
+        // admin_authority directly follows the discriminator in every layout
+        let admin_authority = info
+            .try_borrow_data()?
+            .get(8..40)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(StakingError::UnauthorizedAdmin)?;
+        require_keys_eq!(ctx.accounts.admin.key(), admin_authority, StakingError::UnauthorizedAdmin);
+
+        if original_len >= GlobalConfig::MAX_SIZE {
+            msg!("Global config already at the current layout ({} bytes)", original_len);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            GlobalConfig::MAX_SIZE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+        let mut config = GlobalConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        config.fill_appended_defaults(original_len);
+        config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Global config migrated from {} to {} bytes",
+            original_len,
+            GlobalConfig::MAX_SIZE
+        );
+        emit!(GlobalConfigMigratedEvent {
+            from_len: original_len as u32,
+            to_len: GlobalConfig::MAX_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move the test clock forward (admin only, `test-utils` builds only)
+    ///
+    /// Instructions read time through current_clock, which adds this offset
+    /// when the TestClock account is passed as a remaining account, so
+    /// localnet suites can cross cooldowns and timelocks without waiting.
+    /// Like a real clock, the offset never goes backwards.
+    #[cfg(feature = "test-utils")]
+    pub fn set_test_clock(ctx: Context<SetTestClock>, offset: i64) -> Result<()> {
+        require!(
+            ctx.accounts.admin.key() == ctx.accounts.global_config.admin_authority,
+            StakingError::UnauthorizedAdmin
+        );
+
+        let test_clock = &mut ctx.accounts.test_clock;
+        require!(offset >= test_clock.offset, StakingError::TestClockRewind);
+        test_clock.offset = offset;
+        test_clock.bump = ctx.bumps.test_clock;
+
+        msg!("Test clock offset set to: {}s", offset);
+
+        Ok(())
+    }
+}
+
+/// SECURITY FIX: Global configuration for staking program
+/// Stores admin authority and configurable parameters
+/// Now includes registry_program_id for CPI integration
+/// Y2.5: Added slash_nonce for deterministic SlashRequest PDA seeds
+#[account]
+pub struct GlobalConfig {
+    pub admin_authority: Pubkey,        // Admin who can slash and update config (32 bytes)
+    pub min_stake_amount: u64,          // Minimum stake required (8 bytes)
+    pub unstake_cooldown_period: i64,   // Cooldown in seconds (8 bytes)
+    pub treasury: Pubkey,               // Treasury for slashed tokens (32 bytes)
+    pub registry_program_id: Pubkey,    // SECURITY FIX: Registry program for CPI (32 bytes)
+    pub paused: bool,                   // Emergency pause flag (1 byte)
+    pub bump: u8,                       // PDA bump (1 byte)
+    pub slash_nonce: u64,               // Y2.5: Nonce for SlashRequest PDA seeds (8 bytes)
+    pub exit_delay_on_flag: bool,       // Investigation flags block execute_unstake (1 byte)
+    pub violation_cooldowns: [i64; SlashingViolation::COUNT], // Per-violation slash cooldowns in seconds (5 x 8 bytes)
+}
+
+impl GlobalConfig {
+    pub const MAX_SIZE: usize = 8 +  // discriminator
+        32 +                          // admin_authority
+        8 +                           // min_stake_amount
+        8 +                           // unstake_cooldown_period
+        32 +                          // treasury
+        32 +                          // registry_program_id
+        1 +                           // paused
+        1 +                           // bump
+        8 +                           // slash_nonce (Y2.5)
+        1 +                           // exit_delay_on_flag
+        8 * SlashingViolation::COUNT; // violation_cooldowns
+
+    /// Size of configs written before exit_delay_on_flag was appended
+    pub const LEGACY_SIZE: usize = Self::MAX_SIZE - 1 - 8 * SlashingViolation::COUNT;
+
+    /// Give the fields appended after a config of `original_len` bytes was
+    /// written their defaults
+    ///
+    /// Growing the account zero-fills the new bytes, which is already the
+    /// default for exit_delay_on_flag (off); the cooldowns need theirs set.
+    pub fn fill_appended_defaults(&mut self, original_len: usize) {
+        if original_len < Self::MAX_SIZE {
+            self.violation_cooldowns = DEFAULT_VIOLATION_COOLDOWNS;
+        }
+    }
+}
+
+/// Stake account - tracks operator's staked tokens
+#[account]
+pub struct StakeAccount {
+    pub operator: Pubkey,           // Node operator (32 bytes)
+    pub staked_amount: u64,         // Currently staked amount (8 bytes)
+    pub pending_unstake: u64,       // Amount pending unstake (8 bytes)
+    pub unstake_request_time: i64,  // When unstake was requested (8 bytes)
+    pub total_staked_ever: u64,     // Lifetime staking total (8 bytes)
+    pub total_unstaked_ever: u64,   // Lifetime unstaking total (8 bytes)
+    pub created_at: i64,            // Account creation timestamp (8 bytes)
+    pub updated_at: i64,            // Last update timestamp (8 bytes)
+    pub bump: u8,                   // PDA bump seed (1 byte)
+    pub last_slashed_at: [i64; SlashingViolation::COUNT], // Last executed slash per violation type (5 x 8 bytes)
+    pub schema_version: u8,         // Layout version, see CURRENT_STAKE_SCHEMA_VERSION (1 byte)
+}
+
+impl StakeAccount {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                        // operator
+        8 +                         // staked_amount
+        8 +                         // pending_unstake
+        8 +                         // unstake_request_time
+        8 +                         // total_staked_ever
+        8 +                         // total_unstaked_ever
+        8 +                         // created_at
+        8 +                         // updated_at
+        1 +                         // bump
+        8 * SlashingViolation::COUNT + // last_slashed_at
+        1;                          // schema_version
+
+    /// Initialize a freshly created (zeroed) account for `operator`, or check
+    /// that an existing one belongs to them
+    ///
+    /// Returns whether the account was initialized. Existing accounts are
+    /// left untouched, so created_at and lifetime counters survive.
+    pub fn init_or_verify(&mut self, operator: Pubkey, bump: u8, now: i64) -> Result<bool> {
+        if self.operator != Pubkey::default() {
+            require_keys_eq!(self.operator, operator, StakingError::UnauthorizedOperator);
+            return Ok(false);
+        }
+
+        self.operator = operator;
+        self.staked_amount = 0;
+        self.pending_unstake = 0;
+        self.unstake_request_time = 0;
+        self.total_staked_ever = 0;
+        self.total_unstaked_ever = 0;
+        self.created_at = now;
+        self.updated_at = now;
+        self.bump = bump;
+        self.last_slashed_at = [0; SlashingViolation::COUNT];
+        self.schema_version = CURRENT_STAKE_SCHEMA_VERSION;
+        Ok(true)
+    }
+
+    /// Whether the account has been created or migrated at the current schema
+    pub fn is_current_schema(&self) -> bool {
+        self.schema_version >= CURRENT_STAKE_SCHEMA_VERSION
+    }
+
+    /// Repair a pre-current-schema account and stamp the current version
+    ///
+    /// Returns the migration event, or None if the account is already
+    /// current. Repairs:
+    /// - total_staked_ever below what the account still holds plus what it
+    ///   has unstaked (pre-CPI counters) is raised to that sum
+    /// - a stale unstake_request_time with nothing pending is cleared
+    /// - a pending unstake with no request time restarts its cooldown at
+    ///   `now` rather than being immediately withdrawable
+    /// - a missing created_at falls back to updated_at (or `now`), and
+    ///   updated_at is never before created_at
+    pub fn migrate(&mut self, now: i64) -> Option<StakeAccountMigratedEvent> {
+        if self.is_current_schema() {
+            return None;
+        }
+        let before = StakeCounters::from(&*self);
+
+        let accounted = self
+            .staked_amount
+            .saturating_add(self.pending_unstake)
+            .saturating_add(self.total_unstaked_ever);
+        self.total_staked_ever = self.total_staked_ever.max(accounted);
+
+        if self.pending_unstake == 0 {
+            self.unstake_request_time = 0;
+        } else if self.unstake_request_time == 0 {
+            self.unstake_request_time = now;
+        }
+
+        if self.created_at == 0 {
+            self.created_at = if self.updated_at > 0 { self.updated_at } else { now };
+        }
+        self.updated_at = self.updated_at.max(self.created_at);
+
+        let from_version = self.schema_version;
+        self.schema_version = CURRENT_STAKE_SCHEMA_VERSION;
+        Some(StakeAccountMigratedEvent {
+            operator: self.operator,
+            from_version,
+            to_version: self.schema_version,
+            before,
+            after: StakeCounters::from(&*self),
+            timestamp: now,
+        })
+    }
+
+    /// Reject a new slash request for `violation` while its cooldown since
+    /// the last executed slash of that type is still running
+    pub fn check_violation_cooldown(
+        &self,
+        config: &GlobalConfig,
+        violation: SlashingViolation,
+        now: i64,
+    ) -> Result<()> {
+        if let Some(ends_at) = self.cooldown_ends_at(config, violation) {
+            require!(now >= ends_at, StakingError::ViolationCooldownActive);
+        }
+        Ok(())
+    }
+
+    /// When the cooldown for `violation` ends (None if never slashed for it,
+    /// or if the type has no cooldown)
+    pub fn cooldown_ends_at(&self, config: &GlobalConfig, violation: SlashingViolation) -> Option<i64> {
+        let last_slashed_at = self.last_slashed_at[violation.index()];
+        let cooldown = config.violation_cooldowns[violation.index()];
+        if last_slashed_at == 0 || cooldown == 0 {
+            return None;
+        }
+        Some(last_slashed_at.saturating_add(cooldown))
+    }
+
+    /// Start the cooldown for `violation` from an executed slash
+    pub fn record_slash(&mut self, violation: SlashingViolation, now: i64) {
+        self.last_slashed_at[violation.index()] = now;
+    }
+}
+
+/// SECURITY FIX: Slash request account - implements timelock pattern
+/// Stores pending slash requests that can be executed after 24 hours
+/// This prevents instant slashing and allows for dispute resolution
+/// Y2.4: Uses nonce instead of timestamp for PDA seeds
+#[account]
+pub struct SlashRequest {
+    pub operator: Pubkey,             // Target operator to slash (32 bytes)
+    pub amount: u64,                  // Amount to slash (8 bytes)
+    pub violation_type: SlashingViolation, // Type of violation (1 byte)
+    pub evidence_cid: String,         // IPFS CID of evidence (4 + 128 bytes max)
+    pub request_time: i64,            // When request was created (8 bytes)
+    pub authority: Pubkey,            // Oracle/admin who requested (32 bytes)
+    pub executed: bool,               // Whether slash has been executed (1 byte)
+    pub cancelled: bool,              // Whether slash was cancelled (1 byte)
+    pub bump: u8,                     // PDA bump (1 byte)
+    pub slash_nonce: u64,             // Y2.4: Unique nonce for this slash request (8 bytes)
+    pub additional_evidence: [String; 4], // Follow-up evidence CIDs (4 x (4 + 128) bytes max)
+    pub additional_evidence_count: u8, // Number of follow-up CIDs in use (1 byte)
+    pub indexed: bool,                // Recorded in the operator's SlashRequestIndex (1 byte)
+}
+
+impl SlashRequest {
+    /// Maximum evidence CID length (IPFS CIDv1)
+    pub const MAX_EVIDENCE_CID_LEN: usize = 128;
+
+    /// Maximum follow-up evidence CIDs per request
+    pub const MAX_ADDITIONAL_EVIDENCE: u8 = 4;
+
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +                          // operator
         8 +                           // amount
         1 +                           // violation_type (enum)
         4 + Self::MAX_EVIDENCE_CID_LEN + // evidence_cid (string)
@@ -807,34 +1447,470 @@ impl SlashRequest {
         1 +                           // executed
         1 +                           // cancelled
         1 +                           // bump
-        8;                            // slash_nonce (Y2.4)
+        8 +                           // slash_nonce (Y2.4)
+        Self::MAX_ADDITIONAL_EVIDENCE as usize * (4 + Self::MAX_EVIDENCE_CID_LEN) + // additional_evidence
+        1 +                           // additional_evidence_count
+        1;                            // indexed
+
+    /// Check the request can be executed against `stake_account` at `now`
+    ///
+    /// Besides the timelock, the violation cooldown is checked again as of
+    /// the request: a second request queued for the same incident before
+    /// the first was executed is rejected once the first one executes.
+    pub fn check_executable(&self, stake_account: &StakeAccount, config: &GlobalConfig, now: i64) -> Result<()> {
+        // Verify slash request is not already executed or cancelled
+        require!(!self.executed, StakingError::SlashAlreadyExecuted);
+        require!(!self.cancelled, StakingError::SlashCancelled);
+
+        // CRITICAL: Enforce 24-hour timelock
+        let execute_after = self.request_time + SLASH_TIMELOCK_PERIOD;
+        require!(now >= execute_after, StakingError::SlashTimelockNotExpired);
+
+        stake_account.check_violation_cooldown(config, self.violation_type, self.request_time)?;
+
+        // Verify there's still enough stake to slash
+        require!(
+            stake_account.staked_amount >= self.amount,
+            StakingError::InsufficientStakedBalance
+        );
+        Ok(())
+    }
+
+    /// Record a follow-up evidence CID while the request is still pending
+    pub fn append_evidence(&mut self, evidence_cid: String) -> Result<()> {
+        require!(!self.executed, StakingError::SlashAlreadyExecuted);
+        require!(!self.cancelled, StakingError::SlashCancelled);
+        require!(
+            evidence_cid.len() <= Self::MAX_EVIDENCE_CID_LEN,
+            StakingError::EvidenceCidTooLong
+        );
+        require!(
+            self.additional_evidence_count < Self::MAX_ADDITIONAL_EVIDENCE,
+            StakingError::EvidenceLimitReached
+        );
+
+        self.additional_evidence[self.additional_evidence_count as usize] = evidence_cid;
+        self.additional_evidence_count += 1;
+        Ok(())
+    }
+}
+
+/// Lifecycle state of an indexed slash request
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlashRequestStatus {
+    #[default]
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+/// One slash request recorded in a SlashRequestIndex
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlashIndexEntry {
+    pub slash_request: Pubkey,        // SlashRequest PDA (32 bytes)
+    pub slash_nonce: u64,             // Nonce in the PDA seeds (8 bytes)
+    pub request_time: i64,            // When the request was filed (8 bytes)
+    pub status: SlashRequestStatus,   // Pending / Executed / Cancelled (1 byte)
+}
+
+impl SlashIndexEntry {
+    pub const SIZE: usize = 32 + 8 + 8 + 1;
+}
+
+/// Per-operator index of recent slash requests (seeds ["slash_index", operator])
+///
+/// SlashRequest PDAs are seeded by a global nonce the operator doesn't know,
+/// so this ring buffer of the most recent requests is how operators and their
+/// monitoring find requests targeting them without scanning program accounts.
+/// `active_request_count` counts every pending indexed request, including ones
+/// the ring buffer has already overwritten.
+#[account]
+pub struct SlashRequestIndex {
+    pub operator: Pubkey,             // Operator the requests target (32 bytes)
+    pub entries: [SlashIndexEntry; SLASH_INDEX_CAPACITY], // Ring buffer (8 x 49 bytes)
+    pub next_slot: u8,                // Slot the next request is written to (1 byte)
+    pub entry_count: u8,              // Slots in use, up to SLASH_INDEX_CAPACITY (1 byte)
+    pub active_request_count: u32,    // Pending indexed requests (4 bytes)
+    pub bump: u8,                     // PDA bump (1 byte)
+}
+
+impl SlashRequestIndex {
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +                          // operator
+        SLASH_INDEX_CAPACITY * SlashIndexEntry::SIZE + // entries
+        1 +                           // next_slot
+        1 +                           // entry_count
+        4 +                           // active_request_count
+        1;                            // bump
+
+    /// Set up a freshly created index, or check an existing one belongs to
+    /// `operator` (the account is created with `init_if_needed`)
+    pub fn init_or_verify(&mut self, operator: Pubkey, bump: u8) -> Result<()> {
+        if self.operator != Pubkey::default() {
+            require_keys_eq!(self.operator, operator, StakingError::UnauthorizedOperator);
+            return Ok(());
+        }
+        self.operator = operator;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Record a new pending request, overwriting the oldest entry when full
+    pub fn record_request(&mut self, slash_request: Pubkey, slash_nonce: u64, request_time: i64) -> Result<()> {
+        self.entries[self.next_slot as usize] = SlashIndexEntry {
+            slash_request,
+            slash_nonce,
+            request_time,
+            status: SlashRequestStatus::Pending,
+        };
+        self.next_slot = ((self.next_slot as usize + 1) % SLASH_INDEX_CAPACITY) as u8;
+        self.entry_count = (self.entry_count as usize + 1).min(SLASH_INDEX_CAPACITY) as u8;
+        self.active_request_count = self
+            .active_request_count
+            .checked_add(1)
+            .ok_or(StakingError::Overflow)?;
+        Ok(())
+    }
+
+    /// Mark a pending request executed or cancelled
+    ///
+    /// The entry is updated if it is still in the ring buffer; the active
+    /// count drops either way.
+    pub fn record_resolution(&mut self, slash_nonce: u64, status: SlashRequestStatus) -> Result<()> {
+        if let Some(entry) = self.entries[..self.entry_count as usize]
+            .iter_mut()
+            .find(|e| e.slash_nonce == slash_nonce && e.status == SlashRequestStatus::Pending)
+        {
+            entry.status = status;
+        }
+        self.active_request_count = self
+            .active_request_count
+            .checked_sub(1)
+            .ok_or(StakingError::Underflow)?;
+        Ok(())
+    }
+
+    /// Most recently filed request still in the ring buffer
+    pub fn latest(&self) -> Option<&SlashIndexEntry> {
+        if self.entry_count == 0 {
+            return None;
+        }
+        let slot = (self.next_slot as usize + SLASH_INDEX_CAPACITY - 1) % SLASH_INDEX_CAPACITY;
+        Some(&self.entries[slot])
+    }
+}
+
+/// Investigation flag on an operator; while it is unexpired (and
+/// GlobalConfig.exit_delay_on_flag is set) execute_unstake is blocked
+#[account]
+pub struct InvestigationFlag {
+    pub operator: Pubkey,             // Flagged operator (32 bytes)
+    pub flagged_by: Pubkey,           // Oracle that placed the flag (32 bytes)
+    pub flagged_at: i64,              // When the flag was placed (8 bytes)
+    pub expires_at: i64,              // When the flag stops blocking exits (8 bytes)
+    pub bump: u8,                     // PDA bump (1 byte)
+}
+
+impl InvestigationFlag {
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +                          // operator
+        32 +                          // flagged_by
+        8 +                           // flagged_at
+        8 +                           // expires_at
+        1;                            // bump
+
+    /// Whether the flag still blocks exits at `now`
+    pub fn blocks_exit(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+}
+
+/// Per-oracle rate limit for investigation flags
+#[account]
+pub struct OracleFlagQuota {
+    pub oracle: Pubkey,               // Oracle the quota belongs to (32 bytes)
+    pub window_start: i64,            // Start of the current rate window (8 bytes)
+    pub flags_in_window: u8,          // Flags placed in the current window (1 byte)
+    pub bump: u8,                     // PDA bump (1 byte)
+}
+
+impl OracleFlagQuota {
+    pub const MAX_SIZE: usize = 8 +   // discriminator
+        32 +                          // oracle
+        8 +                           // window_start
+        1 +                           // flags_in_window
+        1;                            // bump
+
+    /// Set up a freshly created quota, or check an existing one belongs to
+    /// `oracle` (the account is created with `init_if_needed`)
+    pub fn init_or_verify(&mut self, oracle: Pubkey, bump: u8) -> Result<()> {
+        if self.oracle != Pubkey::default() {
+            require_keys_eq!(self.oracle, oracle, StakingError::UnauthorizedOracle);
+            return Ok(());
+        }
+        self.oracle = oracle;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Count a new flag, starting a fresh window once the current one has passed
+    pub fn record_flag(&mut self, now: i64) -> Result<()> {
+        if now >= self.window_start.saturating_add(FLAG_RATE_WINDOW) {
+            self.window_start = now;
+            self.flags_in_window = 0;
+        }
+        require!(
+            self.flags_in_window < MAX_FLAGS_PER_WINDOW,
+            StakingError::FlagRateLimited
+        );
+        self.flags_in_window += 1;
+        Ok(())
+    }
+}
+
+/// Signer seeds for transfers out of the stake vault
+///
+/// The vault is the token account at the ["stake_vault"] PDA and is its own
+/// token authority (see initialize_stake_vault). Unstakes and both slash
+/// paths move tokens out of it, so they must all sign with these seeds; the
+/// stake account PDA has no authority over the vault.
+fn stake_vault_seeds(bump: &[u8; 1]) -> [&[u8]; 2] {
+    [b"stake_vault", bump]
+}
+
+/// Slash a request would record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlashQuote {
+    /// Percent of staked_amount taken
+    slash_percentage: u64,
+    amount: u64,
+}
+
+/// Amount a slash request for `violation` takes from `stake_account`
+///
+/// Shared by request_slash and emit_slash_preview so the two can't diverge.
+/// Only staked_amount is slashable: request_unstake has already moved
+/// pending_unstake out of it, so pending tokens neither count towards the
+/// amount nor absorb any of it.
+fn quote_slash(stake_account: &StakeAccount, violation: SlashingViolation) -> Result<SlashQuote> {
+    let slash_percentage = violation.slash_percentage();
+    let amount = stake_account
+        .staked_amount
+        .checked_mul(slash_percentage)
+        .ok_or(StakingError::Overflow)?
+        .checked_div(100)
+        .ok_or(StakingError::Underflow)?;
+
+    require!(amount > 0, StakingError::InvalidAmount);
+    require!(
+        stake_account.staked_amount >= amount,
+        StakingError::InsufficientStakedBalance
+    );
+    Ok(SlashQuote { slash_percentage, amount })
+}
+
+/// Expiry of the operator's investigation flag if it currently blocks exits
+///
+/// `flag_info` is the operator's flag PDA, which usually doesn't exist; a
+/// missing account, an expired flag or a disabled exit delay mean no block.
+fn active_investigation_flag(config: &GlobalConfig, flag_info: &AccountInfo, now: i64) -> Result<Option<i64>> {
+    if !config.exit_delay_on_flag || flag_info.owner != &crate::ID || flag_info.data_is_empty() {
+        return Ok(None);
+    }
+    let flag = InvestigationFlag::try_deserialize(&mut &flag_info.data.borrow()[..])?;
+    Ok(flag.blocks_exit(now).then_some(flag.expires_at))
+}
+
+/// Contact hash and notify flags from `operator`'s registry node account
+fn operator_contact(
+    registry_program_id: &Pubkey,
+    node_info: &AccountInfo,
+    operator: Pubkey,
+) -> Result<([u8; 32], u8)> {
+    require_keys_eq!(*node_info.owner, *registry_program_id, StakingError::InvalidNodeAccount);
+    let node = registry::NodeAccount::try_deserialize(&mut &node_info.data.borrow()[..])?;
+    require_keys_eq!(node.operator, operator, StakingError::InvalidNodeAccount);
+    Ok((node.contact_hash, node.notify_flags))
+}
+
+/// Grow a program-owned account to `len` bytes, zero-filled, with the payer
+/// topping up rent; accounts already that long are left alone
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= len {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Clock for instruction handlers
+///
+/// Release builds read the sysvar and ignore `remaining_accounts`.
+#[cfg(not(feature = "test-utils"))]
+fn current_clock(_remaining_accounts: &[AccountInfo]) -> Result<Clock> {
+    Ok(Clock::get()?)
+}
+
+/// Clock for instruction handlers, shifted by the TestClock offset if that
+/// account is among `remaining_accounts` (see set_test_clock)
+#[cfg(feature = "test-utils")]
+fn current_clock(remaining_accounts: &[AccountInfo]) -> Result<Clock> {
+    let mut clock = Clock::get()?;
+    let (test_clock_key, _) = Pubkey::find_program_address(&[b"test_clock"], &crate::ID);
+    let Some(info) = remaining_accounts.iter().find(|info| info.key() == test_clock_key) else {
+        return Ok(clock);
+    };
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(clock);
+    }
+    let test_clock = TestClock::try_deserialize(&mut &info.data.borrow()[..])?;
+    clock.unix_timestamp = clock
+        .unix_timestamp
+        .checked_add(test_clock.offset)
+        .ok_or(StakingError::Overflow)?;
+    Ok(clock)
+}
+
+/// Mark an executed or cancelled request in the operator's index
+///
+/// Requests filed before the index existed aren't in it and are skipped;
+/// indexed requests must be resolved with the index so its count stays exact.
+fn resolve_in_index(
+    slash_request: &SlashRequest,
+    index: Option<&mut SlashRequestIndex>,
+    status: SlashRequestStatus,
+) -> Result<()> {
+    if !slash_request.indexed {
+        return Ok(());
+    }
+    let index = index.ok_or(StakingError::SlashIndexRequired)?;
+    index.record_resolution(slash_request.slash_nonce, status)
+}
+
+/// Offset added to the cluster clock in `test-utils` builds (see current_clock)
+#[cfg(feature = "test-utils")]
+#[account]
+pub struct TestClock {
+    pub offset: i64,                    // Seconds added to unix_timestamp (8 bytes)
+    pub bump: u8,                       // PDA bump (1 byte)
+}
+
+#[cfg(feature = "test-utils")]
+impl TestClock {
+    pub const MAX_SIZE: usize = 8 +  // discriminator
+        8 +                           // offset
+        1;                            // bump
+}
+
+/// SECURITY FIX: Initialize global config (one-time setup)
+/// Now includes registry_program_id parameter
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(
+        init,
+        payer = deployer,
+        space = GlobalConfig::MAX_SIZE,
+        seeds = [b"global_config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Treasury account for slashed tokens
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub deployer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Set the test clock offset (`test-utils` builds only)
+#[cfg(feature = "test-utils")]
+#[derive(Accounts)]
+pub struct SetTestClock<'info> {
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TestClock::MAX_SIZE,
+        seeds = [b"test_clock"],
+        bump
+    )]
+    pub test_clock: Account<'info, TestClock>,
+
+    /// Must be current admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// SECURITY FIX: Update global config
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Must be current admin
+    pub admin: Signer<'info>,
+}
+
+/// SECURITY FIX: Set paused status
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Must be admin
+    pub admin: Signer<'info>,
 }
 
-/// SECURITY FIX: Initialize global config (one-time setup)
-/// Now includes registry_program_id parameter
+/// Toggle exit delays for flagged operators
 #[derive(Accounts)]
-pub struct InitializeGlobalConfig<'info> {
+pub struct SetExitDelayOnFlag<'info> {
     #[account(
-        init,
-        payer = deployer,
-        space = GlobalConfig::MAX_SIZE,
+        mut,
         seeds = [b"global_config"],
-        bump
+        bump = global_config.bump
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
-    /// Treasury account for slashed tokens
-    pub treasury: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub deployer: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
+    /// Must be admin
+    pub admin: Signer<'info>,
 }
 
-/// SECURITY FIX: Update global config
+/// Set a per-violation slash cooldown
 #[derive(Accounts)]
-pub struct UpdateGlobalConfig<'info> {
+pub struct SetViolationCooldown<'info> {
     #[account(
         mut,
         seeds = [b"global_config"],
@@ -842,22 +1918,39 @@ pub struct UpdateGlobalConfig<'info> {
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
-    /// Must be current admin
+    /// Must be admin
     pub admin: Signer<'info>,
 }
 
-/// SECURITY FIX: Set paused status
+/// Create the stake vault
 #[derive(Accounts)]
-pub struct SetPaused<'info> {
+pub struct InitializeStakeVault<'info> {
     #[account(
-        mut,
         seeds = [b"global_config"],
         bump = global_config.bump
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
+    /// Token account owned by its own PDA (see stake_vault_seeds)
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"stake_vault"],
+        bump,
+        token::mint = mint,
+        token::authority = stake_vault
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// AEGIS token mint
+    pub mint: Account<'info, Mint>,
+
     /// Must be admin
+    #[account(mut)]
     pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 /// Initialize stake account
@@ -893,14 +1986,19 @@ pub struct Stake<'info> {
         mut,
         seeds = [b"stake", operator.key().as_ref()],
         bump = stake_account.bump,
-        has_one = operator @ StakingError::UnauthorizedOperator
+        has_one = operator @ StakingError::UnauthorizedOperator,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
     #[account(mut)]
     pub operator_token_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
     pub stake_vault: Account<'info, TokenAccount>,
 
     pub operator: Signer<'info>,
@@ -929,6 +2027,65 @@ pub struct Stake<'info> {
     pub staking_authority: SystemAccount<'info>,
 }
 
+/// Initialize a stake account if needed and stake in one instruction
+#[derive(Accounts)]
+pub struct InitializeAndStake<'info> {
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Created on first use; see initialize_and_stake for why this is safe.
+    /// A brand-new account (zero operator) is stamped current by init_or_verify
+    #[account(
+        init_if_needed,
+        payer = operator,
+        space = StakeAccount::MAX_SIZE,
+        seeds = [b"stake", operator.key().as_ref()],
+        bump,
+        constraint = stake_account.operator == Pubkey::default()
+            || stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(mut)]
+    pub operator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Registry program validated against global_config.registry_program_id
+    #[account(
+        constraint = registry_program.key() == global_config.registry_program_id @ StakingError::InvalidRegistryProgram
+    )]
+    pub registry_program: AccountInfo<'info>,
+
+    /// CHECK: Registry config PDA
+    #[account(mut)]
+    pub registry_config: AccountInfo<'info>,
+
+    /// CHECK: Node account in registry
+    #[account(mut)]
+    pub node_account: AccountInfo<'info>,
+
+    /// Staking program PDA that acts as authority for registry CPI
+    #[account(
+        seeds = [b"staking_authority"],
+        bump
+    )]
+    pub staking_authority: SystemAccount<'info>,
+}
+
 /// Request unstake
 #[derive(Accounts)]
 pub struct RequestUnstake<'info> {
@@ -943,10 +2100,19 @@ pub struct RequestUnstake<'info> {
         mut,
         seeds = [b"stake", operator.key().as_ref()],
         bump = stake_account.bump,
-        has_one = operator @ StakingError::UnauthorizedOperator
+        has_one = operator @ StakingError::UnauthorizedOperator,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
+    /// CHECK: Operator's investigation flag PDA; usually uninitialized, and
+    /// only read when owned by this program (see active_investigation_flag)
+    #[account(
+        seeds = [b"investigation_flag", operator.key().as_ref()],
+        bump
+    )]
+    pub investigation_flag: UncheckedAccount<'info>,
+
     pub operator: Signer<'info>,
 }
 
@@ -965,16 +2131,29 @@ pub struct ExecuteUnstake<'info> {
         mut,
         seeds = [b"stake", operator.key().as_ref()],
         bump = stake_account.bump,
-        has_one = operator @ StakingError::UnauthorizedOperator
+        has_one = operator @ StakingError::UnauthorizedOperator,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
     pub stake_vault: Account<'info, TokenAccount>,
 
     #[account(mut)]
     pub operator_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: Operator's investigation flag PDA; usually uninitialized, and
+    /// only read when owned by this program (see active_investigation_flag)
+    #[account(
+        seeds = [b"investigation_flag", operator.key().as_ref()],
+        bump
+    )]
+    pub investigation_flag: UncheckedAccount<'info>,
+
     pub operator: Signer<'info>,
     pub token_program: Program<'info, Token>,
 
@@ -1008,7 +2187,8 @@ pub struct CancelUnstake<'info> {
         mut,
         seeds = [b"stake", operator.key().as_ref()],
         bump = stake_account.bump,
-        has_one = operator @ StakingError::UnauthorizedOperator
+        has_one = operator @ StakingError::UnauthorizedOperator,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
@@ -1029,7 +2209,8 @@ pub struct SlashStake<'info> {
     #[account(
         mut,
         seeds = [b"stake", stake_account.operator.as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
@@ -1090,7 +2271,8 @@ pub struct RequestSlash<'info> {
     /// Stake account to slash (read-only to calculate slash amount)
     #[account(
         seeds = [b"stake", stake_account.operator.as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
@@ -1105,6 +2287,21 @@ pub struct RequestSlash<'info> {
     )]
     pub slash_request: Account<'info, SlashRequest>,
 
+    /// Operator's recent slash requests, created on the first request against
+    /// them (seeded by the operator, and init_or_verify checks the owner)
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        space = SlashRequestIndex::MAX_SIZE,
+        seeds = [b"slash_index", stake_account.operator.as_ref()],
+        bump
+    )]
+    pub slash_request_index: Account<'info, SlashRequestIndex>,
+
+    /// CHECK: Operator's registry node account (optional); owner and operator
+    /// are checked by operator_contact
+    pub node_account: Option<UncheckedAccount<'info>>,
+
     /// Oracle authorized to request slashing
     /// Must match global_config.admin_authority
     #[account(mut)]
@@ -1130,7 +2327,8 @@ pub struct ExecuteSlash<'info> {
     #[account(
         mut,
         seeds = [b"stake", slash_request.operator.as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
@@ -1143,6 +2341,14 @@ pub struct ExecuteSlash<'info> {
     )]
     pub slash_request: Account<'info, SlashRequest>,
 
+    /// Operator's slash request index (required unless the request predates it)
+    #[account(
+        mut,
+        seeds = [b"slash_index", slash_request.operator.as_ref()],
+        bump = slash_request_index.bump
+    )]
+    pub slash_request_index: Option<Account<'info, SlashRequestIndex>>,
+
     #[account(
         mut,
         seeds = [b"stake_vault"],
@@ -1161,49 +2367,218 @@ pub struct ExecuteSlash<'info> {
     // Registry CPI accounts for stake synchronization
     /// CHECK: Registry program validated against global_config.registry_program_id
     #[account(
-        constraint = registry_program.key() == global_config.registry_program_id @ StakingError::InvalidRegistryProgram
+        constraint = registry_program.key() == global_config.registry_program_id @ StakingError::InvalidRegistryProgram
+    )]
+    pub registry_program: AccountInfo<'info>,
+
+    /// CHECK: Registry config PDA
+    #[account(mut)]
+    pub registry_config: AccountInfo<'info>,
+
+    /// CHECK: Node account in registry
+    #[account(mut)]
+    pub node_account: AccountInfo<'info>,
+
+    /// Staking program PDA for registry CPI signing
+    #[account(
+        seeds = [b"staking_authority"],
+        bump
+    )]
+    pub staking_authority: SystemAccount<'info>,
+}
+
+/// SECURITY FIX: Cancel slash context (admin only)
+/// Allows admin to cancel pending slash requests (false positive handling)
+/// Y2.4: Uses nonce instead of timestamp for PDA seeds
+#[derive(Accounts)]
+pub struct CancelSlash<'info> {
+    /// Global config stores admin authority
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Slash request to cancel
+    /// Y2.4: Uses nonce in seeds instead of timestamp
+    #[account(
+        mut,
+        seeds = [b"slash_request", slash_request.operator.as_ref(), &slash_request.slash_nonce.to_le_bytes()],
+        bump = slash_request.bump
+    )]
+    pub slash_request: Account<'info, SlashRequest>,
+
+    /// Operator's slash request index (required unless the request predates it)
+    #[account(
+        mut,
+        seeds = [b"slash_index", slash_request.operator.as_ref()],
+        bump = slash_request_index.bump
+    )]
+    pub slash_request_index: Option<Account<'info, SlashRequestIndex>>,
+
+    /// Admin authority (must match global_config.admin_authority)
+    pub admin: Signer<'info>,
+}
+
+/// Append evidence context (requesting oracle only)
+#[derive(Accounts)]
+pub struct AppendEvidence<'info> {
+    /// Pending slash request to extend
+    #[account(
+        mut,
+        seeds = [b"slash_request", slash_request.operator.as_ref(), &slash_request.slash_nonce.to_le_bytes()],
+        bump = slash_request.bump
+    )]
+    pub slash_request: Account<'info, SlashRequest>,
+
+    /// Oracle that filed the request (must match slash_request.authority)
+    pub oracle: Signer<'info>,
+}
+
+/// Place an investigation flag (authorized oracle only)
+#[derive(Accounts)]
+pub struct PlaceInvestigationFlag<'info> {
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Operator to flag
+    #[account(
+        seeds = [b"stake", stake_account.operator.as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// Operator's flag, re-used once a previous flag expired
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        space = InvestigationFlag::MAX_SIZE,
+        seeds = [b"investigation_flag", stake_account.operator.as_ref()],
+        bump
+    )]
+    pub investigation_flag: Account<'info, InvestigationFlag>,
+
+    /// Oracle's flag rate limit, created on its first flag
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        space = OracleFlagQuota::MAX_SIZE,
+        seeds = [b"flag_quota", oracle.key().as_ref()],
+        bump
+    )]
+    pub flag_quota: Account<'info, OracleFlagQuota>,
+
+    /// Must match global_config.admin_authority
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Clear an investigation flag early (admin only)
+#[derive(Accounts)]
+pub struct ClearInvestigationFlag<'info> {
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"investigation_flag", investigation_flag.operator.as_ref()],
+        bump = investigation_flag.bump,
+        close = admin
+    )]
+    pub investigation_flag: Account<'info, InvestigationFlag>,
+
+    /// Admin authority (must match global_config.admin_authority)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+/// Attest to an operator's stake (read-only)
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct EmitStakeAttestation<'info> {
+    #[account(
+        seeds = [b"stake", operator.as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+/// Preview a slash against an operator (read-only)
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct EmitSlashPreview<'info> {
+    /// Per-violation cooldowns
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        seeds = [b"stake", operator.as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.is_current_schema() @ StakingError::StakeAccountNotMigrated
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+/// Migrate the global config to the current layout
+#[derive(Accounts)]
+pub struct MigrateGlobalConfig<'info> {
+    /// CHECK: Older layouts are shorter than GlobalConfig::MAX_SIZE and
+    /// can't be decoded as Account<GlobalConfig> until resized; the PDA and
+    /// owner are checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump,
+        owner = crate::ID
     )]
-    pub registry_program: AccountInfo<'info>,
-
-    /// CHECK: Registry config PDA
-    #[account(mut)]
-    pub registry_config: AccountInfo<'info>,
+    pub global_config: UncheckedAccount<'info>,
 
-    /// CHECK: Node account in registry
+    /// Admin authority (must match global_config.admin_authority); pays
+    /// any extra rent
     #[account(mut)]
-    pub node_account: AccountInfo<'info>,
+    pub admin: Signer<'info>,
 
-    /// Staking program PDA for registry CPI signing
-    #[account(
-        seeds = [b"staking_authority"],
-        bump
-    )]
-    pub staking_authority: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-/// SECURITY FIX: Cancel slash context (admin only)
-/// Allows admin to cancel pending slash requests (false positive handling)
-/// Y2.4: Uses nonce instead of timestamp for PDA seeds
+/// Migrate a legacy stake account to the current schema
 #[derive(Accounts)]
-pub struct CancelSlash<'info> {
-    /// Global config stores admin authority
+#[instruction(operator: Pubkey)]
+pub struct MigrateStakeAccount<'info> {
     #[account(
         seeds = [b"global_config"],
         bump = global_config.bump
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
-    /// Slash request to cancel
-    /// Y2.4: Uses nonce in seeds instead of timestamp
+    /// CHECK: Legacy layouts are shorter than StakeAccount::MAX_SIZE and
+    /// can't be decoded as Account<StakeAccount> until resized; the PDA and
+    /// owner are checked here and the data is decoded in the handler
     #[account(
         mut,
-        seeds = [b"slash_request", slash_request.operator.as_ref(), &slash_request.slash_nonce.to_le_bytes()],
-        bump = slash_request.bump
+        seeds = [b"stake", operator.as_ref()],
+        bump,
+        owner = crate::ID
     )]
-    pub slash_request: Account<'info, SlashRequest>,
+    pub stake_account: UncheckedAccount<'info>,
 
-    /// Admin authority (must match global_config.admin_authority)
+    /// Admin authority (must match global_config.admin_authority); pays
+    /// any extra rent
+    #[account(mut)]
     pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 /// Events
@@ -1226,6 +2601,23 @@ pub struct UnstakeRequestedEvent {
     pub operator: Pubkey,
     pub amount: u64,
     pub cooldown_ends_at: i64,
+    /// Expiry of the investigation flag blocking execute_unstake, if any
+    pub exit_blocked_until: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvestigationFlagPlacedEvent {
+    pub operator: Pubkey,
+    pub oracle: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvestigationFlagClearedEvent {
+    pub operator: Pubkey,
+    pub admin: Pubkey,
     pub timestamp: i64,
 }
 
@@ -1253,6 +2645,137 @@ pub struct StakeSlashedEvent {
     pub timestamp: i64,
 }
 
+/// Uniform post-state summary emitted after every stake mutation
+///
+/// Emitted alongside the instruction-specific event so indexers can track an
+/// operator's position from a single event type.
+#[event]
+pub struct StakePositionChangedEvent {
+    pub operator: Pubkey,
+    pub staked_amount: u64,
+    pub pending_unstake: u64,
+    pub change_kind: StakeChangeKind,
+    /// Amount moved by the mutation (direction is implied by change_kind)
+    pub delta: u64,
+    pub timestamp: i64,
+}
+
+impl StakePositionChangedEvent {
+    pub fn new(
+        stake_account: &StakeAccount,
+        change_kind: StakeChangeKind,
+        delta: u64,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            operator: stake_account.operator,
+            staked_amount: stake_account.staked_amount,
+            pending_unstake: stake_account.pending_unstake,
+            change_kind,
+            delta,
+            timestamp,
+        }
+    }
+}
+
+/// Current StakeAttestationEvent layout version
+pub const STAKE_ATTESTATION_VERSION: u8 = 1;
+
+/// Lock tier reported for stakes with no lock commitment
+///
+/// The program has no lock tiers yet, so every attestation reports this.
+pub const NO_LOCK_TIER: u8 = 0;
+
+/// Versioned stake snapshot for cross-program consumers
+///
+/// Stability guarantee: within a `version`, fields are never removed,
+/// reordered or retyped. New fields are only appended, and any change to
+/// the layout or meaning of a field bumps `version`, so consumers should
+/// check `version` before decoding and reject versions they don't know.
+#[event]
+pub struct StakeAttestationEvent {
+    pub operator: Pubkey,
+    pub staked_amount: u64,
+    pub pending_unstake: u64,
+    /// Always NO_LOCK_TIER until lock tiers exist
+    pub lock_tier: u8,
+    /// Slot the attestation was emitted in
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl StakeAttestationEvent {
+    pub fn new(stake_account: &StakeAccount, slot: u64) -> Self {
+        Self {
+            operator: stake_account.operator,
+            staked_amount: stake_account.staked_amount,
+            pending_unstake: stake_account.pending_unstake,
+            lock_tier: NO_LOCK_TIER,
+            slot,
+            version: STAKE_ATTESTATION_VERSION,
+        }
+    }
+}
+
+/// StakeAccount fields migrate_stake_account may repair
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeCounters {
+    pub total_staked_ever: u64,
+    pub total_unstaked_ever: u64,
+    pub unstake_request_time: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&StakeAccount> for StakeCounters {
+    fn from(stake_account: &StakeAccount) -> Self {
+        Self {
+            total_staked_ever: stake_account.total_staked_ever,
+            total_unstaked_ever: stake_account.total_unstaked_ever,
+            unstake_request_time: stake_account.unstake_request_time,
+            created_at: stake_account.created_at,
+            updated_at: stake_account.updated_at,
+        }
+    }
+}
+
+/// Emitted when migrate_global_config grows the config to the current layout
+#[event]
+pub struct GlobalConfigMigratedEvent {
+    pub from_len: u32,
+    pub to_len: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when migrate_stake_account brings an account to the current schema
+#[event]
+#[derive(Debug)]
+pub struct StakeAccountMigratedEvent {
+    pub operator: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub before: StakeCounters,
+    pub after: StakeCounters,
+    pub timestamp: i64,
+}
+
+/// Instruction that produced a StakePositionChangedEvent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeChangeKind {
+    /// Tokens added to staked_amount
+    Stake,
+    /// Tokens moved from staked_amount to pending_unstake
+    RequestUnstake,
+    /// Pending unstake returned to the operator after cooldown
+    ExecuteUnstake,
+    /// Pending unstake moved back to staked_amount
+    CancelUnstake,
+    /// Staked tokens slashed via the timelocked request/execute path
+    ExecuteSlash,
+    /// Staked tokens slashed directly by the admin
+    SlashStake,
+}
+
 /// SECURITY FIX: Event emitted when slash is requested (Phase 1 of timelock)
 #[event]
 pub struct SlashRequestedEvent {
@@ -1262,6 +2785,28 @@ pub struct SlashRequestedEvent {
     pub evidence_cid: String,
     pub request_time: i64,
     pub execute_after: i64,  // Timestamp when slash can be executed
+    /// Operator's published contact hash, zeros if none or no node account was passed
+    pub contact_hash: [u8; 32],
+    /// Operator's registry notify_flags
+    pub notify_flags: u8,
+}
+
+/// What a slash request would take from an operator right now
+#[event]
+pub struct SlashPreviewEvent {
+    pub operator: Pubkey,
+    pub violation_type: SlashingViolation,
+    pub slash_percentage: u8,
+    /// Tokens the request would record (taken from staked_amount only)
+    pub amount: u64,
+    pub staked_amount: u64,
+    /// Not slashable; shown so dashboards needn't fetch the stake account
+    pub pending_unstake: u64,
+    /// staked_amount after the slash executes
+    pub remaining_staked: u64,
+    /// When a request for this violation is allowed again (0 = now)
+    pub cooldown_ends_at: i64,
+    pub timestamp: i64,
 }
 
 /// SECURITY FIX: Event emitted when slash is executed (Phase 2 of timelock)
@@ -1285,6 +2830,17 @@ pub struct SlashCancelledEvent {
     pub timestamp: i64,
 }
 
+/// Event emitted when follow-up evidence is attached to a pending slash request
+#[event]
+pub struct EvidenceAppendedEvent {
+    pub operator: Pubkey,
+    pub slash_nonce: u64,
+    pub evidence_cid: String,
+    pub evidence_index: u8,   // Position in additional_evidence
+    pub appended_by: Pubkey,
+    pub timestamp: i64,
+}
+
 /// Slashing violation types per whitepaper
 /// Used by automated_slash instruction to determine penalty percentage
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
@@ -1301,6 +2857,27 @@ pub enum SlashingViolation {
     MaliciousBehavior,
 }
 
+impl SlashingViolation {
+    /// Number of violation types (length of per-violation arrays)
+    pub const COUNT: usize = 5;
+
+    /// Position in per-violation arrays (declaration order)
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Percent of staked_amount a slash for this violation takes (per whitepaper)
+    pub fn slash_percentage(self) -> u64 {
+        match self {
+            SlashingViolation::Offline48Hours => 10,         // 10% for extended offline
+            SlashingViolation::LowUptime => 5,               // 5% for <90% uptime
+            SlashingViolation::ChallengeFailed => 15,        // 15% for failed challenge
+            SlashingViolation::DataIntegrityViolation => 25, // 25% for data corruption
+            SlashingViolation::MaliciousBehavior => 100,     // 100% for attacks
+        }
+    }
+}
+
 /// Custom errors
 #[error_code]
 pub enum StakingError {
@@ -1374,4 +2951,790 @@ pub enum StakingError {
     /// Y2.7: Cooldown period validation
     #[msg("Cooldown period must be at least 1 day (86400 seconds)")]
     CooldownTooShort,
+
+    #[msg("Slash request already holds the maximum follow-up evidence (4 CIDs)")]
+    EvidenceLimitReached,
+
+    #[msg("Slash request is indexed; pass the operator's slash request index")]
+    SlashIndexRequired,
+
+    #[msg("Exit delay on investigation flag is disabled")]
+    ExitDelayDisabled,
+
+    #[msg("Investigation flag duration must be between 1 second and 7 days")]
+    InvalidFlagDuration,
+
+    #[msg("Operator already has an active investigation flag")]
+    InvestigationFlagActive,
+
+    #[msg("Oracle has reached its investigation flag limit for this window")]
+    FlagRateLimited,
+
+    #[msg("Unstake is blocked while the operator is under investigation")]
+    UnderInvestigation,
+
+    #[msg("Operator was recently slashed for this violation type; cooldown still active")]
+    ViolationCooldownActive,
+
+    #[msg("Violation cooldown must not be negative")]
+    InvalidViolationCooldown,
+
+    #[msg("Stake account uses a legacy schema; run migrate_stake_account first")]
+    StakeAccountNotMigrated,
+
+    #[msg("Test clock offset cannot move backwards")]
+    TestClockRewind,
+
+    #[msg("Node account is not the operator's registry node")]
+    InvalidNodeAccount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_account(staked_amount: u64, pending_unstake: u64) -> StakeAccount {
+        StakeAccount {
+            operator: Pubkey::new_unique(),
+            staked_amount,
+            pending_unstake,
+            unstake_request_time: 0,
+            total_staked_ever: 0,
+            total_unstaked_ever: 0,
+            created_at: 0,
+            updated_at: 0,
+            bump: 255,
+            last_slashed_at: [0; SlashingViolation::COUNT],
+            schema_version: CURRENT_STAKE_SCHEMA_VERSION,
+        }
+    }
+
+    #[test]
+    fn test_position_event_reflects_post_state() {
+        // (post-state staked, post-state pending, kind, delta)
+        let cases = [
+            (500, 0, StakeChangeKind::Stake, 500),
+            (300, 200, StakeChangeKind::RequestUnstake, 200),
+            (300, 0, StakeChangeKind::ExecuteUnstake, 200),
+            (500, 0, StakeChangeKind::CancelUnstake, 200),
+            (450, 0, StakeChangeKind::ExecuteSlash, 50),
+            (400, 0, StakeChangeKind::SlashStake, 50),
+        ];
+
+        for (staked, pending, kind, delta) in cases {
+            let account = stake_account(staked, pending);
+            let event = StakePositionChangedEvent::new(&account, kind, delta, 1_700_000_000);
+
+            assert_eq!(event.operator, account.operator);
+            assert_eq!(event.staked_amount, staked);
+            assert_eq!(event.pending_unstake, pending);
+            assert_eq!(event.change_kind, kind);
+            assert_eq!(event.delta, delta);
+            assert_eq!(event.timestamp, 1_700_000_000);
+        }
+    }
+
+    fn zeroed_stake_account() -> StakeAccount {
+        let mut account = stake_account(0, 0);
+        account.operator = Pubkey::default();
+        account.bump = 0;
+        account
+    }
+
+    #[test]
+    fn test_init_or_verify_fresh_account() {
+        let operator = Pubkey::new_unique();
+        let mut account = zeroed_stake_account();
+
+        assert!(account.init_or_verify(operator, 254, 1_700_000_000).unwrap());
+        assert_eq!(account.operator, operator);
+        assert_eq!(account.created_at, 1_700_000_000);
+        assert_eq!(account.updated_at, 1_700_000_000);
+        assert_eq!(account.bump, 254);
+    }
+
+    #[test]
+    fn test_init_or_verify_preserves_existing_account() {
+        let mut account = stake_account(500, 100);
+        account.total_staked_ever = 900;
+        account.total_unstaked_ever = 400;
+        account.created_at = 1_690_000_000;
+        account.updated_at = 1_695_000_000;
+        let before = account.clone();
+
+        assert!(!account.init_or_verify(before.operator, 1, 1_700_000_000).unwrap());
+        assert_eq!(account.total_staked_ever, 900);
+        assert_eq!(account.total_unstaked_ever, 400);
+        assert_eq!(account.created_at, 1_690_000_000);
+        assert_eq!(account.updated_at, before.updated_at);
+        assert_eq!(account.staked_amount, 500);
+        assert_eq!(account.pending_unstake, 100);
+        assert_eq!(account.bump, 255);
+    }
+
+    #[test]
+    fn test_init_or_verify_rejects_other_operator() {
+        let mut account = stake_account(500, 0);
+        let owner = account.operator;
+
+        assert!(account.init_or_verify(Pubkey::new_unique(), 255, 1_700_000_000).is_err());
+        assert_eq!(account.operator, owner);
+        assert_eq!(account.staked_amount, 500);
+    }
+
+    #[test]
+    fn test_attestation_matches_account() {
+        let account = stake_account(750, 250);
+        let event = StakeAttestationEvent::new(&account, 42);
+
+        assert_eq!(event.operator, account.operator);
+        assert_eq!(event.staked_amount, 750);
+        assert_eq!(event.pending_unstake, 250);
+        assert_eq!(event.lock_tier, NO_LOCK_TIER);
+        assert_eq!(event.slot, 42);
+        assert_eq!(event.version, STAKE_ATTESTATION_VERSION);
+    }
+
+    fn pending_slash_request() -> SlashRequest {
+        SlashRequest {
+            operator: Pubkey::new_unique(),
+            amount: 50,
+            violation_type: SlashingViolation::ChallengeFailed,
+            evidence_cid: "QmInitialEvidence".to_string(),
+            request_time: 1_700_000_000,
+            authority: Pubkey::new_unique(),
+            executed: false,
+            cancelled: false,
+            bump: 255,
+            slash_nonce: 0,
+            additional_evidence: Default::default(),
+            additional_evidence_count: 0,
+            indexed: true,
+        }
+    }
+
+    #[test]
+    fn test_append_evidence_up_to_limit() {
+        let mut request = pending_slash_request();
+
+        request.append_evidence("QmProbeResults".to_string()).unwrap();
+        request.append_evidence("QmPacketCapture".to_string()).unwrap();
+        assert_eq!(request.additional_evidence_count, 2);
+        assert_eq!(request.additional_evidence[0], "QmProbeResults");
+        assert_eq!(request.additional_evidence[1], "QmPacketCapture");
+
+        request.append_evidence("QmThird".to_string()).unwrap();
+        request.append_evidence("QmFourth".to_string()).unwrap();
+        let fifth = request.append_evidence("QmFifth".to_string());
+        assert_eq!(fifth.unwrap_err(), StakingError::EvidenceLimitReached.into());
+        assert_eq!(request.additional_evidence_count, SlashRequest::MAX_ADDITIONAL_EVIDENCE);
+    }
+
+    #[test]
+    fn test_append_evidence_requires_pending_request() {
+        let mut cancelled = pending_slash_request();
+        cancelled.cancelled = true;
+        let err = cancelled.append_evidence("QmLate".to_string()).unwrap_err();
+        assert_eq!(err, StakingError::SlashCancelled.into());
+
+        let mut executed = pending_slash_request();
+        executed.executed = true;
+        let err = executed.append_evidence("QmLate".to_string()).unwrap_err();
+        assert_eq!(err, StakingError::SlashAlreadyExecuted.into());
+    }
+
+    #[test]
+    fn test_append_evidence_rejects_long_cid() {
+        let mut request = pending_slash_request();
+        let too_long = "Q".repeat(SlashRequest::MAX_EVIDENCE_CID_LEN + 1);
+        let err = request.append_evidence(too_long).unwrap_err();
+        assert_eq!(err, StakingError::EvidenceCidTooLong.into());
+        assert_eq!(request.additional_evidence_count, 0);
+    }
+
+    fn empty_index(operator: Pubkey) -> SlashRequestIndex {
+        let mut index = SlashRequestIndex {
+            operator: Pubkey::default(),
+            entries: Default::default(),
+            next_slot: 0,
+            entry_count: 0,
+            active_request_count: 0,
+            bump: 0,
+        };
+        index.init_or_verify(operator, 254).unwrap();
+        index
+    }
+
+    #[test]
+    fn test_slash_index_tracks_creation_and_resolution() {
+        let operator = Pubkey::new_unique();
+        let mut index = empty_index(operator);
+        assert!(index.latest().is_none());
+
+        let first = Pubkey::new_unique();
+        index.record_request(first, 7, 1_700_000_000).unwrap();
+        let latest = index.latest().unwrap();
+        assert_eq!(latest.slash_request, first);
+        assert_eq!(latest.slash_nonce, 7);
+        assert_eq!(latest.status, SlashRequestStatus::Pending);
+        assert_eq!(index.active_request_count, 1);
+
+        index.record_resolution(7, SlashRequestStatus::Executed).unwrap();
+        assert_eq!(index.latest().unwrap().status, SlashRequestStatus::Executed);
+        assert_eq!(index.active_request_count, 0);
+
+        // A resolved request can't be resolved again through the index
+        assert!(index.record_resolution(7, SlashRequestStatus::Cancelled).is_err());
+
+        // Same index for the same operator, rejected for another
+        assert!(index.init_or_verify(operator, 1).is_ok());
+        assert_eq!(index.bump, 254);
+        assert!(index.init_or_verify(Pubkey::new_unique(), 1).is_err());
+    }
+
+    #[test]
+    fn test_slash_index_ring_buffer_wraps() {
+        let mut index = empty_index(Pubkey::new_unique());
+        let requests: Vec<Pubkey> = (0..SLASH_INDEX_CAPACITY + 3).map(|_| Pubkey::new_unique()).collect();
+        for (nonce, request) in requests.iter().enumerate() {
+            index.record_request(*request, nonce as u64, 1_700_000_000 + nonce as i64).unwrap();
+        }
+
+        assert_eq!(index.entry_count as usize, SLASH_INDEX_CAPACITY);
+        assert_eq!(index.next_slot, 3);
+        assert_eq!(index.latest().unwrap().slash_request, *requests.last().unwrap());
+        // The three oldest were overwritten
+        let kept: Vec<u64> = index.entries.iter().map(|e| e.slash_nonce).collect();
+        for nonce in 0..3 {
+            assert!(!kept.contains(&nonce));
+        }
+        assert_eq!(index.active_request_count as usize, requests.len());
+
+        // Resolving an overwritten request still lowers the count
+        index.record_resolution(0, SlashRequestStatus::Cancelled).unwrap();
+        assert_eq!(index.active_request_count as usize, requests.len() - 1);
+        assert!(index.entries.iter().all(|e| e.status == SlashRequestStatus::Pending));
+    }
+
+    #[test]
+    fn test_slash_index_count_with_overlapping_requests() {
+        let mut index = empty_index(Pubkey::new_unique());
+        index.record_request(Pubkey::new_unique(), 1, 100).unwrap();
+        index.record_request(Pubkey::new_unique(), 2, 200).unwrap();
+        index.record_request(Pubkey::new_unique(), 3, 300).unwrap();
+        assert_eq!(index.active_request_count, 3);
+
+        index.record_resolution(2, SlashRequestStatus::Cancelled).unwrap();
+        assert_eq!(index.active_request_count, 2);
+        index.record_request(Pubkey::new_unique(), 4, 400).unwrap();
+        index.record_resolution(1, SlashRequestStatus::Executed).unwrap();
+        assert_eq!(index.active_request_count, 2);
+
+        let statuses: Vec<_> = index.entries[..4].iter().map(|e| (e.slash_nonce, e.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (1, SlashRequestStatus::Executed),
+                (2, SlashRequestStatus::Cancelled),
+                (3, SlashRequestStatus::Pending),
+                (4, SlashRequestStatus::Pending),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_in_index_requires_index_for_indexed_requests() {
+        let request = pending_slash_request();
+        let err = resolve_in_index(&request, None, SlashRequestStatus::Executed).unwrap_err();
+        assert_eq!(err, StakingError::SlashIndexRequired.into());
+
+        // Requests filed before the index existed resolve without it
+        let mut legacy = pending_slash_request();
+        legacy.indexed = false;
+        assert!(resolve_in_index(&legacy, None, SlashRequestStatus::Executed).is_ok());
+    }
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn flag_config(exit_delay_on_flag: bool) -> GlobalConfig {
+        GlobalConfig {
+            admin_authority: Pubkey::new_unique(),
+            min_stake_amount: 0,
+            unstake_cooldown_period: MIN_COOLDOWN_PERIOD,
+            treasury: Pubkey::new_unique(),
+            registry_program_id: Pubkey::new_unique(),
+            paused: false,
+            bump: 255,
+            slash_nonce: 0,
+            exit_delay_on_flag,
+            violation_cooldowns: DEFAULT_VIOLATION_COOLDOWNS,
+        }
+    }
+
+    /// Serialized flag data as stored in the operator's flag PDA
+    fn flag_data(expires_at: i64) -> Vec<u8> {
+        let flag = InvestigationFlag {
+            operator: Pubkey::new_unique(),
+            flagged_by: Pubkey::new_unique(),
+            flagged_at: NOW,
+            expires_at,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        flag.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn flag_block(config: &GlobalConfig, owner: &Pubkey, data: &mut [u8], now: i64) -> Option<i64> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        active_investigation_flag(config, &info, now).unwrap()
+    }
+
+    /// Serialized registry node account for `operator`
+    fn node_data(operator: Pubkey, contact_hash: [u8; 32], notify_flags: u8) -> Vec<u8> {
+        let node = registry::NodeAccount {
+            operator,
+            metadata_url: String::new(),
+            status: registry::NodeStatus::Active,
+            stake_amount: 0,
+            registered_at: NOW,
+            updated_at: NOW,
+            reputation_score: 5000,
+            last_heartbeat: NOW,
+            total_heartbeats: 1,
+            missed_heartbeats: 0,
+            bump: 255,
+            node_id: 0,
+            routing_score: 0,
+            maintenance_windows: Default::default(),
+            last_client_timestamp: 0,
+            heartbeat_sequence: 0,
+            zone_assignments: 0,
+            heartbeat_gaps: Default::default(),
+            heartbeat_gap_count: 0,
+            deactivation_count: 0,
+            deactivation_window_start: 0,
+            contact_hash,
+            notify_flags,
+            uptime_bps: 10_000,
+        };
+        let mut data = Vec::new();
+        node.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn contact(owner: &Pubkey, data: &mut [u8], operator: Pubkey) -> Result<([u8; 32], u8)> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        operator_contact(&registry::ID, &info, operator)
+    }
+
+    #[test]
+    fn test_offline_slash_reads_operator_contact() {
+        let operator = Pubkey::new_unique();
+        let flags = registry::NOTIFY_SLASH_REQUESTS;
+        let mut data = node_data(operator, [9; 32], flags);
+
+        assert_eq!(contact(&registry::ID, &mut data, operator).unwrap(), ([9; 32], flags));
+    }
+
+    #[test]
+    fn test_operator_contact_rejects_foreign_node() {
+        let operator = Pubkey::new_unique();
+
+        // Another operator's node
+        let mut data = node_data(Pubkey::new_unique(), [9; 32], 0);
+        let err = contact(&registry::ID, &mut data, operator).unwrap_err();
+        assert_eq!(err, StakingError::InvalidNodeAccount.into());
+
+        // Node data not owned by the registry
+        let mut data = node_data(operator, [9; 32], 0);
+        let err = contact(&Pubkey::new_unique(), &mut data, operator).unwrap_err();
+        assert_eq!(err, StakingError::InvalidNodeAccount.into());
+    }
+
+    #[test]
+    fn test_flag_blocks_execute_until_expiry() {
+        let config = flag_config(true);
+        let expires_at = NOW + MAX_INVESTIGATION_FLAG_DURATION;
+        let mut data = flag_data(expires_at);
+
+        // request_unstake only reports the block; execute_unstake fails on it
+        assert_eq!(flag_block(&config, &crate::ID, &mut data, NOW), Some(expires_at));
+        assert_eq!(flag_block(&config, &crate::ID, &mut data, expires_at - 1), Some(expires_at));
+
+        // Expiry unblocks without any transaction
+        assert_eq!(flag_block(&config, &crate::ID, &mut data, expires_at), None);
+    }
+
+    #[test]
+    fn test_cleared_or_disabled_flag_does_not_block() {
+        let expires_at = NOW + 3_600;
+
+        // Admin clear closes the PDA: empty and owned by the system program
+        assert_eq!(flag_block(&flag_config(true), &Pubkey::default(), &mut [], NOW), None);
+
+        // Exit delays switched off
+        let mut data = flag_data(expires_at);
+        assert_eq!(flag_block(&flag_config(false), &crate::ID, &mut data, NOW), None);
+
+        // Data at the flag address not owned by this program is ignored
+        let mut data = flag_data(expires_at);
+        assert_eq!(flag_block(&flag_config(true), &Pubkey::new_unique(), &mut data, NOW), None);
+    }
+
+    #[test]
+    fn test_flag_quota_limits_each_oracle() {
+        let oracle = Pubkey::new_unique();
+        let mut quota = OracleFlagQuota {
+            oracle: Pubkey::default(),
+            window_start: 0,
+            flags_in_window: 0,
+            bump: 0,
+        };
+        quota.init_or_verify(oracle, 254).unwrap();
+
+        for i in 0..MAX_FLAGS_PER_WINDOW as i64 {
+            quota.record_flag(NOW + i).unwrap();
+        }
+        let err = quota.record_flag(NOW + 60).unwrap_err();
+        assert_eq!(err, StakingError::FlagRateLimited.into());
+
+        // A new window restores the allowance
+        quota.record_flag(NOW + FLAG_RATE_WINDOW).unwrap();
+        assert_eq!(quota.flags_in_window, 1);
+
+        // Another oracle's PDA can't be used
+        let err = quota.init_or_verify(Pubkey::new_unique(), 254).unwrap_err();
+        assert_eq!(err, StakingError::UnauthorizedOracle.into());
+    }
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_violation_cooldown_blocks_repeat_offline_slash() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+
+        // Never slashed: nothing to wait for
+        account.check_violation_cooldown(&config, SlashingViolation::Offline48Hours, NOW).unwrap();
+
+        account.record_slash(SlashingViolation::Offline48Hours, NOW);
+        let err = account
+            .check_violation_cooldown(&config, SlashingViolation::Offline48Hours, NOW + DAY)
+            .unwrap_err();
+        assert_eq!(err, StakingError::ViolationCooldownActive.into());
+        assert!(account
+            .check_violation_cooldown(&config, SlashingViolation::Offline48Hours, NOW + 7 * DAY - 1)
+            .is_err());
+        account
+            .check_violation_cooldown(&config, SlashingViolation::Offline48Hours, NOW + 7 * DAY)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_violation_cooldowns_are_independent() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+        account.record_slash(SlashingViolation::Offline48Hours, NOW);
+
+        account.check_violation_cooldown(&config, SlashingViolation::ChallengeFailed, NOW + DAY).unwrap();
+        account.check_violation_cooldown(&config, SlashingViolation::LowUptime, NOW + DAY).unwrap();
+
+        // No cooldown by default for distinct-incident violations
+        account.record_slash(SlashingViolation::MaliciousBehavior, NOW);
+        account.check_violation_cooldown(&config, SlashingViolation::MaliciousBehavior, NOW).unwrap();
+    }
+
+    #[test]
+    fn test_violation_cooldown_runs_from_execution() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+
+        // Requested at NOW, executed after the timelock
+        let executed_at = NOW + SLASH_TIMELOCK_PERIOD;
+        account.check_violation_cooldown(&config, SlashingViolation::ChallengeFailed, NOW).unwrap();
+        account.record_slash(SlashingViolation::ChallengeFailed, executed_at);
+
+        // Three days after the request is still inside the window
+        assert!(account
+            .check_violation_cooldown(&config, SlashingViolation::ChallengeFailed, NOW + 3 * DAY)
+            .is_err());
+        account
+            .check_violation_cooldown(&config, SlashingViolation::ChallengeFailed, executed_at + 3 * DAY)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_second_queued_request_rejected_after_first_executes() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+
+        // Two requests for the same incident, both queued before either executes
+        let mut first = pending_slash_request();
+        first.request_time = NOW;
+        let mut second = pending_slash_request();
+        second.request_time = NOW + 3600;
+        account.check_violation_cooldown(&config, second.violation_type, second.request_time).unwrap();
+
+        let executed_at = NOW + SLASH_TIMELOCK_PERIOD;
+        first.check_executable(&account, &config, executed_at).unwrap();
+        account.record_slash(first.violation_type, executed_at);
+        first.executed = true;
+
+        // The second is rejected, even once the cooldown since the first has passed
+        for now in [second.request_time + SLASH_TIMELOCK_PERIOD, executed_at + 30 * DAY] {
+            let err = second.check_executable(&account, &config, now).unwrap_err();
+            assert_eq!(err, StakingError::ViolationCooldownActive.into());
+        }
+        let err = first.check_executable(&account, &config, executed_at).unwrap_err();
+        assert_eq!(err, StakingError::SlashAlreadyExecuted.into());
+
+        // A new request after the cooldown is executable
+        let mut later = pending_slash_request();
+        later.request_time = executed_at + 3 * DAY;
+        later
+            .check_executable(&account, &config, later.request_time + SLASH_TIMELOCK_PERIOD)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_queued_requests_without_cooldown_all_execute() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+
+        for violation in [SlashingViolation::DataIntegrityViolation, SlashingViolation::MaliciousBehavior] {
+            assert_eq!(config.violation_cooldowns[violation.index()], 0);
+
+            // Two distinct incidents, both queued before either executes
+            let mut first = pending_slash_request();
+            first.violation_type = violation;
+            first.request_time = NOW;
+            let mut second = pending_slash_request();
+            second.violation_type = violation;
+            second.request_time = NOW + 3600;
+
+            let executed_at = NOW + SLASH_TIMELOCK_PERIOD;
+            first.check_executable(&account, &config, executed_at).unwrap();
+            account.record_slash(violation, executed_at);
+            assert_eq!(account.cooldown_ends_at(&config, violation), None);
+
+            second
+                .check_executable(&account, &config, second.request_time + SLASH_TIMELOCK_PERIOD)
+                .unwrap();
+            account.record_slash(violation, second.request_time + SLASH_TIMELOCK_PERIOD);
+        }
+    }
+
+    #[test]
+    fn test_vault_signer_seeds_derive_vault_authority() {
+        let (vault, bump) = Pubkey::find_program_address(&[b"stake_vault"], &crate::ID);
+
+        // execute_unstake, slash_stake and execute_slash all sign with these
+        let bump = [bump];
+        let signer = Pubkey::create_program_address(&stake_vault_seeds(&bump), &crate::ID).unwrap();
+        assert_eq!(signer, vault);
+    }
+
+    #[test]
+    fn test_stake_account_is_not_vault_authority() {
+        // The seeds execute_unstake used to sign with derive the operator's
+        // stake account, which never owns the vault
+        let operator = Pubkey::new_unique();
+        let (stake_pda, _) = Pubkey::find_program_address(&[b"stake", operator.as_ref()], &crate::ID);
+        let (vault, _) = Pubkey::find_program_address(&[b"stake_vault"], &crate::ID);
+        assert_ne!(stake_pda, vault);
+    }
+
+    /// An account from before schema versioning: zero-extended by the
+    /// resize, with counters from the pre-CPI period
+    fn legacy_stake_account() -> StakeAccount {
+        let mut account = stake_account(500, 100);
+        account.total_staked_ever = 300;
+        account.total_unstaked_ever = 400;
+        account.created_at = 0;
+        account.updated_at = 1_690_000_000;
+        account.schema_version = 0;
+        account
+    }
+
+    #[test]
+    fn test_migrate_repairs_legacy_account() {
+        let mut account = legacy_stake_account();
+        let now = 1_700_000_000;
+
+        let event = account.migrate(now).unwrap();
+        // Everything held or withdrawn must have been staked
+        assert_eq!(account.total_staked_ever, 1_000);
+        // The pending unstake restarts its cooldown instead of being withdrawable
+        assert_eq!(account.unstake_request_time, now);
+        assert_eq!(account.created_at, 1_690_000_000);
+        assert_eq!(account.updated_at, 1_690_000_000);
+        assert_eq!(account.schema_version, CURRENT_STAKE_SCHEMA_VERSION);
+        // Balances are never touched
+        assert_eq!((account.staked_amount, account.pending_unstake), (500, 100));
+
+        assert_eq!(event.operator, account.operator);
+        assert_eq!((event.from_version, event.to_version), (0, CURRENT_STAKE_SCHEMA_VERSION));
+        assert_eq!(event.before.total_staked_ever, 300);
+        assert_eq!(event.before.unstake_request_time, 0);
+        assert_eq!(event.before.created_at, 0);
+        assert_eq!(event.after, StakeCounters::from(&account));
+    }
+
+    #[test]
+    fn test_migrate_clears_stale_request_time() {
+        let mut account = legacy_stake_account();
+        account.pending_unstake = 0;
+        account.unstake_request_time = 1_680_000_000;
+        account.created_at = 1_695_000_000;
+
+        account.migrate(1_700_000_000).unwrap();
+        assert_eq!(account.unstake_request_time, 0);
+        assert_eq!(account.total_staked_ever, 900);
+        // updated_at is never before created_at
+        assert_eq!(account.updated_at, 1_695_000_000);
+    }
+
+    #[test]
+    fn test_second_migration_is_noop() {
+        let mut account = legacy_stake_account();
+        account.migrate(1_700_000_000).unwrap();
+        let migrated = account.clone();
+
+        assert!(account.migrate(1_800_000_000).is_none());
+        assert_eq!(StakeCounters::from(&account), StakeCounters::from(&migrated));
+        assert_eq!(account.schema_version, migrated.schema_version);
+
+        // Accounts created by this version never need it
+        assert!(stake_account(500, 0).migrate(1_800_000_000).is_none());
+    }
+
+    #[test]
+    fn test_unmigrated_account_fails_schema_guard() {
+        let mut account = legacy_stake_account();
+        assert!(!account.is_current_schema());
+
+        // The zero-extended legacy layout decodes, but as schema 0
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        let decoded = StakeAccount::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(decoded.schema_version, 0);
+        assert!(!decoded.is_current_schema());
+
+        account.migrate(1_700_000_000).unwrap();
+        assert!(account.is_current_schema());
+
+        let mut fresh = zeroed_stake_account();
+        fresh.schema_version = 0;
+        fresh.init_or_verify(Pubkey::new_unique(), 255, 1_700_000_000).unwrap();
+        assert!(fresh.is_current_schema());
+    }
+
+    #[test]
+    fn test_quote_slash_by_violation_and_stake() {
+        let expected = [
+            (SlashingViolation::Offline48Hours, 10, 1_000),
+            (SlashingViolation::LowUptime, 5, 500),
+            (SlashingViolation::ChallengeFailed, 15, 1_500),
+            (SlashingViolation::DataIntegrityViolation, 25, 2_500),
+            (SlashingViolation::MaliciousBehavior, 100, 10_000),
+        ];
+        for (violation, slash_percentage, amount) in expected {
+            let quote = quote_slash(&stake_account(10_000, 0), violation).unwrap();
+            assert_eq!(quote, SlashQuote { slash_percentage, amount });
+        }
+
+        // Rounds down; a stake too small to lose a whole token can't be slashed
+        let quote = quote_slash(&stake_account(39, 0), SlashingViolation::LowUptime).unwrap();
+        assert_eq!(quote.amount, 1);
+        let err = quote_slash(&stake_account(19, 0), SlashingViolation::LowUptime).unwrap_err();
+        assert_eq!(err, StakingError::InvalidAmount.into());
+        let err = quote_slash(&stake_account(0, 0), SlashingViolation::MaliciousBehavior).unwrap_err();
+        assert_eq!(err, StakingError::InvalidAmount.into());
+
+        // The percentage is applied before dividing, so huge stakes overflow
+        let err = quote_slash(&stake_account(u64::MAX, 0), SlashingViolation::LowUptime).unwrap_err();
+        assert_eq!(err, StakingError::Overflow.into());
+    }
+
+    #[test]
+    fn test_quote_slash_ignores_pending_unstake() {
+        // Pending tokens are outside staked_amount and don't absorb the slash
+        let quote = quote_slash(&stake_account(1_000, 9_000), SlashingViolation::MaliciousBehavior).unwrap();
+        assert_eq!(quote.amount, 1_000);
+        let quote = quote_slash(&stake_account(1_000, 9_000), SlashingViolation::Offline48Hours).unwrap();
+        assert_eq!(quote.amount, 100);
+
+        // Fully unstaking leaves nothing to slash
+        let err = quote_slash(&stake_account(0, 10_000), SlashingViolation::MaliciousBehavior).unwrap_err();
+        assert_eq!(err, StakingError::InvalidAmount.into());
+    }
+
+    #[test]
+    fn test_cooldown_end_matches_cooldown_check() {
+        let config = flag_config(false);
+        let mut account = stake_account(1_000, 0);
+        let violation = SlashingViolation::Offline48Hours;
+        assert_eq!(account.cooldown_ends_at(&config, violation), None);
+
+        account.record_slash(violation, 1_000);
+        let ends_at = account.cooldown_ends_at(&config, violation).unwrap();
+        assert_eq!(ends_at, 1_000 + config.violation_cooldowns[violation.index()]);
+        assert!(account.check_violation_cooldown(&config, violation, ends_at - 1).is_err());
+        account.check_violation_cooldown(&config, violation, ends_at).unwrap();
+    }
+
+    /// Bytes of a config written by an older program version, zero-extended
+    /// the way migrate_global_config grows it
+    fn legacy_config_bytes(config: &GlobalConfig, original_len: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data.truncate(original_len);
+        data.resize(GlobalConfig::MAX_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn test_migrate_legacy_global_config() {
+        let current = flag_config(true);
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), GlobalConfig::MAX_SIZE);
+
+        // Before exit_delay_on_flag and violation_cooldowns were appended
+        let data = legacy_config_bytes(&current, GlobalConfig::LEGACY_SIZE);
+        let mut config = GlobalConfig::try_deserialize(&mut &data[..]).unwrap();
+        config.fill_appended_defaults(GlobalConfig::LEGACY_SIZE);
+        assert_eq!(config.admin_authority, current.admin_authority);
+        assert_eq!(config.slash_nonce, current.slash_nonce);
+        assert!(!config.exit_delay_on_flag);
+        assert_eq!(config.violation_cooldowns, DEFAULT_VIOLATION_COOLDOWNS);
+
+        // After exit_delay_on_flag but before violation_cooldowns
+        let data = legacy_config_bytes(&current, GlobalConfig::LEGACY_SIZE + 1);
+        let mut config = GlobalConfig::try_deserialize(&mut &data[..]).unwrap();
+        config.fill_appended_defaults(GlobalConfig::LEGACY_SIZE + 1);
+        assert!(config.exit_delay_on_flag);
+        assert_eq!(config.violation_cooldowns, DEFAULT_VIOLATION_COOLDOWNS);
+    }
+
+    #[test]
+    fn test_current_global_config_keeps_settings() {
+        let mut current = flag_config(true);
+        current.violation_cooldowns = [1, 2, 3, 4, 5];
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+
+        // The legacy layout can't be loaded until it's grown
+        let short = &data[..GlobalConfig::LEGACY_SIZE];
+        assert!(GlobalConfig::try_deserialize(&mut &short[..]).is_err());
+
+        let mut config = GlobalConfig::try_deserialize(&mut &data[..]).unwrap();
+        config.fill_appended_defaults(GlobalConfig::MAX_SIZE);
+        assert_eq!(config.violation_cooldowns, [1, 2, 3, 4, 5]);
+    }
 }
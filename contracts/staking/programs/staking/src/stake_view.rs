@@ -0,0 +1,134 @@
+//! Stable read-only view of staking accounts for other programs
+//!
+//! Programs that need an operator's stake (rewards, registry, DAO) should
+//! decode StakeAccount data through this module rather than deserializing
+//! `StakeAccount` themselves. `StakeView` only grows by appending fields, and
+//! this module is updated alongside any StakeAccount layout change, so
+//! consumers keep compiling and decoding across program upgrades.
+
+use anchor_lang::prelude::*;
+
+use crate::{StakeAccount, NO_LOCK_TIER};
+
+/// Operator stake as seen by other programs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeView {
+    pub operator: Pubkey,
+    pub staked_amount: u64,
+    pub pending_unstake: u64,
+    /// Unix time of the pending unstake request (0 if none)
+    pub unstake_request_time: i64,
+    /// Always NO_LOCK_TIER until lock tiers exist
+    pub lock_tier: u8,
+}
+
+impl From<&StakeAccount> for StakeView {
+    fn from(stake_account: &StakeAccount) -> Self {
+        Self {
+            operator: stake_account.operator,
+            staked_amount: stake_account.staked_amount,
+            pending_unstake: stake_account.pending_unstake,
+            unstake_request_time: stake_account.unstake_request_time,
+            lock_tier: NO_LOCK_TIER,
+        }
+    }
+}
+
+/// Decode raw StakeAccount data (including the discriminator)
+///
+/// Fails if the discriminator doesn't match or the data is truncated.
+pub fn decode(data: &[u8]) -> Result<StakeView> {
+    let stake_account = StakeAccount::try_deserialize(&mut &data[..])?;
+    Ok(StakeView::from(&stake_account))
+}
+
+/// Decode a StakeAccount, checking it is owned by the staking program
+pub fn load(account: &AccountInfo) -> Result<StakeView> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    decode(&account.try_borrow_data()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_stake_account() -> (StakeAccount, Vec<u8>) {
+        let stake_account = StakeAccount {
+            operator: Pubkey::new_unique(),
+            staked_amount: 1_000,
+            pending_unstake: 200,
+            unstake_request_time: 1_700_000_000,
+            total_staked_ever: 1_200,
+            total_unstaked_ever: 0,
+            created_at: 1_690_000_000,
+            updated_at: 1_700_000_000,
+            bump: 254,
+            last_slashed_at: [0; crate::SlashingViolation::COUNT],
+            schema_version: crate::CURRENT_STAKE_SCHEMA_VERSION,
+        };
+        let mut data = Vec::with_capacity(StakeAccount::MAX_SIZE);
+        stake_account.try_serialize(&mut data).unwrap();
+        (stake_account, data)
+    }
+
+    #[test]
+    fn test_decode_current_layout() {
+        let (stake_account, data) = serialized_stake_account();
+        assert_eq!(data.len(), StakeAccount::MAX_SIZE);
+
+        let view = decode(&data).unwrap();
+        assert_eq!(view, StakeView::from(&stake_account));
+        assert_eq!(view.operator, stake_account.operator);
+        assert_eq!(view.staked_amount, 1_000);
+        assert_eq!(view.pending_unstake, 200);
+        assert_eq!(view.unstake_request_time, 1_700_000_000);
+        assert_eq!(view.lock_tier, NO_LOCK_TIER);
+    }
+
+    #[test]
+    fn test_decode_rejects_foreign_or_truncated_data() {
+        let (_, mut data) = serialized_stake_account();
+        assert!(decode(&data[..data.len() - 1]).is_err());
+
+        data[0] ^= 0xff;
+        assert!(decode(&data).is_err());
+    }
+
+    #[test]
+    fn test_load_checks_owner() {
+        let (_, mut data) = serialized_stake_account();
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+
+        let foreign_owner = Pubkey::new_unique();
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &foreign_owner,
+            false,
+            0,
+        );
+        assert!(load(&account).is_err());
+
+        let mut lamports = 1;
+        let (_, mut data) = serialized_stake_account();
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        assert_eq!(load(&account).unwrap().staked_amount, 1_000);
+    }
+}
@@ -2,6 +2,8 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Staking } from "../target/types/staking";
 import { expect } from "chai";
+import * as fs from "fs";
+import * as path from "path";
 import {
   createMint,
   mintTo,
@@ -25,6 +27,7 @@ describe("staking", () => {
   let stakingAuthorityPDA: anchor.web3.PublicKey;
 
   const MIN_STAKE = new anchor.BN(100_000_000_000); // 100 AEGIS
+  const NO_CONTACT = Array(32).fill(0); // registry registerNode contact_hash when none is published
   const COOLDOWN_PERIOD = new anchor.BN(7 * 24 * 60 * 60); // 7 days in seconds
 
   // Mock registry program (we use system program as placeholder since CPI is optional for tests)
@@ -46,6 +49,14 @@ describe("staking", () => {
     );
   }
 
+  // Helper to get stake vault PDA (one vault holds every operator's stake)
+  function getStakeVaultPDA(): [anchor.web3.PublicKey, number] {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_vault")],
+      program.programId
+    );
+  }
+
   // Helper to get staking authority PDA
   function getStakingAuthorityPDA(): [anchor.web3.PublicKey, number] {
     return anchor.web3.PublicKey.findProgramAddressSync(
@@ -117,26 +128,9 @@ describe("staking", () => {
       9 // 9 decimals
     );
 
-    // Create stake vault token account (owned by stake vault PDA)
-    const stakeVaultKeypair = anchor.web3.Keypair.generate();
-    const lamports = await provider.connection.getMinimumBalanceForRentExemption(ACCOUNT_SIZE);
-    const createVaultTx = new anchor.web3.Transaction().add(
-      anchor.web3.SystemProgram.createAccount({
-        fromPubkey: provider.wallet.publicKey,
-        newAccountPubkey: stakeVaultKeypair.publicKey,
-        space: ACCOUNT_SIZE,
-        lamports,
-        programId: TOKEN_PROGRAM_ID,
-      }),
-      createInitializeAccountInstruction(
-        stakeVaultKeypair.publicKey,
-        mint,
-        provider.wallet.publicKey, // Owner will be changed later
-        TOKEN_PROGRAM_ID
-      )
-    );
-    await provider.sendAndConfirm(createVaultTx, [stakeVaultKeypair]);
-    stakeVault = stakeVaultKeypair.publicKey;
+    // Stake vault token account (owned by the stake vault PDA itself);
+    // created by initializeStakeVault once the global config exists
+    [stakeVault] = getStakeVaultPDA();
 
     // Create treasury token account
     treasury = await createTokenAccount(
@@ -172,6 +166,25 @@ describe("staking", () => {
       // May already exist from previous test run
       console.log("Global config may already exist:", error.message);
     }
+
+    try {
+      await program.methods
+        .initializeStakeVault()
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeVault: stakeVault,
+          mint: mint,
+          admin: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Stake vault initialized successfully");
+    } catch (error) {
+      // May already exist from previous test run
+      console.log("Stake vault may already exist:", error.message);
+    }
   });
 
   describe("Global Config", () => {
@@ -522,6 +535,896 @@ describe("staking", () => {
     });
   });
 
+  describe("Initialize And Stake", () => {
+    // The full path needs a deployed registry for the stake CPI; fresh-account
+    // setup and preservation of an existing account's created_at and lifetime
+    // counters are covered by the init_or_verify unit tests
+
+    it("Rejects staking into another operator's stake account", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      const attacker = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      await fundAccount(attacker.publicKey, 0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+      const [stakePDA] = getStakePDA(operator.publicKey);
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+      const before = await program.account.stakeAccount.fetch(stakePDA);
+
+      const attackerTokenAccount = await createTokenAccountForOperator(attacker);
+      try {
+        await program.methods
+          .initializeAndStake(MIN_STAKE)
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: stakePDA,
+            operatorTokenAccount: attackerTokenAccount,
+            stakeVault: stakeVault,
+            operator: attacker.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            registryProgram: MOCK_REGISTRY_PROGRAM,
+            registryConfig: globalConfigPDA,
+            nodeAccount: stakePDA,
+            stakingAuthority: stakingAuthorityPDA,
+          })
+          .signers([attacker])
+          .rpc();
+
+        expect.fail("Should have rejected another operator's stake account");
+      } catch (error) {
+        expect(error.toString()).to.include("ConstraintSeeds");
+      }
+
+      const after = await program.account.stakeAccount.fetch(stakePDA);
+      expect(after.operator.toString()).to.equal(operator.publicKey.toString());
+      expect(after.createdAt.toString()).to.equal(before.createdAt.toString());
+    });
+  });
+
+  describe("Stake Position Events", () => {
+    it("Exposes a uniform position event covering every mutation path", () => {
+      const event = program.idl.events.find(
+        (e) => e.name === "stakePositionChangedEvent"
+      );
+      expect(event).to.exist;
+
+      const kinds = program.idl.types.find((t) => t.name === "stakeChangeKind");
+      expect(kinds).to.exist;
+      const variants = (kinds.type as any).variants.map((v) => v.name);
+      expect(variants).to.have.members([
+        "stake",
+        "requestUnstake",
+        "executeUnstake",
+        "cancelUnstake",
+        "executeSlash",
+        "slashStake",
+      ]);
+
+      const fields = (
+        program.idl.types.find((t) => t.name === "stakePositionChangedEvent")
+          .type as any
+      ).fields.map((f) => f.name);
+      expect(fields).to.deep.equal([
+        "operator",
+        "stakedAmount",
+        "pendingUnstake",
+        "changeKind",
+        "delta",
+        "timestamp",
+      ]);
+    });
+  });
+
+  describe("Stake Attestation", () => {
+    // Decoding through stake_view is covered by the program unit tests
+    it("Emits a versioned attestation matching the stake account", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      const [stakePDA] = getStakePDA(operator.publicKey);
+
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      let received: any = null;
+      const listener = program.addEventListener("stakeAttestationEvent", (event) => {
+        received = event;
+      });
+
+      // Permissionless: the provider wallet, not the operator, pays
+      await program.methods
+        .emitStakeAttestation(operator.publicKey)
+        .accounts({
+          stakeAccount: stakePDA,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePDA);
+      expect(received).to.not.be.null;
+      expect(received.operator.toString()).to.equal(operator.publicKey.toString());
+      expect(received.stakedAmount.toString()).to.equal(stakeAccount.stakedAmount.toString());
+      expect(received.pendingUnstake.toString()).to.equal(
+        stakeAccount.pendingUnstake.toString()
+      );
+      expect(received.lockTier).to.equal(0);
+      expect(received.slot.toNumber()).to.be.greaterThan(0);
+      expect(received.version).to.equal(1);
+    });
+  });
+
+  describe("Slash Preview", () => {
+    // Amounts for staked positions (per violation, rounding, pending_unstake
+    // and cooldowns) are covered by the program unit tests on quote_slash,
+    // the calculation request_slash shares
+    it("Rejects a preview the request itself would reject", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      const [stakePDA] = getStakePDA(operator.publicKey);
+
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      // Nothing staked, so there is nothing to slash
+      try {
+        await program.methods
+          .emitSlashPreview(operator.publicKey, { maliciousBehavior: {} })
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: stakePDA,
+          })
+          .rpc();
+        expect.fail("Should have rejected an empty stake");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAmount");
+      }
+    });
+
+    it("Exposes the preview event breakdown", () => {
+      const fields = (
+        program.idl.types.find((t) => t.name === "slashPreviewEvent").type as any
+      ).fields.map((f) => f.name);
+      expect(fields).to.deep.equal([
+        "operator",
+        "violationType",
+        "slashPercentage",
+        "amount",
+        "stakedAmount",
+        "pendingUnstake",
+        "remainingStaked",
+        "cooldownEndsAt",
+        "timestamp",
+      ]);
+    });
+  });
+
+  describe("Slash Evidence Retention", () => {
+    // Filing a slash request needs a staked position (registry CPI), so the
+    // append / limit / post-cancel paths are covered by the program unit tests
+    it("Stores up to four follow-up evidence CIDs per slash request", () => {
+      const slashRequest = program.idl.types.find((t) => t.name === "slashRequest");
+      expect(slashRequest).to.exist;
+
+      const fields = (slashRequest.type as any).fields;
+      const evidence = fields.find((f) => f.name === "additionalEvidence");
+      expect(evidence.type).to.deep.equal({ array: ["string", 4] });
+      expect(fields.map((f) => f.name)).to.include("additionalEvidenceCount");
+    });
+
+    it("Exposes append_evidence and its event", () => {
+      const ix = program.idl.instructions.find((i) => i.name === "appendEvidence");
+      expect(ix).to.exist;
+      expect(ix.accounts.map((a) => a.name)).to.deep.equal(["slashRequest", "oracle"]);
+
+      const event = program.idl.events.find((e) => e.name === "evidenceAppendedEvent");
+      expect(event).to.exist;
+
+      const errors = program.idl.errors.map((e) => e.name);
+      expect(errors).to.include("evidenceLimitReached");
+    });
+  });
+
+  describe("Slash Request Index", () => {
+    // Filing and resolving slash requests needs a staked position (registry
+    // CPI), so index updates, wrap-around and the active count are covered by
+    // the program unit tests
+    function getSlashIndexPDA(operator: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("slash_index"), operator.toBuffer()],
+        program.programId
+      );
+    }
+
+    it("Derives one index per operator", () => {
+      const operator1 = anchor.web3.Keypair.generate();
+      const operator2 = anchor.web3.Keypair.generate();
+
+      const [index1] = getSlashIndexPDA(operator1.publicKey);
+      const [index2] = getSlashIndexPDA(operator2.publicKey);
+      expect(index1.toString()).to.not.equal(index2.toString());
+      expect(getSlashIndexPDA(operator1.publicKey)[0].toString()).to.equal(index1.toString());
+    });
+
+    it("Keeps the eight most recent requests and an active count", () => {
+      const index = program.idl.types.find((t) => t.name === "slashRequestIndex");
+      expect(index).to.exist;
+
+      const fields = (index.type as any).fields;
+      const entries = fields.find((f) => f.name === "entries");
+      expect(entries.type).to.deep.equal({ array: [{ defined: { name: "slashIndexEntry" } }, 8] });
+      expect(fields.map((f) => f.name)).to.include.members(["nextSlot", "entryCount", "activeRequestCount"]);
+    });
+
+    it("Updates the index in request, execute and cancel", () => {
+      for (const name of ["requestSlash", "executeSlash", "cancelSlash"]) {
+        const ix = program.idl.instructions.find((i) => i.name === name);
+        expect(ix.accounts.map((a) => a.name), name).to.include("slashRequestIndex");
+      }
+
+      const errors = program.idl.errors.map((e) => e.name);
+      expect(errors).to.include("slashIndexRequired");
+    });
+  });
+
+  describe("Investigation Flags", () => {
+    // execute_unstake needs a staked position and an elapsed cooldown
+    // (registry CPI), so the blocked / expired / cleared exit paths are
+    // covered by the program unit tests
+    const ONE_DAY = new anchor.BN(24 * 60 * 60);
+
+    function getInvestigationFlagPDA(operator: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("investigation_flag"), operator.toBuffer()],
+        program.programId
+      );
+    }
+
+    function getFlagQuotaPDA(oracle: anchor.web3.PublicKey): [anchor.web3.PublicKey, number] {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("flag_quota"), oracle.toBuffer()],
+        program.programId
+      );
+    }
+
+    async function setExitDelayOnFlag(enabled: boolean) {
+      await program.methods
+        .setExitDelayOnFlag(enabled)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    async function createOperator(): Promise<anchor.web3.Keypair> {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: getStakePDA(operator.publicKey)[0],
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+      return operator;
+    }
+
+    async function placeFlag(operator: anchor.web3.PublicKey, duration: anchor.BN) {
+      await program.methods
+        .placeInvestigationFlag(duration)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeAccount: getStakePDA(operator)[0],
+          investigationFlag: getInvestigationFlagPDA(operator)[0],
+          flagQuota: getFlagQuotaPDA(provider.wallet.publicKey)[0],
+          oracle: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      await setExitDelayOnFlag(true);
+    });
+
+    after(async () => {
+      await setExitDelayOnFlag(false);
+    });
+
+    it("Blocks execute_unstake but not request_unstake", () => {
+      for (const name of ["requestUnstake", "executeUnstake"]) {
+        const ix = program.idl.instructions.find((i) => i.name === name);
+        expect(ix.accounts.map((a) => a.name), name).to.include("investigationFlag");
+      }
+
+      const event = program.idl.types.find((t) => t.name === "unstakeRequestedEvent");
+      const fields = (event.type as any).fields.map((f) => f.name);
+      expect(fields).to.include("exitBlockedUntil");
+
+      const errors = program.idl.errors.map((e) => e.name);
+      expect(errors).to.include("underInvestigation");
+    });
+
+    it("Lets the oracle flag an operator and the admin clear it early", async () => {
+      const operator = await createOperator();
+      const [flagPDA] = getInvestigationFlagPDA(operator.publicKey);
+
+      await placeFlag(operator.publicKey, ONE_DAY);
+      const flag = await program.account.investigationFlag.fetch(flagPDA);
+      expect(flag.operator.toString()).to.equal(operator.publicKey.toString());
+      expect(flag.expiresAt.sub(flag.flaggedAt).toString()).to.equal(ONE_DAY.toString());
+
+      try {
+        await placeFlag(operator.publicKey, ONE_DAY);
+        expect.fail("Should have rejected a second active flag");
+      } catch (error) {
+        expect(error.toString()).to.include("InvestigationFlagActive");
+      }
+
+      await program.methods
+        .clearInvestigationFlag()
+        .accounts({
+          globalConfig: globalConfigPDA,
+          investigationFlag: flagPDA,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(flagPDA)).to.be.null;
+    });
+
+    it("Rejects flags longer than 7 days", async () => {
+      const operator = await createOperator();
+      try {
+        await placeFlag(operator.publicKey, ONE_DAY.muln(7).addn(1));
+        expect.fail("Should have rejected an 8-day flag");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidFlagDuration");
+      }
+    });
+
+    it("Rejects flags from anyone but the oracle", async () => {
+      const operator = await createOperator();
+      const impostor = anchor.web3.Keypair.generate();
+      await fundAccount(impostor.publicKey);
+
+      try {
+        await program.methods
+          .placeInvestigationFlag(ONE_DAY)
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: getStakePDA(operator.publicKey)[0],
+            investigationFlag: getInvestigationFlagPDA(operator.publicKey)[0],
+            flagQuota: getFlagQuotaPDA(impostor.publicKey)[0],
+            oracle: impostor.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected an unauthorized oracle");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedOracle");
+      }
+    });
+
+    it("Limits how many operators one oracle can flag per day", async () => {
+      // Earlier tests in this run may already have used part of the quota
+      let placed = 0;
+      let limited = false;
+      for (let i = 0; i <= 5 && !limited; i++) {
+        const operator = await createOperator();
+        try {
+          await placeFlag(operator.publicKey, ONE_DAY);
+          placed++;
+        } catch (error) {
+          expect(error.toString()).to.include("FlagRateLimited");
+          limited = true;
+        }
+      }
+
+      expect(limited).to.equal(true);
+      expect(placed).to.be.at.most(5);
+      const quota = await program.account.oracleFlagQuota.fetch(getFlagQuotaPDA(provider.wallet.publicKey)[0]);
+      expect(quota.flagsInWindow).to.equal(5);
+    });
+  });
+
+  describe("Violation Cooldowns", () => {
+    // Slashing needs a staked position and the 24h timelock (registry CPI),
+    // so the repeat-request rejection, independent violation types and the
+    // execution-time start of the window are covered by the program unit tests
+    const ONE_DAY = 24 * 60 * 60;
+
+    async function setViolationCooldown(violation: object, cooldown: anchor.BN) {
+      await program.methods
+        .setViolationCooldown(violation as any, cooldown)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    it("Initializes per-violation cooldowns with the defaults", async () => {
+      const config = await program.account.globalConfig.fetch(globalConfigPDA);
+      expect(config.violationCooldowns.map((c) => c.toNumber())).to.deep.equal([
+        7 * ONE_DAY,
+        30 * ONE_DAY,
+        3 * ONE_DAY,
+        0,
+        0,
+      ]);
+    });
+
+    it("Tracks the last executed slash per violation type", () => {
+      const stakeAccount = program.idl.types.find((t) => t.name === "stakeAccount");
+      const field = (stakeAccount.type as any).fields.find((f) => f.name === "lastSlashedAt");
+      expect(field.type).to.deep.equal({ array: ["i64", 5] });
+
+      const errors = program.idl.errors.map((e) => e.name);
+      expect(errors).to.include("violationCooldownActive");
+    });
+
+    it("Lets the admin change one violation's cooldown", async () => {
+      await setViolationCooldown({ challengeFailed: {} }, new anchor.BN(ONE_DAY));
+      let config = await program.account.globalConfig.fetch(globalConfigPDA);
+      expect(config.violationCooldowns[2].toNumber()).to.equal(ONE_DAY);
+      expect(config.violationCooldowns[0].toNumber()).to.equal(7 * ONE_DAY);
+
+      await setViolationCooldown({ challengeFailed: {} }, new anchor.BN(3 * ONE_DAY));
+      config = await program.account.globalConfig.fetch(globalConfigPDA);
+      expect(config.violationCooldowns[2].toNumber()).to.equal(3 * ONE_DAY);
+    });
+
+    it("Rejects negative cooldowns and non-admin callers", async () => {
+      try {
+        await setViolationCooldown({ lowUptime: {} }, new anchor.BN(-1));
+        expect.fail("Should have rejected a negative cooldown");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidViolationCooldown");
+      }
+
+      const impostor = anchor.web3.Keypair.generate();
+      await fundAccount(impostor.publicKey);
+      try {
+        await program.methods
+          .setViolationCooldown({ lowUptime: {} } as any, new anchor.BN(0))
+          .accounts({
+            globalConfig: globalConfigPDA,
+            admin: impostor.publicKey,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected a non-admin");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Global Config Migration", () => {
+    // The current program can't write a shorter config, so growing a legacy
+    // one and filling its defaults are covered by
+    // test_migrate_legacy_global_config
+
+    it("Leaves a current config untouched", async () => {
+      const before = await program.account.globalConfig.fetch(globalConfigPDA);
+      await program.methods
+        .migrateGlobalConfig()
+        .accounts({
+          globalConfig: globalConfigPDA,
+          admin: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.account.globalConfig.fetch(globalConfigPDA);
+      expect(after.slashNonce.toString()).to.equal(before.slashNonce.toString());
+      expect(after.violationCooldowns.map((c) => c.toString())).to.deep.equal(
+        before.violationCooldowns.map((c) => c.toString())
+      );
+    });
+
+    it("Only lets the admin migrate the config", async () => {
+      const impostor = anchor.web3.Keypair.generate();
+      await fundAccount(impostor.publicKey);
+      try {
+        await program.methods
+          .migrateGlobalConfig()
+          .accounts({
+            globalConfig: globalConfigPDA,
+            admin: impostor.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected a non-admin");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Stake Account Migration", () => {
+    // Legacy (shorter, unversioned) accounts can't be created by the current
+    // program, so repairing one and the schema guard rejecting it are covered
+    // by test_migrate_repairs_legacy_account and
+    // test_unmigrated_account_fails_schema_guard
+    async function initializedStakeAccount(): Promise<[anchor.web3.PublicKey, anchor.web3.PublicKey]> {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey);
+      const [stakePDA] = getStakePDA(operator.publicKey);
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+      return [operator.publicKey, stakePDA];
+    }
+
+    it("Creates stake accounts at the current schema", async () => {
+      const [, stakePDA] = await initializedStakeAccount();
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePDA);
+      expect(stakeAccount.schemaVersion).to.equal(1);
+    });
+
+    it("Leaves current accounts untouched on migration", async () => {
+      const [operator, stakePDA] = await initializedStakeAccount();
+      const before = await program.account.stakeAccount.fetch(stakePDA);
+
+      let migrated = false;
+      const listener = program.addEventListener("stakeAccountMigratedEvent", () => {
+        migrated = true;
+      });
+      await program.methods
+        .migrateStakeAccount(operator)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeAccount: stakePDA,
+          admin: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const after = await program.account.stakeAccount.fetch(stakePDA);
+      expect(migrated).to.equal(false);
+      expect(after.schemaVersion).to.equal(before.schemaVersion);
+      expect(after.totalStakedEver.toString()).to.equal(before.totalStakedEver.toString());
+      expect(after.updatedAt.toNumber()).to.equal(before.updatedAt.toNumber());
+    });
+
+    it("Only lets the admin migrate", async () => {
+      const [operator, stakePDA] = await initializedStakeAccount();
+      const impostor = anchor.web3.Keypair.generate();
+      await fundAccount(impostor.publicKey);
+      try {
+        await program.methods
+          .migrateStakeAccount(operator)
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: stakePDA,
+            admin: impostor.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected a non-admin");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAdmin");
+      }
+
+      const errors = program.idl.errors.map((e) => e.name);
+      expect(errors).to.include("stakeAccountNotMigrated");
+    });
+  });
+
+  describe("Stake Vault Ownership", () => {
+    // stake -> slash -> unstake against the one vault PDA. Stake, slash and
+    // unstake sync the registry via CPI, so this needs the registry program
+    // deployed to the same validator and its IDL built, and a `test-utils`
+    // build (`npm run test:integration`) so the unstake cooldown can be warped
+    // past with set_test_clock; otherwise it skips, or fails under that script.
+    // The vault signer itself is covered by the Rust unit test
+    // test_vault_signer_seeds_derive_vault_authority.
+
+    const REGISTRY_IDL_PATH = path.join(__dirname, "../../registry/target/idl/registry.json");
+
+    let registry: Program;
+
+    function getRegistryPDA(seeds: Buffer[]): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(seeds, registry.programId)[0];
+    }
+
+    const [testClockPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("test_clock")],
+      program.programId
+    );
+    // Passed to the cooldown-checked calls so they see the warped time
+    const clockAccounts = [{ pubkey: testClockPDA, isSigner: false, isWritable: false }];
+
+    // Advance the program's clock by `seconds`; the offset only moves forward
+    async function warp(seconds: number) {
+      const testClock = await (program.account as any).testClock.fetchNullable(testClockPDA);
+      const offset = (testClock ? testClock.offset.toNumber() : 0) + seconds;
+      await (program.methods as any)
+        .setTestClock(new anchor.BN(offset))
+        .accounts({
+          globalConfig: globalConfigPDA,
+          testClock: testClockPDA,
+          admin: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    // `npm run test:integration` sets up every prerequisite, so a missing one
+    // there is a broken build and fails the block instead of skipping it
+    function skipOrFail(ctx: Mocha.Context, reason: string) {
+      if (process.env.AEGIS_TEST_INTEGRATION) {
+        throw new Error(`Integration prerequisite missing: ${reason}`);
+      }
+      console.log(`    ⚠ Skipping: ${reason}`);
+      ctx.skip();
+    }
+
+    before(async function() {
+      if (!program.idl.instructions.some((ix) => ix.name === "setTestClock")) {
+        skipOrFail(this, "needs a test-utils build (npm run test:integration)");
+      }
+      if (!fs.existsSync(REGISTRY_IDL_PATH)) {
+        skipOrFail(this, "registry IDL not built");
+      }
+      registry = new Program(JSON.parse(fs.readFileSync(REGISTRY_IDL_PATH, "utf8")), provider);
+      const registryAccount = await provider.connection.getAccountInfo(registry.programId);
+      if (!registryAccount || !registryAccount.executable) {
+        skipOrFail(this, "registry program not deployed");
+      }
+
+      const registryConfigPDA = getRegistryPDA([Buffer.from("registry_config")]);
+      if (!(await provider.connection.getAccountInfo(registryConfigPDA))) {
+        await registry.methods
+          .initializeRegistryConfig(provider.wallet.publicKey, program.programId, MIN_STAKE)
+          .accounts({
+            registryConfig: registryConfigPDA,
+            deployer: provider.wallet.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+      const registryConfig: any = await (registry.account as any).registryConfig.fetch(registryConfigPDA);
+      if (!registryConfig.stakingProgramId.equals(program.programId)) {
+        skipOrFail(this, "registry is bound to another staking program");
+      }
+
+      // Point staking's registry CPIs at the real registry for this block
+      await program.methods
+        .updateGlobalConfig(null, null, null, registry.programId)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+    });
+
+    after(async () => {
+      if (!registry) {
+        return;
+      }
+      await program.methods
+        .updateGlobalConfig(null, null, null, MOCK_REGISTRY_PROGRAM)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Stakes, slashes and unstakes through the same vault", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey, 1 * anchor.web3.LAMPORTS_PER_SOL);
+      const [stakePDA] = getStakePDA(operator.publicKey);
+      const [flagPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("investigation_flag"), operator.publicKey.toBuffer()],
+        program.programId
+      );
+      const registryConfigPDA = getRegistryPDA([Buffer.from("registry_config")]);
+      const nodePDA = getRegistryPDA([Buffer.from("node"), operator.publicKey.toBuffer(), Buffer.from([0])]);
+
+      await registry.methods
+        .registerNode("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG", MIN_STAKE, NO_CONTACT, 0)
+        .accounts({
+          registryConfig: registryConfigPDA,
+          operatorNodes: getRegistryPDA([Buffer.from("operator_nodes"), operator.publicKey.toBuffer()]),
+          nodeAccount: nodePDA,
+          legacyNodeAccount: getRegistryPDA([Buffer.from("node"), operator.publicKey.toBuffer()]),
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      const operatorTokenAccount = await createTokenAccountForOperator(operator);
+      const stakeAmount = MIN_STAKE.muln(3);
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        operatorTokenAccount,
+        provider.wallet.publicKey,
+        BigInt(stakeAmount.toString())
+      );
+
+      const registryAccounts = {
+        registryProgram: registry.programId,
+        registryConfig: registryConfigPDA,
+        nodeAccount: nodePDA,
+        stakingAuthority: stakingAuthorityPDA,
+      };
+      const vaultBefore = (await getAccount(provider.connection, stakeVault)).amount;
+
+      await program.methods
+        .stake(stakeAmount)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeAccount: stakePDA,
+          operatorTokenAccount: operatorTokenAccount,
+          stakeVault: stakeVault,
+          operator: operator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ...registryAccounts,
+        })
+        .signers([operator])
+        .rpc();
+
+      const slashAmount = MIN_STAKE;
+      const treasuryBefore = (await getAccount(provider.connection, treasury)).amount;
+      await program.methods
+        .slashStake(slashAmount, "Vault ownership test")
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeAccount: stakePDA,
+          stakeVault: stakeVault,
+          treasury: treasury,
+          authority: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ...registryAccounts,
+        })
+        .rpc();
+
+      // The slash signed as the vault PDA and moved tokens out of the same vault
+      const vaultAfterSlash = (await getAccount(provider.connection, stakeVault)).amount;
+      expect((vaultAfterSlash - vaultBefore).toString()).to.equal(stakeAmount.sub(slashAmount).toString());
+      expect(((await getAccount(provider.connection, treasury)).amount - treasuryBefore).toString())
+        .to.equal(slashAmount.toString());
+
+      const unstakeAmount = stakeAmount.sub(slashAmount);
+      await program.methods
+        .requestUnstake(unstakeAmount)
+        .accounts({
+          globalConfig: globalConfigPDA,
+          stakeAccount: stakePDA,
+          investigationFlag: flagPDA,
+          operator: operator.publicKey,
+        })
+        .remainingAccounts(clockAccounts)
+        .signers([operator])
+        .rpc();
+
+      const executeUnstake = () =>
+        program.methods
+          .executeUnstake()
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: stakePDA,
+            stakeVault: stakeVault,
+            operatorTokenAccount: operatorTokenAccount,
+            investigationFlag: flagPDA,
+            operator: operator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...registryAccounts,
+          })
+          .remainingAccounts(clockAccounts)
+          .signers([operator])
+          .rpc();
+
+      // Past vault validation, the unstake stops at the cooldown until it has run
+      try {
+        await executeUnstake();
+        expect.fail("Should have rejected unstake before the cooldown");
+      } catch (error) {
+        expect(error.toString()).to.include("CooldownNotComplete");
+      }
+
+      const config = await program.account.globalConfig.fetch(globalConfigPDA);
+      await warp(config.unstakeCooldownPeriod.toNumber() + 1);
+
+      await executeUnstake();
+      const operatorBalance = (await getAccount(provider.connection, operatorTokenAccount)).amount;
+      expect(operatorBalance.toString()).to.equal(unstakeAmount.toString());
+      expect((await getAccount(provider.connection, stakeVault)).amount.toString()).to.equal(vaultBefore.toString());
+    });
+
+    it("Rejects a stake vault other than the vault PDA", async () => {
+      const operator = anchor.web3.Keypair.generate();
+      await fundAccount(operator.publicKey, 0.1 * anchor.web3.LAMPORTS_PER_SOL);
+      const [stakePDA] = getStakePDA(operator.publicKey);
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA,
+          operator: operator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      // A vault owned by the stake account, as execute_unstake used to assume
+      const strayVault = await createTokenAccount(stakePDA, provider.wallet.payer);
+      const operatorTokenAccount = await createTokenAccountForOperator(operator);
+      try {
+        await program.methods
+          .stake(MIN_STAKE)
+          .accounts({
+            globalConfig: globalConfigPDA,
+            stakeAccount: stakePDA,
+            operatorTokenAccount: operatorTokenAccount,
+            stakeVault: strayVault,
+            operator: operator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            registryProgram: registry.programId,
+            registryConfig: getRegistryPDA([Buffer.from("registry_config")]),
+            nodeAccount: stakePDA,
+            stakingAuthority: stakingAuthorityPDA,
+          })
+          .signers([operator])
+          .rpc();
+        expect.fail("Should have rejected a stray stake vault");
+      } catch (error) {
+        expect(error.toString()).to.include("ConstraintSeeds");
+      }
+    });
+  });
+
   describe("PDA Derivation", () => {
     it("Derives unique PDA for each operator", async () => {
       const operator1 = anchor.web3.Keypair.generate();
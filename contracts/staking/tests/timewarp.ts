@@ -0,0 +1,726 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Staking } from "../target/types/staking";
+import { expect } from "chai";
+import * as fs from "fs";
+import * as path from "path";
+import { createHash } from "crypto";
+import {
+  createMint,
+  getAccount,
+  getMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+
+// End-to-end staking lifecycle against the real registry program, with time
+// advanced through the test clock instead of waited out.
+//
+// Needs a build with the `test-utils` feature (`npm run test:integration`),
+// which adds set_test_clock; every instruction here passes the TestClock
+// account so it sees the warped time. The registry program is loaded by the
+// [[test.genesis]] entry in Anchor.toml; the same script builds it and its
+// IDL first. Without either, the suite skips (or fails, under that script).
+// It runs after staking.ts and reuses the global config, vault and mint that
+// suite creates, restoring any setting it changes.
+describe("staking integration (time warp)", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Staking as Program<Staking>;
+  const admin = provider.wallet.publicKey;
+
+  const REGISTRY_IDL_PATH = path.join(__dirname, "../../registry/target/idl/registry.json");
+  const MIN_STAKE = new anchor.BN(100_000_000_000); // 100 AEGIS
+  const COOLDOWN_PERIOD = new anchor.BN(7 * 24 * 60 * 60);
+  const SLASH_TIMELOCK_PERIOD = 24 * 60 * 60;
+  const LOW_UPTIME_COOLDOWN = 30 * 24 * 60 * 60; // DEFAULT_VIOLATION_COOLDOWNS[LowUptime]
+  const EVIDENCE_CID = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+  const NO_CONTACT = Array(32).fill(0); // registry registerNode contact_hash when none is published
+  const NOTIFY_SLASH_REQUESTS = 1; // registry notify_flags bit
+
+  let registry: Program;
+  let mint: anchor.web3.PublicKey;
+  let treasury: anchor.web3.PublicKey;
+  let cooldownPeriod: number;
+  let original: { registryProgramId: anchor.web3.PublicKey; paused: boolean; exitDelayOnFlag: boolean };
+
+  function pda(seeds: Buffer[], programId = program.programId): anchor.web3.PublicKey {
+    return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+  }
+
+  const globalConfigPDA = () => pda([Buffer.from("global_config")]);
+  const stakeVaultPDA = () => pda([Buffer.from("stake_vault")]);
+  const stakingAuthorityPDA = () => pda([Buffer.from("staking_authority")]);
+  const testClockPDA = () => pda([Buffer.from("test_clock")]);
+  const stakePDA = (operator: anchor.web3.PublicKey) => pda([Buffer.from("stake"), operator.toBuffer()]);
+  const flagPDA = (operator: anchor.web3.PublicKey) =>
+    pda([Buffer.from("investigation_flag"), operator.toBuffer()]);
+  const slashIndexPDA = (operator: anchor.web3.PublicKey) => pda([Buffer.from("slash_index"), operator.toBuffer()]);
+  const slashRequestPDA = (operator: anchor.web3.PublicKey, nonce: anchor.BN) =>
+    pda([Buffer.from("slash_request"), operator.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)]);
+  const registryConfigPDA = () => pda([Buffer.from("registry_config")], registry.programId);
+  const nodePDA = (operator: anchor.web3.PublicKey) =>
+    pda([Buffer.from("node"), operator.toBuffer(), Buffer.from([0])], registry.programId);
+
+  // Every call passes the test clock so the program sees warped time
+  const clockAccounts = () => [{ pubkey: testClockPDA(), isSigner: false, isWritable: false }];
+
+  async function clockOffset(): Promise<number> {
+    const testClock = await (program.account as any).testClock.fetchNullable(testClockPDA());
+    return testClock ? testClock.offset.toNumber() : 0;
+  }
+
+  // Advance the program's clock by `seconds`
+  async function warp(seconds: number) {
+    const offset = (await clockOffset()) + seconds;
+    await (program.methods as any)
+      .setTestClock(new anchor.BN(offset))
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        testClock: testClockPDA(),
+        admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async function registryStake(operator: anchor.web3.PublicKey): Promise<string> {
+    const node: any = await (registry.account as any).nodeAccount.fetch(nodePDA(operator));
+    return node.stakeAmount.toString();
+  }
+
+  async function tokenBalance(account: anchor.web3.PublicKey): Promise<bigint> {
+    return (await getAccount(provider.connection, account)).amount;
+  }
+
+  const registryAccounts = (operator: anchor.web3.PublicKey) => ({
+    registryProgram: registry.programId,
+    registryConfig: registryConfigPDA(),
+    nodeAccount: nodePDA(operator),
+    stakingAuthority: stakingAuthorityPDA(),
+  });
+
+  interface Operator {
+    keypair: anchor.web3.Keypair;
+    key: anchor.web3.PublicKey;
+    tokenAccount: anchor.web3.PublicKey;
+  }
+
+  // Operator with a registered node and `balance` AEGIS to stake
+  async function newOperator(balance: anchor.BN): Promise<Operator> {
+    const keypair = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: admin,
+          toPubkey: keypair.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    await registry.methods
+      .registerNode(EVIDENCE_CID, MIN_STAKE, NO_CONTACT, 0)
+      .accounts({
+        registryConfig: registryConfigPDA(),
+        operatorNodes: pda([Buffer.from("operator_nodes"), keypair.publicKey.toBuffer()], registry.programId),
+        nodeAccount: nodePDA(keypair.publicKey),
+        legacyNodeAccount: pda([Buffer.from("node"), keypair.publicKey.toBuffer()], registry.programId),
+        operator: keypair.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([keypair])
+      .rpc();
+
+    const tokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, mint, keypair.publicKey)
+    ).address;
+    await mintTo(provider.connection, provider.wallet.payer, mint, tokenAccount, admin, BigInt(balance.toString()));
+
+    return { keypair, key: keypair.publicKey, tokenAccount };
+  }
+
+  function stake(operator: Operator, amount: anchor.BN) {
+    return program.methods
+      .stake(amount)
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        operatorTokenAccount: operator.tokenAccount,
+        stakeVault: stakeVaultPDA(),
+        operator: operator.key,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...registryAccounts(operator.key),
+      })
+      .remainingAccounts(clockAccounts())
+      .signers([operator.keypair])
+      .rpc();
+  }
+
+  function initializeAndStake(operator: Operator, amount: anchor.BN) {
+    return program.methods
+      .initializeAndStake(amount)
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        operatorTokenAccount: operator.tokenAccount,
+        stakeVault: stakeVaultPDA(),
+        operator: operator.key,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        ...registryAccounts(operator.key),
+      })
+      .remainingAccounts(clockAccounts())
+      .signers([operator.keypair])
+      .rpc();
+  }
+
+  function requestUnstake(operator: Operator, amount: anchor.BN) {
+    return program.methods
+      .requestUnstake(amount)
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        investigationFlag: flagPDA(operator.key),
+        operator: operator.key,
+      })
+      .remainingAccounts(clockAccounts())
+      .signers([operator.keypair])
+      .rpc();
+  }
+
+  function executeUnstake(operator: Operator) {
+    return program.methods
+      .executeUnstake()
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        stakeVault: stakeVaultPDA(),
+        operatorTokenAccount: operator.tokenAccount,
+        investigationFlag: flagPDA(operator.key),
+        operator: operator.key,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...registryAccounts(operator.key),
+      })
+      .remainingAccounts(clockAccounts())
+      .signers([operator.keypair])
+      .rpc();
+  }
+
+  function cancelUnstake(operator: Operator) {
+    return program.methods
+      .cancelUnstake()
+      .accounts({
+        stakeAccount: stakePDA(operator.key),
+        operator: operator.key,
+      })
+      .remainingAccounts(clockAccounts())
+      .signers([operator.keypair])
+      .rpc();
+  }
+
+  // File a slash request, optionally passing the operator's registry node;
+  // returns its PDA
+  async function requestSlash(
+    operator: Operator,
+    violation: object,
+    nodeAccount: anchor.web3.PublicKey | null = null
+  ): Promise<anchor.web3.PublicKey> {
+    const config = await program.account.globalConfig.fetch(globalConfigPDA());
+    const slashRequest = slashRequestPDA(operator.key, config.slashNonce);
+    await program.methods
+      .requestSlash(violation as any, EVIDENCE_CID)
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        slashRequest,
+        slashRequestIndex: slashIndexPDA(operator.key),
+        nodeAccount,
+        oracle: admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(clockAccounts())
+      .rpc();
+    return slashRequest;
+  }
+
+  function executeSlash(operator: Operator, slashRequest: anchor.web3.PublicKey) {
+    return program.methods
+      .executeSlash()
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        stakeAccount: stakePDA(operator.key),
+        slashRequest,
+        slashRequestIndex: slashIndexPDA(operator.key),
+        stakeVault: stakeVaultPDA(),
+        treasury,
+        executor: admin,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...registryAccounts(operator.key),
+      })
+      .remainingAccounts(clockAccounts())
+      .rpc();
+  }
+
+  function cancelSlash(operator: Operator, slashRequest: anchor.web3.PublicKey) {
+    return program.methods
+      .cancelSlash()
+      .accounts({
+        globalConfig: globalConfigPDA(),
+        slashRequest,
+        slashRequestIndex: slashIndexPDA(operator.key),
+        admin,
+      })
+      .remainingAccounts(clockAccounts())
+      .rpc();
+  }
+
+  function setPaused(paused: boolean) {
+    return program.methods.setPaused(paused).accounts({ globalConfig: globalConfigPDA(), admin }).rpc();
+  }
+
+  async function expectError(promise: Promise<unknown>, error: string) {
+    try {
+      await promise;
+      expect.fail(`Should have failed with ${error}`);
+    } catch (e) {
+      expect(e.toString()).to.include(error);
+    }
+  }
+
+  // `npm run test:integration` sets up every prerequisite, so a missing one
+  // there is a broken build and fails the suite instead of skipping it
+  function skipOrFail(ctx: Mocha.Context, reason: string) {
+    if (process.env.AEGIS_TEST_INTEGRATION) {
+      throw new Error(`Integration prerequisite missing: ${reason}`);
+    }
+    console.log(`    ⚠ Skipping: ${reason}`);
+    ctx.skip();
+  }
+
+  before(async function() {
+    if (!program.idl.instructions.some((ix) => ix.name === "setTestClock")) {
+      skipOrFail(this, "program not built with the test-utils feature");
+    }
+    if (!fs.existsSync(REGISTRY_IDL_PATH)) {
+      skipOrFail(this, "registry IDL not built");
+    }
+    registry = new Program(JSON.parse(fs.readFileSync(REGISTRY_IDL_PATH, "utf8")), provider);
+    const registryAccount = await provider.connection.getAccountInfo(registry.programId);
+    if (!registryAccount || !registryAccount.executable) {
+      skipOrFail(this, "registry program not deployed");
+    }
+
+    if (!(await provider.connection.getAccountInfo(registryConfigPDA()))) {
+      await registry.methods
+        .initializeRegistryConfig(admin, program.programId, MIN_STAKE)
+        .accounts({
+          registryConfig: registryConfigPDA(),
+          deployer: admin,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+    const registryConfig: any = await (registry.account as any).registryConfig.fetch(registryConfigPDA());
+    if (!registryConfig.stakingProgramId.equals(program.programId)) {
+      skipOrFail(this, "registry is bound to another staking program");
+    }
+
+    // Reuse the deployment staking.ts set up, or create one when run alone
+    let config = await program.account.globalConfig.fetchNullable(globalConfigPDA());
+    if (!config) {
+      mint = await createMint(provider.connection, provider.wallet.payer, admin, null, 9);
+      treasury = (await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, mint, admin)).address;
+      await program.methods
+        .initializeGlobalConfig(admin, MIN_STAKE, COOLDOWN_PERIOD, registry.programId)
+        .accounts({
+          globalConfig: globalConfigPDA(),
+          treasury,
+          deployer: admin,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .initializeStakeVault()
+        .accounts({
+          globalConfig: globalConfigPDA(),
+          stakeVault: stakeVaultPDA(),
+          mint,
+          admin,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.globalConfig.fetch(globalConfigPDA());
+    }
+    if (!config.adminAuthority.equals(admin)) {
+      skipOrFail(this, "global config has another admin");
+    }
+    mint = (await getAccount(provider.connection, stakeVaultPDA())).mint;
+    const mintInfo = await getMint(provider.connection, mint);
+    if (!mintInfo.mintAuthority || !mintInfo.mintAuthority.equals(admin)) {
+      skipOrFail(this, "stake mint has another mint authority");
+    }
+    treasury = config.treasury;
+    cooldownPeriod = config.unstakeCooldownPeriod.toNumber();
+
+    original = {
+      registryProgramId: config.registryProgramId,
+      paused: config.paused,
+      exitDelayOnFlag: config.exitDelayOnFlag,
+    };
+    await program.methods
+      .updateGlobalConfig(null, null, null, registry.programId)
+      .accounts({ globalConfig: globalConfigPDA(), admin })
+      .rpc();
+    if (config.paused) {
+      await setPaused(false);
+    }
+  });
+
+  after(async () => {
+    if (!original) {
+      return;
+    }
+    await program.methods
+      .updateGlobalConfig(null, null, null, original.registryProgramId)
+      .accounts({ globalConfig: globalConfigPDA(), admin })
+      .rpc();
+    await setPaused(original.paused);
+    await program.methods
+      .setExitDelayOnFlag(original.exitDelayOnFlag)
+      .accounts({ globalConfig: globalConfigPDA(), admin })
+      .rpc();
+    await program.methods
+      .setViolationCooldown({ lowUptime: {} } as any, new anchor.BN(LOW_UPTIME_COOLDOWN))
+      .accounts({ globalConfig: globalConfigPDA(), admin })
+      .rpc();
+  });
+
+  describe("Test clock", () => {
+    it("Only moves forward, and only for the admin", async () => {
+      await warp(60);
+      const offset = await clockOffset();
+
+      await expectError(
+        (program.methods as any)
+          .setTestClock(new anchor.BN(offset - 1))
+          .accounts({
+            globalConfig: globalConfigPDA(),
+            testClock: testClockPDA(),
+            admin,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+        "TestClockRewind"
+      );
+
+      const outsider = anchor.web3.Keypair.generate();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: admin,
+            toPubkey: outsider.publicKey,
+            lamports: 0.1 * anchor.web3.LAMPORTS_PER_SOL,
+          })
+        )
+      );
+      await expectError(
+        (program.methods as any)
+          .setTestClock(new anchor.BN(offset + 60))
+          .accounts({
+            globalConfig: globalConfigPDA(),
+            testClock: testClockPDA(),
+            admin: outsider.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([outsider])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+      expect(await clockOffset()).to.equal(offset);
+    });
+
+    it("Stamps warped time on stake accounts", async () => {
+      const operator = await newOperator(MIN_STAKE);
+      const before = Math.floor(Date.now() / 1000);
+      await warp(3600);
+      const offset = await clockOffset();
+
+      await initializeAndStake(operator, MIN_STAKE);
+
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.createdAt.toNumber()).to.be.at.least(before + offset - 60);
+    });
+  });
+
+  describe("Stake → registry sync", () => {
+    it("Syncs the registry on stake", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(3));
+      await program.methods
+        .initializeStake()
+        .accounts({
+          stakeAccount: stakePDA(operator.key),
+          operator: operator.key,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(clockAccounts())
+        .signers([operator.keypair])
+        .rpc();
+
+      await stake(operator, MIN_STAKE.muln(2));
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+
+      await stake(operator, MIN_STAKE);
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(3).toString());
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.stakedAmount.toString()).to.equal(MIN_STAKE.muln(3).toString());
+    });
+
+    it("Syncs the registry on initialize_and_stake", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(2));
+
+      await initializeAndStake(operator, MIN_STAKE);
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.toString());
+
+      // Second call tops up the existing account
+      await initializeAndStake(operator, MIN_STAKE);
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+    });
+  });
+
+  describe("Unstake across the cooldown", () => {
+    it("Executes only once the cooldown has passed, then syncs the registry", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(3));
+      await initializeAndStake(operator, MIN_STAKE.muln(3));
+
+      await requestUnstake(operator, MIN_STAKE);
+      // The registry keeps the full stake until the unstake executes
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(3).toString());
+      await expectError(executeUnstake(operator), "CooldownNotComplete");
+
+      await warp(cooldownPeriod - 120);
+      await expectError(executeUnstake(operator), "CooldownNotComplete");
+
+      await warp(120);
+      await executeUnstake(operator);
+
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.pendingUnstake.toNumber()).to.equal(0);
+      expect(account.totalUnstakedEver.toString()).to.equal(MIN_STAKE.toString());
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+      expect((await tokenBalance(operator.tokenAccount)).toString()).to.equal(MIN_STAKE.toString());
+    });
+
+    it("Cancels a pending unstake without touching the registry", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(2));
+      await initializeAndStake(operator, MIN_STAKE.muln(2));
+
+      await requestUnstake(operator, MIN_STAKE);
+      await warp(cooldownPeriod / 2);
+      await cancelUnstake(operator);
+
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.stakedAmount.toString()).to.equal(MIN_STAKE.muln(2).toString());
+      expect(account.pendingUnstake.toNumber()).to.equal(0);
+      await expectError(executeUnstake(operator), "NoPendingUnstake");
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+    });
+
+    it("Holds a flagged operator's unstake until the flag expires or is cleared", async () => {
+      await program.methods.setExitDelayOnFlag(true).accounts({ globalConfig: globalConfigPDA(), admin }).rpc();
+      const operator = await newOperator(MIN_STAKE.muln(2));
+      await initializeAndStake(operator, MIN_STAKE.muln(2));
+
+      const placeFlag = (duration: number) =>
+        program.methods
+          .placeInvestigationFlag(new anchor.BN(duration))
+          .accounts({
+            globalConfig: globalConfigPDA(),
+            stakeAccount: stakePDA(operator.key),
+            investigationFlag: flagPDA(operator.key),
+            flagQuota: pda([Buffer.from("flag_quota"), admin.toBuffer()]),
+            oracle: admin,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(clockAccounts())
+          .rpc();
+
+      await requestUnstake(operator, MIN_STAKE);
+      await placeFlag(cooldownPeriod + SLASH_TIMELOCK_PERIOD);
+      await warp(cooldownPeriod);
+      await expectError(executeUnstake(operator), "UnderInvestigation");
+
+      // The flag lapses on its own
+      await warp(SLASH_TIMELOCK_PERIOD + 60);
+      await executeUnstake(operator);
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.toString());
+
+      // A fresh flag, cleared early by the admin
+      await requestUnstake(operator, MIN_STAKE);
+      await placeFlag(SLASH_TIMELOCK_PERIOD);
+      await warp(cooldownPeriod);
+      await expectError(executeUnstake(operator), "UnderInvestigation");
+      await program.methods
+        .clearInvestigationFlag()
+        .accounts({ globalConfig: globalConfigPDA(), investigationFlag: flagPDA(operator.key), admin })
+        .remainingAccounts(clockAccounts())
+        .rpc();
+      await executeUnstake(operator);
+      expect(await registryStake(operator.key)).to.equal("0");
+    });
+  });
+
+  describe("Slash across the timelock", () => {
+    it("Executes a slash only after 24 hours, then syncs the registry", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(4));
+      await initializeAndStake(operator, MIN_STAKE.muln(4));
+      const treasuryBefore = await tokenBalance(treasury);
+
+      const slashRequest = await requestSlash(operator, { lowUptime: {} });
+      await program.methods
+        .appendEvidence("QmFollowUpProbeResults")
+        .accounts({ slashRequest, oracle: admin })
+        .remainingAccounts(clockAccounts())
+        .rpc();
+      await expectError(executeSlash(operator, slashRequest), "SlashTimelockNotExpired");
+
+      await warp(SLASH_TIMELOCK_PERIOD - 120);
+      await expectError(executeSlash(operator, slashRequest), "SlashTimelockNotExpired");
+
+      await warp(120);
+      await executeSlash(operator, slashRequest);
+
+      // LowUptime slashes 5%
+      const slashed = MIN_STAKE.muln(4).muln(5).divn(100);
+      const remaining = MIN_STAKE.muln(4).sub(slashed);
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.stakedAmount.toString()).to.equal(remaining.toString());
+      expect(await registryStake(operator.key)).to.equal(remaining.toString());
+      expect((await tokenBalance(treasury)) - treasuryBefore).to.equal(BigInt(slashed.toString()));
+      const request = await program.account.slashRequest.fetch(slashRequest);
+      expect(request.executed).to.equal(true);
+      expect(request.additionalEvidenceCount).to.equal(1);
+      await expectError(executeSlash(operator, slashRequest), "SlashAlreadyExecuted");
+    });
+
+    it("Enforces the violation cooldown across warped time", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(4));
+      await initializeAndStake(operator, MIN_STAKE.muln(4));
+      await program.methods
+        .setViolationCooldown({ lowUptime: {} } as any, new anchor.BN(2 * SLASH_TIMELOCK_PERIOD))
+        .accounts({ globalConfig: globalConfigPDA(), admin })
+        .rpc();
+
+      const first = await requestSlash(operator, { lowUptime: {} });
+      await warp(SLASH_TIMELOCK_PERIOD);
+      await executeSlash(operator, first);
+
+      await expectError(requestSlash(operator, { lowUptime: {} }), "ViolationCooldownActive");
+      await warp(2 * SLASH_TIMELOCK_PERIOD);
+      await requestSlash(operator, { lowUptime: {} });
+    });
+
+    it("Includes the operator's published contact in an offline slash request", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(2));
+      await initializeAndStake(operator, MIN_STAKE.muln(2));
+      const contactHash = Array.from(createHash("sha256").update("mailto:noc@operator.example").digest());
+      await registry.methods
+        .updateContact(contactHash, NOTIFY_SLASH_REQUESTS)
+        .accounts({ nodeAccount: nodePDA(operator.key), operator: operator.key })
+        .signers([operator.keypair])
+        .rpc();
+
+      let event: any = null;
+      const listener = program.addEventListener("slashRequestedEvent", (e) => {
+        event = e;
+      });
+      await requestSlash(operator, { offline48Hours: {} }, nodePDA(operator.key));
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(event.operator.toString()).to.equal(operator.key.toString());
+      expect(Array.from(event.contactHash)).to.deep.equal(contactHash);
+      expect(event.notifyFlags).to.equal(NOTIFY_SLASH_REQUESTS);
+
+      // Another operator's node can't stand in for theirs
+      const other = await newOperator(MIN_STAKE);
+      await expectError(
+        requestSlash(operator, { lowUptime: {} }, nodePDA(other.key)),
+        "InvalidNodeAccount"
+      );
+    });
+
+    it("Cancels a slash so it can never execute", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(2));
+      await initializeAndStake(operator, MIN_STAKE.muln(2));
+
+      const slashRequest = await requestSlash(operator, { challengeFailed: {} });
+      await cancelSlash(operator, slashRequest);
+      await warp(SLASH_TIMELOCK_PERIOD);
+      await expectError(executeSlash(operator, slashRequest), "SlashCancelled");
+      await expectError(cancelSlash(operator, slashRequest), "SlashAlreadyCancelled");
+
+      const account = await program.account.stakeAccount.fetch(stakePDA(operator.key));
+      expect(account.stakedAmount.toString()).to.equal(MIN_STAKE.muln(2).toString());
+      expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+    });
+  });
+
+  describe("Pause matrix", () => {
+    it("Blocks new stake while paused; exits, slashes and attestations continue", async () => {
+      const operator = await newOperator(MIN_STAKE.muln(4));
+      await initializeAndStake(operator, MIN_STAKE.muln(3));
+      const newcomer = await newOperator(MIN_STAKE);
+
+      await setPaused(true);
+      try {
+        await expectError(stake(operator, MIN_STAKE), "StakingPaused");
+        await expectError(initializeAndStake(newcomer, MIN_STAKE), "StakingPaused");
+
+        // Exits
+        await requestUnstake(operator, MIN_STAKE);
+        await cancelUnstake(operator);
+        await requestUnstake(operator, MIN_STAKE);
+        await warp(cooldownPeriod);
+        await executeUnstake(operator);
+        expect(await registryStake(operator.key)).to.equal(MIN_STAKE.muln(2).toString());
+
+        // Slashing
+        const slashRequest = await requestSlash(operator, { dataIntegrityViolation: {} });
+        await warp(SLASH_TIMELOCK_PERIOD);
+        await executeSlash(operator, slashRequest);
+        await program.methods
+          .slashStake(MIN_STAKE.divn(2), "Pause matrix")
+          .accounts({
+            globalConfig: globalConfigPDA(),
+            stakeAccount: stakePDA(operator.key),
+            stakeVault: stakeVaultPDA(),
+            treasury,
+            authority: admin,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ...registryAccounts(operator.key),
+          })
+          .remainingAccounts(clockAccounts())
+          .rpc();
+        // DataIntegrityViolation slashes 25% of 2x, then half a MIN_STAKE more
+        const remaining = MIN_STAKE.muln(2).muln(75).divn(100).sub(MIN_STAKE.divn(2));
+        expect(await registryStake(operator.key)).to.equal(remaining.toString());
+
+        await program.methods
+          .emitStakeAttestation(operator.key)
+          .accounts({ stakeAccount: stakePDA(operator.key) })
+          .remainingAccounts(clockAccounts())
+          .rpc();
+      } finally {
+        await setPaused(false);
+      }
+
+      await stake(operator, MIN_STAKE);
+      await initializeAndStake(newcomer, MIN_STAKE);
+      expect(await registryStake(newcomer.key)).to.equal(MIN_STAKE.toString());
+    });
+  });
+});
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 
 declare_id!("9uVLmgqJz3nYcCxHVSAJA8bi6412LEZ5uGM5yguvKHRq");
@@ -12,6 +13,52 @@ pub const MAX_MULTISIG_SIGNERS: usize = 9;
 /// Default fee burn percentage (basis points: 100 = 1%)
 pub const DEFAULT_FEE_BURN_BPS: u16 = 50; // 0.5% burn
 
+/// Maximum reason CID length for compliance holds (IPFS CID)
+pub const MAX_REASON_CID_LENGTH: usize = 64;
+
+/// Maximum memo CID length for multi-sig transactions (IPFS CID)
+pub const MAX_MEMO_CID_LENGTH: usize = 64;
+
+/// Maximum human-readable label length for multi-sig transactions
+pub const MAX_LABEL_LENGTH: usize = 48;
+
+/// Treasury transfers above this amount must carry a label (10,000 AEGIS)
+pub const DEFAULT_LABEL_REQUIRED_ABOVE: u64 = 10_000_000_000_000;
+
+/// Hard cap on the treasury dust burn threshold (1,000 AEGIS)
+pub const MAX_DUST_BURN_THRESHOLD: u64 = 1_000_000_000_000;
+
+// Tokenomics allocation caps (whitepaper section 10.1); they sum to TOTAL_SUPPLY
+
+/// Node operator rewards (500M) plus public sale (30M)
+pub const COMMUNITY_REWARDS_ALLOCATION: u64 = 530_000_000_000_000_000;
+
+/// Team & advisors (150M)
+pub const TEAM_ALLOCATION: u64 = 150_000_000_000_000_000;
+
+/// Private sale (100M)
+pub const INVESTORS_ALLOCATION: u64 = 100_000_000_000_000_000;
+
+/// Ecosystem fund / DAO treasury (200M)
+pub const TREASURY_ALLOCATION: u64 = 200_000_000_000_000_000;
+
+/// DEX liquidity (20M)
+pub const LIQUIDITY_ALLOCATION: u64 = 20_000_000_000_000_000;
+
+/// Number of allocation buckets
+pub const ALLOCATION_BUCKET_COUNT: usize = 5;
+
+/// Extra approvals an unregistered treasury destination needs to execute
+/// without waiting out TREASURY_DESTINATION_DELAY
+pub const UNREGISTERED_DESTINATION_EXTRA_APPROVALS: u8 = 2;
+
+/// Delay after the final approval before a transfer to an unregistered
+/// destination may execute at the normal threshold (48 hours)
+pub const TREASURY_DESTINATION_DELAY: i64 = 48 * 60 * 60;
+
+/// Domain prefix of the canonical multisig configuration serialization
+pub const CONFIG_HASH_DOMAIN: &[u8] = b"aegis-token-config-v1";
+
 #[program]
 pub mod aegis_token {
     use super::*;
@@ -205,6 +252,11 @@ pub mod aegis_token {
         config.total_fees_collected = 0;
         config.created_at = clock.unix_timestamp;
         config.bump = ctx.bumps.token_config;
+        config.label_required_above = DEFAULT_LABEL_REQUIRED_ABOVE;
+        // Dust burning stays disabled until a threshold is configured
+        config.dust_burn_threshold = 0;
+        config.treasury_floor = 0;
+        config.next_nonce = 0;
 
         // Copy signers
         for (i, signer) in signers.iter().enumerate() {
@@ -231,20 +283,59 @@ pub mod aegis_token {
     }
 
     /// Create a multi-sig transaction proposal
-    /// Used for controlled minting and treasury operations
-    /// nonce: unique identifier for this transaction (e.g., timestamp or incrementing counter)
-    pub fn create_multisig_transaction(
-        ctx: Context<CreateMultisigTransaction>,
-        transaction_type: MultisigTransactionType,
-        amount: u64,
-        recipient: Pubkey,
-        nonce: u64,
+    /// Used for controlled minting, treasury operations, and compliance holds
+    /// See CreateMultisigTransactionParams for the proposal fields
+    ///
+    /// The transaction always takes token_config.next_nonce, which is
+    /// consumed here; the counter keeps transaction PDAs sequential so
+    /// upcoming ones can't be squatted. multisig_tx is the PDA for the nonce
+    /// the client last saw, and remaining_accounts may list the PDAs for the
+    /// following nonces, so a creation that lands after others still
+    /// succeeds at the current counter value.
+    pub fn create_multisig_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateMultisigTransaction<'info>>,
+        params: CreateMultisigTransactionParams,
     ) -> Result<()> {
-        let _ = nonce; // Used in PDA seeds via instruction macro
-        let config = &ctx.accounts.token_config;
-        let tx = &mut ctx.accounts.multisig_tx;
+        let CreateMultisigTransactionParams {
+            transaction_type,
+            amount,
+            recipient,
+            reason_cid,
+            memo_cid,
+            label,
+            allocation_bucket,
+        } = params;
+        let config = &mut ctx.accounts.token_config;
         let clock = Clock::get()?;
 
+        require!(
+            reason_cid.len() <= MAX_REASON_CID_LENGTH,
+            TokenError::InvalidReasonCid
+        );
+        // Compliance holds must be documented
+        if matches!(
+            transaction_type,
+            MultisigTransactionType::FreezeAccount | MultisigTransactionType::ThawAccount
+        ) {
+            require!(!reason_cid.is_empty(), TokenError::InvalidReasonCid);
+        }
+        require!(
+            memo_cid.len() <= MAX_MEMO_CID_LENGTH,
+            TokenError::InvalidMemoCid
+        );
+        require!(label.len() <= MAX_LABEL_LENGTH, TokenError::InvalidLabel);
+        // Large treasury transfers must say what they are for
+        if transaction_type == MultisigTransactionType::TreasuryTransfer
+            && amount > config.label_required_above
+        {
+            require!(!label.is_empty(), TokenError::InvalidLabel);
+        }
+        // Every mint is accounted against a tokenomics bucket
+        require!(
+            allocation_bucket.is_some() == (transaction_type == MultisigTransactionType::Mint),
+            TokenError::InvalidAllocationBucket
+        );
+
         // Verify proposer is a valid signer
         let proposer = ctx.accounts.proposer.key();
         let is_valid_signer = config.signers[..config.signer_count as usize]
@@ -252,16 +343,38 @@ pub mod aegis_token {
             .any(|s| *s == proposer);
         require!(is_valid_signer, TokenError::InvalidSigner);
 
-        tx.config = config.key();
-        tx.transaction_type = transaction_type;
-        tx.amount = amount;
-        tx.recipient = recipient;
-        tx.proposer = proposer;
-        tx.approvals = vec![false; config.signer_count as usize];
-        tx.approval_count = 0;
-        tx.executed = false;
-        tx.created_at = clock.unix_timestamp;
-        tx.bump = ctx.bumps.multisig_tx;
+        let nonce = config.next_nonce;
+        let config_key = config.key();
+        let nonce_bytes = nonce.to_le_bytes();
+        let (tx_address, bump) = Pubkey::find_program_address(
+            &[b"multisig_tx", config_key.as_ref(), &nonce_bytes],
+            ctx.program_id,
+        );
+        let tx_info = std::iter::once(ctx.accounts.multisig_tx.to_account_info())
+            .chain(ctx.remaining_accounts.iter().cloned())
+            .find(|info| info.key() == tx_address)
+            .ok_or(TokenError::UnexpectedMultisigNonce)?;
+
+        let mut tx = MultisigTransaction {
+            config: config_key,
+            transaction_type,
+            amount,
+            recipient,
+            proposer,
+            approvals: vec![false; config.signer_count as usize],
+            approval_count: 0,
+            executed: false,
+            created_at: clock.unix_timestamp,
+            bump,
+            reason_cid: reason_cid.clone(),
+            memo_cid: memo_cid.clone(),
+            label: label.clone(),
+            allocation_bucket,
+            approved_at: None,
+            nonce,
+        };
+
+        config.next_nonce = nonce.checked_add(1).ok_or(TokenError::Overflow)?;
 
         // Auto-approve for proposer
         for (i, signer) in config.signers[..config.signer_count as usize].iter().enumerate() {
@@ -271,20 +384,37 @@ pub mod aegis_token {
                 break;
             }
         }
+        if tx.approval_count >= config.threshold {
+            tx.approved_at = Some(clock.unix_timestamp);
+        }
+
+        create_pda_account(
+            &tx_info,
+            MultisigTransaction::MAX_SIZE,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            &[b"multisig_tx", config_key.as_ref(), &nonce_bytes, &[bump]],
+        )?;
+        tx.try_serialize(&mut &mut tx_info.try_borrow_mut_data()?[..])?;
 
         msg!(
-            "Multisig transaction created: {:?}, amount={}, recipient={}",
+            "Multisig transaction created: {:?}, amount={}, recipient={}, label={:?}",
             transaction_type,
             amount,
-            recipient
+            recipient,
+            label
         );
 
         emit!(MultisigTransactionCreatedEvent {
-            tx_id: tx.key(),
+            tx_id: tx_address,
             transaction_type,
             amount,
             recipient,
             proposer,
+            reason_cid,
+            memo_cid,
+            label,
+            nonce,
             timestamp: clock.unix_timestamp,
         });
 
@@ -299,120 +429,392 @@ pub mod aegis_token {
         let tx = &mut ctx.accounts.multisig_tx;
         let clock = Clock::get()?;
 
+        let approver = ctx.accounts.approver.key();
+        tx.record_approval(config, approver, clock.unix_timestamp)?;
+        emit_approval(tx, approver, config.threshold, clock.unix_timestamp);
+
+        Ok(())
+    }
+
+    /// Execute a multi-sig transaction once threshold is reached
+    pub fn execute_multisig_transaction(
+        ctx: Context<ExecuteMultisigTransaction>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut *ctx.accounts;
+
+        execute_approved_multisig(
+            MultisigExecution {
+                token_config: &accounts.token_config,
+                mint: &accounts.mint,
+                treasury: &accounts.treasury,
+                recipient_token_account: &accounts.recipient_token_account,
+                token_program: &accounts.token_program,
+                mint_allocations: accounts.mint_allocations.as_mut(),
+                destination_registered: accounts.treasury_destination.is_some(),
+                executor: accounts.executor.key(),
+            },
+            &mut accounts.multisig_tx,
+            clock.unix_timestamp,
+        )?;
+
+        // Y9.12: MultisigTransaction account will be closed via `close = proposer` constraint
+        // Rent is refunded to the original proposer of the transaction
+        msg!("Multisig transaction executed, account closed, rent refunded to proposer");
+
+        Ok(())
+    }
+
+    /// Approve a multi-sig transaction and, if that brings it to the
+    /// threshold, execute it in the same instruction
+    ///
+    /// Below the threshold this is exactly approve_multisig_transaction.
+    /// Once the threshold is met, execution runs with the same checks as
+    /// execute_multisig_transaction, and a failing check rolls the approval
+    /// back with it, so signers whose approval can't execute yet (e.g. an
+    /// unregistered destination inside its delay) should use approve instead.
+    pub fn approve_and_execute_multisig_transaction(
+        ctx: Context<ApproveAndExecuteMultisigTransaction>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut *ctx.accounts;
+
+        let approver = accounts.approver.key();
+        let threshold = accounts.token_config.threshold;
+        accounts
+            .multisig_tx
+            .record_approval(&accounts.token_config, approver, clock.unix_timestamp)?;
+        emit_approval(&accounts.multisig_tx, approver, threshold, clock.unix_timestamp);
+
+        if accounts.multisig_tx.approval_count < threshold {
+            return Ok(());
+        }
+
+        execute_approved_multisig(
+            MultisigExecution {
+                token_config: &accounts.token_config,
+                mint: &accounts.mint,
+                treasury: &accounts.treasury,
+                recipient_token_account: &accounts.recipient_token_account,
+                token_program: &accounts.token_program,
+                mint_allocations: accounts.mint_allocations.as_mut(),
+                destination_registered: accounts.treasury_destination.is_some(),
+                executor: approver,
+            },
+            &mut accounts.multisig_tx,
+            clock.unix_timestamp,
+        )?;
+
+        // Closed only once executed, unlike execute's `close = proposer`
+        accounts.multisig_tx.close(accounts.proposer.to_account_info())?;
+        msg!("Multisig transaction executed, account closed, rent refunded to proposer");
+
+        Ok(())
+    }
+
+    /// Create the per-bucket mint allocation tracker (admin only)
+    ///
+    /// Caps come from the tokenomics constants. Multisig mints can't execute
+    /// until this exists. Tokens minted before it was created are not
+    /// attributed to any bucket.
+    pub fn initialize_mint_allocations(ctx: Context<InitializeMintAllocations>) -> Result<()> {
+        let allocations = &mut ctx.accounts.mint_allocations;
+        let clock = Clock::get()?;
+
+        allocations.mint = ctx.accounts.token_config.mint;
+        allocations.minted = [0; ALLOCATION_BUCKET_COUNT];
+        allocations.caps = AllocationBucket::ALL.map(AllocationBucket::cap);
+        allocations.bump = ctx.bumps.mint_allocations;
+
+        msg!("Mint allocations initialized for mint {}", allocations.mint);
+
+        emit!(MintAllocationsInitializedEvent {
+            mint: allocations.mint,
+            caps: allocations.caps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze a token account (compliance hold)
+    ///
+    /// Executes an approved FreezeAccount multi-sig transaction whose recipient
+    /// is the token account to freeze. The token_config PDA must be the mint's
+    /// freeze authority.
+    pub fn freeze_account(ctx: Context<ExecuteComplianceHold>) -> Result<()> {
+        let config = &ctx.accounts.token_config;
+        let tx = &mut ctx.accounts.multisig_tx;
+        let clock = Clock::get()?;
+
         require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+        require!(
+            tx.transaction_type == MultisigTransactionType::FreezeAccount,
+            TokenError::WrongTransactionType
+        );
+        require!(
+            tx.approval_count >= config.threshold,
+            TokenError::InsufficientApprovals
+        );
 
-        // Find signer index
-        let approver = ctx.accounts.approver.key();
-        let signer_index = config.signers[..config.signer_count as usize]
-            .iter()
-            .position(|s| *s == approver)
-            .ok_or(TokenError::InvalidSigner)?;
+        let seeds = &[
+            b"token_config".as_ref(),
+            config.mint.as_ref(),
+            &[config.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::FreezeAccount {
+                account: ctx.accounts.target_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: ctx.accounts.token_config.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        // Closed via `close = proposer` after the instruction
+        tx.executed = true;
+
+        msg!(
+            "Account frozen: {} (owner {}) - Reason: {}",
+            ctx.accounts.target_account.key(),
+            ctx.accounts.target_account.owner,
+            tx.reason_cid
+        );
+
+        emit!(AccountFrozenEvent {
+            mint: config.mint,
+            account: ctx.accounts.target_account.key(),
+            owner: ctx.accounts.target_account.owner,
+            reason_cid: tx.reason_cid.clone(),
+            tx_id: tx.key(),
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Thaw a frozen token account (release compliance hold)
+    ///
+    /// Executes an approved ThawAccount multi-sig transaction whose recipient
+    /// is the token account to thaw.
+    pub fn thaw_account(ctx: Context<ExecuteComplianceHold>) -> Result<()> {
+        let config = &ctx.accounts.token_config;
+        let tx = &mut ctx.accounts.multisig_tx;
+        let clock = Clock::get()?;
+
+        require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+        require!(
+            tx.transaction_type == MultisigTransactionType::ThawAccount,
+            TokenError::WrongTransactionType
+        );
+        require!(
+            tx.approval_count >= config.threshold,
+            TokenError::InsufficientApprovals
+        );
+
+        let seeds = &[
+            b"token_config".as_ref(),
+            config.mint.as_ref(),
+            &[config.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::thaw_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::ThawAccount {
+                account: ctx.accounts.target_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                authority: ctx.accounts.token_config.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        // Closed via `close = proposer` after the instruction
+        tx.executed = true;
+
+        msg!(
+            "Account thawed: {} (owner {}) - Reason: {}",
+            ctx.accounts.target_account.key(),
+            ctx.accounts.target_account.owner,
+            tx.reason_cid
+        );
+
+        emit!(AccountThawedEvent {
+            mint: config.mint,
+            account: ctx.accounts.target_account.key(),
+            owner: ctx.accounts.target_account.owner,
+            reason_cid: tx.reason_cid.clone(),
+            tx_id: tx.key(),
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burn dust left in the treasury
+    ///
+    /// Executes an approved BurnTreasuryDust multi-sig transaction whose
+    /// amount is the most it may burn. Burns min(amount, dust_burn_threshold)
+    /// with the token_config PDA as signer, and fails if that would leave the
+    /// treasury below treasury_floor.
+    pub fn burn_treasury_dust(ctx: Context<BurnTreasuryDust>) -> Result<()> {
+        let tx = &mut ctx.accounts.multisig_tx;
+        let clock = Clock::get()?;
+
+        require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+        require!(
+            tx.transaction_type == MultisigTransactionType::BurnTreasuryDust,
+            TokenError::WrongTransactionType
+        );
+        require!(
+            tx.approval_count >= ctx.accounts.token_config.threshold,
+            TokenError::InsufficientApprovals
+        );
+
+        let max_amount = tx.amount;
+        let burn_amount = max_amount.min(ctx.accounts.token_config.dust_burn_threshold);
+        require!(burn_amount > 0, TokenError::InvalidAmount);
+
+        let remaining = ctx
+            .accounts
+            .treasury
+            .amount
+            .checked_sub(burn_amount)
+            .ok_or(TokenError::TreasuryFloorBreached)?;
+        require!(
+            remaining >= ctx.accounts.token_config.treasury_floor,
+            TokenError::TreasuryFloorBreached
+        );
 
-        require!(!tx.approvals[signer_index], TokenError::AlreadyApproved);
+        let mint_key = ctx.accounts.token_config.mint;
+        let seeds = &[
+            b"token_config".as_ref(),
+            mint_key.as_ref(),
+            &[ctx.accounts.token_config.bump],
+        ];
+        let signer = &[&seeds[..]];
 
-        tx.approvals[signer_index] = true;
-        tx.approval_count += 1;
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.token_config.to_account_info(),
+                },
+                signer,
+            ),
+            burn_amount,
+        )?;
+
+        let config = &mut ctx.accounts.token_config;
+        config.total_burned = config
+            .total_burned
+            .checked_add(burn_amount)
+            .ok_or(TokenError::Overflow)?;
+
+        // Closed via `close = proposer` after the instruction
+        tx.executed = true;
 
         msg!(
-            "Transaction approved by {}: {}/{}",
-            approver,
-            tx.approval_count,
-            config.threshold
+            "Treasury dust burned: {} (requested {}), treasury balance {}",
+            burn_amount,
+            max_amount,
+            remaining
         );
 
-        emit!(MultisigApprovalEvent {
+        emit!(TreasuryDustBurnedEvent {
+            mint: config.mint,
+            treasury: ctx.accounts.treasury.key(),
+            requested_amount: max_amount,
+            burn_amount,
+            treasury_balance: remaining,
+            total_burned: config.total_burned,
             tx_id: tx.key(),
-            approver,
-            approval_count: tx.approval_count,
-            threshold: config.threshold,
+            executor: ctx.accounts.executor.key(),
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Execute a multi-sig transaction once threshold is reached
-    pub fn execute_multisig_transaction(
-        ctx: Context<ExecuteMultisigTransaction>,
-    ) -> Result<()> {
+    /// Register a treasury transfer destination
+    ///
+    /// Executes an approved AddTreasuryDestination multi-sig transaction whose
+    /// recipient is the destination token account. Transfers to registered
+    /// destinations execute at the normal threshold.
+    pub fn add_treasury_destination(ctx: Context<AddTreasuryDestination>) -> Result<()> {
         let config = &ctx.accounts.token_config;
         let tx = &mut ctx.accounts.multisig_tx;
         let clock = Clock::get()?;
 
         require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+        require!(
+            tx.transaction_type == MultisigTransactionType::AddTreasuryDestination,
+            TokenError::WrongTransactionType
+        );
         require!(
             tx.approval_count >= config.threshold,
             TokenError::InsufficientApprovals
         );
 
-        // Execute based on transaction type
-        match tx.transaction_type {
-            MultisigTransactionType::Mint => {
-                // Mint tokens to recipient
-                let seeds = &[
-                    b"token_config".as_ref(),
-                    config.mint.as_ref(),
-                    &[config.bump],
-                ];
-                let signer = &[&seeds[..]];
-
-                token::mint_to(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        MintTo {
-                            mint: ctx.accounts.mint.to_account_info(),
-                            to: ctx.accounts.recipient_token_account.to_account_info(),
-                            authority: ctx.accounts.token_config.to_account_info(),
-                        },
-                        signer,
-                    ),
-                    tx.amount,
-                )?;
-
-                msg!("Multi-sig mint executed: {} tokens to {}", tx.amount, tx.recipient);
-            }
-            MultisigTransactionType::TreasuryTransfer => {
-                // Transfer from treasury
-                let seeds = &[
-                    b"token_config".as_ref(),
-                    config.mint.as_ref(),
-                    &[config.bump],
-                ];
-                let signer = &[&seeds[..]];
-
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.treasury.to_account_info(),
-                            to: ctx.accounts.recipient_token_account.to_account_info(),
-                            authority: ctx.accounts.token_config.to_account_info(),
-                        },
-                        signer,
-                    ),
-                    tx.amount,
-                )?;
-
-                msg!("Treasury transfer executed: {} tokens to {}", tx.amount, tx.recipient);
-            }
-            MultisigTransactionType::UpdateConfig => {
-                // Config updates handled separately
-                msg!("Config update executed");
-            }
-        }
+        let destination = &mut ctx.accounts.treasury_destination;
+        destination.config = config.key();
+        destination.destination = tx.recipient;
+        destination.added_at = clock.unix_timestamp;
+        destination.tx_id = tx.key();
+        destination.bump = ctx.bumps.treasury_destination;
 
-        // Y9.12: tx.executed set for event emission before account close
+        // Closed via `close = proposer` after the instruction
         tx.executed = true;
 
-        // Y9.12: MultisigTransaction account will be closed via `close = proposer` constraint
-        // Rent is refunded to the original proposer of the transaction
-        msg!("Multisig transaction executed, account closed, rent refunded to proposer");
+        msg!("Treasury destination registered: {} - Label: {:?}", tx.recipient, tx.label);
 
-        emit!(MultisigExecutedEvent {
+        emit!(TreasuryDestinationAddedEvent {
+            config: config.key(),
+            destination: tx.recipient,
+            label: tx.label.clone(),
+            tx_id: tx.key(),
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Deregister a treasury transfer destination
+    ///
+    /// Executes an approved RemoveTreasuryDestination multi-sig transaction
+    /// whose recipient is the destination token account.
+    pub fn remove_treasury_destination(ctx: Context<RemoveTreasuryDestination>) -> Result<()> {
+        let config = &ctx.accounts.token_config;
+        let tx = &mut ctx.accounts.multisig_tx;
+        let clock = Clock::get()?;
+
+        require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+        require!(
+            tx.transaction_type == MultisigTransactionType::RemoveTreasuryDestination,
+            TokenError::WrongTransactionType
+        );
+        require!(
+            tx.approval_count >= config.threshold,
+            TokenError::InsufficientApprovals
+        );
+
+        // Both accounts are closed via `close = proposer` after the instruction
+        tx.executed = true;
+
+        msg!("Treasury destination removed: {}", tx.recipient);
+
+        emit!(TreasuryDestinationRemovedEvent {
+            config: config.key(),
+            destination: tx.recipient,
             tx_id: tx.key(),
-            transaction_type: tx.transaction_type,
-            amount: tx.amount,
-            recipient: tx.recipient,
             executor: ctx.accounts.executor.key(),
             timestamp: clock.unix_timestamp,
         });
@@ -528,46 +930,217 @@ pub mod aegis_token {
         Ok(())
     }
 
-    /// Update multi-sig signers (requires current multi-sig approval)
-    pub fn update_signers(
-        ctx: Context<UpdateSigners>,
-        new_signers: Vec<Pubkey>,
-        new_threshold: u8,
+    /// Update the treasury transfer amount above which a label is required (admin only)
+    pub fn update_label_threshold(
+        ctx: Context<UpdateTokenConfig>,
+        new_label_required_above: u64,
     ) -> Result<()> {
         let config = &mut ctx.accounts.token_config;
         let clock = Clock::get()?;
 
         require!(
-            new_signers.len() >= new_threshold as usize,
-            TokenError::InvalidThreshold
-        );
-        require!(
-            new_signers.len() <= MAX_MULTISIG_SIGNERS,
-            TokenError::TooManySigners
+            ctx.accounts.admin.key() == config.admin,
+            TokenError::InvalidAuthority
         );
-        require!(new_threshold >= 1, TokenError::InvalidThreshold);
 
-        // 🔒 SECURITY FIX (X6): Check for duplicate signers in updates too
-        {
-            let mut seen = std::collections::HashSet::new();
-            for signer in &new_signers {
-                require!(
-                    seen.insert(*signer),
-                    TokenError::DuplicateSigner
-                );
-            }
-        }
+        let old_threshold = config.label_required_above;
+        config.label_required_above = new_label_required_above;
 
-        let old_threshold = config.threshold;
-        let old_count = config.signer_count;
+        msg!(
+            "Label threshold updated: {} -> {}",
+            old_threshold,
+            new_label_required_above
+        );
 
-        // Clear old signers
-        for i in 0..MAX_MULTISIG_SIGNERS {
-            config.signers[i] = Pubkey::default();
-        }
+        emit!(LabelThresholdUpdatedEvent {
+            mint: config.mint,
+            old_threshold,
+            new_threshold: new_label_required_above,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Set new signers
-        for (i, signer) in new_signers.iter().enumerate() {
+        Ok(())
+    }
+
+    /// Update the treasury dust burn threshold and floor (admin only)
+    pub fn update_dust_burn_config(
+        ctx: Context<UpdateTokenConfig>,
+        new_dust_burn_threshold: u64,
+        new_treasury_floor: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.token_config;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts.admin.key() == config.admin,
+            TokenError::InvalidAuthority
+        );
+        require!(
+            new_dust_burn_threshold <= MAX_DUST_BURN_THRESHOLD,
+            TokenError::InvalidDustBurnThreshold
+        );
+
+        config.dust_burn_threshold = new_dust_burn_threshold;
+        config.treasury_floor = new_treasury_floor;
+
+        msg!(
+            "Dust burn config updated: threshold={}, treasury_floor={}",
+            new_dust_burn_threshold,
+            new_treasury_floor
+        );
+
+        emit!(DustBurnConfigUpdatedEvent {
+            mint: config.mint,
+            dust_burn_threshold: new_dust_burn_threshold,
+            treasury_floor: new_treasury_floor,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grow a token config created by an older program version to the
+    /// current layout (admin only)
+    ///
+    /// Appended fields get their defaults: label_required_above becomes
+    /// DEFAULT_LABEL_REQUIRED_ABOVE and the dust burn settings stay zero
+    /// (disabled). `first_nonce` seeds next_nonce when the config predates
+    /// the multisig counter; pass a value above every nonce already used
+    /// by an existing multisig_tx PDA. No-op for configs already at the
+    /// current size.
+    pub fn migrate_token_config(ctx: Context<MigrateTokenConfig>, first_nonce: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let info = ctx.accounts.token_config.to_account_info();
+        let original_len = info.data_len();
+
+        // admin directly follows the discriminator in every layout
+        let admin = info
+            .try_borrow_data()?
+            .get(8..40)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(TokenError::InvalidAuthority)?;
+        require_keys_eq!(ctx.accounts.admin.key(), admin, TokenError::InvalidAuthority);
+
+        if original_len >= TokenConfig::MAX_SIZE {
+            msg!("Token config already at the current layout ({} bytes)", original_len);
+            return Ok(());
+        }
+
+        grow_account(
+            &info,
+            TokenConfig::MAX_SIZE,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+        let mut config = TokenConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        config.fill_appended_defaults(original_len, first_nonce);
+        config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Token config migrated from {} to {} bytes",
+            original_len,
+            TokenConfig::MAX_SIZE
+        );
+        emit!(TokenConfigMigratedEvent {
+            mint: config.mint,
+            from_len: original_len as u32,
+            to_len: TokenConfig::MAX_SIZE as u32,
+            next_nonce: config.next_nonce,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Emit a TokenStatsEvent with the current supply and burn totals
+    ///
+    /// Read-only and permissionless, so dashboards can take a snapshot
+    /// instead of summing burn events. total_burned counts protocol burns
+    /// (fee and treasury dust burns); holder burns via burn_tokens show up
+    /// only as reduced supply.
+    pub fn emit_token_stats(ctx: Context<EmitTokenStats>) -> Result<()> {
+        let config = &ctx.accounts.token_config;
+        let clock = Clock::get()?;
+
+        emit!(TokenStatsEvent {
+            mint: config.mint,
+            supply: ctx.accounts.mint.supply,
+            max_supply: TOTAL_SUPPLY,
+            total_burned: config.total_burned,
+            total_fees_collected: config.total_fees_collected,
+            fee_burn_bps: config.fee_burn_bps,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Check the multisig configuration against a published hash
+    ///
+    /// Read-only and permissionless: lets auditors confirm on-chain that the
+    /// signer set, threshold, fee burn rate and admin match an announced
+    /// committee (see `TokenConfig::config_hash` for what is hashed).
+    pub fn verify_config(ctx: Context<VerifyConfig>, expected_hash: [u8; 32]) -> Result<()> {
+        let config = &ctx.accounts.token_config;
+        let clock = Clock::get()?;
+
+        let actual_hash = config.config_hash();
+        let matches = actual_hash == expected_hash;
+        msg!("Config verification: matches={}", matches);
+
+        emit!(ConfigVerificationEvent {
+            mint: config.mint,
+            matches,
+            actual_hash,
+            expected_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update multi-sig signers (requires current multi-sig approval)
+    pub fn update_signers(
+        ctx: Context<UpdateSigners>,
+        new_signers: Vec<Pubkey>,
+        new_threshold: u8,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.token_config;
+        let clock = Clock::get()?;
+
+        require!(
+            new_signers.len() >= new_threshold as usize,
+            TokenError::InvalidThreshold
+        );
+        require!(
+            new_signers.len() <= MAX_MULTISIG_SIGNERS,
+            TokenError::TooManySigners
+        );
+        require!(new_threshold >= 1, TokenError::InvalidThreshold);
+
+        // 🔒 SECURITY FIX (X6): Check for duplicate signers in updates too
+        {
+            let mut seen = std::collections::HashSet::new();
+            for signer in &new_signers {
+                require!(
+                    seen.insert(*signer),
+                    TokenError::DuplicateSigner
+                );
+            }
+        }
+
+        let old_threshold = config.threshold;
+        let old_count = config.signer_count;
+
+        // Clear old signers
+        for i in 0..MAX_MULTISIG_SIGNERS {
+            config.signers[i] = Pubkey::default();
+        }
+
+        // Set new signers
+        for (i, signer) in new_signers.iter().enumerate() {
             config.signers[i] = *signer;
         }
         config.signer_count = new_signers.len() as u8;
@@ -587,6 +1160,7 @@ pub mod aegis_token {
             new_threshold,
             old_count,
             new_count: config.signer_count,
+            config_hash: config.config_hash(),
             timestamp: clock.unix_timestamp,
         });
 
@@ -674,6 +1248,18 @@ pub struct BurnContext<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+/// Verify the multisig configuration (read-only)
+#[derive(Accounts)]
+pub struct VerifyConfig<'info> {
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    pub mint: Account<'info, Mint>,
+}
+
 // ============================================================================
 // Multi-Sig & Governance Account Contexts
 // ============================================================================
@@ -691,6 +1277,10 @@ pub struct TokenConfig {
     pub total_fees_collected: u64,               // Total fees collected (8 bytes)
     pub created_at: i64,                         // Creation timestamp (8 bytes)
     pub bump: u8,                                // PDA bump (1 byte)
+    pub label_required_above: u64,               // Treasury transfers above this need a label (8 bytes)
+    pub dust_burn_threshold: u64,                // Most a dust burn may remove (8 bytes)
+    pub treasury_floor: u64,                     // Dust burns may not go below this (8 bytes)
+    pub next_nonce: u64,                         // Nonce the next multisig transaction must use (8 bytes)
 }
 
 impl TokenConfig {
@@ -704,7 +1294,48 @@ impl TokenConfig {
         8 +                         // total_burned
         8 +                         // total_fees_collected
         8 +                         // created_at
-        1;                          // bump
+        1 +                         // bump
+        8 +                         // label_required_above
+        8 +                         // dust_burn_threshold
+        8 +                         // treasury_floor
+        8;                          // next_nonce
+
+    /// Canonical serialization of the governance-relevant configuration:
+    /// domain, signer_count, the active signers in order, threshold,
+    /// fee_burn_bps (little-endian) and admin
+    ///
+    /// Unused signer slots are excluded, so only the active set and its
+    /// order affect the result.
+    pub fn canonical_config_bytes(&self) -> Vec<u8> {
+        let signers = &self.signers[..self.signer_count as usize];
+        let mut bytes = Vec::with_capacity(CONFIG_HASH_DOMAIN.len() + 1 + 32 * signers.len() + 1 + 2 + 32);
+        bytes.extend_from_slice(CONFIG_HASH_DOMAIN);
+        bytes.push(self.signer_count);
+        for signer in signers {
+            bytes.extend_from_slice(signer.as_ref());
+        }
+        bytes.push(self.threshold);
+        bytes.extend_from_slice(&self.fee_burn_bps.to_le_bytes());
+        bytes.extend_from_slice(self.admin.as_ref());
+        bytes
+    }
+
+    /// SHA-256 of `canonical_config_bytes`
+    pub fn config_hash(&self) -> [u8; 32] {
+        solana_sha256_hasher::hash(&self.canonical_config_bytes()).to_bytes()
+    }
+
+    /// Set defaults for fields appended after an account of `original_len`
+    /// bytes was created; they deserialize as zero after a resize
+    pub fn fill_appended_defaults(&mut self, original_len: usize, first_nonce: u64) {
+        // label_required_above is the first of the four trailing u64s
+        if original_len <= Self::MAX_SIZE - 32 {
+            self.label_required_above = DEFAULT_LABEL_REQUIRED_ABOVE;
+        }
+        if original_len < Self::MAX_SIZE {
+            self.next_nonce = first_nonce;
+        }
+    }
 }
 
 /// Multi-sig transaction proposal
@@ -720,6 +1351,12 @@ pub struct MultisigTransaction {
     pub executed: bool,                    // Whether executed (1 byte)
     pub created_at: i64,                   // Creation timestamp (8 bytes)
     pub bump: u8,                          // PDA bump (1 byte)
+    pub reason_cid: String,                // IPFS CID justifying the action (4 + 64 bytes)
+    pub memo_cid: String,                  // IPFS CID of supporting document (4 + 64 bytes)
+    pub label: String,                     // Short description for signers (4 + 48 bytes)
+    pub allocation_bucket: Option<AllocationBucket>, // Bucket charged by a mint (1 + 1 bytes)
+    pub approved_at: Option<i64>,          // When approvals first reached the threshold (1 + 8 bytes)
+    pub nonce: u64,                        // Nonce in the PDA seeds (8 bytes)
 }
 
 impl MultisigTransaction {
@@ -733,7 +1370,294 @@ impl MultisigTransaction {
         1 +                         // approval_count
         1 +                         // executed
         8 +                         // created_at
-        1;                          // bump
+        1 +                         // bump
+        (4 + MAX_REASON_CID_LENGTH) + // reason_cid
+        (4 + MAX_MEMO_CID_LENGTH) + // memo_cid
+        (4 + MAX_LABEL_LENGTH) +    // label
+        (1 + 1) +                   // allocation_bucket
+        (1 + 8) +                   // approved_at
+        8;                          // nonce
+
+    /// Record `approver`'s approval
+    ///
+    /// The approval that reaches the threshold starts the
+    /// unregistered-destination delay.
+    pub fn record_approval(&mut self, config: &TokenConfig, approver: Pubkey, now: i64) -> Result<()> {
+        require!(!self.executed, TokenError::TransactionAlreadyExecuted);
+
+        let signer_index = config.signers[..config.signer_count as usize]
+            .iter()
+            .position(|s| *s == approver)
+            .ok_or(TokenError::InvalidSigner)?;
+        require!(!self.approvals[signer_index], TokenError::AlreadyApproved);
+
+        self.approvals[signer_index] = true;
+        self.approval_count += 1;
+        if self.approved_at.is_none() && self.approval_count >= config.threshold {
+            self.approved_at = Some(now);
+        }
+        Ok(())
+    }
+}
+
+/// Accounts an approved multi-sig transaction executes against, shared by
+/// execute_multisig_transaction and approve_and_execute_multisig_transaction
+struct MultisigExecution<'a, 'info> {
+    token_config: &'a Account<'info, TokenConfig>,
+    mint: &'a Account<'info, Mint>,
+    treasury: &'a Account<'info, TokenAccount>,
+    recipient_token_account: &'a Account<'info, TokenAccount>,
+    token_program: &'a Program<'info, Token>,
+    mint_allocations: Option<&'a mut Account<'info, MintAllocations>>,
+    /// A TreasuryDestination registration for the recipient was passed
+    destination_registered: bool,
+    executor: Pubkey,
+}
+
+/// Execute a multi-sig transaction that has reached its threshold
+///
+/// Marks it executed and emits MultisigExecutedEvent; closing the account
+/// is left to the caller.
+fn execute_approved_multisig(
+    exec: MultisigExecution,
+    tx: &mut Account<MultisigTransaction>,
+    now: i64,
+) -> Result<()> {
+    let config = exec.token_config;
+
+    require!(!tx.executed, TokenError::TransactionAlreadyExecuted);
+    require!(
+        tx.approval_count >= config.threshold,
+        TokenError::InsufficientApprovals
+    );
+
+    // Remaining capacity of the bucket a mint was charged to
+    let mut bucket_remaining = None;
+    // Which destination rule let a treasury transfer through
+    let mut treasury_transfer_path = None;
+
+    // Execute based on transaction type
+    match tx.transaction_type {
+        MultisigTransactionType::Mint => {
+            let bucket = tx
+                .allocation_bucket
+                .ok_or(TokenError::InvalidAllocationBucket)?;
+            let allocations = exec
+                .mint_allocations
+                .ok_or(TokenError::MintAllocationsRequired)?;
+
+            let new_supply = exec
+                .mint
+                .supply
+                .checked_add(tx.amount)
+                .ok_or(TokenError::Overflow)?;
+            require!(new_supply <= TOTAL_SUPPLY, TokenError::SupplyExceeded);
+            bucket_remaining = Some(allocations.record_mint(bucket, tx.amount)?);
+
+            // Mint tokens to recipient
+            let seeds = &[
+                b"token_config".as_ref(),
+                config.mint.as_ref(),
+                &[config.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    exec.token_program.to_account_info(),
+                    MintTo {
+                        mint: exec.mint.to_account_info(),
+                        to: exec.recipient_token_account.to_account_info(),
+                        authority: config.to_account_info(),
+                    },
+                    signer,
+                ),
+                tx.amount,
+            )?;
+
+            msg!(
+                "Multi-sig mint executed: {} tokens to {} from {:?} allocation",
+                tx.amount,
+                tx.recipient,
+                bucket
+            );
+        }
+        MultisigTransactionType::TreasuryTransfer => {
+            require_keys_eq!(
+                exec.recipient_token_account.key(),
+                tx.recipient,
+                TokenError::TargetMismatch
+            );
+            let path = treasury_transfer_path_for(
+                exec.destination_registered,
+                tx.approval_count,
+                config.threshold,
+                tx.approved_at,
+                now,
+            )?;
+            treasury_transfer_path = Some(path);
+
+            // Transfer from treasury
+            let seeds = &[
+                b"token_config".as_ref(),
+                config.mint.as_ref(),
+                &[config.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    exec.token_program.to_account_info(),
+                    Transfer {
+                        from: exec.treasury.to_account_info(),
+                        to: exec.recipient_token_account.to_account_info(),
+                        authority: config.to_account_info(),
+                    },
+                    signer,
+                ),
+                tx.amount,
+            )?;
+
+            msg!(
+                "Treasury transfer executed: {} tokens to {} ({:?})",
+                tx.amount,
+                tx.recipient,
+                path
+            );
+        }
+        MultisigTransactionType::UpdateConfig => {
+            // Config updates handled separately
+            msg!("Config update executed");
+        }
+        MultisigTransactionType::FreezeAccount
+        | MultisigTransactionType::ThawAccount
+        | MultisigTransactionType::BurnTreasuryDust
+        | MultisigTransactionType::AddTreasuryDestination
+        | MultisigTransactionType::RemoveTreasuryDestination => {
+            // Compliance holds go through freeze_account / thaw_account,
+            // dust burns through burn_treasury_dust, destination changes
+            // through add_treasury_destination / remove_treasury_destination
+            return err!(TokenError::WrongTransactionType);
+        }
+    }
+
+    // Y9.12: tx.executed set for event emission before account close
+    tx.executed = true;
+
+    emit!(MultisigExecutedEvent {
+        tx_id: tx.key(),
+        transaction_type: tx.transaction_type,
+        amount: tx.amount,
+        recipient: tx.recipient,
+        executor: exec.executor,
+        memo_cid: tx.memo_cid.clone(),
+        label: tx.label.clone(),
+        allocation_bucket: tx.allocation_bucket,
+        bucket_remaining,
+        treasury_transfer_path,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Resize a program-owned account to `len`, topping up rent from `payer`
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= len {
+        return Ok(());
+    }
+    let top_up = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Create the program-owned PDA `info` with `space` bytes, paid by `payer`
+///
+/// Mirrors Anchor's `init`: lamports already sent to the address are kept
+/// and only the shortfall is transferred.
+fn create_pda_account<'info>(
+    info: &AccountInfo<'info>,
+    space: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let signer = &[signer_seeds];
+    if info.lamports() == 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::CreateAccount {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+            signer,
+        );
+        return anchor_lang::system_program::create_account(cpi_ctx, rent, space as u64, &crate::ID);
+    }
+
+    let top_up = rent.saturating_sub(info.lamports());
+    if top_up > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, top_up)?;
+    }
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Allocate {
+            account_to_allocate: info.clone(),
+        },
+        signer,
+    );
+    anchor_lang::system_program::allocate(cpi_ctx, space as u64)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Assign {
+            account_to_assign: info.clone(),
+        },
+        signer,
+    );
+    anchor_lang::system_program::assign(cpi_ctx, &crate::ID)
+}
+
+/// Emit MultisigApprovalEvent for an approval just recorded
+fn emit_approval(tx: &Account<MultisigTransaction>, approver: Pubkey, threshold: u8, now: i64) {
+    msg!(
+        "Transaction approved by {}: {}/{}",
+        approver,
+        tx.approval_count,
+        threshold
+    );
+
+    emit!(MultisigApprovalEvent {
+        tx_id: tx.key(),
+        approver,
+        approval_count: tx.approval_count,
+        threshold,
+        memo_cid: tx.memo_cid.clone(),
+        label: tx.label.clone(),
+        timestamp: now,
+    });
 }
 
 /// Multi-sig transaction types
@@ -745,77 +1669,499 @@ pub enum MultisigTransactionType {
     TreasuryTransfer,
     /// Update configuration
     UpdateConfig,
+    /// Freeze the token account in `recipient` (compliance hold)
+    FreezeAccount,
+    /// Thaw the token account in `recipient`
+    ThawAccount,
+    /// Burn up to `amount` of treasury dust
+    BurnTreasuryDust,
+    /// Register the token account in `recipient` as a treasury destination
+    AddTreasuryDestination,
+    /// Deregister the treasury destination in `recipient`
+    RemoveTreasuryDestination,
+}
+
+/// Rule that allowed a treasury transfer to execute
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreasuryTransferPath {
+    /// Registered destination at the normal threshold
+    RegisteredDestination,
+    /// Unregistered destination with threshold + 2 approvals
+    ElevatedApprovals,
+    /// Unregistered destination after the 48-hour delay
+    Delayed,
+}
+
+/// Decide whether a treasury transfer may execute now
+///
+/// Registered destinations need only the normal threshold. Unregistered ones
+/// need UNREGISTERED_DESTINATION_EXTRA_APPROVALS more approvals, or
+/// TREASURY_DESTINATION_DELAY to pass since the threshold was reached.
+fn treasury_transfer_path_for(
+    registered: bool,
+    approval_count: u8,
+    threshold: u8,
+    approved_at: Option<i64>,
+    now: i64,
+) -> Result<TreasuryTransferPath> {
+    if registered {
+        return Ok(TreasuryTransferPath::RegisteredDestination);
+    }
+    if approval_count as u16 >= threshold as u16 + UNREGISTERED_DESTINATION_EXTRA_APPROVALS as u16 {
+        return Ok(TreasuryTransferPath::ElevatedApprovals);
+    }
+    let executable_at = approved_at
+        .ok_or(TokenError::TreasuryTransferDelayed)?
+        .checked_add(TREASURY_DESTINATION_DELAY)
+        .ok_or(TokenError::Overflow)?;
+    require!(now >= executable_at, TokenError::TreasuryTransferDelayed);
+    Ok(TreasuryTransferPath::Delayed)
+}
+
+/// Registered treasury transfer destination
+/// (seeds ["treasury_destination", token_config, destination])
+#[account]
+pub struct TreasuryDestination {
+    pub config: Pubkey,                            // Associated config (32 bytes)
+    pub destination: Pubkey,                       // Destination token account (32 bytes)
+    pub added_at: i64,                             // Registration timestamp (8 bytes)
+    pub tx_id: Pubkey,                             // Multi-sig transaction that added it (32 bytes)
+    pub bump: u8,                                  // PDA bump (1 byte)
+}
+
+impl TreasuryDestination {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                        // config
+        32 +                        // destination
+        8 +                         // added_at
+        32 +                        // tx_id
+        1;                          // bump
+}
+
+/// Tokenomics allocation a mint is charged against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocationBucket {
+    /// Node operator rewards and public sale
+    CommunityRewards,
+    /// Team & advisors
+    Team,
+    /// Private sale
+    Investors,
+    /// Ecosystem fund (DAO treasury)
+    Treasury,
+    /// DEX liquidity
+    Liquidity,
+}
+
+impl AllocationBucket {
+    pub const ALL: [AllocationBucket; ALLOCATION_BUCKET_COUNT] = [
+        AllocationBucket::CommunityRewards,
+        AllocationBucket::Team,
+        AllocationBucket::Investors,
+        AllocationBucket::Treasury,
+        AllocationBucket::Liquidity,
+    ];
+
+    /// Tokenomics cap for this bucket
+    pub fn cap(self) -> u64 {
+        match self {
+            AllocationBucket::CommunityRewards => COMMUNITY_REWARDS_ALLOCATION,
+            AllocationBucket::Team => TEAM_ALLOCATION,
+            AllocationBucket::Investors => INVESTORS_ALLOCATION,
+            AllocationBucket::Treasury => TREASURY_ALLOCATION,
+            AllocationBucket::Liquidity => LIQUIDITY_ALLOCATION,
+        }
+    }
+}
+
+/// Minted totals per tokenomics bucket
+#[account]
+pub struct MintAllocations {
+    pub mint: Pubkey,                              // Associated mint (32 bytes)
+    pub minted: [u64; ALLOCATION_BUCKET_COUNT],    // Minted per bucket, in AllocationBucket order (5 * 8 bytes)
+    pub caps: [u64; ALLOCATION_BUCKET_COUNT],      // Cap per bucket (5 * 8 bytes)
+    pub bump: u8,                                  // PDA bump (1 byte)
+}
+
+impl MintAllocations {
+    pub const MAX_SIZE: usize = 8 + // discriminator
+        32 +                        // mint
+        (8 * ALLOCATION_BUCKET_COUNT) + // minted
+        (8 * ALLOCATION_BUCKET_COUNT) + // caps
+        1;                          // bump
+
+    /// Charge `amount` to `bucket`, returning the bucket's remaining capacity
+    ///
+    /// Fails if the bucket cap or TOTAL_SUPPLY (across all buckets) would be
+    /// exceeded.
+    pub fn record_mint(&mut self, bucket: AllocationBucket, amount: u64) -> Result<u64> {
+        let index = bucket as usize;
+        let minted = self.minted[index]
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        require!(minted <= self.caps[index], TokenError::AllocationCapExceeded);
+
+        let total = self
+            .minted
+            .iter()
+            .try_fold(amount, |acc, m| acc.checked_add(*m))
+            .ok_or(TokenError::Overflow)?;
+        require!(total <= TOTAL_SUPPLY, TokenError::SupplyExceeded);
+
+        self.minted[index] = minted;
+        Ok(self.caps[index] - minted)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = TokenConfig::MAX_SIZE,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Proposal fields for create_multisig_transaction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CreateMultisigTransactionParams {
+    pub transaction_type: MultisigTransactionType,
+    /// Amount for mint/transfer/burn (0 for compliance holds)
+    pub amount: u64,
+    pub recipient: Pubkey,
+    /// IPFS CID documenting the action (required for freeze/thaw, may be empty otherwise)
+    pub reason_cid: String,
+    /// IPFS CID of the supporting document (grant agreement, invoice), may be empty
+    pub memo_cid: String,
+    /// Short description shown to signers (required for large treasury transfers)
+    pub label: String,
+    /// Tokenomics bucket charged by a mint (required for mints, None otherwise)
+    pub allocation_bucket: Option<AllocationBucket>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisigTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// CHECK: Candidate multisig_tx PDA, normally the one for the nonce the
+    /// client last saw; the handler creates whichever supplied candidate
+    /// matches token_config.next_nonce
+    #[account(mut)]
+    pub multisig_tx: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMultisigTransaction<'info> {
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        mut,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+    )]
+    pub multisig_tx: Account<'info, MultisigTransaction>,
+
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteMultisigTransaction<'info> {
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// Y9.12: MultisigTransaction account is closed after execution, returning rent to proposer
+    #[account(
+        mut,
+        close = proposer,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+    )]
+    pub multisig_tx: Account<'info, MultisigTransaction>,
+
+    /// CHECK: The original proposer who receives rent refund
+    /// Validated against multisig_tx.proposer
+    #[account(
+        mut,
+        constraint = proposer.key() == multisig_tx.proposer @ TokenError::InvalidProposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// Treasury token account (for transfers)
+    /// Y7.3 SECURITY FIX: Validate treasury is owned by the token_config PDA
+    /// This prevents attackers from substituting a malicious treasury account
+    #[account(
+        mut,
+        constraint = treasury.owner == token_config.key() @ TokenError::InvalidTreasuryOwner
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Recipient token account
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub executor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    /// Per-bucket mint totals (required for Mint transactions)
+    #[account(
+        mut,
+        seeds = [b"mint_allocations", token_config.mint.as_ref()],
+        bump = mint_allocations.bump
+    )]
+    pub mint_allocations: Option<Account<'info, MintAllocations>>,
+
+    /// Registration of the recipient as a treasury destination; omit for
+    /// unregistered destinations (which need more approvals or a delay)
+    #[account(
+        seeds = [b"treasury_destination", token_config.key().as_ref(), recipient_token_account.key().as_ref()],
+        bump = treasury_destination.bump
+    )]
+    pub treasury_destination: Option<Account<'info, TreasuryDestination>>,
+}
+
+/// Approve a multi-sig transaction, executing it if the threshold is met;
+/// carries every account execute_multisig_transaction needs
+#[derive(Accounts)]
+pub struct ApproveAndExecuteMultisigTransaction<'info> {
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// Closed to the proposer only if this approval executes it
+    #[account(
+        mut,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+    )]
+    pub multisig_tx: Account<'info, MultisigTransaction>,
+
+    /// CHECK: The original proposer who receives rent refund
+    /// Validated against multisig_tx.proposer
+    #[account(
+        mut,
+        constraint = proposer.key() == multisig_tx.proposer @ TokenError::InvalidProposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+
+    /// Treasury token account (for transfers), owned by the token_config PDA
+    #[account(
+        mut,
+        constraint = treasury.owner == token_config.key() @ TokenError::InvalidTreasuryOwner
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Recipient token account
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Signer approving; also the executor if the threshold is met
+    pub approver: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    /// Per-bucket mint totals (required for Mint transactions)
+    #[account(
+        mut,
+        seeds = [b"mint_allocations", token_config.mint.as_ref()],
+        bump = mint_allocations.bump
+    )]
+    pub mint_allocations: Option<Account<'info, MintAllocations>>,
+
+    /// Registration of the recipient as a treasury destination; omit for
+    /// unregistered destinations
+    #[account(
+        seeds = [b"treasury_destination", token_config.key().as_ref(), recipient_token_account.key().as_ref()],
+        bump = treasury_destination.bump
+    )]
+    pub treasury_destination: Option<Account<'info, TreasuryDestination>>,
 }
 
+/// Register a treasury destination via an approved multi-sig transaction
 #[derive(Accounts)]
-pub struct InitializeTokenConfig<'info> {
+pub struct AddTreasuryDestination<'info> {
     #[account(
-        init,
-        payer = admin,
-        space = TokenConfig::MAX_SIZE,
-        seeds = [b"token_config", mint.key().as_ref()],
-        bump
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
     )]
     pub token_config: Account<'info, TokenConfig>,
 
-    pub mint: Account<'info, Mint>,
+    /// Closed after execution, returning rent to proposer
+    #[account(
+        mut,
+        close = proposer,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+    )]
+    pub multisig_tx: Account<'info, MultisigTransaction>,
+
+    /// CHECK: The original proposer who receives rent refund
+    /// Validated against multisig_tx.proposer
+    #[account(
+        mut,
+        constraint = proposer.key() == multisig_tx.proposer @ TokenError::InvalidProposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = TreasuryDestination::MAX_SIZE,
+        seeds = [b"treasury_destination", token_config.key().as_ref(), multisig_tx.recipient.as_ref()],
+        bump
+    )]
+    pub treasury_destination: Account<'info, TreasuryDestination>,
 
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub executor: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// Deregister a treasury destination via an approved multi-sig transaction
 #[derive(Accounts)]
-#[instruction(transaction_type: MultisigTransactionType, amount: u64, recipient: Pubkey, nonce: u64)]
-pub struct CreateMultisigTransaction<'info> {
+pub struct RemoveTreasuryDestination<'info> {
     #[account(
         seeds = [b"token_config", token_config.mint.as_ref()],
         bump = token_config.bump
     )]
     pub token_config: Account<'info, TokenConfig>,
 
+    /// Closed after execution, returning rent to proposer
+    #[account(
+        mut,
+        close = proposer,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+    )]
+    pub multisig_tx: Account<'info, MultisigTransaction>,
+
+    /// CHECK: The original proposer who receives rent refund
+    /// Validated against multisig_tx.proposer
+    #[account(
+        mut,
+        constraint = proposer.key() == multisig_tx.proposer @ TokenError::InvalidProposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"treasury_destination", token_config.key().as_ref(), multisig_tx.recipient.as_ref()],
+        bump = treasury_destination.bump
+    )]
+    pub treasury_destination: Account<'info, TreasuryDestination>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMintAllocations<'info> {
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        has_one = admin @ TokenError::InvalidAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
     #[account(
         init,
-        payer = proposer,
-        space = MultisigTransaction::MAX_SIZE,
-        seeds = [b"multisig_tx", token_config.key().as_ref(), &nonce.to_le_bytes()],
+        payer = admin,
+        space = MintAllocations::MAX_SIZE,
+        seeds = [b"mint_allocations", token_config.mint.as_ref()],
         bump
     )]
-    pub multisig_tx: Account<'info, MultisigTransaction>,
+    pub mint_allocations: Account<'info, MintAllocations>,
 
     #[account(mut)]
-    pub proposer: Signer<'info>,
+    pub admin: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// Freeze or thaw a token account via an approved multi-sig transaction
 #[derive(Accounts)]
-pub struct ApproveMultisigTransaction<'info> {
+pub struct ExecuteComplianceHold<'info> {
     #[account(
         seeds = [b"token_config", token_config.mint.as_ref()],
         bump = token_config.bump
     )]
     pub token_config: Account<'info, TokenConfig>,
 
+    /// Closed after execution, returning rent to proposer
     #[account(
         mut,
-        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch
+        close = proposer,
+        constraint = multisig_tx.config == token_config.key() @ TokenError::ConfigMismatch,
+        constraint = multisig_tx.recipient == target_account.key() @ TokenError::TargetMismatch
     )]
     pub multisig_tx: Account<'info, MultisigTransaction>,
 
-    pub approver: Signer<'info>,
+    /// CHECK: The original proposer who receives rent refund
+    /// Validated against multisig_tx.proposer
+    #[account(
+        mut,
+        constraint = proposer.key() == multisig_tx.proposer @ TokenError::InvalidProposer
+    )]
+    pub proposer: AccountInfo<'info>,
+
+    /// Mint whose freeze authority is the token_config PDA
+    #[account(
+        constraint = mint.key() == token_config.mint @ TokenError::MintMismatch,
+        constraint = mint.freeze_authority == COption::Some(token_config.key()) @ TokenError::InvalidFreezeAuthority
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// Token account to freeze or thaw
+    #[account(
+        mut,
+        constraint = target_account.mint == mint.key() @ TokenError::MintMismatch
+    )]
+    pub target_account: Account<'info, TokenAccount>,
+
+    pub executor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
+/// Burn treasury dust via an approved multi-sig transaction
 #[derive(Accounts)]
-pub struct ExecuteMultisigTransaction<'info> {
+pub struct BurnTreasuryDust<'info> {
     #[account(
+        mut,
         seeds = [b"token_config", token_config.mint.as_ref()],
         bump = token_config.bump
     )]
     pub token_config: Account<'info, TokenConfig>,
 
-    /// Y9.12: MultisigTransaction account is closed after execution, returning rent to proposer
+    /// Closed after execution, returning rent to proposer
     #[account(
         mut,
         close = proposer,
@@ -831,22 +2177,20 @@ pub struct ExecuteMultisigTransaction<'info> {
     )]
     pub proposer: AccountInfo<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = mint.key() == token_config.mint @ TokenError::MintMismatch
+    )]
     pub mint: Account<'info, Mint>,
 
-    /// Treasury token account (for transfers)
-    /// Y7.3 SECURITY FIX: Validate treasury is owned by the token_config PDA
-    /// This prevents attackers from substituting a malicious treasury account
+    /// Treasury token account owned by the token_config PDA
     #[account(
         mut,
-        constraint = treasury.owner == token_config.key() @ TokenError::InvalidTreasuryOwner
+        constraint = treasury.owner == token_config.key() @ TokenError::InvalidTreasuryOwner,
+        constraint = treasury.mint == mint.key() @ TokenError::MintMismatch
     )]
     pub treasury: Account<'info, TokenAccount>,
 
-    /// Recipient token account
-    #[account(mut)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-
     pub executor: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
@@ -893,6 +2237,29 @@ pub struct UpdateTokenConfig<'info> {
     pub admin: Signer<'info>,
 }
 
+/// Migrate a token config to the current layout
+#[derive(Accounts)]
+pub struct MigrateTokenConfig<'info> {
+    /// CHECK: Older layouts are shorter than TokenConfig::MAX_SIZE and
+    /// can't be decoded as Account<TokenConfig> until resized; the PDA and
+    /// owner are checked here and the data is decoded in the handler
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub token_config: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Admin (must match token_config.admin); pays any extra rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateSigners<'info> {
     #[account(
@@ -906,6 +2273,18 @@ pub struct UpdateSigners<'info> {
     pub admin: Signer<'info>,
 }
 
+/// Read token stats (no state changes)
+#[derive(Accounts)]
+pub struct EmitTokenStats<'info> {
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    pub mint: Account<'info, Mint>,
+}
+
 // ============================================================================
 // Events
 // ============================================================================
@@ -963,6 +2342,10 @@ pub struct MultisigTransactionCreatedEvent {
     pub amount: u64,
     pub recipient: Pubkey,
     pub proposer: Pubkey,
+    pub reason_cid: String,
+    pub memo_cid: String,
+    pub label: String,
+    pub nonce: u64,
     pub timestamp: i64,
 }
 
@@ -972,6 +2355,8 @@ pub struct MultisigApprovalEvent {
     pub approver: Pubkey,
     pub approval_count: u8,
     pub threshold: u8,
+    pub memo_cid: String,
+    pub label: String,
     pub timestamp: i64,
 }
 
@@ -982,6 +2367,62 @@ pub struct MultisigExecutedEvent {
     pub amount: u64,
     pub recipient: Pubkey,
     pub executor: Pubkey,
+    pub memo_cid: String,
+    pub label: String,
+    /// Bucket charged by a mint (None for other transaction types)
+    pub allocation_bucket: Option<AllocationBucket>,
+    /// Remaining capacity of that bucket after the mint
+    pub bucket_remaining: Option<u64>,
+    /// Destination rule applied to a treasury transfer (None for other types)
+    pub treasury_transfer_path: Option<TreasuryTransferPath>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryDestinationAddedEvent {
+    pub config: Pubkey,
+    pub destination: Pubkey,
+    pub label: String,
+    pub tx_id: Pubkey,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryDestinationRemovedEvent {
+    pub config: Pubkey,
+    pub destination: Pubkey,
+    pub tx_id: Pubkey,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintAllocationsInitializedEvent {
+    pub mint: Pubkey,
+    pub caps: [u64; ALLOCATION_BUCKET_COUNT],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountFrozenEvent {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub owner: Pubkey,
+    pub reason_cid: String,
+    pub tx_id: Pubkey,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountThawedEvent {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub owner: Pubkey,
+    pub reason_cid: String,
+    pub tx_id: Pubkey,
+    pub executor: Pubkey,
     pub timestamp: i64,
 }
 
@@ -995,6 +2436,40 @@ pub struct FeeBurnEvent {
     pub timestamp: i64,
 }
 
+/// Snapshot of supply and burn totals (see emit_token_stats)
+#[event]
+pub struct TokenStatsEvent {
+    pub mint: Pubkey,
+    pub supply: u64,
+    pub max_supply: u64,
+    pub total_burned: u64,
+    pub total_fees_collected: u64,
+    pub fee_burn_bps: u16,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryDustBurnedEvent {
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
+    pub requested_amount: u64,
+    pub burn_amount: u64,
+    pub treasury_balance: u64,
+    pub total_burned: u64,
+    pub tx_id: Pubkey,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DustBurnConfigUpdatedEvent {
+    pub mint: Pubkey,
+    pub dust_burn_threshold: u64,
+    pub treasury_floor: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct FeeBpsUpdatedEvent {
     pub mint: Pubkey,
@@ -1003,6 +2478,23 @@ pub struct FeeBpsUpdatedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct LabelThresholdUpdatedEvent {
+    pub mint: Pubkey,
+    pub old_threshold: u64,
+    pub new_threshold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenConfigMigratedEvent {
+    pub mint: Pubkey,
+    pub from_len: u32,
+    pub to_len: u32,
+    pub next_nonce: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct SignersUpdatedEvent {
     pub mint: Pubkey,
@@ -1010,6 +2502,18 @@ pub struct SignersUpdatedEvent {
     pub new_threshold: u8,
     pub old_count: u8,
     pub new_count: u8,
+    /// TokenConfig::config_hash after the update
+    pub config_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Result of verify_config
+#[event]
+pub struct ConfigVerificationEvent {
+    pub mint: Pubkey,
+    pub matches: bool,
+    pub actual_hash: [u8; 32],
+    pub expected_hash: [u8; 32],
     pub timestamp: i64,
 }
 
@@ -1073,4 +2577,265 @@ pub enum TokenError {
     // Y9.12: Proposer validation for rent refund
     #[msg("Proposer account does not match transaction proposer")]
     InvalidProposer,
+
+    // Compliance holds (freeze/thaw)
+    #[msg("Reason CID is required for compliance holds and must be at most 64 bytes")]
+    InvalidReasonCid,
+
+    #[msg("Transaction type cannot be executed by this instruction")]
+    WrongTransactionType,
+
+    #[msg("Target account does not match transaction recipient")]
+    TargetMismatch,
+
+    #[msg("Token config PDA is not the mint's freeze authority")]
+    InvalidFreezeAuthority,
+
+    #[msg("Memo CID must be at most 64 bytes")]
+    InvalidMemoCid,
+
+    #[msg("Label must be at most 48 bytes and is required for treasury transfers above the label threshold")]
+    InvalidLabel,
+
+    #[msg("Dust burn threshold must be at most 1,000 AEGIS")]
+    InvalidDustBurnThreshold,
+
+    #[msg("Dust burn would leave the treasury below its floor")]
+    TreasuryFloorBreached,
+
+    #[msg("Mint transactions must name an allocation bucket; other types must not")]
+    InvalidAllocationBucket,
+
+    #[msg("Mint allocations account is required to execute a mint")]
+    MintAllocationsRequired,
+
+    #[msg("Mint would exceed the allocation bucket's cap")]
+    AllocationCapExceeded,
+
+    #[msg("Unregistered treasury destination: needs threshold + 2 approvals or 48 hours after the final approval")]
+    TreasuryTransferDelayed,
+
+    #[msg("No supplied multisig transaction account matches the config's next nonce")]
+    UnexpectedMultisigNonce,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_config(signers: &[Pubkey], threshold: u8) -> TokenConfig {
+        let mut slots = [Pubkey::default(); MAX_MULTISIG_SIGNERS];
+        slots[..signers.len()].copy_from_slice(signers);
+        TokenConfig {
+            admin: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            signers: slots,
+            threshold,
+            signer_count: signers.len() as u8,
+            fee_burn_bps: DEFAULT_FEE_BURN_BPS,
+            total_burned: 0,
+            total_fees_collected: 0,
+            created_at: 0,
+            bump: 255,
+            label_required_above: DEFAULT_LABEL_REQUIRED_ABOVE,
+            dust_burn_threshold: 0,
+            treasury_floor: 0,
+            next_nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_canonical_config_layout() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = token_config(&signers, 2);
+
+        let bytes = config.canonical_config_bytes();
+        assert_eq!(bytes.len(), CONFIG_HASH_DOMAIN.len() + 1 + 3 * 32 + 1 + 2 + 32);
+        assert!(bytes.starts_with(CONFIG_HASH_DOMAIN));
+        let body = &bytes[CONFIG_HASH_DOMAIN.len()..];
+        assert_eq!(body[0], 3);
+        assert_eq!(&body[1..33], signers[0].as_ref());
+        assert_eq!(body[97], 2);
+        assert_eq!(&body[98..100], &DEFAULT_FEE_BURN_BPS.to_le_bytes());
+        assert_eq!(&body[100..], config.admin.as_ref());
+    }
+
+    #[test]
+    fn test_reordering_signers_changes_hash() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut config = token_config(&signers, 2);
+        let original = config.config_hash();
+
+        config.signers.swap(0, 1);
+        assert_ne!(config.config_hash(), original);
+    }
+
+    #[test]
+    fn test_padding_slots_do_not_affect_hash() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut config = token_config(&signers, 2);
+        let original = config.config_hash();
+
+        // Stale keys past signer_count are not part of the configuration
+        config.signers[2] = Pubkey::new_unique();
+        config.signers[MAX_MULTISIG_SIGNERS - 1] = Pubkey::new_unique();
+        assert_eq!(config.config_hash(), original);
+
+        // Counters and timestamps aren't either
+        config.total_burned = 1_000;
+        config.next_nonce = 7;
+        assert_eq!(config.config_hash(), original);
+    }
+
+    #[test]
+    fn test_governance_fields_change_hash() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = token_config(&signers, 2);
+        let original = config.config_hash();
+
+        let mut changed = config.clone();
+        changed.threshold = 1;
+        assert_ne!(changed.config_hash(), original);
+
+        let mut changed = config.clone();
+        changed.fee_burn_bps += 1;
+        assert_ne!(changed.config_hash(), original);
+
+        let mut changed = config.clone();
+        changed.admin = Pubkey::new_unique();
+        assert_ne!(changed.config_hash(), original);
+
+        // Dropping the last signer changes the count and the set
+        let mut changed = config.clone();
+        changed.signer_count = 1;
+        assert_ne!(changed.config_hash(), original);
+    }
+
+    /// Treasury transfer proposed (and auto-approved) by `signers[0]`
+    fn proposed_transfer(config: &TokenConfig) -> MultisigTransaction {
+        let mut approvals = vec![false; config.signer_count as usize];
+        approvals[0] = true;
+        MultisigTransaction {
+            config: Pubkey::new_unique(),
+            transaction_type: MultisigTransactionType::TreasuryTransfer,
+            amount: 1_000,
+            recipient: Pubkey::new_unique(),
+            proposer: config.signers[0],
+            approvals,
+            approval_count: 1,
+            executed: false,
+            created_at: 0,
+            bump: 255,
+            reason_cid: String::new(),
+            memo_cid: String::new(),
+            label: String::new(),
+            allocation_bucket: None,
+            approved_at: None,
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_final_approval_reaches_threshold() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = token_config(&signers, 3);
+        let mut tx = proposed_transfer(&config);
+
+        // A non-final approval leaves the transaction pending
+        tx.record_approval(&config, signers[1], 100).unwrap();
+        assert_eq!(tx.approval_count, 2);
+        assert!(tx.approval_count < config.threshold);
+        assert_eq!(tx.approved_at, None);
+
+        tx.record_approval(&config, signers[2], 200).unwrap();
+        assert_eq!(tx.approval_count, 3);
+        assert_eq!(tx.approved_at, Some(200));
+        assert_eq!(tx.approvals, vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_approval_after_threshold_keeps_approved_at() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = token_config(&signers, 2);
+        let mut tx = proposed_transfer(&config);
+
+        tx.record_approval(&config, signers[1], 100).unwrap();
+        tx.record_approval(&config, signers[2], 500).unwrap();
+        // The delay runs from when the threshold was first reached
+        assert_eq!(tx.approved_at, Some(100));
+    }
+
+    #[test]
+    fn test_rejected_approvals() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let config = token_config(&signers, 2);
+        let mut tx = proposed_transfer(&config);
+
+        let err = tx.record_approval(&config, signers[0], 100).unwrap_err();
+        assert_eq!(err, TokenError::AlreadyApproved.into());
+
+        let err = tx.record_approval(&config, Pubkey::new_unique(), 100).unwrap_err();
+        assert_eq!(err, TokenError::InvalidSigner.into());
+
+        tx.executed = true;
+        let err = tx.record_approval(&config, signers[1], 100).unwrap_err();
+        assert_eq!(err, TokenError::TransactionAlreadyExecuted.into());
+        assert_eq!(tx.approval_count, 1);
+    }
+
+    /// Bytes of `config` as an older program version laid it out: truncated
+    /// to `len`, then zero-extended as resize would leave it
+    fn legacy_config_bytes(config: &TokenConfig, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        config.try_serialize(&mut bytes).unwrap();
+        bytes.truncate(len);
+        assert!(TokenConfig::try_deserialize(&mut &bytes[..]).is_err());
+        bytes.resize(TokenConfig::MAX_SIZE, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_migrate_legacy_token_config() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut current = token_config(&signers, 2);
+        current.total_burned = 1_234;
+        current.label_required_above = 42;
+        current.dust_burn_threshold = 7;
+        current.treasury_floor = 9;
+        current.next_nonce = 3;
+
+        // Before label_required_above, before the dust burn settings, and
+        // before next_nonce
+        for (len, label) in [
+            (TokenConfig::MAX_SIZE - 32, DEFAULT_LABEL_REQUIRED_ABOVE),
+            (TokenConfig::MAX_SIZE - 24, 42),
+            (TokenConfig::MAX_SIZE - 8, 42),
+        ] {
+            let bytes = legacy_config_bytes(&current, len);
+            let mut config = TokenConfig::try_deserialize(&mut &bytes[..]).unwrap();
+            config.fill_appended_defaults(len, 100);
+
+            assert_eq!(config.admin, current.admin);
+            assert_eq!(config.signers, current.signers);
+            assert_eq!(config.total_burned, 1_234);
+            assert_eq!(config.label_required_above, label);
+            assert_eq!(config.next_nonce, 100);
+        }
+
+        let bytes = legacy_config_bytes(&current, TokenConfig::MAX_SIZE - 32);
+        let config = TokenConfig::try_deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(config.dust_burn_threshold, 0);
+        assert_eq!(config.treasury_floor, 0);
+    }
+
+    #[test]
+    fn test_current_token_config_keeps_settings() {
+        let mut config = token_config(&[Pubkey::new_unique()], 1);
+        config.label_required_above = 42;
+        config.next_nonce = 3;
+
+        config.fill_appended_defaults(TokenConfig::MAX_SIZE, 100);
+        assert_eq!(config.label_required_above, 42);
+        assert_eq!(config.next_nonce, 3);
+    }
 }
@@ -4,10 +4,17 @@ import { AegisToken } from "../target/types/aegis_token";
 import {
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountInstruction,
+  getAccount,
+  getMint,
+  setAuthority,
+  freezeAccount,
+  transfer,
+  AuthorityType,
   TOKEN_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
 import { expect } from "chai";
+import { createHash } from "crypto";
 
 describe("aegis-token", () => {
   // Configure the client to use the local cluster
@@ -205,4 +212,1612 @@ describe("aegis-token", () => {
       expect(error).to.exist;
     }
   });
+
+  describe("Compliance holds (freeze/thaw via multisig)", () => {
+    let holdMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let secondSigner: anchor.web3.Keypair;
+    let holder: anchor.web3.Keypair;
+    let holderTokenAccount: anchor.web3.PublicKey;
+    let destinationTokenAccount: anchor.web3.PublicKey;
+    let nonce = 0;
+
+    const REASON_CID = "QmComplianceHoldReasonCid";
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    // Creates a freeze/thaw transaction (auto-approved by the proposer) and returns its PDA
+    async function proposeHold(type: object): Promise<anchor.web3.PublicKey> {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: type as any,
+          amount: new anchor.BN(0),
+          recipient: holderTokenAccount,
+          reasonCid: REASON_CID,
+          memoCid: "",
+          label: "",
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+      return multisigTx;
+    }
+
+    async function approve(multisigTx: anchor.web3.PublicKey) {
+      await program.methods
+        .approveMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          approver: secondSigner.publicKey,
+        })
+        .signers([secondSigner])
+        .rpc();
+    }
+
+    function holdAccounts(multisigTx: anchor.web3.PublicKey) {
+      return {
+        tokenConfig: tokenConfigPDA,
+        multisigTx,
+        proposer: payer.publicKey,
+        mint: holdMint.publicKey,
+        targetAccount: holderTokenAccount,
+        executor: payer.publicKey,
+      };
+    }
+
+    async function transferFromHolder() {
+      await transfer(
+        provider.connection,
+        (payer as any).payer,
+        holderTokenAccount,
+        destinationTokenAccount,
+        holder,
+        1_000_000_000
+      );
+    }
+
+    before(async () => {
+      holdMint = anchor.web3.Keypair.generate();
+      secondSigner = anchor.web3.Keypair.generate();
+      holder = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: holdMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([holdMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), holdMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      // 2-of-2 multisig so a single signer cannot execute a hold
+      await program.methods
+        .initializeTokenConfig([payer.publicKey, secondSigner.publicKey], 2, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: holdMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      // Hand the freeze authority to the token config PDA
+      await setAuthority(
+        provider.connection,
+        (payer as any).payer,
+        holdMint.publicKey,
+        mintAuthority,
+        AuthorityType.FreezeAccount,
+        tokenConfigPDA
+      );
+
+      holderTokenAccount = await createTokenAccount(holdMint.publicKey, holder.publicKey);
+      destinationTokenAccount = await createTokenAccount(holdMint.publicKey, payer.publicKey);
+
+      await program.methods
+        .mintTo(new anchor.BN(10_000_000_000))
+        .accounts({
+          mint: holdMint.publicKey,
+          to: holderTokenAccount,
+          authority: mintAuthority.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Rejects a freeze approved by a single signer", async () => {
+      const multisigTx = await proposeHold({ freezeAccount: {} });
+
+      try {
+        await program.methods
+          .freezeAccount()
+          .accounts(holdAccounts(multisigTx))
+          .rpc();
+        expect.fail("Should have required threshold approvals");
+      } catch (error) {
+        expect(error.toString()).to.include("InsufficientApprovals");
+      }
+
+      // The original keypair is no longer the freeze authority either
+      try {
+        await freezeAccount(
+          provider.connection,
+          (payer as any).payer,
+          holderTokenAccount,
+          holdMint.publicKey,
+          mintAuthority
+        );
+        expect.fail("Direct freeze with the old authority should fail");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+
+      const account = await getAccount(provider.connection, holderTokenAccount);
+      expect(account.isFrozen).to.equal(false);
+
+      // Complete the pending transaction for the next test
+      await approve(multisigTx);
+      await program.methods
+        .freezeAccount()
+        .accounts(holdAccounts(multisigTx))
+        .rpc();
+    });
+
+    it("Freezing via multisig blocks transfers from the account", async () => {
+      const account = await getAccount(provider.connection, holderTokenAccount);
+      expect(account.isFrozen).to.equal(true);
+
+      try {
+        await transferFromHolder();
+        expect.fail("Transfer from a frozen account should fail");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Thawing via multisig restores transfers", async () => {
+      const multisigTx = await proposeHold({ thawAccount: {} });
+      await approve(multisigTx);
+
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.reasonCid).to.equal(REASON_CID);
+
+      await program.methods
+        .thawAccount()
+        .accounts(holdAccounts(multisigTx))
+        .rpc();
+
+      const account = await getAccount(provider.connection, holderTokenAccount);
+      expect(account.isFrozen).to.equal(false);
+
+      await transferFromHolder();
+      const destination = await getAccount(provider.connection, destinationTokenAccount);
+      expect(destination.amount.toString()).to.equal("1000000000");
+    });
+
+    it("Requires a reason CID for compliance holds", async () => {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      try {
+        await program.methods
+          .createMultisigTransaction({
+            transactionType: { freezeAccount: {} } as any,
+            amount: new anchor.BN(0),
+            recipient: holderTokenAccount,
+            reasonCid: "",
+            memoCid: "",
+            label: "",
+            allocationBucket: null,
+          })
+          .accounts({
+            tokenConfig: tokenConfigPDA,
+            multisigTx: getMultisigTxPDA(nonce),
+            proposer: payer.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have required a reason CID");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidReasonCid");
+      }
+    });
+  });
+
+  describe("Multisig transaction memos and labels", () => {
+    let memoMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let nonce = 0;
+
+    const MEMO_CID = "QmGrantAgreementInvoiceCid";
+    const LABEL_REQUIRED_ABOVE = new anchor.BN(10_000_000_000_000); // 10,000 AEGIS
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    async function proposeTransfer(
+      amount: anchor.BN,
+      memoCid: string,
+      label: string
+    ): Promise<anchor.web3.PublicKey> {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: { treasuryTransfer: {} } as any,
+          amount,
+          recipient: payer.publicKey,
+          reasonCid: "",
+          memoCid,
+          label,
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+      return multisigTx;
+    }
+
+    before(async () => {
+      memoMint = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: memoMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([memoMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), memoMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: memoMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Stores the memo CID and label set at creation", async () => {
+      const multisigTx = await proposeTransfer(
+        LABEL_REQUIRED_ABOVE.muln(2),
+        MEMO_CID,
+        "Q3 grant: edge node tooling"
+      );
+
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.memoCid).to.equal(MEMO_CID);
+      expect(stored.label).to.equal("Q3 grant: edge node tooling");
+    });
+
+    it("Allows small transfers without a memo or label", async () => {
+      const multisigTx = await proposeTransfer(new anchor.BN(1_000_000_000), "", "");
+
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.memoCid).to.equal("");
+      expect(stored.label).to.equal("");
+    });
+
+    it("Requires a label for treasury transfers above the threshold", async () => {
+      const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      expect(config.labelRequiredAbove.toString()).to.equal(LABEL_REQUIRED_ABOVE.toString());
+
+      // Exactly at the threshold is still allowed unlabelled
+      await proposeTransfer(LABEL_REQUIRED_ABOVE, "", "");
+
+      try {
+        await proposeTransfer(LABEL_REQUIRED_ABOVE.addn(1), MEMO_CID, "");
+        expect.fail("Should have required a label");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidLabel");
+      }
+    });
+
+    it("Applies an updated label threshold", async () => {
+      await program.methods
+        .updateLabelThreshold(new anchor.BN(0))
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      try {
+        await proposeTransfer(new anchor.BN(1), "", "");
+        expect.fail("Should have required a label");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidLabel");
+      }
+    });
+
+    it("Rejects oversized labels and memo CIDs", async () => {
+      try {
+        await proposeTransfer(new anchor.BN(1), "", "x".repeat(49));
+        expect.fail("Should have rejected a 49-byte label");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidLabel");
+      }
+
+      try {
+        await proposeTransfer(new anchor.BN(1), "Q".repeat(65), "label");
+        expect.fail("Should have rejected a 65-byte memo CID");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidMemoCid");
+      }
+    });
+  });
+
+  describe("Multisig nonces", () => {
+    let nonceMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let secondSigner: anchor.web3.Keypair;
+
+    function getMultisigTxPDA(txNonce: anchor.BN): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig_tx"), tokenConfigPDA.toBuffer(), txNonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+    }
+
+    async function nextNonce(): Promise<anchor.BN> {
+      return (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce;
+    }
+
+    // Proposes with the PDA for txNonce plus the PDAs for the following
+    // `spare` nonces as candidates, so it lands even if others got in first
+    async function propose(txNonce: anchor.BN, proposer?: anchor.web3.Keypair, spare = 0) {
+      const candidates = Array.from({ length: spare }, (_, i) => ({
+        pubkey: getMultisigTxPDA(txNonce.addn(i + 1)),
+        isWritable: true,
+        isSigner: false,
+      }));
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: { treasuryTransfer: {} } as any,
+          amount: new anchor.BN(1_000_000_000),
+          recipient: payer.publicKey,
+          reasonCid: "",
+          memoCid: "",
+          label: "",
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx: getMultisigTxPDA(txNonce),
+          proposer: proposer ? proposer.publicKey : payer.publicKey,
+        })
+        .remainingAccounts(candidates)
+        .signers(proposer ? [proposer] : [])
+        .rpc();
+    }
+
+    before(async () => {
+      nonceMint = anchor.web3.Keypair.generate();
+      secondSigner = anchor.web3.Keypair.generate();
+
+      const fundTx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: secondSigner.publicKey,
+          lamports: 0.1 * anchor.web3.LAMPORTS_PER_SOL,
+        })
+      );
+      await provider.sendAndConfirm(fundTx);
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: nonceMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([nonceMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), nonceMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey, secondSigner.publicKey], 2, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: nonceMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Assigns consecutive nonces to sequential creations", async () => {
+      const first = await nextNonce();
+      expect(first.toNumber()).to.equal(0);
+      await propose(first);
+
+      const second = await nextNonce();
+      expect(second.toNumber()).to.equal(first.toNumber() + 1);
+      await propose(second);
+
+      const firstTx = await program.account.multisigTransaction.fetch(getMultisigTxPDA(first));
+      const secondTx = await program.account.multisigTransaction.fetch(getMultisigTxPDA(second));
+      expect(firstTx.nonce.toNumber()).to.equal(0);
+      expect(secondTx.nonce.toNumber()).to.equal(1);
+      expect((await nextNonce()).toNumber()).to.equal(2);
+    });
+
+    it("Gives a creation that lost a race the next counter value", async () => {
+      const seen = await nextNonce();
+
+      // Another signer's unrelated proposal lands first and consumes the nonce
+      await propose(seen, secondSigner);
+
+      // Built against the stale counter, but with a spare candidate PDA
+      await propose(seen, undefined, 2);
+
+      const raced = await program.account.multisigTransaction.fetch(getMultisigTxPDA(seen.addn(1)));
+      expect(raced.nonce.toString()).to.equal(seen.addn(1).toString());
+      expect(raced.proposer.toBase58()).to.equal(payer.publicKey.toBase58());
+      expect((await nextNonce()).toString()).to.equal(seen.addn(2).toString());
+
+      // The winner's transaction is untouched
+      const winner = await program.account.multisigTransaction.fetch(getMultisigTxPDA(seen));
+      expect(winner.proposer.toBase58()).to.equal(secondSigner.publicKey.toBase58());
+
+      // The spare candidate past the counter was never created
+      const info = await provider.connection.getAccountInfo(getMultisigTxPDA(seen.addn(2)));
+      expect(info).to.be.null;
+    });
+
+    it("Rejects creations that skip ahead of the counter", async () => {
+      const upcoming = (await nextNonce()).addn(1);
+      try {
+        await propose(upcoming, secondSigner);
+        expect.fail("Should have rejected a future nonce");
+      } catch (error) {
+        expect(error.toString()).to.include("UnexpectedMultisigNonce");
+      }
+
+      // The squatted-for PDA was never created
+      const info = await provider.connection.getAccountInfo(getMultisigTxPDA(upcoming));
+      expect(info).to.be.null;
+    });
+  });
+
+  describe("Token config migration", () => {
+    // The current program can't write a shorter config, so growing a legacy
+    // one and filling its defaults are covered by
+    // test_migrate_legacy_token_config
+
+    let migrateMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+
+    before(async () => {
+      migrateMint = anchor.web3.Keypair.generate();
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: migrateMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([migrateMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), migrateMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: migrateMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Leaves a current config untouched", async () => {
+      const before = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      await program.methods
+        .migrateTokenConfig(new anchor.BN(100))
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: migrateMint.publicKey,
+          admin: payer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      expect(after.nextNonce.toString()).to.equal(before.nextNonce.toString());
+      expect(after.labelRequiredAbove.toString()).to.equal(before.labelRequiredAbove.toString());
+    });
+
+    it("Only lets the admin migrate the config", async () => {
+      const impostor = anchor.web3.Keypair.generate();
+      const fundTx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: impostor.publicKey,
+          lamports: 0.1 * anchor.web3.LAMPORTS_PER_SOL,
+        })
+      );
+      await provider.sendAndConfirm(fundTx);
+
+      try {
+        await program.methods
+          .migrateTokenConfig(new anchor.BN(100))
+          .accounts({
+            tokenConfig: tokenConfigPDA,
+            mint: migrateMint.publicKey,
+            admin: impostor.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([impostor])
+          .rpc();
+        expect.fail("Should have rejected a non-admin");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAuthority");
+      }
+    });
+  });
+
+  describe("Treasury dust burns", () => {
+    let dustMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let treasury: anchor.web3.PublicKey;
+    let nonce = 0;
+
+    const AEGIS = 1_000_000_000;
+    const DUST_THRESHOLD = new anchor.BN(500 * AEGIS);
+    const TREASURY_FLOOR = new anchor.BN(9_000 * AEGIS);
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    // Proposes and (1-of-1 multisig) executes a dust burn of at most maxAmount
+    async function burnDust(maxAmount: anchor.BN) {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: { burnTreasuryDust: {} } as any,
+          amount: maxAmount,
+          recipient: treasury,
+          reasonCid: "",
+          memoCid: "",
+          label: "",
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .burnTreasuryDust()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+          mint: dustMint.publicKey,
+          treasury,
+          executor: payer.publicKey,
+        })
+        .rpc();
+    }
+
+    async function treasuryBalance(): Promise<string> {
+      return (await getAccount(provider.connection, treasury)).amount.toString();
+    }
+
+    before(async () => {
+      dustMint = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: dustMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([dustMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), dustMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: dustMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      // Treasury is owned by the token config PDA (off-curve owner)
+      treasury = getAssociatedTokenAddressSync(dustMint.publicKey, tokenConfigPDA, true);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            payer.publicKey,
+            treasury,
+            tokenConfigPDA,
+            dustMint.publicKey,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+          )
+        )
+      );
+
+      await program.methods
+        .mintTo(new anchor.BN(10_000 * AEGIS))
+        .accounts({
+          mint: dustMint.publicKey,
+          to: treasury,
+          authority: mintAuthority.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .updateDustBurnConfig(DUST_THRESHOLD, TREASURY_FLOOR)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Burns dust within the threshold and updates counters", async () => {
+      await burnDust(new anchor.BN(100 * AEGIS));
+
+      expect(await treasuryBalance()).to.equal((9_900 * AEGIS).toString());
+      const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      expect(config.totalBurned.toString()).to.equal((100 * AEGIS).toString());
+    });
+
+    it("Clamps requests above the threshold", async () => {
+      await burnDust(new anchor.BN(5_000 * AEGIS));
+
+      expect(await treasuryBalance()).to.equal((9_400 * AEGIS).toString());
+      const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      expect(config.totalBurned.toString()).to.equal((600 * AEGIS).toString());
+    });
+
+    it("Rejects burns that would drop the treasury below its floor", async () => {
+      try {
+        // 9,400 - 500 would leave 8,900, under the 9,000 floor
+        await burnDust(DUST_THRESHOLD);
+        expect.fail("Should have protected the treasury floor");
+      } catch (error) {
+        expect(error.toString()).to.include("TreasuryFloorBreached");
+      }
+
+      expect(await treasuryBalance()).to.equal((9_400 * AEGIS).toString());
+    });
+
+    it("Caps the dust threshold at 1,000 AEGIS", async () => {
+      try {
+        await program.methods
+          .updateDustBurnConfig(new anchor.BN(1_001 * AEGIS), TREASURY_FLOOR)
+          .accounts({
+            tokenConfig: tokenConfigPDA,
+            admin: payer.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have rejected a threshold above the cap");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidDustBurnThreshold");
+      }
+    });
+  });
+
+  describe("Mint allocations", () => {
+    let allocMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let mintAllocationsPDA: anchor.web3.PublicKey;
+    let treasury: anchor.web3.PublicKey;
+    let recipient: anchor.web3.PublicKey;
+    let nonce = 0;
+
+    const TOTAL_SUPPLY = new anchor.BN("1000000000000000000");
+    const LIQUIDITY_CAP = new anchor.BN("20000000000000000"); // 20M AEGIS
+    const LIQUIDITY_INDEX = 4;
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    async function proposeMint(amount: anchor.BN, bucket: object | null): Promise<anchor.web3.PublicKey> {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: { mint: {} } as any,
+          amount,
+          recipient,
+          reasonCid: "",
+          memoCid: "",
+          label: "allocation mint",
+          allocationBucket: bucket as any,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+      return multisigTx;
+    }
+
+    // Proposes and (1-of-1 multisig) executes a mint charged to bucket
+    async function mintFromBucket(amount: anchor.BN, bucket: object) {
+      const multisigTx = await proposeMint(amount, bucket);
+      await program.methods
+        .executeMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+          mint: allocMint.publicKey,
+          treasury,
+          recipientTokenAccount: recipient,
+          executor: payer.publicKey,
+          mintAllocations: mintAllocationsPDA,
+        } as any)
+        .rpc();
+    }
+
+    before(async () => {
+      allocMint = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: allocMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([allocMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), allocMint.publicKey.toBuffer()],
+        program.programId
+      );
+      [mintAllocationsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("mint_allocations"), allocMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: allocMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      // Multisig mints sign as the token config PDA
+      await setAuthority(
+        provider.connection,
+        (payer as any).payer,
+        allocMint.publicKey,
+        mintAuthority,
+        AuthorityType.MintTokens,
+        tokenConfigPDA
+      );
+
+      treasury = getAssociatedTokenAddressSync(allocMint.publicKey, tokenConfigPDA, true);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            payer.publicKey,
+            treasury,
+            tokenConfigPDA,
+            allocMint.publicKey,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+          )
+        )
+      );
+      recipient = await createTokenAccount(allocMint.publicKey, payer.publicKey);
+
+      await program.methods
+        .initializeMintAllocations()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mintAllocations: mintAllocationsPDA,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Caps across all buckets sum to TOTAL_SUPPLY", async () => {
+      const allocations = await program.account.mintAllocations.fetch(mintAllocationsPDA);
+      const total = allocations.caps.reduce((sum, cap) => sum.add(cap), new anchor.BN(0));
+      expect(total.toString()).to.equal(TOTAL_SUPPLY.toString());
+      expect(allocations.caps[LIQUIDITY_INDEX].toString()).to.equal(LIQUIDITY_CAP.toString());
+    });
+
+    it("Mints within a bucket's cap and records the total", async () => {
+      let event = null;
+      const listener = program.addEventListener("multisigExecutedEvent", (e) => {
+        event = e;
+      });
+
+      const amount = LIQUIDITY_CAP.sub(new anchor.BN(1));
+      await mintFromBucket(amount, { liquidity: {} });
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const allocations = await program.account.mintAllocations.fetch(mintAllocationsPDA);
+      expect(allocations.minted[LIQUIDITY_INDEX].toString()).to.equal(amount.toString());
+      const account = await getAccount(provider.connection, recipient);
+      expect(account.amount.toString()).to.equal(amount.toString());
+
+      expect(event).to.not.be.null;
+      expect(event.allocationBucket).to.deep.equal({ liquidity: {} });
+      expect(event.bucketRemaining.toString()).to.equal("1");
+    });
+
+    it("Rejects a mint that would exceed the bucket's cap", async () => {
+      try {
+        await mintFromBucket(new anchor.BN(2), { liquidity: {} });
+        expect.fail("Should have enforced the liquidity cap");
+      } catch (error) {
+        expect(error.toString()).to.include("AllocationCapExceeded");
+      }
+
+      // Filling the bucket exactly is still allowed
+      await mintFromBucket(new anchor.BN(1), { liquidity: {} });
+      const allocations = await program.account.mintAllocations.fetch(mintAllocationsPDA);
+      expect(allocations.minted[LIQUIDITY_INDEX].toString()).to.equal(LIQUIDITY_CAP.toString());
+    });
+
+    it("Requires a bucket on mint proposals", async () => {
+      try {
+        await proposeMint(new anchor.BN(1), null);
+        expect.fail("Should have required an allocation bucket");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAllocationBucket");
+      }
+    });
+  });
+
+  describe("Treasury destinations", () => {
+    let destMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let treasury: anchor.web3.PublicKey;
+    let registered: anchor.web3.PublicKey;
+    let unregistered: anchor.web3.PublicKey;
+    let signerB: anchor.web3.Keypair;
+    let signerC: anchor.web3.Keypair;
+    let nonce = 0;
+
+    const AEGIS = 1_000_000_000;
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    function getDestinationPDA(destination: anchor.web3.PublicKey): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury_destination"), tokenConfigPDA.toBuffer(), destination.toBuffer()],
+        program.programId
+      )[0];
+    }
+
+    // Creates a transaction (auto-approved by the proposer, which meets the 1-of-3 threshold)
+    async function propose(type: object, amount: anchor.BN, recipient: anchor.web3.PublicKey) {
+      nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: type as any,
+          amount,
+          recipient,
+          reasonCid: "",
+          memoCid: "",
+          label: "",
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+      return multisigTx;
+    }
+
+    async function approve(multisigTx: anchor.web3.PublicKey, approver: anchor.web3.Keypair) {
+      await program.methods
+        .approveMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          approver: approver.publicKey,
+        })
+        .signers([approver])
+        .rpc();
+    }
+
+    async function executeTransfer(multisigTx: anchor.web3.PublicKey, destination: anchor.web3.PublicKey, isRegistered: boolean) {
+      await program.methods
+        .executeMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+          mint: destMint.publicKey,
+          treasury,
+          recipientTokenAccount: destination,
+          executor: payer.publicKey,
+          mintAllocations: null,
+          treasuryDestination: isRegistered ? getDestinationPDA(destination) : null,
+        } as any)
+        .rpc();
+    }
+
+    async function balance(account: anchor.web3.PublicKey): Promise<string> {
+      return (await getAccount(provider.connection, account)).amount.toString();
+    }
+
+    before(async () => {
+      destMint = anchor.web3.Keypair.generate();
+      signerB = anchor.web3.Keypair.generate();
+      signerC = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: destMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([destMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), destMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey, signerB.publicKey, signerC.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: destMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      treasury = getAssociatedTokenAddressSync(destMint.publicKey, tokenConfigPDA, true);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            payer.publicKey,
+            treasury,
+            tokenConfigPDA,
+            destMint.publicKey,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+          )
+        )
+      );
+
+      await program.methods
+        .mintTo(new anchor.BN(1_000 * AEGIS))
+        .accounts({
+          mint: destMint.publicKey,
+          to: treasury,
+          authority: mintAuthority.publicKey,
+        })
+        .rpc();
+
+      registered = await createTokenAccount(destMint.publicKey, anchor.web3.Keypair.generate().publicKey);
+      unregistered = await createTokenAccount(destMint.publicKey, anchor.web3.Keypair.generate().publicKey);
+
+      const addTx = await propose({ addTreasuryDestination: {} }, new anchor.BN(0), registered);
+      await program.methods
+        .addTreasuryDestination()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx: addTx,
+          proposer: payer.publicKey,
+          treasuryDestination: getDestinationPDA(registered),
+          executor: payer.publicKey,
+        } as any)
+        .rpc();
+    });
+
+    it("Records the registered destination", async () => {
+      const destination = await program.account.treasuryDestination.fetch(getDestinationPDA(registered));
+      expect(destination.config.toString()).to.equal(tokenConfigPDA.toString());
+      expect(destination.destination.toString()).to.equal(registered.toString());
+    });
+
+    it("Transfers to a registered destination at the normal threshold", async () => {
+      let event = null;
+      const listener = program.addEventListener("multisigExecutedEvent", (e) => {
+        event = e;
+      });
+
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(100 * AEGIS), registered);
+      await executeTransfer(multisigTx, registered, true);
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(await balance(registered)).to.equal((100 * AEGIS).toString());
+      expect(event).to.not.be.null;
+      expect(event.treasuryTransferPath).to.deep.equal({ registeredDestination: {} });
+    });
+
+    it("Delays transfers to an unregistered destination at the normal threshold", async () => {
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(100 * AEGIS), unregistered);
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.approvedAt).to.not.be.null;
+
+      try {
+        await executeTransfer(multisigTx, unregistered, false);
+        expect.fail("Should have required extra approvals or the delay");
+      } catch (error) {
+        expect(error.toString()).to.include("TreasuryTransferDelayed");
+      }
+      expect(await balance(unregistered)).to.equal("0");
+    });
+
+    it("Transfers to an unregistered destination with threshold + 2 approvals", async () => {
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(50 * AEGIS), unregistered);
+      await approve(multisigTx, signerB);
+      await approve(multisigTx, signerC);
+
+      await executeTransfer(multisigTx, unregistered, false);
+      expect(await balance(unregistered)).to.equal((50 * AEGIS).toString());
+    });
+
+    it("Rejects a recipient token account that differs from the proposal", async () => {
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(AEGIS), unregistered);
+
+      try {
+        await executeTransfer(multisigTx, registered, true);
+        expect.fail("Should have rejected the substituted recipient");
+      } catch (error) {
+        expect(error.toString()).to.include("TargetMismatch");
+      }
+    });
+
+    it("Removes a destination so transfers to it are delayed again", async () => {
+      const removeTx = await propose({ removeTreasuryDestination: {} }, new anchor.BN(0), registered);
+      await program.methods
+        .removeTreasuryDestination()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx: removeTx,
+          proposer: payer.publicKey,
+          treasuryDestination: getDestinationPDA(registered),
+          executor: payer.publicKey,
+        } as any)
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(getDestinationPDA(registered));
+      expect(info).to.be.null;
+
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(AEGIS), registered);
+      try {
+        await executeTransfer(multisigTx, registered, false);
+        expect.fail("Should have treated the removed destination as unregistered");
+      } catch (error) {
+        expect(error.toString()).to.include("TreasuryTransferDelayed");
+      }
+    });
+  });
+
+  describe("Approve and execute", () => {
+    // 3-of-3 config with one registered treasury destination
+    let destMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let treasury: anchor.web3.PublicKey;
+    let registered: anchor.web3.PublicKey;
+    let other: anchor.web3.PublicKey;
+    let signerB: anchor.web3.Keypair;
+    let signerC: anchor.web3.Keypair;
+
+    const AEGIS = 1_000_000_000;
+
+    function getMultisigTxPDA(txNonce: number): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("multisig_tx"),
+          tokenConfigPDA.toBuffer(),
+          new anchor.BN(txNonce).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    }
+
+    function getDestinationPDA(destination: anchor.web3.PublicKey): anchor.web3.PublicKey {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury_destination"), tokenConfigPDA.toBuffer(), destination.toBuffer()],
+        program.programId
+      )[0];
+    }
+
+    // Creates a transaction, auto-approved by the proposer (1 of 3)
+    async function propose(type: object, amount: anchor.BN, recipient: anchor.web3.PublicKey) {
+      const nonce = (await program.account.tokenConfig.fetch(tokenConfigPDA)).nextNonce.toNumber();
+      const multisigTx = getMultisigTxPDA(nonce);
+      await program.methods
+        .createMultisigTransaction({
+          transactionType: type as any,
+          amount,
+          recipient,
+          reasonCid: "",
+          memoCid: "",
+          label: "",
+          allocationBucket: null,
+        })
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+        })
+        .rpc();
+      return multisigTx;
+    }
+
+    async function approve(multisigTx: anchor.web3.PublicKey, approver: anchor.web3.Keypair) {
+      await program.methods
+        .approveMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          approver: approver.publicKey,
+        })
+        .signers([approver])
+        .rpc();
+    }
+
+    async function approveAndExecute(
+      multisigTx: anchor.web3.PublicKey,
+      approver: anchor.web3.Keypair,
+      recipientTokenAccount: anchor.web3.PublicKey
+    ) {
+      await program.methods
+        .approveAndExecuteMultisigTransaction()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx,
+          proposer: payer.publicKey,
+          mint: destMint.publicKey,
+          treasury,
+          recipientTokenAccount,
+          approver: approver.publicKey,
+          mintAllocations: null,
+          treasuryDestination: recipientTokenAccount.equals(registered) ? getDestinationPDA(registered) : null,
+        } as any)
+        .signers([approver])
+        .rpc();
+    }
+
+    async function balance(account: anchor.web3.PublicKey): Promise<string> {
+      return (await getAccount(provider.connection, account)).amount.toString();
+    }
+
+    before(async () => {
+      destMint = anchor.web3.Keypair.generate();
+      signerB = anchor.web3.Keypair.generate();
+      signerC = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: destMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([destMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), destMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey, signerB.publicKey, signerC.publicKey], 3, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: destMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      treasury = getAssociatedTokenAddressSync(destMint.publicKey, tokenConfigPDA, true);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createAssociatedTokenAccountInstruction(
+            payer.publicKey,
+            treasury,
+            tokenConfigPDA,
+            destMint.publicKey,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+          )
+        )
+      );
+
+      await program.methods
+        .mintTo(new anchor.BN(1_000 * AEGIS))
+        .accounts({
+          mint: destMint.publicKey,
+          to: treasury,
+          authority: mintAuthority.publicKey,
+        })
+        .rpc();
+
+      registered = await createTokenAccount(destMint.publicKey, anchor.web3.Keypair.generate().publicKey);
+      other = await createTokenAccount(destMint.publicKey, anchor.web3.Keypair.generate().publicKey);
+
+      const addTx = await propose({ addTreasuryDestination: {} }, new anchor.BN(0), registered);
+      await approve(addTx, signerB);
+      await approve(addTx, signerC);
+      await program.methods
+        .addTreasuryDestination()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          multisigTx: addTx,
+          proposer: payer.publicKey,
+          treasuryDestination: getDestinationPDA(registered),
+          executor: payer.publicKey,
+        } as any)
+        .rpc();
+    });
+
+    it("Leaves the transaction pending after a non-final approval", async () => {
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(10 * AEGIS), registered);
+      const before = await balance(registered);
+
+      await approveAndExecute(multisigTx, signerB, registered);
+
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.approvalCount).to.equal(2);
+      expect(stored.executed).to.equal(false);
+      expect(stored.approvedAt).to.be.null;
+      expect(await balance(registered)).to.equal(before);
+    });
+
+    it("Executes atomically when the approval reaches the threshold", async () => {
+      let approval = null;
+      let executed = null;
+      const approvalListener = program.addEventListener("multisigApprovalEvent", (e) => {
+        approval = e;
+      });
+      const executedListener = program.addEventListener("multisigExecutedEvent", (e) => {
+        executed = e;
+      });
+
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(25 * AEGIS), registered);
+      await approve(multisigTx, signerB);
+      const before = BigInt(await balance(registered));
+
+      await approveAndExecute(multisigTx, signerC, registered);
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(approvalListener);
+      await program.removeEventListener(executedListener);
+
+      expect((BigInt(await balance(registered)) - before).toString()).to.equal((25 * AEGIS).toString());
+      // Executed and closed in the same instruction, rent back to the proposer
+      expect(await provider.connection.getAccountInfo(multisigTx)).to.be.null;
+      expect(approval).to.not.be.null;
+      expect(approval.approvalCount).to.equal(3);
+      expect(executed).to.not.be.null;
+      expect(executed.executor.toString()).to.equal(signerC.publicKey.toString());
+      expect(executed.treasuryTransferPath).to.deep.equal({ registeredDestination: {} });
+    });
+
+    it("Rejects a substituted recipient and rolls back the approval", async () => {
+      const multisigTx = await propose({ treasuryTransfer: {} }, new anchor.BN(AEGIS), registered);
+      await approve(multisigTx, signerB);
+
+      try {
+        await approveAndExecute(multisigTx, signerC, other);
+        expect.fail("Should have rejected the substituted recipient");
+      } catch (error) {
+        expect(error.toString()).to.include("TargetMismatch");
+      }
+
+      const stored = await program.account.multisigTransaction.fetch(multisigTx);
+      expect(stored.approvalCount).to.equal(2);
+      expect(stored.executed).to.equal(false);
+      expect(await balance(other)).to.equal("0");
+    });
+  });
+
+  describe("Token stats", () => {
+    let statsMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    let treasury: anchor.web3.PublicKey;
+    let payerAccount: anchor.web3.PublicKey;
+
+    const AEGIS = 1_000_000_000;
+
+    before(async () => {
+      statsMint = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: statsMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([statsMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), statsMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey], 1, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: statsMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+
+      treasury = getAssociatedTokenAddressSync(statsMint.publicKey, tokenConfigPDA, true);
+      payerAccount = getAssociatedTokenAddressSync(statsMint.publicKey, payer.publicKey);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction()
+          .add(
+            createAssociatedTokenAccountInstruction(
+              payer.publicKey,
+              treasury,
+              tokenConfigPDA,
+              statsMint.publicKey,
+              TOKEN_PROGRAM_ID,
+              ASSOCIATED_TOKEN_PROGRAM_ID
+            )
+          )
+          .add(
+            createAssociatedTokenAccountInstruction(
+              payer.publicKey,
+              payerAccount,
+              payer.publicKey,
+              statsMint.publicKey,
+              TOKEN_PROGRAM_ID,
+              ASSOCIATED_TOKEN_PROGRAM_ID
+            )
+          )
+      );
+
+      await program.methods
+        .mintTo(new anchor.BN(10_000 * AEGIS))
+        .accounts({
+          mint: statsMint.publicKey,
+          to: payerAccount,
+          authority: mintAuthority.publicKey,
+        })
+        .rpc();
+
+      // 50 bps of a 1,000 AEGIS fee burns 5 AEGIS
+      await program.methods
+        .processFeeWithBurn(new anchor.BN(1_000 * AEGIS))
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: statsMint.publicKey,
+          feePayerAccount: payerAccount,
+          treasury,
+          feePayer: payer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("Emits supply and burn totals matching on-chain state", async () => {
+      let event = null;
+      const listener = program.addEventListener("tokenStatsEvent", (e) => {
+        event = e;
+      });
+
+      await program.methods
+        .emitTokenStats()
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: statsMint.publicKey,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(event).to.not.be.null;
+      const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+      const mint = await getMint(provider.connection, statsMint.publicKey);
+
+      expect(event.mint.toString()).to.equal(statsMint.publicKey.toString());
+      expect(event.supply.toString()).to.equal(mint.supply.toString());
+      expect(event.supply.toString()).to.equal((9_995 * AEGIS).toString());
+      expect(event.totalBurned.toString()).to.equal(config.totalBurned.toString());
+      expect(event.totalBurned.toString()).to.equal((5 * AEGIS).toString());
+      expect(event.totalFeesCollected.toString()).to.equal(config.totalFeesCollected.toString());
+      expect(event.feeBurnBps).to.equal(config.feeBurnBps);
+      expect(event.maxSupply.toString()).to.equal(new anchor.BN(1_000_000_000).mul(new anchor.BN(AEGIS)).toString());
+    });
+  });
+
+  describe("Config verification", () => {
+    let verifyMint: anchor.web3.Keypair;
+    let tokenConfigPDA: anchor.web3.PublicKey;
+    const cosigner = anchor.web3.Keypair.generate();
+
+    // Mirrors TokenConfig::canonical_config_bytes
+    function configHash(signers: anchor.web3.PublicKey[], threshold: number, feeBurnBps: number, admin: anchor.web3.PublicKey): Buffer {
+      const fee = Buffer.alloc(2);
+      fee.writeUInt16LE(feeBurnBps);
+      return createHash("sha256")
+        .update(Buffer.from("aegis-token-config-v1"))
+        .update(Buffer.from([signers.length]))
+        .update(Buffer.concat(signers.map((s) => s.toBuffer())))
+        .update(Buffer.from([threshold]))
+        .update(fee)
+        .update(admin.toBuffer())
+        .digest();
+    }
+
+    async function verify(expectedHash: Buffer): Promise<any> {
+      let event = null;
+      const listener = program.addEventListener("configVerificationEvent", (e) => {
+        event = e;
+      });
+
+      await program.methods
+        .verifyConfig(Array.from(expectedHash))
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: verifyMint.publicKey,
+        })
+        .rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+      return event;
+    }
+
+    before(async () => {
+      verifyMint = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeMint(9)
+        .accounts({
+          mint: verifyMint.publicKey,
+          mintAuthority: mintAuthority.publicKey,
+          payer: payer.publicKey,
+        })
+        .signers([verifyMint])
+        .rpc();
+
+      [tokenConfigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), verifyMint.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initializeTokenConfig([payer.publicKey, cosigner.publicKey], 2, 50)
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: verifyMint.publicKey,
+          admin: payer.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Matches the hash of the announced committee", async () => {
+      const expected = configHash([payer.publicKey, cosigner.publicKey], 2, 50, payer.publicKey);
+      const event = await verify(expected);
+
+      expect(event).to.not.be.null;
+      expect(event.matches).to.be.true;
+      expect(Buffer.from(event.actualHash).equals(expected)).to.be.true;
+      expect(event.mint.toString()).to.equal(verifyMint.publicKey.toString());
+    });
+
+    it("Reports a mismatch when the signer order differs", async () => {
+      const reordered = configHash([cosigner.publicKey, payer.publicKey], 2, 50, payer.publicKey);
+      const event = await verify(reordered);
+
+      expect(event).to.not.be.null;
+      expect(event.matches).to.be.false;
+      expect(Buffer.from(event.actualHash).equals(reordered)).to.be.false;
+      expect(Buffer.from(event.expectedHash).equals(reordered)).to.be.true;
+    });
+
+    it("Is callable without signing as an admin or signer", async () => {
+      const tx = await program.methods
+        .verifyConfig(Array.from(Buffer.alloc(32)))
+        .accounts({
+          tokenConfig: tokenConfigPDA,
+          mint: verifyMint.publicKey,
+        })
+        .transaction();
+
+      // Only the fee payer signs; no account in the instruction is a signer
+      const ix = tx.instructions[0];
+      expect(ix.keys.every((k) => !k.isSigner)).to.be.true;
+    });
+  });
 });
@@ -16,6 +16,8 @@ pub enum BotVerdict {
     Human = 0,
     KnownBot = 1,
     Suspicious = 2,
+    /// The module rejected the input as a host bug (e.g. an impossible length)
+    InvalidInput = 3,
 }
 
 impl From<u32> for BotVerdict {
@@ -24,6 +26,7 @@ impl From<u32> for BotVerdict {
             0 => BotVerdict::Human,
             1 => BotVerdict::KnownBot,
             2 => BotVerdict::Suspicious,
+            3 => BotVerdict::InvalidInput,
             _ => BotVerdict::Suspicious, // Default to suspicious for unknown values
         }
     }
@@ -86,6 +89,8 @@ pub struct BotMetrics {
     pub known_bot_count: u64,
     /// Requests classified as suspicious
     pub suspicious_count: u64,
+    /// Requests the module rejected as invalid host input (not attacks)
+    pub invalid_input_count: u64,
     /// Requests blocked
     pub blocked_count: u64,
     /// Challenges issued
@@ -148,6 +153,55 @@ pub struct BotManager {
     metrics: Arc<Mutex<BotMetrics>>,
 }
 
+/// Read the module's length-prefixed build metadata JSON via `get_build_info()`
+///
+/// Returns `Ok(None)` for modules built before the export existed.
+fn read_build_info(engine: &Engine, module: &Module) -> Result<Option<String>> {
+    let mut store = Store::new(engine, ());
+    let instance = Linker::new(engine)
+        .instantiate(&mut store, module)
+        .context("Failed to instantiate Wasm module")?;
+
+    let get_build_info = match instance.get_typed_func::<(), u32>(&mut store, "get_build_info") {
+        Ok(func) => func,
+        Err(_) => return Ok(None),
+    };
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .context("Failed to get Wasm memory")?;
+
+    let ptr = get_build_info
+        .call(&mut store, ())
+        .context("Failed to call get_build_info")?;
+
+    let mut len_bytes = [0u8; 4];
+    memory
+        .read(&store, ptr as usize, &mut len_bytes)
+        .context("Failed to read build info length")?;
+    let len = u32::from_le_bytes(len_bytes);
+
+    let mut json = vec![0u8; len as usize];
+    memory
+        .read(&store, ptr as usize + 4, &mut json)
+        .context("Failed to read build info")?;
+
+    if let Ok(dealloc) = instance.get_typed_func::<(u32, u32), ()>(&mut store, "dealloc") {
+        let _ = dealloc.call(&mut store, (ptr, len + 4));
+    }
+
+    Ok(Some(String::from_utf8(json).context("Build info is not valid UTF-8")?))
+}
+
+/// Log bot-detector build metadata so operators can correlate false positives
+/// with the signature set each node runs
+fn log_build_info(engine: &Engine, module: &Module) {
+    match read_build_info(engine, module) {
+        Ok(Some(info)) => log::info!("Loaded bot detector module: {}", info),
+        Ok(None) => log::info!("Loaded bot detector module (no build info export)"),
+        Err(e) => log::warn!("Failed to read bot detector build info: {:#}", e),
+    }
+}
+
 impl BotManager {
     /// Create new bot manager with Wasm module from file
     pub fn new(wasm_path: impl AsRef<Path>, policy: BotPolicy) -> Result<Self> {
@@ -155,6 +209,8 @@ impl BotManager {
         let module = Module::from_file(&engine, wasm_path)
             .context("Failed to load Wasm module")?;
 
+        log_build_info(&engine, &module);
+
         Ok(Self {
             engine,
             module,
@@ -170,6 +226,8 @@ impl BotManager {
         let module = Module::new(&engine, wasm_bytes)
             .context("Failed to load Wasm module from bytes")?;
 
+        log_build_info(&engine, &module);
+
         Ok(Self {
             engine,
             module,
@@ -305,7 +363,8 @@ impl BotManager {
         let action = match verdict {
             BotVerdict::Human => self.policy.human_action,
             BotVerdict::KnownBot => self.policy.known_bot_action,
-            BotVerdict::Suspicious => self.policy.suspicious_action,
+            // Treated like Suspicious, but counted apart so host bugs stand out
+            BotVerdict::Suspicious | BotVerdict::InvalidInput => self.policy.suspicious_action,
         };
 
         // Track metrics
@@ -317,6 +376,7 @@ impl BotManager {
                 BotVerdict::Human => metrics.human_count += 1,
                 BotVerdict::KnownBot => metrics.known_bot_count += 1,
                 BotVerdict::Suspicious => metrics.suspicious_count += 1,
+                BotVerdict::InvalidInput => metrics.invalid_input_count += 1,
             }
 
             // Track action
@@ -443,6 +503,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verdict_from_module_value() {
+        assert_eq!(BotVerdict::from(2), BotVerdict::Suspicious);
+        assert_eq!(BotVerdict::from(3), BotVerdict::InvalidInput);
+        assert_eq!(BotVerdict::from(4), BotVerdict::Suspicious);
+    }
+
     #[test]
     fn test_policy_actions() {
         let manager = get_test_manager();
@@ -0,0 +1,408 @@
+//! Durable key-value storage for edge functions
+//!
+//! The cache host API is explicitly ephemeral: DragonflyDB may evict entries
+//! under memory pressure at any time. Edge functions implementing counters,
+//! feature flags, or small configuration documents need storage that only
+//! changes when they change it. This module provides that store, backed by
+//! SQLite on the node's local disk, with per-tenant isolation and quotas.
+//!
+//! Every operation is scoped to a tenant (the edge function module ID), so one
+//! module can never read or exhaust another module's keys.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing::info;
+
+/// Maximum size of a single KV value (64KB)
+pub const MAX_KV_VALUE_SIZE: usize = 64 * 1024;
+
+/// Maximum size of a KV key
+pub const MAX_KV_KEY_SIZE: usize = 256;
+
+/// Maximum number of keys returned by a single `list_prefix` call
+pub const MAX_KV_LIST_LIMIT: u32 = 100;
+
+/// Default per-tenant storage quota (keys + values, in bytes)
+pub const DEFAULT_KV_TENANT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Default per-tenant key count quota
+pub const DEFAULT_KV_TENANT_MAX_KEYS: u64 = 1000;
+
+/// KV errors surfaced to edge functions
+///
+/// Each variant maps to a distinct negative return code on the host import
+/// boundary so the SDK can reconstruct a typed error.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum KvError {
+    #[error("KV store unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Key not found")]
+    NotFound,
+
+    #[error("Value size {size} exceeds maximum {max} bytes")]
+    ValueTooLarge { size: usize, max: usize },
+
+    #[error("Tenant quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+}
+
+impl KvError {
+    /// Host import return code for this error
+    pub fn code(&self) -> i32 {
+        match self {
+            KvError::Unavailable(_) => -1,
+            KvError::NotFound => -2,
+            KvError::ValueTooLarge { .. } => -3,
+            KvError::QuotaExceeded(_) => -4,
+            KvError::InvalidKey(_) => -5,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for KvError {
+    fn from(err: rusqlite::Error) -> Self {
+        KvError::Unavailable(err.to_string())
+    }
+}
+
+/// Per-tenant storage limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KvQuota {
+    /// Maximum total bytes (keys + values) per tenant
+    pub max_bytes: u64,
+    /// Maximum number of keys per tenant
+    pub max_keys: u64,
+}
+
+impl Default for KvQuota {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_KV_TENANT_MAX_BYTES,
+            max_keys: DEFAULT_KV_TENANT_MAX_KEYS,
+        }
+    }
+}
+
+/// One page of a prefix listing
+///
+/// Serialized as JSON into the shared buffer by the `kv_list_prefix` host import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KvListPage {
+    /// Keys in lexicographic order
+    pub keys: Vec<String>,
+    /// Opaque cursor for the next page, `None` when the listing is complete
+    pub cursor: Option<String>,
+}
+
+/// Durable per-tenant key-value store for edge functions
+pub struct EdgeKvStore {
+    conn: Mutex<Connection>,
+    quota: KvQuota,
+}
+
+impl EdgeKvStore {
+    /// Create or open the KV database with the default quota
+    pub fn new(db_path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_quota(db_path, KvQuota::default())
+    }
+
+    /// Create or open the KV database with a custom per-tenant quota
+    pub fn with_quota(db_path: impl AsRef<Path>, quota: KvQuota) -> Result<Self> {
+        let conn = Connection::open(db_path.as_ref())
+            .context("Failed to open edge KV database")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS edge_kv (
+                tenant TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value BLOB NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (tenant, key)
+            )",
+            [],
+        )
+        .context("Failed to create edge_kv table")?;
+
+        info!("Opened edge KV database: {:?}", db_path.as_ref());
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            quota,
+        })
+    }
+
+    /// Per-tenant quota applied by this store
+    pub fn quota(&self) -> KvQuota {
+        self.quota
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, KvError> {
+        self.conn
+            .lock()
+            .map_err(|e| KvError::Unavailable(format!("KV lock poisoned: {}", e)))
+    }
+
+    fn validate_key(key: &str) -> Result<(), KvError> {
+        if key.is_empty() {
+            return Err(KvError::InvalidKey("key is empty".to_string()));
+        }
+        if key.len() > MAX_KV_KEY_SIZE {
+            return Err(KvError::InvalidKey(format!(
+                "key length {} exceeds maximum {}",
+                key.len(),
+                MAX_KV_KEY_SIZE
+            )));
+        }
+        if key.chars().any(|c| c.is_control()) {
+            return Err(KvError::InvalidKey("key contains control characters".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Get a value
+    pub fn get(&self, tenant: &str, key: &str) -> Result<Vec<u8>, KvError> {
+        Self::validate_key(key)?;
+        let conn = self.lock()?;
+
+        conn.query_row(
+            "SELECT value FROM edge_kv WHERE tenant = ?1 AND key = ?2",
+            params![tenant, key],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or(KvError::NotFound)
+    }
+
+    /// Insert or replace a value, enforcing the value size limit and tenant quota
+    pub fn put(&self, tenant: &str, key: &str, value: &[u8]) -> Result<(), KvError> {
+        Self::validate_key(key)?;
+        if value.len() > MAX_KV_VALUE_SIZE {
+            return Err(KvError::ValueTooLarge {
+                size: value.len(),
+                max: MAX_KV_VALUE_SIZE,
+            });
+        }
+
+        let conn = self.lock()?;
+
+        let (used_keys, used_bytes): (u64, u64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(CAST(key AS BLOB)) + LENGTH(value)), 0)
+             FROM edge_kv WHERE tenant = ?1",
+            params![tenant],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        // Replacing an existing key frees its current footprint first
+        let existing_len: Option<u64> = conn
+            .query_row(
+                "SELECT LENGTH(value) FROM edge_kv WHERE tenant = ?1 AND key = ?2",
+                params![tenant, key],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let (new_keys, new_bytes) = match existing_len {
+            Some(old_len) => (used_keys, used_bytes - old_len + value.len() as u64),
+            None => (used_keys + 1, used_bytes + (key.len() + value.len()) as u64),
+        };
+
+        if new_keys > self.quota.max_keys {
+            return Err(KvError::QuotaExceeded(format!(
+                "key count {} exceeds tenant limit {}",
+                new_keys, self.quota.max_keys
+            )));
+        }
+        if new_bytes > self.quota.max_bytes {
+            return Err(KvError::QuotaExceeded(format!(
+                "storage {} bytes exceeds tenant limit {}",
+                new_bytes, self.quota.max_bytes
+            )));
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO edge_kv (tenant, key, value, updated_at)
+             VALUES (?1, ?2, ?3, strftime('%s', 'now'))",
+            params![tenant, key, value],
+        )?;
+
+        Ok(())
+    }
+
+    /// Delete a key, returning whether it existed
+    pub fn delete(&self, tenant: &str, key: &str) -> Result<bool, KvError> {
+        Self::validate_key(key)?;
+        let conn = self.lock()?;
+
+        let rows = conn.execute(
+            "DELETE FROM edge_kv WHERE tenant = ?1 AND key = ?2",
+            params![tenant, key],
+        )?;
+
+        Ok(rows > 0)
+    }
+
+    /// List keys starting with `prefix`, one page at a time
+    ///
+    /// `cursor` is the value returned by the previous page; listing resumes
+    /// strictly after it. `limit` is clamped to `1..=MAX_KV_LIST_LIMIT`.
+    pub fn list_prefix(
+        &self,
+        tenant: &str,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<KvListPage, KvError> {
+        if prefix.len() > MAX_KV_KEY_SIZE {
+            return Err(KvError::InvalidKey(format!(
+                "prefix length {} exceeds maximum {}",
+                prefix.len(),
+                MAX_KV_KEY_SIZE
+            )));
+        }
+
+        let limit = limit.clamp(1, MAX_KV_LIST_LIMIT);
+        let conn = self.lock()?;
+
+        // Fetch one extra row to learn whether another page exists
+        let mut stmt = conn.prepare(
+            "SELECT key FROM edge_kv
+             WHERE tenant = ?1
+               AND substr(key, 1, length(?2)) = ?2
+               AND key > ?3
+             ORDER BY key
+             LIMIT ?4",
+        )?;
+
+        let mut keys = stmt
+            .query_map(
+                params![tenant, prefix, cursor.unwrap_or(""), limit + 1],
+                |row| row.get::<_, String>(0),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let cursor = if keys.len() > limit as usize {
+            keys.truncate(limit as usize);
+            keys.last().cloned()
+        } else {
+            None
+        };
+
+        Ok(KvListPage { keys, cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(quota: KvQuota) -> (tempfile::TempDir, EdgeKvStore) {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = EdgeKvStore::with_quota(temp_dir.path().join("kv.db"), quota)
+            .expect("Failed to create KV store");
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_put_get_delete() {
+        let (_dir, store) = temp_store(KvQuota::default());
+
+        store.put("fn-a", "counter", b"42").expect("put should succeed");
+        assert_eq!(store.get("fn-a", "counter").unwrap(), b"42");
+
+        assert!(store.delete("fn-a", "counter").unwrap());
+        assert!(!store.delete("fn-a", "counter").unwrap());
+        assert_eq!(store.get("fn-a", "counter"), Err(KvError::NotFound));
+    }
+
+    #[test]
+    fn test_tenant_isolation() {
+        let (_dir, store) = temp_store(KvQuota::default());
+
+        store.put("fn-a", "flags", b"{}").unwrap();
+        assert_eq!(store.get("fn-b", "flags"), Err(KvError::NotFound));
+    }
+
+    #[test]
+    fn test_value_too_large() {
+        let (_dir, store) = temp_store(KvQuota::default());
+
+        let value = vec![0u8; MAX_KV_VALUE_SIZE + 1];
+        let err = store.put("fn-a", "big", &value).unwrap_err();
+        assert!(matches!(err, KvError::ValueTooLarge { .. }));
+        assert_eq!(err.code(), -3);
+    }
+
+    #[test]
+    fn test_quota_exceeded() {
+        let (_dir, store) = temp_store(KvQuota {
+            max_bytes: 64,
+            max_keys: 2,
+        });
+
+        store.put("fn-a", "k1", b"v").unwrap();
+        store.put("fn-a", "k2", b"v").unwrap();
+        let err = store.put("fn-a", "k3", b"v").unwrap_err();
+        assert!(matches!(err, KvError::QuotaExceeded(_)));
+        assert_eq!(err.code(), -4);
+
+        // Overwriting an existing key does not count as a new key
+        store.put("fn-a", "k1", b"updated").unwrap();
+
+        let err = store.put("fn-a", "k2", &[0u8; 64]).unwrap_err();
+        assert!(matches!(err, KvError::QuotaExceeded(_)));
+
+        // Other tenants have their own quota
+        store.put("fn-b", "k3", b"v").unwrap();
+    }
+
+    #[test]
+    fn test_list_prefix_pagination() {
+        let (_dir, store) = temp_store(KvQuota::default());
+
+        for i in 0..5 {
+            store.put("fn-a", &format!("user:{}", i), b"x").unwrap();
+        }
+        store.put("fn-a", "other", b"x").unwrap();
+
+        let page1 = store.list_prefix("fn-a", "user:", None, 2).unwrap();
+        assert_eq!(page1.keys, vec!["user:0", "user:1"]);
+        assert_eq!(page1.cursor.as_deref(), Some("user:1"));
+
+        let page2 = store
+            .list_prefix("fn-a", "user:", page1.cursor.as_deref(), 2)
+            .unwrap();
+        assert_eq!(page2.keys, vec!["user:2", "user:3"]);
+
+        let page3 = store
+            .list_prefix("fn-a", "user:", page2.cursor.as_deref(), 2)
+            .unwrap();
+        assert_eq!(page3.keys, vec!["user:4"]);
+        assert_eq!(page3.cursor, None);
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let (_dir, store) = temp_store(KvQuota::default());
+
+        assert!(matches!(store.put("fn-a", "", b"v"), Err(KvError::InvalidKey(_))));
+        assert!(matches!(store.get("fn-a", "a\nb"), Err(KvError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let db_path = temp_dir.path().join("kv.db");
+
+        EdgeKvStore::new(&db_path).unwrap().put("fn-a", "k", b"v").unwrap();
+        let reopened = EdgeKvStore::new(&db_path).unwrap();
+        assert_eq!(reopened.get("fn-a", "k").unwrap(), b"v");
+    }
+}
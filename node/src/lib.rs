@@ -37,6 +37,7 @@ pub mod blocklist_persistence;
 
 // Sprint 13: Wasm Edge Functions Runtime & WAF Migration
 pub mod wasm_runtime;
+pub mod edge_kv;
 
 // Sprint 16: Route-based Dispatch for Wasm Modules
 pub mod route_config;
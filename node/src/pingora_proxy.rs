@@ -4,13 +4,15 @@ use crate::challenge::{
     ChallengeManager, ChallengeType,
     CHALLENGE_TOKEN_COOKIE, CHALLENGE_TOKEN_HEADER,
 };
+use crate::dns::geo_resolver::GeoIpDatabase;
+use crate::edge_kv::EdgeKvStore;
 use crate::enhanced_bot_detection::EnhancedBotDetector;
 use crate::ip_extraction::{extract_client_ip, IpExtractionConfig};
 use crate::module_dispatcher::ModuleDispatcher;
 use crate::route_config::RouteConfig;
-use crate::tls_fingerprint::TlsFingerprint;
+use crate::tls_fingerprint::{TlsFingerprint, TlsVersion};
 use crate::tls_intercept::FingerprintStore;
-use crate::wasm_runtime::{WasmRuntime, WasmExecutionContext};
+use crate::wasm_runtime::{ClientInfo, ResponseStreamState, WasmRuntime, WasmExecutionContext};
 use async_trait::async_trait;
 use hyper::body::Bytes;
 use pingora::prelude::*;
@@ -32,6 +34,18 @@ pub struct ProxyContext {
     pub challenge_verified: bool, // Sprint 20: Whether client has valid challenge token
 }
 
+/// Version name reported to edge functions, e.g. "TLSv1.3"
+fn tls_version_name(version: TlsVersion) -> String {
+    match version {
+        TlsVersion::Ssl30 => "SSLv3".to_string(),
+        TlsVersion::Tls10 => "TLSv1".to_string(),
+        TlsVersion::Tls11 => "TLSv1.1".to_string(),
+        TlsVersion::Tls12 => "TLSv1.2".to_string(),
+        TlsVersion::Tls13 => "TLSv1.3".to_string(),
+        TlsVersion::Unknown(v) => format!("0x{:04x}", v),
+    }
+}
+
 /// AEGIS Pingora-based reverse proxy
 pub struct AegisProxy {
     /// Origin server to proxy requests to
@@ -60,6 +74,8 @@ pub struct AegisProxy {
     pub enhanced_bot_detector: Option<Arc<EnhancedBotDetector>>,
     /// Sprint 20: JavaScript Challenge Manager
     pub challenge_manager: Option<Arc<ChallengeManager>>,
+    /// GeoIP database for the client country/region given to edge functions
+    pub geoip: Option<Arc<GeoIpDatabase>>,
 }
 
 impl AegisProxy {
@@ -132,6 +148,7 @@ impl AegisProxy {
             fingerprint_store: None,
             enhanced_bot_detector: None,
             challenge_manager: None,
+            geoip: None,
         }
     }
 
@@ -189,6 +206,39 @@ impl AegisProxy {
             fingerprint_store: None,
             enhanced_bot_detector: None,
             challenge_manager: None,
+            geoip: None,
+        }
+    }
+
+    /// Set the GeoIP database used for edge function client metadata
+    pub fn with_geoip(mut self, geoip: Arc<GeoIpDatabase>) -> Self {
+        self.geoip = Some(geoip);
+        self
+    }
+
+    /// Client metadata exposed to edge functions via get_client_info
+    fn client_info(&self, ip: &str, tls_fingerprint: Option<&TlsFingerprint>) -> ClientInfo {
+        // Direct connection IPs carry the client port
+        let addr = ip
+            .parse::<std::net::IpAddr>()
+            .ok()
+            .or_else(|| ip.parse::<std::net::SocketAddr>().ok().map(|a| a.ip()));
+        let location = self
+            .geoip
+            .as_ref()
+            .zip(addr)
+            .and_then(|(db, addr)| db.lookup(addr).ok());
+        let (country, region) = match location {
+            Some(location) => (location.country, location.region),
+            None => (None, None),
+        };
+        ClientInfo {
+            ip: addr.map(|addr| addr.to_string()),
+            country,
+            region,
+            asn: None,
+            tls_version: tls_fingerprint.map(|f| tls_version_name(f.tls_version)),
+            ja3: tls_fingerprint.map(|f| f.ja3.clone()),
         }
     }
 
@@ -632,6 +682,8 @@ impl ProxyHttp for AegisProxy {
                     response_headers: Vec::new(),
                     response_body: Vec::new(),
                     terminate_early: false,
+                    response_stream: ResponseStreamState::NotStarted,
+                    client_info: Some(self.client_info(ip, ctx.tls_fingerprint.as_ref())),
                 };
 
                 // Execute pipeline
@@ -722,6 +774,8 @@ impl ProxyHttp for AegisProxy {
                 response_headers: Vec::new(),
                 response_body: Vec::new(),
                 terminate_early: false,
+                response_stream: ResponseStreamState::NotStarted,
+                client_info: None,
             };
 
             // Sprint 15.5: Execute WAF through generic Wasm dispatch
@@ -743,13 +797,21 @@ impl ProxyHttp for AegisProxy {
                         ctx.waf_blocked = true;
                         log::error!("WAF BLOCKED: {} {} - {} rule(s) triggered", method, uri, waf_result.matches.len());
 
-                        // Send 403 Forbidden response
-                        let mut header = pingora::http::ResponseHeader::build(403, Some(3))?;
+                        // Use the module's suggested response (403 Forbidden if none)
+                        let (status, body) = match &waf_result.block_response {
+                            Some(response) => (response.status_code, response.message.clone()),
+                            None => (403, "403 Forbidden - Request blocked by WAF".to_string()),
+                        };
+                        let mut header = pingora::http::ResponseHeader::build(status, Some(4))?;
                         header.insert_header("Content-Type", "text/plain")?;
+                        if let Some(response) = &waf_result.block_response {
+                            header.insert_header("X-Aegis-Block-Reason", response.block_reason_code.as_str())?;
+                            if let Some(retry_after) = response.retry_after_secs {
+                                header.insert_header("Retry-After", retry_after.to_string())?;
+                            }
+                        }
                         session.write_response_header(Box::new(header), true).await?;
-                        session
-                            .write_response_body(Some("403 Forbidden - Request blocked by WAF".into()), true)
-                            .await?;
+                        session.write_response_body(Some(body.into()), true).await?;
 
                         // Return true to skip upstream
                         return Ok(true);
@@ -1021,6 +1083,15 @@ pub struct ProxyConfig {
     pub cache_url: Option<String>,
     pub cache_ttl: Option<u64>,
     pub enable_caching: Option<bool>,
+    /// SQLite file backing edge function KV storage (kv_* host imports
+    /// return the "unavailable" code when unset). Defaults to `edge_kv.db`;
+    /// an empty string in the config file disables it.
+    #[serde(default = "default_edge_kv_path")]
+    pub edge_kv_path: Option<String>,
+}
+
+fn default_edge_kv_path() -> Option<String> {
+    Some("edge_kv.db".to_string())
 }
 
 impl Default for ProxyConfig {
@@ -1035,6 +1106,23 @@ impl Default for ProxyConfig {
             cache_url: Some("redis://127.0.0.1:6379".to_string()),
             cache_ttl: Some(60),
             enable_caching: Some(true),
+            edge_kv_path: default_edge_kv_path(),
+        }
+    }
+}
+
+/// Build the node's Wasm runtime, with the durable KV store attached when configured
+pub fn build_wasm_runtime(config: &ProxyConfig) -> anyhow::Result<WasmRuntime> {
+    let runtime = WasmRuntime::new()?;
+    match config.edge_kv_path.as_deref().filter(|path| !path.is_empty()) {
+        Some(path) => {
+            let kv_store = EdgeKvStore::new(path)?;
+            log::info!("Edge KV storage: {}", path);
+            Ok(runtime.with_kv_store(Arc::new(kv_store)))
+        }
+        None => {
+            log::info!("Edge KV storage disabled");
+            Ok(runtime)
         }
     }
 }
@@ -1077,14 +1165,23 @@ pub fn run_proxy(config: ProxyConfig) -> Result<()> {
         None
     };
 
-    // Sprint 15.5: Create proxy instance with Wasm runtime disabled by default
-    // To enable WAF via Wasm, load a WAF module and pass the runtime + module ID
+    let wasm_runtime = match build_wasm_runtime(&config) {
+        Ok(runtime) => Some(Arc::new(runtime)),
+        Err(e) => {
+            log::warn!("Failed to initialize Wasm runtime: {}", e);
+            log::warn!("Wasm modules disabled");
+            None
+        }
+    };
+
+    // Sprint 15.5: The WAF stays disabled until a WAF module is loaded
+    // into the runtime and its module ID passed here
     let proxy = AegisProxy::new_with_wasm(
         config.origin.clone(),
         cache_client,
         config.cache_ttl.unwrap_or(60),
         config.enable_caching.unwrap_or(false),
-        None, // wasm_runtime (can be initialized separately)
+        wasm_runtime,
         None, // waf_module_id (can be set after loading WAF module)
     );
 
@@ -1143,4 +1240,93 @@ mod tests {
         let proxy = AegisProxy::new("http://example.com:8080".to_string());
         assert_eq!(proxy.origin_addr, "example.com:8080");
     }
+
+    /// Load a signed edge function that stores `visits = 1` and returns kv_put's code
+    fn load_kv_writer(runtime: &WasmRuntime) {
+        use crate::wasm_runtime::WasmModuleType;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "kv_put" (func $kv_put (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 16) "visits")
+                (data (i32.const 32) "1")
+                (func (export "run") (result i32)
+                    (call $kv_put (i32.const 16) (i32.const 6) (i32.const 32) (i32.const 1))
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        runtime
+            .load_module_from_bytes_with_signature(
+                "kv-writer",
+                &wasm,
+                WasmModuleType::EdgeFunction,
+                None,
+                Some(hex::encode(signing_key.sign(&wasm).to_bytes())),
+                Some(hex::encode(signing_key.verifying_key().to_bytes())),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_built_runtime_serves_edge_kv() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("edge_kv.db");
+        let config = ProxyConfig {
+            edge_kv_path: Some(db_path.to_string_lossy().into_owned()),
+            ..ProxyConfig::default()
+        };
+
+        let runtime = build_wasm_runtime(&config).unwrap();
+        load_kv_writer(&runtime);
+        runtime.execute_edge_function("kv-writer", "run", None).unwrap();
+
+        // The write landed in the configured database, under the module's tenant
+        assert_eq!(EdgeKvStore::new(&db_path).unwrap().get("kv-writer", "visits").unwrap(), b"1");
+    }
+
+    #[test]
+    fn test_built_runtime_without_kv_path() {
+        let config = ProxyConfig {
+            edge_kv_path: None,
+            ..ProxyConfig::default()
+        };
+
+        let runtime = build_wasm_runtime(&config).unwrap();
+        load_kv_writer(&runtime);
+        let err = runtime.execute_edge_function("kv-writer", "run", None).unwrap_err();
+        assert!(err.to_string().contains("error code: -1"), "{}", err);
+    }
+
+    #[test]
+    fn test_default_config_enables_edge_kv() {
+        assert_eq!(ProxyConfig::default().edge_kv_path.as_deref(), Some("edge_kv.db"));
+
+        // Config files written before the setting existed get it too
+        let config: ProxyConfig = toml::from_str(r#"
+            http_addr = "0.0.0.0:8080"
+            origin = "http://127.0.0.1:3000"
+        "#).unwrap();
+        assert_eq!(config.edge_kv_path.as_deref(), Some("edge_kv.db"));
+    }
+
+    #[test]
+    fn test_empty_kv_path_disables_edge_kv() {
+        let config: ProxyConfig = toml::from_str(r#"
+            http_addr = "0.0.0.0:8080"
+            origin = "http://127.0.0.1:3000"
+            edge_kv_path = ""
+        "#).unwrap();
+        assert_eq!(config.edge_kv_path.as_deref(), Some(""));
+
+        let runtime = build_wasm_runtime(&config).unwrap();
+        load_kv_writer(&runtime);
+        let err = runtime.execute_edge_function("kv-writer", "run", None).unwrap_err();
+        assert!(err.to_string().contains("error code: -1"), "{}", err);
+    }
 }
@@ -11,6 +11,20 @@
 //! - Controlled outbound HTTP requests
 //! - Enhanced resource governance
 //!
+//! Durable KV storage (kv_get/kv_put/kv_delete/kv_list_prefix)
+//! - Backed by the node's persistent store, unlike the ephemeral cache
+//! - 64KB value limit and per-tenant quotas with distinct error codes
+//!
+//! Invocation metadata (get_invocation_context)
+//! - Function name, deployment id, invocation id, cold start flag, node region
+//!
+//! Quota usage (get_usage)
+//! - Tenant invocation and egress counters for the billing period, so
+//!   functions can degrade before hitting their limits
+//!
+//! Client metadata (get_client_info)
+//! - Client IP, GeoIP country/region, ASN and TLS details for personalization
+//!
 //! Sprint Y4: Security Hardening
 //! - Calibrated fuel limits based on time constraints
 //! - Epoch-based timeout enforcement
@@ -41,9 +55,9 @@ use anyhow::{Context, Result};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use thiserror::Error;
@@ -51,6 +65,7 @@ use tracing::{debug, info, warn, error};
 use wasmtime::*;
 
 use crate::cache::CacheClient;
+use crate::edge_kv::{EdgeKvStore, KvError, MAX_KV_KEY_SIZE, MAX_KV_VALUE_SIZE};
 
 /// Custom error type for Wasm runtime operations
 #[derive(Debug, Error)]
@@ -194,6 +209,10 @@ const MAX_HTTP_REQUEST_BODY_SIZE: usize = 1024 * 1024;
 /// A malicious Wasm module could return a huge length value causing unbounded allocation
 const MAX_WAF_RESULT_SIZE: usize = 10 * 1024 * 1024; // 10MB limit
 
+/// Largest non-UTF-8 request body passed to the WAF as raw bytes for
+/// charset transcoding (matches the module's transcode bound)
+const MAX_WAF_RAW_BODY_SIZE: usize = 64 * 1024;
+
 /// Security fix (X1.1): Maximum size for edge function result data
 const MAX_EDGE_FUNCTION_RESULT_SIZE: usize = 50 * 1024 * 1024; // 50MB limit
 
@@ -426,6 +445,56 @@ fn validate_url_ssrf(url: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Read a bounded byte range from guest memory
+///
+/// Returns None (after logging) on null pointers, oversized lengths, or
+/// out-of-bounds reads.
+fn read_guest_bytes(
+    caller: &mut Caller<'_, EdgeFunctionStoreData>,
+    ptr: u32,
+    len: u32,
+    max_size: usize,
+    context: &str,
+) -> Option<Vec<u8>> {
+    if !is_valid_wasm_ptr(ptr, context) {
+        return None;
+    }
+    let len = safe_u32_to_usize(len, max_size, context)?;
+
+    let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+        Some(m) => m,
+        None => {
+            error!("Failed to get Wasm memory");
+            return None;
+        }
+    };
+
+    let mut buffer = vec![0u8; len];
+    if memory.read(&*caller, ptr as usize, &mut buffer).is_err() {
+        error!("Failed to read {} from Wasm memory", context);
+        return None;
+    }
+    Some(buffer)
+}
+
+/// Read a bounded UTF-8 string from guest memory
+fn read_guest_string(
+    caller: &mut Caller<'_, EdgeFunctionStoreData>,
+    ptr: u32,
+    len: u32,
+    max_size: usize,
+    context: &str,
+) -> Option<String> {
+    let bytes = read_guest_bytes(caller, ptr, len, max_size, context)?;
+    match String::from_utf8(bytes) {
+        Ok(s) => Some(s),
+        Err(_) => {
+            error!("Invalid UTF-8 in {}", context);
+            None
+        }
+    }
+}
+
 /// Y8.4: Generate namespaced cache key for module isolation
 ///
 /// Prefixes the user-provided key with the module ID to prevent
@@ -533,8 +602,27 @@ pub struct WasmExecutionContext {
     pub response_body: Vec<u8>,
     /// Sprint 15: Flag to indicate if request should terminate early
     pub terminate_early: bool,
+    /// Progress of a response emitted via response_start/response_write/response_end
+    pub response_stream: ResponseStreamState,
+    /// What the node knows about the client, exposed via get_client_info
+    pub client_info: Option<ClientInfo>,
+}
+
+/// Call-order state of a streamed edge function response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseStreamState {
+    #[default]
+    NotStarted,
+    Streaming,
+    Ended,
 }
 
+/// Stream host function error codes (shared with the edge function SDK)
+const STREAM_ERR_FAILED: i32 = -1;
+const STREAM_ERR_NOT_STARTED: i32 = -2;
+const STREAM_ERR_ENDED: i32 = -3;
+const STREAM_ERR_ALREADY_STARTED: i32 = -5;
+
 impl Default for WasmExecutionContext {
     fn default() -> Self {
         Self {
@@ -546,10 +634,71 @@ impl Default for WasmExecutionContext {
             response_headers: Vec::new(),
             response_body: Vec::new(),
             terminate_early: false,
+            response_stream: ResponseStreamState::NotStarted,
+            client_info: None,
         }
     }
 }
 
+/// Metadata about one edge function invocation, exposed via get_invocation_context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvocationContext {
+    /// Exported function being invoked
+    pub function_name: String,
+    /// IPFS CID of the deployed module, or its content hash when loaded locally
+    pub deployment_id: String,
+    /// Random per-invocation identifier (hex) for log correlation
+    pub invocation_id: String,
+    /// True for the first invocation of this deployment on this node
+    pub cold_start: bool,
+    /// Region of this node, if configured
+    pub node_region: Option<String>,
+}
+
+/// Tenant quota usage for the current billing period, exposed via get_usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantUsage {
+    pub invocations_this_period: u64,
+    /// None = unlimited
+    pub invocation_limit: Option<u64>,
+    pub egress_bytes: u64,
+    /// None = unlimited
+    pub egress_limit: Option<u64>,
+    /// When the period resets (unix seconds)
+    pub period_ends_at: u64,
+}
+
+/// Client metadata for one request, exposed via get_client_info
+///
+/// Fields the node couldn't determine (no GeoIP database, plain HTTP) are None.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientInfo {
+    /// Client IP after trusted proxy header extraction
+    pub ip: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    /// ISO 3166-2 subdivision code (without the country prefix)
+    pub region: Option<String>,
+    /// Autonomous system number of the client IP
+    pub asn: Option<u32>,
+    /// Negotiated TLS version, e.g. "TLSv1.3"
+    pub tls_version: Option<String>,
+    /// JA3 fingerprint of the ClientHello
+    pub ja3: Option<String>,
+}
+
+/// Source of per-tenant quota usage (tenant = module_id), typically backed by
+/// the node's metering
+pub trait UsageSource: Send + Sync {
+    /// Current usage, or None if the tenant isn't metered
+    fn usage(&self, tenant: &str) -> Option<TenantUsage>;
+}
+
+/// Generate a random 128-bit invocation id (hex-encoded)
+fn generate_invocation_id() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
+
 /// Sprint 14: Edge function store data for host functions
 /// This is passed to the Wasmtime store to enable host functions to access resources
 pub struct EdgeFunctionStoreData {
@@ -563,6 +712,14 @@ pub struct EdgeFunctionStoreData {
     pub execution_context: Arc<RwLock<WasmExecutionContext>>,
     /// Y8.4: Module ID for cache key namespacing (SSRF and cache isolation)
     pub module_id: String,
+    /// Durable KV store (tenant = module_id)
+    pub kv: Option<Arc<EdgeKvStore>>,
+    /// Headers of the most recent http_get response, for cache policy decisions
+    pub last_http_response_headers: Vec<(String, String)>,
+    /// Metadata about this invocation
+    pub invocation: InvocationContext,
+    /// Quota usage reported by get_usage (tenant = module_id)
+    pub usage_source: Option<Arc<dyn UsageSource>>,
 }
 
 /// Sprint 15: Result from edge function execution with request/response context
@@ -575,11 +732,49 @@ pub struct EdgeFunctionResult {
 }
 
 /// WAF analysis result returned from Wasm module
+///
+/// Modules only add fields between schema versions (see the module's
+/// `result_schema` export), so unknown fields are ignored here.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WafResult {
+    /// Result schema version; modules that predate versioning wrote 1
+    #[serde(default = "legacy_waf_schema_version")]
+    pub schema_version: u32,
     pub blocked: bool,
     pub matches: Vec<WafMatch>,
     pub execution_time_us: u64,
+    /// The module found a structurally valid challenge-clearance cookie whose
+    /// HMAC the host must still verify
+    #[serde(default)]
+    pub needs_hmac_check: bool,
+    /// Key id to verify the clearance cookie with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac_key_id: Option<String>,
+    /// Module-suggested client response when blocked; the host may override it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_response: Option<WafBlockResponse>,
+    /// Decoded body bytes per body byte; above the zone's limit the module
+    /// stopped decoding and flagged the request as evasion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_expansion_ratio: Option<f64>,
+}
+
+fn legacy_waf_schema_version() -> u32 {
+    1
+}
+
+/// Suggested response for a blocked request (status, stable reason code,
+/// redacted message)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WafBlockResponse {
+    pub status_code: u16,
+    /// `<category>:<rule_id>` of the top-severity match, e.g. `sqli:942100`
+    pub block_reason_code: String,
+    /// Client-facing message; never contains matched values
+    pub message: String,
+    /// Retry-After seconds (429 responses only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -609,6 +804,14 @@ pub struct WasmRuntime {
     epoch_incrementer_handle: Option<std::thread::JoinHandle<()>>,
     /// Y4.4: Flag to signal epoch incrementer to stop
     epoch_incrementer_stop: Arc<std::sync::atomic::AtomicBool>,
+    /// Durable KV store exposed to edge functions via kv_* host imports
+    kv_store: Option<Arc<EdgeKvStore>>,
+    /// Node region reported to edge functions in their invocation context
+    node_region: Option<String>,
+    /// Deployments (by deployment id) invoked at least once, for cold start detection
+    warm_deployments: Arc<Mutex<HashSet<String>>>,
+    /// Tenant usage exposed to edge functions via get_usage
+    usage_source: Option<Arc<dyn UsageSource>>,
 }
 
 impl WasmRuntime {
@@ -695,9 +898,57 @@ impl WasmRuntime {
             modules: Arc::new(RwLock::new(HashMap::new())),
             epoch_incrementer_handle,
             epoch_incrementer_stop: stop_flag,
+            kv_store: None,
+            node_region: None,
+            warm_deployments: Arc::new(Mutex::new(HashSet::new())),
+            usage_source: None,
         })
     }
 
+    /// Attach a durable KV store for edge functions
+    ///
+    /// Without a store, kv_* host imports return the "unavailable" error code.
+    pub fn with_kv_store(mut self, kv_store: Arc<EdgeKvStore>) -> Self {
+        self.kv_store = Some(kv_store);
+        self
+    }
+
+    /// Attach a tenant usage source for edge functions
+    ///
+    /// Without one, get_usage returns -1 and functions run unrestricted.
+    pub fn with_usage_source(mut self, usage_source: Arc<dyn UsageSource>) -> Self {
+        self.usage_source = Some(usage_source);
+        self
+    }
+
+    /// Set the node region reported in edge function invocation contexts
+    pub fn with_node_region(mut self, region: impl Into<String>) -> Self {
+        self.node_region = Some(region.into());
+        self
+    }
+
+    /// Build the invocation context for a call, marking the deployment warm
+    fn next_invocation_context(&self, metadata: &WasmModuleMetadata, function_name: &str) -> InvocationContext {
+        let deployment_id = metadata
+            .ipfs_cid
+            .clone()
+            .unwrap_or_else(|| metadata.content_hash.clone());
+
+        // A poisoned lock only loses cold start accuracy, never the invocation
+        let cold_start = match self.warm_deployments.lock() {
+            Ok(mut warm) => warm.insert(deployment_id.clone()),
+            Err(poisoned) => poisoned.into_inner().insert(deployment_id.clone()),
+        };
+
+        InvocationContext {
+            function_name: function_name.to_string(),
+            deployment_id,
+            invocation_id: generate_invocation_id(),
+            cold_start,
+            node_region: self.node_region.clone(),
+        }
+    }
+
     /// Load Wasm module from file
     pub fn load_module(
         &self,
@@ -1029,12 +1280,19 @@ impl WasmRuntime {
             .context("WAF module missing alloc function")?;
 
         // Serialize request data to JSON
-        let request_json = serde_json::json!({
+        let mut request_json = serde_json::json!({
             "method": context.request_method,
             "uri": context.request_uri,
             "headers": context.request_headers,
             "body": String::from_utf8_lossy(&context.request_body),
         });
+        // Lossy decoding mangles UTF-16 / Latin-1 bodies; pass the bytes so
+        // the module can transcode them from the declared charset
+        if context.request_body.len() <= MAX_WAF_RAW_BODY_SIZE
+            && std::str::from_utf8(&context.request_body).is_err()
+        {
+            request_json["body_raw"] = serde_json::json!(context.request_body);
+        }
         let request_str = request_json.to_string();
 
         // Allocate memory in Wasm and copy request data
@@ -1141,6 +1399,11 @@ impl WasmRuntime {
         }
 
         let start = Instant::now();
+        let invocation = self.next_invocation_context(metadata, function_name);
+        debug!(
+            "Edge function invocation {} ({}::{}, cold_start={})",
+            invocation.invocation_id, module_id, function_name, invocation.cold_start
+        );
 
         // Create store data with cache, HTTP client, and execution context
         // Y8.4: Include module_id for cache key namespacing
@@ -1152,6 +1415,10 @@ impl WasmRuntime {
             shared_buffer: Arc::new(RwLock::new(Vec::new())),
             execution_context: Arc::new(RwLock::new(context)),
             module_id: module_id.to_string(),
+            kv: self.kv_store.clone(),
+            last_http_response_headers: Vec::new(),
+            invocation,
+            usage_source: self.usage_source.clone(),
         };
 
         // Create store with resource limits
@@ -1245,6 +1512,80 @@ impl WasmRuntime {
             }
         })?;
 
+        // Host function: get_invocation_context() -> i32
+        // Returns the length of the invocation metadata JSON (stored in shared buffer), or -1 on error
+        linker.func_wrap(
+            "env",
+            "get_invocation_context",
+            |mut caller: Caller<EdgeFunctionStoreData>| -> i32 {
+                let data = caller.data_mut();
+                match serde_json::to_vec(&data.invocation) {
+                    Ok(json_bytes) => {
+                        let len = json_bytes.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = json_bytes;
+                        len
+                    }
+                    Err(e) => {
+                        error!("Failed to serialize invocation context: {}", e);
+                        -1
+                    }
+                }
+            },
+        )?;
+
+        // Host function: get_usage() -> i32
+        // Returns the length of the tenant usage JSON (stored in shared buffer), or -1 if unavailable
+        linker.func_wrap(
+            "env",
+            "get_usage",
+            |mut caller: Caller<EdgeFunctionStoreData>| -> i32 {
+                let data = caller.data_mut();
+                let usage = match &data.usage_source {
+                    Some(source) => source.usage(&data.module_id),
+                    None => None,
+                };
+                let Some(usage) = usage else {
+                    return -1;
+                };
+                match serde_json::to_vec(&usage) {
+                    Ok(json_bytes) => {
+                        let len = json_bytes.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = json_bytes;
+                        len
+                    }
+                    Err(e) => {
+                        error!("Failed to serialize tenant usage: {}", e);
+                        -1
+                    }
+                }
+            },
+        )?;
+
+        // Host function: get_client_info() -> i32
+        // Returns the length of the client metadata JSON (stored in shared buffer), or -1 if unavailable
+        linker.func_wrap(
+            "env",
+            "get_client_info",
+            |mut caller: Caller<EdgeFunctionStoreData>| -> i32 {
+                let data = caller.data_mut();
+                let client_info = try_read_lock!(data.execution_context, -1).client_info.clone();
+                let Some(client_info) = client_info else {
+                    return -1;
+                };
+                match serde_json::to_vec(&client_info) {
+                    Ok(json_bytes) => {
+                        let len = json_bytes.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = json_bytes;
+                        len
+                    }
+                    Err(e) => {
+                        error!("Failed to serialize client info: {}", e);
+                        -1
+                    }
+                }
+            },
+        )?;
+
         // Host function: cache_get(key_ptr, key_len) -> i32
         // Returns the length of the value (stored in shared buffer), or -1 if not found
         linker.func_wrap(
@@ -1489,6 +1830,16 @@ impl WasmRuntime {
                         let status = response.status();
                         debug!("HTTP GET response status: {}", status);
 
+                        // Keep response headers for http_get_response_headers()
+                        // (non-UTF-8 values are dropped rather than mangled)
+                        caller.data_mut().last_http_response_headers = response
+                            .headers()
+                            .iter()
+                            .filter_map(|(name, value)| {
+                                value.to_str().ok().map(|v| (name.as_str().to_string(), v.to_string()))
+                            })
+                            .collect();
+
                         let body_result = tokio::task::block_in_place(|| {
                             tokio::runtime::Handle::current().block_on(async {
                                 response.bytes().await
@@ -1520,6 +1871,28 @@ impl WasmRuntime {
             },
         )?;
 
+        // Host function: http_get_response_headers() -> i32
+        // Returns the length of a JSON array of [name, value] pairs from the most recent
+        // http_get response (stored in shared buffer), or -1 on error
+        linker.func_wrap(
+            "env",
+            "http_get_response_headers",
+            |mut caller: Caller<EdgeFunctionStoreData>| -> i32 {
+                let data = caller.data_mut();
+                match serde_json::to_vec(&data.last_http_response_headers) {
+                    Ok(json_bytes) => {
+                        let len = json_bytes.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = json_bytes;
+                        len
+                    }
+                    Err(e) => {
+                        error!("Failed to serialize HTTP response headers: {}", e);
+                        -1
+                    }
+                }
+            },
+        )?;
+
         // ============================================
         // Security fix: HTTP POST/PUT/DELETE support with body size limits
         // ============================================
@@ -2304,6 +2677,124 @@ impl WasmRuntime {
             },
         )?;
 
+        // Host function: response_start(status, headers_ptr, headers_len) -> i32
+        // Starts a streamed response; headers are a JSON array of [name, value] pairs
+        // Returns 0 on success, or a negative stream error code
+        linker.func_wrap(
+            "env",
+            "response_start",
+            |mut caller: Caller<EdgeFunctionStoreData>, status: u32, headers_ptr: u32, headers_len: u32| -> i32 {
+                if !(100..=599).contains(&status) {
+                    error!("Invalid HTTP status code for streamed response: {}", status);
+                    return STREAM_ERR_FAILED;
+                }
+
+                let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+                    Some(m) => m,
+                    None => {
+                        error!("Failed to get Wasm memory");
+                        return STREAM_ERR_FAILED;
+                    }
+                };
+
+                let mut headers_bytes = vec![0u8; headers_len as usize];
+                if memory.read(&caller, headers_ptr as usize, &mut headers_bytes).is_err() {
+                    error!("Failed to read stream headers from Wasm memory");
+                    return STREAM_ERR_FAILED;
+                }
+
+                let headers: Vec<(String, String)> = match serde_json::from_slice(&headers_bytes) {
+                    Ok(h) => h,
+                    Err(_) => {
+                        error!("Invalid stream headers JSON");
+                        return STREAM_ERR_FAILED;
+                    }
+                };
+
+                if let Some((name, _)) = headers
+                    .iter()
+                    .find(|(name, value)| !is_header_name_safe(name) || !is_header_value_safe(value))
+                {
+                    error!("Invalid stream header (RFC 7230 violation or CRLF injection): {}", name);
+                    return STREAM_ERR_FAILED;
+                }
+
+                let data = caller.data_mut();
+                let mut context = try_write_lock!(data.execution_context, STREAM_ERR_FAILED);
+                if context.response_stream != ResponseStreamState::NotStarted {
+                    return STREAM_ERR_ALREADY_STARTED;
+                }
+                context.response_stream = ResponseStreamState::Streaming;
+                context.response_status = Some(status as u16);
+                context.response_headers = headers;
+                context.response_body.clear();
+
+                debug!("Streamed response started with status {}", status);
+                0
+            },
+        )?;
+
+        // Host function: response_write(chunk_ptr, chunk_len) -> i32
+        // Appends a chunk to a streamed response
+        // Returns 0 on success, or a negative stream error code. Chunks are
+        // buffered into the response body, so this host never reports
+        // would-block (-4).
+        linker.func_wrap(
+            "env",
+            "response_write",
+            |mut caller: Caller<EdgeFunctionStoreData>, chunk_ptr: u32, chunk_len: u32| -> i32 {
+                let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+                    Some(m) => m,
+                    None => {
+                        error!("Failed to get Wasm memory");
+                        return STREAM_ERR_FAILED;
+                    }
+                };
+
+                let mut chunk = vec![0u8; chunk_len as usize];
+                if memory.read(&caller, chunk_ptr as usize, &mut chunk).is_err() {
+                    error!("Failed to read stream chunk from Wasm memory");
+                    return STREAM_ERR_FAILED;
+                }
+
+                let data = caller.data_mut();
+                let mut context = try_write_lock!(data.execution_context, STREAM_ERR_FAILED);
+                match context.response_stream {
+                    ResponseStreamState::NotStarted => return STREAM_ERR_NOT_STARTED,
+                    ResponseStreamState::Ended => return STREAM_ERR_ENDED,
+                    ResponseStreamState::Streaming => {}
+                }
+                if context.response_body.len() + chunk.len() > MAX_EDGE_FUNCTION_RESULT_SIZE {
+                    error!("Streamed response exceeds {} bytes", MAX_EDGE_FUNCTION_RESULT_SIZE);
+                    return STREAM_ERR_FAILED;
+                }
+                context.response_body.extend_from_slice(&chunk);
+
+                debug!("Streamed response chunk ({} bytes)", chunk_len);
+                0
+            },
+        )?;
+
+        // Host function: response_end() -> i32
+        // Finishes a streamed response, returns 0 or a negative stream error code
+        linker.func_wrap(
+            "env",
+            "response_end",
+            |mut caller: Caller<EdgeFunctionStoreData>| -> i32 {
+                let data = caller.data_mut();
+                let mut context = try_write_lock!(data.execution_context, STREAM_ERR_FAILED);
+                match context.response_stream {
+                    ResponseStreamState::NotStarted => return STREAM_ERR_NOT_STARTED,
+                    ResponseStreamState::Ended => return STREAM_ERR_ENDED,
+                    ResponseStreamState::Streaming => {}
+                }
+                context.response_stream = ResponseStreamState::Ended;
+
+                debug!("Streamed response ended ({} bytes)", context.response_body.len());
+                0
+            },
+        )?;
+
         // ============================================
         // Sprint 15: Early Termination Function
         // ============================================
@@ -2330,6 +2821,158 @@ impl WasmRuntime {
             },
         )?;
 
+        // ============================================
+        // Durable KV Storage Functions
+        // ============================================
+        //
+        // Error codes (see KvError::code): -1 unavailable, -2 not found,
+        // -3 value too large, -4 quota exceeded, -5 invalid key.
+
+        // Host function: kv_get(key_ptr, key_len) -> i32
+        // Returns the length of the value (stored in shared buffer), or a negative error code
+        linker.func_wrap(
+            "env",
+            "kv_get",
+            |mut caller: Caller<EdgeFunctionStoreData>, key_ptr: u32, key_len: u32| -> i32 {
+                let key = match read_guest_string(&mut caller, key_ptr, key_len, MAX_KV_KEY_SIZE, "kv key") {
+                    Some(k) => k,
+                    None => return KvError::InvalidKey(String::new()).code(),
+                };
+
+                let data = caller.data_mut();
+                let kv = match &data.kv {
+                    Some(kv) => kv.clone(),
+                    None => return KvError::Unavailable(String::new()).code(),
+                };
+
+                match kv.get(&data.module_id, &key) {
+                    Ok(value) => {
+                        let len = value.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = value;
+                        len
+                    }
+                    Err(e) => {
+                        debug!("kv_get '{}' failed: {}", key, e);
+                        e.code()
+                    }
+                }
+            },
+        )?;
+
+        // Host function: kv_put(key_ptr, key_len, value_ptr, value_len) -> i32
+        // Returns 0 on success, or a negative error code
+        linker.func_wrap(
+            "env",
+            "kv_put",
+            |mut caller: Caller<EdgeFunctionStoreData>,
+             key_ptr: u32, key_len: u32,
+             value_ptr: u32, value_len: u32| -> i32 {
+                let key = match read_guest_string(&mut caller, key_ptr, key_len, MAX_KV_KEY_SIZE, "kv key") {
+                    Some(k) => k,
+                    None => return KvError::InvalidKey(String::new()).code(),
+                };
+
+                // Reject oversized values before copying them out of guest memory
+                if value_len as usize > MAX_KV_VALUE_SIZE {
+                    return KvError::ValueTooLarge { size: value_len as usize, max: MAX_KV_VALUE_SIZE }.code();
+                }
+                let value = match read_guest_bytes(&mut caller, value_ptr, value_len, MAX_KV_VALUE_SIZE, "kv value") {
+                    Some(v) => v,
+                    None => return -1,
+                };
+
+                let data = caller.data();
+                let kv = match &data.kv {
+                    Some(kv) => kv.clone(),
+                    None => return KvError::Unavailable(String::new()).code(),
+                };
+
+                match kv.put(&data.module_id, &key, &value) {
+                    Ok(()) => 0,
+                    Err(e) => {
+                        warn!("kv_put '{}' for module '{}' failed: {}", key, data.module_id, e);
+                        e.code()
+                    }
+                }
+            },
+        )?;
+
+        // Host function: kv_delete(key_ptr, key_len) -> i32
+        // Returns 1 if the key existed, 0 if not, or a negative error code
+        linker.func_wrap(
+            "env",
+            "kv_delete",
+            |mut caller: Caller<EdgeFunctionStoreData>, key_ptr: u32, key_len: u32| -> i32 {
+                let key = match read_guest_string(&mut caller, key_ptr, key_len, MAX_KV_KEY_SIZE, "kv key") {
+                    Some(k) => k,
+                    None => return KvError::InvalidKey(String::new()).code(),
+                };
+
+                let data = caller.data();
+                let kv = match &data.kv {
+                    Some(kv) => kv.clone(),
+                    None => return KvError::Unavailable(String::new()).code(),
+                };
+
+                match kv.delete(&data.module_id, &key) {
+                    Ok(existed) => existed as i32,
+                    Err(e) => e.code(),
+                }
+            },
+        )?;
+
+        // Host function: kv_list_prefix(prefix_ptr, prefix_len, cursor_ptr, cursor_len, limit) -> i32
+        // Returns the length of a JSON page {"keys": [...], "cursor": ...} (stored in shared
+        // buffer), or a negative error code. Pass cursor_len = 0 for the first page.
+        linker.func_wrap(
+            "env",
+            "kv_list_prefix",
+            |mut caller: Caller<EdgeFunctionStoreData>,
+             prefix_ptr: u32, prefix_len: u32,
+             cursor_ptr: u32, cursor_len: u32,
+             limit: u32| -> i32 {
+                let prefix = if prefix_len == 0 {
+                    String::new()
+                } else {
+                    match read_guest_string(&mut caller, prefix_ptr, prefix_len, MAX_KV_KEY_SIZE, "kv prefix") {
+                        Some(p) => p,
+                        None => return KvError::InvalidKey(String::new()).code(),
+                    }
+                };
+                let cursor = if cursor_len == 0 {
+                    None
+                } else {
+                    match read_guest_string(&mut caller, cursor_ptr, cursor_len, MAX_KV_KEY_SIZE, "kv cursor") {
+                        Some(c) => Some(c),
+                        None => return KvError::InvalidKey(String::new()).code(),
+                    }
+                };
+
+                let data = caller.data_mut();
+                let kv = match &data.kv {
+                    Some(kv) => kv.clone(),
+                    None => return KvError::Unavailable(String::new()).code(),
+                };
+
+                let page = match kv.list_prefix(&data.module_id, &prefix, cursor.as_deref(), limit) {
+                    Ok(page) => page,
+                    Err(e) => return e.code(),
+                };
+
+                match serde_json::to_vec(&page) {
+                    Ok(json_bytes) => {
+                        let len = json_bytes.len() as i32;
+                        *try_write_lock!(data.shared_buffer, -1) = json_bytes;
+                        len
+                    }
+                    Err(e) => {
+                        error!("Failed to serialize KV page: {}", e);
+                        -1
+                    }
+                }
+            },
+        )?;
+
         Ok(())
     }
 
@@ -2468,6 +3111,7 @@ mod tests {
     #[test]
     fn test_waf_result_serialization() {
         let result = WafResult {
+            schema_version: 2,
             blocked: true,
             matches: vec![WafMatch {
                 rule_id: 1,
@@ -2478,6 +3122,15 @@ mod tests {
                 location: "URI".to_string(),
             }],
             execution_time_us: 1500,
+            needs_hmac_check: false,
+            hmac_key_id: None,
+            block_response: Some(WafBlockResponse {
+                status_code: 403,
+                block_reason_code: "sqli:1".to_string(),
+                message: "Request blocked by AEGIS WAF (reference: sqli:1)".to_string(),
+                retry_after_secs: None,
+            }),
+            body_expansion_ratio: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -2486,6 +3139,10 @@ mod tests {
         let deserialized: WafResult = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.blocked, true);
         assert_eq!(deserialized.matches.len(), 1);
+        assert_eq!(
+            deserialized.block_response.unwrap().block_reason_code,
+            "sqli:1"
+        );
     }
 
     #[test]
@@ -2720,6 +3377,65 @@ mod tests {
         assert_eq!(result, Some(150), "Should calculate correct end offset");
     }
 
+    fn test_metadata(ipfs_cid: Option<&str>, content_hash: &str) -> WasmModuleMetadata {
+        WasmModuleMetadata {
+            module_type: WasmModuleType::EdgeFunction,
+            name: "edge-fn".to_string(),
+            version: "1.0.0".to_string(),
+            ipfs_cid: ipfs_cid.map(str::to_string),
+            loaded_at: Instant::now(),
+            signature: None,
+            public_key: None,
+            signature_verified: false,
+            content_hash: content_hash.to_string(),
+            last_integrity_check: Instant::now(),
+            ref_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    #[test]
+    fn test_invocation_context_cold_start() {
+        let runtime = WasmRuntime::new()
+            .expect("Failed to create runtime")
+            .with_node_region("eu-west");
+        let metadata = test_metadata(Some("QmDeploy"), "hash1");
+
+        let first = runtime.next_invocation_context(&metadata, "handler");
+        let second = runtime.next_invocation_context(&metadata, "handler");
+
+        assert!(first.cold_start);
+        assert!(!second.cold_start);
+        assert_eq!(first.deployment_id, "QmDeploy");
+        assert_eq!(first.function_name, "handler");
+        assert_eq!(first.node_region.as_deref(), Some("eu-west"));
+        assert_eq!(first.invocation_id.len(), 32);
+        assert_ne!(first.invocation_id, second.invocation_id);
+
+        // A new deployment (no CID, different content) starts cold again
+        let redeployed = test_metadata(None, "hash2");
+        let third = runtime.next_invocation_context(&redeployed, "handler");
+        assert!(third.cold_start);
+        assert_eq!(third.deployment_id, "hash2");
+    }
+
+    #[test]
+    fn test_client_info_json_shape() {
+        let info = ClientInfo {
+            ip: Some("203.0.113.7".to_string()),
+            country: Some("DE".to_string()),
+            tls_version: Some("TLSv1.3".to_string()),
+            ..Default::default()
+        };
+
+        let json: serde_json::Value = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["country"], "DE");
+        assert_eq!(json["tls_version"], "TLSv1.3");
+        // Unknown fields are sent as null rather than omitted
+        assert!(json["asn"].is_null());
+        assert!(json.as_object().unwrap().contains_key("ja3"));
+        assert!(WasmExecutionContext::default().client_info.is_none());
+    }
+
     #[test]
     fn test_edge_function_result_size_limit() {
         // Verify the limit is reasonable
@@ -88,6 +88,7 @@ fn test_proxy_config_serialization() {
         cache_url: None,
         cache_ttl: None,
         enable_caching: None,
+        edge_kv_path: None,
     };
 
     let toml_string = toml::to_string(&config).unwrap();
@@ -110,6 +111,7 @@ fn test_proxy_config_validation() {
         cache_url: None,
         cache_ttl: None,
         enable_caching: None,
+        edge_kv_path: None,
     };
     assert!(config.http_addr.contains(':'));
 
@@ -124,6 +126,7 @@ fn test_proxy_config_validation() {
         cache_url: None,
         cache_ttl: None,
         enable_caching: None,
+        edge_kv_path: None,
     };
     assert!(config_v6.http_addr.contains('['));
 }
@@ -152,6 +155,7 @@ fn test_proxy_config_thread_values() {
             cache_url: None,
             cache_ttl: None,
             enable_caching: None,
+            edge_kv_path: None,
         };
         assert_eq!(config.threads, Some(threads));
     }
@@ -169,6 +173,7 @@ fn test_tls_config_with_cert_paths() {
         cache_url: None,
         cache_ttl: None,
         enable_caching: None,
+        edge_kv_path: None,
     };
 
     assert!(config.tls_cert_path.is_some());
@@ -400,6 +405,7 @@ fn test_proxy_config_serde_roundtrip() {
         cache_url: Some("redis://localhost:6379".to_string()),
         cache_ttl: Some(120),
         enable_caching: Some(true),
+        edge_kv_path: Some("/var/lib/aegis/edge_kv.db".to_string()),
     };
 
     // Serialize to TOML
@@ -414,4 +420,5 @@ fn test_proxy_config_serde_roundtrip() {
     assert_eq!(original.threads, deserialized.threads);
     assert_eq!(original.tls_cert_path, deserialized.tls_cert_path);
     assert_eq!(original.tls_key_path, deserialized.tls_key_path);
+    assert_eq!(original.edge_kv_path, deserialized.edge_kv_path);
 }
@@ -8,7 +8,7 @@
 /// These tests use inline WAT (WebAssembly Text Format) to create test modules
 /// that exercise all the new host functions.
 
-use aegis_node::wasm_runtime::{WasmRuntime, WasmModuleType, WasmExecutionContext, EdgeFunctionResult};
+use aegis_node::wasm_runtime::{WasmRuntime, WasmModuleType, WasmExecutionContext, EdgeFunctionResult, ResponseStreamState};
 use anyhow::Result;
 
 /// Helper function to create a simple WAT module that tests request context access
@@ -186,6 +186,8 @@ async fn test_request_context_access() -> Result<()> {
         response_headers: Vec::new(),
         response_body: Vec::new(),
         terminate_early: false,
+        response_stream: ResponseStreamState::NotStarted,
+        client_info: None,
     };
 
     // Execute edge function with context
@@ -234,6 +236,8 @@ async fn test_response_manipulation() -> Result<()> {
         response_headers: Vec::new(),
         response_body: Vec::new(),
         terminate_early: false,
+        response_stream: ResponseStreamState::NotStarted,
+        client_info: None,
     };
 
     // Execute edge function
@@ -328,6 +332,8 @@ async fn test_header_reading() -> Result<()> {
         response_headers: Vec::new(),
         response_body: Vec::new(),
         terminate_early: false,
+        response_stream: ResponseStreamState::NotStarted,
+        client_info: None,
     };
 
     // Execute edge function
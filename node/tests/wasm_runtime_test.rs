@@ -64,6 +64,9 @@ fn test_waf_result_parsing() {
             },
         ],
         execution_time_us: 2500,
+        needs_hmac_check: false,
+        hmac_key_id: None,
+        block_response: None,
     };
 
     // Test serialization
@@ -173,6 +176,9 @@ fn test_waf_result_no_matches() {
         blocked: false,
         matches: Vec::new(),
         execution_time_us: 500,
+        needs_hmac_check: false,
+        hmac_key_id: None,
+        block_response: None,
     };
 
     assert_eq!(clean_result.blocked, false);
@@ -211,6 +217,9 @@ fn test_waf_result_multiple_categories() {
             },
         ],
         execution_time_us: 3000,
+        needs_hmac_check: false,
+        hmac_key_id: None,
+        block_response: None,
     };
 
     // Count unique categories
@@ -261,6 +270,9 @@ fn test_execution_time_tracking() {
         blocked: false,
         matches: Vec::new(),
         execution_time_us: elapsed_us,
+        needs_hmac_check: false,
+        hmac_key_id: None,
+        block_response: None,
     };
 
     // Should be at least 5ms (5000us)
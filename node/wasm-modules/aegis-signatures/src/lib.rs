@@ -0,0 +1,90 @@
+// AEGIS Shared Signature Tables
+// Sprint Y8.7 follow-up: single source of truth for scanner/tool signatures
+//
+// The WAF (rule 913100) and the bot detector used to carry their own copies
+// of the scanner list, which drifted apart between releases and produced
+// different verdicts for the same request. Both modules now compile these
+// tables in and report SIGNATURES_VERSION so operators can confirm which
+// set a node is running.
+
+#![cfg_attr(not(test), no_std)]
+
+/// Version of the signature tables (bump whenever any table changes)
+pub const SIGNATURES_VERSION: u32 = 1;
+
+/// Vulnerability scanner signatures (case-insensitive substring match)
+///
+/// Flagged by both the WAF (rule 913100, any request field) and the bot
+/// detector (User-Agent only).
+pub static SCANNER_SIGNATURES: &[&str] = &[
+    "nikto",
+    "nmap",
+    "masscan",
+    "sqlmap",
+    "dirbuster",
+    "acunetix",
+];
+
+/// Additional scanner signatures only matched against User-Agent strings
+///
+/// These are too short or too generic ("ZAP", "w3af") to match against URIs
+/// and bodies without false positives, so the WAF does not use them.
+pub static USER_AGENT_SCANNER_SIGNATURES: &[&str] = &[
+    "nessus",
+    "OpenVAS",
+    "w3af",
+    "ZAP",              // OWASP ZAP
+];
+
+/// Exact User-Agent prefixes emitted by scanner tools
+///
+/// Used by the bot detector's exact prefix matching, e.g. "nikto/2.1.6".
+pub static SCANNER_EXACT_PREFIXES: &[&str] = &[
+    "nikto/",          // nikto/2.1.6
+    "nmap ",           // nmap 7.80
+    "masscan/",        // masscan/1.3.2
+    "sqlmap/",         // sqlmap/1.5.2
+];
+
+/// Canonical scanner User-Agents every consuming module must flag
+///
+/// Each module's tests run these through its own detection path so the WAF
+/// and bot detector cannot silently disagree on a scanner.
+pub static CANONICAL_SCANNER_USER_AGENTS: &[&str] = &[
+    "nikto/2.1.6",
+    "Mozilla/5.00 (Nikto/2.1.6) (Evasions:None) (Test:000001)",
+    "nmap 7.80",
+    "Mozilla/5.0 (compatible; Nmap Scripting Engine; https://nmap.org/book/nse.html)",
+    "masscan/1.3.2 (https://github.com/robertdavidgraham/masscan)",
+    "sqlmap/1.5.2#stable (http://sqlmap.org)",
+    "DirBuster-1.0-RC1 (http://www.owasp.org/index.php/Category:OWASP_DirBuster_Project)",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Acunetix-Product",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_user_agents_hit_shared_table() {
+        for ua in CANONICAL_SCANNER_USER_AGENTS {
+            let lower = ua.to_ascii_lowercase();
+            assert!(
+                SCANNER_SIGNATURES.iter().any(|s| lower.contains(s.to_ascii_lowercase().as_str())),
+                "Canonical UA not covered by SCANNER_SIGNATURES: {}",
+                ua
+            );
+        }
+    }
+
+    #[test]
+    fn test_exact_prefixes_are_scanner_signatures() {
+        for prefix in SCANNER_EXACT_PREFIXES {
+            assert!(
+                SCANNER_SIGNATURES.iter().any(|s| prefix.starts_with(s)),
+                "Prefix without a matching scanner signature: {}",
+                prefix
+            );
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Injects build metadata consumed by `get_build_info()`.
+//
+// Honors SOURCE_DATE_EPOCH so reproducible builds produce identical modules.
+// No rerun-if directives: cargo re-runs this whenever any package file changes,
+// so the timestamp tracks the last source edit.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    println!("cargo:rustc-env=BOT_DETECTOR_BUILD_TIMESTAMP={}", timestamp);
+}
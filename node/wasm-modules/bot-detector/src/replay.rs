@@ -0,0 +1,147 @@
+//! Corpus replay for regression testing
+//!
+//! Each line of `tests/replay_corpus.jsonl` is one captured request and the
+//! verdict it must keep: `{"name", "input", "expected": {"verdict",
+//! "suspicion_score", "reasons"}}`, plus an optional `feedback` document
+//! loaded for that case only. `input` goes through `analyze_request()` as
+//! the host would send it, so a case may also hold input the module must
+//! reject. Cases run against REPLAY_SIGNATURES, which exercises the host
+//! tables and zone lists.
+//!
+//! When a heuristic changes on purpose, update the affected lines; anything
+//! else that moves is a regression.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::tests::read_length_prefixed;
+use crate::*;
+
+/// Corpus replayed by `cargo test`
+const CORPUS: &str = include_str!("../tests/replay_corpus.jsonl");
+
+/// Host signatures loaded before replaying
+const REPLAY_SIGNATURES: &str = r#"{
+    "exact_prefixes": ["AcmeMonitor/"],
+    "signatures": ["internal-probe"],
+    "zones": {
+        "shop": {
+            "ua_allow_exact": ["curl/7.68.0 shop-healthcheck", "ShopSync/1.0 both-lists"],
+            "ua_deny_exact": ["Mozilla/5.0 (Windows NT 10.0; Win64; x64) PriceScraper/2.0", "ShopSync/1.0 both-lists"]
+        }
+    }
+}"#;
+
+/// Verdict fields a case pins down
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+struct Expected {
+    verdict: String,
+    suspicion_score: u32,
+    reasons: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Case {
+    name: String,
+    input: Value,
+    expected: Expected,
+    /// Feedback document (see `load_feedback()`) in force for this case
+    #[serde(default)]
+    feedback: Option<Value>,
+}
+
+/// Parse a corpus, panicking on a malformed line
+fn parse_corpus(corpus: &str) -> Vec<Case> {
+    corpus
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).unwrap_or_else(|e| panic!("corpus line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Run one case end to end; Err describes how the verdict differs
+fn replay_case(case: &Case) -> Result<(), String> {
+    if let Some(feedback) = &case.feedback {
+        let feedback = feedback.to_string();
+        if load_feedback(feedback.as_ptr(), feedback.len()) != 1 {
+            return Err("feedback document rejected".to_string());
+        }
+    }
+
+    // String inputs are sent verbatim so malformed documents can be replayed
+    let input = match &case.input {
+        Value::String(raw) => raw.clone(),
+        input => input.to_string(),
+    };
+    let json = read_length_prefixed(analyze_request(input.as_ptr(), input.len()));
+
+    if case.feedback.is_some() {
+        load_feedback(b"{}".as_ptr(), 2);
+    }
+
+    let actual: Expected = serde_json::from_str(&json).map_err(|e| format!("{}: {}", e, json))?;
+    if actual == case.expected {
+        Ok(())
+    } else {
+        Err(format!("expected {:?}, got {:?}", case.expected, actual))
+    }
+}
+
+/// Replay every case, returning one line per regression
+fn replay(cases: &[Case]) -> Vec<String> {
+    assert_eq!(load_signatures(REPLAY_SIGNATURES.as_ptr(), REPLAY_SIGNATURES.len()), 1);
+    cases
+        .iter()
+        .filter_map(|case| replay_case(case).err().map(|e| format!("{}: {}", case.name, e)))
+        .collect()
+}
+
+#[test]
+fn test_replay_corpus() {
+    let cases = parse_corpus(CORPUS);
+    assert!(cases.len() >= 50, "corpus has only {} cases", cases.len());
+
+    let regressions = replay(&cases);
+    assert!(regressions.is_empty(), "{} regressions:\n{}", regressions.len(), regressions.join("\n"));
+}
+
+#[test]
+fn test_replay_covers_every_rule_kind() {
+    let cases = parse_corpus(CORPUS);
+    let reasons: Vec<&str> = cases
+        .iter()
+        .flat_map(|case| &case.expected.reasons)
+        .map(String::as_str)
+        .collect();
+
+    for kind in [
+        "suspicious_exact:",
+        "exact_prefix:",
+        "host_exact_prefix:",
+        "signature:",
+        "host_signature:",
+        "suspicious_pattern:",
+        "zone_allowlist",
+        "zone_denylist",
+    ] {
+        assert!(reasons.iter().any(|r| r.starts_with(kind)), "no case for {}", kind);
+    }
+    for heuristic in UA_HEURISTICS.iter().chain(FEEDBACK_REASONS).chain([&INVALID_INPUT_HEURISTIC]) {
+        let id = heuristic_id(heuristic);
+        assert!(reasons.contains(&id.as_str()), "no case for {}", id);
+    }
+}
+
+#[test]
+fn test_replay_reports_regression() {
+    let mut cases = parse_corpus(CORPUS);
+    let curl = cases.iter_mut().find(|case| case.name == "curl").expect("curl case");
+    curl.expected.verdict = "human".to_string();
+
+    let regressions = replay(&cases);
+    assert_eq!(regressions.len(), 1, "{:?}", regressions);
+    assert!(regressions[0].starts_with("curl: expected"), "{}", regressions[0]);
+}
@@ -0,0 +1,356 @@
+//! Cache SDK: binary-safe values with optional compression
+//!
+//! The cache stores arbitrary bytes. `cache_get_raw()`/`cache_set_raw()`
+//! pass them through unchanged.
+//!
+//! `cache_set_compressed()` compresses values larger than
+//! `COMPRESSION_THRESHOLD` with a small LZ4-style block codec (pure Rust, no
+//! dependencies, so it stays within the Wasm size budget) and marks them with
+//! a one-byte format prefix. `cache_get_compressed()` reads those entries as
+//! well as anything stored raw, so switching a key to compression doesn't
+//! invalidate what is already cached:
+//!
+//! - `0xFF` + original length (u32 LE) + compressed block
+//! - `0xFE` + value: stored uncompressed, but the value itself starts with a
+//!   prefix byte
+//! - anything else: stored as-is (sub-threshold values, legacy entries)
+//!
+//! `0xFE` and `0xFF` never occur in UTF-8, so text and JSON written raw by
+//! older code are never mistaken for framed values.
+
+use crate::{host, log_message, read_shared_buffer};
+
+/// Values larger than this are compressed by `cache_set_compressed()` (8KB)
+pub const COMPRESSION_THRESHOLD: usize = 8 * 1024;
+
+/// Largest value `cache_get_compressed()` will inflate, so a corrupt length
+/// header can't force a huge allocation (16MB)
+pub const MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Prefix of a compressed entry
+const FORMAT_COMPRESSED: u8 = 0xFF;
+
+/// Prefix of an uncompressed entry whose value starts with a prefix byte
+const FORMAT_RAW: u8 = 0xFE;
+
+/// Prefix byte plus original length
+const COMPRESSED_HEADER_LEN: usize = 5;
+
+/// Shortest match the codec encodes (LZ4 minimum)
+const MIN_MATCH: usize = 4;
+
+/// Trailing bytes always emitted as literals (LZ4 end-of-block rule)
+const END_LITERALS: usize = 5;
+
+/// No match may start within this many bytes of the end (LZ4 end-of-block rule)
+const MATCH_FIND_LIMIT: usize = 12;
+
+/// Farthest back a match can reference (16-bit offsets)
+const MAX_OFFSET: usize = u16::MAX as usize;
+
+/// log2 of the match finder's hash table size
+const HASH_BITS: u32 = 12;
+
+/// Get a value exactly as stored
+pub fn cache_get_raw(key: &str) -> Option<Vec<u8>> {
+    let result_len = unsafe { host::cache_get(key.as_ptr(), key.len() as u32) };
+    read_shared_buffer(result_len)
+}
+
+/// Store a value as-is (`ttl` in seconds, 0 for no expiry)
+pub fn cache_set_raw(key: &str, value: &[u8], ttl: u32) -> bool {
+    unsafe {
+        let result = host::cache_set(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32, ttl);
+        result == 0
+    }
+}
+
+/// Store a value, compressing it when larger than `COMPRESSION_THRESHOLD`
+///
+/// Values that don't shrink are stored uncompressed.
+pub fn cache_set_compressed(key: &str, value: &[u8], ttl: u32) -> bool {
+    cache_set_raw(key, &encode(value), ttl)
+}
+
+/// Get a value stored by `cache_set_compressed()` or `cache_set_raw()`
+///
+/// A compressed entry that fails to decode is reported as a miss, so the
+/// caller refetches and overwrites it.
+pub fn cache_get_compressed(key: &str) -> Option<Vec<u8>> {
+    let stored = cache_get_raw(key)?;
+    let value = decode(&stored);
+    if value.is_none() {
+        log_message(&format!("cache: corrupt compressed entry {}", key));
+    }
+    value
+}
+
+/// Frame a value for storage
+fn encode(value: &[u8]) -> Vec<u8> {
+    if value.len() > COMPRESSION_THRESHOLD && value.len() <= MAX_DECOMPRESSED_SIZE {
+        let block = compress_block(value);
+        if block.len() + COMPRESSED_HEADER_LEN < value.len() {
+            let mut framed = Vec::with_capacity(block.len() + COMPRESSED_HEADER_LEN);
+            framed.push(FORMAT_COMPRESSED);
+            framed.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            framed.extend_from_slice(&block);
+            return framed;
+        }
+    }
+
+    match value.first() {
+        Some(&(FORMAT_COMPRESSED | FORMAT_RAW)) => {
+            let mut framed = Vec::with_capacity(value.len() + 1);
+            framed.push(FORMAT_RAW);
+            framed.extend_from_slice(value);
+            framed
+        }
+        _ => value.to_vec(),
+    }
+}
+
+/// Recover a value from its stored form; `None` if a compressed entry is corrupt
+fn decode(stored: &[u8]) -> Option<Vec<u8>> {
+    match stored.first() {
+        Some(&FORMAT_COMPRESSED) => {
+            let len = u32::from_le_bytes(stored.get(1..COMPRESSED_HEADER_LEN)?.try_into().ok()?) as usize;
+            if len > MAX_DECOMPRESSED_SIZE {
+                return None;
+            }
+            decompress_block(&stored[COMPRESSED_HEADER_LEN..], len)
+        }
+        Some(&FORMAT_RAW) => Some(stored[1..].to_vec()),
+        _ => Some(stored.to_vec()),
+    }
+}
+
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Append an LZ4 length continuation (runs of 255 plus a remainder)
+fn write_length(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 255 {
+        out.push(255);
+        len -= 255;
+    }
+    out.push(len as u8);
+}
+
+/// Append one sequence: literals, then an optional (offset, length) match
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_code = matched.map_or(0, |(_, len)| len - MIN_MATCH);
+    out.push(((literals.len().min(15) << 4) | match_code.min(15)) as u8);
+    if literals.len() >= 15 {
+        write_length(out, literals.len() - 15);
+    }
+    out.extend_from_slice(literals);
+
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_code >= 15 {
+            write_length(out, match_code - 15);
+        }
+    }
+}
+
+/// Compress into an LZ4 block (greedy single-probe match finder)
+fn compress_block(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2 + 16);
+    // Position + 1 of the last occurrence of each hashed 4-byte sequence
+    let mut table = vec![0usize; 1 << HASH_BITS];
+    let match_limit = input.len().saturating_sub(MATCH_FIND_LIMIT);
+    let mut anchor = 0;
+    let mut pos = 0;
+
+    while pos < match_limit {
+        let sequence = read_u32(input, pos);
+        let slot = hash(sequence);
+        let candidate = table[slot];
+        table[slot] = pos + 1;
+
+        if candidate == 0 || pos - (candidate - 1) > MAX_OFFSET || read_u32(input, candidate - 1) != sequence {
+            pos += 1;
+            continue;
+        }
+        let candidate = candidate - 1;
+
+        let max_len = input.len() - END_LITERALS - pos;
+        let mut len = MIN_MATCH;
+        while len < max_len && input[candidate + len] == input[pos + len] {
+            len += 1;
+        }
+
+        write_sequence(&mut out, &input[anchor..pos], Some((pos - candidate, len)));
+        pos += len;
+        anchor = pos;
+    }
+
+    write_sequence(&mut out, &input[anchor..], None);
+    out
+}
+
+/// Read an LZ4 length continuation
+fn read_length(input: &[u8], i: &mut usize) -> Option<usize> {
+    let mut len = 0usize;
+    loop {
+        let byte = *input.get(*i)?;
+        *i += 1;
+        len = len.saturating_add(byte as usize);
+        if byte != 255 {
+            return Some(len);
+        }
+    }
+}
+
+/// Decompress an LZ4 block that must inflate to exactly `len` bytes
+fn decompress_block(input: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+
+    loop {
+        let token = *input.get(i)?;
+        i += 1;
+
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len = literal_len.saturating_add(read_length(input, &mut i)?);
+        }
+        let literals = input.get(i..i.checked_add(literal_len)?)?;
+        if out.len() + literal_len > len {
+            return None;
+        }
+        out.extend_from_slice(literals);
+        i += literal_len;
+
+        // The last sequence has no match
+        if i == input.len() {
+            break;
+        }
+
+        let offset = u16::from_le_bytes(input.get(i..i + 2)?.try_into().ok()?) as usize;
+        i += 2;
+        let mut match_len = (token & 0x0F) as usize;
+        if match_len == 15 {
+            match_len = match_len.saturating_add(read_length(input, &mut i)?);
+        }
+        let match_len = match_len.saturating_add(MIN_MATCH);
+        if offset == 0 || offset > out.len() || out.len().saturating_add(match_len) > len {
+            return None;
+        }
+
+        // Byte by byte: a match may overlap the bytes it produces
+        let start = out.len() - offset;
+        for k in 0..match_len {
+            out.push(out[start + k]);
+        }
+    }
+
+    (out.len() == len).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    /// ~100KB exchange-rate style JSON document
+    fn large_json() -> Vec<u8> {
+        let rates: Vec<String> = (0..3000)
+            .map(|i| format!("{{\"pair\":\"USD/X{:04}\",\"rate\":{}.{:04}}}", i, i % 97, (i * 7919) % 10_000))
+            .collect();
+        format!("{{\"base\":\"USD\",\"rates\":[{}]}}", rates.join(",")).into_bytes()
+    }
+
+    fn stored(key: &str) -> Vec<u8> {
+        with_host(|h| h.cache[key].clone())
+    }
+
+    #[test]
+    fn test_large_json_roundtrip() {
+        let document = large_json();
+        assert!(document.len() > 100 * 1024);
+
+        assert!(cache_set_compressed("rates", &document, 60));
+        let raw = stored("rates");
+        assert_eq!(raw[0], FORMAT_COMPRESSED);
+        assert!(raw.len() < document.len() / 2, "{} of {} bytes", raw.len(), document.len());
+
+        assert_eq!(cache_get_compressed("rates").unwrap(), document);
+    }
+
+    #[test]
+    fn test_legacy_entry_reads() {
+        let legacy = br#"{"base":"USD","rates":{"EUR":0.92}}"#;
+        assert!(cache_set_raw("rates", legacy, 60));
+        assert_eq!(cache_get_compressed("rates").unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_sub_threshold_stored_raw() {
+        let value = vec![b'a'; COMPRESSION_THRESHOLD];
+        assert!(cache_set_compressed("small", &value, 60));
+        assert_eq!(stored("small"), value);
+        assert_eq!(cache_get_compressed("small").unwrap(), value);
+    }
+
+    #[test]
+    fn test_prefix_byte_values_roundtrip() {
+        // e.g. a JPEG, which starts with 0xFF
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        assert!(cache_set_compressed("image", &jpeg, 60));
+        assert_eq!(stored("image")[0], FORMAT_RAW);
+        assert_eq!(cache_get_compressed("image").unwrap(), jpeg);
+        assert_eq!(cache_get_compressed("missing"), None);
+    }
+
+    #[test]
+    fn test_incompressible_stored_uncompressed() {
+        let mut state = 0x1234_5678u32;
+        let noise: Vec<u8> = (0..COMPRESSION_THRESHOLD * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect();
+        assert!(cache_set_compressed("noise", &noise, 60));
+        assert_ne!(stored("noise")[0], FORMAT_COMPRESSED);
+        assert_eq!(cache_get_compressed("noise").unwrap(), noise);
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_a_miss() {
+        let mut framed = encode(&large_json());
+        framed.truncate(framed.len() - 10);
+        cache_set_raw("rates", &framed, 60);
+        assert_eq!(cache_get_compressed("rates"), None);
+
+        let mut huge = vec![FORMAT_COMPRESSED];
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(decode(&huge), None);
+    }
+
+    #[test]
+    fn test_decompress_rejects_bad_offsets() {
+        // One literal, then a match reaching before the start of output
+        assert_eq!(decompress_block(&[0x10, b'a', 0x05, 0x00], 5), None);
+        assert_eq!(decompress_block(&[0x10, b'a', 0x00, 0x00], 5), None);
+        // Overlapping match repeating the literal, then an empty final sequence
+        assert_eq!(decompress_block(&[0x10, b'a', 0x01, 0x00, 0x00], 5).unwrap(), b"aaaaa");
+        assert_eq!(decompress_block(&[0x10, b'a', 0x01, 0x00, 0x00], 6), None);
+    }
+
+    #[test]
+    fn test_codec_edge_lengths() {
+        for len in [0, 1, 12, 13, 15, 270, 70_000] {
+            let input: Vec<u8> = (0..len).map(|i| (i % 7) as u8).collect();
+            assert_eq!(decompress_block(&compress_block(&input), len).unwrap(), input, "len {}", len);
+        }
+    }
+}
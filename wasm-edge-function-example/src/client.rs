@@ -0,0 +1,164 @@
+//! Client metadata SDK
+//!
+//! The host reports what the node knows about the client (GeoIP country and
+//! region, ASN, TLS version and JA3 fingerprint) so functions can personalize
+//! responses: pick a currency, geo-block, redirect by language.
+//!
+//! The client IP is personal data, so it is kept out of the way:
+//!
+//! - `ClientInfo` exposes country, region and the network details directly;
+//!   the IP is only reachable through `dangerously_raw_ip`
+//! - `Debug` output redacts it
+//! - Once fetched, `logging` redacts it from every structured log line, so
+//!   a handler that copies it into a message or field can't leak it to logs
+//!
+//! The client doesn't change during an invocation, so the host is asked once
+//! and the result cached, like the invocation context.
+
+use std::cell::OnceCell;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{host, read_shared_buffer};
+
+/// Placeholder written in place of the client IP
+pub const REDACTED: &str = "[redacted]";
+
+/// What the node knows about the client, as reported by the host
+///
+/// Every field is optional: a node without a GeoIP database has no country,
+/// a plain HTTP request has no TLS details.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ClientInfo {
+    #[serde(default)]
+    ip: Option<String>,
+    /// ISO 3166-1 alpha-2 country code, e.g. "DE"
+    #[serde(default)]
+    pub country: Option<String>,
+    /// ISO 3166-2 subdivision code, e.g. "BY"
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Autonomous system number of the client network
+    #[serde(default)]
+    pub asn: Option<u32>,
+    /// Negotiated TLS version, e.g. "TLSv1.3"
+    #[serde(default)]
+    pub tls_version: Option<String>,
+    /// JA3 fingerprint of the TLS ClientHello
+    #[serde(default)]
+    pub ja3: Option<String>,
+}
+
+impl ClientInfo {
+    /// The client IP address
+    ///
+    /// Explicit opt-in: only call this when the function really needs the
+    /// address (rate limiting, allow lists), never to log or store it.
+    pub fn dangerously_raw_ip(&self) -> Option<&str> {
+        self.ip.as_deref()
+    }
+
+    /// Whether the client is in `country` (case-insensitive ISO code)
+    pub fn in_country(&self, country: &str) -> bool {
+        self.country.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(country))
+    }
+}
+
+impl fmt::Debug for ClientInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientInfo")
+            .field("ip", &self.ip.as_ref().map(|_| REDACTED))
+            .field("country", &self.country)
+            .field("region", &self.region)
+            .field("asn", &self.asn)
+            .field("tls_version", &self.tls_version)
+            .field("ja3", &self.ja3)
+            .finish()
+    }
+}
+
+thread_local! {
+    static CLIENT: OnceCell<Option<ClientInfo>> = const { OnceCell::new() };
+}
+
+fn fetch() -> Option<ClientInfo> {
+    let len = unsafe { host::get_client_info() };
+    let json = read_shared_buffer(len)?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Get the client's metadata
+///
+/// Returns `None` when the host doesn't provide it (or sends malformed
+/// JSON). The first call replaces the contents of the shared buffer.
+pub fn get_client_info() -> Option<ClientInfo> {
+    CLIENT.with(|cell| cell.get_or_init(fetch).clone())
+}
+
+/// The client IP if `get_client_info` already fetched it
+///
+/// Never calls the host, so logging can redact without clobbering the
+/// shared buffer.
+pub(crate) fn fetched_ip() -> Option<String> {
+    CLIENT.with(|cell| cell.get().cloned().flatten().and_then(|info| info.ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    const PAYLOAD: &str = r#"{
+        "ip": "203.0.113.7",
+        "country": "DE",
+        "region": "BY",
+        "asn": 3320,
+        "tls_version": "TLSv1.3",
+        "ja3": "e7d705a3286e19ea42f587b344ee6865"
+    }"#;
+
+    #[test]
+    fn test_parses_host_payload() {
+        with_host(|h| h.client_info = Some(PAYLOAD.to_string()));
+
+        let info = get_client_info().unwrap();
+        assert_eq!(info.country.as_deref(), Some("DE"));
+        assert_eq!(info.region.as_deref(), Some("BY"));
+        assert_eq!(info.asn, Some(3320));
+        assert_eq!(info.tls_version.as_deref(), Some("TLSv1.3"));
+        assert_eq!(info.ja3.as_deref(), Some("e7d705a3286e19ea42f587b344ee6865"));
+        assert_eq!(info.dangerously_raw_ip(), Some("203.0.113.7"));
+        assert!(info.in_country("de"));
+    }
+
+    #[test]
+    fn test_partial_payload() {
+        with_host(|h| h.client_info = Some(r#"{"ip":"198.51.100.1","country":null}"#.to_string()));
+
+        let info = get_client_info().unwrap();
+        assert_eq!(info.country, None);
+        assert_eq!(info.tls_version, None);
+        assert!(!info.in_country("DE"));
+    }
+
+    #[test]
+    fn test_unavailable() {
+        assert_eq!(get_client_info(), None);
+        assert_eq!(fetched_ip(), None);
+        // Cached for the rest of the invocation
+        with_host(|h| h.client_info = Some(PAYLOAD.to_string()));
+        assert_eq!(get_client_info(), None);
+        assert_eq!(with_host(|h| h.client_info_calls), 1);
+    }
+
+    #[test]
+    fn test_debug_redacts_ip() {
+        with_host(|h| h.client_info = Some(PAYLOAD.to_string()));
+
+        let debug = format!("{:?}", get_client_info().unwrap());
+        assert!(!debug.contains("203.0.113.7"));
+        assert!(debug.contains(REDACTED));
+        assert!(debug.contains("DE"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Entrypoint guard
+//!
+//! `run_guarded` wraps a request handler so a failure reaches the host as a
+//! JSON error envelope instead of a bare return code or wasm trap:
+//!
+//! ```json
+//! {"code": "panic", "message": "index out of bounds", "backtrace_hint": "panicked at src/lib.rs:42:9 in webhook_ack"}
+//! ```
+//!
+//! The envelope is set as the response body and logged at error level
+//! through `log_structured`; the export then returns -1.
+//!
+//! wasm32-unknown-unknown only supports `panic = "abort"`, so
+//! `catch_unwind` can't stop a panic there. Instead a panic hook (installed
+//! by the first `run_guarded` call) builds and sends the envelope before the
+//! instance traps: the host still sees a trap, but the response body and log
+//! carry the envelope. On targets that unwind (native builds and the tests)
+//! the panic is caught and the export returns -1 normally.
+
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::headers::HeaderError;
+use crate::host;
+use crate::idempotency::IdempotencyError;
+use crate::kv::KvError;
+use crate::logging::{log_structured, LogLevel};
+use crate::streaming::StreamError;
+
+/// Envelope code for a handler that panicked
+pub const PANIC_ERROR_CODE: &str = "panic";
+
+/// Error returned by a guarded handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeError {
+    /// Stable, machine-readable code (e.g. `origin_failed`)
+    pub code: String,
+    /// Human-readable detail
+    pub message: String,
+}
+
+impl EdgeError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<HeaderError> for EdgeError {
+    fn from(e: HeaderError) -> Self {
+        Self::new("invalid_header", format!("{:?}", e))
+    }
+}
+
+impl From<StreamError> for EdgeError {
+    fn from(e: StreamError) -> Self {
+        Self::new("stream_failed", format!("{:?}", e))
+    }
+}
+
+impl From<IdempotencyError> for EdgeError {
+    fn from(e: IdempotencyError) -> Self {
+        Self::new("idempotency_failed", format!("{:?}", e))
+    }
+}
+
+impl From<KvError> for EdgeError {
+    fn from(e: KvError) -> Self {
+        Self::new("kv_failed", format!("{:?}", e))
+    }
+}
+
+/// Standard error document sent to the host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorEnvelope {
+    pub code: String,
+    pub message: String,
+    /// Where the failure surfaced (entrypoint, plus source location for panics)
+    pub backtrace_hint: String,
+}
+
+thread_local! {
+    /// Entrypoint currently running under `run_guarded`
+    static ENTRYPOINT: RefCell<Option<&'static str>> = const { RefCell::new(None) };
+
+    /// Envelope built by the panic hook for the current panic
+    static PANIC_ENVELOPE: RefCell<Option<ErrorEnvelope>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Build the envelope for a panic and, where panics abort, send it right away
+fn install_panic_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let entrypoint = ENTRYPOINT.with(|e| *e.borrow());
+            if let Some(entrypoint) = entrypoint {
+                let payload = info.payload();
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "panic with non-string payload".to_string());
+                let location = info
+                    .location()
+                    .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+                    .unwrap_or_else(|| "unknown location".to_string());
+                let envelope = ErrorEnvelope {
+                    code: PANIC_ERROR_CODE.to_string(),
+                    message,
+                    backtrace_hint: format!("panicked at {} in {}", location, entrypoint),
+                };
+
+                // Nothing runs after this hook when panics abort
+                if cfg!(panic = "abort") {
+                    send_envelope(&envelope);
+                }
+                PANIC_ENVELOPE.with(|p| *p.borrow_mut() = Some(envelope));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Set the envelope as the response body and log it
+fn send_envelope(envelope: &ErrorEnvelope) {
+    log_structured(
+        LogLevel::Error,
+        "Edge function failed",
+        &[
+            ("code", Value::from(envelope.code.as_str())),
+            ("error", Value::from(envelope.message.as_str())),
+            ("backtrace_hint", Value::from(envelope.backtrace_hint.as_str())),
+        ],
+    );
+
+    let body = serde_json::to_string(envelope).unwrap_or_else(|_| r#"{"code":"internal"}"#.to_string());
+    // Best effort: fails if the handler already streamed a response
+    unsafe {
+        host::response_set_body(body.as_ptr(), body.len() as u32);
+    }
+}
+
+/// Run a request handler, turning errors and panics into an error envelope
+///
+/// Returns 0 on success and -1 after sending the envelope.
+pub fn run_guarded(entrypoint: &'static str, handler: impl FnOnce() -> Result<(), EdgeError>) -> i32 {
+    install_panic_hook();
+    ENTRYPOINT.with(|e| *e.borrow_mut() = Some(entrypoint));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(handler));
+    ENTRYPOINT.with(|e| *e.borrow_mut() = None);
+
+    let envelope = match outcome {
+        Ok(Ok(())) => return 0,
+        Ok(Err(e)) => ErrorEnvelope {
+            code: e.code,
+            message: e.message,
+            backtrace_hint: format!("returned from {}", entrypoint),
+        },
+        Err(_) => PANIC_ENVELOPE.with(|p| p.borrow_mut().take()).unwrap_or_else(|| ErrorEnvelope {
+            code: PANIC_ERROR_CODE.to_string(),
+            message: "panic".to_string(),
+            backtrace_hint: format!("panicked in {}", entrypoint),
+        }),
+    };
+    send_envelope(&envelope);
+    -1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    fn response_envelope() -> Value {
+        let body = with_host(|h| h.response_body.clone()).expect("envelope not sent");
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn error_logs() -> Vec<Value> {
+        with_host(|h| h.logs_at("error"))
+    }
+
+    #[test]
+    fn test_panic_becomes_envelope() {
+        let result = run_guarded("panicky_handler", || {
+            let items: Vec<u32> = Vec::new();
+            if items.is_empty() {
+                panic!("no items for {}", "cart-7");
+            }
+            Ok(())
+        });
+
+        assert_eq!(result, -1);
+        let envelope = response_envelope();
+        assert_eq!(envelope["code"], PANIC_ERROR_CODE);
+        assert_eq!(envelope["message"], "no items for cart-7");
+        let hint = envelope["backtrace_hint"].as_str().unwrap();
+        assert!(hint.starts_with("panicked at src/guard.rs:"), "{}", hint);
+        assert!(hint.ends_with("in panicky_handler"), "{}", hint);
+
+        let logs = error_logs();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["code"], PANIC_ERROR_CODE);
+    }
+
+    #[test]
+    fn test_error_becomes_envelope() {
+        let result = run_guarded("failing_handler", || Err(HeaderError::InvalidName("Bad Name".to_string()).into()));
+
+        assert_eq!(result, -1);
+        let envelope = response_envelope();
+        assert_eq!(envelope["code"], "invalid_header");
+        assert_eq!(envelope["backtrace_hint"], "returned from failing_handler");
+        assert_eq!(error_logs().len(), 1);
+    }
+
+    #[test]
+    fn test_success_untouched() {
+        assert_eq!(run_guarded("ok_handler", || Ok(())), 0);
+        assert!(with_host(|h| h.response_body.is_none() && h.logs.is_empty()));
+
+        // Panics outside a guarded handler don't leave a stale envelope
+        assert!(panic::catch_unwind(|| panic!("unguarded")).is_err());
+        assert!(PANIC_ENVELOPE.with(|p| p.borrow().is_none()));
+    }
+}
@@ -0,0 +1,266 @@
+//! Header manipulation SDK
+//!
+//! `Headers` wraps the `[[name, value], ...]` JSON header list exchanged with
+//! the host and guards the mistakes header-rewriting functions keep making:
+//!
+//! - Names must be RFC 7230 tokens and values may not contain CR, LF, NUL or
+//!   other control characters (tab excepted), so user input copied into a
+//!   header can't inject extra headers or split the response
+//! - Framing and routing headers (`PROTECTED_HEADERS`) can't be changed by
+//!   `set`, `append` or `remove`; `dangerously_set` is the explicit opt-in
+//! - Lookups are case-insensitive and stored names are canonical-cased
+//!   (`content-type` becomes `Content-Type`)
+
+use serde::{Deserialize, Serialize};
+
+use crate::{host, read_shared_buffer};
+
+/// Headers that `set`, `append` and `remove` refuse to touch
+pub const PROTECTED_HEADERS: &[&str] = &["Host", "Content-Length", "Transfer-Encoding", "Connection"];
+
+/// Longest accepted header name (matches the host limit)
+pub const MAX_HEADER_NAME_LENGTH: usize = 256;
+
+/// Why a header change was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The name is empty, too long, or not an RFC 7230 token
+    InvalidName(String),
+    /// The value contains control characters (e.g. CR/LF)
+    InvalidValue(String),
+    /// The header is in `PROTECTED_HEADERS`; use `dangerously_set`
+    Protected(String),
+    /// The host rejected the header
+    Host(String),
+}
+
+/// Ordered header list with case-insensitive lookups
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+/// Canonical form of a header name: each `-`-separated part capitalized
+pub fn canonical_name(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Look up an inbound request header via the host (case-insensitive)
+pub fn request_header(name: &str) -> Option<String> {
+    let len = unsafe { host::request_get_header(name.as_ptr(), name.len() as u32) };
+    read_shared_buffer(len).and_then(|v| String::from_utf8(v).ok())
+}
+
+fn is_protected(name: &str) -> bool {
+    PROTECTED_HEADERS.iter().any(|p| p.eq_ignore_ascii_case(name))
+}
+
+fn validate(name: &str, value: &str) -> Result<(), HeaderError> {
+    let valid_name = !name.is_empty()
+        && name.len() <= MAX_HEADER_NAME_LENGTH
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+    if !valid_name {
+        return Err(HeaderError::InvalidName(name.to_string()));
+    }
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(HeaderError::InvalidValue(name.to_string()));
+    }
+    Ok(())
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the host's JSON header list, canonicalizing names
+    pub fn from_json(json: &[u8]) -> Result<Self, serde_json::Error> {
+        let entries: Vec<(String, String)> = serde_json::from_slice(json)?;
+        Ok(Self {
+            entries: entries.into_iter().map(|(n, v)| (canonical_name(&n), v)).collect(),
+        })
+    }
+
+    /// Serialize as the host's JSON header list
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.entries).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// First value of a header
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.get_all(name).into_iter().next()
+    }
+
+    /// Every value of a header, in order
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// Replace every value of a header with `value`
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), HeaderError> {
+        if is_protected(name) {
+            return Err(HeaderError::Protected(name.to_string()));
+        }
+        self.dangerously_set(name, value)
+    }
+
+    /// Add a value, keeping existing ones
+    pub fn append(&mut self, name: &str, value: &str) -> Result<(), HeaderError> {
+        if is_protected(name) {
+            return Err(HeaderError::Protected(name.to_string()));
+        }
+        validate(name, value)?;
+        self.entries.push((canonical_name(name), value.to_string()));
+        Ok(())
+    }
+
+    /// Remove every value of a header, returning how many were removed
+    pub fn remove(&mut self, name: &str) -> Result<usize, HeaderError> {
+        if is_protected(name) {
+            return Err(HeaderError::Protected(name.to_string()));
+        }
+        let before = self.entries.len();
+        self.entries.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        Ok(before - self.entries.len())
+    }
+
+    /// `set` without the protected-header check
+    ///
+    /// Only for functions that really own message framing or routing; values
+    /// are still checked for control characters.
+    pub fn dangerously_set(&mut self, name: &str, value: &str) -> Result<(), HeaderError> {
+        validate(name, value)?;
+        self.entries.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.entries.push((canonical_name(name), value.to_string()));
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Send these headers to the host as response headers
+    ///
+    /// The first value of each name replaces whatever the response already
+    /// had; later values are added alongside it.
+    pub fn apply_to_response(&self) -> Result<(), HeaderError> {
+        let mut sent: Vec<&str> = Vec::new();
+        for (name, value) in self.iter() {
+            let first = !sent.iter().any(|s| s.eq_ignore_ascii_case(name));
+            let result = unsafe {
+                if first {
+                    host::response_set_header(name.as_ptr(), name.len() as u32, value.as_ptr(), value.len() as u32)
+                } else {
+                    host::response_add_header(name.as_ptr(), name.len() as u32, value.as_ptr(), value.len() as u32)
+                }
+            };
+            if result < 0 {
+                return Err(HeaderError::Host(name.to_string()));
+            }
+            sent.push(name);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    #[test]
+    fn test_rejects_injection() {
+        let mut headers = Headers::new();
+        assert_eq!(
+            headers.set("X-Receipt", "abc\r\nSet-Cookie: session=evil"),
+            Err(HeaderError::InvalidValue("X-Receipt".to_string()))
+        );
+        assert!(matches!(headers.append("X-Receipt", "a\nb"), Err(HeaderError::InvalidValue(_))));
+        assert!(matches!(headers.set("X-Receipt", "a\0b"), Err(HeaderError::InvalidValue(_))));
+        assert!(matches!(headers.dangerously_set("Host", "a\rb"), Err(HeaderError::InvalidValue(_))));
+        assert!(matches!(headers.set("Bad Name", "v"), Err(HeaderError::InvalidName(_))));
+        assert!(matches!(headers.set("X-A\r\nX-B", "v"), Err(HeaderError::InvalidName(_))));
+        assert!(matches!(headers.set("", "v"), Err(HeaderError::InvalidName(_))));
+        assert!(headers.is_empty());
+
+        // Tabs are legal whitespace in values
+        headers.set("X-Note", "a\tb").unwrap();
+    }
+
+    #[test]
+    fn test_protected_headers() {
+        let mut headers = Headers::from_json(br#"[["host","example.com"],["content-length","12"]]"#).unwrap();
+        for name in ["Host", "content-length", "TRANSFER-ENCODING", "Connection"] {
+            assert_eq!(headers.set(name, "x"), Err(HeaderError::Protected(name.to_string())));
+            assert!(matches!(headers.append(name, "x"), Err(HeaderError::Protected(_))));
+            assert!(matches!(headers.remove(name), Err(HeaderError::Protected(_))));
+        }
+        assert_eq!(headers.get("Host"), Some("example.com"));
+
+        headers.dangerously_set("host", "origin.internal").unwrap();
+        assert_eq!(headers.get_all("Host"), vec!["origin.internal"]);
+    }
+
+    #[test]
+    fn test_case_insensitive_get() {
+        let mut headers = Headers::from_json(br#"[["x-forwarded-for","1.1.1.1"],["Set-Cookie","a=1"]]"#).unwrap();
+        headers.append("set-cookie", "b=2").unwrap();
+
+        assert_eq!(headers.get("X-FORWARDED-FOR"), Some("1.1.1.1"));
+        assert_eq!(headers.get_all("SET-COOKIE"), vec!["a=1", "b=2"]);
+        assert_eq!(headers.get("missing"), None);
+
+        headers.set("SET-COOKIE", "c=3").unwrap();
+        assert_eq!(headers.get_all("set-cookie"), vec!["c=3"]);
+        assert_eq!(headers.remove("Set-cookie"), Ok(1));
+        assert_eq!(headers.remove("Set-cookie"), Ok(0));
+    }
+
+    #[test]
+    fn test_canonical_names() {
+        assert_eq!(canonical_name("content-type"), "Content-Type");
+        assert_eq!(canonical_name("X-REQUEST-ID"), "X-Request-Id");
+        assert_eq!(canonical_name("etag"), "Etag");
+
+        let headers = Headers::from_json(br#"[["cache-control","no-store"]]"#).unwrap();
+        assert_eq!(headers.to_json(), r#"[["Cache-Control","no-store"]]"#);
+    }
+
+    #[test]
+    fn test_apply_to_response() {
+        let mut headers = Headers::new();
+        headers.set("cache-control", "no-store").unwrap();
+        headers.append("Set-Cookie", "a=1").unwrap();
+        headers.append("Set-Cookie", "b=2").unwrap();
+        with_host(|h| h.response_headers.push(("Set-Cookie".to_string(), "stale=1".to_string())));
+
+        headers.apply_to_response().unwrap();
+        let sent = with_host(|h| h.response_headers.clone());
+        assert_eq!(
+            sent,
+            vec![
+                ("Cache-Control".to_string(), "no-store".to_string()),
+                ("Set-Cookie".to_string(), "a=1".to_string()),
+                ("Set-Cookie".to_string(), "b=2".to_string()),
+            ]
+        );
+    }
+}
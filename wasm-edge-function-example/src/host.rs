@@ -0,0 +1,112 @@
+//! Host API imports
+//!
+//! On wasm32 these resolve to the functions registered by the AEGIS node's
+//! Wasm runtime. Under `cfg(test)` they are replaced by the in-process mock
+//! host from `aegis-edge-testkit` so the SDK wrappers can be unit tested
+//! natively.
+
+#[cfg(not(test))]
+extern "C" {
+    /// Log a message to the host
+    pub fn log(ptr: *const u8, len: u32);
+
+    /// Get a value from cache
+    /// Returns the length of the value (stored in shared buffer), or -1 if not found
+    pub fn cache_get(key_ptr: *const u8, key_len: u32) -> i32;
+
+    /// Set a value in cache
+    /// Returns 0 on success, -1 on error
+    pub fn cache_set(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32, ttl: u32) -> i32;
+
+    /// Make an HTTP GET request
+    /// Returns the length of the response (stored in shared buffer), or -1 on error
+    pub fn http_get(url_ptr: *const u8, url_len: u32) -> i32;
+
+    /// Get the headers of the most recent http_get response
+    /// Returns the length of a JSON array of [name, value] pairs (stored in shared buffer), or -1 on error
+    pub fn http_get_response_headers() -> i32;
+
+    /// Make an HTTP POST request
+    /// Returns the length of the response (stored in shared buffer), or -1 on error
+    pub fn http_post(
+        url_ptr: *const u8,
+        url_len: u32,
+        body_ptr: *const u8,
+        body_len: u32,
+        content_type_ptr: *const u8,
+        content_type_len: u32,
+    ) -> i32;
+
+    /// Get data from shared buffer
+    /// Returns number of bytes copied, or -1 on error
+    pub fn get_shared_buffer(dest_ptr: *mut u8, offset: u32, length: u32) -> i32;
+
+    /// Get the inbound request URI (path)
+    /// Returns the length of the URI (stored in shared buffer), or -1 on error
+    pub fn request_get_uri() -> i32;
+
+    /// Get an inbound request header (case-insensitive)
+    /// Returns the length of the value (stored in shared buffer), or -1 if not found
+    pub fn request_get_header(name_ptr: *const u8, name_len: u32) -> i32;
+
+    /// Set the response body
+    /// Returns 0 on success, -1 on error
+    pub fn response_set_body(body_ptr: *const u8, body_len: u32) -> i32;
+
+    /// Set (replace) a response header
+    /// Returns 0 on success, -1 on error (invalid name or CR/LF in value)
+    pub fn response_set_header(name_ptr: *const u8, name_len: u32, value_ptr: *const u8, value_len: u32) -> i32;
+
+    /// Add a response header, keeping existing values
+    /// Returns 0 on success, -1 on error (invalid name or CR/LF in value)
+    pub fn response_add_header(name_ptr: *const u8, name_len: u32, value_ptr: *const u8, value_len: u32) -> i32;
+
+    /// Start a streamed response with a status and a JSON array of [name, value] header pairs
+    /// Returns 0 on success, or a negative stream error code
+    pub fn response_start(status: u32, headers_ptr: *const u8, headers_len: u32) -> i32;
+
+    /// Send a body chunk of a streamed response
+    /// Returns 0 on success, or a negative stream error code (-4: would block, retry later)
+    pub fn response_write(chunk_ptr: *const u8, chunk_len: u32) -> i32;
+
+    /// Finish a streamed response
+    /// Returns 0 on success, or a negative stream error code
+    pub fn response_end() -> i32;
+
+    /// Get a value from durable KV storage
+    /// Returns the length of the value (stored in shared buffer), or a negative KV error code
+    pub fn kv_get(key_ptr: *const u8, key_len: u32) -> i32;
+
+    /// Store a value in durable KV storage
+    /// Returns 0 on success, or a negative KV error code
+    pub fn kv_put(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32) -> i32;
+
+    /// Delete a key from durable KV storage
+    /// Returns 1 if the key existed, 0 if not, or a negative KV error code
+    pub fn kv_delete(key_ptr: *const u8, key_len: u32) -> i32;
+
+    /// List KV keys by prefix, one page at a time (cursor_len = 0 for the first page)
+    /// Returns the length of a JSON page (stored in shared buffer), or a negative KV error code
+    pub fn kv_list_prefix(
+        prefix_ptr: *const u8,
+        prefix_len: u32,
+        cursor_ptr: *const u8,
+        cursor_len: u32,
+        limit: u32,
+    ) -> i32;
+
+    /// Get metadata about the current invocation
+    /// Returns the length of a JSON object (stored in shared buffer), or -1 on error
+    pub fn get_invocation_context() -> i32;
+
+    /// Get the tenant's quota usage for the current billing period
+    /// Returns the length of a JSON object (stored in shared buffer), or -1 if usage is unavailable
+    pub fn get_usage() -> i32;
+
+    /// Get what the node knows about the client (IP, GeoIP, ASN, TLS details)
+    /// Returns the length of a JSON object (stored in shared buffer), or -1 if unavailable
+    pub fn get_client_info() -> i32;
+}
+
+#[cfg(test)]
+aegis_edge_testkit::bind_mock_host!();
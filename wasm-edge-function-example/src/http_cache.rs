@@ -0,0 +1,489 @@
+//! Response caching decisions for edge functions
+//!
+//! `cache_policy()` applies RFC 9111 semantics from the point of view of a
+//! shared (edge) cache, so functions proxying origin responses don't have to
+//! hand-roll Cache-Control parsing:
+//!
+//! - `no-store` beats everything, including explicit freshness
+//! - `private` responses are never stored at the edge
+//! - `no-cache` requires revalidation on every use, which the edge cache
+//!   cannot do, so such responses are not stored
+//! - `s-maxage` beats `max-age`, which beats `Expires`
+//! - `Vary: *` makes a response uncacheable
+//! - Responses without explicit freshness are not cached (no heuristic TTLs)
+//!
+//! `fetch_through()` wires the policy into an origin fetch so responses are
+//! only stored when allowed and are keyed by their Vary dimensions. Bodies
+//! over `cache::COMPRESSION_THRESHOLD` are stored compressed.
+
+use crate::cache::{cache_get_compressed, cache_get_raw, cache_set_compressed, cache_set_raw};
+use crate::headers::request_header;
+use crate::{host, log_message, read_shared_buffer};
+
+/// Caching decision for an origin response
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CachePolicy {
+    /// Whether the edge may store the response
+    pub cacheable: bool,
+    /// Freshness lifetime in seconds (0 when not cacheable)
+    pub ttl: u32,
+    /// Lowercased request header names the response varies on
+    pub vary_keys: Vec<String>,
+}
+
+impl CachePolicy {
+    fn uncacheable(vary_keys: Vec<String>) -> Self {
+        Self {
+            cacheable: false,
+            ttl: 0,
+            vary_keys,
+        }
+    }
+}
+
+/// Parsed Cache-Control directives relevant to a shared cache
+#[derive(Debug, Default)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    max_age: Option<Option<u64>>,
+    s_maxage: Option<Option<u64>>,
+}
+
+/// Parse a delta-seconds directive value; `None` if malformed
+fn parse_delta_seconds(value: Option<&str>) -> Option<u64> {
+    let value = value?.trim().trim_matches('"');
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Values too large to represent saturate rather than fail (RFC 9111 1.2.2)
+    Some(value.parse::<u64>().unwrap_or(u64::MAX))
+}
+
+/// Split a header value on commas that are not inside quoted strings
+fn split_list(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn parse_directives<'a>(values: impl Iterator<Item = &'a str>) -> Directives {
+    let mut d = Directives::default();
+    for value in values {
+        for directive in split_list(value) {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                continue;
+            }
+            let (name, arg) = match directive.split_once('=') {
+                Some((n, a)) => (n.trim(), Some(a)),
+                None => (directive, None),
+            };
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => d.no_store = true,
+                "no-cache" => d.no_cache = true,
+                // Both bare and field-qualified private forbid shared storage here
+                "private" => d.private = true,
+                // First occurrence wins for duplicated durations
+                "max-age" if d.max_age.is_none() => d.max_age = Some(parse_delta_seconds(arg)),
+                "s-maxage" if d.s_maxage.is_none() => d.s_maxage = Some(parse_delta_seconds(arg)),
+                _ => {}
+            }
+        }
+    }
+    d
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parse an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`) into Unix seconds
+///
+/// Obsolete RFC 850 and asctime formats are not accepted; an unparseable
+/// date is treated as already expired by callers.
+fn parse_http_date(value: &str) -> Option<i64> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != 6 || !parts[0].ends_with(',') || parts[5] != "GMT" {
+        return None;
+    }
+
+    let day: i64 = parts[1].parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == parts[2])? as i64 + 1;
+    let year: i64 = parts[3].parse().ok()?;
+
+    let hms: Vec<i64> = parts[4]
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if hms.len() != 3 || !(1..=31).contains(&day) || hms[0] > 23 || hms[1] > 59 || hms[2] > 60 {
+        return None;
+    }
+
+    // Days since epoch (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = (if y >= 0 { y } else { y - 399 }) / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Some(days * 86_400 + hms[0] * 3600 + hms[1] * 60 + hms[2])
+}
+
+/// All values of a header, matched case-insensitively
+fn header_values<'a>(headers: &'a [(String, String)], name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    headers
+        .iter()
+        .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Decide whether and for how long an origin response may be cached at the edge
+///
+/// Header names are matched case-insensitively; repeated Cache-Control and
+/// Vary headers are combined as if comma-joined.
+pub fn cache_policy(headers: &[(String, String)]) -> CachePolicy {
+    let values = |name: &'static str| header_values(headers, name);
+
+    let mut vary_keys: Vec<String> = Vec::new();
+    let mut vary_star = false;
+    for value in values("vary") {
+        for key in value.split(',') {
+            let key = key.trim().to_ascii_lowercase();
+            if key == "*" {
+                vary_star = true;
+            } else if !key.is_empty() && !vary_keys.contains(&key) {
+                vary_keys.push(key);
+            }
+        }
+    }
+
+    let d = parse_directives(values("cache-control"));
+
+    if d.no_store || d.private || d.no_cache || vary_star {
+        return CachePolicy::uncacheable(vary_keys);
+    }
+
+    // Freshness lifetime: s-maxage > max-age > Expires - Date
+    let lifetime = if let Some(s_maxage) = d.s_maxage {
+        s_maxage.unwrap_or(0)
+    } else if let Some(max_age) = d.max_age {
+        max_age.unwrap_or(0)
+    } else if let Some(expires) = values("expires").next() {
+        // Without a Date header there is no reference clock in the sandbox
+        match (parse_http_date(expires), values("date").next().and_then(parse_http_date)) {
+            (Some(expires), Some(date)) if expires > date => (expires - date) as u64,
+            _ => 0,
+        }
+    } else {
+        0
+    };
+
+    let age = values("age").next().and_then(|a| parse_delta_seconds(Some(a))).unwrap_or(0);
+    let ttl = lifetime.saturating_sub(age).min(u32::MAX as u64) as u32;
+
+    CachePolicy {
+        cacheable: ttl > 0,
+        ttl,
+        vary_keys,
+    }
+}
+
+/// Headers of the most recent `http_get` response
+fn last_response_headers() -> Vec<(String, String)> {
+    let len = unsafe { host::http_get_response_headers() };
+    read_shared_buffer(len)
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+fn vary_index_key(url: &str) -> String {
+    format!("fetch:vary:{}", url)
+}
+
+/// Cache key for a URL, extended with the request's value for each Vary header
+fn variant_key(url: &str, vary_keys: &[String]) -> String {
+    let mut key = format!("fetch:{}", url);
+    for name in vary_keys {
+        key.push('|');
+        key.push_str(name);
+        key.push('=');
+        key.push_str(&request_header(name).unwrap_or_default());
+    }
+    key
+}
+
+/// Fetch a URL through the edge cache, honoring the origin's caching policy
+///
+/// On a miss the origin response is stored only if `cache_policy()` allows
+/// it, for the policy's TTL, under a key that includes the inbound request's
+/// values for every Vary header. The Vary header list itself is remembered
+/// per URL so later requests can compute the right variant key before
+/// contacting the origin. Bodies larger than 8KB are compressed in the cache
+/// (see `cache::cache_set_compressed()`).
+pub fn fetch_through(url: &str) -> Option<Vec<u8>> {
+    let vary_keys: Vec<String> = cache_get_raw(&vary_index_key(url))
+        .and_then(|v| String::from_utf8(v).ok())
+        .map(|v| v.split('\n').filter(|k| !k.is_empty()).map(String::from).collect())
+        .unwrap_or_default();
+
+    if let Some(cached) = cache_get_compressed(&variant_key(url, &vary_keys)) {
+        return Some(cached);
+    }
+
+    let len = unsafe { host::http_get(url.as_ptr(), url.len() as u32) };
+    let body = read_shared_buffer(len)?;
+
+    let policy = cache_policy(&last_response_headers());
+    if !policy.cacheable {
+        log_message(&format!("fetch_through: not caching {} (policy forbids)", url));
+        return Some(body);
+    }
+
+    if !policy.vary_keys.is_empty() {
+        cache_set_raw(&vary_index_key(url), policy.vary_keys.join("\n").as_bytes(), policy.ttl);
+    }
+    if !cache_set_compressed(&variant_key(url, &policy.vary_keys), &body, policy.ttl) {
+        log_message(&format!("fetch_through: failed to cache {}", url));
+    }
+
+    Some(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+    }
+
+    fn policy(pairs: &[(&str, &str)]) -> CachePolicy {
+        cache_policy(&headers(pairs))
+    }
+
+    const DATE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    #[test]
+    fn test_max_age() {
+        let p = policy(&[("Cache-Control", "public, max-age=300")]);
+        assert!(p.cacheable);
+        assert_eq!(p.ttl, 300);
+    }
+
+    #[test]
+    fn test_no_store_beats_everything() {
+        let p = policy(&[
+            ("Cache-Control", "public, s-maxage=600, max-age=300, no-store"),
+            ("Expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+            ("Date", DATE),
+        ]);
+        assert!(!p.cacheable);
+        assert_eq!(p.ttl, 0);
+    }
+
+    #[test]
+    fn test_no_store_in_separate_header() {
+        let p = policy(&[("Cache-Control", "max-age=300"), ("cache-control", "no-store")]);
+        assert!(!p.cacheable);
+    }
+
+    #[test]
+    fn test_private_not_cached_at_edge() {
+        assert!(!policy(&[("Cache-Control", "private, max-age=300")]).cacheable);
+        assert!(!policy(&[("Cache-Control", "private=\"Set-Cookie\", s-maxage=300")]).cacheable);
+    }
+
+    #[test]
+    fn test_no_cache_not_stored() {
+        assert!(!policy(&[("Cache-Control", "no-cache, max-age=300")]).cacheable);
+    }
+
+    #[test]
+    fn test_s_maxage_beats_max_age() {
+        assert_eq!(policy(&[("Cache-Control", "max-age=60, s-maxage=600")]).ttl, 600);
+        assert_eq!(policy(&[("Cache-Control", "s-maxage=10, max-age=600")]).ttl, 10);
+    }
+
+    #[test]
+    fn test_s_maxage_zero_overrides_max_age() {
+        let p = policy(&[("Cache-Control", "max-age=600, s-maxage=0")]);
+        assert!(!p.cacheable);
+    }
+
+    #[test]
+    fn test_max_age_beats_expires() {
+        let p = policy(&[
+            ("Cache-Control", "max-age=30"),
+            ("Expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+            ("Date", DATE),
+        ]);
+        assert_eq!(p.ttl, 30);
+    }
+
+    #[test]
+    fn test_expires_relative_to_date() {
+        let p = policy(&[("Expires", "Sun, 06 Nov 1994 09:49:37 GMT"), ("Date", DATE)]);
+        assert!(p.cacheable);
+        assert_eq!(p.ttl, 3600);
+    }
+
+    #[test]
+    fn test_expires_in_past_or_invalid() {
+        assert!(!policy(&[("Expires", "Sat, 05 Nov 1994 08:49:37 GMT"), ("Date", DATE)]).cacheable);
+        assert!(!policy(&[("Expires", "0"), ("Date", DATE)]).cacheable);
+        assert!(!policy(&[("Expires", "-1"), ("Date", DATE)]).cacheable);
+    }
+
+    #[test]
+    fn test_expires_without_date_not_cached() {
+        assert!(!policy(&[("Expires", "Sun, 06 Nov 1994 09:49:37 GMT")]).cacheable);
+    }
+
+    #[test]
+    fn test_no_explicit_freshness_not_cached() {
+        assert!(!policy(&[("Content-Type", "text/html")]).cacheable);
+        assert!(!policy(&[("Cache-Control", "public")]).cacheable);
+    }
+
+    #[test]
+    fn test_malformed_max_age_is_stale() {
+        assert!(!policy(&[("Cache-Control", "max-age=abc")]).cacheable);
+        assert!(!policy(&[("Cache-Control", "max-age=-5")]).cacheable);
+        assert!(!policy(&[("Cache-Control", "max-age")]).cacheable);
+    }
+
+    #[test]
+    fn test_quoted_max_age_accepted() {
+        assert_eq!(policy(&[("Cache-Control", "max-age=\"120\"")]).ttl, 120);
+    }
+
+    #[test]
+    fn test_first_duplicate_directive_wins() {
+        assert_eq!(policy(&[("Cache-Control", "max-age=60, max-age=600")]).ttl, 60);
+    }
+
+    #[test]
+    fn test_directives_case_insensitive() {
+        assert!(!policy(&[("CACHE-CONTROL", "No-Store")]).cacheable);
+        assert_eq!(policy(&[("Cache-Control", "S-MAXAGE=90")]).ttl, 90);
+    }
+
+    #[test]
+    fn test_quoted_comma_does_not_split() {
+        let p = policy(&[("Cache-Control", "community=\"a, no-store\", max-age=60")]);
+        assert!(p.cacheable);
+        assert_eq!(p.ttl, 60);
+    }
+
+    #[test]
+    fn test_age_reduces_ttl() {
+        assert_eq!(policy(&[("Cache-Control", "max-age=300"), ("Age", "100")]).ttl, 200);
+        assert!(!policy(&[("Cache-Control", "max-age=300"), ("Age", "400")]).cacheable);
+    }
+
+    #[test]
+    fn test_huge_max_age_saturates() {
+        let p = policy(&[("Cache-Control", "max-age=99999999999999999999999")]);
+        assert_eq!(p.ttl, u32::MAX);
+    }
+
+    #[test]
+    fn test_vary_star_uncacheable() {
+        let p = policy(&[("Cache-Control", "max-age=300"), ("Vary", "Accept-Encoding, *")]);
+        assert!(!p.cacheable);
+    }
+
+    #[test]
+    fn test_vary_keys_normalized() {
+        let p = policy(&[
+            ("Cache-Control", "max-age=300"),
+            ("Vary", "Accept-Encoding, Accept-Language"),
+            ("vary", "accept-encoding, Cookie"),
+        ]);
+        assert_eq!(p.vary_keys, vec!["accept-encoding", "accept-language", "cookie"]);
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(parse_http_date(DATE), Some(784_111_777));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), None);
+    }
+
+    const ORIGIN: &str = "https://origin.example/data";
+
+    fn serve_origin(body: &[u8], response_headers: &[(&str, &str)]) {
+        with_host(|h| {
+            h.http_responses.insert(ORIGIN.to_string(), body.to_vec());
+            h.http_response_headers.insert(ORIGIN.to_string(), headers(response_headers));
+        });
+    }
+
+    #[test]
+    fn test_fetch_through_caches_when_allowed() {
+        serve_origin(b"fresh", &[("Cache-Control", "s-maxage=60")]);
+
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"fresh");
+        with_host(|h| h.http_responses.clear());
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"fresh");
+    }
+
+    #[test]
+    fn test_fetch_through_skips_no_store() {
+        serve_origin(b"secret", &[("Cache-Control", "no-store")]);
+
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"secret");
+        assert!(with_host(|h| h.cache.is_empty()));
+    }
+
+    #[test]
+    fn test_fetch_through_keys_by_vary() {
+        serve_origin(b"en body", &[("Cache-Control", "max-age=60"), ("Vary", "Accept-Language")]);
+        with_host(|h| h.request_headers = headers(&[("Accept-Language", "en")]));
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"en body");
+
+        // A different language must not be served the cached English variant
+        serve_origin(b"fr body", &[("Cache-Control", "max-age=60"), ("Vary", "Accept-Language")]);
+        with_host(|h| h.request_headers = headers(&[("Accept-Language", "fr")]));
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"fr body");
+
+        // Both variants are now cached
+        with_host(|h| {
+            h.http_responses.clear();
+            h.request_headers = headers(&[("Accept-Language", "en")]);
+        });
+        assert_eq!(fetch_through(ORIGIN).unwrap(), b"en body");
+    }
+
+    #[test]
+    fn test_fetch_through_compresses_large_bodies() {
+        let body = "{\"rate\":1.0842},".repeat(1024).into_bytes();
+        assert!(body.len() > crate::cache::COMPRESSION_THRESHOLD);
+        serve_origin(&body, &[("Cache-Control", "max-age=60")]);
+
+        assert_eq!(fetch_through(ORIGIN).unwrap(), body);
+        let stored = with_host(|h| h.cache[&variant_key(ORIGIN, &[])].clone());
+        assert!(stored.len() < body.len() / 4, "{} of {} bytes", stored.len(), body.len());
+
+        with_host(|h| h.http_responses.clear());
+        assert_eq!(fetch_through(ORIGIN).unwrap(), body);
+    }
+}
@@ -0,0 +1,287 @@
+//! Idempotency keys for side-effecting requests
+//!
+//! The host retries an invocation that times out, so a function that POSTs
+//! to a payment or notification API can repeat the side effect.
+//! `idempotent_fetch()` records each attempt in the cache under the caller's
+//! idempotency key:
+//!
+//! - Before sending, an in-flight marker with a short TTL (`IN_FLIGHT_TTL`)
+//! - Once the response arrives, the response itself for the caller's TTL
+//!
+//! A retry that finds a stored response gets it back without the request
+//! being sent again. A retry that finds only the marker can't know whether
+//! the earlier attempt reached the origin (it may have crashed or timed out
+//! between sending and storing), so it gets `InFlightUnknown` and must decide
+//! for itself: ask the origin, tell the client to retry later, or give up.
+//! Once the marker expires the key is free and the next attempt sends again.
+//!
+//! The cache has no compare-and-set, so two attempts racing on the same key
+//! can both send. This guards against sequential retries, which is what the
+//! host does.
+
+use serde_json::Value;
+
+use crate::cache::{cache_get_raw, cache_set_raw};
+use crate::logging::{log_structured, LogLevel};
+use crate::{host, read_shared_buffer};
+
+/// How long an in-flight marker blocks retries (seconds)
+///
+/// Well past the host's outbound request timeout, so the marker outlives
+/// any attempt still waiting on the origin.
+pub const IN_FLIGHT_TTL: u32 = 60;
+
+/// Entry tag: request sent, response not yet stored
+const TAG_IN_FLIGHT: u8 = b'P';
+
+/// Entry tag: response stored
+const TAG_DONE: u8 = b'D';
+
+/// Tag byte plus request fingerprint
+const ENTRY_HEADER_LEN: usize = 9;
+
+/// A side-effecting HTTP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// A POST of `body` to `url`
+    pub fn post(url: &str, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            url: url.to_string(),
+            content_type: content_type.to_string(),
+            body: body.into(),
+        }
+    }
+
+    /// FNV-1a hash of the request, to catch a key reused for a different request
+    fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for part in [self.url.as_bytes(), self.content_type.as_bytes(), &self.body] {
+            for &byte in part.iter().chain(&[0]) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    fn send(&self) -> Option<Vec<u8>> {
+        let len = unsafe {
+            host::http_post(
+                self.url.as_ptr(),
+                self.url.len() as u32,
+                self.body.as_ptr(),
+                self.body.len() as u32,
+                self.content_type.as_ptr(),
+                self.content_type.len() as u32,
+            )
+        };
+        read_shared_buffer(len)
+    }
+}
+
+/// Outcome of `idempotent_fetch()`
+#[must_use = "InFlightUnknown must be handled explicitly"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotentResult {
+    /// The request was sent by this call
+    Fresh(Vec<u8>),
+    /// An earlier attempt completed; this is its stored response
+    Replayed(Vec<u8>),
+    /// An earlier attempt sent the request but its response was never
+    /// stored, so it may or may not have reached the origin
+    InFlightUnknown,
+}
+
+/// Why `idempotent_fetch()` couldn't produce a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyError {
+    /// The in-flight marker couldn't be written; nothing was sent
+    CacheUnavailable,
+    /// The request was sent but failed or timed out; the marker stays, so
+    /// retries see `InFlightUnknown` until it expires
+    RequestFailed,
+    /// The key was already used for a different request
+    KeyConflict,
+}
+
+fn entry_key(key: &str) -> String {
+    format!("idempotency:{}", key)
+}
+
+fn encode_entry(tag: u8, fingerprint: u64, body: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(ENTRY_HEADER_LEN + body.len());
+    entry.push(tag);
+    entry.extend_from_slice(&fingerprint.to_le_bytes());
+    entry.extend_from_slice(body);
+    entry
+}
+
+/// Split a stored entry into (tag, fingerprint, body)
+fn decode_entry(entry: &[u8]) -> Option<(u8, u64, &[u8])> {
+    let fingerprint = u64::from_le_bytes(entry.get(1..ENTRY_HEADER_LEN)?.try_into().ok()?);
+    Some((entry[0], fingerprint, &entry[ENTRY_HEADER_LEN..]))
+}
+
+/// Send `request` at most once per idempotency `key`
+///
+/// The response is kept for `ttl` seconds (0 for no expiry); retries within
+/// that window get `Replayed` with the stored body. An unreadable entry is
+/// treated as absent. If the response can't be stored the call still
+/// returns `Fresh`, but retries see `InFlightUnknown` until the marker
+/// expires.
+pub fn idempotent_fetch(key: &str, request: &HttpRequest, ttl: u32) -> Result<IdempotentResult, IdempotencyError> {
+    let cache_key = entry_key(key);
+    let fingerprint = request.fingerprint();
+
+    if let Some(entry) = cache_get_raw(&cache_key) {
+        match decode_entry(&entry) {
+            Some((_, stored, _)) if stored != fingerprint => return Err(IdempotencyError::KeyConflict),
+            Some((TAG_DONE, _, body)) => return Ok(IdempotentResult::Replayed(body.to_vec())),
+            Some((TAG_IN_FLIGHT, _, _)) => return Ok(IdempotentResult::InFlightUnknown),
+            _ => log_structured(
+                LogLevel::Warn,
+                "Ignoring unreadable idempotency entry",
+                &[("key", Value::from(key))],
+            ),
+        }
+    }
+
+    if !cache_set_raw(&cache_key, &encode_entry(TAG_IN_FLIGHT, fingerprint, &[]), IN_FLIGHT_TTL) {
+        return Err(IdempotencyError::CacheUnavailable);
+    }
+
+    let response = request.send().ok_or(IdempotencyError::RequestFailed)?;
+
+    if !cache_set_raw(&cache_key, &encode_entry(TAG_DONE, fingerprint, &response), ttl) {
+        log_structured(
+            LogLevel::Warn,
+            "Failed to store idempotent response, retries will be ambiguous",
+            &[("key", Value::from(key))],
+        );
+    }
+    Ok(IdempotentResult::Fresh(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::{with_host, Call, Reply};
+
+    const URL: &str = "https://payments.example/charges";
+    const KEY: &str = "order-42";
+
+    fn charge() -> HttpRequest {
+        HttpRequest::post(URL, "application/json", br#"{"amount":1000}"#.to_vec())
+    }
+
+    fn serve(body: &[u8]) {
+        with_host(|h| h.http_responses.insert(URL.to_string(), body.to_vec()));
+    }
+
+    fn posts() -> usize {
+        with_host(|h| h.calls.iter().filter(|c| matches!(c, Call::HttpPost(_))).count())
+    }
+
+    #[test]
+    fn test_first_call_sends_and_stores() {
+        serve(br#"{"id":"ch_1"}"#);
+        with_host(|h| {
+            h.expect(Call::cache_get(entry_key(KEY)));
+            h.expect(Call::cache_set(entry_key(KEY), IN_FLIGHT_TTL));
+            h.expect(Call::http_post(URL));
+            h.expect(Call::cache_set(entry_key(KEY), 3600));
+        });
+
+        let result = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+        assert_eq!(result, IdempotentResult::Fresh(br#"{"id":"ch_1"}"#.to_vec()));
+        with_host(|h| {
+            h.assert_expectations();
+            let (url, content_type, body) = &h.http_posts[0];
+            assert_eq!((url.as_str(), content_type.as_str()), (URL, "application/json"));
+            assert_eq!(body, br#"{"amount":1000}"#);
+        });
+    }
+
+    #[test]
+    fn test_retry_replays_stored_response() {
+        serve(br#"{"id":"ch_1"}"#);
+        let _ = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+
+        // The origin would now answer differently; the retry must not ask it
+        serve(br#"{"id":"ch_2"}"#);
+        let result = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+        assert_eq!(result, IdempotentResult::Replayed(br#"{"id":"ch_1"}"#.to_vec()));
+        assert_eq!(posts(), 1);
+
+        // Once the response expires the key is free again
+        with_host(|h| h.advance(3600));
+        let result = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+        assert_eq!(result, IdempotentResult::Fresh(br#"{"id":"ch_2"}"#.to_vec()));
+        assert_eq!(posts(), 2);
+    }
+
+    #[test]
+    fn test_crash_before_store_is_in_flight_unknown() {
+        serve(br#"{"id":"ch_1"}"#);
+        // The response store fails, as if the instance died right after sending
+        with_host(|h| {
+            h.expect(Call::cache_set(entry_key(KEY), 3600)).unordered().returning(Reply::Code(-1));
+        });
+
+        let _ = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+        assert_eq!(idempotent_fetch(KEY, &charge(), 3600).unwrap(), IdempotentResult::InFlightUnknown);
+        assert_eq!(posts(), 1);
+        assert_eq!(with_host(|h| h.logs_at("warn").len()), 1);
+
+        // The marker is short-lived: after it expires the request goes out again
+        with_host(|h| h.advance(u64::from(IN_FLIGHT_TTL)));
+        assert!(matches!(idempotent_fetch(KEY, &charge(), 3600).unwrap(), IdempotentResult::Fresh(_)));
+        assert_eq!(posts(), 2);
+    }
+
+    #[test]
+    fn test_timed_out_request_leaves_marker() {
+        with_host(|h| {
+            h.expect(Call::http_post(URL)).returning(Reply::Timeout);
+        });
+
+        assert_eq!(idempotent_fetch(KEY, &charge(), 3600), Err(IdempotencyError::RequestFailed));
+        assert_eq!(idempotent_fetch(KEY, &charge(), 3600).unwrap(), IdempotentResult::InFlightUnknown);
+        assert_eq!(posts(), 1);
+    }
+
+    #[test]
+    fn test_marker_write_failure_sends_nothing() {
+        serve(b"ok");
+        with_host(|h| {
+            h.expect(Call::cache_set(entry_key(KEY), IN_FLIGHT_TTL)).returning(Reply::Code(-1));
+        });
+
+        assert_eq!(idempotent_fetch(KEY, &charge(), 3600), Err(IdempotencyError::CacheUnavailable));
+        assert_eq!(posts(), 0);
+    }
+
+    #[test]
+    fn test_key_reused_for_different_request() {
+        serve(b"ok");
+        let _ = idempotent_fetch(KEY, &charge(), 3600).unwrap();
+
+        let other = HttpRequest::post(URL, "application/json", br#"{"amount":5000}"#.to_vec());
+        assert_eq!(idempotent_fetch(KEY, &other, 3600), Err(IdempotencyError::KeyConflict));
+        assert_eq!(posts(), 1);
+    }
+
+    #[test]
+    fn test_unreadable_entry_is_ignored() {
+        serve(b"ok");
+        with_host(|h| h.cache.insert(entry_key(KEY), b"D".to_vec()));
+
+        assert_eq!(idempotent_fetch(KEY, &charge(), 3600).unwrap(), IdempotentResult::Fresh(b"ok".to_vec()));
+    }
+}
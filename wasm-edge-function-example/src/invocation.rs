@@ -0,0 +1,109 @@
+//! Invocation metadata SDK
+//!
+//! The host describes each invocation (which function, which deployment,
+//! whether the module was cold) so logs from different functions can be told
+//! apart and latency spikes correlated with cold starts.
+//!
+//! The runtime instantiates a fresh module per invocation, so the context is
+//! fetched from the host once and cached for the rest of the invocation.
+
+use std::cell::OnceCell;
+
+use serde::Deserialize;
+
+use crate::{host, read_shared_buffer};
+
+/// Metadata about the current invocation, as reported by the host
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InvocationContext {
+    /// Exported function being invoked
+    pub function_name: String,
+    /// Identifier of the deployed module (IPFS CID or content hash)
+    pub deployment_id: String,
+    /// Unique identifier of this invocation
+    pub invocation_id: String,
+    /// True for the first invocation of this deployment on the node
+    pub cold_start: bool,
+    /// Region of the executing node, if configured
+    #[serde(default)]
+    pub node_region: Option<String>,
+}
+
+thread_local! {
+    static CONTEXT: OnceCell<Option<InvocationContext>> = const { OnceCell::new() };
+}
+
+fn fetch() -> Option<InvocationContext> {
+    let len = unsafe { host::get_invocation_context() };
+    let json = read_shared_buffer(len)?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Get the current invocation's metadata
+///
+/// Returns `None` when the host doesn't provide it (or sends malformed JSON).
+pub fn context() -> Option<InvocationContext> {
+    CONTEXT.with(|cell| cell.get_or_init(fetch).clone())
+}
+
+/// Get the current invocation id, if the host provides one
+pub fn invocation_id() -> Option<String> {
+    context().map(|ctx| ctx.invocation_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    const PAYLOAD: &str = r#"{
+        "function_name": "feature_flags",
+        "deployment_id": "QmDeployment123",
+        "invocation_id": "a1b2c3d4",
+        "cold_start": true,
+        "node_region": "eu-west"
+    }"#;
+
+    #[test]
+    fn test_parses_host_payload() {
+        with_host(|h| h.invocation_context = Some(PAYLOAD.to_string()));
+
+        let ctx = context().unwrap();
+        assert_eq!(ctx.function_name, "feature_flags");
+        assert_eq!(ctx.deployment_id, "QmDeployment123");
+        assert_eq!(ctx.invocation_id, "a1b2c3d4");
+        assert!(ctx.cold_start);
+        assert_eq!(ctx.node_region.as_deref(), Some("eu-west"));
+    }
+
+    #[test]
+    fn test_missing_region_defaults_to_none() {
+        with_host(|h| {
+            h.invocation_context = Some(
+                r#"{"function_name":"f","deployment_id":"d","invocation_id":"i","cold_start":false}"#.to_string(),
+            )
+        });
+
+        assert_eq!(context().unwrap().node_region, None);
+    }
+
+    #[test]
+    fn test_cached_per_invocation() {
+        with_host(|h| h.invocation_context = Some(PAYLOAD.to_string()));
+
+        context();
+        context();
+        assert_eq!(invocation_id().as_deref(), Some("a1b2c3d4"));
+        assert_eq!(with_host(|h| h.invocation_context_calls), 1);
+    }
+
+    #[test]
+    fn test_unavailable_or_malformed() {
+        assert_eq!(context(), None);
+
+        with_host(|h| h.invocation_context = Some("not json".to_string()));
+        // Still cached as unavailable for this invocation
+        assert_eq!(context(), None);
+        assert_eq!(with_host(|h| h.invocation_context_calls), 1);
+    }
+}
@@ -0,0 +1,186 @@
+//! Durable key-value storage SDK
+//!
+//! Unlike the cache, KV entries are never evicted behind the function's back:
+//! a value stays until the function overwrites or deletes it. Use it for
+//! counters, feature flags, and small configuration documents.
+//!
+//! Limits enforced by the host:
+//! - Values up to 64KB
+//! - Per-function quota on total keys and bytes
+
+use serde::Deserialize;
+
+use crate::{host, read_shared_buffer};
+
+/// Maximum size of a single KV value (64KB)
+pub const MAX_VALUE_SIZE: usize = 64 * 1024;
+
+/// Typed KV error, decoded from the host's negative return codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KvError {
+    /// The node has no KV store configured or it failed internally
+    Unavailable,
+    /// The key does not exist
+    NotFound,
+    /// The value exceeds `MAX_VALUE_SIZE`
+    ValueTooLarge,
+    /// The function's key-count or byte quota is exhausted
+    QuotaExceeded,
+    /// The key is empty, too long, or contains control characters
+    InvalidKey,
+}
+
+impl KvError {
+    fn from_code(code: i32) -> Self {
+        match code {
+            -2 => KvError::NotFound,
+            -3 => KvError::ValueTooLarge,
+            -4 => KvError::QuotaExceeded,
+            -5 => KvError::InvalidKey,
+            _ => KvError::Unavailable,
+        }
+    }
+}
+
+/// One page of a prefix listing
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct KvPage {
+    /// Keys in lexicographic order
+    pub keys: Vec<String>,
+    /// Pass to the next `list_prefix` call; `None` when the listing is complete
+    pub cursor: Option<String>,
+}
+
+/// Get a value
+pub fn get(key: &str) -> Result<Vec<u8>, KvError> {
+    let len = unsafe { host::kv_get(key.as_ptr(), key.len() as u32) };
+    if len < 0 {
+        return Err(KvError::from_code(len));
+    }
+    read_shared_buffer(len).ok_or(KvError::Unavailable)
+}
+
+/// Get a value, mapping a missing key to `None`
+pub fn get_optional(key: &str) -> Result<Option<Vec<u8>>, KvError> {
+    match get(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(KvError::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Insert or replace a value
+pub fn put(key: &str, value: &[u8]) -> Result<(), KvError> {
+    if value.len() > MAX_VALUE_SIZE {
+        return Err(KvError::ValueTooLarge);
+    }
+    let result = unsafe { host::kv_put(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32) };
+    if result < 0 {
+        return Err(KvError::from_code(result));
+    }
+    Ok(())
+}
+
+/// Delete a key, returning whether it existed
+pub fn delete(key: &str) -> Result<bool, KvError> {
+    let result = unsafe { host::kv_delete(key.as_ptr(), key.len() as u32) };
+    if result < 0 {
+        return Err(KvError::from_code(result));
+    }
+    Ok(result == 1)
+}
+
+/// List keys starting with `prefix`
+///
+/// Pass `None` as the cursor for the first page, then the returned
+/// `KvPage::cursor` until it is `None`. The host caps `limit` at 100.
+pub fn list_prefix(prefix: &str, cursor: Option<&str>, limit: u32) -> Result<KvPage, KvError> {
+    let cursor = cursor.unwrap_or("");
+    let len = unsafe {
+        host::kv_list_prefix(
+            prefix.as_ptr(),
+            prefix.len() as u32,
+            cursor.as_ptr(),
+            cursor.len() as u32,
+            limit,
+        )
+    };
+    if len < 0 {
+        return Err(KvError::from_code(len));
+    }
+    let json = read_shared_buffer(len).ok_or(KvError::Unavailable)?;
+    serde_json::from_slice(&json).map_err(|_| KvError::Unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    #[test]
+    fn test_put_get_roundtrip() {
+        put("counter", b"1").unwrap();
+        assert_eq!(get("counter").unwrap(), b"1");
+        assert_eq!(get_optional("counter").unwrap(), Some(b"1".to_vec()));
+    }
+
+    #[test]
+    fn test_missing_key() {
+        assert_eq!(get("missing"), Err(KvError::NotFound));
+        assert_eq!(get_optional("missing"), Ok(None));
+        assert_eq!(delete("missing"), Ok(false));
+    }
+
+    #[test]
+    fn test_delete_existing() {
+        put("k", b"v").unwrap();
+        assert_eq!(delete("k"), Ok(true));
+        assert_eq!(get("k"), Err(KvError::NotFound));
+    }
+
+    #[test]
+    fn test_quota_exceeded() {
+        with_host(|h| h.kv_max_keys = Some(1));
+        put("a", b"1").unwrap();
+        assert_eq!(put("b", b"2"), Err(KvError::QuotaExceeded));
+        // Overwriting an existing key stays within quota
+        assert_eq!(put("a", b"3"), Ok(()));
+    }
+
+    #[test]
+    fn test_value_too_large() {
+        let value = vec![0u8; MAX_VALUE_SIZE + 1];
+        assert_eq!(put("big", &value), Err(KvError::ValueTooLarge));
+    }
+
+    #[test]
+    fn test_unavailable() {
+        with_host(|h| h.kv_unavailable = true);
+        assert_eq!(get("k"), Err(KvError::Unavailable));
+        assert_eq!(put("k", b"v"), Err(KvError::Unavailable));
+    }
+
+    #[test]
+    fn test_list_prefix_pagination() {
+        for i in 0..5 {
+            put(&format!("flag:{}", i), b"x").unwrap();
+        }
+        put("other", b"x").unwrap();
+
+        let mut all = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut pages = 0;
+        loop {
+            let page = list_prefix("flag:", cursor.as_deref(), 2).unwrap();
+            pages += 1;
+            all.extend(page.keys);
+            match page.cursor {
+                Some(c) => cursor = Some(c),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        assert_eq!(all, vec!["flag:0", "flag:1", "flag:2", "flag:3", "flag:4"]);
+    }
+}
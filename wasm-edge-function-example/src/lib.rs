@@ -6,83 +6,148 @@
 //! 3. Using the host API for data and external access
 //!
 //! Use case: Fetch exchange rate data from an external API and cache it
+//!
+//! It also demonstrates durable KV storage (see `kv`) with a feature flag
+//! lookup that falls back to defaults when no flags document is stored.
+//!
+//! Cache values are binary-safe; `cache` also compresses large values (see
+//! `http_cache::fetch_through`).
+//!
+//! All log lines are structured JSON tagged with the invocation id (see
+//! `logging` and `invocation`).
+//!
+//! `refresh_rates_cron` shows the scheduled entrypoint (see `schedule`): it
+//! keeps the exchange-rate cache warm without waiting for request traffic.
+//!
+//! `webhook_ack` rewrites response headers from client input through the
+//! guarded `headers::Headers` helper.
+//!
+//! `proxy_large_file` relays a large origin response to the client in chunks
+//! (see `streaming`) instead of buffering the whole body.
+//!
+//! Request entrypoints run their handler under `guard::run_guarded`, so an
+//! error or panic reaches the host as a JSON error envelope.
+//!
+//! `fetch_exchange_rates` checks the tenant's egress quota (see `usage`) and
+//! serves a stale copy instead of calling the external API when it is
+//! nearly used up.
+//!
+//! `geo_redirect` sends EU visitors to the `/eu/` site based on the client
+//! metadata the node reports (see `client`).
+//!
+//! `send_notification` POSTs to a notification API at most once per
+//! client-supplied idempotency key (see `idempotency`), so host retries
+//! don't send duplicates.
+
+use serde_json::{Map, Value};
+
+pub mod cache;
+pub mod client;
+pub mod guard;
+pub mod headers;
+mod host;
+pub mod http_cache;
+pub mod idempotency;
+pub mod invocation;
+pub mod kv;
+pub mod logging;
+pub mod schedule;
+pub mod streaming;
+pub mod usage;
+
+use cache::{cache_get_raw, cache_set_raw};
+use guard::{run_guarded, EdgeError};
+use headers::{request_header, Headers};
+use host::*;
+use idempotency::{idempotent_fetch, HttpRequest, IdempotentResult};
+use logging::{log_structured, LogLevel};
+use schedule::{ScheduleError, ScheduleEvent};
+use streaming::ResponseWriter;
+use usage::QuotaKind;
+
+/// Helper function to log messages (info level, tagged with the invocation id)
+fn log_message(msg: &str) {
+    log_structured(LogLevel::Info, msg, &[]);
+}
 
-use core::slice;
-use serde_json::Value;
-
-// Host API function declarations
-extern "C" {
-    /// Log a message to the host
-    fn log(ptr: *const u8, len: u32);
-
-    /// Get a value from cache
-    /// Returns the length of the value (stored in shared buffer), or -1 if not found
-    fn cache_get(key_ptr: *const u8, key_len: u32) -> i32;
+/// Copy `len` bytes out of the host's shared buffer
+pub(crate) fn read_shared_buffer(len: i32) -> Option<Vec<u8>> {
+    if len < 0 {
+        return None;
+    }
 
-    /// Set a value in cache
-    /// Returns 0 on success, -1 on error
-    fn cache_set(key_ptr: *const u8, key_len: u32, value_ptr: *const u8, value_len: u32, ttl: u32) -> i32;
+    let mut buffer = vec![0u8; len as usize];
+    let copied = unsafe { get_shared_buffer(buffer.as_mut_ptr(), 0, len as u32) };
+    // A short copy would hand out a zero-padded value
+    if copied != len {
+        return None;
+    }
 
-    /// Make an HTTP GET request
-    /// Returns the length of the response (stored in shared buffer), or -1 on error
-    fn http_get(url_ptr: *const u8, url_len: u32) -> i32;
+    Some(buffer)
+}
 
-    /// Get data from shared buffer
-    /// Returns number of bytes copied, or -1 on error
-    fn get_shared_buffer(dest_ptr: *mut u8, offset: u32, length: u32) -> i32;
+/// The inbound request URI (path)
+fn request_uri() -> Option<String> {
+    let len = unsafe { request_get_uri() };
+    read_shared_buffer(len).and_then(|v| String::from_utf8(v).ok())
 }
 
-/// Helper function to log messages
-fn log_message(msg: &str) {
-    unsafe {
-        log(msg.as_ptr(), msg.len() as u32);
-    }
+/// Helper function to make HTTP GET request
+fn http_get_request(url: &str) -> Option<Vec<u8>> {
+    let result_len = unsafe { http_get(url.as_ptr(), url.len() as u32) };
+    read_shared_buffer(result_len)
 }
 
-/// Helper function to get from cache
-fn get_from_cache(key: &str) -> Option<Vec<u8>> {
-    unsafe {
-        let result_len = cache_get(key.as_ptr(), key.len() as u32);
-        if result_len < 0 {
-            return None;
-        }
+/// Cache key holding the exchange rate document
+const EXCHANGE_RATES_CACHE_KEY: &str = "exchange_rates:usd";
 
-        // Allocate buffer and read from shared buffer
-        let mut buffer = vec![0u8; result_len as usize];
-        let copied = get_shared_buffer(buffer.as_mut_ptr(), 0, result_len as u32);
-        if copied < 0 {
-            return None;
-        }
+/// Cache key recording when the scheduled refresh last ran (unix seconds)
+const EXCHANGE_RATES_LAST_RUN_KEY: &str = "exchange_rates:last_refresh";
 
-        Some(buffer)
-    }
-}
+/// Exchange rate source (httpbin.org/json as a demo API returning sample JSON)
+const EXCHANGE_RATES_API_URL: &str = "https://httpbin.org/json";
 
-/// Helper function to set in cache
-fn set_in_cache(key: &str, value: &[u8], ttl: u32) -> bool {
-    unsafe {
-        let result = cache_set(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32, ttl);
-        result == 0
-    }
-}
+/// How long fetched exchange rates stay cached (seconds)
+const EXCHANGE_RATES_TTL: u32 = 60;
 
-/// Helper function to make HTTP GET request
-fn http_get_request(url: &str) -> Option<Vec<u8>> {
-    unsafe {
-        let result_len = http_get(url.as_ptr(), url.len() as u32);
-        if result_len < 0 {
-            return None;
-        }
+/// How long the last-run marker is kept (seconds)
+const LAST_RUN_TTL: u32 = 86_400;
 
-        // Allocate buffer and read from shared buffer
-        let mut buffer = vec![0u8; result_len as usize];
-        let copied = get_shared_buffer(buffer.as_mut_ptr(), 0, result_len as u32);
-        if copied < 0 {
-            return None;
-        }
+/// Cache key holding the last fetched exchange rates, kept past their TTL
+/// for serving when the external API is off limits
+const EXCHANGE_RATES_STALE_KEY: &str = "exchange_rates:usd:stale";
+
+/// How long the stale copy is kept (seconds)
+const EXCHANGE_RATES_STALE_TTL: u32 = 86_400;
+
+/// Egress quota usage (percent) above which the external API isn't called
+const EGRESS_DEGRADE_PERCENT: u64 = 95;
+
+/// Fetch exchange rates from the external API and store them in the cache
+///
+/// Shared by the request handler (on a cache miss) and the scheduled
+/// refresh. Returns whether the cache write succeeded.
+fn refresh_exchange_rates() -> Result<bool, &'static str> {
+    let response_data = http_get_request(EXCHANGE_RATES_API_URL).ok_or("Failed to fetch from external API")?;
+    log_message("Successfully fetched data from external API");
+
+    // Validate JSON response
+    let json_str = std::str::from_utf8(&response_data).map_err(|_| "Response is not valid UTF-8")?;
+    if serde_json::from_str::<Value>(json_str).is_err() {
+        return Err("Invalid JSON response");
+    }
+    log_message("Response is valid JSON");
 
-        Some(buffer)
+    let cached = cache_set_raw(EXCHANGE_RATES_CACHE_KEY, &response_data, EXCHANGE_RATES_TTL);
+    if !cache_set_raw(EXCHANGE_RATES_STALE_KEY, &response_data, EXCHANGE_RATES_STALE_TTL) {
+        log_structured(LogLevel::Warn, "Failed to keep stale copy of exchange rates", &[]);
+    }
+    if cached {
+        log_message("Successfully cached exchange rates");
+    } else {
+        log_message("Warning: Failed to cache data");
     }
+    Ok(cached)
 }
 
 /// Main edge function: Fetch exchange rate data with caching
@@ -92,56 +157,337 @@ fn http_get_request(url: &str) -> Option<Vec<u8>> {
 /// 2. If cache miss, fetch from external API (slow path)
 /// 3. Cache the result for future requests
 /// 4. Return the data
+///
+/// When more than EGRESS_DEGRADE_PERCENT of the egress quota is used, a cache
+/// miss serves the stale copy instead of fetching.
 #[no_mangle]
 pub extern "C" fn fetch_exchange_rates() -> i32 {
-    log_message("Edge function: fetch_exchange_rates started");
+    run_guarded("fetch_exchange_rates", exchange_rates_handler)
+}
 
-    let cache_key = "exchange_rates:usd";
+fn exchange_rates_handler() -> Result<(), EdgeError> {
+    let cold_start = invocation::context().is_some_and(|ctx| ctx.cold_start);
+    log_structured(
+        LogLevel::Info,
+        "Edge function: fetch_exchange_rates started",
+        &[("cold_start", Value::Bool(cold_start))],
+    );
 
     // Try to get from cache first
     log_message("Checking cache for exchange rates...");
-    if let Some(cached_data) = get_from_cache(cache_key) {
-        log_message("Cache HIT! Returning cached exchange rates");
-        return 0; // Success - data is in shared buffer
+    if cache_get_raw(EXCHANGE_RATES_CACHE_KEY).is_some() {
+        log_structured(
+            LogLevel::Info,
+            "Cache HIT! Returning cached exchange rates",
+            &[("cache_key", Value::from(EXCHANGE_RATES_CACHE_KEY))],
+        );
+        return Ok(()); // Success - data is in shared buffer
     }
 
-    log_message("Cache MISS! Fetching from external API...");
+    if let Some(usage) = usage::get_usage().filter(|u| u.above(QuotaKind::Egress, EGRESS_DEGRADE_PERCENT)) {
+        log_structured(
+            LogLevel::Warn,
+            "Egress quota nearly used, serving stale exchange rates",
+            &[
+                ("egress_bytes", Value::from(usage.egress_bytes)),
+                ("egress_limit", Value::from(usage.egress_limit)),
+                ("period_ends_at", Value::from(usage.period_ends_at)),
+            ],
+        );
+        // Leaves the stale copy in the shared buffer
+        return match cache_get_raw(EXCHANGE_RATES_STALE_KEY) {
+            Some(_) => Ok(()),
+            None => Err(EdgeError::new(
+                "quota_exceeded",
+                "egress quota nearly used and no stale exchange rates cached",
+            )),
+        };
+    }
 
-    // Cache miss - fetch from external API
-    // Using httpbin.org/json as a demo API (returns sample JSON)
-    let api_url = "https://httpbin.org/json";
+    log_structured(
+        LogLevel::Info,
+        "Cache MISS! Fetching from external API...",
+        &[("cache_key", Value::from(EXCHANGE_RATES_CACHE_KEY))],
+    );
 
-    match http_get_request(api_url) {
-        Some(response_data) => {
-            log_message("Successfully fetched data from external API");
+    // Cache miss - fetch from external API (a failed cache write is not fatal here)
+    refresh_exchange_rates()
+        .map(|_| ())
+        .map_err(|e| EdgeError::new("origin_failed", e.to_string()))
+}
 
-            // Validate JSON response
-            if let Ok(json_str) = std::str::from_utf8(&response_data) {
-                if serde_json::from_str::<Value>(json_str).is_ok() {
-                    log_message("Response is valid JSON");
+/// Scheduled job: Refresh the exchange rate cache
+///
+/// Runs regardless of what is cached so the request path keeps hitting a
+/// warm cache, then records the scheduled time as the last run. Fetch and
+/// cache failures are retryable; the dispatcher applies the backoff.
+pub fn refresh_rates_cron(event: &ScheduleEvent) -> Result<(), ScheduleError> {
+    log_structured(
+        LogLevel::Info,
+        "Scheduled exchange rate refresh started",
+        &[
+            ("schedule_name", Value::from(event.schedule_name.as_str())),
+            ("attempt", Value::from(event.attempt)),
+        ],
+    );
+
+    match refresh_exchange_rates() {
+        Ok(true) => {}
+        Ok(false) => return Err(ScheduleError::Retryable("failed to cache exchange rates".to_string())),
+        Err(e) => return Err(ScheduleError::Retryable(e.to_string())),
+    }
 
-                    // Cache the result for 60 seconds
-                    if set_in_cache(cache_key, &response_data, 60) {
-                        log_message("Successfully cached exchange rates");
-                    } else {
-                        log_message("Warning: Failed to cache data");
-                    }
-
-                    return 0; // Success
-                } else {
-                    log_message("Error: Invalid JSON response");
-                    return -1;
-                }
-            } else {
-                log_message("Error: Response is not valid UTF-8");
-                return -1;
-            }
+    let last_run = event.scheduled_time.to_string();
+    if !cache_set_raw(EXCHANGE_RATES_LAST_RUN_KEY, last_run.as_bytes(), LAST_RUN_TTL) {
+        log_structured(LogLevel::Warn, "Failed to record last refresh time", &[]);
+    }
+    Ok(())
+}
+
+crate::schedule_handlers! {
+    "refresh_rates" => refresh_rates_cron,
+}
+
+/// KV key holding the feature flags document
+const FEATURE_FLAGS_KEY: &str = "config:feature_flags";
+
+/// Flags served when no document is stored (or it is unreadable)
+fn default_feature_flags() -> Map<String, Value> {
+    let mut flags = Map::new();
+    flags.insert("new_checkout".to_string(), Value::Bool(false));
+    flags.insert("dark_mode".to_string(), Value::Bool(true));
+    flags.insert("beta_banner".to_string(), Value::Bool(false));
+    flags
+}
+
+/// Overlay a stored flags document on the defaults
+///
+/// Unknown flags in the stored document are kept; a missing or malformed
+/// document yields the defaults unchanged.
+fn resolve_feature_flags(stored: Option<&[u8]>) -> Map<String, Value> {
+    let mut flags = default_feature_flags();
+    if let Some(bytes) = stored {
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(Value::Object(overrides)) => flags.extend(overrides),
+            _ => log_structured(
+                LogLevel::Warn,
+                "Stored feature flags are not a JSON object, using defaults",
+                &[("key", Value::from(FEATURE_FLAGS_KEY))],
+            ),
         }
-        None => {
-            log_message("Error: Failed to fetch from external API");
-            return -1;
+    }
+    flags
+}
+
+/// Edge function: Serve feature flags from durable KV storage
+///
+/// Reads the flags document from KV and falls back to built-in defaults
+/// when it is missing or the KV store is unavailable. The resolved flags
+/// are written as the JSON response body.
+#[no_mangle]
+pub extern "C" fn feature_flags() -> i32 {
+    run_guarded("feature_flags", feature_flags_handler)
+}
+
+fn feature_flags_handler() -> Result<(), EdgeError> {
+    let stored = match kv::get_optional(FEATURE_FLAGS_KEY) {
+        Ok(stored) => stored,
+        Err(e) => {
+            log_structured(
+                LogLevel::Warn,
+                "KV read failed, using default flags",
+                &[("error", Value::from(format!("{:?}", e)))],
+            );
+            None
+        }
+    };
+
+    let flags = resolve_feature_flags(stored.as_deref());
+    set_body(&Value::Object(flags).to_string())
+}
+
+/// Set the response body for a guarded handler
+fn set_body(body: &str) -> Result<(), EdgeError> {
+    if unsafe { response_set_body(body.as_ptr(), body.len() as u32) } < 0 {
+        return Err(EdgeError::new("response_failed", "failed to set response body"));
+    }
+    Ok(())
+}
+
+/// Request header carrying the sender's delivery id
+const WEBHOOK_ID_HEADER: &str = "X-Webhook-Id";
+
+/// Response header echoing the delivery id back to the sender
+const WEBHOOK_RECEIPT_HEADER: &str = "X-Webhook-Receipt";
+
+/// Edge function: Acknowledge a webhook delivery
+///
+/// Echoes the sender's delivery id as a receipt header. The id is client
+/// input, so a value with control characters is dropped (and logged) rather
+/// than being allowed to inject headers into the response.
+#[no_mangle]
+pub extern "C" fn webhook_ack() -> i32 {
+    run_guarded("webhook_ack", webhook_ack_handler)
+}
+
+fn webhook_ack_handler() -> Result<(), EdgeError> {
+    let delivery_id = request_header(WEBHOOK_ID_HEADER);
+
+    let mut headers = Headers::new();
+    headers.set("Cache-Control", "no-store")?;
+    headers.set("Content-Type", "application/json")?;
+    let mut receipt = None;
+    if let Some(id) = &delivery_id {
+        match headers.set(WEBHOOK_RECEIPT_HEADER, id) {
+            Ok(()) => receipt = Some(id.as_str()),
+            Err(e) => log_structured(
+                LogLevel::Warn,
+                "Rejected webhook delivery id",
+                &[("error", Value::from(format!("{:?}", e)))],
+            ),
+        }
+    }
+
+    headers.apply_to_response()?;
+    set_body(&serde_json::json!({ "received": true, "delivery_id": receipt }).to_string())
+}
+
+/// Large origin object relayed by `proxy_large_file` (2MB demo payload)
+const LARGE_FILE_URL: &str = "https://httpbin.org/bytes/2097152";
+
+/// Edge function: Stream a large origin response to the client
+///
+/// The fetched body stays in the host's shared buffer and is relayed in
+/// 64KB chunks, so the function never holds the whole file in memory.
+#[no_mangle]
+pub extern "C" fn proxy_large_file() -> i32 {
+    run_guarded("proxy_large_file", proxy_large_file_handler)
+}
+
+fn proxy_large_file_handler() -> Result<(), EdgeError> {
+    let len = unsafe { http_get(LARGE_FILE_URL.as_ptr(), LARGE_FILE_URL.len() as u32) };
+    if len < 0 {
+        return Err(EdgeError::new("origin_failed", "failed to fetch large file from origin"));
+    }
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/octet-stream")?;
+
+    // No logging until the body is relayed: fetching the invocation id for a
+    // log line would replace the shared buffer
+    let mut writer = ResponseWriter::start(200, &headers)?;
+    writer.write_shared_buffer(len as u32)?;
+    writer.end()?;
+    Ok(())
+}
+
+/// Path prefix of the EU site
+const EU_PATH_PREFIX: &str = "/eu/";
+
+/// EU member states (ISO 3166-1 alpha-2)
+const EU_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU", "IE", "IT", "LT", "LU",
+    "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+/// Edge function: Redirect EU visitors to the EU site
+///
+/// Visitors whose country is an EU member state get a 302 to the same path
+/// under `/eu/`. Everyone else, requests already under `/eu/`, and clients
+/// the node couldn't locate pass through untouched.
+#[no_mangle]
+pub extern "C" fn geo_redirect() -> i32 {
+    run_guarded("geo_redirect", geo_redirect_handler)
+}
+
+fn geo_redirect_handler() -> Result<(), EdgeError> {
+    let Some(country) = client::get_client_info().and_then(|info| info.country) else {
+        return Ok(());
+    };
+    if !EU_COUNTRIES.iter().any(|c| c.eq_ignore_ascii_case(&country)) {
+        return Ok(());
+    }
+
+    let uri = request_uri().unwrap_or_else(|| "/".to_string());
+    if uri.starts_with(EU_PATH_PREFIX) || uri == EU_PATH_PREFIX.trim_end_matches('/') {
+        return Ok(());
+    }
+    let location = format!("{}{}", EU_PATH_PREFIX.trim_end_matches('/'), uri);
+
+    let mut headers = Headers::new();
+    headers.set("Location", &location)?;
+    // The redirect depends on the client, so shared caches must not keep it
+    headers.set("Cache-Control", "private, no-store")?;
+    let writer = ResponseWriter::start(302, &headers)?;
+    writer.end()?;
+
+    log_structured(
+        LogLevel::Info,
+        "Redirected visitor to EU site",
+        &[("country", Value::from(country)), ("location", Value::from(location))],
+    );
+    Ok(())
+}
+
+/// Notification API called by `send_notification` (httpbin.org/post echoes the request)
+const NOTIFICATION_API_URL: &str = "https://httpbin.org/post";
+
+/// Request header carrying the client's idempotency key
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Response header set when the stored response of an earlier attempt is served
+const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// How long a sent notification's response is kept for retries (seconds)
+const NOTIFICATION_RESPONSE_TTL: u32 = 86_400;
+
+/// Edge function: Send a notification at most once per idempotency key
+///
+/// The client supplies the key in an `Idempotency-Key` header. A retry
+/// after the notification went out gets the stored API response (marked
+/// with `Idempotent-Replayed: true`) instead of a second notification. A
+/// retry while an earlier attempt's outcome is unknown fails with
+/// `in_flight` rather than risk sending twice; the client retries later.
+#[no_mangle]
+pub extern "C" fn send_notification() -> i32 {
+    run_guarded("send_notification", send_notification_handler)
+}
+
+fn send_notification_handler() -> Result<(), EdgeError> {
+    let key = request_header(IDEMPOTENCY_KEY_HEADER)
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| EdgeError::new("missing_idempotency_key", "Idempotency-Key header is required"))?;
+
+    let payload = serde_json::json!({
+        "channel": "email",
+        "template": "order_shipped",
+        "reference": key,
+    });
+    let request = HttpRequest::post(NOTIFICATION_API_URL, "application/json", payload.to_string());
+
+    let (body, replayed) = match idempotent_fetch(&key, &request, NOTIFICATION_RESPONSE_TTL)? {
+        IdempotentResult::Fresh(body) => (body, false),
+        IdempotentResult::Replayed(body) => (body, true),
+        IdempotentResult::InFlightUnknown => {
+            return Err(EdgeError::new(
+                "in_flight",
+                "an earlier attempt with this key may still be in progress, retry later",
+            ));
         }
+    };
+    log_structured(LogLevel::Info, "Notification sent", &[("replayed", Value::Bool(replayed))]);
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/json")?;
+    headers.set("Cache-Control", "no-store")?;
+    if replayed {
+        headers.set(IDEMPOTENT_REPLAYED_HEADER, "true")?;
     }
+    headers.apply_to_response()?;
+    if unsafe { response_set_body(body.as_ptr(), body.len() as u32) } < 0 {
+        return Err(EdgeError::new("response_failed", "failed to set response body"));
+    }
+    Ok(())
 }
 
 /// Simple test function: Just logs a message
@@ -160,7 +506,7 @@ pub extern "C" fn test_cache() -> i32 {
     let key = "test:key";
     let value = b"Hello from edge function!";
 
-    if set_in_cache(key, value, 30) {
+    if cache_set_raw(key, value, 30) {
         log_message("Cache SET successful");
     } else {
         log_message("Cache SET failed");
@@ -168,7 +514,7 @@ pub extern "C" fn test_cache() -> i32 {
     }
 
     // Get the value back
-    if let Some(retrieved) = get_from_cache(key) {
+    if let Some(retrieved) = cache_get_raw(key) {
         if retrieved == value {
             log_message("Cache GET successful - value matches!");
             return 0;
@@ -226,3 +572,385 @@ pub extern "C" fn dealloc(ptr: *mut u8, size: u32) {
         let _ = Vec::from_raw_parts(ptr, size as usize, size as usize);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::{with_host, Call, Reply};
+
+    fn response_json() -> Value {
+        let body = with_host(|h| h.response_body.clone()).expect("response body not set");
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn test_feature_flags_defaults_when_missing() {
+        assert_eq!(feature_flags(), 0);
+        assert_eq!(response_json(), Value::Object(default_feature_flags()));
+    }
+
+    #[test]
+    fn test_feature_flags_overrides_from_kv() {
+        kv::put(FEATURE_FLAGS_KEY, br#"{"new_checkout": true, "holiday_theme": true}"#).unwrap();
+
+        assert_eq!(feature_flags(), 0);
+        let flags = response_json();
+        assert_eq!(flags["new_checkout"], Value::Bool(true));
+        assert_eq!(flags["holiday_theme"], Value::Bool(true));
+        assert_eq!(flags["dark_mode"], Value::Bool(true));
+    }
+
+    #[test]
+    fn test_feature_flags_kv_unavailable() {
+        with_host(|h| h.kv_unavailable = true);
+
+        assert_eq!(feature_flags(), 0);
+        assert_eq!(response_json(), Value::Object(default_feature_flags()));
+    }
+
+    #[test]
+    fn test_feature_flags_logs_tagged_with_invocation_id() {
+        with_host(|h| {
+            h.kv_unavailable = true;
+            h.invocation_context = Some(
+                r#"{"function_name":"feature_flags","deployment_id":"d","invocation_id":"inv-7","cold_start":true}"#
+                    .to_string(),
+            );
+        });
+
+        assert_eq!(feature_flags(), 0);
+        let logs = with_host(|h| h.logs.clone());
+        assert!(!logs.is_empty());
+        for line in logs {
+            let line: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(line["invocation_id"], "inv-7");
+        }
+    }
+
+    #[test]
+    fn test_feature_flags_malformed_document() {
+        kv::put(FEATURE_FLAGS_KEY, b"[1, 2, 3]").unwrap();
+
+        assert_eq!(feature_flags(), 0);
+        assert_eq!(response_json(), Value::Object(default_feature_flags()));
+    }
+
+    #[test]
+    fn test_webhook_ack_echoes_delivery_id() {
+        with_host(|h| h.request_headers.push(("x-webhook-id".to_string(), "evt_123".to_string())));
+
+        assert_eq!(webhook_ack(), 0);
+        let sent = with_host(|h| h.response_headers.clone());
+        assert!(sent.contains(&(WEBHOOK_RECEIPT_HEADER.to_string(), "evt_123".to_string())));
+        assert!(sent.contains(&("Cache-Control".to_string(), "no-store".to_string())));
+        assert_eq!(response_json()["delivery_id"], "evt_123");
+    }
+
+    #[test]
+    fn test_webhook_ack_rejects_injected_delivery_id() {
+        with_host(|h| {
+            h.request_headers
+                .push((WEBHOOK_ID_HEADER.to_string(), "evt\r\nSet-Cookie: session=evil".to_string()))
+        });
+
+        assert_eq!(webhook_ack(), 0);
+        let sent = with_host(|h| h.response_headers.clone());
+        assert!(!sent.iter().any(|(n, _)| n == WEBHOOK_RECEIPT_HEADER || n == "Set-Cookie"));
+        assert_eq!(response_json()["delivery_id"], Value::Null);
+    }
+
+    fn rates_event(attempt: u32) -> Vec<u8> {
+        format!(
+            r#"{{"schedule_name":"refresh_rates","scheduled_time":1700000060,"attempt":{}}}"#,
+            attempt
+        )
+        .into_bytes()
+    }
+
+    /// Call the generated export and decode its length-prefixed status
+    fn run_schedule(input: &[u8]) -> Value {
+        let ptr = handle_schedule(input.as_ptr(), input.len() as u32);
+        unsafe {
+            let len = u32::from_le_bytes(*(ptr as *const [u8; 4])) as usize;
+            let json = std::slice::from_raw_parts(ptr.add(4), len).to_vec();
+            dealloc(ptr, (4 + len) as u32);
+            serde_json::from_slice(&json).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_refresh_rates_cron_success() {
+        with_host(|h| {
+            h.cache.insert(EXCHANGE_RATES_CACHE_KEY.to_string(), br#"{"stale":true}"#.to_vec());
+            h.http_responses.insert(EXCHANGE_RATES_API_URL.to_string(), br#"{"usd":1.0}"#.to_vec());
+        });
+
+        assert_eq!(run_schedule(&rates_event(1)), serde_json::json!({"status": "ok"}));
+
+        // Refreshed even though the cache was warm
+        let cache = with_host(|h| h.cache.clone());
+        assert_eq!(cache[EXCHANGE_RATES_CACHE_KEY], br#"{"usd":1.0}"#);
+        assert_eq!(cache[EXCHANGE_RATES_LAST_RUN_KEY], b"1700000060");
+    }
+
+    #[test]
+    fn test_refresh_rates_cron_http_failure_retryable() {
+        let status = run_schedule(&rates_event(1));
+        assert_eq!(status["status"], "retry");
+        assert_eq!(status["retry_after_secs"], schedule::BASE_RETRY_DELAY_SECS);
+        assert!(with_host(|h| !h.cache.contains_key(EXCHANGE_RATES_LAST_RUN_KEY)));
+    }
+
+    #[test]
+    fn test_refresh_rates_cron_backoff_by_attempt() {
+        assert_eq!(run_schedule(&rates_event(3))["retry_after_secs"], 20);
+        assert_eq!(run_schedule(&rates_event(schedule::MAX_ATTEMPTS))["status"], "failed");
+    }
+
+    fn set_egress(egress_bytes: u64) {
+        let usage = serde_json::json!({
+            "invocations_this_period": 10,
+            "invocation_limit": null,
+            "egress_bytes": egress_bytes,
+            "egress_limit": 1_000,
+            "period_ends_at": 1_700_000_000u64,
+        });
+        with_host(|h| {
+            h.usage = Some(usage.to_string());
+            h.cache.insert(EXCHANGE_RATES_STALE_KEY.to_string(), br#"{"usd":0.9}"#.to_vec());
+            h.http_responses.insert(EXCHANGE_RATES_API_URL.to_string(), br#"{"usd":1.0}"#.to_vec());
+        });
+    }
+
+    #[test]
+    fn test_exchange_rates_fetch_under_egress_limit() {
+        set_egress(500);
+
+        assert_eq!(fetch_exchange_rates(), 0);
+        let cache = with_host(|h| h.cache.clone());
+        assert_eq!(cache[EXCHANGE_RATES_CACHE_KEY], br#"{"usd":1.0}"#);
+        assert_eq!(cache[EXCHANGE_RATES_STALE_KEY], br#"{"usd":1.0}"#);
+    }
+
+    #[test]
+    fn test_exchange_rates_stale_near_egress_limit() {
+        set_egress(960);
+
+        assert_eq!(fetch_exchange_rates(), 0);
+        // No fetch: the stale copy is served and the fresh key stays empty
+        assert_eq!(with_host(|h| h.shared_buffer.clone()), br#"{"usd":0.9}"#);
+        assert!(with_host(|h| !h.cache.contains_key(EXCHANGE_RATES_CACHE_KEY)));
+
+        let warnings = with_host(|h| h.logs_at("warn"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["egress_bytes"], 960);
+        assert_eq!(warnings[0]["egress_limit"], 1_000);
+
+        // Nothing stale to serve either
+        with_host(|h| h.cache.clear());
+        assert_eq!(fetch_exchange_rates(), -1);
+    }
+
+    #[test]
+    fn test_exchange_rates_usage_unavailable_fetches() {
+        with_host(|h| h.http_responses.insert(EXCHANGE_RATES_API_URL.to_string(), br#"{"usd":1.0}"#.to_vec()));
+
+        assert_eq!(fetch_exchange_rates(), 0);
+        assert!(with_host(|h| h.cache.contains_key(EXCHANGE_RATES_CACHE_KEY)));
+    }
+
+    #[test]
+    fn test_exchange_rates_miss_then_hit() {
+        with_host(|h| {
+            h.http_responses.insert(EXCHANGE_RATES_API_URL.to_string(), br#"{"usd":1.0}"#.to_vec());
+            h.expect(Call::cache_get(EXCHANGE_RATES_CACHE_KEY));
+            h.expect(Call::log("Cache MISS"));
+            h.expect(Call::http_get(EXCHANGE_RATES_API_URL));
+            h.expect(Call::cache_set(EXCHANGE_RATES_CACHE_KEY, EXCHANGE_RATES_TTL));
+            h.expect(Call::cache_set(EXCHANGE_RATES_STALE_KEY, EXCHANGE_RATES_STALE_TTL));
+            h.expect(Call::cache_get(EXCHANGE_RATES_CACHE_KEY));
+            h.expect(Call::log("Cache HIT"));
+        });
+
+        assert_eq!(fetch_exchange_rates(), 0);
+        assert_eq!(fetch_exchange_rates(), 0);
+        assert_eq!(with_host(|h| h.shared_buffer.clone()), br#"{"usd":1.0}"#);
+        with_host(|h| {
+            h.assert_expectations();
+            assert_eq!(h.calls.iter().filter(|c| matches!(c, Call::HttpGet(_))).count(), 1);
+        });
+
+        // Expired entries are fetched again
+        with_host(|h| {
+            h.advance(u64::from(EXCHANGE_RATES_TTL));
+            h.expect(Call::http_get(EXCHANGE_RATES_API_URL));
+        });
+        assert_eq!(fetch_exchange_rates(), 0);
+        with_host(|h| h.assert_expectations());
+    }
+
+    #[test]
+    fn test_exchange_rates_origin_timeout() {
+        with_host(|h| {
+            h.expect(Call::http_get(EXCHANGE_RATES_API_URL)).returning(Reply::Timeout);
+        });
+
+        assert_eq!(fetch_exchange_rates(), -1);
+        with_host(|h| {
+            h.assert_expectations();
+            assert!(h.cache.is_empty());
+            assert_eq!(h.logs_at("error").len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_truncated_cache_read_is_a_miss() {
+        with_host(|h| {
+            h.cache.insert(EXCHANGE_RATES_CACHE_KEY.to_string(), br#"{"usd":0.5}"#.to_vec());
+            h.http_responses.insert(EXCHANGE_RATES_API_URL.to_string(), br#"{"usd":1.0}"#.to_vec());
+            h.truncate_next_read = Some(4);
+        });
+
+        assert_eq!(fetch_exchange_rates(), 0);
+        assert_eq!(with_host(|h| h.cache[EXCHANGE_RATES_CACHE_KEY].clone()), br#"{"usd":1.0}"#);
+    }
+
+    #[test]
+    fn test_proxy_large_file_streams_in_chunks() {
+        let file: Vec<u8> = (0..(2 * 1024 * 1024 + 100)).map(|i| (i % 251) as u8).collect();
+        with_host(|h| {
+            h.http_responses.insert(LARGE_FILE_URL.to_string(), file.clone());
+            h.stream_would_block = 2;
+        });
+
+        assert_eq!(proxy_large_file(), 0);
+        let (status, headers, chunks, ended) =
+            with_host(|h| (h.stream_status, h.stream_headers.clone(), h.stream_chunks.clone(), h.stream_ended));
+        assert_eq!(status, Some(200));
+        assert_eq!(headers, vec![("Content-Type".to_string(), "application/octet-stream".to_string())]);
+        assert_eq!(chunks.len(), 33);
+        assert!(chunks.iter().all(|c| c.len() <= streaming::STREAM_CHUNK_SIZE));
+        assert_eq!(chunks.concat(), file);
+        assert!(ended);
+    }
+
+    fn set_client(client_info: Option<&str>, uri: &str) {
+        with_host(|h| {
+            h.client_info = client_info.map(str::to_string);
+            h.request_uri = uri.to_string();
+        });
+    }
+
+    #[test]
+    fn test_geo_redirect_eu_visitor() {
+        set_client(Some(r#"{"ip":"203.0.113.7","country":"de","region":"BY"}"#), "/pricing");
+
+        assert_eq!(geo_redirect(), 0);
+        let (status, headers, ended) = with_host(|h| (h.stream_status, h.stream_headers.clone(), h.stream_ended));
+        assert_eq!(status, Some(302));
+        assert!(headers.contains(&("Location".to_string(), "/eu/pricing".to_string())));
+        assert!(ended);
+    }
+
+    #[test]
+    fn test_geo_redirect_passes_through() {
+        // Outside the EU
+        set_client(Some(r#"{"country":"US"}"#), "/pricing");
+        assert_eq!(geo_redirect(), 0);
+        assert!(with_host(|h| h.stream_status.is_none()));
+    }
+
+    #[test]
+    fn test_geo_redirect_already_on_eu_site() {
+        set_client(Some(r#"{"country":"FR"}"#), "/eu/pricing");
+        assert_eq!(geo_redirect(), 0);
+        assert!(with_host(|h| h.stream_status.is_none()));
+    }
+
+    #[test]
+    fn test_geo_redirect_without_client_info() {
+        // Host returns -1
+        set_client(None, "/pricing");
+        assert_eq!(geo_redirect(), 0);
+        assert!(with_host(|h| h.stream_status.is_none()));
+    }
+
+    #[test]
+    fn test_geo_redirect_unknown_country() {
+        set_client(Some(r#"{"ip":"203.0.113.7","country":null}"#), "/pricing");
+        assert_eq!(geo_redirect(), 0);
+        assert!(with_host(|h| h.stream_status.is_none()));
+    }
+
+    #[test]
+    fn test_geo_redirect_logs_without_ip() {
+        set_client(Some(r#"{"ip":"203.0.113.7","country":"NL"}"#), "/");
+
+        assert_eq!(geo_redirect(), 0);
+        let logs = with_host(|h| h.logs.clone());
+        assert!(logs.iter().any(|line| line.contains("/eu/")));
+        assert!(!logs.iter().any(|line| line.contains("203.0.113.7")));
+    }
+
+    fn notification_request(key: Option<&str>) {
+        with_host(|h| {
+            h.http_responses.insert(NOTIFICATION_API_URL.to_string(), br#"{"queued":true}"#.to_vec());
+            if let Some(key) = key {
+                h.request_headers.push((IDEMPOTENCY_KEY_HEADER.to_string(), key.to_string()));
+            }
+        });
+    }
+
+    fn replayed_header() -> bool {
+        with_host(|h| h.response_headers.iter().any(|(n, v)| n == IDEMPOTENT_REPLAYED_HEADER && v == "true"))
+    }
+
+    #[test]
+    fn test_send_notification_once_per_key() {
+        notification_request(Some("ship-1001"));
+
+        assert_eq!(send_notification(), 0);
+        assert_eq!(response_json(), serde_json::json!({"queued": true}));
+        assert!(!replayed_header());
+        let (_, content_type, body) = with_host(|h| h.http_posts[0].clone());
+        assert_eq!(content_type, "application/json");
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["reference"], "ship-1001");
+
+        // Host retry after a timeout: served from the cache, nothing sent
+        with_host(|h| h.response_headers.clear());
+        assert_eq!(send_notification(), 0);
+        assert_eq!(response_json(), serde_json::json!({"queued": true}));
+        assert!(replayed_header());
+        assert_eq!(with_host(|h| h.http_posts.len()), 1);
+    }
+
+    #[test]
+    fn test_send_notification_in_flight_fails() {
+        notification_request(Some("ship-1002"));
+        with_host(|h| {
+            h.expect(Call::http_post(NOTIFICATION_API_URL)).returning(Reply::Timeout);
+        });
+
+        assert_eq!(send_notification(), -1);
+        assert_eq!(response_json()["code"], "idempotency_failed");
+
+        assert_eq!(send_notification(), -1);
+        assert_eq!(response_json()["code"], "in_flight");
+        assert_eq!(with_host(|h| h.calls.iter().filter(|c| matches!(c, Call::HttpPost(_))).count()), 1);
+    }
+
+    #[test]
+    fn test_send_notification_requires_key() {
+        notification_request(None);
+
+        assert_eq!(send_notification(), -1);
+        assert_eq!(response_json()["code"], "missing_idempotency_key");
+        assert!(with_host(|h| h.calls.iter().all(|c| !matches!(c, Call::HttpPost(_)))));
+    }
+
+    #[test]
+    fn test_proxy_large_file_origin_failure() {
+        assert_eq!(proxy_large_file(), -1);
+        assert!(with_host(|h| h.stream_status.is_none()));
+    }
+}
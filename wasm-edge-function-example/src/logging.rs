@@ -0,0 +1,139 @@
+//! Structured logging SDK
+//!
+//! Each log line is a JSON object sent through the host's `log` import.
+//! The invocation id and function name are attached automatically (when the
+//! host provides them) so lines from concurrent invocations can be separated.
+//!
+//! Once a function has fetched the client's metadata (see `client`), the
+//! client IP is replaced with `client::REDACTED` anywhere in the line.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{client, host, invocation};
+
+/// Severity of a structured log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Build the JSON document for one log line
+fn build_line(level: LogLevel, message: &str, fields: &[(&str, Value)]) -> Value {
+    let mut line = Map::new();
+    line.insert("level".to_string(), serde_json::to_value(level).unwrap_or(Value::Null));
+    line.insert("message".to_string(), Value::String(message.to_string()));
+
+    if let Some(ctx) = invocation::context() {
+        line.insert("invocation_id".to_string(), Value::String(ctx.invocation_id));
+        line.insert("function_name".to_string(), Value::String(ctx.function_name));
+    }
+
+    // Caller fields never overwrite the reserved keys above
+    for (key, value) in fields {
+        line.entry(key.to_string()).or_insert_with(|| value.clone());
+    }
+
+    let mut line = Value::Object(line);
+    if let Some(ip) = client::fetched_ip().filter(|ip| !ip.is_empty()) {
+        redact(&mut line, &ip);
+    }
+    line
+}
+
+/// Replace `needle` in every string of a JSON value
+fn redact(value: &mut Value, needle: &str) {
+    match value {
+        Value::String(s) if s.contains(needle) => *s = s.replace(needle, client::REDACTED),
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, needle)),
+        Value::Object(map) => map.values_mut().for_each(|item| redact(item, needle)),
+        _ => {}
+    }
+}
+
+/// Log a structured message with additional fields
+pub fn log_structured(level: LogLevel, message: &str, fields: &[(&str, Value)]) {
+    let line = build_line(level, message, fields).to_string();
+    unsafe {
+        host::log(line.as_ptr(), line.len() as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    fn logged_lines() -> Vec<Value> {
+        with_host(|h| h.logs.clone())
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_includes_invocation_id() {
+        with_host(|h| {
+            h.invocation_context = Some(
+                r#"{"function_name":"feature_flags","deployment_id":"d","invocation_id":"inv-42","cold_start":false}"#
+                    .to_string(),
+            )
+        });
+
+        log_structured(LogLevel::Info, "hello", &[("count", Value::from(3))]);
+
+        let lines = logged_lines();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["level"], "info");
+        assert_eq!(lines[0]["message"], "hello");
+        assert_eq!(lines[0]["invocation_id"], "inv-42");
+        assert_eq!(lines[0]["function_name"], "feature_flags");
+        assert_eq!(lines[0]["count"], 3);
+    }
+
+    #[test]
+    fn test_fields_cannot_spoof_invocation_id() {
+        with_host(|h| {
+            h.invocation_context = Some(
+                r#"{"function_name":"f","deployment_id":"d","invocation_id":"real","cold_start":false}"#.to_string(),
+            )
+        });
+
+        log_structured(LogLevel::Warn, "x", &[("invocation_id", Value::from("fake"))]);
+
+        assert_eq!(logged_lines()[0]["invocation_id"], "real");
+    }
+
+    #[test]
+    fn test_redacts_client_ip() {
+        with_host(|h| h.client_info = Some(r#"{"ip":"203.0.113.7","country":"FR"}"#.to_string()));
+        let info = client::get_client_info().unwrap();
+        let ip = info.dangerously_raw_ip().unwrap();
+
+        log_structured(
+            LogLevel::Info,
+            &format!("visitor {} from FR", ip),
+            &[("client_ip", Value::from(ip)), ("hops", serde_json::json!([ip, "10.0.0.1"]))],
+        );
+
+        let raw = with_host(|h| h.logs.clone()).concat();
+        assert!(!raw.contains("203.0.113.7"));
+        let line = &logged_lines()[0];
+        assert_eq!(line["message"], "visitor [redacted] from FR");
+        assert_eq!(line["client_ip"], client::REDACTED);
+        assert_eq!(line["hops"][1], "10.0.0.1");
+    }
+
+    #[test]
+    fn test_without_host_context() {
+        log_structured(LogLevel::Error, "boom", &[]);
+
+        let lines = logged_lines();
+        assert_eq!(lines[0]["level"], "error");
+        assert!(lines[0].get("invocation_id").is_none());
+    }
+}
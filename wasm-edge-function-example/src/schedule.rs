@@ -0,0 +1,217 @@
+//! Scheduled execution SDK
+//!
+//! Besides request handlers, a module can export `handle_schedule(ptr, len)`
+//! for cron-like triggers. The host writes a JSON `ScheduleEvent` into module
+//! memory (via `alloc`) and calls the export; it returns a pointer to a
+//! length-prefixed JSON `ScheduleStatus`:
+//!
+//! - bytes 0..4: JSON length as little-endian u32
+//! - bytes 4..: UTF-8 JSON document
+//!
+//! The host must release the buffer with `dealloc(ptr, 4 + len)`.
+//!
+//! `schedule_handlers!` generates the export from a table of schedule names
+//! and handler functions, so request and schedule handlers live in the same
+//! module and share its helpers.
+
+use serde::{Deserialize, Serialize};
+
+/// Attempts (including the first) before a failing run is given up
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry (seconds); doubles on each attempt
+pub const BASE_RETRY_DELAY_SECS: u32 = 5;
+
+/// Upper bound on the retry delay (seconds)
+pub const MAX_RETRY_DELAY_SECS: u32 = 300;
+
+/// Trigger passed by the host to `handle_schedule`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ScheduleEvent {
+    /// Name of the schedule that fired (selects the handler)
+    pub schedule_name: String,
+    /// Unix time the run was scheduled for (not when it started)
+    pub scheduled_time: u64,
+    /// 1 for the first run of this trigger, incremented on each retry
+    #[serde(default = "default_attempt")]
+    pub attempt: u32,
+}
+
+fn default_attempt() -> u32 {
+    1
+}
+
+/// Why a scheduled handler failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// Transient failure (e.g. origin unreachable); the host may run it again
+    Retryable(String),
+    /// Permanent failure; retrying won't help
+    Fatal(String),
+}
+
+/// Signature of a scheduled handler
+pub type ScheduleHandler = fn(&ScheduleEvent) -> Result<(), ScheduleError>;
+
+/// Outcome reported back to the host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    /// The run completed
+    Ok,
+    /// The run failed transiently; run it again after `retry_after_secs`
+    Retry { retry_after_secs: u32, error: String },
+    /// The run failed and must not be retried
+    Failed { error: String },
+}
+
+/// Delay before retrying after failed attempt `attempt`, or `None` once
+/// `MAX_ATTEMPTS` is reached
+pub fn retry_delay(attempt: u32) -> Option<u32> {
+    if attempt >= MAX_ATTEMPTS {
+        return None;
+    }
+    let exponent = attempt.saturating_sub(1).min(31);
+    Some(
+        BASE_RETRY_DELAY_SECS
+            .saturating_mul(1u32 << exponent)
+            .min(MAX_RETRY_DELAY_SECS),
+    )
+}
+
+/// Map a handler result to the status returned to the host
+pub fn status_for(event: &ScheduleEvent, result: Result<(), ScheduleError>) -> ScheduleStatus {
+    match result {
+        Ok(()) => ScheduleStatus::Ok,
+        Err(ScheduleError::Retryable(error)) => match retry_delay(event.attempt) {
+            Some(retry_after_secs) => ScheduleStatus::Retry { retry_after_secs, error },
+            None => ScheduleStatus::Failed {
+                error: format!("{} (gave up after {} attempts)", error, event.attempt),
+            },
+        },
+        Err(ScheduleError::Fatal(error)) => ScheduleStatus::Failed { error },
+    }
+}
+
+/// Parse a host event and run the matching handler
+///
+/// Malformed input and unknown schedule names fail without retry.
+pub fn dispatch(input: &[u8], handlers: &[(&str, ScheduleHandler)]) -> ScheduleStatus {
+    let event: ScheduleEvent = match serde_json::from_slice(input) {
+        Ok(event) => event,
+        Err(e) => {
+            return ScheduleStatus::Failed {
+                error: format!("invalid schedule event: {}", e),
+            }
+        }
+    };
+
+    match handlers.iter().find(|(name, _)| *name == event.schedule_name) {
+        Some((_, handler)) => status_for(&event, handler(&event)),
+        None => ScheduleStatus::Failed {
+            error: format!("unknown schedule: {}", event.schedule_name),
+        },
+    }
+}
+
+/// Copy a status into a length-prefixed buffer owned by the host
+pub fn into_length_prefixed(status: &ScheduleStatus) -> *mut u8 {
+    let json = serde_json::to_vec(status).unwrap_or_else(|_| br#"{"status":"failed"}"#.to_vec());
+
+    let mut buf = Vec::with_capacity(4 + json.len());
+    buf.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&json);
+    // Capacity must equal the length the host passes back to dealloc
+    buf.shrink_to_fit();
+
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Define the module's `handle_schedule(ptr, len)` export
+///
+/// ```ignore
+/// schedule_handlers! {
+///     "refresh_rates" => refresh_rates_cron,
+/// }
+/// ```
+#[macro_export]
+macro_rules! schedule_handlers {
+    ($($name:literal => $handler:path),* $(,)?) => {
+        // The host owns the pointer contract for this export
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        #[no_mangle]
+        pub extern "C" fn handle_schedule(ptr: *const u8, len: u32) -> *mut u8 {
+            let input = unsafe { core::slice::from_raw_parts(ptr, len as usize) };
+            let handlers: &[(&str, $crate::schedule::ScheduleHandler)] = &[$(($name, $handler)),*];
+            $crate::schedule::into_length_prefixed(&$crate::schedule::dispatch(input, handlers))
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn succeed(_: &ScheduleEvent) -> Result<(), ScheduleError> {
+        Ok(())
+    }
+
+    fn flaky(_: &ScheduleEvent) -> Result<(), ScheduleError> {
+        Err(ScheduleError::Retryable("origin timeout".to_string()))
+    }
+
+    const HANDLERS: &[(&str, ScheduleHandler)] = &[("ok", succeed), ("flaky", flaky)];
+
+    fn event(name: &str, attempt: u32) -> Vec<u8> {
+        format!(r#"{{"schedule_name":"{}","scheduled_time":1700000000,"attempt":{}}}"#, name, attempt).into_bytes()
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Some(5));
+        assert_eq!(retry_delay(2), Some(10));
+        assert_eq!(retry_delay(4), Some(40));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+        assert_eq!(retry_delay(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_dispatch_by_name() {
+        assert_eq!(dispatch(&event("ok", 1), HANDLERS), ScheduleStatus::Ok);
+        assert_eq!(
+            dispatch(&event("flaky", 2), HANDLERS),
+            ScheduleStatus::Retry {
+                retry_after_secs: 10,
+                error: "origin timeout".to_string()
+            }
+        );
+        assert!(matches!(dispatch(&event("flaky", MAX_ATTEMPTS), HANDLERS), ScheduleStatus::Failed { .. }));
+    }
+
+    #[test]
+    fn test_dispatch_unknown_or_malformed() {
+        assert!(matches!(dispatch(&event("nope", 1), HANDLERS), ScheduleStatus::Failed { .. }));
+        assert!(matches!(dispatch(b"not json", HANDLERS), ScheduleStatus::Failed { .. }));
+    }
+
+    #[test]
+    fn test_attempt_defaults_to_first() {
+        let event: ScheduleEvent = serde_json::from_str(r#"{"schedule_name":"ok","scheduled_time":1}"#).unwrap();
+        assert_eq!(event.attempt, 1);
+    }
+
+    #[test]
+    fn test_status_json() {
+        let status = ScheduleStatus::Retry {
+            retry_after_secs: 5,
+            error: "x".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"status":"retry","retry_after_secs":5,"error":"x"}"#
+        );
+        assert_eq!(serde_json::to_string(&ScheduleStatus::Ok).unwrap(), r#"{"status":"ok"}"#);
+    }
+}
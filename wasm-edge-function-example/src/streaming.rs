@@ -0,0 +1,162 @@
+//! Streamed responses
+//!
+//! `response_set_body` needs the whole body in Wasm memory. For proxy-style
+//! functions relaying large origin responses, `ResponseWriter` sends the body
+//! to the client in chunks instead:
+//!
+//! ```ignore
+//! let mut writer = ResponseWriter::start(200, &headers)?;
+//! writer.write(b"chunk")?;
+//! writer.end()?;
+//! ```
+//!
+//! Call order is enforced by the types: a writer only exists once the
+//! response has started, and `end` consumes it. A writer dropped without
+//! `end` is finished by the host when the function returns.
+
+use crate::headers::Headers;
+use crate::host;
+
+/// Chunk size used when relaying the host's shared buffer (64KB)
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many times a would-block write is retried before giving up
+pub const MAX_WOULD_BLOCK_RETRIES: u32 = 64;
+
+/// Typed stream error, decoded from the host's negative return codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamError {
+    /// The host rejected the call (e.g. invalid headers)
+    Failed,
+    /// A chunk was written before the response started
+    NotStarted,
+    /// A chunk was written after the response ended
+    Ended,
+    /// The response was already started (or already sent with `response_set_body`)
+    AlreadyStarted,
+    /// The client kept the host's send buffer full for every retry
+    Backpressure,
+    /// The host's shared buffer couldn't be read
+    SharedBuffer,
+}
+
+const WOULD_BLOCK: i32 = -4;
+
+impl StreamError {
+    fn from_code(code: i32) -> Self {
+        match code {
+            -2 => StreamError::NotStarted,
+            -3 => StreamError::Ended,
+            WOULD_BLOCK => StreamError::Backpressure,
+            -5 => StreamError::AlreadyStarted,
+            _ => StreamError::Failed,
+        }
+    }
+}
+
+fn check(code: i32) -> Result<(), StreamError> {
+    if code < 0 {
+        return Err(StreamError::from_code(code));
+    }
+    Ok(())
+}
+
+/// A started streamed response
+#[derive(Debug)]
+pub struct ResponseWriter {
+    _started: (),
+}
+
+impl ResponseWriter {
+    /// Send the status and headers, starting the streamed response
+    pub fn start(status: u16, headers: &Headers) -> Result<Self, StreamError> {
+        let json = headers.to_json();
+        check(unsafe { host::response_start(status as u32, json.as_ptr(), json.len() as u32) })?;
+        Ok(Self { _started: () })
+    }
+
+    /// Send a body chunk, retrying while the host reports backpressure
+    pub fn write(&mut self, chunk: &[u8]) -> Result<(), StreamError> {
+        for _ in 0..=MAX_WOULD_BLOCK_RETRIES {
+            let result = unsafe { host::response_write(chunk.as_ptr(), chunk.len() as u32) };
+            if result != WOULD_BLOCK {
+                return check(result);
+            }
+        }
+        Err(StreamError::Backpressure)
+    }
+
+    /// Relay the first `len` bytes of the host's shared buffer in
+    /// `STREAM_CHUNK_SIZE` chunks
+    ///
+    /// Only one chunk is held in Wasm memory at a time. Host calls that
+    /// replace the shared buffer (cache, HTTP, KV, invocation context) must
+    /// not be made until this returns.
+    pub fn write_shared_buffer(&mut self, len: u32) -> Result<(), StreamError> {
+        let mut chunk = vec![0u8; STREAM_CHUNK_SIZE.min(len as usize)];
+        let mut offset = 0u32;
+        while offset < len {
+            let size = (len - offset).min(STREAM_CHUNK_SIZE as u32);
+            let copied = unsafe { host::get_shared_buffer(chunk.as_mut_ptr(), offset, size) };
+            if copied != size as i32 {
+                return Err(StreamError::SharedBuffer);
+            }
+            self.write(&chunk[..size as usize])?;
+            offset += size;
+        }
+        Ok(())
+    }
+
+    /// Finish the response
+    pub fn end(self) -> Result<(), StreamError> {
+        check(unsafe { host::response_end() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::{with_host, STREAM_ERR_ENDED, STREAM_ERR_NOT_STARTED};
+
+    fn headers() -> Headers {
+        let mut headers = Headers::new();
+        headers.set("content-type", "text/plain").unwrap();
+        headers
+    }
+
+    #[test]
+    fn test_ordering_violations() {
+        // Raw host calls out of order are rejected and decode to typed errors
+        let chunk = b"early";
+        let code = unsafe { host::response_write(chunk.as_ptr(), chunk.len() as u32) };
+        assert_eq!(code, STREAM_ERR_NOT_STARTED);
+        assert_eq!(check(code), Err(StreamError::NotStarted));
+
+        let mut writer = ResponseWriter::start(200, &headers()).unwrap();
+        assert_eq!(ResponseWriter::start(200, &headers()).unwrap_err(), StreamError::AlreadyStarted);
+        writer.write(b"body").unwrap();
+        writer.end().unwrap();
+
+        let code = unsafe { host::response_write(chunk.as_ptr(), chunk.len() as u32) };
+        assert_eq!(code, STREAM_ERR_ENDED);
+        assert_eq!(check(code), Err(StreamError::Ended));
+
+        let (status, sent, chunks) = with_host(|h| (h.stream_status, h.stream_headers.clone(), h.stream_chunks.clone()));
+        assert_eq!(status, Some(200));
+        assert_eq!(sent, vec![("Content-Type".to_string(), "text/plain".to_string())]);
+        assert_eq!(chunks, vec![b"body".to_vec()]);
+    }
+
+    #[test]
+    fn test_backpressure_retried_within_budget() {
+        let mut writer = ResponseWriter::start(200, &headers()).unwrap();
+
+        with_host(|h| h.stream_would_block = 3);
+        writer.write(b"a").unwrap();
+        assert_eq!(with_host(|h| h.stream_chunks.clone()), vec![b"a".to_vec()]);
+
+        with_host(|h| h.stream_would_block = MAX_WOULD_BLOCK_RETRIES + 1);
+        assert_eq!(writer.write(b"b"), Err(StreamError::Backpressure));
+        assert_eq!(with_host(|h| h.stream_chunks.len()), 1);
+    }
+}
@@ -0,0 +1,137 @@
+//! Tenant quota and usage SDK
+//!
+//! The host reports how much of the tenant's billing period quota has been
+//! used, so functions that fan out to external APIs can degrade gracefully
+//! (serve stale data, skip optional calls) before the host cuts them off.
+//!
+//! Usage changes during an invocation (every outbound request adds egress),
+//! so unlike the invocation context it is fetched from the host on each call.
+//! When the host doesn't report usage, every check reports "not exceeded":
+//! a function should never degrade because the data is missing.
+
+use serde::Deserialize;
+
+use crate::{host, read_shared_buffer};
+
+/// Tenant usage for the current billing period, as reported by the host
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Usage {
+    /// Invocations so far this period
+    pub invocations_this_period: u64,
+    /// Invocations allowed per period (None: unlimited)
+    #[serde(default)]
+    pub invocation_limit: Option<u64>,
+    /// Outbound bytes so far this period
+    pub egress_bytes: u64,
+    /// Outbound bytes allowed per period (None: unlimited)
+    #[serde(default)]
+    pub egress_limit: Option<u64>,
+    /// When the period (and the counters) reset, unix seconds
+    pub period_ends_at: u64,
+}
+
+/// Quota a usage check applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    Invocations,
+    Egress,
+}
+
+impl Usage {
+    /// Amount used and the limit for a quota (None when unlimited)
+    fn used_and_limit(&self, kind: QuotaKind) -> Option<(u64, u64)> {
+        match kind {
+            QuotaKind::Invocations => self.invocation_limit.map(|limit| (self.invocations_this_period, limit)),
+            QuotaKind::Egress => self.egress_limit.map(|limit| (self.egress_bytes, limit)),
+        }
+    }
+
+    /// Whether more than `percent` of a quota is used
+    ///
+    /// Unlimited quotas are never above any threshold.
+    pub fn above(&self, kind: QuotaKind, percent: u64) -> bool {
+        self.used_and_limit(kind)
+            .is_some_and(|(used, limit)| used as u128 * 100 > limit as u128 * percent as u128)
+    }
+
+    /// Whether a quota is used up
+    pub fn exceeded(&self, kind: QuotaKind) -> bool {
+        self.used_and_limit(kind).is_some_and(|(used, limit)| used >= limit)
+    }
+}
+
+/// Get the tenant's usage for the current billing period
+///
+/// Returns `None` when the host doesn't report usage (or sends malformed
+/// JSON). Replaces the contents of the shared buffer.
+pub fn get_usage() -> Option<Usage> {
+    let len = unsafe { host::get_usage() };
+    let json = read_shared_buffer(len)?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Whether a quota is used up (false when usage is unavailable)
+pub fn quota_exceeded(kind: QuotaKind) -> bool {
+    get_usage().is_some_and(|usage| usage.exceeded(kind))
+}
+
+/// Whether more than `percent` of a quota is used (false when usage is
+/// unavailable)
+pub fn quota_above(kind: QuotaKind, percent: u64) -> bool {
+    get_usage().is_some_and(|usage| usage.above(kind, percent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_edge_testkit::with_host;
+
+    fn set_usage(egress_bytes: u64, egress_limit: Option<u64>) {
+        let usage = serde_json::json!({
+            "invocations_this_period": 1_000,
+            "invocation_limit": 1_000,
+            "egress_bytes": egress_bytes,
+            "egress_limit": egress_limit,
+            "period_ends_at": 1_700_000_000u64,
+        });
+        with_host(|h| h.usage = Some(usage.to_string()));
+    }
+
+    #[test]
+    fn test_parses_host_payload() {
+        set_usage(500, Some(1_000));
+
+        let usage = get_usage().unwrap();
+        assert_eq!(usage.invocations_this_period, 1_000);
+        assert_eq!(usage.invocation_limit, Some(1_000));
+        assert_eq!(usage.egress_bytes, 500);
+        assert_eq!(usage.egress_limit, Some(1_000));
+        assert_eq!(usage.period_ends_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        set_usage(950, Some(1_000));
+        assert!(!quota_above(QuotaKind::Egress, 95));
+        assert!(!quota_exceeded(QuotaKind::Egress));
+        // Invocations are at exactly the limit
+        assert!(quota_exceeded(QuotaKind::Invocations));
+
+        set_usage(951, Some(1_000));
+        assert!(quota_above(QuotaKind::Egress, 95));
+
+        set_usage(u64::MAX, None);
+        assert!(!quota_above(QuotaKind::Egress, 0));
+        assert!(!quota_exceeded(QuotaKind::Egress));
+    }
+
+    #[test]
+    fn test_unavailable_is_unrestricted() {
+        assert_eq!(get_usage(), None);
+        assert!(!quota_exceeded(QuotaKind::Egress));
+        assert!(!quota_above(QuotaKind::Invocations, 0));
+
+        with_host(|h| h.usage = Some("not json".to_string()));
+        assert!(!quota_exceeded(QuotaKind::Egress));
+    }
+}
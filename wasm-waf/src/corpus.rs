@@ -0,0 +1,252 @@
+//! Golden-file corpus for the rule engine
+//!
+//! Every `tests/corpus/*.jsonl` file holds one case per line:
+//! `{"name", "rule", "request": {"method", "uri", "headers", "body",
+//! "body_raw", "config"}, "expected": {"rule_ids", "blocked"}}`. `method`
+//! defaults to GET and every other request field but `uri` may be omitted;
+//! `config` is the host's WafConfig. `rule` names the rule a case exercises
+//! (omitted for general traffic): each rule needs at least one case that
+//! matches it and two that don't. `rule_ids` is the sorted set of rule ids
+//! that matched. Cases run through `analyze()` with the built-in ruleset.
+//!
+//! When a rule change is meant to alter outcomes, rewrite the expectations
+//! with `WAF_CORPUS_REGENERATE=1 cargo test corpus` and review the diff;
+//! anything else that moves is a regression.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::*;
+
+/// Set to rewrite every case's expectations from the current rule engine
+const REGENERATE_VAR: &str = "WAF_CORPUS_REGENERATE";
+
+/// Outcome a case pins down
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Expected {
+    rule_ids: Vec<u32>,
+    blocked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CorpusRequest {
+    #[serde(default = "default_method")]
+    method: String,
+    uri: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_raw: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    config: Value,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl CorpusRequest {
+    fn to_request(&self) -> Result<RequestData, String> {
+        let config = if self.config.is_null() {
+            WafConfig::default()
+        } else {
+            serde_json::from_value(self.config.clone()).map_err(|e| format!("config: {}", e))?
+        };
+        Ok(RequestData {
+            method: self.method.clone(),
+            uri: self.uri.clone(),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            body_raw: self.body_raw.clone(),
+            config,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Case {
+    name: String,
+    /// Rule this case exercises
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rule: Option<u32>,
+    request: CorpusRequest,
+    expected: Expected,
+}
+
+/// One corpus file and its cases, in file order
+struct CorpusFile {
+    path: PathBuf,
+    cases: Vec<Case>,
+}
+
+impl CorpusFile {
+    fn name(&self) -> String {
+        self.path.file_name().unwrap().to_string_lossy().into_owned()
+    }
+}
+
+fn corpus_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+/// Every corpus file, sorted by name, panicking on a malformed line
+fn load_corpus() -> Vec<CorpusFile> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(corpus_dir())
+        .expect("tests/corpus")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let text = fs::read_to_string(&path).unwrap();
+            let cases = text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    serde_json::from_str(line)
+                        .unwrap_or_else(|e| panic!("{}:{}: {}", path.display(), i + 1, e))
+                })
+                .collect();
+            CorpusFile { path, cases }
+        })
+        .collect()
+}
+
+/// Run a request and reduce the result to what the corpus records
+fn outcome(request: &CorpusRequest) -> Result<Expected, String> {
+    let result = analyze(request.to_request()?);
+    let rule_ids: BTreeSet<u32> = result.matches.iter().map(|m| m.rule_id).collect();
+    Ok(Expected {
+        rule_ids: rule_ids.into_iter().collect(),
+        blocked: result.blocked,
+    })
+}
+
+/// How `actual` differs from `expected`, e.g. `rule_ids +942120 -941100; blocked true -> false`
+fn describe_diff(expected: &Expected, actual: &Expected) -> String {
+    let mut changes: Vec<String> = actual
+        .rule_ids
+        .iter()
+        .filter(|id| !expected.rule_ids.contains(id))
+        .map(|id| format!("+{}", id))
+        .collect();
+    changes.extend(
+        expected
+            .rule_ids
+            .iter()
+            .filter(|id| !actual.rule_ids.contains(id))
+            .map(|id| format!("-{}", id)),
+    );
+
+    let mut parts = Vec::new();
+    if !changes.is_empty() {
+        parts.push(format!("rule_ids {}", changes.join(" ")));
+    }
+    if expected.blocked != actual.blocked {
+        parts.push(format!("blocked {} -> {}", expected.blocked, actual.blocked));
+    }
+    parts.join("; ")
+}
+
+/// Replay every case, returning one line per mismatch
+fn mismatches(corpus: &[CorpusFile]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for file in corpus {
+        for (i, case) in file.cases.iter().enumerate() {
+            let diff = match outcome(&case.request) {
+                Ok(actual) if actual == case.expected => continue,
+                Ok(actual) => describe_diff(&case.expected, &actual),
+                Err(e) => e,
+            };
+            lines.push(format!("{} #{} \"{}\": {}", file.name(), i + 1, case.name, diff));
+        }
+    }
+    lines
+}
+
+/// Rewrite every corpus file with the current outcomes, returning what changed
+fn regenerate(corpus: &mut [CorpusFile]) -> Vec<String> {
+    let changed = mismatches(corpus);
+    for file in corpus.iter_mut() {
+        let mut text = String::new();
+        for case in &mut file.cases {
+            case.expected = outcome(&case.request).unwrap_or_else(|e| panic!("{}: {}", case.name, e));
+            text.push_str(&serde_json::to_string(case).unwrap());
+            text.push('\n');
+        }
+        fs::write(&file.path, text).unwrap();
+    }
+    changed
+}
+
+#[test]
+fn test_corpus_matches_golden() {
+    let mut corpus = load_corpus();
+    let cases: usize = corpus.iter().map(|file| file.cases.len()).sum();
+    assert!(cases >= 100, "corpus has only {} cases", cases);
+
+    if std::env::var_os(REGENERATE_VAR).is_some() {
+        for line in regenerate(&mut corpus) {
+            eprintln!("regenerated {}", line);
+        }
+        return;
+    }
+
+    let mismatches = mismatches(&corpus);
+    assert!(
+        mismatches.is_empty(),
+        "{} corpus mismatches (if intended, rerun with {}=1 to accept):\n{}",
+        mismatches.len(),
+        REGENERATE_VAR,
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_corpus_covers_every_rule() {
+    let corpus = load_corpus();
+    let cases: Vec<&Case> = corpus.iter().flat_map(|file| &file.cases).collect();
+    let rule_ids = build_rules()
+        .iter()
+        .map(|rule| rule.id)
+        .chain(SYNTHETIC_RULE_IDS.iter().copied())
+        .collect::<Vec<_>>();
+
+    for id in rule_ids {
+        let targeted: Vec<&&Case> = cases.iter().filter(|case| case.rule == Some(id)).collect();
+        let positives = targeted.iter().filter(|case| case.expected.rule_ids.contains(&id)).count();
+        let negatives = targeted.len() - positives;
+        assert!(positives >= 1, "no case matching rule {}", id);
+        assert!(negatives >= 2, "rule {} has {} negative cases, want 2", id, negatives);
+    }
+}
+
+#[test]
+fn test_corpus_reports_mismatch() {
+    let mut corpus = load_corpus();
+    let case = corpus
+        .iter_mut()
+        .flat_map(|file| file.cases.iter_mut())
+        .find(|case| case.expected.rule_ids == [942100])
+        .expect("union select case");
+    case.expected.rule_ids = vec![941100];
+    case.expected.blocked = false;
+
+    let mismatches = mismatches(&corpus);
+    assert_eq!(mismatches.len(), 1, "{:?}", mismatches);
+    assert!(
+        mismatches[0].ends_with("rule_ids +942100 -941100; blocked false -> true"),
+        "{}",
+        mismatches[0]
+    );
+}
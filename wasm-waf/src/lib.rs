@@ -7,6 +7,20 @@
 //! - analyze_request(ptr, len) -> ptr to JSON result
 //! - alloc(size) -> ptr for host to write data
 //! - dealloc(ptr, size) -> free memory
+//! - get_signatures_version() -> version of the shared aegis-signatures tables
+//! - get_stats() -> ptr to JSON per-rule hit/block counters
+//! - reset_stats() -> zero the per-rule counters
+//! - add_rule(ptr, len) / update_rule(ptr, len) / remove_rule(rule_id) ->
+//!   ptr to JSON ruleset update result
+//! - get_ruleset_info() -> ptr to JSON ruleset revision and rule ids
+//! - self_test() -> ptr to JSON canary report for the active ruleset
+//! - load_rules(ptr, len) / unload_rules(ptr, len) -> ptr to JSON zone
+//!   ruleset load result
+//! - get_memory_report() -> ptr to JSON per-zone ruleset memory usage
+//! - result_schema() -> ptr to the JSON Schema of analyze_request results
+//!
+//! All JSON results are canonical (sorted keys, no whitespace); see
+//! `schema` for how WafResult is versioned.
 //!
 //! Resource Limits (enforced by host):
 //! - Max execution: 10ms
@@ -15,14 +29,50 @@
 
 use serde::{Deserialize, Serialize};
 use std::alloc::{alloc as std_alloc, dealloc as std_dealloc, Layout};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::slice;
 
+mod schema;
+
+use schema::{canonical_json, RESULT_SCHEMA_VERSION};
+
 /// WAF analysis result (matches wasm_runtime.rs WafResult)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WafResult {
+    /// RESULT_SCHEMA_VERSION of the module that wrote the result (1 when absent)
+    #[serde(default = "schema::legacy_schema_version")]
+    pub schema_version: u32,
     pub blocked: bool,
     pub matches: Vec<WafMatch>,
     pub execution_time_us: u64, // Set by host after return
+    /// A structurally valid challenge-clearance cookie was found; the host
+    /// must verify its HMAC (the key never enters the module)
+    #[serde(default)]
+    pub needs_hmac_check: bool,
+    /// Key id the host should verify the clearance cookie with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac_key_id: Option<String>,
+    /// Suggested client response, set when blocked (the host may override it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_response: Option<BlockResponse>,
+    /// Bytes produced by body decoding divided by the body's size (None when
+    /// there was no body)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_expansion_ratio: Option<f64>,
+}
+
+/// Suggested response for a blocked request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockResponse {
+    pub status_code: u16,
+    /// Stable `<category>:<rule_id>` of the top-severity match
+    pub block_reason_code: String,
+    /// Client-facing message; never contains matched values
+    pub message: String,
+    /// Retry-After seconds (429 responses only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
 }
 
 /// Individual rule match (matches wasm_runtime.rs WafMatch)
@@ -43,19 +93,435 @@ struct RequestData {
     uri: String,
     headers: Vec<(String, String)>,
     body: String,
+    /// Raw body bytes, sent by the host when the body isn't valid UTF-8 so
+    /// declared non-UTF-8 charsets can be transcoded
+    #[serde(default)]
+    body_raw: Option<Vec<u8>>,
+    /// Per-request configuration (read at time of check; defaults when omitted)
+    #[serde(default)]
+    config: WafConfig,
+}
+
+/// Host-supplied WAF configuration
+#[derive(Debug, Default, Deserialize)]
+struct WafConfig {
+    /// Host sits behind a declared upstream proxy/CDN that may set URL override headers
+    #[serde(default)]
+    upstream_proxy: bool,
+    /// Treat X-Original-URL / X-Rewrite-URL as the request path and scan them
+    /// with the URI rule set (implies a trusted upstream proxy)
+    #[serde(default)]
+    trust_original_url: bool,
+    /// AEGIS-issued challenge-clearance cookie to validate (disabled when omitted)
+    #[serde(default)]
+    challenge_cookie: Option<ChallengeCookieConfig>,
+    /// Per-zone templates for the suggested block response
+    #[serde(default)]
+    block_response: BlockResponseConfig,
+    /// Origin/Referer policy for state-changing requests (disabled when omitted)
+    #[serde(default)]
+    csrf: Option<CsrfConfig>,
+    /// Per-header value length and repetition baselines
+    #[serde(default)]
+    header_limits: HeaderLimitsConfig,
+    /// Bounds on how much text body decoding may produce
+    #[serde(default)]
+    body_decode: BodyDecodeConfig,
+    /// Zone whose loaded ruleset (see load_rules) the request is checked
+    /// against; the instance-wide ruleset when omitted or not loaded
+    #[serde(default)]
+    zone_id: Option<String>,
+}
+
+/// Limits on body normalization output
+///
+/// Every decoded form of the body (charset transcode, then repeated base64,
+/// URL and HTML entity passes) counts toward one budget per request.
+#[derive(Debug, Deserialize)]
+struct BodyDecodeConfig {
+    /// Most decoded bytes per byte of body
+    #[serde(default = "default_body_max_expansion_ratio")]
+    max_expansion_ratio: usize,
+    /// Most decoded bytes per request, whatever the body size
+    #[serde(default = "default_body_max_decoded_bytes")]
+    max_decoded_bytes: usize,
+}
+
+impl Default for BodyDecodeConfig {
+    fn default() -> Self {
+        Self {
+            max_expansion_ratio: default_body_max_expansion_ratio(),
+            max_decoded_bytes: default_body_max_decoded_bytes(),
+        }
+    }
+}
+
+fn default_body_max_expansion_ratio() -> usize {
+    10
+}
+
+fn default_body_max_decoded_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+/// Per-header-name baselines for header anomaly checks
+///
+/// Header names are matched case-insensitively. A zone that supplies
+/// `max_value_length` replaces the whole default map.
+#[derive(Debug, Deserialize)]
+struct HeaderLimitsConfig {
+    /// Longest value accepted per header name, in bytes
+    #[serde(default = "default_header_max_value_length")]
+    max_value_length: HashMap<String, usize>,
+    /// Longest value accepted for headers not in `max_value_length`
+    #[serde(default = "default_header_default_max_length")]
+    default_max_value_length: usize,
+    /// Most times one header name may appear in a request
+    #[serde(default = "default_header_max_repetitions")]
+    max_repetitions: usize,
+    /// Header names legitimately repeated any number of times
+    #[serde(default = "default_header_repeatable")]
+    repeatable: Vec<String>,
+}
+
+impl Default for HeaderLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_value_length: default_header_max_value_length(),
+            default_max_value_length: default_header_default_max_length(),
+            max_repetitions: default_header_max_repetitions(),
+            repeatable: default_header_repeatable(),
+        }
+    }
+}
+
+impl HeaderLimitsConfig {
+    fn max_length(&self, name: &str) -> usize {
+        self.max_value_length
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map_or(self.default_max_value_length, |(_, limit)| *limit)
+    }
+
+    fn is_repeatable(&self, name: &str) -> bool {
+        self.repeatable.iter().any(|header| header.eq_ignore_ascii_case(name))
+    }
+}
+
+fn default_header_max_value_length() -> HashMap<String, usize> {
+    HashMap::from([
+        ("Cookie".to_string(), 8 * 1024),
+        ("Authorization".to_string(), 4 * 1024),
+    ])
+}
+
+fn default_header_default_max_length() -> usize {
+    1024
+}
+
+fn default_header_max_repetitions() -> usize {
+    5
+}
+
+fn default_header_repeatable() -> Vec<String> {
+    vec!["Set-Cookie".to_string(), "Via".to_string()]
+}
+
+/// Per-zone cross-origin policy
+///
+/// Only hosts are compared; the scheme and port of an origin are ignored.
+#[derive(Debug, Default, Deserialize)]
+struct CsrfConfig {
+    /// Hosts the zone accepts state-changing requests from, as bare hosts,
+    /// origins (`https://shop.example.com`) or `*.example.com` wildcards
+    /// (subdomains only). No foreign-origin check when empty.
+    #[serde(default)]
+    allowed_origins: Vec<String>,
+    /// Flag cookie-bearing POSTs that carry neither Origin nor Referer
+    #[serde(default)]
+    require_origin_with_cookies: bool,
+    /// Path prefixes (e.g. `/admin`) where CSRF matches are Error severity
+    #[serde(default)]
+    elevated_paths: Vec<String>,
+}
+
+/// Per-zone block response templates
+#[derive(Debug, Default, Deserialize)]
+struct BlockResponseConfig {
+    /// Status code per rule category (DEFAULT_BLOCK_STATUS otherwise)
+    #[serde(default)]
+    status_by_category: HashMap<String, u16>,
+    /// Retry-After seconds suggested with 429 responses
+    #[serde(default)]
+    retry_after_secs: Option<u64>,
+    /// Message template; `{code}` and `{status}` are substituted
+    #[serde(default)]
+    message_template: Option<String>,
+}
+
+/// Expected shape of the challenge-clearance cookie
+///
+/// The value is `<issued_at>.<nonce>.<mac>`: a unix timestamp, a random nonce,
+/// and an HMAC over the first two parts. The module only checks structure and
+/// age; the host verifies the MAC with the key named by `hmac_key_id`.
+#[derive(Debug, Deserialize)]
+struct ChallengeCookieConfig {
+    /// Cookie name (e.g. `__aegis_clr`)
+    name: String,
+    /// Id of the HMAC key the host verifies the cookie with
+    hmac_key_id: String,
+    /// Current unix time, supplied by the host
+    now: u64,
+    /// Maximum cookie age in seconds
+    #[serde(default = "default_challenge_max_age")]
+    max_age_secs: u64,
+    /// Separator between the three parts
+    #[serde(default = "default_challenge_separator")]
+    separator: char,
+    /// Minimum length of the whole cookie value
+    #[serde(default = "default_challenge_min_length")]
+    min_length: usize,
+    /// Maximum length of the whole cookie value
+    #[serde(default = "default_challenge_max_length")]
+    max_length: usize,
+}
+
+fn default_challenge_max_age() -> u64 {
+    3600
+}
+
+fn default_challenge_separator() -> char {
+    '.'
+}
+
+fn default_challenge_min_length() -> usize {
+    32
+}
+
+fn default_challenge_max_length() -> usize {
+    256
+}
+
+/// Status suggested for blocked requests unless the zone maps the category
+const DEFAULT_BLOCK_STATUS: u16 = 403;
+
+/// Message suggested for blocked requests unless the zone sets a template
+const DEFAULT_BLOCK_MESSAGE: &str = "Request blocked by AEGIS WAF (reference: {code})";
+
+/// Headers upstream proxies use to carry the pre-rewrite request path.
+/// Attackers abuse them to smuggle paths past path-based ACLs.
+const URL_OVERRIDE_HEADERS: &[&str] = &["X-Original-URL", "X-Rewrite-URL"];
+
+/// Rule id for URL override headers arriving without a declared upstream proxy
+const URL_OVERRIDE_SPOOFING_RULE_ID: u32 = 921200;
+
+/// Rule id for a challenge-clearance cookie that isn't `<issued_at>.<nonce>.<mac>`
+const CHALLENGE_COOKIE_MALFORMED_RULE_ID: u32 = 949500;
+
+/// Rule id for a challenge-clearance cookie older than the configured max age
+const CHALLENGE_COOKIE_EXPIRED_RULE_ID: u32 = 949510;
+
+/// Clock skew tolerated for clearance cookies issued "in the future" (seconds)
+const CHALLENGE_COOKIE_CLOCK_SKEW_SECS: u64 = 60;
+
+/// Rule id for a repeated query parameter whose instances or concatenation
+/// match an injection rule (HTTP parameter pollution)
+const HPP_INJECTION_RULE_ID: u32 = 921170;
+
+/// Rule id for any repetition of a security-relevant query parameter
+const HPP_SENSITIVE_PARAM_RULE_ID: u32 = 921180;
+
+/// Rule categories re-checked against duplicated parameter values
+const HPP_INJECTION_CATEGORIES: &[&str] = &["sqli", "xss", "rce", "path-traversal"];
+
+/// Parameter names whose repetition is worth flagging even without a payload
+/// (backends disagree on first-wins vs last-wins for these)
+const HPP_SENSITIVE_PARAMS: &[&str] = &["token", "redirect", "url", "id"];
+
+/// Rule id for bodies declaring a charset used to hide payloads from the WAF
+/// (UTF-7, EBCDIC, UTF-16 whose BOM contradicts the declared byte order)
+const CHARSET_SMUGGLING_RULE_ID: u32 = 920480;
+
+/// Rule id for bodies whose declared charset could not be transcoded; only
+/// the body as received was scanned
+const CHARSET_TRANSCODE_FAILED_RULE_ID: u32 = 920490;
+
+/// Rule id for a state-changing request whose Origin/Referer host isn't allowed
+const CSRF_FOREIGN_ORIGIN_RULE_ID: u32 = 943300;
+
+/// Rule id for a cookie-bearing POST without Origin or Referer (zone opt-in)
+const CSRF_MISSING_ORIGIN_RULE_ID: u32 = 943310;
+
+/// Rule id for a header value longer than its baseline
+const HEADER_VALUE_LENGTH_RULE_ID: u32 = 920500;
+
+/// Rule id for a header name repeated more than the baseline allows
+const HEADER_REPETITION_RULE_ID: u32 = 920510;
+
+/// Rule id for a body whose decoding outgrew the expansion budget; decoding
+/// stopped there and only the truncated output was scanned
+const BODY_EXPANSION_RULE_ID: u32 = 920520;
+
+/// Rule id for a GET, HEAD or OPTIONS request carrying a body (a common
+/// request smuggling and WAF evasion vector)
+const BODYLESS_METHOD_BODY_RULE_ID: u32 = 920170;
+
+/// Rule id for a declared Content-Length that disagrees with the body the
+/// host actually received
+const CONTENT_LENGTH_MISMATCH_RULE_ID: u32 = 920530;
+
+/// Methods that carry no body in legitimate traffic
+const BODYLESS_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// Content-Length disagreement tolerated before flagging: this many bytes or
+/// CONTENT_LENGTH_SLACK_PERCENT of the declared length, whichever is larger
+const CONTENT_LENGTH_SLACK_BYTES: usize = 64;
+const CONTENT_LENGTH_SLACK_PERCENT: usize = 10;
+
+/// Shortest run of base64 characters decoded in body text
+const MIN_BASE64_TOKEN_LENGTH: usize = 16;
+
+/// Methods the CSRF checks apply to
+const STATE_CHANGING_METHODS: &[&str] = &["POST", "PUT", "PATCH", "DELETE"];
+
+/// Largest body (in bytes) transcoded from its declared charset
+const MAX_TRANSCODE_BYTES: usize = 64 * 1024;
+
+/// Charsets the WAF flags outright: origins decode them, but payloads in
+/// them don't match ASCII patterns
+const UTF7_CHARSETS: &[&str] = &["utf-7", "utf7", "unicode-1-1-utf-7", "csunicode11utf7"];
+const EBCDIC_CHARSETS: &[&str] = &[
+    "ibm037", "cp037", "ebcdic-cp-us", "ibm500", "cp500", "ibm1026", "cp1026",
+];
+
+/// Charsets transcoded as ISO-8859-1 (bytes map 1:1 to code points)
+const LATIN1_CHARSETS: &[&str] = &["iso-8859-1", "iso8859-1", "latin1", "latin-1", "l1", "cp819"];
+
+/// Number of rules returned by `build_rules()`
+const RULE_COUNT: usize = 13;
+
+/// Most rules the in-memory ruleset may hold
+const MAX_RULES: usize = 256;
+
+/// Most patterns a single added or updated rule may have
+const MAX_RULE_PATTERNS: usize = 64;
+
+/// Most zones with a loaded ruleset per instance
+const MAX_ZONES: usize = 64;
+
+/// Longest zone id accepted by load_rules
+const MAX_ZONE_ID_LENGTH: usize = 128;
+
+/// Memory a zone's ruleset may use unless load_rules sets a cap
+const DEFAULT_ZONE_MEMORY_BYTES: usize = 512 * 1024;
+
+/// Largest per-zone cap load_rules accepts
+const MAX_ZONE_MEMORY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Memory all zone rulesets together may use, leaving the rest of the
+/// instance's 10MB for the default ruleset and request processing
+const ZONE_MEMORY_BUDGET_BYTES: usize = 6 * 1024 * 1024;
+
+/// Longest pattern accepted in an added or updated rule
+const MAX_PATTERN_LENGTH: usize = 256;
+
+/// Rule ids emitted outside the pattern rule set, counted after it
+const SYNTHETIC_RULE_IDS: &[u32] = &[
+    URL_OVERRIDE_SPOOFING_RULE_ID,
+    CHALLENGE_COOKIE_MALFORMED_RULE_ID,
+    CHALLENGE_COOKIE_EXPIRED_RULE_ID,
+    HPP_INJECTION_RULE_ID,
+    HPP_SENSITIVE_PARAM_RULE_ID,
+    CHARSET_SMUGGLING_RULE_ID,
+    CHARSET_TRANSCODE_FAILED_RULE_ID,
+    CSRF_FOREIGN_ORIGIN_RULE_ID,
+    CSRF_MISSING_ORIGIN_RULE_ID,
+    HEADER_VALUE_LENGTH_RULE_ID,
+    HEADER_REPETITION_RULE_ID,
+    BODY_EXPANSION_RULE_ID,
+    BODYLESS_METHOD_BODY_RULE_ID,
+    CONTENT_LENGTH_MISMATCH_RULE_ID,
+];
+
+/// One counter slot per pattern rule, then one per synthetic rule id
+const STAT_SLOTS: usize = RULE_COUNT + SYNTHETIC_RULE_IDS.len();
+
+/// Hit counters for one rule (saturating)
+#[derive(Debug, Clone, Copy, Default)]
+struct RuleCounters {
+    hits: u32,
+    blocks: u32,
+}
+
+thread_local! {
+    /// Per-instance rule counters (the module runs single-threaded)
+    static RULE_STATS: RefCell<[RuleCounters; STAT_SLOTS]> =
+        const { RefCell::new([RuleCounters { hits: 0, blocks: 0 }; STAT_SLOTS]) };
+
+    /// Ids of the built-in rules, in counter slot order
+    static BUILTIN_RULE_IDS: Vec<u32> = build_rules().iter().map(|rule| rule.id).collect();
+
+    /// Rules `analyze` runs with; starts as `build_rules()`
+    static RULESET: RefCell<Ruleset> = RefCell::new(Ruleset {
+        rules: build_rules(),
+        revision: 0,
+    });
+
+    /// Zone-scoped rulesets loaded by `load_rules`, by zone id
+    static ZONE_RULESETS: RefCell<HashMap<String, ZoneRuleset>> = RefCell::new(HashMap::new());
+}
+
+/// Rule statistics returned by `get_stats()`
+#[derive(Debug, Serialize)]
+struct WafStats {
+    rules: Vec<RuleStats>,
+}
+
+/// Counters for a single rule id
+#[derive(Debug, Serialize)]
+struct RuleStats {
+    rule_id: u32,
+    /// Matches reported for this rule
+    hits: u32,
+    /// Matches that were Critical in a blocked request
+    blocks: u32,
 }
 
 /// WAF Rule (internal) - simplified pattern matching
 struct WafRule {
     id: u32,
-    description: &'static str,
-    patterns: &'static [&'static str],
+    description: String,
+    patterns: Vec<String>,
     severity: u8,
-    category: &'static str,
+    category: String,
     case_sensitive: bool,
+    /// Severity override for matches in the request body (None: use `severity`)
+    body_override: Option<LocationOverride>,
+    /// Patterns lowercased once by `compile` (empty: fold on every match)
+    folded_patterns: Vec<String>,
+}
+
+/// Per-location severity override
+///
+/// Lets a rule that is critical in the URI score lower where the same
+/// pattern is common in legitimate traffic, unless the text still looks
+/// hostile there.
+struct LocationOverride {
+    /// Severity used when `keep_severity` doesn't hold for the scanned text
+    severity: u8,
+    /// True if the rule's own severity still applies to this text
+    keep_severity: fn(&str) -> bool,
 }
 
 impl WafRule {
+    /// Severity of a match in body text
+    fn body_severity(&self, text: &str) -> u8 {
+        match &self.body_override {
+            Some(o) if !(o.keep_severity)(text) => o.severity,
+            _ => self.severity,
+        }
+    }
+
     fn matches(&self, text: &str) -> Option<String> {
         let search_text = if self.case_sensitive {
             text.to_string()
@@ -63,7 +529,14 @@ impl WafRule {
             text.to_lowercase()
         };
 
-        for pattern in self.patterns {
+        if !self.folded_patterns.is_empty() {
+            return self.folded_patterns.iter().find_map(|pattern| {
+                let start = search_text.find(pattern.as_str())?;
+                Some(text[start..start + pattern.len()].to_string())
+            });
+        }
+
+        for pattern in &self.patterns {
             let search_pattern = if self.case_sensitive {
                 pattern.to_string()
             } else {
@@ -81,6 +554,414 @@ impl WafRule {
     }
 }
 
+impl WafRule {
+    /// Fold a case-insensitive rule's patterns up front
+    fn compile(mut self) -> Self {
+        if !self.case_sensitive {
+            self.folded_patterns = self.patterns.iter().map(|p| p.to_lowercase()).collect();
+        }
+        self
+    }
+
+    /// Bytes taken by the rule definition
+    fn storage_bytes(&self) -> usize {
+        std::mem::size_of::<WafRule>()
+            + self.description.len()
+            + self.category.len()
+            + string_table_bytes(&self.patterns)
+    }
+
+    /// Bytes taken by the compiled pattern table
+    fn matcher_bytes(&self) -> usize {
+        string_table_bytes(&self.folded_patterns)
+    }
+}
+
+fn string_table_bytes(strings: &[String]) -> usize {
+    strings.iter().map(|s| std::mem::size_of::<String>() + s.len()).sum()
+}
+
+fn to_patterns(patterns: &[&str]) -> Vec<String> {
+    patterns.iter().map(|p| p.to_string()).collect()
+}
+
+/// In-memory ruleset, changed by add_rule / update_rule / remove_rule
+struct Ruleset {
+    rules: Vec<WafRule>,
+    /// Bumped by every successful change
+    revision: u64,
+}
+
+/// Rule definition accepted by add_rule and update_rule
+#[derive(Debug, Deserialize)]
+struct RuleSpec {
+    id: u32,
+    description: String,
+    patterns: Vec<String>,
+    severity: u8,
+    category: String,
+    #[serde(default)]
+    case_sensitive: bool,
+}
+
+/// Why a ruleset change was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RulesetError {
+    InvalidJson,
+    InvalidRule,
+    /// The id belongs to a synthetic (non-pattern) rule
+    ReservedRuleId,
+    DuplicateRuleId,
+    UnknownRuleId,
+    TooManyRules,
+    InvalidZone,
+    UnknownZone,
+    TooManyZones,
+    /// Requested per-zone cap above MAX_ZONE_MEMORY_BYTES
+    InvalidMemoryCap,
+    /// The zone's ruleset needs more than its cap
+    ZoneMemoryExceeded,
+    /// All zone rulesets together would exceed ZONE_MEMORY_BUDGET_BYTES
+    MemoryBudgetExceeded,
+}
+
+impl RulesetError {
+    fn code(self) -> &'static str {
+        match self {
+            RulesetError::InvalidJson => "invalid_json",
+            RulesetError::InvalidRule => "invalid_rule",
+            RulesetError::ReservedRuleId => "reserved_rule_id",
+            RulesetError::DuplicateRuleId => "duplicate_rule_id",
+            RulesetError::UnknownRuleId => "unknown_rule_id",
+            RulesetError::TooManyRules => "too_many_rules",
+            RulesetError::InvalidZone => "invalid_zone",
+            RulesetError::UnknownZone => "unknown_zone",
+            RulesetError::TooManyZones => "too_many_zones",
+            RulesetError::InvalidMemoryCap => "invalid_memory_cap",
+            RulesetError::ZoneMemoryExceeded => "zone_memory_exceeded",
+            RulesetError::MemoryBudgetExceeded => "memory_budget_exceeded",
+        }
+    }
+}
+
+/// Result of a ruleset change, returned by add_rule / update_rule / remove_rule
+#[derive(Debug, Serialize)]
+struct RulesetUpdate {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+    /// Revision after the change (unchanged on error)
+    revision: u64,
+}
+
+/// Ruleset summary returned by `get_ruleset_info()`
+#[derive(Debug, Serialize)]
+struct RulesetInfo {
+    revision: u64,
+    rule_count: usize,
+    rule_ids: Vec<u32>,
+}
+
+impl RuleSpec {
+    /// Validate into a rule (added rules have no location overrides)
+    fn into_rule(self) -> Result<WafRule, RulesetError> {
+        if SYNTHETIC_RULE_IDS.contains(&self.id) {
+            return Err(RulesetError::ReservedRuleId);
+        }
+        let valid = self.id != 0
+            && !self.description.is_empty()
+            && !self.category.is_empty()
+            && (1..=5).contains(&self.severity)
+            && !self.patterns.is_empty()
+            && self.patterns.len() <= MAX_RULE_PATTERNS
+            && self
+                .patterns
+                .iter()
+                .all(|p| !p.is_empty() && p.len() <= MAX_PATTERN_LENGTH);
+        if !valid {
+            return Err(RulesetError::InvalidRule);
+        }
+
+        Ok(WafRule {
+            id: self.id,
+            description: self.description,
+            patterns: self.patterns,
+            severity: self.severity,
+            category: self.category,
+            case_sensitive: self.case_sensitive,
+            folded_patterns: Vec::new(),
+            body_override: None,
+        })
+    }
+}
+
+impl Ruleset {
+    fn position(&self, rule_id: u32) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.id == rule_id)
+    }
+
+    fn add(&mut self, spec: RuleSpec) -> Result<u64, RulesetError> {
+        let rule = spec.into_rule()?;
+        if self.position(rule.id).is_some() {
+            return Err(RulesetError::DuplicateRuleId);
+        }
+        if self.rules.len() >= MAX_RULES {
+            return Err(RulesetError::TooManyRules);
+        }
+        self.rules.push(rule);
+        self.revision += 1;
+        Ok(self.revision)
+    }
+
+    /// Replace a rule in place, keeping its evaluation order
+    fn update(&mut self, spec: RuleSpec) -> Result<u64, RulesetError> {
+        let rule = spec.into_rule()?;
+        let index = self.position(rule.id).ok_or(RulesetError::UnknownRuleId)?;
+        self.rules[index] = rule;
+        self.revision += 1;
+        Ok(self.revision)
+    }
+
+    fn remove(&mut self, rule_id: u32) -> Result<u64, RulesetError> {
+        let index = self.position(rule_id).ok_or(RulesetError::UnknownRuleId)?;
+        self.rules.remove(index);
+        self.revision += 1;
+        Ok(self.revision)
+    }
+}
+
+/// Apply a change to the ruleset and report the outcome
+fn change_ruleset(
+    change: impl FnOnce(&mut Ruleset) -> Result<u64, RulesetError>,
+) -> RulesetUpdate {
+    RULESET.with(|ruleset| {
+        let mut ruleset = ruleset.borrow_mut();
+        match change(&mut ruleset) {
+            Ok(revision) => RulesetUpdate { ok: true, error: None, revision },
+            Err(e) => RulesetUpdate { ok: false, error: Some(e.code()), revision: ruleset.revision },
+        }
+    })
+}
+
+fn add_rule_json(json: &[u8]) -> RulesetUpdate {
+    change_ruleset(|ruleset| {
+        let spec: RuleSpec = serde_json::from_slice(json).map_err(|_| RulesetError::InvalidJson)?;
+        ruleset.add(spec)
+    })
+}
+
+fn update_rule_json(json: &[u8]) -> RulesetUpdate {
+    change_ruleset(|ruleset| {
+        let spec: RuleSpec = serde_json::from_slice(json).map_err(|_| RulesetError::InvalidJson)?;
+        ruleset.update(spec)
+    })
+}
+
+fn ruleset_info() -> RulesetInfo {
+    RULESET.with(|ruleset| {
+        let ruleset = ruleset.borrow();
+        RulesetInfo {
+            revision: ruleset.revision,
+            rule_count: ruleset.rules.len(),
+            rule_ids: ruleset.rules.iter().map(|rule| rule.id).collect(),
+        }
+    })
+}
+
+/// Ruleset loaded for one zone, with its own compiled matcher
+struct ZoneRuleset {
+    rules: Vec<WafRule>,
+    /// Memory cap set at load time
+    max_memory_bytes: usize,
+}
+
+impl ZoneRuleset {
+    fn rule_bytes(&self) -> usize {
+        self.rules.iter().map(WafRule::storage_bytes).sum()
+    }
+
+    fn matcher_bytes(&self) -> usize {
+        self.rules.iter().map(WafRule::matcher_bytes).sum()
+    }
+
+    fn memory_bytes(&self) -> usize {
+        self.rule_bytes() + self.matcher_bytes()
+    }
+}
+
+/// Zone ruleset accepted by load_rules
+#[derive(Debug, Deserialize)]
+struct ZoneRulesetSpec {
+    zone_id: String,
+    /// Built-in rules the zone runs, by id (none when omitted)
+    #[serde(default)]
+    builtin_rule_ids: Vec<u32>,
+    /// The zone's own rules (same shape as add_rule)
+    #[serde(default)]
+    rules: Vec<RuleSpec>,
+    /// Cap on the zone's rule storage plus compiled matcher, in bytes
+    /// (DEFAULT_ZONE_MEMORY_BYTES when omitted)
+    #[serde(default)]
+    max_memory_bytes: Option<usize>,
+}
+
+impl ZoneRulesetSpec {
+    /// Validate and compile the zone's rules
+    fn compile(self) -> Result<(String, ZoneRuleset), RulesetError> {
+        if self.zone_id.is_empty() || self.zone_id.len() > MAX_ZONE_ID_LENGTH {
+            return Err(RulesetError::InvalidZone);
+        }
+        let max_memory_bytes = self.max_memory_bytes.unwrap_or(DEFAULT_ZONE_MEMORY_BYTES);
+        if max_memory_bytes > MAX_ZONE_MEMORY_BYTES {
+            return Err(RulesetError::InvalidMemoryCap);
+        }
+        if self.builtin_rule_ids.len() + self.rules.len() > MAX_RULES {
+            return Err(RulesetError::TooManyRules);
+        }
+
+        let mut builtins = build_rules();
+        let mut rules: Vec<WafRule> = Vec::with_capacity(self.builtin_rule_ids.len() + self.rules.len());
+        for id in self.builtin_rule_ids {
+            if rules.iter().any(|rule| rule.id == id) {
+                return Err(RulesetError::DuplicateRuleId);
+            }
+            let index = builtins
+                .iter()
+                .position(|rule| rule.id == id)
+                .ok_or(RulesetError::UnknownRuleId)?;
+            rules.push(builtins.swap_remove(index).compile());
+        }
+        for spec in self.rules {
+            let rule = spec.into_rule()?;
+            if rules.iter().any(|existing| existing.id == rule.id) {
+                return Err(RulesetError::DuplicateRuleId);
+            }
+            rules.push(rule.compile());
+        }
+
+        let zone = ZoneRuleset { rules, max_memory_bytes };
+        if zone.memory_bytes() > max_memory_bytes {
+            return Err(RulesetError::ZoneMemoryExceeded);
+        }
+        Ok((self.zone_id, zone))
+    }
+}
+
+/// Result of load_rules / unload_rules
+#[derive(Debug, Serialize)]
+struct ZoneRulesetUpdate {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+    /// Memory the zone's ruleset uses after the call (unchanged on error)
+    memory_bytes: usize,
+}
+
+/// Per-zone memory usage returned by `get_memory_report()`
+#[derive(Debug, Serialize)]
+struct MemoryReport {
+    zones: Vec<ZoneMemory>,
+    /// Sum over all zones
+    total_bytes: usize,
+    budget_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ZoneMemory {
+    zone_id: String,
+    rule_count: usize,
+    /// Rule definitions (ids, descriptions, categories, patterns)
+    rule_bytes: usize,
+    /// Compiled (case-folded) pattern tables
+    matcher_bytes: usize,
+    total_bytes: usize,
+    max_memory_bytes: usize,
+}
+
+/// Load (or replace) a zone's ruleset
+///
+/// Nothing changes unless the whole ruleset validates and fits both the
+/// zone's cap and the instance-wide zone budget.
+fn load_rules_json(json: &[u8]) -> ZoneRulesetUpdate {
+    let spec: ZoneRulesetSpec = match serde_json::from_slice(json) {
+        Ok(spec) => spec,
+        Err(_) => {
+            return ZoneRulesetUpdate {
+                ok: false,
+                error: Some(RulesetError::InvalidJson.code()),
+                memory_bytes: 0,
+            }
+        }
+    };
+    let zone_id = spec.zone_id.clone();
+
+    ZONE_RULESETS.with(|zones| {
+        let mut zones = zones.borrow_mut();
+        match load_zone(&mut zones, spec) {
+            Ok(memory_bytes) => ZoneRulesetUpdate { ok: true, error: None, memory_bytes },
+            Err(e) => ZoneRulesetUpdate {
+                ok: false,
+                error: Some(e.code()),
+                memory_bytes: zones.get(&zone_id).map_or(0, ZoneRuleset::memory_bytes),
+            },
+        }
+    })
+}
+
+/// Compile `spec` and swap it in; returns the zone's memory use
+fn load_zone(zones: &mut HashMap<String, ZoneRuleset>, spec: ZoneRulesetSpec) -> Result<usize, RulesetError> {
+    let (zone_id, zone) = spec.compile()?;
+    if !zones.contains_key(&zone_id) && zones.len() >= MAX_ZONES {
+        return Err(RulesetError::TooManyZones);
+    }
+    let others: usize = zones
+        .iter()
+        .filter(|(id, _)| **id != zone_id)
+        .map(|(_, zone)| zone.memory_bytes())
+        .sum();
+    let memory_bytes = zone.memory_bytes();
+    if others + memory_bytes > ZONE_MEMORY_BUDGET_BYTES {
+        return Err(RulesetError::MemoryBudgetExceeded);
+    }
+    zones.insert(zone_id, zone);
+    Ok(memory_bytes)
+}
+
+/// Drop a zone's ruleset; its requests fall back to the instance-wide ruleset
+fn unload_zone(zone_id: &str) -> ZoneRulesetUpdate {
+    ZONE_RULESETS.with(|zones| match zones.borrow_mut().remove(zone_id) {
+        Some(_) => ZoneRulesetUpdate { ok: true, error: None, memory_bytes: 0 },
+        None => ZoneRulesetUpdate {
+            ok: false,
+            error: Some(RulesetError::UnknownZone.code()),
+            memory_bytes: 0,
+        },
+    })
+}
+
+fn memory_report() -> MemoryReport {
+    ZONE_RULESETS.with(|zones| {
+        let mut zones: Vec<ZoneMemory> = zones
+            .borrow()
+            .iter()
+            .map(|(zone_id, zone)| ZoneMemory {
+                zone_id: zone_id.clone(),
+                rule_count: zone.rules.len(),
+                rule_bytes: zone.rule_bytes(),
+                matcher_bytes: zone.matcher_bytes(),
+                total_bytes: zone.memory_bytes(),
+                max_memory_bytes: zone.max_memory_bytes,
+            })
+            .collect();
+        zones.sort_by(|a, b| a.zone_id.cmp(&b.zone_id));
+        MemoryReport {
+            total_bytes: zones.iter().map(|zone| zone.total_bytes).sum(),
+            zones,
+            budget_bytes: ZONE_MEMORY_BUDGET_BYTES,
+        }
+    })
+}
+
 /// Build the 13 OWASP rules from Sprint 8 (simplified patterns)
 fn build_rules() -> Vec<WafRule> {
     vec![
@@ -89,30 +970,36 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 942100,
-            description: "SQL Injection Attack: Common DB names",
-            patterns: &[
+            description: "SQL Injection Attack: Common DB names".to_string(),
+            patterns: to_patterns(&[
                 "union select", "select from", "insert into",
                 "delete from", "drop table", "exec xp_",
-            ],
+            ]),
             severity: 5, // Critical
-            category: "sqli",
+            category: "sqli".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 942110,
-            description: "SQL Injection: Comment-based injection",
-            patterns: &["' or '", "' and '", "'--", "' --"],
+            description: "SQL Injection: Comment-based injection".to_string(),
+            patterns: to_patterns(&["' or '", "' and '", "'--", "' --"]),
             severity: 5, // Critical
-            category: "sqli",
+            category: "sqli".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 942120,
-            description: "SQL Injection: MySQL comments and operators",
-            patterns: &["/*!", "--", "xp_cmdshell", "sp_executesql"],
+            description: "SQL Injection: MySQL comments and operators".to_string(),
+            patterns: to_patterns(&["/*!", "--", "xp_cmdshell", "sp_executesql"]),
             severity: 4, // Error
-            category: "sqli",
+            category: "sqli".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
 
         // ========================================
@@ -120,35 +1007,43 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 941100,
-            description: "XSS Attack: Script tag injection",
-            patterns: &["<script", "</script>"],
+            description: "XSS Attack: Script tag injection".to_string(),
+            patterns: to_patterns(&["<script", "</script>"]),
             severity: 5, // Critical
-            category: "xss",
+            category: "xss".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 941110,
-            description: "XSS Attack: Event handler injection",
-            patterns: &["onerror=", "onload=", "onclick=", "onmouseover="],
+            description: "XSS Attack: Event handler injection".to_string(),
+            patterns: to_patterns(&["onerror=", "onload=", "onclick=", "onmouseover="]),
             severity: 5, // Critical
-            category: "xss",
+            category: "xss".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 941120,
-            description: "XSS Attack: JavaScript protocol",
-            patterns: &["javascript:"],
+            description: "XSS Attack: JavaScript protocol".to_string(),
+            patterns: to_patterns(&["javascript:"]),
             severity: 4, // Error
-            category: "xss",
+            category: "xss".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 941130,
-            description: "XSS Attack: Iframe injection",
-            patterns: &["<iframe"],
+            description: "XSS Attack: Iframe injection".to_string(),
+            patterns: to_patterns(&["<iframe"]),
             severity: 4, // Error
-            category: "xss",
+            category: "xss".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
 
         // ========================================
@@ -156,19 +1051,27 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 930100,
-            description: "Path Traversal: ../ patterns",
-            patterns: &["../", "..\\"],
+            description: "Path Traversal: ../ patterns".to_string(),
+            patterns: to_patterns(&["../", "..\\"]),
             severity: 5, // Critical
-            category: "path-traversal",
+            category: "path-traversal".to_string(),
             case_sensitive: true,
+            folded_patterns: Vec::new(),
+            // Relative paths are routine in JSON bodies (manifests, import maps)
+            body_override: Some(LocationOverride {
+                severity: 3, // Warning
+                keep_severity: is_hostile_traversal,
+            }),
         },
         WafRule {
             id: 930110,
-            description: "Path Traversal: /etc/passwd access",
-            patterns: &["/etc/passwd", "/etc/shadow", "../../etc"],
+            description: "Path Traversal: /etc/passwd access".to_string(),
+            patterns: to_patterns(&["/etc/passwd", "/etc/shadow", "../../etc"]),
             severity: 5, // Critical
-            category: "path-traversal",
+            category: "path-traversal".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
 
         // ========================================
@@ -176,19 +1079,23 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 932100,
-            description: "RCE: Unix shell command injection",
-            patterns: &["; ls", "; cat", "; wget", "; curl", "; bash", "; sh", "| cat", "| ls", "$(", "&& "],
+            description: "RCE: Unix shell command injection".to_string(),
+            patterns: to_patterns(&["; ls", "; cat", "; wget", "; curl", "; bash", "; sh", "| cat", "| ls", "$(", "&& "]),
             severity: 5, // Critical
-            category: "rce",
+            category: "rce".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
         WafRule {
             id: 932110,
-            description: "RCE: Windows commands",
-            patterns: &["cmd.exe", "powershell", "net.exe", "wscript"],
+            description: "RCE: Windows commands".to_string(),
+            patterns: to_patterns(&["cmd.exe", "powershell", "net.exe", "wscript"]),
             severity: 5, // Critical
-            category: "rce",
+            category: "rce".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
 
         // ========================================
@@ -196,11 +1103,13 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 920100,
-            description: "HTTP Protocol: Invalid method",
-            patterns: &["TRACE", "TRACK", "DEBUG"],
+            description: "HTTP Protocol: Invalid method".to_string(),
+            patterns: to_patterns(&["TRACE", "TRACK", "DEBUG"]),
             severity: 3, // Warning
-            category: "protocol",
+            category: "protocol".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
 
         // ========================================
@@ -208,62 +1117,971 @@ fn build_rules() -> Vec<WafRule> {
         // ========================================
         WafRule {
             id: 913100,
-            description: "Scanner Detection: Common scanner signatures",
-            patterns: &["nikto", "nmap", "masscan", "sqlmap", "dirbuster", "acunetix"],
+            description: "Scanner Detection: Common scanner signatures".to_string(),
+            patterns: to_patterns(aegis_signatures::SCANNER_SIGNATURES),
             severity: 4, // Error
-            category: "scanner",
+            category: "scanner".to_string(),
             case_sensitive: false,
+            folded_patterns: Vec::new(),
+            body_override: None,
         },
     ]
 }
 
-/// Analyze request and return matches
-fn analyze(request: RequestData) -> WafResult {
-    let rules = build_rules();
-    let mut matches = Vec::new();
-    let min_severity = 3; // Warning and above
+/// Minimum severity reported in results (Warning and above)
+const MIN_SEVERITY: u8 = 3;
 
-    // Check URI
-    for rule in &rules {
-        if let Some(matched_value) = rule.matches(&request.uri) {
-            if rule.severity >= min_severity {
+/// Consecutive `../` segments at which body traversal stays critical
+const TRAVERSAL_DEPTH_THRESHOLD: usize = 3;
+
+/// Targets that keep a body traversal critical when they follow it directly
+const SENSITIVE_TRAVERSAL_TARGETS: &[&str] = &[
+    "etc/passwd",
+    "etc/shadow",
+    "windows/system32",
+    ".ssh",
+    "proc/self",
+];
+
+/// Whether text contains a traversal that climbs `TRAVERSAL_DEPTH_THRESHOLD`
+/// or more levels, or lands directly on a sensitive target
+fn is_hostile_traversal(text: &str) -> bool {
+    let text = text.replace('\\', "/").to_lowercase();
+    let mut rest = text.as_str();
+    while let Some(pos) = rest.find("../") {
+        let mut depth = 0;
+        rest = &rest[pos..];
+        while let Some(next) = rest.strip_prefix("../") {
+            depth += 1;
+            // JSON-escaped backslashes leave doubled separators
+            rest = next.trim_start_matches('/');
+        }
+        if depth >= TRAVERSAL_DEPTH_THRESHOLD
+            || SENSITIVE_TRAVERSAL_TARGETS.iter().any(|t| rest.starts_with(t))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Scan a request path with the URI rule set
+fn check_uri(rules: &[WafRule], uri: &str, location: &str, matches: &mut Vec<WafMatch>) {
+    for rule in rules {
+        if let Some(matched_value) = rule.matches(uri) {
+            if rule.severity >= MIN_SEVERITY {
                 matches.push(WafMatch {
                     rule_id: rule.id,
                     description: rule.description.to_string(),
                     severity: rule.severity,
                     category: rule.category.to_string(),
                     matched_value,
-                    location: "URI".to_string(),
+                    location: location.to_string(),
                 });
             }
         }
     }
+}
 
-    // Check headers
-    for (name, value) in &request.headers {
-        for rule in &rules {
-            if let Some(matched_value) = rule.matches(value) {
-                if rule.severity >= min_severity {
-                    matches.push(WafMatch {
-                        rule_id: rule.id,
-                        description: rule.description.to_string(),
-                        severity: rule.severity,
-                        category: rule.category.to_string(),
-                        matched_value,
-                        location: format!("Header:{}", name),
-                    });
-                }
+/// Decode `%XX` escapes and `+` in a query component (invalid escapes kept as-is)
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("00");
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or(0));
+                i += 3;
+                continue;
             }
+            byte => decoded.push(byte),
         }
+        i += 1;
     }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
 
-    // Check method (for protocol violations)
-    for rule in &rules {
-        if rule.category == "protocol" {
-            if let Some(matched_value) = rule.matches(&request.method) {
-                if rule.severity >= min_severity {
-                    matches.push(WafMatch {
-                        rule_id: rule.id,
+/// Split a request URI's query string into decoded `(name, value)` pairs, in order
+fn parse_query(uri: &str) -> Vec<(String, String)> {
+    let query = match uri.split_once('?') {
+        Some((_, query)) => query,
+        None => return Vec::new(),
+    };
+    let query = query.split('#').next().unwrap_or("");
+
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Flag repeated query parameters (HTTP parameter pollution)
+///
+/// Backends resolve duplicates differently (first wins, last wins, or the
+/// values joined with `,`), so a payload split across instances can slip past
+/// per-value checks. Each instance and both joined forms are re-checked with
+/// the injection rules. The location lists every position of the name in the
+/// query string, e.g. `Query:id[0,1]`.
+fn check_parameter_pollution(rules: &[WafRule], uri: &str, matches: &mut Vec<WafMatch>) {
+    let params = parse_query(uri);
+
+    // Group positions by name, keeping first-seen order
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (index, (name, _)) in params.iter().enumerate() {
+        match groups.iter_mut().find(|(seen, _)| *seen == name.as_str()) {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((name.as_str(), vec![index])),
+        }
+    }
+
+    for (name, indices) in groups.iter().filter(|(_, indices)| indices.len() > 1) {
+        let values: Vec<&str> = indices.iter().map(|&i| params[i].1.as_str()).collect();
+        let positions: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+        let location = format!("Query:{}[{}]", name, positions.join(","));
+
+        let mut candidates: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        candidates.push(values.join(","));
+        candidates.push(values.concat());
+
+        let injection = rules
+            .iter()
+            .filter(|rule| HPP_INJECTION_CATEGORIES.contains(&rule.category.as_str()))
+            .find_map(|rule| {
+                candidates
+                    .iter()
+                    .find_map(|candidate| rule.matches(candidate))
+                    .map(|matched_value| (rule, matched_value))
+            });
+
+        if let Some((rule, matched_value)) = injection {
+            matches.push(WafMatch {
+                rule_id: HPP_INJECTION_RULE_ID,
+                description: format!("HPP: duplicated parameter matches rule {} ({})", rule.id, rule.description),
+                severity: 5, // Critical
+                category: "hpp".to_string(),
+                matched_value,
+                location,
+            });
+        } else if HPP_SENSITIVE_PARAMS.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+            // Informational: reported at the lowest severity that still shows up
+            matches.push(WafMatch {
+                rule_id: HPP_SENSITIVE_PARAM_RULE_ID,
+                description: "HPP: security-relevant parameter repeated".to_string(),
+                severity: MIN_SEVERITY, // Warning
+                category: "hpp".to_string(),
+                matched_value: values.join(","),
+                location,
+            });
+        }
+    }
+}
+
+/// Scan body text with every rule
+fn check_body(rules: &[WafRule], body: &str, location: &str, matches: &mut Vec<WafMatch>) {
+    for rule in rules {
+        if let Some(matched_value) = rule.matches(body) {
+            let severity = rule.body_severity(body);
+            if severity >= MIN_SEVERITY {
+                matches.push(WafMatch {
+                    rule_id: rule.id,
+                    description: rule.description.to_string(),
+                    severity,
+                    category: rule.category.to_string(),
+                    matched_value,
+                    location: location.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Lowercased `charset` parameter of the Content-Type header, if any
+fn content_type_charset(headers: &[(String, String)]) -> Option<String> {
+    let (_, value) = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))?;
+
+    value.split(';').skip(1).find_map(|param| {
+        let (key, charset) = param.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        let charset = charset.trim().trim_matches('"').to_ascii_lowercase();
+        (!charset.is_empty()).then_some(charset)
+    })
+}
+
+/// How the WAF treats a declared body charset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyCharset {
+    Utf7,
+    Ebcdic,
+    /// UTF-16; `big_endian` is None when the label doesn't fix the byte order
+    Utf16 { big_endian: Option<bool> },
+    Latin1,
+    /// UTF-8 or anything else scanned as received
+    Other,
+}
+
+fn classify_charset(charset: &str) -> BodyCharset {
+    match charset {
+        c if UTF7_CHARSETS.contains(&c) => BodyCharset::Utf7,
+        c if EBCDIC_CHARSETS.contains(&c) => BodyCharset::Ebcdic,
+        "utf-16" | "utf16" => BodyCharset::Utf16 { big_endian: None },
+        "utf-16be" | "utf16be" => BodyCharset::Utf16 { big_endian: Some(true) },
+        "utf-16le" | "utf16le" => BodyCharset::Utf16 { big_endian: Some(false) },
+        c if LATIN1_CHARSETS.contains(&c) => BodyCharset::Latin1,
+        _ => BodyCharset::Other,
+    }
+}
+
+/// Byte order announced by a UTF-16 byte order mark (true = big endian)
+fn utf16_bom(bytes: &[u8]) -> Option<bool> {
+    match bytes {
+        [0xFE, 0xFF, ..] => Some(true),
+        [0xFF, 0xFE, ..] => Some(false),
+        _ => None,
+    }
+}
+
+/// Decode UTF-16, honoring a BOM and defaulting to big endian (RFC 2781)
+fn decode_utf16(bytes: &[u8], declared_big_endian: Option<bool>) -> Option<String> {
+    let bom = utf16_bom(bytes);
+    let big_endian = bom.or(declared_big_endian).unwrap_or(true);
+    let bytes = if bom.is_some() { &bytes[2..] } else { bytes };
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if big_endian {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        })
+        .collect();
+    String::from_utf16(&units).ok()
+}
+
+/// Decode UTF-7 (RFC 2152): `+<modified base64 of UTF-16BE>-`, `+-` for '+'
+fn decode_utf7(bytes: &[u8]) -> Option<String> {
+    let mut out = String::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        if b != b'+' {
+            if !b.is_ascii() {
+                return None;
+            }
+            out.push(b as char);
+            continue;
+        }
+        if bytes.get(i) == Some(&b'-') {
+            out.push('+');
+            i += 1;
+            continue;
+        }
+
+        let mut bits: u32 = 0;
+        let mut bit_count = 0;
+        let mut units = Vec::new();
+        while let Some(&c) = bytes.get(i) {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => break,
+            };
+            bits = (bits << 6) | value as u32;
+            bit_count += 6;
+            if bit_count >= 16 {
+                bit_count -= 16;
+                units.push((bits >> bit_count) as u16);
+                bits &= (1 << bit_count) - 1;
+            }
+            i += 1;
+        }
+        // '-' ends the shifted run and is absorbed
+        if bytes.get(i) == Some(&b'-') {
+            i += 1;
+        }
+        out.push_str(&String::from_utf16(&units).ok()?);
+    }
+    Some(out)
+}
+
+/// Flag smuggling charsets and scan the body decoded from its declared
+/// charset (best effort, bounded by MAX_TRANSCODE_BYTES)
+fn check_body_charset(
+    rules: &[WafRule],
+    request: &RequestData,
+    charset: &str,
+    budget: &mut DecodeBudget,
+    matches: &mut Vec<WafMatch>,
+) {
+    let raw = request.body_raw.as_deref().unwrap_or(request.body.as_bytes());
+    let class = classify_charset(charset);
+    let location = format!("Body:charset={}", charset);
+
+    let smuggling = match class {
+        BodyCharset::Utf7 | BodyCharset::Ebcdic => true,
+        BodyCharset::Utf16 { big_endian: Some(declared) } => {
+            utf16_bom(raw).is_some_and(|bom| bom != declared)
+        }
+        _ => false,
+    };
+    if smuggling {
+        matches.push(WafMatch {
+            rule_id: CHARSET_SMUGGLING_RULE_ID,
+            description: "Charset smuggling: body declares a charset used to hide payloads".to_string(),
+            severity: 4, // Error
+            category: "charset-smuggling".to_string(),
+            matched_value: charset.to_string(),
+            location: location.clone(),
+        });
+    }
+
+    let decode: fn(&[u8]) -> Option<String> = match class {
+        BodyCharset::Utf7 => decode_utf7,
+        BodyCharset::Utf16 { big_endian: None } => |b| decode_utf16(b, None),
+        BodyCharset::Utf16 { big_endian: Some(true) } => |b| decode_utf16(b, Some(true)),
+        BodyCharset::Utf16 { big_endian: Some(false) } => |b| decode_utf16(b, Some(false)),
+        // A valid UTF-8 body was already scanned as-is
+        BodyCharset::Latin1 if request.body_raw.is_some() => {
+            |b| Some(b.iter().map(|&byte| byte as char).collect())
+        }
+        _ => return,
+    };
+
+    let decoded = if raw.len() <= MAX_TRANSCODE_BYTES {
+        decode(raw)
+    } else {
+        None
+    };
+    match decoded {
+        Some(text) => check_body(rules, &budget.charge(text), &location, matches),
+        None => matches.push(WafMatch {
+            rule_id: CHARSET_TRANSCODE_FAILED_RULE_ID,
+            description: "Charset: body could not be transcoded, scanned as received".to_string(),
+            severity: MIN_SEVERITY, // Warning
+            category: "charset-smuggling".to_string(),
+            matched_value: charset.to_string(),
+            location,
+        }),
+    }
+}
+
+/// Running total of decoded body text, checked against BodyDecodeConfig
+struct DecodeBudget {
+    input_len: usize,
+    limit: usize,
+    produced: usize,
+    exceeded: bool,
+}
+
+impl DecodeBudget {
+    fn new(input_len: usize, config: &BodyDecodeConfig) -> Self {
+        Self {
+            input_len,
+            limit: input_len
+                .saturating_mul(config.max_expansion_ratio)
+                .min(config.max_decoded_bytes),
+            produced: 0,
+            exceeded: false,
+        }
+    }
+
+    /// Count decoded text against the budget, truncating it (on a char
+    /// boundary) to whatever is left once the budget runs out
+    fn charge(&mut self, mut text: String) -> String {
+        let remaining = self.limit.saturating_sub(self.produced);
+        if text.len() > remaining {
+            let mut end = remaining;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            self.exceeded = true;
+        }
+        self.produced += text.len();
+        text
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.input_len == 0 {
+            return 0.0;
+        }
+        self.produced as f64 / self.input_len as f64
+    }
+}
+
+/// Value of a standard base64 character
+fn base64_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode a padded base64 token that encodes printable text
+///
+/// Anything else (wrong length, misplaced padding, binary output) is left
+/// alone, so identifiers that merely look like base64 aren't rewritten.
+fn decode_base64_text(token: &str) -> Option<String> {
+    let data = token.trim_end_matches('=');
+    if !token.len().is_multiple_of(4) || token.len() - data.len() > 2 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for &c in data.as_bytes() {
+        bits = (bits << 6) | base64_value(c)? as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            out.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+
+    let text = String::from_utf8(out).ok()?;
+    text.chars()
+        .all(|c| !c.is_control() || c.is_ascii_whitespace())
+        .then_some(text)
+}
+
+/// Replace every base64 token of MIN_BASE64_TOKEN_LENGTH or more characters
+/// with the text it encodes
+fn decode_base64_tokens(text: &str) -> String {
+    let is_base64 = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/');
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(is_base64) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let data_end = rest.find(|c: char| !is_base64(c)).unwrap_or(rest.len());
+        // Up to two '=' of padding belong to the token
+        let padding = rest[data_end..].bytes().take(2).take_while(|&b| b == b'=').count();
+        let end = data_end + padding;
+        let token = &rest[..end];
+        match decode_base64_text(token) {
+            Some(decoded) if token.len() >= MIN_BASE64_TOKEN_LENGTH => out.push_str(&decoded),
+            _ => out.push_str(token),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decode the HTML character references attackers use to hide markup:
+/// the XML named entities and decimal or hex numeric references
+fn decode_html_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 8).and_then(|end| {
+            let entity = &rest[1..1 + end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            c.map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((c, consumed)) => {
+                out.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Scan the body after each base64, URL or entity decoding pass until
+/// decoding stops changing it or the budget runs out
+///
+/// Rules already matched in the body aren't reported again for its decoded
+/// forms.
+fn check_body_layers(
+    rules: &[WafRule],
+    body: &str,
+    budget: &mut DecodeBudget,
+    matches: &mut Vec<WafMatch>,
+) {
+    let mut current = body.to_string();
+    while !budget.exceeded {
+        // One layer per pass, so a decoded base64 token's '+' isn't taken
+        // for a URL-encoded space before it's decoded in turn
+        let decoded = [decode_base64_tokens, percent_decode, decode_html_entities]
+            .iter()
+            .map(|decode| decode(&current))
+            .find(|decoded| *decoded != current);
+        let Some(decoded) = decoded else {
+            break;
+        };
+        let decoded = budget.charge(decoded);
+
+        let mut found = Vec::new();
+        check_body(rules, &decoded, "Body:decoded", &mut found);
+        for m in found {
+            let seen = matches
+                .iter()
+                .any(|prev| prev.rule_id == m.rule_id && prev.location.starts_with("Body"));
+            if !seen {
+                matches.push(m);
+            }
+        }
+        current = decoded;
+    }
+}
+
+/// Counter slot for a rule id (built-in rules by position, then synthetic ids)
+///
+/// Rules added at runtime have no counters.
+fn stat_slot(rule_id: u32) -> Option<usize> {
+    BUILTIN_RULE_IDS
+        .with(|ids| ids.iter().position(|&id| id == rule_id))
+        .or_else(|| {
+            SYNTHETIC_RULE_IDS
+                .iter()
+                .position(|&id| id == rule_id)
+                .map(|i| RULE_COUNT + i)
+        })
+}
+
+/// Add a finished analysis to the per-rule counters
+fn record_stats(result: &WafResult) {
+    RULE_STATS.with(|stats| {
+        let mut stats = stats.borrow_mut();
+        for m in &result.matches {
+            if let Some(counters) = stat_slot(m.rule_id).and_then(|slot| stats.get_mut(slot)) {
+                counters.hits = counters.hits.saturating_add(1);
+                if result.blocked && m.severity >= 5 {
+                    counters.blocks = counters.blocks.saturating_add(1);
+                }
+            }
+        }
+    });
+}
+
+/// Snapshot the per-rule counters
+fn collect_stats() -> WafStats {
+    let rule_ids = BUILTIN_RULE_IDS
+        .with(|ids| ids.clone())
+        .into_iter()
+        .chain(SYNTHETIC_RULE_IDS.iter().copied());
+
+    RULE_STATS.with(|stats| {
+        let stats = stats.borrow();
+        WafStats {
+            rules: rule_ids
+                .zip(stats.iter())
+                .map(|(rule_id, counters)| RuleStats {
+                    rule_id,
+                    hits: counters.hits,
+                    blocks: counters.blocks,
+                })
+                .collect(),
+        }
+    })
+}
+
+/// Find a cookie's value in the request's Cookie headers
+fn find_cookie<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .filter(|(header, _)| header.eq_ignore_ascii_case("Cookie"))
+        .flat_map(|(_, value)| value.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+}
+
+/// Host part of an origin, URL or bare host entry, lowercased
+///
+/// Drops scheme, userinfo, port, path and a trailing dot; IPv6 literals keep
+/// their brackets. None when no host is left.
+fn origin_host(value: &str) -> Option<String> {
+    let value = value.trim();
+    let rest = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = if host_port.starts_with('[') {
+        &host_port[..host_port.find(']')? + 1]
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Whether a host is an IPv4 or bracketed IPv6 literal
+fn is_ip_literal(host: &str) -> bool {
+    host.starts_with('[') || host.parse::<std::net::Ipv4Addr>().is_ok()
+}
+
+/// Whether an origin host matches one allowlist entry
+///
+/// `*.example.com` matches subdomains of example.com but never the apex or
+/// an IP literal.
+fn origin_allowed(host: &str, entry: &str) -> bool {
+    if let Some(suffix) = entry.trim().strip_prefix("*.") {
+        let Some(suffix) = origin_host(suffix) else {
+            return false;
+        };
+        return !is_ip_literal(host)
+            && host.len() > suffix.len() + 1
+            && host.ends_with(&suffix)
+            && host.as_bytes()[host.len() - suffix.len() - 1] == b'.';
+    }
+    origin_host(entry).is_some_and(|allowed| allowed == host)
+}
+
+/// Cross-origin checks for state-changing requests
+///
+/// Origin is preferred over Referer. A `null` or unparseable Origin/Referer
+/// counts as foreign.
+fn check_csrf(request: &RequestData, config: &CsrfConfig, matches: &mut Vec<WafMatch>) {
+    if !STATE_CHANGING_METHODS
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(&request.method))
+    {
+        return;
+    }
+
+    let path = request.uri.split(['?', '#']).next().unwrap_or("");
+    let elevated = config.elevated_paths.iter().any(|prefix| {
+        let prefix = prefix.trim_end_matches('/');
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    let severity = if elevated { 4 } else { 3 }; // Error on elevated paths, else Warning
+
+    let header = |name: &str| {
+        request
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    };
+    let source = header("Origin")
+        .map(|value| ("Origin", value))
+        .or_else(|| header("Referer").map(|value| ("Referer", value)));
+
+    match source {
+        Some((name, value)) if !config.allowed_origins.is_empty() => {
+            let allowed = origin_host(value)
+                .filter(|_| !value.trim().eq_ignore_ascii_case("null"))
+                .is_some_and(|host| config.allowed_origins.iter().any(|entry| origin_allowed(&host, entry)));
+            if !allowed {
+                matches.push(WafMatch {
+                    rule_id: CSRF_FOREIGN_ORIGIN_RULE_ID,
+                    description: format!("CSRF: state-changing request from a foreign {}", name),
+                    severity,
+                    category: "csrf".to_string(),
+                    matched_value: value.to_string(),
+                    location: format!("Header:{}", name),
+                });
+            }
+        }
+        Some(_) => {}
+        None => {
+            let has_cookies = header("Cookie").is_some_and(|value| !value.trim().is_empty());
+            if config.require_origin_with_cookies
+                && request.method.eq_ignore_ascii_case("POST")
+                && has_cookies
+            {
+                matches.push(WafMatch {
+                    rule_id: CSRF_MISSING_ORIGIN_RULE_ID,
+                    description: "CSRF: cookie-bearing POST without Origin or Referer".to_string(),
+                    severity,
+                    category: "csrf".to_string(),
+                    matched_value: String::new(),
+                    location: "Header:Origin".to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Header value length and repetition anomalies
+///
+/// Payloads hidden in rarely-inspected headers show up as oversized values
+/// or one header name sent many times. Each repeated name is reported once.
+fn check_header_limits(
+    headers: &[(String, String)],
+    config: &HeaderLimitsConfig,
+    matches: &mut Vec<WafMatch>,
+) {
+    let mut repetitions: Vec<(&str, usize)> = Vec::new();
+    for (name, value) in headers {
+        let limit = config.max_length(name);
+        if value.len() > limit {
+            matches.push(WafMatch {
+                rule_id: HEADER_VALUE_LENGTH_RULE_ID,
+                description: format!("Protocol: header value exceeds {} bytes", limit),
+                severity: 4, // Error
+                category: "protocol".to_string(),
+                matched_value: format!("{} bytes", value.len()),
+                location: format!("Header:{}", name),
+            });
+        }
+
+        match repetitions.iter_mut().find(|(seen, _)| seen.eq_ignore_ascii_case(name)) {
+            Some((_, count)) => *count += 1,
+            None => repetitions.push((name, 1)),
+        }
+    }
+
+    for (name, count) in repetitions {
+        if count > config.max_repetitions && !config.is_repeatable(name) {
+            matches.push(WafMatch {
+                rule_id: HEADER_REPETITION_RULE_ID,
+                description: format!("Protocol: header repeated more than {} times", config.max_repetitions),
+                severity: 3, // Warning
+                category: "protocol".to_string(),
+                matched_value: format!("{} occurrences", count),
+                location: format!("Header:{}", name),
+            });
+        }
+    }
+}
+
+/// Check the body against the method and the declared Content-Length
+///
+/// The host passes the client's Content-Length header through unchanged
+/// alongside the body it actually read. A value that can't be parsed counts
+/// as a mismatch.
+fn check_body_framing(
+    request: &RequestData,
+    body_len: usize,
+    bodyless_method: bool,
+    matches: &mut Vec<WafMatch>,
+) {
+    if bodyless_method && body_len > 0 {
+        matches.push(WafMatch {
+            rule_id: BODYLESS_METHOD_BODY_RULE_ID,
+            description: format!("Protocol: {} request with a body", request.method.to_uppercase()),
+            severity: 4, // Error
+            category: "protocol".to_string(),
+            matched_value: format!("{} bytes", body_len),
+            location: "Body".to_string(),
+        });
+    }
+
+    let Some((name, declared)) = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+    else {
+        return;
+    };
+    let mismatched = match declared.trim().parse::<usize>() {
+        Ok(declared) => {
+            let slack = CONTENT_LENGTH_SLACK_BYTES.max(declared / 100 * CONTENT_LENGTH_SLACK_PERCENT);
+            declared.abs_diff(body_len) > slack
+        }
+        Err(_) => true,
+    };
+    if mismatched {
+        matches.push(WafMatch {
+            rule_id: CONTENT_LENGTH_MISMATCH_RULE_ID,
+            description: "Evasion: declared Content-Length disagrees with the body received".to_string(),
+            severity: 4, // Error
+            category: "evasion".to_string(),
+            matched_value: format!("declared {}, received {} bytes", declared, body_len),
+            location: format!("Header:{}", name),
+        });
+    }
+}
+
+/// Why a clearance cookie failed the structural check
+#[derive(Debug, PartialEq)]
+enum ChallengeCookieError {
+    Malformed,
+    Expired,
+}
+
+/// Structural validation of a clearance cookie value (no MAC verification)
+fn check_challenge_cookie(
+    value: &str,
+    config: &ChallengeCookieConfig,
+) -> Result<(), ChallengeCookieError> {
+    if value.len() < config.min_length || value.len() > config.max_length {
+        return Err(ChallengeCookieError::Malformed);
+    }
+
+    let parts: Vec<&str> = value.split(config.separator).collect();
+    let [issued_at, nonce, mac] = parts[..] else {
+        return Err(ChallengeCookieError::Malformed);
+    };
+
+    let is_token = |part: &str| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    };
+    if !is_token(nonce) || !is_token(mac) {
+        return Err(ChallengeCookieError::Malformed);
+    }
+
+    let issued_at: u64 = issued_at
+        .parse()
+        .map_err(|_| ChallengeCookieError::Malformed)?;
+    if issued_at > config.now.saturating_add(CHALLENGE_COOKIE_CLOCK_SKEW_SECS) {
+        return Err(ChallengeCookieError::Malformed);
+    }
+    if config.now.saturating_sub(issued_at) > config.max_age_secs {
+        return Err(ChallengeCookieError::Expired);
+    }
+
+    Ok(())
+}
+
+/// Build the suggested response for a blocked request from its top match
+///
+/// The top match is the first one with the highest severity. Configured
+/// statuses outside 400-599 are ignored.
+fn block_response(matches: &[WafMatch], config: &BlockResponseConfig) -> Option<BlockResponse> {
+    let top = matches.iter().rev().max_by_key(|m| m.severity)?;
+    let block_reason_code = format!("{}:{}", top.category, top.rule_id);
+    let status_code = config
+        .status_by_category
+        .get(&top.category)
+        .copied()
+        .filter(|status| (400..600).contains(status))
+        .unwrap_or(DEFAULT_BLOCK_STATUS);
+    let message = config
+        .message_template
+        .as_deref()
+        .unwrap_or(DEFAULT_BLOCK_MESSAGE)
+        .replace("{code}", &block_reason_code)
+        .replace("{status}", &status_code.to_string());
+
+    Some(BlockResponse {
+        status_code,
+        block_reason_code,
+        message,
+        retry_after_secs: config.retry_after_secs.filter(|_| status_code == 429),
+    })
+}
+
+/// Analyze request against its zone's ruleset, or the instance-wide one
+fn analyze(request: RequestData) -> WafResult {
+    ZONE_RULESETS.with(|zones| {
+        let zones = zones.borrow();
+        match request.config.zone_id.as_deref().and_then(|zone_id| zones.get(zone_id)) {
+            // Only this zone's compiled rules are consulted
+            Some(zone) => analyze_with(&zone.rules, request),
+            None => RULESET.with(|ruleset| analyze_with(&ruleset.borrow().rules, request)),
+        }
+    })
+}
+
+/// Analyze request and return matches
+fn analyze_with(rules: &[WafRule], request: RequestData) -> WafResult {
+    let mut matches = Vec::new();
+    let min_severity = MIN_SEVERITY;
+    let config = &request.config;
+
+    // Check URI
+    check_uri(rules, &request.uri, "URI", &mut matches);
+    check_parameter_pollution(rules, &request.uri, &mut matches);
+
+    // Check headers
+    for (name, value) in &request.headers {
+        let location = format!("Header:{}", name);
+
+        if URL_OVERRIDE_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+            if config.trust_original_url {
+                // Trusted proxy: the header carries the real request path
+                check_uri(rules, value, &location, &mut matches);
+                continue;
+            }
+            if !config.upstream_proxy {
+                // No proxy declared, so nothing legitimate sets this header
+                matches.push(WafMatch {
+                    rule_id: URL_OVERRIDE_SPOOFING_RULE_ID,
+                    description: "Spoofing: URL override header without a declared upstream proxy".to_string(),
+                    severity: 4, // Error
+                    category: "spoofing".to_string(),
+                    matched_value: value.clone(),
+                    location: location.clone(),
+                });
+            }
+        }
+
+        for rule in rules {
+            if let Some(matched_value) = rule.matches(value) {
+                if rule.severity >= min_severity {
+                    matches.push(WafMatch {
+                        rule_id: rule.id,
+                        description: rule.description.to_string(),
+                        severity: rule.severity,
+                        category: rule.category.to_string(),
+                        matched_value,
+                        location: location.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    check_header_limits(&request.headers, &config.header_limits, &mut matches);
+
+    // Check method (for protocol violations)
+    for rule in rules {
+        if rule.category == "protocol" {
+            if let Some(matched_value) = rule.matches(&request.method) {
+                if rule.severity >= min_severity {
+                    matches.push(WafMatch {
+                        rule_id: rule.id,
                         description: rule.description.to_string(),
                         severity: rule.severity,
                         category: rule.category.to_string(),
@@ -275,208 +2093,1514 @@ fn analyze(request: RequestData) -> WafResult {
         }
     }
 
-    // Check body
-    if !request.body.is_empty() {
-        for rule in &rules {
-            if let Some(matched_value) = rule.matches(&request.body) {
-                if rule.severity >= min_severity {
-                    matches.push(WafMatch {
-                        rule_id: rule.id,
-                        description: rule.description.to_string(),
-                        severity: rule.severity,
-                        category: rule.category.to_string(),
-                        matched_value,
-                        location: "Body".to_string(),
-                    });
-                }
-            }
-        }
+    let body_len = request.body_raw.as_ref().map_or(request.body.len(), Vec::len);
+    let bodyless_method = BODYLESS_METHODS.iter().any(|m| m.eq_ignore_ascii_case(&request.method));
+    check_body_framing(&request, body_len, bodyless_method, &mut matches);
+
+    // Check body, then again as decoded from its declared charset and as
+    // normalized, within one decoding budget. Empty bodies of bodyless
+    // methods (most traffic) skip this entirely.
+    let mut body_expansion_ratio = None;
+    let scan_body = if bodyless_method {
+        body_len > 0
+    } else {
+        !request.body.is_empty() || request.body_raw.is_some()
+    };
+    if scan_body {
+        let mut budget = DecodeBudget::new(body_len, &config.body_decode);
+
+        check_body(rules, &request.body, "Body", &mut matches);
+        if let Some(charset) = content_type_charset(&request.headers) {
+            check_body_charset(rules, &request, &charset, &mut budget, &mut matches);
+        }
+        check_body_layers(rules, &request.body, &mut budget, &mut matches);
+
+        if budget.exceeded {
+            // Legitimate bodies rarely decode to several times their size
+            matches.push(WafMatch {
+                rule_id: BODY_EXPANSION_RULE_ID,
+                description: "Evasion: body decoding exceeded the expansion limit".to_string(),
+                severity: 4, // Error
+                category: "evasion".to_string(),
+                matched_value: format!("{:.1}x", budget.ratio()),
+                location: "Body:decoded".to_string(),
+            });
+        }
+        body_expansion_ratio = Some(budget.ratio());
+    }
+
+    // Check the challenge-clearance cookie (flagged requests get re-challenged)
+    let mut needs_hmac_check = false;
+    let mut hmac_key_id = None;
+    if let Some(cookie_config) = &config.challenge_cookie {
+        if let Some(value) = find_cookie(&request.headers, &cookie_config.name) {
+            let location = format!("Cookie:{}", cookie_config.name);
+            match check_challenge_cookie(value, cookie_config) {
+                Ok(()) => {
+                    needs_hmac_check = true;
+                    hmac_key_id = Some(cookie_config.hmac_key_id.clone());
+                }
+                Err(ChallengeCookieError::Malformed) => matches.push(WafMatch {
+                    rule_id: CHALLENGE_COOKIE_MALFORMED_RULE_ID,
+                    description: "Challenge: malformed clearance cookie".to_string(),
+                    severity: 4, // Error
+                    category: "challenge".to_string(),
+                    matched_value: value.to_string(),
+                    location,
+                }),
+                Err(ChallengeCookieError::Expired) => matches.push(WafMatch {
+                    rule_id: CHALLENGE_COOKIE_EXPIRED_RULE_ID,
+                    description: "Challenge: expired clearance cookie".to_string(),
+                    severity: 3, // Warning
+                    category: "challenge".to_string(),
+                    matched_value: value.to_string(),
+                    location,
+                }),
+            }
+        }
+    }
+
+    if let Some(csrf) = &config.csrf {
+        check_csrf(&request, csrf, &mut matches);
+    }
+
+    // Determine if request should be blocked (any Critical match)
+    let blocked = matches.iter().any(|m| m.severity >= 5);
+    let block_response = if blocked {
+        block_response(&matches, &config.block_response)
+    } else {
+        None
+    };
+
+    let result = WafResult {
+        schema_version: RESULT_SCHEMA_VERSION,
+        blocked,
+        matches,
+        execution_time_us: 0, // Host will set this
+        needs_hmac_check,
+        hmac_key_id,
+        block_response,
+        body_expansion_ratio,
+    };
+    record_stats(&result);
+    result
+}
+
+/// Canary request run by `self_test`
+struct Canary {
+    /// Rule category the canary must trigger ("clean": must trigger nothing)
+    category: &'static str,
+    method: &'static str,
+    uri: &'static str,
+    headers: &'static [(&'static str, &'static str)],
+}
+
+/// Category of the canary that must pass without any match
+const CLEAN_CANARY: &str = "clean";
+
+/// One canonical attack per category plus a clean request; versioned with
+/// the engine so a ruleset that can't catch these is rejected before use
+const CANARIES: &[Canary] = &[
+    Canary { category: "sqli", method: "GET", uri: "/products?id=1 union select password from users", headers: &[] },
+    Canary { category: "xss", method: "GET", uri: "/comment?text=<script>alert(1)</script>", headers: &[] },
+    Canary { category: "path-traversal", method: "GET", uri: "/download?file=../../etc/passwd", headers: &[] },
+    Canary { category: "rce", method: "GET", uri: "/ping?host=127.0.0.1; cat /etc/hosts", headers: &[] },
+    Canary { category: "scanner", method: "GET", uri: "/", headers: &[("User-Agent", "sqlmap/1.7.2#stable")] },
+    Canary {
+        category: CLEAN_CANARY,
+        method: "GET",
+        uri: "/articles/2024/edge-caching?page=2",
+        headers: &[("User-Agent", "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0")],
+    },
+];
+
+/// Report returned by `self_test()`
+#[derive(Debug, Serialize)]
+struct SelfTestReport {
+    passed: bool,
+    failures: Vec<SelfTestFailure>,
+}
+
+/// A canary the active ruleset got wrong
+#[derive(Debug, Serialize)]
+struct SelfTestFailure {
+    category: &'static str,
+    /// "detected" or "clean"
+    expected: &'static str,
+    /// What the ruleset reported: "clean" or the matched categories
+    got: String,
+}
+
+/// Run every canary through the active ruleset
+///
+/// Canary traffic is left out of the rule statistics.
+fn run_self_test() -> SelfTestReport {
+    let saved_stats = RULE_STATS.with(|stats| *stats.borrow());
+
+    let mut failures = Vec::new();
+    for canary in CANARIES {
+        let result = analyze(RequestData {
+            method: canary.method.to_string(),
+            uri: canary.uri.to_string(),
+            headers: canary
+                .headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        });
+
+        let mut categories: Vec<&str> = result.matches.iter().map(|m| m.category.as_str()).collect();
+        categories.sort_unstable();
+        categories.dedup();
+        let got = if categories.is_empty() {
+            CLEAN_CANARY.to_string()
+        } else {
+            categories.join(",")
+        };
+
+        let (expected, ok) = if canary.category == CLEAN_CANARY {
+            (CLEAN_CANARY, categories.is_empty())
+        } else {
+            ("detected", categories.contains(&canary.category))
+        };
+        if !ok {
+            failures.push(SelfTestFailure { category: canary.category, expected, got });
+        }
+    }
+
+    RULE_STATS.with(|stats| *stats.borrow_mut() = saved_stats);
+    SelfTestReport { passed: failures.is_empty(), failures }
+}
+
+/// WASM Export: Analyze request
+///
+/// Host allocates memory via alloc(), writes JSON request data, calls this.
+/// Returns pointer to result (format: 4 bytes length + JSON data)
+#[no_mangle]
+pub extern "C" fn analyze_request(ptr: u32, len: u32) -> u32 {
+    // Read request JSON from Wasm memory
+    let request_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+
+    let request: RequestData = match serde_json::from_slice(request_bytes) {
+        Ok(req) => req,
+        Err(_) => {
+            // Return error result
+            let error_result = WafResult {
+                schema_version: RESULT_SCHEMA_VERSION,
+                blocked: false,
+                matches: Vec::new(),
+                execution_time_us: 0,
+                needs_hmac_check: false,
+                hmac_key_id: None,
+                block_response: None,
+                body_expansion_ratio: None,
+            };
+            return write_result(&error_result);
+        }
+    };
+
+    // Analyze request
+    let result = analyze(request);
+
+    // Write result to Wasm memory
+    write_result(&result)
+}
+
+/// Write result to Wasm memory (format: 4-byte length + canonical JSON)
+fn write_result<T: Serialize>(result: &T) -> u32 {
+    let json = canonical_json(result).unwrap_or_else(|_| "{}".to_string());
+    let json_bytes = json.as_bytes();
+    let json_len = json_bytes.len() as u32;
+
+    // Allocate: 4 bytes for length + JSON data
+    let total_size = 4 + json_bytes.len();
+    let layout = Layout::from_size_align(total_size, 4).unwrap();
+    let result_ptr = unsafe { std_alloc(layout) };
+
+    // Write length (first 4 bytes)
+    unsafe {
+        *(result_ptr as *mut u32) = json_len;
+    }
+
+    // Write JSON data
+    unsafe {
+        let data_ptr = result_ptr.add(4);
+        std::ptr::copy_nonoverlapping(json_bytes.as_ptr(), data_ptr, json_bytes.len());
+    }
+
+    result_ptr as u32
+}
+
+/// WASM Export: Version of the shared scanner signature tables
+///
+/// Matches the bot detector's `signatures_version` when both modules were
+/// built from the same aegis-signatures release.
+#[no_mangle]
+pub extern "C" fn get_signatures_version() -> u32 {
+    aegis_signatures::SIGNATURES_VERSION
+}
+
+/// WASM Export: Per-rule hit statistics for tuning
+///
+/// Returns pointer to result (format: 4 bytes length + JSON data) listing
+/// `{rule_id, hits, blocks}` for every rule. Counters cover this instance only.
+#[no_mangle]
+pub extern "C" fn get_stats() -> u32 {
+    write_result(&collect_stats())
+}
+
+/// WASM Export: Zero the per-rule hit statistics
+#[no_mangle]
+pub extern "C" fn reset_stats() {
+    RULE_STATS.with(|stats| *stats.borrow_mut() = [RuleCounters::default(); STAT_SLOTS]);
+}
+
+/// WASM Export: Add a rule to this instance's ruleset
+///
+/// Takes a JSON rule `{id, description, patterns, severity, category,
+/// case_sensitive?}`; fails with `duplicate_rule_id` if the id is in use.
+/// Returns pointer to result (format: 4 bytes length + JSON data).
+#[no_mangle]
+pub extern "C" fn add_rule(ptr: u32, len: u32) -> u32 {
+    let rule_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+    write_result(&add_rule_json(rule_bytes))
+}
+
+/// WASM Export: Replace an existing rule (same JSON as `add_rule`)
+///
+/// Fails with `unknown_rule_id` if no rule has the id. Built-in rules can be
+/// replaced; the replacement loses any per-location severity override.
+#[no_mangle]
+pub extern "C" fn update_rule(ptr: u32, len: u32) -> u32 {
+    let rule_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+    write_result(&update_rule_json(rule_bytes))
+}
+
+/// WASM Export: Remove a rule by id (`unknown_rule_id` if absent)
+#[no_mangle]
+pub extern "C" fn remove_rule(rule_id: u32) -> u32 {
+    write_result(&change_ruleset(|ruleset| ruleset.remove(rule_id)))
+}
+
+/// WASM Export: Ruleset revision and rule ids
+///
+/// The revision starts at 0 and is bumped by every successful add, update or
+/// remove, so controllers can detect concurrent changes.
+#[no_mangle]
+pub extern "C" fn get_ruleset_info() -> u32 {
+    write_result(&ruleset_info())
+}
+
+/// WASM Export: Check the active ruleset against the built-in canaries
+///
+/// Hosts call this after changing the ruleset and roll back if `passed` is
+/// false. Returns pointer to result (format: 4 bytes length + JSON data)
+/// `{passed, failures: [{category, expected, got}]}`.
+#[no_mangle]
+pub extern "C" fn self_test() -> u32 {
+    write_result(&run_self_test())
+}
+
+/// WASM Export: Load or replace a zone-scoped ruleset
+///
+/// Takes JSON `{zone_id, builtin_rule_ids?, rules?, max_memory_bytes?}`;
+/// requests whose config names the zone are then checked against only these
+/// rules. Fails without changing anything (`zone_memory_exceeded`,
+/// `memory_budget_exceeded`, ...) if the ruleset is invalid or too large.
+/// Returns pointer to result (format: 4 bytes length + JSON data)
+/// `{ok, error?, memory_bytes}`.
+#[no_mangle]
+pub extern "C" fn load_rules(ptr: u32, len: u32) -> u32 {
+    let spec_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+    write_result(&load_rules_json(spec_bytes))
+}
+
+/// WASM Export: Drop a zone's ruleset (zone id as UTF-8; `unknown_zone` if
+/// none is loaded)
+#[no_mangle]
+pub extern "C" fn unload_rules(ptr: u32, len: u32) -> u32 {
+    let zone_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+    write_result(&unload_zone(&String::from_utf8_lossy(zone_bytes)))
+}
+
+/// WASM Export: Memory used by each zone's ruleset
+///
+/// Returns pointer to result (format: 4 bytes length + JSON data)
+/// `{zones: [{zone_id, rule_count, rule_bytes, matcher_bytes, total_bytes,
+/// max_memory_bytes}], total_bytes, budget_bytes}`.
+#[no_mangle]
+pub extern "C" fn get_memory_report() -> u32 {
+    write_result(&memory_report())
+}
+
+/// WASM Export: JSON Schema of `analyze_request` results
+///
+/// Hosts can validate results against it at module load. Returns pointer to
+/// result (format: 4 bytes length + JSON data).
+#[no_mangle]
+pub extern "C" fn result_schema() -> u32 {
+    write_result(&schema::result_schema_document())
+}
+
+/// WASM Export: Allocate memory for host to write data
+#[no_mangle]
+pub extern "C" fn alloc(size: u32) -> u32 {
+    let layout = Layout::from_size_align(size as usize, 4).unwrap();
+    unsafe { std_alloc(layout) as u32 }
+}
+
+/// WASM Export: Deallocate memory (not currently used, but exported for completeness)
+#[no_mangle]
+pub extern "C" fn dealloc(ptr: u32, size: u32) {
+    let layout = Layout::from_size_align(size as usize, 4).unwrap();
+    unsafe { std_dealloc(ptr as *mut u8, layout) };
+}
+
+#[cfg(test)]
+mod corpus;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_injection_detection() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "SELECT * FROM users".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "sqli");
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_xss_detection() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "<script>alert('XSS')</script>".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "xss");
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_path_traversal_detection() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "../../../etc/passwd".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "path-traversal");
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_rce_detection() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "; ls -la".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "rce");
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_clean_request() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "/api/users".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(result.matches.is_empty());
+        assert!(!result.blocked);
+    }
+
+    #[test]
+    fn test_header_analysis() {
+        let request = RequestData {
+            method: "GET".to_string(),
+            uri: "/".to_string(),
+            headers: vec![("User-Agent".to_string(), "nikto scanner".to_string())],
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "scanner");
+        assert!(result.matches[0].location.starts_with("Header:"));
+    }
+
+    #[test]
+    fn test_canonical_scanner_user_agents() {
+        for ua in aegis_signatures::CANONICAL_SCANNER_USER_AGENTS {
+            let request = RequestData {
+                method: "GET".to_string(),
+                uri: "/".to_string(),
+                headers: vec![("User-Agent".to_string(), ua.to_string())],
+                body: String::new(),
+                body_raw: None,
+                config: WafConfig::default(),
+            };
+
+            let result = analyze(request);
+            assert!(
+                result.matches.iter().any(|m| m.rule_id == 913100),
+                "Rule 913100 missed scanner UA: {}",
+                ua
+            );
+        }
+    }
+
+    #[test]
+    fn test_signatures_version_exported() {
+        assert_eq!(get_signatures_version(), aegis_signatures::SIGNATURES_VERSION);
+    }
+
+    fn original_url_request(config: WafConfig) -> RequestData {
+        RequestData {
+            method: "GET".to_string(),
+            uri: "/".to_string(),
+            headers: vec![("X-Original-URL".to_string(), "../../etc/passwd".to_string())],
+            body: String::new(),
+            body_raw: None,
+            config,
+        }
+    }
+
+    #[test]
+    fn test_untrusted_original_url_flagged() {
+        let result = analyze(original_url_request(WafConfig::default()));
+
+        let spoof = result
+            .matches
+            .iter()
+            .find(|m| m.category == "spoofing")
+            .expect("URL override header should be flagged");
+        assert_eq!(spoof.rule_id, URL_OVERRIDE_SPOOFING_RULE_ID);
+        assert_eq!(spoof.severity, 4);
+        assert_eq!(spoof.location, "Header:X-Original-URL");
+    }
+
+    #[test]
+    fn test_declared_proxy_not_flagged_as_spoofing() {
+        let config = WafConfig {
+            upstream_proxy: true,
+            trust_original_url: false,
+            ..Default::default()
+        };
+        let result = analyze(original_url_request(config));
+
+        assert!(result.matches.iter().all(|m| m.category != "spoofing"));
+    }
+
+    #[test]
+    fn test_trusted_original_url_scanned_as_path() {
+        let config = WafConfig {
+            upstream_proxy: true,
+            trust_original_url: true,
+            ..Default::default()
+        };
+        let result = analyze(original_url_request(config));
+
+        assert!(result.blocked);
+        assert!(result.matches.iter().all(|m| m.category != "spoofing"));
+        let traversal = result
+            .matches
+            .iter()
+            .find(|m| m.category == "path-traversal")
+            .expect("traversal in trusted header should match");
+        assert_eq!(traversal.location, "Header:X-Original-URL");
+    }
+
+    fn csrf_request(method: &str, uri: &str, headers: &[(&str, &str)]) -> RequestData {
+        let config: WafConfig = serde_json::from_value(serde_json::json!({
+            "csrf": {
+                "allowed_origins": ["https://shop.example.com", "*.cdn.example.com", "10.0.0.5"],
+                "require_origin_with_cookies": true,
+                "elevated_paths": ["/admin"]
+            }
+        }))
+        .unwrap();
+
+        RequestData {
+            method: method.to_string(),
+            uri: uri.to_string(),
+            headers: headers.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+            body: String::new(),
+            body_raw: None,
+            config,
+        }
+    }
+
+    fn csrf_matches(result: &WafResult) -> Vec<&WafMatch> {
+        result.matches.iter().filter(|m| m.category == "csrf").collect()
+    }
+
+    #[test]
+    fn test_cross_origin_post_flagged() {
+        let result = analyze(csrf_request("POST", "/cart", &[("Origin", "https://evil.example.net")]));
+        let csrf = csrf_matches(&result);
+        assert_eq!(csrf.len(), 1);
+        assert_eq!(csrf[0].rule_id, CSRF_FOREIGN_ORIGIN_RULE_ID);
+        assert_eq!(csrf[0].severity, 3);
+        assert_eq!(csrf[0].location, "Header:Origin");
+        assert!(!result.blocked);
+
+        // Referer is used when Origin is absent; admin paths are Error severity
+        let result = analyze(csrf_request("DELETE", "/admin/users/7", &[("Referer", "https://evil.example.net/x")]));
+        let csrf = csrf_matches(&result);
+        assert_eq!(csrf[0].location, "Header:Referer");
+        assert_eq!(csrf[0].severity, 4);
+
+        // Suffix look-alikes and null origins are foreign
+        for origin in ["https://shop.example.com.evil.net", "https://evilshop.example.com", "null", "https://10.0.0.55"] {
+            let result = analyze(csrf_request("PUT", "/cart", &[("Origin", origin)]));
+            assert_eq!(csrf_matches(&result).len(), 1, "{} should be foreign", origin);
+        }
+    }
+
+    #[test]
+    fn test_same_site_post_clean() {
+        for origin in ["https://shop.example.com", "http://SHOP.example.com:8443", "https://10.0.0.5:8080"] {
+            let result = analyze(csrf_request("POST", "/cart", &[("Origin", origin)]));
+            assert!(csrf_matches(&result).is_empty(), "{} should be allowed", origin);
+        }
+        let result = analyze(csrf_request("POST", "/cart", &[("Referer", "https://shop.example.com/checkout?step=2")]));
+        assert!(csrf_matches(&result).is_empty());
+    }
+
+    #[test]
+    fn test_get_never_csrf_flagged() {
+        let result = analyze(csrf_request("GET", "/admin", &[("Origin", "https://evil.example.net")]));
+        assert!(csrf_matches(&result).is_empty());
+        let result = analyze(csrf_request("GET", "/", &[("Cookie", "session=abc")]));
+        assert!(csrf_matches(&result).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_subdomain_allowed() {
+        let allowed = analyze(csrf_request("POST", "/upload", &[("Origin", "https://img.eu.cdn.example.com")]));
+        assert!(csrf_matches(&allowed).is_empty());
+
+        // The wildcard covers subdomains only
+        let apex = analyze(csrf_request("POST", "/upload", &[("Origin", "https://cdn.example.com")]));
+        assert_eq!(csrf_matches(&apex).len(), 1);
+    }
+
+    #[test]
+    fn test_missing_origin_with_cookies_flagged() {
+        let result = analyze(csrf_request("POST", "/cart", &[("Cookie", "session=abc")]));
+        let csrf = csrf_matches(&result);
+        assert_eq!(csrf.len(), 1);
+        assert_eq!(csrf[0].rule_id, CSRF_MISSING_ORIGIN_RULE_ID);
+
+        // No cookies, no ambient credentials to ride on
+        let result = analyze(csrf_request("POST", "/cart", &[]));
+        assert!(csrf_matches(&result).is_empty());
+    }
+
+    #[test]
+    fn test_origin_host_parsing() {
+        assert_eq!(origin_host("https://user@Shop.Example.com.:8443/a?b").as_deref(), Some("shop.example.com"));
+        assert_eq!(origin_host("http://[::1]:8080").as_deref(), Some("[::1]"));
+        assert_eq!(origin_host("example.com").as_deref(), Some("example.com"));
+        assert_eq!(origin_host("https://"), None);
+        assert!(!origin_allowed("[::1]", "*.1"));
+        assert!(!origin_allowed("10.0.0.5", "*.0.0.5"));
+    }
+
+    const CHALLENGE_NOW: u64 = 1_700_000_000;
+    const CHALLENGE_MAC: &str = "kq3Vf0bA9xR2m1Y8Zt6W4pLcN7eHsJuQ-_oDgEiTyUw";
+
+    fn headers_request(headers: Vec<(String, String)>) -> RequestData {
+        RequestData {
+            method: "GET".to_string(),
+            uri: "/".to_string(),
+            headers,
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        }
+    }
+
+    fn header_limit_matches(result: &WafResult) -> Vec<&WafMatch> {
+        result
+            .matches
+            .iter()
+            .filter(|m| m.rule_id == HEADER_VALUE_LENGTH_RULE_ID || m.rule_id == HEADER_REPETITION_RULE_ID)
+            .collect()
+    }
+
+    #[test]
+    fn test_long_cookie_under_limit_passes() {
+        let cookie = format!("session={}", "a".repeat(6 * 1024));
+        let result = analyze(headers_request(vec![
+            ("cookie".to_string(), cookie),
+            ("Authorization".to_string(), format!("Bearer {}", "b".repeat(3 * 1024))),
+        ]));
+        assert!(header_limit_matches(&result).is_empty());
+    }
+
+    #[test]
+    fn test_long_unknown_header_over_default_flagged() {
+        let result = analyze(headers_request(vec![("X-Custom".to_string(), "a".repeat(1025))]));
+
+        let flagged = header_limit_matches(&result);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].rule_id, HEADER_VALUE_LENGTH_RULE_ID);
+        assert_eq!(flagged[0].category, "protocol");
+        assert_eq!(flagged[0].location, "Header:X-Custom");
+        assert_eq!(flagged[0].matched_value, "1025 bytes");
+        assert!(!result.blocked);
+    }
+
+    #[test]
+    fn test_repeated_header_flagged() {
+        let headers = (0..10)
+            .map(|i| ("X-Forwarded-For".to_string(), format!("10.0.0.{}", i)))
+            .collect();
+        let result = analyze(headers_request(headers));
+
+        let flagged = header_limit_matches(&result);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].rule_id, HEADER_REPETITION_RULE_ID);
+        assert_eq!(flagged[0].category, "protocol");
+        assert_eq!(flagged[0].location, "Header:X-Forwarded-For");
+        assert_eq!(flagged[0].matched_value, "10 occurrences");
+    }
+
+    #[test]
+    fn test_repeatable_headers_and_zone_limits() {
+        let headers = (0..10).map(|i| ("via".to_string(), format!("1.1 proxy-{}", i))).collect();
+        assert!(header_limit_matches(&analyze(headers_request(headers))).is_empty());
+
+        // Zone baselines replace the defaults
+        let mut request = headers_request(vec![("X-Custom".to_string(), "a".repeat(1025))]);
+        request.config = serde_json::from_value(serde_json::json!({
+            "header_limits": { "max_value_length": { "x-custom": 2048 } }
+        }))
+        .unwrap();
+        assert!(header_limit_matches(&analyze(request)).is_empty());
+    }
+
+    fn challenge_request(cookie: Option<&str>) -> RequestData {
+        let config: WafConfig = serde_json::from_value(serde_json::json!({
+            "challenge_cookie": {
+                "name": "__aegis_clr",
+                "hmac_key_id": "clr-2024-01",
+                "now": CHALLENGE_NOW,
+                "max_age_secs": 1800
+            }
+        }))
+        .unwrap();
+
+        let mut headers = vec![("User-Agent".to_string(), "Mozilla/5.0".to_string())];
+        if let Some(cookie) = cookie {
+            headers.push(("Cookie".to_string(), format!("theme=dark; __aegis_clr={}", cookie)));
+        }
+
+        RequestData {
+            method: "GET".to_string(),
+            uri: "/".to_string(),
+            headers,
+            body: String::new(),
+            body_raw: None,
+            config,
+        }
+    }
+
+    #[test]
+    fn test_missing_challenge_cookie() {
+        let result = analyze(challenge_request(None));
+
+        assert!(result.matches.is_empty());
+        assert!(!result.needs_hmac_check);
+        assert_eq!(result.hmac_key_id, None);
+    }
+
+    #[test]
+    fn test_fresh_challenge_cookie_needs_hmac_check() {
+        let cookie = format!("{}.a1b2c3d4e5f60718.{}", CHALLENGE_NOW - 60, CHALLENGE_MAC);
+        let result = analyze(challenge_request(Some(&cookie)));
+
+        assert!(result.matches.is_empty());
+        assert!(result.needs_hmac_check);
+        assert_eq!(result.hmac_key_id.as_deref(), Some("clr-2024-01"));
+    }
+
+    #[test]
+    fn test_expired_challenge_cookie() {
+        let cookie = format!("{}.a1b2c3d4e5f60718.{}", CHALLENGE_NOW - 7200, CHALLENGE_MAC);
+        let result = analyze(challenge_request(Some(&cookie)));
+
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].rule_id, CHALLENGE_COOKIE_EXPIRED_RULE_ID);
+        assert_eq!(result.matches[0].category, "challenge");
+        assert_eq!(result.matches[0].location, "Cookie:__aegis_clr");
+        assert!(!result.needs_hmac_check);
+        assert!(!result.blocked);
+    }
+
+    #[test]
+    fn test_garbage_challenge_cookie() {
+        let future = format!("{}.a1b2c3d4e5f60718.{}", CHALLENGE_NOW + 3600, CHALLENGE_MAC);
+        let cases = [
+            "garbage".to_string(),
+            format!("notatimestamp.a1b2c3d4e5f60718.{}", CHALLENGE_MAC),
+            format!("{}.{}", CHALLENGE_NOW, CHALLENGE_MAC),
+            format!("{}.a1b2.c3d4.{}", CHALLENGE_NOW, CHALLENGE_MAC),
+            format!("{}.<script>.{}", CHALLENGE_NOW, CHALLENGE_MAC),
+            future,
+        ];
+
+        for cookie in cases {
+            let result = analyze(challenge_request(Some(&cookie)));
+            let challenge = result
+                .matches
+                .iter()
+                .find(|m| m.category == "challenge")
+                .unwrap_or_else(|| panic!("malformed cookie not flagged: {}", cookie));
+            assert_eq!(challenge.rule_id, CHALLENGE_COOKIE_MALFORMED_RULE_ID);
+            assert!(!result.needs_hmac_check);
+        }
+    }
+
+    #[test]
+    fn test_body_analysis() {
+        let request = RequestData {
+            method: "POST".to_string(),
+            uri: "/api/data".to_string(),
+            headers: Vec::new(),
+            body: "INSERT INTO users VALUES ('admin', 'pass')".to_string(),
+            body_raw: None,
+            config: WafConfig::default(),
+        };
+
+        let result = analyze(request);
+        assert!(!result.matches.is_empty());
+        assert_eq!(result.matches[0].category, "sqli");
+        assert_eq!(result.matches[0].location, "Body");
+        assert!(result.blocked);
+    }
+
+    fn json_body_request(body: &str) -> RequestData {
+        RequestData {
+            method: "POST".to_string(),
+            uri: "/api/manifests".to_string(),
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+            body_raw: None,
+            config: WafConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_body_relative_path_not_blocked() {
+        let result = analyze(json_body_request(r#"{"path": "../assets/logo.png"}"#));
+        assert!(!result.blocked);
+        let traversal = result.matches.iter().find(|m| m.rule_id == 930100).unwrap();
+        assert_eq!(traversal.severity, 3);
+        assert_eq!(traversal.location, "Body");
+    }
+
+    #[test]
+    fn test_body_hostile_traversal_blocked() {
+        let result = analyze(json_body_request(r#"{"path": "../../../../etc/shadow"}"#));
+        assert!(result.blocked);
+        let traversal = result.matches.iter().find(|m| m.rule_id == 930100).unwrap();
+        assert_eq!(traversal.severity, 5);
+
+        // One level up (JSON-escaped backslash), but straight onto a sensitive target
+        let result = analyze(json_body_request(r#"{"key": "..\\.ssh/id_rsa"}"#));
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_uri_traversal_stays_critical() {
+        let result = analyze(query_request("/static?file=../assets/logo.png"));
+        let traversal = result.matches.iter().find(|m| m.rule_id == 930100).unwrap();
+        assert_eq!(traversal.severity, 5);
+        assert!(result.blocked);
+    }
+
+    #[test]
+    fn test_is_hostile_traversal() {
+        assert!(!is_hostile_traversal("../assets/logo.png"));
+        assert!(!is_hostile_traversal("../../src/main.rs and ../lib"));
+        assert!(is_hostile_traversal("../../../var/log"));
+        assert!(is_hostile_traversal("..\\..\\windows\\system32"));
+        assert!(is_hostile_traversal("../proc/self/environ"));
+    }
+
+    fn query_request(uri: &str) -> RequestData {
+        RequestData {
+            method: "GET".to_string(),
+            uri: uri.to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_sqli_block_response() {
+        let result = analyze(query_request("/search?q=1 union select password from users"));
+        assert!(result.blocked);
+
+        let response = result.block_response.expect("blocked requests carry a block response");
+        assert_eq!(response.status_code, 403);
+        assert_eq!(response.block_reason_code, "sqli:942100");
+        assert_eq!(response.message, "Request blocked by AEGIS WAF (reference: sqli:942100)");
+        assert!(!response.message.contains("union"));
+        assert_eq!(response.retry_after_secs, None);
+
+        assert!(analyze(query_request("/search?q=shoes")).block_response.is_none());
+    }
+
+    #[test]
+    fn test_configured_block_response() {
+        let mut request = query_request("/search?q=1 union select password from users");
+        request.config.block_response = BlockResponseConfig {
+            status_by_category: HashMap::from([("sqli".to_string(), 429)]),
+            retry_after_secs: Some(30),
+            message_template: Some("Denied ({status}, ref {code})".to_string()),
+        };
+
+        let response = analyze(request).block_response.unwrap();
+        assert_eq!(response.status_code, 429);
+        assert_eq!(response.retry_after_secs, Some(30));
+        assert_eq!(response.message, "Denied (429, ref sqli:942100)");
+
+        // Non-error statuses fall back to 403, which gets no Retry-After
+        let mut request = query_request("/search?q=1 union select password from users");
+        request.config.block_response = BlockResponseConfig {
+            status_by_category: HashMap::from([("sqli".to_string(), 200)]),
+            retry_after_secs: Some(30),
+            message_template: None,
+        };
+        let response = analyze(request).block_response.unwrap();
+        assert_eq!(response.status_code, 403);
+        assert_eq!(response.retry_after_secs, None);
+    }
+
+    #[test]
+    fn test_repeated_checkbox_params_not_blocked() {
+        let result = analyze(query_request("/search?color=red&color=blue&color=green&size=m"));
+        assert!(!result.blocked);
+        assert!(result.matches.is_empty());
+    }
+
+    #[test]
+    fn test_split_sqli_across_duplicates() {
+        let result = analyze(query_request("/items?id=1%20union&id=%20select%20password%20from%20users"));
+        assert!(result.blocked);
+
+        let hpp = result.matches.iter().find(|m| m.category == "hpp").unwrap();
+        assert_eq!(hpp.rule_id, HPP_INJECTION_RULE_ID);
+        assert_eq!(hpp.severity, 5);
+        assert_eq!(hpp.location, "Query:id[0,1]");
+        assert_eq!(hpp.matched_value, "union select");
+    }
+
+    #[test]
+    fn test_repeated_sensitive_param_informational() {
+        let result = analyze(query_request("/login?redirect=%2Fhome&page=1&redirect=https%3A%2F%2Fevil.example"));
+        assert!(!result.blocked);
+
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].rule_id, HPP_SENSITIVE_PARAM_RULE_ID);
+        assert_eq!(result.matches[0].location, "Query:redirect[0,2]");
+        assert_eq!(result.matches[0].matched_value, "/home,https://evil.example");
+    }
+
+    #[test]
+    fn test_parse_query_decodes_components() {
+        let params = parse_query("/p?a=1+2&b=%3Cx%3E&c&d=%zz#frag");
+        assert_eq!(
+            params,
+            vec![
+                ("a".to_string(), "1 2".to_string()),
+                ("b".to_string(), "<x>".to_string()),
+                ("c".to_string(), String::new()),
+                ("d".to_string(), "%zz".to_string()),
+            ]
+        );
+    }
+
+    fn rule_stats(rule_id: u32) -> (u32, u32) {
+        let stats = collect_stats();
+        let entry = stats.rules.iter().find(|r| r.rule_id == rule_id).unwrap();
+        (entry.hits, entry.blocks)
+    }
+
+    #[test]
+    fn test_rule_count_matches_rule_set() {
+        assert_eq!(build_rules().len(), RULE_COUNT);
+        assert_eq!(collect_stats().rules.len(), STAT_SLOTS);
+    }
+
+    #[test]
+    fn test_rule_stats_count_and_reset() {
+        reset_stats();
+
+        analyze(query_request("/page?q=<script>alert(1)</script>"));
+        analyze(query_request("/page?q=<script>"));
+        analyze(query_request("/page?q=javascript:void(0)"));
+        analyze(query_request("/login?token=a&token=b"));
+        analyze(query_request("/clean"));
+
+        // Script tag: two hits, both blocking
+        assert_eq!(rule_stats(941100), (2, 2));
+        // javascript: is Error severity, never blocks on its own
+        assert_eq!(rule_stats(941120), (1, 0));
+        assert_eq!(rule_stats(HPP_SENSITIVE_PARAM_RULE_ID), (1, 0));
+        assert_eq!(rule_stats(942100), (0, 0));
+
+        reset_stats();
+        assert!(collect_stats().rules.iter().all(|r| r.hits == 0 && r.blocks == 0));
     }
 
-    // Determine if request should be blocked (any Critical match)
-    let blocked = matches.iter().any(|m| m.severity >= 5);
+    #[test]
+    fn test_rule_stats_saturate() {
+        reset_stats();
+        RULE_STATS.with(|stats| stats.borrow_mut()[0] = RuleCounters { hits: u32::MAX, blocks: u32::MAX });
 
-    WafResult {
-        blocked,
-        matches,
-        execution_time_us: 0, // Host will set this
+        let rules = build_rules();
+        analyze(query_request("/items?q=union select"));
+
+        assert_eq!(rules[0].id, 942100);
+        assert_eq!(rule_stats(942100), (u32::MAX, u32::MAX));
+        reset_stats();
     }
-}
 
-/// WASM Export: Analyze request
-///
-/// Host allocates memory via alloc(), writes JSON request data, calls this.
-/// Returns pointer to result (format: 4 bytes length + JSON data)
-#[no_mangle]
-pub extern "C" fn analyze_request(ptr: u32, len: u32) -> u32 {
-    // Read request JSON from Wasm memory
-    let request_bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) };
+    fn custom_rule_json(id: u32, pattern: &str, severity: u8) -> Vec<u8> {
+        serde_json::json!({
+            "id": id,
+            "description": "Custom: blocked token",
+            "patterns": [pattern],
+            "severity": severity,
+            "category": "custom",
+        })
+        .to_string()
+        .into_bytes()
+    }
 
-    let request: RequestData = match serde_json::from_slice(request_bytes) {
-        Ok(req) => req,
-        Err(_) => {
-            // Return error result
-            let error_result = WafResult {
-                blocked: false,
-                matches: Vec::new(),
-                execution_time_us: 0,
-            };
-            return write_result(&error_result);
-        }
-    };
+    #[test]
+    fn test_added_rule_fires() {
+        assert!(!analyze(query_request("/page?q=evil-token")).blocked);
 
-    // Analyze request
-    let result = analyze(request);
+        let update = add_rule_json(&custom_rule_json(990001, "EVIL-TOKEN", 5));
+        assert!(update.ok);
+        assert_eq!(update.revision, 1);
 
-    // Write result to Wasm memory
-    write_result(&result)
-}
+        let result = analyze(query_request("/page?q=evil-token"));
+        assert!(result.blocked);
+        assert_eq!(result.matches[0].rule_id, 990001);
+        assert_eq!(result.matches[0].category, "custom");
 
-/// Write result to Wasm memory (format: 4-byte length + JSON)
-fn write_result(result: &WafResult) -> u32 {
-    let json = serde_json::to_string(result).unwrap_or_else(|_| "{}".to_string());
-    let json_bytes = json.as_bytes();
-    let json_len = json_bytes.len() as u32;
+        // Ids must be unique and synthetic ids are reserved
+        let duplicate = add_rule_json(&custom_rule_json(990001, "other", 5));
+        assert_eq!(duplicate.error, Some("duplicate_rule_id"));
+        let duplicate = add_rule_json(&custom_rule_json(942100, "other", 5));
+        assert_eq!(duplicate.error, Some("duplicate_rule_id"));
+        let reserved = add_rule_json(&custom_rule_json(HPP_INJECTION_RULE_ID, "other", 5));
+        assert_eq!(reserved.error, Some("reserved_rule_id"));
+        assert_eq!(add_rule_json(&custom_rule_json(990002, "", 5)).error, Some("invalid_rule"));
+        assert_eq!(add_rule_json(&custom_rule_json(990002, "x", 6)).error, Some("invalid_rule"));
+        assert_eq!(add_rule_json(b"{not json").error, Some("invalid_json"));
+    }
 
-    // Allocate: 4 bytes for length + JSON data
-    let total_size = 4 + json_bytes.len();
-    let layout = Layout::from_size_align(total_size, 4).unwrap();
-    let result_ptr = unsafe { std_alloc(layout) };
+    #[test]
+    fn test_removed_builtin_rule_stops_firing() {
+        let script = "/page?q=<script>alert(1)</script>";
+        assert!(analyze(query_request(script)).matches.iter().any(|m| m.rule_id == 941100));
 
-    // Write length (first 4 bytes)
-    unsafe {
-        *(result_ptr as *mut u32) = json_len;
+        assert!(change_ruleset(|ruleset| ruleset.remove(941100)).ok);
+        let result = analyze(query_request(script));
+        assert!(!result.matches.iter().any(|m| m.rule_id == 941100));
+        assert!(!ruleset_info().rule_ids.contains(&941100));
+
+        let again = change_ruleset(|ruleset| ruleset.remove(941100));
+        assert_eq!(again.error, Some("unknown_rule_id"));
     }
 
-    // Write JSON data
-    unsafe {
-        let data_ptr = result_ptr.add(4);
-        std::ptr::copy_nonoverlapping(json_bytes.as_ptr(), data_ptr, json_bytes.len());
+    #[test]
+    fn test_ruleset_revision_bumps_per_change() {
+        let info = ruleset_info();
+        assert_eq!(info.revision, 0);
+        assert_eq!(info.rule_count, RULE_COUNT);
+
+        assert_eq!(add_rule_json(&custom_rule_json(990001, "evil-token", 5)).revision, 1);
+        // Downgrade the custom rule so it only logs
+        assert_eq!(update_rule_json(&custom_rule_json(990001, "evil-token", 3)).revision, 2);
+        assert!(!analyze(query_request("/page?q=evil-token")).blocked);
+        assert_eq!(change_ruleset(|ruleset| ruleset.remove(990001)).revision, 3);
+
+        // Failed changes leave the revision alone
+        let failed = update_rule_json(&custom_rule_json(990001, "evil-token", 5));
+        assert_eq!(failed.error, Some("unknown_rule_id"));
+        assert_eq!(failed.revision, 3);
+        assert_eq!(ruleset_info().revision, 3);
+        assert_eq!(ruleset_info().rule_count, RULE_COUNT);
     }
 
-    result_ptr as u32
-}
+    #[test]
+    fn test_updated_builtin_keeps_position_and_counters() {
+        reset_stats();
+        assert!(update_rule_json(&custom_rule_json(942100, "drop database", 5)).ok);
+        assert_eq!(ruleset_info().rule_ids[0], 942100);
 
-/// WASM Export: Allocate memory for host to write data
-#[no_mangle]
-pub extern "C" fn alloc(size: u32) -> u32 {
-    let layout = Layout::from_size_align(size as usize, 4).unwrap();
-    unsafe { std_alloc(layout) as u32 }
-}
+        analyze(query_request("/items?q=drop database"));
+        assert!(!analyze(query_request("/items?q=union select")).matches.iter().any(|m| m.rule_id == 942100));
+        assert_eq!(rule_stats(942100), (1, 1));
+        reset_stats();
+    }
 
-/// WASM Export: Deallocate memory (not currently used, but exported for completeness)
-#[no_mangle]
-pub extern "C" fn dealloc(ptr: u32, size: u32) {
-    let layout = Layout::from_size_align(size as usize, 4).unwrap();
-    unsafe { std_dealloc(ptr as *mut u8, layout) };
-}
+    #[test]
+    fn test_self_test_passes_with_default_ruleset() {
+        let report = run_self_test();
+        assert!(report.passed, "{:?}", report.failures);
+        assert!(report.failures.is_empty());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_self_test_reports_crippled_ruleset() {
+        reset_stats();
+        for rule_id in [941100, 941110, 941120, 941130] {
+            assert!(change_ruleset(|ruleset| ruleset.remove(rule_id)).ok);
+        }
+
+        let report = run_self_test();
+        assert!(!report.passed);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].category, "xss");
+        assert_eq!(report.failures[0].expected, "detected");
+        assert_eq!(report.failures[0].got, "clean");
+
+        // Canary traffic doesn't count toward rule statistics
+        assert!(collect_stats().rules.iter().all(|r| r.hits == 0));
+    }
+
+    fn body_request(content_type: &str, body: &str, body_raw: Option<Vec<u8>>) -> RequestData {
+        RequestData {
+            method: "POST".to_string(),
+            uri: "/comment".to_string(),
+            headers: vec![("content-type".to_string(), content_type.to_string())],
+            body: body.to_string(),
+            body_raw,
+            config: WafConfig::default(),
+        }
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect()
+    }
 
     #[test]
-    fn test_sql_injection_detection() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "SELECT * FROM users".to_string(),
-            headers: Vec::new(),
-            body: String::new(),
-        };
+    fn test_content_type_charset_parsing() {
+        let headers = |value: &str| vec![("Content-Type".to_string(), value.to_string())];
+        assert_eq!(content_type_charset(&headers("text/html; charset=UTF-7")), Some("utf-7".to_string()));
+        assert_eq!(content_type_charset(&headers("text/plain;Charset=\"ibm037\"")), Some("ibm037".to_string()));
+        assert_eq!(content_type_charset(&headers("application/json")), None);
+        assert_eq!(content_type_charset(&headers("text/plain; charset=")), None);
+        assert_eq!(content_type_charset(&[]), None);
+    }
+
+    #[test]
+    fn test_utf7_script_tag_blocked() {
+        let result = analyze(body_request(
+            "text/html; charset=utf-7",
+            "+ADw-script+AD4-alert(1)+ADw-/script+AD4-",
+            None,
+        ));
 
-        let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "sqli");
         assert!(result.blocked);
+        assert!(result.matches.iter().any(|m| m.rule_id == CHARSET_SMUGGLING_RULE_ID && m.severity == 4));
+        let xss = result.matches.iter().find(|m| m.category == "xss").unwrap();
+        assert_eq!(xss.location, "Body:charset=utf-7");
     }
 
     #[test]
-    fn test_xss_detection() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "<script>alert('XSS')</script>".to_string(),
-            headers: Vec::new(),
-            body: String::new(),
-        };
+    fn test_utf8_body_with_explicit_charset_unaffected() {
+        let result = analyze(body_request(
+            "application/json; charset=utf-8",
+            r#"{"comment":"great post, 1+1=2"}"#,
+            None,
+        ));
+        assert!(!result.blocked);
+        assert!(result.matches.is_empty());
+    }
 
-        let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "xss");
+    #[test]
+    fn test_utf16_body_transcoded_before_scanning() {
+        let raw = utf16le("<script>alert(1)</script>");
+        let lossy = String::from_utf8_lossy(&raw).into_owned();
+
+        let result = analyze(body_request("text/html; charset=utf-16le", &lossy, Some(raw)));
         assert!(result.blocked);
+        assert!(result.matches.iter().all(|m| m.rule_id != CHARSET_SMUGGLING_RULE_ID));
+
+        // A big-endian BOM contradicting the declared little-endian charset
+        let mut raw = vec![0xFE, 0xFF];
+        raw.extend("<p>".encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+        let result = analyze(body_request("text/html; charset=utf-16le", "", Some(raw)));
+        assert!(result.matches.iter().any(|m| m.rule_id == CHARSET_SMUGGLING_RULE_ID));
     }
 
     #[test]
-    fn test_path_traversal_detection() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "../../../etc/passwd".to_string(),
-            headers: Vec::new(),
-            body: String::new(),
-        };
+    fn test_latin1_body_transcoded_from_raw_bytes() {
+        let mut raw = b"caf\xe9 ".to_vec();
+        raw.extend_from_slice(b"union select");
+        let lossy = String::from_utf8_lossy(&raw).into_owned();
 
-        let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "path-traversal");
-        assert!(result.blocked);
+        let result = analyze(body_request("text/plain; charset=iso-8859-1", &lossy, Some(raw)));
+        assert!(result
+            .matches
+            .iter()
+            .any(|m| m.rule_id == 942100 && m.location == "Body:charset=iso-8859-1"));
     }
 
     #[test]
-    fn test_rce_detection() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "; ls -la".to_string(),
-            headers: Vec::new(),
-            body: String::new(),
-        };
+    fn test_failed_transcode_is_flagged() {
+        // Odd-length UTF-16
+        let result = analyze(body_request("text/plain; charset=utf-16", "", Some(vec![0, b'a', 0])));
+        assert!(result.matches.iter().any(|m| m.rule_id == CHARSET_TRANSCODE_FAILED_RULE_ID));
+
+        // Over the transcode bound
+        let raw = utf16le(&"a".repeat(MAX_TRANSCODE_BYTES));
+        let result = analyze(body_request("text/plain; charset=utf-16le", "", Some(raw)));
+        let failed = result
+            .matches
+            .iter()
+            .find(|m| m.rule_id == CHARSET_TRANSCODE_FAILED_RULE_ID)
+            .unwrap();
+        assert_eq!(failed.severity, MIN_SEVERITY);
+        assert!(!result.blocked);
+    }
+
+    fn base64(text: &str) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in text.as_bytes().chunks(3) {
+            let bits = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        while !out.len().is_multiple_of(4) {
+            out.push('=');
+        }
+        out
+    }
+
+    /// Base64 twice around a `<` percent-encoded 200 times: each URL decoding
+    /// pass peels one `25`, so decoding it all produces over 50x the body
+    fn expanding_body() -> String {
+        let nested = format!("union select %{}3Cscript>", "25".repeat(200));
+        format!("data={}", base64(&base64(&nested)))
+    }
+
+    #[test]
+    fn test_nested_base64_expansion_flagged() {
+        let mut request = body_request("application/x-www-form-urlencoded", &expanding_body(), None);
+        request.config.body_decode.max_decoded_bytes = usize::MAX;
+        request.config.body_decode.max_expansion_ratio = 1000;
+        let unbounded = analyze(request);
+        assert!(unbounded.body_expansion_ratio.unwrap() > 50.0);
+        assert!(unbounded.matches.iter().all(|m| m.rule_id != BODY_EXPANSION_RULE_ID));
+
+        let result = analyze(body_request("application/x-www-form-urlencoded", &expanding_body(), None));
+        let ratio = result.body_expansion_ratio.unwrap();
+        assert!((10.0..11.0).contains(&ratio));
+        let evasion = result.matches.iter().find(|m| m.rule_id == BODY_EXPANSION_RULE_ID).unwrap();
+        assert_eq!(evasion.category, "evasion");
+        assert_eq!(evasion.severity, 4);
+
+        // The layers decoded before the budget ran out were still scanned,
+        // but decoding never reached the innermost `<script>`
+        assert!(result.matches.iter().any(|m| m.rule_id == 942100 && m.location == "Body:decoded"));
+        assert!(result.matches.iter().all(|m| m.category != "xss"));
+    }
+
+    #[test]
+    fn test_url_encoded_body_within_budget() {
+        let body = "comment=Nice%20post%2C%20thanks%21&name=Alice%20B.&tags=rust%2Cwasm";
+        let result = analyze(body_request("application/x-www-form-urlencoded", body, None));
+
+        let ratio = result.body_expansion_ratio.unwrap();
+        assert!(ratio > 0.0 && ratio < 2.0);
+        assert!(result.matches.is_empty());
+        assert!(!result.blocked);
+    }
 
+    #[test]
+    fn test_absolute_decode_cap() {
+        let mut request = body_request("text/plain", &format!("q={}", base64("<iframe src=x>")), None);
+        request.config.body_decode.max_decoded_bytes = 4;
         let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "rce");
-        assert!(result.blocked);
+
+        assert!(result.matches.iter().any(|m| m.rule_id == BODY_EXPANSION_RULE_ID));
+        assert!(result.matches.iter().all(|m| m.category != "xss"));
     }
 
     #[test]
-    fn test_clean_request() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "/api/users".to_string(),
-            headers: Vec::new(),
-            body: String::new(),
-        };
+    fn test_body_decoders() {
+        assert_eq!(decode_base64_tokens(&format!("x={}&y=1", base64("<script>alert(1)"))), "x=<script>alert(1)&y=1");
+        // Short tokens and base64 of binary data are left alone
+        assert_eq!(decode_base64_tokens("id=abcd1234"), "id=abcd1234");
+        assert_eq!(decode_base64_tokens("AAAAAAAAAAAAAAAA"), "AAAAAAAAAAAAAAAA");
+        assert_eq!(decode_html_entities("&lt;a&#62;&#x3C;&amp;lt;&bogus; & x"), "<a><&lt;&bogus; & x");
+    }
+
+    fn method_request(method: &str, body: &str, content_length: Option<&str>) -> RequestData {
+        let mut request = body_request("application/x-www-form-urlencoded", body, None);
+        request.method = method.to_string();
+        if let Some(value) = content_length {
+            request.headers.push(("Content-Length".to_string(), value.to_string()));
+        }
+        request
+    }
+
+    #[test]
+    fn test_get_with_sqli_body_flagged_twice() {
+        let body = "id=1 union select password from users";
+        let result = analyze(method_request("GET", body, Some(&body.len().to_string())));
+
+        let anomaly = result.matches.iter().find(|m| m.rule_id == BODYLESS_METHOD_BODY_RULE_ID).unwrap();
+        assert_eq!(anomaly.category, "protocol");
+        assert_eq!(anomaly.severity, 4);
+        assert!(result.matches.iter().any(|m| m.category == "sqli" && m.location == "Body"));
+        assert!(result.matches.iter().all(|m| m.rule_id != CONTENT_LENGTH_MISMATCH_RULE_ID));
+        assert!(result.blocked);
+
+        for method in ["head", "OPTIONS"] {
+            let result = analyze(method_request(method, "x=1", None));
+            assert!(result.matches.iter().any(|m| m.rule_id == BODYLESS_METHOD_BODY_RULE_ID), "{}", method);
+        }
+    }
 
+    #[test]
+    fn test_bodyless_method_skips_empty_body() {
+        let mut request = method_request("GET", "", Some("0"));
+        request.body_raw = Some(Vec::new());
         let result = analyze(request);
+
+        assert!(result.matches.is_empty());
+        assert_eq!(result.body_expansion_ratio, None);
+    }
+
+    #[test]
+    fn test_post_body_unaffected() {
+        let body = "comment=Nice%20post&name=Alice";
+        let result = analyze(method_request("POST", body, Some(&body.len().to_string())));
+
         assert!(result.matches.is_empty());
+        assert!(result.body_expansion_ratio.is_some());
         assert!(!result.blocked);
     }
 
     #[test]
-    fn test_header_analysis() {
-        let request = RequestData {
-            method: "GET".to_string(),
-            uri: "/".to_string(),
-            headers: vec![("User-Agent".to_string(), "nikto scanner".to_string())],
-            body: String::new(),
-        };
+    fn test_content_length_mismatch_flagged() {
+        let body = "a=".to_string() + &"b".repeat(1000);
 
-        let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "scanner");
-        assert!(result.matches[0].location.starts_with("Header:"));
+        // Within the slack: a few bytes either way
+        let result = analyze(method_request("POST", &body, Some("1050")));
+        assert!(result.matches.iter().all(|m| m.rule_id != CONTENT_LENGTH_MISMATCH_RULE_ID));
+
+        for declared in ["4", "20000", "12abc"] {
+            let result = analyze(method_request("POST", &body, Some(declared)));
+            let mismatch = result
+                .matches
+                .iter()
+                .find(|m| m.rule_id == CONTENT_LENGTH_MISMATCH_RULE_ID)
+                .unwrap_or_else(|| panic!("declared {}", declared));
+            assert_eq!(mismatch.category, "evasion");
+            assert_eq!(mismatch.location, "Header:Content-Length");
+            assert_eq!(mismatch.matched_value, format!("declared {}, received 1002 bytes", declared));
+        }
+    }
+
+    fn zone_spec(zone_id: &str, builtin_rule_ids: &[u32], rules: &[serde_json::Value], max_memory_bytes: Option<usize>) -> Vec<u8> {
+        let mut spec = serde_json::json!({
+            "zone_id": zone_id,
+            "builtin_rule_ids": builtin_rule_ids,
+            "rules": rules,
+        });
+        if let Some(cap) = max_memory_bytes {
+            spec["max_memory_bytes"] = cap.into();
+        }
+        spec.to_string().into_bytes()
+    }
+
+    fn zone_rule(id: u32, patterns: &[String]) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "description": "Custom: zone token",
+            "patterns": patterns,
+            "severity": 5,
+            "category": "custom",
+        })
+    }
+
+    fn zone_request(uri: &str, zone_id: &str) -> RequestData {
+        let mut request = query_request(uri);
+        request.config.zone_id = Some(zone_id.to_string());
+        request
+    }
+
+    fn zone_bytes(zone_id: &str) -> Option<usize> {
+        memory_report()
+            .zones
+            .into_iter()
+            .find(|zone| zone.zone_id == zone_id)
+            .map(|zone| zone.total_bytes)
+    }
+
+    /// `count` rules of MAX_RULE_PATTERNS max-length patterns each
+    fn bulky_rules(count: u32) -> Vec<serde_json::Value> {
+        (0..count)
+            .map(|i| {
+                let patterns: Vec<String> = (0..MAX_RULE_PATTERNS)
+                    .map(|j| format!("{:0width$}", i as usize * MAX_RULE_PATTERNS + j, width = MAX_PATTERN_LENGTH))
+                    .collect();
+                zone_rule(991000 + i, &patterns)
+            })
+            .collect()
     }
 
     #[test]
-    fn test_body_analysis() {
-        let request = RequestData {
-            method: "POST".to_string(),
-            uri: "/api/data".to_string(),
-            headers: Vec::new(),
-            body: "INSERT INTO users VALUES ('admin', 'pass')".to_string(),
-        };
+    fn test_zone_rule_only_fires_for_its_zone() {
+        let token = ["ACME-Token".to_string()];
+        assert!(load_rules_json(&zone_spec("zone-a", &[], &[zone_rule(990100, &token)], None)).ok);
+        assert!(load_rules_json(&zone_spec("zone-b", &[942100], &[], None)).ok);
 
-        let result = analyze(request);
-        assert!(!result.matches.is_empty());
-        assert_eq!(result.matches[0].category, "sqli");
-        assert_eq!(result.matches[0].location, "Body");
+        // Compiled case-insensitive patterns match any casing
+        let result = analyze(zone_request("/page?q=acme-token", "zone-a"));
         assert!(result.blocked);
+        assert_eq!(result.matches[0].rule_id, 990100);
+        assert_eq!(result.matches[0].matched_value, "acme-token");
+
+        assert!(!analyze(zone_request("/page?q=acme-token", "zone-b")).blocked);
+        assert!(!analyze(query_request("/page?q=acme-token")).blocked);
+
+        // Each zone runs only its subset
+        let sqli = "/items?q=1 union select password";
+        assert!(analyze(zone_request(sqli, "zone-b")).blocked);
+        assert!(analyze(zone_request(sqli, "zone-a")).matches.is_empty());
+
+        // Unknown zones use the instance-wide ruleset
+        assert!(analyze(zone_request(sqli, "zone-c")).blocked);
+    }
+
+    #[test]
+    fn test_oversized_zone_ruleset_fails_cleanly() {
+        let token = ["acme-token".to_string()];
+        assert!(load_rules_json(&zone_spec("zone-a", &[942100], &[zone_rule(990100, &token)], None)).ok);
+        let before = zone_bytes("zone-a").unwrap();
+
+        let oversized = load_rules_json(&zone_spec("zone-b", &[], &bulky_rules(2), Some(16 * 1024)));
+        assert!(!oversized.ok);
+        assert_eq!(oversized.error, Some("zone_memory_exceeded"));
+        assert_eq!(oversized.memory_bytes, 0);
+        assert_eq!(zone_bytes("zone-b"), None);
+
+        // A failed reload keeps the zone's previous ruleset
+        let reload = load_rules_json(&zone_spec("zone-a", &[], &bulky_rules(2), Some(16 * 1024)));
+        assert_eq!(reload.error, Some("zone_memory_exceeded"));
+        assert_eq!(reload.memory_bytes, before);
+        assert_eq!(zone_bytes("zone-a"), Some(before));
+        assert!(analyze(zone_request("/page?q=acme-token", "zone-a")).blocked);
+
+        let cap = Some(MAX_ZONE_MEMORY_BYTES + 1);
+        assert_eq!(load_rules_json(&zone_spec("zone-b", &[942100], &[], cap)).error, Some("invalid_memory_cap"));
+        assert_eq!(load_rules_json(&zone_spec("zone-b", &[942100, 942100], &[], None)).error, Some("duplicate_rule_id"));
+        assert_eq!(load_rules_json(&zone_spec("zone-b", &[990100], &[], None)).error, Some("unknown_rule_id"));
+        assert_eq!(load_rules_json(&zone_spec("", &[942100], &[], None)).error, Some("invalid_zone"));
+        assert_eq!(load_rules_json(b"{not json").error, Some("invalid_json"));
+        assert_eq!(memory_report().zones.len(), 1);
+    }
+
+    #[test]
+    fn test_zone_memory_budget_shared_across_zones() {
+        let cap = Some(MAX_ZONE_MEMORY_BYTES);
+        let first = load_rules_json(&zone_spec("zone-a", &[], &bulky_rules(100), cap));
+        assert!(first.ok, "{:?}", first.error);
+        assert!(first.memory_bytes > ZONE_MEMORY_BUDGET_BYTES / 2);
+
+        let second = load_rules_json(&zone_spec("zone-b", &[], &bulky_rules(100), cap));
+        assert_eq!(second.error, Some("memory_budget_exceeded"));
+        assert_eq!(zone_bytes("zone-b"), None);
+
+        // Replacing a zone doesn't count its old ruleset against the budget
+        assert!(load_rules_json(&zone_spec("zone-a", &[], &bulky_rules(100), cap)).ok);
+    }
+
+    #[test]
+    fn test_memory_report_reflects_unloads() {
+        let token = ["acme-token".to_string()];
+        assert!(load_rules_json(&zone_spec("zone-a", &[], &[zone_rule(990100, &token)], None)).ok);
+        assert!(load_rules_json(&zone_spec("zone-b", &[942100, 941100], &[], None)).ok);
+
+        let report = memory_report();
+        assert_eq!(report.zones.len(), 2);
+        assert_eq!(report.zones[0].zone_id, "zone-a");
+        assert_eq!(report.zones[0].rule_count, 1);
+        assert!(report.zones[0].rule_bytes > 0);
+        assert!(report.zones[0].matcher_bytes > 0);
+        assert_eq!(report.zones[0].max_memory_bytes, DEFAULT_ZONE_MEMORY_BYTES);
+        let zone_b = report.zones[1].total_bytes;
+        assert_eq!(report.total_bytes, report.zones[0].total_bytes + zone_b);
+
+        let unloaded = unload_zone("zone-a");
+        assert!(unloaded.ok);
+        assert_eq!(unloaded.memory_bytes, 0);
+        let report = memory_report();
+        assert_eq!(report.zones.len(), 1);
+        assert_eq!(report.total_bytes, zone_b);
+
+        assert_eq!(unload_zone("zone-a").error, Some("unknown_zone"));
+        // The zone's requests are back on the instance-wide ruleset
+        assert!(!analyze(zone_request("/page?q=acme-token", "zone-a")).blocked);
+
+        assert!(unload_zone("zone-b").ok);
+        assert_eq!(memory_report().total_bytes, 0);
     }
 }
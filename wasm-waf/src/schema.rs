@@ -0,0 +1,256 @@
+//! WafResult schema versioning
+//!
+//! The host runtime, the log pipeline and billing all parse `analyze_request`
+//! output, so WafResult only evolves additively:
+//!
+//! - Fields are never removed, renamed or retyped
+//! - A new field gets a serde default, so results from older modules still
+//!   parse, and consumers must ignore fields they don't know
+//! - Each addition bumps RESULT_SCHEMA_VERSION, updates
+//!   `result_schema_document()` and adds fixtures under `tests/schema/v<N>/`
+//!
+//! Version 1 is every result written before `schema_version` existed; those
+//! parse with `schema_version: 1`. Results are written as canonical JSON
+//! (object keys sorted, no insignificant whitespace) so consumers that hash
+//! or diff the raw bytes see a stable layout.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Schema version of WafResult written by this module
+pub const RESULT_SCHEMA_VERSION: u32 = 2;
+
+/// Version assumed for results that predate the `schema_version` field
+pub(crate) fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Serialize with object keys sorted and no whitespace
+///
+/// Going through `Value` sorts keys (serde_json's map is ordered by key
+/// unless `preserve_order` is enabled, which this crate doesn't do).
+pub(crate) fn canonical_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_value(value).map(|value| value.to_string())
+}
+
+/// JSON Schema (draft 2020-12) of the current WafResult version
+pub(crate) fn result_schema_document() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:aegis:waf-result:v{}", RESULT_SCHEMA_VERSION),
+        "title": "WafResult",
+        "type": "object",
+        "required": ["schema_version", "blocked", "matches", "execution_time_us"],
+        // Later versions only add fields
+        "additionalProperties": true,
+        "properties": {
+            "schema_version": { "type": "integer", "minimum": 1 },
+            "blocked": { "type": "boolean" },
+            "matches": { "type": "array", "items": { "$ref": "#/$defs/WafMatch" } },
+            "execution_time_us": { "type": "integer", "minimum": 0 },
+            "needs_hmac_check": { "type": "boolean", "default": false },
+            "hmac_key_id": { "type": "string" },
+            "block_response": { "$ref": "#/$defs/BlockResponse" },
+            "body_expansion_ratio": { "type": "number", "minimum": 0 }
+        },
+        "$defs": {
+            "WafMatch": {
+                "type": "object",
+                "required": ["rule_id", "description", "severity", "category", "matched_value", "location"],
+                "additionalProperties": true,
+                "properties": {
+                    "rule_id": { "type": "integer", "minimum": 0 },
+                    "description": { "type": "string" },
+                    "severity": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "category": { "type": "string" },
+                    "matched_value": { "type": "string" },
+                    "location": { "type": "string" }
+                }
+            },
+            "BlockResponse": {
+                "type": "object",
+                "required": ["status_code", "block_reason_code", "message"],
+                "additionalProperties": true,
+                "properties": {
+                    "status_code": { "type": "integer", "minimum": 100, "maximum": 599 },
+                    "block_reason_code": { "type": "string" },
+                    "message": { "type": "string" },
+                    "retry_after_secs": { "type": "integer", "minimum": 0 }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::*;
+
+    /// Every `tests/schema/v<N>/*.json` fixture, sorted by path
+    fn fixtures() -> Vec<(PathBuf, Value)> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/schema");
+        let mut paths: Vec<PathBuf> = fs::read_dir(&root)
+            .expect("tests/schema")
+            .flat_map(|dir| fs::read_dir(dir.unwrap().path()).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|path| {
+                let value = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+                    .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+                (path, value)
+            })
+            .collect()
+    }
+
+    /// Fixture schema version, from its `v<N>` directory
+    fn fixture_version(path: &Path) -> u32 {
+        let dir = path.parent().unwrap().file_name().unwrap().to_str().unwrap();
+        dir.strip_prefix('v').and_then(|n| n.parse().ok()).expect("v<N> directory")
+    }
+
+    /// Where `actual` drops or changes something `expected` has (`actual` may add fields)
+    fn missing_from(expected: &Value, actual: &Value, path: &str) -> Vec<String> {
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => expected
+                .iter()
+                .flat_map(|(key, value)| {
+                    let path = format!("{}.{}", path, key);
+                    match actual.get(key) {
+                        Some(actual) => missing_from(value, actual, &path),
+                        None => vec![format!("{}: removed", path)],
+                    }
+                })
+                .collect(),
+            (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => expected
+                .iter()
+                .zip(actual)
+                .enumerate()
+                .flat_map(|(i, (expected, actual))| missing_from(expected, actual, &format!("{}[{}]", path, i)))
+                .collect(),
+            (expected, actual) if expected == actual => Vec::new(),
+            (expected, actual) => vec![format!("{}: {} -> {}", path, expected, actual)],
+        }
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        let fixtures = fixtures();
+        assert!(fixtures.iter().any(|(path, _)| fixture_version(path) < RESULT_SCHEMA_VERSION));
+
+        for (path, fixture) in &fixtures {
+            // Fails when a field's type changes or a new field lacks a default
+            let result: WafResult = serde_json::from_value(fixture.clone())
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            assert_eq!(result.schema_version, fixture_version(path), "{}", path.display());
+
+            // Fails when a field is removed: serde drops unknown input fields
+            let written: Value = serde_json::from_str(&canonical_json(&result).unwrap()).unwrap();
+            let lost = missing_from(fixture, &written, "");
+            assert!(lost.is_empty(), "{}:\n{}", path.display(), lost.join("\n"));
+        }
+    }
+
+    #[test]
+    fn test_current_fixtures_are_canonical() {
+        let current: Vec<(PathBuf, Value)> = fixtures()
+            .into_iter()
+            .filter(|(path, _)| fixture_version(path) == RESULT_SCHEMA_VERSION)
+            .collect();
+        assert!(!current.is_empty(), "add tests/schema/v{}/ fixtures", RESULT_SCHEMA_VERSION);
+
+        // Current-version fixtures are stored exactly as the module writes them
+        for (path, fixture) in current {
+            let result: WafResult = serde_json::from_value(fixture).unwrap();
+            let stored = fs::read_to_string(&path).unwrap();
+            assert_eq!(canonical_json(&result).unwrap(), stored.trim_end(), "{}", path.display());
+        }
+    }
+
+    fn full_result() -> WafResult {
+        WafResult {
+            schema_version: RESULT_SCHEMA_VERSION,
+            blocked: true,
+            matches: vec![WafMatch {
+                rule_id: 942100,
+                description: "SQL Injection Attack: Common DB names".to_string(),
+                severity: 5,
+                category: "sqli".to_string(),
+                matched_value: "union select".to_string(),
+                location: "URI".to_string(),
+            }],
+            execution_time_us: 412,
+            needs_hmac_check: true,
+            hmac_key_id: Some("clr-2024-01".to_string()),
+            block_response: Some(BlockResponse {
+                status_code: 429,
+                block_reason_code: "sqli:942100".to_string(),
+                message: "Request blocked by AEGIS WAF (reference: sqli:942100)".to_string(),
+                retry_after_secs: Some(30),
+            }),
+            body_expansion_ratio: Some(1.5),
+        }
+    }
+
+    fn keys(value: &Value) -> Vec<String> {
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+
+    #[test]
+    fn test_schema_covers_every_field() {
+        let schema = result_schema_document();
+        let result = serde_json::to_value(full_result()).unwrap();
+
+        assert_eq!(keys(&result), keys(&schema["properties"]));
+        assert_eq!(keys(&result["matches"][0]), keys(&schema["$defs"]["WafMatch"]["properties"]));
+        assert_eq!(keys(&result["block_response"]), keys(&schema["$defs"]["BlockResponse"]["properties"]));
+        assert_eq!(schema["$id"], format!("urn:aegis:waf-result:v{}", RESULT_SCHEMA_VERSION));
+
+        // Everything the schema requires is always written
+        let minimal = serde_json::to_value(analyze(RequestData {
+            method: "GET".to_string(),
+            uri: "/".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        }))
+        .unwrap();
+        for required in schema["required"].as_array().unwrap() {
+            assert!(minimal.get(required.as_str().unwrap()).is_some(), "{}", required);
+        }
+    }
+
+    #[test]
+    fn test_results_carry_current_version() {
+        let result = analyze(RequestData {
+            method: "GET".to_string(),
+            uri: "/search?q=shoes".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+            body_raw: None,
+            config: WafConfig::default(),
+        });
+        assert_eq!(result.schema_version, RESULT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let json = canonical_json(&full_result()).unwrap();
+        assert!(json.starts_with(r#"{"block_response":{"block_reason_code":"sqli:942100","message":"#));
+        assert!(json.contains(r#""matches":[{"category":"sqli","description":"#));
+        assert!(json.ends_with(r#""needs_hmac_check":true,"schema_version":2}"#));
+        assert!(!json.contains("\": ") && !json.contains('\n'));
+
+        // Same result, same bytes
+        assert_eq!(json, canonical_json(&full_result()).unwrap());
+    }
+}